@@ -360,6 +360,7 @@ pub fn update_loop(
             sample_count: RafxSampleCount::SampleCount1,
             depth_stencil_format: None,
             primitive_topology: RafxPrimitiveTopology::TriangleList,
+            subpass: None,
         })?;
 
         log::trace!("Starting event loop");
@@ -97,6 +97,11 @@ struct PerViewData
     SpotLight_1 spot_lights[16];
     ShadowMap2DData shadow_map_2d_data[32];
     ShadowMapCubeData shadow_map_cube_data[16];
+    float4x4 environment_from_view;
+    float4 irradiance_sh[9];
+    float environment_intensity;
+    uint specular_mip_count;
+    uint has_environment_lighting;
 };
 
 struct MaterialData
@@ -125,6 +130,8 @@ struct spvDescriptorSetBuffer0
     constant PerViewData* per_view_data [[id(0)]];
     array<depth2d<float>, 32> shadow_map_images [[id(3)]];
     array<depthcube<float>, 16> shadow_map_images_cube [[id(35)]];
+    texturecube<float> environment_specular [[id(51)]];
+    texture2d<float> environment_brdf_lut [[id(52)]];
 };
 
 struct spvDescriptorSetBuffer1
@@ -170,8 +177,8 @@ float do_calculate_percent_lit_cube(thread const float3& light_position_ws, thre
     float param_1 = near_plane;
     float param_2 = far_plane;
     float depth_of_surface = calculate_cubemap_equivalent_depth(param, param_1, param_2);
-    float4 _270 = float4(light_to_surface_ws, depth_of_surface + bias0);
-    float shadow = shadow_map_images_cube[index].sample_compare(smp_depth, _270.xyz, _270.w);
+    float4 _285 = float4(light_to_surface_ws, depth_of_surface + bias0);
+    float shadow = shadow_map_images_cube[index].sample_compare(smp_depth, _285.xyz, _285.w);
     return shadow;
 }
 
@@ -294,8 +301,8 @@ float do_calculate_percent_lit(thread const float3& normal_vs, thread const int&
     {
         for (int y = -1; y <= 1; y++)
         {
-            float3 _412 = float3(sample_location_uv + (float2(float(x), float(y)) * texelSize), depth_of_surface + bias0);
-            shadow += shadow_map_images[index].sample_compare(smp_depth, _412.xy, _412.z);
+            float3 _427 = float3(sample_location_uv + (float2(float(x), float(y)) * texelSize), depth_of_surface + bias0);
+            shadow += shadow_map_images[index].sample_compare(smp_depth, _427.xy, _427.z);
         }
     }
     shadow /= 9.0;
@@ -365,7 +372,32 @@ float3 directional_light_pbr(thread const DirectionalLight& light, thread const
 }
 
 static inline __attribute__((always_inline))
-float4 pbr_path(thread const float3& surface_to_eye_vs, thread const float4& base_color, thread const float4& emissive_color, thread const float& metalness, thread const float& roughness, thread const float3& normal_vs, constant PerViewData& per_view_data, thread float4& in_position_ws, thread float3& in_position_vs, thread float3& in_normal_vs, thread const array<depthcube<float>, 16> shadow_map_images_cube, thread sampler smp_depth, thread float3x3& in_model_view, thread const array<depth2d<float>, 32> shadow_map_images)
+float3 environment_irradiance(thread const float3& n, constant PerViewData& per_view_data)
+{
+    float3 irradiance = (((((((per_view_data.irradiance_sh[0].xyz + (per_view_data.irradiance_sh[1].xyz * n.y)) + (per_view_data.irradiance_sh[2].xyz * n.z)) + (per_view_data.irradiance_sh[3].xyz * n.x)) + (per_view_data.irradiance_sh[4].xyz * (n.x * n.y))) + (per_view_data.irradiance_sh[5].xyz * (n.y * n.z))) + (per_view_data.irradiance_sh[6].xyz * (((3.0 * n.z) * n.z) - 1.0))) + (per_view_data.irradiance_sh[7].xyz * (n.x * n.z))) + (per_view_data.irradiance_sh[8].xyz * ((n.x * n.x) - (n.y * n.y)));
+    return fast::max(irradiance, float3(0.0));
+}
+
+static inline __attribute__((always_inline))
+float3 environment_light_pbr(thread const float3& surface_to_eye_vs, thread const float3& normal_vs, thread const float3& fresnel_base, thread const float3& base_color, thread const float& roughness, thread const float& metalness, constant PerViewData& per_view_data, thread texturecube<float> environment_specular, thread sampler smp_clamp, thread texture2d<float> environment_brdf_lut)
+{
+    float3x3 environment_from_view = float3x3(per_view_data.environment_from_view[0].xyz, per_view_data.environment_from_view[1].xyz, per_view_data.environment_from_view[2].xyz);
+    float3 normal_env = normalize(environment_from_view * normal_vs);
+    float3 reflection_env = normalize(environment_from_view * reflect(-surface_to_eye_vs, normal_vs));
+    float n_dot_v = fast::max(dot(normal_vs, surface_to_eye_vs), 0.0);
+    float3 fresnel = fresnel_base + ((fast::max(float3(1.0 - roughness), fresnel_base) - fresnel_base) * pow(1.0 - n_dot_v, 5.0));
+    float3 diffuse_factor = (float3(1.0) - fresnel) * (1.0 - metalness);
+    float3 param = normal_env;
+    float3 diffuse = (diffuse_factor * base_color) * environment_irradiance(param, per_view_data);
+    float specular_lod = roughness * float(max(per_view_data.specular_mip_count, 1u) - 1u);
+    float3 prefiltered = environment_specular.sample(smp_clamp, reflection_env, level(specular_lod)).xyz;
+    float2 brdf = environment_brdf_lut.sample(smp_clamp, float2(n_dot_v, roughness)).xy;
+    float3 specular = prefiltered * ((fresnel_base * brdf.x) + float3(brdf.y));
+    return (diffuse + specular) * per_view_data.environment_intensity;
+}
+
+static inline __attribute__((always_inline))
+float4 pbr_path(thread const float3& surface_to_eye_vs, thread const float4& base_color, thread const float4& emissive_color, thread const float& metalness, thread const float& roughness, thread const float3& normal_vs, constant PerViewData& per_view_data, thread float4& in_position_ws, thread float3& in_position_vs, thread float3& in_normal_vs, thread const array<depthcube<float>, 16> shadow_map_images_cube, thread sampler smp_depth, thread float3x3& in_model_view, thread const array<depth2d<float>, 32> shadow_map_images, thread texturecube<float> environment_specular, thread sampler smp_clamp, thread texture2d<float> environment_brdf_lut)
 {
     float3 fresnel_base = float3(0.039999999105930328369140625);
     fresnel_base = mix(fresnel_base, base_color.xyz, float3(metalness));
@@ -440,13 +472,27 @@ float4 pbr_path(thread const float3& surface_to_eye_vs, thread const float4& bas
         float param_34 = metalness;
         total_light += (directional_light_pbr(param_27, param_28, param_29, param_30, param_31, param_32, param_33, param_34) * percent_lit_2);
     }
-    float3 ambient = per_view_data.ambient_light.xyz * base_color.xyz;
+    float3 ambient;
+    if (per_view_data.has_environment_lighting != 0u)
+    {
+        float3 param_35 = surface_to_eye_vs;
+        float3 param_36 = normal_vs;
+        float3 param_37 = fresnel_base;
+        float3 param_38 = base_color.xyz;
+        float param_39 = roughness;
+        float param_40 = metalness;
+        ambient = environment_light_pbr(param_35, param_36, param_37, param_38, param_39, param_40, per_view_data, environment_specular, smp_clamp, environment_brdf_lut);
+    }
+    else
+    {
+        ambient = per_view_data.ambient_light.xyz * base_color.xyz;
+    }
     float3 color = (ambient + total_light) + emissive_color.xyz;
     return float4(color, base_color.w);
 }
 
 static inline __attribute__((always_inline))
-float4 pbr_main(constant PerViewData& per_view_data, thread float4& in_position_ws, thread float3& in_position_vs, thread float3& in_normal_vs, thread const array<depthcube<float>, 16> shadow_map_images_cube, thread sampler smp_depth, thread float3x3& in_model_view, thread const array<depth2d<float>, 32> shadow_map_images, constant MaterialDataUbo& per_material_data)
+float4 pbr_main(constant PerViewData& per_view_data, thread float4& in_position_ws, thread float3& in_position_vs, thread float3& in_normal_vs, thread const array<depthcube<float>, 16> shadow_map_images_cube, thread sampler smp_depth, thread float3x3& in_model_view, thread const array<depth2d<float>, 32> shadow_map_images, thread texturecube<float> environment_specular, thread sampler smp_clamp, thread texture2d<float> environment_brdf_lut, constant MaterialDataUbo& per_material_data)
 {
     float4 base_color = per_material_data.data.base_color_factor;
     float4 emissive_color = float4(per_material_data.data.emissive_factor[0], per_material_data.data.emissive_factor[1], per_material_data.data.emissive_factor[2], 1.0);
@@ -462,20 +508,21 @@ float4 pbr_main(constant PerViewData& per_view_data, thread float4& in_position_
     float param_3 = metalness;
     float param_4 = roughness;
     float3 param_5 = normal_vs;
-    float4 out_color = pbr_path(param, param_1, param_2, param_3, param_4, param_5, per_view_data, in_position_ws, in_position_vs, in_normal_vs, shadow_map_images_cube, smp_depth, in_model_view, shadow_map_images);
+    float4 out_color = pbr_path(param, param_1, param_2, param_3, param_4, param_5, per_view_data, in_position_ws, in_position_vs, in_normal_vs, shadow_map_images_cube, smp_depth, in_model_view, shadow_map_images, environment_specular, smp_clamp, environment_brdf_lut);
     return out_color;
 }
 
 fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]], constant spvDescriptorSetBuffer1& spvDescriptorSet1 [[buffer(1)]])
 {
     constexpr sampler smp_depth(filter::linear, mip_filter::linear, compare_func::greater, max_anisotropy(16));
+    constexpr sampler smp_clamp(filter::linear, mip_filter::linear, compare_func::never, max_anisotropy(1));
     constexpr sampler smp(filter::linear, mip_filter::linear, address::repeat, compare_func::never, max_anisotropy(16));
     main0_out out = {};
     float3x3 in_model_view = {};
     in_model_view[0] = in.in_model_view_0;
     in_model_view[1] = in.in_model_view_1;
     in_model_view[2] = in.in_model_view_2;
-    out.out_color = pbr_main((*spvDescriptorSet0.per_view_data), in.in_position_ws, in.in_position_vs, in.in_normal_vs, spvDescriptorSet0.shadow_map_images_cube, smp_depth, in_model_view, spvDescriptorSet0.shadow_map_images, (*spvDescriptorSet1.per_material_data));
+    out.out_color = pbr_main((*spvDescriptorSet0.per_view_data), in.in_position_ws, in.in_position_vs, in.in_normal_vs, spvDescriptorSet0.shadow_map_images_cube, smp_depth, in_model_view, spvDescriptorSet0.shadow_map_images, spvDescriptorSet0.environment_specular, smp_clamp, spvDescriptorSet0.environment_brdf_lut, (*spvDescriptorSet1.per_material_data));
     return out;
 }
 
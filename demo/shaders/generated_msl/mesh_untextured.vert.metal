@@ -65,6 +65,11 @@ struct PerViewData
     SpotLight spot_lights[16];
     ShadowMap2DData shadow_map_2d_data[32];
     ShadowMapCubeData shadow_map_cube_data[16];
+    float4x4 environment_from_view;
+    float4 irradiance_sh[9];
+    float environment_intensity;
+    uint specular_mip_count;
+    uint has_environment_lighting;
 };
 
 struct MaterialData
@@ -93,6 +98,8 @@ struct spvDescriptorSetBuffer0
     constant PerViewData* per_view_data [[id(0)]];
     array<texture2d<float>, 32> shadow_map_images [[id(3)]];
     array<texturecube<float>, 16> shadow_map_images_cube [[id(35)]];
+    texturecube<float> environment_specular [[id(51)]];
+    texture2d<float> environment_brdf_lut [[id(52)]];
 };
 
 struct spvDescriptorSetBuffer1
@@ -146,6 +153,7 @@ vertex main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer
 {
     constexpr sampler smp(filter::linear, mip_filter::linear, address::repeat, compare_func::never, max_anisotropy(16));
     constexpr sampler smp_depth(filter::linear, mip_filter::linear, compare_func::greater, max_anisotropy(16));
+    constexpr sampler smp_clamp(filter::linear, mip_filter::linear, compare_func::never, max_anisotropy(1));
     main0_out out = {};
     float3x3 out_model_view = {};
     float4x4 in_model_matrix = {};
@@ -1,5 +1,5 @@
 // @[export]
-// @[internal_buffer]
+// @[dynamic]
 layout(set = 0, binding = 0) uniform PerFrameUbo {
     mat4 view_proj;
 } per_frame_data;
@@ -776,6 +776,48 @@ vec4 non_pbr_path(
     return vec4(emissive_color.rgb + rgb_color, 1.0);
 }
 
+// Evaluates the irradiance spherical harmonics in the given direction. The coefficients are divided by
+// pi, so multiplying the result by the base color gives lambertian diffuse.
+vec3 environment_irradiance(vec3 n) {
+    vec3 irradiance = per_view_data.irradiance_sh[0].rgb
+        + per_view_data.irradiance_sh[1].rgb * n.y
+        + per_view_data.irradiance_sh[2].rgb * n.z
+        + per_view_data.irradiance_sh[3].rgb * n.x
+        + per_view_data.irradiance_sh[4].rgb * (n.x * n.y)
+        + per_view_data.irradiance_sh[5].rgb * (n.y * n.z)
+        + per_view_data.irradiance_sh[6].rgb * (3.0 * n.z * n.z - 1.0)
+        + per_view_data.irradiance_sh[7].rgb * (n.x * n.z)
+        + per_view_data.irradiance_sh[8].rgb * (n.x * n.x - n.y * n.y);
+    return max(irradiance, vec3(0.0));
+}
+
+// Image based lighting using the split sum approximation from the unreal paper
+vec3 environment_light_pbr(
+    vec3 surface_to_eye_vs,
+    vec3 normal_vs,
+    vec3 fresnel_base,
+    vec3 base_color,
+    float roughness,
+    float metalness
+) {
+    mat3 environment_from_view = mat3(per_view_data.environment_from_view);
+    vec3 normal_env = normalize(environment_from_view * normal_vs);
+    vec3 reflection_env = normalize(environment_from_view * reflect(-surface_to_eye_vs, normal_vs));
+    float n_dot_v = max(dot(normal_vs, surface_to_eye_vs), 0.0);
+
+    // Fresnel that accounts for roughness so that rough surfaces don't get overly bright edges
+    vec3 fresnel = fresnel_base + (max(vec3(1.0 - roughness), fresnel_base) - fresnel_base) * pow(1.0 - n_dot_v, 5.0);
+    vec3 diffuse_factor = (vec3(1.0) - fresnel) * (1.0 - metalness);
+    vec3 diffuse = diffuse_factor * base_color * environment_irradiance(normal_env);
+
+    float specular_lod = roughness * float(max(per_view_data.specular_mip_count, 1u) - 1u);
+    vec3 prefiltered = textureLod(samplerCube(environment_specular, smp_clamp), reflection_env, specular_lod).rgb;
+    vec2 brdf = texture(sampler2D(environment_brdf_lut, smp_clamp), vec2(n_dot_v, roughness)).rg;
+    vec3 specular = prefiltered * (fresnel_base * brdf.x + brdf.y);
+
+    return (diffuse + specular) * per_view_data.environment_intensity;
+}
+
 //TODO: Light range is not being considered. Will want a method of tapering it to zero
 vec4 pbr_path(
     vec3 surface_to_eye_vs,
@@ -871,7 +913,19 @@ vec4 pbr_path(
     //
     // There are still issues here, not sure how alpha interacts and gamma looks terrible
     //
-    vec3 ambient = per_view_data.ambient_light.rgb * base_color.rgb; //TODO: Multiply ao in here
+    vec3 ambient;
+    if (per_view_data.has_environment_lighting) {
+        ambient = environment_light_pbr(
+            surface_to_eye_vs,
+            normal_vs,
+            fresnel_base,
+            base_color.rgb,
+            roughness,
+            metalness
+        ); //TODO: Multiply ao in here
+    } else {
+        ambient = per_view_data.ambient_light.rgb * base_color.rgb; //TODO: Multiply ao in here
+    }
 
 #ifdef DEBUG_RENDER_PERCENT_LIT
     vec3 color = total_light;
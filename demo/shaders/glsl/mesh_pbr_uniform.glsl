@@ -62,6 +62,14 @@ layout (set = 0, binding = 0) uniform PerViewData {
     SpotLight spot_lights[16];
     ShadowMap2DData shadow_map_2d_data[32];
     ShadowMapCubeData shadow_map_cube_data[16];
+    // Rotates view space directions into the space of the environment lighting cubemaps
+    mat4 environment_from_view;
+    // Diffuse irradiance as order 2 spherical harmonics, premultiplied by the basis constants
+    vec4 irradiance_sh[9];
+    float environment_intensity;
+    uint specular_mip_count;
+    // If false, ambient_light is used instead of the environment
+    bool has_environment_lighting;
 } per_view_data;
 
 // @[immutable_samplers([
@@ -98,6 +106,26 @@ layout (set = 0, binding = 3) uniform texture2D shadow_map_images[32];
 // @[export]
 layout (set = 0, binding = 4) uniform textureCube shadow_map_images_cube[16];
 
+// Prefiltered with GGX, roughness increases linearly from 0 at mip 0 to 1 at the last mip
+// @[export]
+layout (set = 0, binding = 5) uniform textureCube environment_specular;
+
+// Split sum scale (R) and bias (G) for F0, indexed by n.v and roughness
+// @[export]
+layout (set = 0, binding = 6) uniform texture2D environment_brdf_lut;
+
+// @[immutable_samplers([
+//     (
+//         mag_filter: Linear,
+//         min_filter: Linear,
+//         mip_map_mode: Linear,
+//         address_mode_u: ClampToEdge,
+//         address_mode_v: ClampToEdge,
+//         address_mode_w: ClampToEdge,
+//     )
+// ])]
+layout (set = 0, binding = 7) uniform sampler smp_clamp;
+
 //
 // Per-Material Bindings
 //
@@ -30,7 +30,7 @@ pub const PER_FRAME_DATA_DESCRIPTOR_SET_INDEX: usize = 0;
 pub const PER_FRAME_DATA_DESCRIPTOR_BINDING_INDEX: usize = 0;
 
 pub struct DescriptorSet0Args<'a> {
-    pub per_frame_data: &'a PerFrameUboUniform,
+    pub per_frame_data: &'a rafx_framework::DynRingBufferAllocation,
 }
 
 impl<'a> DescriptorSetInitializer<'a> for DescriptorSet0Args<'a> {
@@ -61,7 +61,7 @@ impl<'a> DescriptorSetWriter<'a> for DescriptorSet0Args<'a> {
         descriptor_set: &mut DescriptorSetWriterContext,
         args: Self,
     ) {
-        descriptor_set.set_buffer_data(
+        descriptor_set.set_dynamic_uniform_buffer(
             PER_FRAME_DATA_DESCRIPTOR_BINDING_INDEX as u32,
             args.per_frame_data,
         );
@@ -75,7 +75,7 @@ impl DescriptorSet0 {
         descriptor_set: &mut DynDescriptorSet,
         args: DescriptorSet0Args,
     ) {
-        descriptor_set.set_buffer_data(
+        descriptor_set.set_dynamic_uniform_buffer(
             PER_FRAME_DATA_DESCRIPTOR_BINDING_INDEX as u32,
             args.per_frame_data,
         );
@@ -90,9 +90,9 @@ impl DescriptorSet0 {
 
     pub fn set_per_frame_data(
         &mut self,
-        per_frame_data: &PerFrameUboUniform,
+        per_frame_data: &rafx_framework::DynRingBufferAllocation,
     ) {
-        self.0.set_buffer_data(
+        self.0.set_dynamic_uniform_buffer(
             PER_FRAME_DATA_DESCRIPTOR_BINDING_INDEX as u32,
             per_frame_data,
         );
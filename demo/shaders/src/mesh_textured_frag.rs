@@ -85,7 +85,13 @@ pub struct PerViewDataStd140 {
     pub spot_lights: [SpotLightStd140; 16],                  // +2208 (size: 1536)
     pub shadow_map_2d_data: [ShadowMap2DDataStd140; 32],     // +3744 (size: 2560)
     pub shadow_map_cube_data: [ShadowMapCubeDataStd140; 16], // +6304 (size: 256)
-} // 6560 bytes
+    pub environment_from_view: [[f32; 4]; 4],                // +6560 (size: 64)
+    pub irradiance_sh: [[f32; 4]; 9],                        // +6624 (size: 144)
+    pub environment_intensity: f32,                          // +6768 (size: 4)
+    pub specular_mip_count: u32,                             // +6772 (size: 4)
+    pub has_environment_lighting: u32,                       // +6776 (size: 4)
+    pub _padding1: [u8; 4],                                  // +6780 (size: 4)
+} // 6784 bytes
 
 impl Default for PerViewDataStd140 {
     fn default() -> Self {
@@ -102,6 +108,12 @@ impl Default for PerViewDataStd140 {
             spot_lights: [<SpotLightStd140>::default(); 16],
             shadow_map_2d_data: [<ShadowMap2DDataStd140>::default(); 32],
             shadow_map_cube_data: [<ShadowMapCubeDataStd140>::default(); 16],
+            environment_from_view: <[[f32; 4]; 4]>::default(),
+            irradiance_sh: [<[f32; 4]>::default(); 9],
+            environment_intensity: <f32>::default(),
+            specular_mip_count: <u32>::default(),
+            has_environment_lighting: <u32>::default(),
+            _padding1: [u8::default(); 4],
         }
     }
 }
@@ -256,6 +268,12 @@ pub const SHADOW_MAP_IMAGES_DESCRIPTOR_SET_INDEX: usize = 0;
 pub const SHADOW_MAP_IMAGES_DESCRIPTOR_BINDING_INDEX: usize = 3;
 pub const SHADOW_MAP_IMAGES_CUBE_DESCRIPTOR_SET_INDEX: usize = 0;
 pub const SHADOW_MAP_IMAGES_CUBE_DESCRIPTOR_BINDING_INDEX: usize = 4;
+pub const ENVIRONMENT_SPECULAR_DESCRIPTOR_SET_INDEX: usize = 0;
+pub const ENVIRONMENT_SPECULAR_DESCRIPTOR_BINDING_INDEX: usize = 5;
+pub const ENVIRONMENT_BRDF_LUT_DESCRIPTOR_SET_INDEX: usize = 0;
+pub const ENVIRONMENT_BRDF_LUT_DESCRIPTOR_BINDING_INDEX: usize = 6;
+pub const SMP_CLAMP_DESCRIPTOR_SET_INDEX: usize = 0;
+pub const SMP_CLAMP_DESCRIPTOR_BINDING_INDEX: usize = 7;
 pub const PER_MATERIAL_DATA_DESCRIPTOR_SET_INDEX: usize = 1;
 pub const PER_MATERIAL_DATA_DESCRIPTOR_BINDING_INDEX: usize = 0;
 pub const BASE_COLOR_TEXTURE_DESCRIPTOR_SET_INDEX: usize = 1;
@@ -273,6 +291,8 @@ pub struct DescriptorSet0Args<'a> {
     pub per_view_data: &'a PerViewDataUniform,
     pub shadow_map_images: &'a [Option<&'a ResourceArc<ImageViewResource>>; 32],
     pub shadow_map_images_cube: &'a [Option<&'a ResourceArc<ImageViewResource>>; 16],
+    pub environment_specular: &'a ResourceArc<ImageViewResource>,
+    pub environment_brdf_lut: &'a ResourceArc<ImageViewResource>,
 }
 
 impl<'a> DescriptorSetInitializer<'a> for DescriptorSet0Args<'a> {
@@ -315,6 +335,14 @@ impl<'a> DescriptorSetWriter<'a> for DescriptorSet0Args<'a> {
             SHADOW_MAP_IMAGES_CUBE_DESCRIPTOR_BINDING_INDEX as u32,
             args.shadow_map_images_cube,
         );
+        descriptor_set.set_image(
+            ENVIRONMENT_SPECULAR_DESCRIPTOR_BINDING_INDEX as u32,
+            args.environment_specular,
+        );
+        descriptor_set.set_image(
+            ENVIRONMENT_BRDF_LUT_DESCRIPTOR_BINDING_INDEX as u32,
+            args.environment_brdf_lut,
+        );
     }
 }
 
@@ -337,6 +365,14 @@ impl DescriptorSet0 {
             SHADOW_MAP_IMAGES_CUBE_DESCRIPTOR_BINDING_INDEX as u32,
             args.shadow_map_images_cube,
         );
+        descriptor_set.set_image(
+            ENVIRONMENT_SPECULAR_DESCRIPTOR_BINDING_INDEX as u32,
+            args.environment_specular,
+        );
+        descriptor_set.set_image(
+            ENVIRONMENT_BRDF_LUT_DESCRIPTOR_BINDING_INDEX as u32,
+            args.environment_brdf_lut,
+        );
     }
 
     pub fn set_args(
@@ -346,6 +382,8 @@ impl DescriptorSet0 {
         self.set_per_view_data(args.per_view_data);
         self.set_shadow_map_images(args.shadow_map_images);
         self.set_shadow_map_images_cube(args.shadow_map_images_cube);
+        self.set_environment_specular(args.environment_specular);
+        self.set_environment_brdf_lut(args.environment_brdf_lut);
     }
 
     pub fn set_per_view_data(
@@ -400,6 +438,26 @@ impl DescriptorSet0 {
         );
     }
 
+    pub fn set_environment_specular(
+        &mut self,
+        environment_specular: &ResourceArc<ImageViewResource>,
+    ) {
+        self.0.set_image(
+            ENVIRONMENT_SPECULAR_DESCRIPTOR_BINDING_INDEX as u32,
+            environment_specular,
+        );
+    }
+
+    pub fn set_environment_brdf_lut(
+        &mut self,
+        environment_brdf_lut: &ResourceArc<ImageViewResource>,
+    ) {
+        self.0.set_image(
+            ENVIRONMENT_BRDF_LUT_DESCRIPTOR_BINDING_INDEX as u32,
+            environment_brdf_lut,
+        );
+    }
+
     pub fn flush(
         &mut self,
         descriptor_set_allocator: &mut DescriptorSetAllocator,
@@ -688,7 +746,7 @@ mod test {
 
     #[test]
     fn test_struct_per_view_data_std140() {
-        assert_eq!(std::mem::size_of::<PerViewDataStd140>(), 6560);
+        assert_eq!(std::mem::size_of::<PerViewDataStd140>(), 6784);
         assert_eq!(std::mem::size_of::<[[f32; 4]; 4]>(), 64);
         assert_eq!(std::mem::align_of::<[[f32; 4]; 4]>(), 4);
         assert_eq!(memoffset::offset_of!(PerViewDataStd140, view), 0);
@@ -743,6 +801,39 @@ mod test {
             memoffset::offset_of!(PerViewDataStd140, shadow_map_cube_data),
             6304
         );
+        assert_eq!(std::mem::size_of::<[[f32; 4]; 4]>(), 64);
+        assert_eq!(std::mem::align_of::<[[f32; 4]; 4]>(), 4);
+        assert_eq!(
+            memoffset::offset_of!(PerViewDataStd140, environment_from_view),
+            6560
+        );
+        assert_eq!(std::mem::size_of::<[[f32; 4]; 9]>(), 144);
+        assert_eq!(std::mem::align_of::<[[f32; 4]; 9]>(), 4);
+        assert_eq!(
+            memoffset::offset_of!(PerViewDataStd140, irradiance_sh),
+            6624
+        );
+        assert_eq!(std::mem::size_of::<f32>(), 4);
+        assert_eq!(std::mem::align_of::<f32>(), 4);
+        assert_eq!(
+            memoffset::offset_of!(PerViewDataStd140, environment_intensity),
+            6768
+        );
+        assert_eq!(std::mem::size_of::<u32>(), 4);
+        assert_eq!(std::mem::align_of::<u32>(), 4);
+        assert_eq!(
+            memoffset::offset_of!(PerViewDataStd140, specular_mip_count),
+            6772
+        );
+        assert_eq!(std::mem::size_of::<u32>(), 4);
+        assert_eq!(std::mem::align_of::<u32>(), 4);
+        assert_eq!(
+            memoffset::offset_of!(PerViewDataStd140, has_environment_lighting),
+            6776
+        );
+        assert_eq!(std::mem::size_of::<[u8; 4]>(), 4);
+        assert_eq!(std::mem::align_of::<[u8; 4]>(), 1);
+        assert_eq!(memoffset::offset_of!(PerViewDataStd140, _padding1), 6780);
     }
 
     #[test]
@@ -0,0 +1,75 @@
+use distill::loader::handle::Handle;
+use rafx::api::RafxResult;
+use rafx::assets::{
+    AssetManager, DefaultAssetTypeHandler, DefaultAssetTypeLoadHandler, ImageAsset,
+};
+use rafx::framework::{ImageViewResource, ResourceArc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use type_uuid::*;
+
+#[derive(TypeUuid, Serialize, Deserialize, Clone)]
+#[uuid = "0c3f6f1e-8a0b-4d4e-9b57-5f2e71c4a9d3"]
+pub struct EnvironmentLightingAssetData {
+    // Diffuse irradiance as order 2 spherical harmonics, see ibl_baking::project_irradiance_sh
+    pub irradiance_sh: [[f32; 4]; 9],
+    // Cubemap prefiltered with GGX, roughness increases linearly from 0 at mip 0 to 1 at the last
+    // mip
+    pub specular: Handle<ImageAsset>,
+    pub specular_mip_count: u32,
+    // Split sum scale and bias for F0, indexed by n.v and roughness
+    pub brdf_lut: Handle<ImageAsset>,
+}
+
+pub struct EnvironmentLightingAssetInner {
+    pub irradiance_sh: [[f32; 4]; 9],
+    pub specular_image_view: ResourceArc<ImageViewResource>,
+    pub specular_mip_count: u32,
+    pub brdf_lut_image_view: ResourceArc<ImageViewResource>,
+}
+
+#[derive(TypeUuid, Clone)]
+#[uuid = "7d2b9e64-3f15-4c8a-a0e1-b6c94d27f851"]
+pub struct EnvironmentLightingAsset {
+    pub inner: Arc<EnvironmentLightingAssetInner>,
+}
+
+pub struct EnvironmentLightingLoadHandler;
+
+impl DefaultAssetTypeLoadHandler<EnvironmentLightingAssetData, EnvironmentLightingAsset>
+    for EnvironmentLightingLoadHandler
+{
+    #[profiling::function]
+    fn load(
+        asset_manager: &mut AssetManager,
+        environment_lighting_asset: EnvironmentLightingAssetData,
+    ) -> RafxResult<EnvironmentLightingAsset> {
+        let specular_image_view = asset_manager
+            .latest_asset(&environment_lighting_asset.specular)
+            .unwrap()
+            .image_view
+            .clone();
+        let brdf_lut_image_view = asset_manager
+            .latest_asset(&environment_lighting_asset.brdf_lut)
+            .unwrap()
+            .image_view
+            .clone();
+
+        let inner = EnvironmentLightingAssetInner {
+            irradiance_sh: environment_lighting_asset.irradiance_sh,
+            specular_image_view,
+            specular_mip_count: environment_lighting_asset.specular_mip_count,
+            brdf_lut_image_view,
+        };
+
+        Ok(EnvironmentLightingAsset {
+            inner: Arc::new(inner),
+        })
+    }
+}
+
+pub type EnvironmentLightingAssetType = DefaultAssetTypeHandler<
+    EnvironmentLightingAssetData,
+    EnvironmentLightingAsset,
+    EnvironmentLightingLoadHandler,
+>;
@@ -0,0 +1,519 @@
+// CPU implementation of the image based lighting precomputation from "Real Shading in Unreal Engine
+// 4" (Karis 2013) and "An Efficient Representation for Irradiance Environment Maps" (Ramamoorthi and
+// Hanrahan 2001). Cubemaps are six faces of linear RGBA floats in layer order (+X, -X, +Y, -Y, +Z,
+// -Z), matching rafx::assets::CubemapFaces.
+use glam::{Vec3, Vec4};
+use rafx::assets::cube_face_direction;
+use std::f32::consts::PI;
+
+struct CubemapLevel {
+    face_size: u32,
+    faces: Vec<Vec<f32>>,
+}
+
+impl CubemapLevel {
+    fn texel(
+        &self,
+        face: usize,
+        x: u32,
+        y: u32,
+    ) -> Vec4 {
+        let index = ((y * self.face_size + x) * 4) as usize;
+        let texel = &self.faces[face][index..index + 4];
+        Vec4::new(texel[0], texel[1], texel[2], texel[3])
+    }
+
+    fn downsample(&self) -> CubemapLevel {
+        let face_size = (self.face_size / 2).max(1);
+        let faces = (0..6)
+            .map(|face| {
+                let mut rgba = Vec::with_capacity((face_size * face_size * 4) as usize);
+                for y in 0..face_size {
+                    for x in 0..face_size {
+                        let mut sum = Vec4::ZERO;
+                        for (offset_x, offset_y) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            let src_x = (x * 2 + offset_x).min(self.face_size - 1);
+                            let src_y = (y * 2 + offset_y).min(self.face_size - 1);
+                            sum += self.texel(face, src_x, src_y);
+                        }
+
+                        rgba.extend_from_slice(&<[f32; 4]>::from(sum * 0.25));
+                    }
+                }
+                rgba
+            })
+            .collect();
+
+        CubemapLevel { face_size, faces }
+    }
+
+    // Bilinear sample, clamped to the edges of the face. Seams between faces are not filtered.
+    fn sample(
+        &self,
+        direction: Vec3,
+    ) -> Vec4 {
+        let (face, u, v) = direction_to_face_uv(direction);
+        let px = u * self.face_size as f32 - 0.5;
+        let py = v * self.face_size as f32 - 0.5;
+        let x0 = px.floor();
+        let y0 = py.floor();
+        let fx = px - x0;
+        let fy = py - y0;
+
+        let max = self.face_size as i64 - 1;
+        let texel =
+            |x: i64, y: i64| self.texel(face, x.max(0).min(max) as u32, y.max(0).min(max) as u32);
+
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        texel(x0, y0) * ((1.0 - fx) * (1.0 - fy))
+            + texel(x0 + 1, y0) * (fx * (1.0 - fy))
+            + texel(x0, y0 + 1) * ((1.0 - fx) * fy)
+            + texel(x0 + 1, y0 + 1) * (fx * fy)
+    }
+}
+
+// Inverse of cube_face_direction. Returns the face and the position on it in [0, 1].
+fn direction_to_face_uv(direction: Vec3) -> (usize, f32, f32) {
+    let abs = direction.abs();
+    let (face, u, v, major) = if abs.x >= abs.y && abs.x >= abs.z {
+        if direction.x > 0.0 {
+            (0, -direction.z, -direction.y, abs.x)
+        } else {
+            (1, direction.z, -direction.y, abs.x)
+        }
+    } else if abs.y >= abs.z {
+        if direction.y > 0.0 {
+            (2, direction.x, direction.z, abs.y)
+        } else {
+            (3, direction.x, -direction.z, abs.y)
+        }
+    } else if direction.z > 0.0 {
+        (4, direction.x, -direction.y, abs.z)
+    } else {
+        (5, -direction.x, -direction.y, abs.z)
+    };
+
+    (face, (u / major + 1.0) * 0.5, (v / major + 1.0) * 0.5)
+}
+
+fn texel_direction(
+    face: usize,
+    x: u32,
+    y: u32,
+    face_size: u32,
+) -> Vec3 {
+    let [dx, dy, dz] = cube_face_direction(face, x as f32 + 0.5, y as f32 + 0.5, face_size);
+    Vec3::new(dx, dy, dz).normalize()
+}
+
+// Approximate solid angle of a texel, good enough for weighting samples
+fn texel_solid_angle(
+    x: u32,
+    y: u32,
+    face_size: u32,
+) -> f32 {
+    let u = 2.0 * (x as f32 + 0.5) / face_size as f32 - 1.0;
+    let v = 2.0 * (y as f32 + 0.5) / face_size as f32 - 1.0;
+    let texel_area = 4.0 / (face_size * face_size) as f32;
+    texel_area / (1.0 + u * u + v * v).powf(1.5)
+}
+
+// The source environment and its mip chain. Filtering samples lower resolution mips to keep the
+// sample count low without aliasing.
+pub struct SourceEnvironment {
+    levels: Vec<CubemapLevel>,
+}
+
+impl SourceEnvironment {
+    pub fn new(
+        face_size: u32,
+        faces: Vec<Vec<f32>>,
+    ) -> Self {
+        let mut levels = vec![CubemapLevel { face_size, faces }];
+        while levels.last().unwrap().face_size > 1 {
+            let level = levels.last().unwrap().downsample();
+            levels.push(level);
+        }
+
+        SourceEnvironment { levels }
+    }
+
+    fn face_size(&self) -> u32 {
+        self.levels[0].face_size
+    }
+
+    // Trilinear sample. lod 0 is the full resolution environment.
+    fn sample_lod(
+        &self,
+        direction: Vec3,
+        lod: f32,
+    ) -> Vec4 {
+        let lod = lod.max(0.0).min((self.levels.len() - 1) as f32);
+        let lower = lod.floor() as usize;
+        let upper = (lower + 1).min(self.levels.len() - 1);
+        let t = lod - lower as f32;
+
+        let sample = self.levels[lower].sample(direction);
+        if t > 0.0 {
+            sample * (1.0 - t) + self.levels[upper].sample(direction) * t
+        } else {
+            sample
+        }
+    }
+
+    // The first level at or below the given size, for computations that don't need full resolution
+    fn level_at_most(
+        &self,
+        face_size: u32,
+    ) -> &CubemapLevel {
+        self.levels
+            .iter()
+            .find(|level| level.face_size <= face_size)
+            .unwrap_or_else(|| self.levels.last().unwrap())
+    }
+}
+
+fn radical_inverse(i: u32) -> f32 {
+    i.reverse_bits() as f32 * 2.328_306_4e-10
+}
+
+fn hammersley(
+    i: u32,
+    sample_count: u32,
+) -> (f32, f32) {
+    (i as f32 / sample_count as f32, radical_inverse(i))
+}
+
+// Half vector around +Z, distributed proportionally to D(h) * cos(theta_h)
+fn importance_sample_ggx(
+    xi: (f32, f32),
+    alpha: f32,
+) -> Vec3 {
+    let phi = 2.0 * PI * xi.0;
+    let cos_theta = ((1.0 - xi.1) / (1.0 + (alpha * alpha - 1.0) * xi.1)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+fn distribution_ggx(
+    n_dot_h: f32,
+    alpha: f32,
+) -> f32 {
+    let alpha_squared = alpha * alpha;
+    let denominator = n_dot_h * n_dot_h * (alpha_squared - 1.0) + 1.0;
+    alpha_squared / (PI * denominator * denominator)
+}
+
+fn geometry_smith_ibl(
+    n_dot_v: f32,
+    n_dot_l: f32,
+    roughness: f32,
+) -> f32 {
+    // Karis uses k = alpha / 2 for image based lighting
+    let k = roughness * roughness * 0.5;
+    let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    g_v * g_l
+}
+
+// Projects the environment onto order 2 (9 coefficient) spherical harmonics and convolves it with
+// the clamped cosine lobe. Coefficients are premultiplied by the SH basis constants and divided by
+// pi, so that diffuse lighting is base_color * the polynomial evaluated in mesh_pbr_frag.glsl.
+pub fn project_irradiance_sh(environment: &SourceEnvironment) -> [[f32; 4]; 9] {
+    // Irradiance is very low frequency, a small level is plenty
+    let level = environment.level_at_most(64);
+
+    let mut coefficients = [Vec3::ZERO; 9];
+    let mut total_solid_angle = 0.0;
+    for face in 0..6 {
+        for y in 0..level.face_size {
+            for x in 0..level.face_size {
+                let direction = texel_direction(face, x, y, level.face_size);
+                let solid_angle = texel_solid_angle(x, y, level.face_size);
+                let radiance = level.texel(face, x, y).truncate() * solid_angle;
+                total_solid_angle += solid_angle;
+
+                let (dx, dy, dz) = (direction.x, direction.y, direction.z);
+                let basis = [
+                    0.282095,
+                    0.488603 * dy,
+                    0.488603 * dz,
+                    0.488603 * dx,
+                    1.092548 * dx * dy,
+                    1.092548 * dy * dz,
+                    0.315392 * (3.0 * dz * dz - 1.0),
+                    1.092548 * dx * dz,
+                    0.546274 * (dx * dx - dy * dy),
+                ];
+
+                for (coefficient, basis) in coefficients.iter_mut().zip(&basis) {
+                    *coefficient += radiance * *basis;
+                }
+            }
+        }
+    }
+
+    // Cosine lobe convolution divided by pi for each band, times the basis constants
+    let normalization = 4.0 * PI / total_solid_angle;
+    let band_scale = [1.0, 2.0 / 3.0, 1.0 / 4.0];
+    let basis_constants = [
+        0.282095, 0.488603, 0.488603, 0.488603, 1.092548, 1.092548, 0.315392, 1.092548, 0.546274,
+    ];
+    let bands = [0, 1, 1, 1, 2, 2, 2, 2, 2];
+
+    let mut irradiance_sh = [[0.0; 4]; 9];
+    for i in 0..9 {
+        let scaled = coefficients[i] * (normalization * band_scale[bands[i]] * basis_constants[i]);
+        irradiance_sh[i] = scaled.extend(0.0).into();
+    }
+
+    irradiance_sh
+}
+
+struct PrefilterSample {
+    // Light direction in tangent space, +Z is the normal
+    direction: Vec3,
+    weight: f32,
+    lod: f32,
+}
+
+// Prefilters the environment with the GGX distribution for increasing roughness, one roughness per
+// mip level: roughness = mip / (mip_count - 1). Returns the mip chain of each face, the format
+// expected by ImageAssetData::from_rgba32f_mip_chains.
+pub fn prefilter_specular(
+    environment: &SourceEnvironment,
+    face_size: u32,
+    mip_count: u32,
+    sample_count: u32,
+) -> Vec<Vec<Vec<f32>>> {
+    let mut faces: Vec<Vec<Vec<f32>>> = (0..6)
+        .map(|_| Vec::with_capacity(mip_count as usize))
+        .collect();
+    let source_texel_solid_angle =
+        4.0 * PI / (6 * environment.face_size() * environment.face_size()) as f32;
+
+    for mip in 0..mip_count {
+        let mip_face_size = (face_size >> mip).max(1);
+        let roughness = if mip_count > 1 {
+            mip as f32 / (mip_count - 1) as f32
+        } else {
+            0.0
+        };
+        let alpha = roughness * roughness;
+
+        // The samples are the same for every texel, relative to its normal. Each sample reads a
+        // source mip whose texels cover about the solid angle the sample represents.
+        let samples: Vec<PrefilterSample> = if mip == 0 {
+            let lod = (environment.face_size() as f32 / mip_face_size as f32)
+                .log2()
+                .max(0.0);
+            vec![PrefilterSample {
+                direction: Vec3::Z,
+                weight: 1.0,
+                lod,
+            }]
+        } else {
+            (0..sample_count)
+                .filter_map(|i| {
+                    let h = importance_sample_ggx(hammersley(i, sample_count), alpha);
+                    let direction = h * (2.0 * h.z) - Vec3::Z;
+                    if direction.z <= 0.0 {
+                        return None;
+                    }
+
+                    // With N = V, pdf(l) = D(h) * (n.h) / (4 * (v.h)) = D(h) / 4
+                    let pdf = distribution_ggx(h.z, alpha) * 0.25;
+                    let sample_solid_angle = 1.0 / (sample_count as f32 * pdf + 1e-6);
+                    let lod = 0.5 * (sample_solid_angle / source_texel_solid_angle).log2() + 1.0;
+
+                    Some(PrefilterSample {
+                        direction,
+                        weight: direction.z,
+                        lod,
+                    })
+                })
+                .collect()
+        };
+        let total_weight: f32 = samples.iter().map(|sample| sample.weight).sum();
+
+        for (face, levels) in faces.iter_mut().enumerate() {
+            let mut rgba = Vec::with_capacity((mip_face_size * mip_face_size * 4) as usize);
+            for y in 0..mip_face_size {
+                for x in 0..mip_face_size {
+                    let normal = texel_direction(face, x, y, mip_face_size);
+                    let up = if normal.z.abs() < 0.999 {
+                        Vec3::Z
+                    } else {
+                        Vec3::X
+                    };
+                    let tangent = up.cross(normal).normalize();
+                    let bitangent = normal.cross(tangent);
+
+                    let mut sum = Vec4::ZERO;
+                    for sample in &samples {
+                        let direction = tangent * sample.direction.x
+                            + bitangent * sample.direction.y
+                            + normal * sample.direction.z;
+                        sum += environment.sample_lod(direction, sample.lod) * sample.weight;
+                    }
+
+                    let mut texel = sum / total_weight;
+                    texel.w = 1.0;
+                    rgba.extend_from_slice(&<[f32; 4]>::from(texel));
+                }
+            }
+
+            levels.push(rgba);
+        }
+    }
+
+    faces
+}
+
+// The split sum BRDF lookup table. U is n.v and V is roughness. R is the scale and G the bias
+// applied to F0: specular = prefiltered * (F0 * R + G).
+pub fn integrate_brdf_lut(
+    size: u32,
+    sample_count: u32,
+) -> Vec<f32> {
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        let roughness = (y as f32 + 0.5) / size as f32;
+        let alpha = roughness * roughness;
+        for x in 0..size {
+            let n_dot_v = (x as f32 + 0.5) / size as f32;
+            let v = Vec3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+
+            let mut scale = 0.0;
+            let mut bias = 0.0;
+            for i in 0..sample_count {
+                let h = importance_sample_ggx(hammersley(i, sample_count), alpha);
+                let v_dot_h = v.dot(h);
+                let l = h * (2.0 * v_dot_h) - v;
+
+                let n_dot_l = l.z;
+                let n_dot_h = h.z;
+                if n_dot_l > 0.0 && v_dot_h > 0.0 {
+                    let g = geometry_smith_ibl(n_dot_v, n_dot_l, roughness);
+                    let g_vis = g * v_dot_h / (n_dot_h * n_dot_v);
+                    let fresnel = (1.0 - v_dot_h).powi(5);
+                    scale += (1.0 - fresnel) * g_vis;
+                    bias += fresnel * g_vis;
+                }
+            }
+
+            rgba.extend_from_slice(&[
+                scale / sample_count as f32,
+                bias / sample_count as f32,
+                0.0,
+                1.0,
+            ]);
+        }
+    }
+
+    rgba
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn constant_environment(
+        face_size: u32,
+        radiance: [f32; 3],
+    ) -> SourceEnvironment {
+        let texel = [radiance[0], radiance[1], radiance[2], 1.0];
+        let face: Vec<f32> = texel
+            .iter()
+            .cycle()
+            .take((face_size * face_size * 4) as usize)
+            .copied()
+            .collect();
+        SourceEnvironment::new(face_size, vec![face; 6])
+    }
+
+    // Each face is filled with a single color so that the face a sample came from is easy to tell
+    fn face_colored_environment(face_size: u32) -> SourceEnvironment {
+        let faces = (0..6)
+            .map(|face| {
+                let texel = [face as f32, 10.0 - face as f32, 1.0, 1.0];
+                texel
+                    .iter()
+                    .cycle()
+                    .take((face_size * face_size * 4) as usize)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        SourceEnvironment::new(face_size, faces)
+    }
+
+    #[test]
+    fn test_direction_to_face_uv_round_trip() {
+        let face_size = 8;
+        for face in 0..6 {
+            for y in 0..face_size {
+                for x in 0..face_size {
+                    let direction = texel_direction(face, x, y, face_size);
+                    let (found_face, u, v) = direction_to_face_uv(direction);
+                    assert_eq!(found_face, face);
+                    assert!((u * face_size as f32 - (x as f32 + 0.5)).abs() < 1e-4);
+                    assert!((v * face_size as f32 - (y as f32 + 0.5)).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_environment_irradiance() {
+        let radiance = [0.5, 1.0, 2.0];
+        let irradiance_sh = project_irradiance_sh(&constant_environment(16, radiance));
+
+        // Irradiance from a constant environment is pi * radiance, which the /pi cancels out
+        for channel in 0..3 {
+            assert!(
+                (irradiance_sh[0][channel] - radiance[channel]).abs() < 0.01 * radiance[channel]
+            );
+        }
+
+        for coefficient in &irradiance_sh[1..] {
+            for value in &coefficient[0..3] {
+                assert!(value.abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefilter_specular_mip_0() {
+        let face_size = 4;
+        let faces = prefilter_specular(&face_colored_environment(face_size), face_size, 3, 16);
+
+        assert_eq!(faces.len(), 6);
+        for (face, levels) in faces.iter().enumerate() {
+            assert_eq!(levels.len(), 3);
+            assert_eq!(levels[0].len(), (face_size * face_size * 4) as usize);
+            assert_eq!(levels[1].len(), 2 * 2 * 4);
+            assert_eq!(levels[2].len(), 4);
+
+            // Mip 0 is a mirror, so each texel is the environment in the texel's own direction
+            for texel in levels[0].chunks(4) {
+                assert_eq!(texel, &[face as f32, 10.0 - face as f32, 1.0, 1.0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_brdf_lut_smooth_surface() {
+        let size = 32;
+        let lut = integrate_brdf_lut(size, 64);
+        assert_eq!(lut.len(), (size * size * 4) as usize);
+
+        // At low roughness and n.v near 1 the reflection is nearly lossless
+        let x = size - 1;
+        let y = 0;
+        let index = ((y * size + x) * 4) as usize;
+        let (scale, bias) = (lut[index], lut[index + 1]);
+        assert!((scale + bias - 1.0).abs() < 0.02);
+        assert!(bias < 0.01);
+    }
+}
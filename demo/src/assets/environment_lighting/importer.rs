@@ -0,0 +1,182 @@
+use super::ibl_baking;
+use crate::assets::environment_lighting::EnvironmentLightingAssetData;
+use distill::core::AssetUuid;
+use distill::importer::{Error, ImportOp, ImportedAsset, Importer, ImporterValue};
+use distill::make_handle;
+use rafx::api::RafxResourceType;
+use rafx::assets::{
+    CubemapFaces, CubemapImporter, CubemapSource, ImageAssetColorSpace, ImageAssetData,
+    ImageAssetDataFormatConfig, ImageAssetMipGeneration, ImageAssetMipSettings,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use type_uuid::*;
+
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "b8e54d21-6f3a-4c97-8e0d-2a71f9c3b6e4"]
+#[serde(default)]
+pub struct EnvironmentLightingImporterOptions {
+    // The color space of LDR source images. HDR source images (.hdr, .exr) are always linear.
+    pub color_space: ImageAssetColorSpace,
+    pub specular_face_size: u32,
+    // Clamped to the number of mips the face size allows. Fewer mips keep the roughest levels from
+    // becoming too blocky.
+    pub specular_mip_count: u32,
+    pub specular_sample_count: u32,
+    pub brdf_lut_size: u32,
+    pub brdf_lut_sample_count: u32,
+}
+
+impl Default for EnvironmentLightingImporterOptions {
+    fn default() -> Self {
+        EnvironmentLightingImporterOptions {
+            color_space: ImageAssetColorSpace::Srgb,
+            specular_face_size: 128,
+            specular_mip_count: 6,
+            specular_sample_count: 128,
+            brdf_lut_size: 128,
+            brdf_lut_sample_count: 256,
+        }
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "3e9a0c75-d1b8-42f6-9c4e-85f7a2d06b19"]
+pub struct EnvironmentLightingImporterState {
+    environment_lighting: Option<AssetUuid>,
+    specular: Option<AssetUuid>,
+    brdf_lut: Option<AssetUuid>,
+}
+
+// Bakes image based lighting from a cubemap manifest (.envlight, in the same format as .cubemap).
+// Produces the environment lighting asset and the two images it references.
+#[derive(TypeUuid)]
+#[uuid = "f1c6a2e8-5b94-4d07-b3a8-6e2d9c0f7154"]
+pub struct EnvironmentLightingImporter {
+    cubemap_importer: CubemapImporter,
+}
+
+impl EnvironmentLightingImporter {
+    pub fn new(cubemap_importer: CubemapImporter) -> Self {
+        EnvironmentLightingImporter { cubemap_importer }
+    }
+}
+
+impl Importer for EnvironmentLightingImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = EnvironmentLightingImporterOptions;
+
+    type State = EnvironmentLightingImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        op: &mut ImportOp,
+        source: &mut dyn Read,
+        options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let environment_lighting_uuid = *state
+            .environment_lighting
+            .get_or_insert_with(|| op.new_asset_uuid());
+        let specular_uuid = *state.specular.get_or_insert_with(|| op.new_asset_uuid());
+        let brdf_lut_uuid = *state.brdf_lut.get_or_insert_with(|| op.new_asset_uuid());
+
+        let cubemap_source = ron::de::from_reader::<_, CubemapSource>(source)
+            .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        let cubemap_faces = self
+            .cubemap_importer
+            .load_faces(&cubemap_source, options.color_space)?;
+        let build_deps = cubemap_faces.build_deps();
+        let CubemapFaces {
+            face_size, faces, ..
+        } = cubemap_faces;
+
+        let environment = ibl_baking::SourceEnvironment::new(face_size, faces);
+        let irradiance_sh = ibl_baking::project_irradiance_sh(&environment);
+
+        let specular_face_size = options.specular_face_size.max(1);
+        let max_mip_count = 32 - specular_face_size.leading_zeros();
+        let specular_mip_count = options.specular_mip_count.max(1).min(max_mip_count);
+        let specular_faces = ibl_baking::prefilter_specular(
+            &environment,
+            specular_face_size,
+            specular_mip_count,
+            options.specular_sample_count.max(1),
+        );
+
+        let specular_asset_data = ImageAssetData::from_rgba32f_mip_chains(
+            specular_face_size,
+            specular_face_size,
+            ImageAssetColorSpace::Linear,
+            ImageAssetDataFormatConfig::RawRGBA16Float,
+            RafxResourceType::TEXTURE_CUBE,
+            specular_faces,
+        )
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        let brdf_lut_size = options.brdf_lut_size.max(1);
+        let brdf_lut =
+            ibl_baking::integrate_brdf_lut(brdf_lut_size, options.brdf_lut_sample_count.max(1));
+
+        let brdf_lut_asset_data = ImageAssetData::from_rgba32f_layers(
+            brdf_lut_size,
+            brdf_lut_size,
+            ImageAssetColorSpace::Linear,
+            ImageAssetDataFormatConfig::RawRGBA16Float,
+            ImageAssetMipGeneration::NoMips,
+            &ImageAssetMipSettings::default(),
+            RafxResourceType::TEXTURE,
+            vec![brdf_lut],
+        )
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        let asset_data = EnvironmentLightingAssetData {
+            irradiance_sh,
+            specular: make_handle(specular_uuid),
+            specular_mip_count,
+            brdf_lut: make_handle(brdf_lut_uuid),
+        };
+
+        Ok(ImporterValue {
+            assets: vec![
+                ImportedAsset {
+                    id: specular_uuid,
+                    search_tags: vec![],
+                    build_deps: build_deps.clone(),
+                    load_deps: vec![],
+                    build_pipeline: None,
+                    asset_data: Box::new(specular_asset_data),
+                },
+                ImportedAsset {
+                    id: brdf_lut_uuid,
+                    search_tags: vec![],
+                    build_deps: vec![],
+                    load_deps: vec![],
+                    build_pipeline: None,
+                    asset_data: Box::new(brdf_lut_asset_data),
+                },
+                ImportedAsset {
+                    id: environment_lighting_uuid,
+                    search_tags: vec![],
+                    build_deps,
+                    load_deps: vec![],
+                    build_pipeline: None,
+                    asset_data: Box::new(asset_data),
+                },
+            ],
+        })
+    }
+}
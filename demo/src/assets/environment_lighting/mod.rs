@@ -0,0 +1,10 @@
+mod assets;
+pub use assets::*;
+
+mod importer;
+pub use importer::*;
+
+pub mod ibl_baking;
+
+mod plugin;
+pub use plugin::*;
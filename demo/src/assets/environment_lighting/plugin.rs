@@ -0,0 +1,38 @@
+use crate::assets::environment_lighting::EnvironmentLightingAssetType;
+use rafx::assets::distill_impl::{AssetResource, ImporterRegistry};
+use rafx::assets::{AssetManager, CubemapImporter};
+use rafx::renderer::RendererAssetPlugin;
+use std::path::PathBuf;
+
+pub struct EnvironmentLightingAssetTypeRendererPlugin {
+    asset_dirs: Vec<PathBuf>,
+}
+
+impl EnvironmentLightingAssetTypeRendererPlugin {
+    // Manifests reference images by their path within these directories, normally the same
+    // directories the asset daemon watches
+    pub fn new(asset_dirs: Vec<PathBuf>) -> Self {
+        EnvironmentLightingAssetTypeRendererPlugin { asset_dirs }
+    }
+}
+
+impl RendererAssetPlugin for EnvironmentLightingAssetTypeRendererPlugin {
+    //
+    fn configure_importers(
+        &self,
+        importers: ImporterRegistry,
+    ) -> ImporterRegistry {
+        importers.with_importer(
+            "envlight",
+            super::EnvironmentLightingImporter::new(CubemapImporter::new(self.asset_dirs.clone())),
+        )
+    }
+
+    fn register_asset_types(
+        &self,
+        asset_manager: &mut AssetManager,
+        asset_resource: &mut AssetResource,
+    ) {
+        asset_manager.register_asset_type::<EnvironmentLightingAssetType>(asset_resource);
+    }
+}
@@ -1,18 +1,17 @@
 use crate::assets::font::FontAssetType;
-use rafx::assets::distill_impl::AssetResource;
+use rafx::assets::distill_impl::{AssetResource, ImporterRegistry};
 use rafx::assets::AssetManager;
-use rafx::distill::daemon::AssetDaemon;
 use rafx::renderer::RendererAssetPlugin;
 
 pub struct FontAssetTypeRendererPlugin;
 
 impl RendererAssetPlugin for FontAssetTypeRendererPlugin {
     //
-    fn configure_asset_daemon(
+    fn configure_importers(
         &self,
-        asset_daemon: AssetDaemon,
-    ) -> AssetDaemon {
-        asset_daemon.with_importer("ttf", super::FontImporter)
+        importers: ImporterRegistry,
+    ) -> ImporterRegistry {
+        importers.with_importer("ttf", super::FontImporter)
     }
 
     fn register_asset_types(
@@ -104,9 +104,9 @@ impl DefaultAssetTypeLoadHandler<LdtkAssetData, LdtkProjectAsset> for LdtkLoadHa
                     .find_pass_by_phase::<OpaqueRenderPhase>()
                     .expect("tileset material must have pass for opaque phase");
                 let tileset_image_set_index = shaders::tile_layer_frag::TEX_DESCRIPTOR_SET_INDEX;
-                let descriptor_set = material_instance.material_descriptor_sets
-                    [opaque_phase_pass_index][tileset_image_set_index]
-                    .clone()
+                let descriptor_set = material_instance
+                    .material_descriptor_sets
+                    .get_descriptor_set(opaque_phase_pass_index, tileset_image_set_index)
                     .unwrap();
 
                 let width = layer_data.grid_width * layer_data.grid_size;
@@ -88,7 +88,7 @@ impl Importer for LdtkImporter {
     where
         Self: Sized,
     {
-        4
+        5
     }
 
     fn version(&self) -> u32 {
@@ -168,6 +168,7 @@ impl Importer for LdtkImporter {
             let material_instance = MaterialInstanceAssetData {
                 material: material_handle.clone(),
                 slot_assignments,
+                uniform_params: vec![],
             };
 
             //
@@ -1,18 +1,17 @@
 use crate::assets::ldtk::LdtkAssetType;
-use rafx::assets::distill_impl::AssetResource;
+use rafx::assets::distill_impl::{AssetResource, ImporterRegistry};
 use rafx::assets::AssetManager;
-use rafx::distill::daemon::AssetDaemon;
 use rafx::renderer::RendererAssetPlugin;
 
 pub struct LdtkAssetTypeRendererPlugin;
 
 impl RendererAssetPlugin for LdtkAssetTypeRendererPlugin {
     //
-    fn configure_asset_daemon(
+    fn configure_importers(
         &self,
-        asset_daemon: AssetDaemon,
-    ) -> AssetDaemon {
-        asset_daemon.with_importer("ldtk", super::LdtkImporter)
+        importers: ImporterRegistry,
+    ) -> ImporterRegistry {
+        importers.with_importer("ldtk", super::LdtkImporter)
     }
 
     fn register_asset_types(
@@ -1,12 +1,15 @@
-use crate::features::mesh::MeshUntexturedRenderFeatureFlag;
+use crate::features::mesh::MESH_DEPTH_WRITE_KEYWORD;
 use crate::phases::{DepthPrepassRenderPhase, OpaqueRenderPhase, WireframeRenderPhase};
 use distill::loader::handle::Handle;
+use glam::{Vec2, Vec3};
 use rafx::api::{RafxIndexType, RafxResult};
 use rafx::assets::MaterialInstanceAsset;
 use rafx::assets::{
     AssetManager, BufferAsset, DefaultAssetTypeHandler, DefaultAssetTypeLoadHandler,
 };
-use rafx::framework::render_features::{RenderPhase, RenderPhaseIndex, RenderView};
+use rafx::framework::render_features::{
+    RenderPhase, RenderPhaseIndex, RenderView, ShaderKeyword, ShaderKeywordMask,
+};
 use rafx::framework::{BufferResource, DescriptorSetArc, MaterialPassResource, ResourceArc};
 use rafx::rafx_visibility::VisibleBounds;
 use serde::{Deserialize, Serialize};
@@ -83,6 +86,41 @@ pub struct MeshPartAssetData {
     pub index_buffer_size_in_bytes: u32,
     pub material_instance: Handle<MaterialInstanceAsset>,
     pub index_type: RafxIndexType,
+    // Texture coordinate units per unit of distance on the surface. Zero if unknown.
+    #[serde(default)]
+    pub uv_density: f32,
+}
+
+impl MeshPartAssetData {
+    // Compares the area of the triangles in texture space to their area in model space. Used to
+    // estimate how much of a texture is visible when the mesh part covers part of the screen.
+    pub fn calculate_uv_density(
+        positions: &[[f32; 3]],
+        tex_coords: &[[f32; 2]],
+        indices: &[u32],
+    ) -> f32 {
+        let mut area = 0.0;
+        let mut uv_area = 0.0;
+        for triangle in indices.chunks_exact(3) {
+            let p0 = Vec3::from(positions[triangle[0] as usize]);
+            let p1 = Vec3::from(positions[triangle[1] as usize]);
+            let p2 = Vec3::from(positions[triangle[2] as usize]);
+            area += (p1 - p0).cross(p2 - p0).length() * 0.5;
+
+            let uv0 = Vec2::from(tex_coords[triangle[0] as usize]);
+            let uv1 = Vec2::from(tex_coords[triangle[1] as usize]);
+            let uv2 = Vec2::from(tex_coords[triangle[2] as usize]);
+            let e0 = uv1 - uv0;
+            let e1 = uv2 - uv0;
+            uv_area += (e0.x * e1.y - e0.y * e1.x).abs() * 0.5;
+        }
+
+        if area > 0.0 && uv_area > 0.0 {
+            (uv_area / area).sqrt()
+        } else {
+            0.0
+        }
+    }
 }
 
 #[derive(TypeUuid, Serialize, Deserialize, Clone)]
@@ -94,63 +132,66 @@ pub struct MeshAssetData {
     pub visible_bounds: VisibleBounds,
 }
 
+#[derive(Clone)]
 pub struct MeshAssetPart {
     pub material_instance: MaterialInstanceAsset,
-    pub textured_pass_index: usize,
-    pub untextured_pass_index: usize,
-    pub wireframe_pass_index: usize,
+    // Enabled when drawing the opaque pass in a view without a depth prepass
+    pub depth_write_keyword: ShaderKeywordMask,
     pub vertex_buffer_offset_in_bytes: u32,
     pub vertex_buffer_size_in_bytes: u32,
     pub index_buffer_offset_in_bytes: u32,
     pub index_buffer_size_in_bytes: u32,
     pub index_type: RafxIndexType,
+    pub uv_density: f32,
 }
 
 pub const PER_MATERIAL_DESCRIPTOR_SET_LAYOUT_INDEX: usize = 1;
 
 impl MeshAssetPart {
+    // Returns None if the material has no pass variant for the phase that is usable with the view's
+    // feature flags. The part is not drawn in that phase.
     pub fn get_material_pass_index(
         &self,
         view: &RenderView,
         render_phase_index: RenderPhaseIndex,
-    ) -> usize {
-        if render_phase_index == OpaqueRenderPhase::render_phase_index() {
-            let offset = !view.phase_is_relevant::<DepthPrepassRenderPhase>() as usize;
-            return if view.feature_flag_is_relevant::<MeshUntexturedRenderFeatureFlag>() {
-                self.untextured_pass_index + offset
-            } else {
-                self.textured_pass_index + offset
-            };
-        } else if render_phase_index == WireframeRenderPhase::render_phase_index() {
-            self.wireframe_pass_index
+    ) -> Option<usize> {
+        // The untextured variant is selected by the view's feature flags
+        let keywords = if render_phase_index == OpaqueRenderPhase::render_phase_index()
+            && !view.phase_is_relevant::<DepthPrepassRenderPhase>()
+        {
+            self.depth_write_keyword
         } else {
-            panic!(
-                "mesh does not support render phase index {}",
-                render_phase_index
-            )
-        }
+            ShaderKeywordMask::empty()
+        };
+
+        self.material_instance.material.find_pass_variant_for_view(
+            view,
+            render_phase_index,
+            keywords,
+        )
     }
 
     pub fn get_material_pass_resource(
         &self,
         view: &RenderView,
         render_phase_index: RenderPhaseIndex,
-    ) -> &ResourceArc<MaterialPassResource> {
-        &self.material_instance.material.passes
-            [self.get_material_pass_index(view, render_phase_index)]
-        .material_pass_resource
+    ) -> Option<&ResourceArc<MaterialPassResource>> {
+        let pass_index = self.get_material_pass_index(view, render_phase_index)?;
+        Some(&self.material_instance.material.passes[pass_index].material_pass_resource)
     }
 
+    // Not cached by the mesh part because streamed textures replace the descriptor set
     pub fn get_material_descriptor_set(
         &self,
         view: &RenderView,
         render_phase_index: RenderPhaseIndex,
-    ) -> &DescriptorSetArc {
-        return &self.material_instance.material_descriptor_sets
-            [self.get_material_pass_index(view, render_phase_index)]
-            [PER_MATERIAL_DESCRIPTOR_SET_LAYOUT_INDEX]
-            .as_ref()
-            .unwrap();
+    ) -> Option<DescriptorSetArc> {
+        self.material_instance
+            .material_descriptor_sets
+            .get_descriptor_set(
+                self.get_material_pass_index(view, render_phase_index)?,
+                PER_MATERIAL_DESCRIPTOR_SET_LAYOUT_INDEX,
+            )
     }
 }
 
@@ -167,6 +208,36 @@ pub struct MeshAsset {
     pub inner: Arc<MeshAssetInner>,
 }
 
+impl MeshAsset {
+    // Returns a copy of the mesh that draws every part with the given material instance. It must
+    // have the same passes as the material instances of the parts.
+    pub fn with_material_instance(
+        &self,
+        material_instance: &MaterialInstanceAsset,
+    ) -> MeshAsset {
+        let mesh_parts = self
+            .inner
+            .mesh_parts
+            .iter()
+            .map(|mesh_part| {
+                mesh_part.as_ref().map(|mesh_part| MeshAssetPart {
+                    material_instance: material_instance.clone(),
+                    ..mesh_part.clone()
+                })
+            })
+            .collect();
+
+        MeshAsset {
+            inner: Arc::new(MeshAssetInner {
+                mesh_parts,
+                vertex_buffer: self.inner.vertex_buffer.clone(),
+                index_buffer: self.inner.index_buffer.clone(),
+                asset_data: self.inner.asset_data.clone(),
+            }),
+        }
+    }
+}
+
 pub struct MeshLoadHandler;
 
 impl DefaultAssetTypeLoadHandler<MeshAssetData, MeshAsset> for MeshLoadHandler {
@@ -186,6 +257,20 @@ impl DefaultAssetTypeLoadHandler<MeshAssetData, MeshAsset> for MeshLoadHandler {
             .buffer
             .clone();
 
+        let depth_write_keyword = match asset_manager
+            .resource_manager()
+            .render_registry()
+            .shader_keyword_from_name(MESH_DEPTH_WRITE_KEYWORD)
+        {
+            Some(ShaderKeyword::Instance(index)) => {
+                ShaderKeywordMask::empty().add_keyword_index(index)
+            }
+            _ => Err(format!(
+                "shader keyword {} is not registered",
+                MESH_DEPTH_WRITE_KEYWORD
+            ))?,
+        };
+
         let mesh_parts: Vec<_> = mesh_asset
             .mesh_parts
             .iter()
@@ -194,53 +279,25 @@ impl DefaultAssetTypeLoadHandler<MeshAssetData, MeshAsset> for MeshLoadHandler {
                     .committed_asset(&mesh_part.material_instance)
                     .unwrap();
 
-                let textured_pass_index = material_instance
-                    .material
-                    .find_pass_by_name("mesh textured")
-                    .expect("could not find `mesh textured` pass in mesh part material");
-
-                let textured_z_pass_index = material_instance
+                material_instance
                     .material
-                    .find_pass_by_name("mesh textured z")
-                    .expect("could not find `mesh textured z` pass in mesh part material");
-
-                assert_eq!(
-                    textured_z_pass_index,
-                    textured_pass_index + 1,
-                    "expected `mesh textured z` to occur after `mesh textured`"
-                );
-
-                let untextured_pass_index = material_instance
-                    .material
-                    .find_pass_by_name("mesh untextured")
-                    .expect("could not find `mesh untextured` pass in mesh part material");
-
-                let untextured_z_pass_index = material_instance
-                    .material
-                    .find_pass_by_name("mesh untextured z")
-                    .expect("could not find `mesh untextured z` pass in mesh part material");
-
-                assert_eq!(
-                    untextured_z_pass_index,
-                    untextured_pass_index + 1,
-                    "expected `mesh untextured z` to occur after `mesh untextured`"
-                );
+                    .find_pass_by_phase::<OpaqueRenderPhase>()
+                    .expect("could not find an opaque pass in mesh part material");
 
-                let wireframe_pass_index = material_instance
+                material_instance
                     .material
-                    .find_pass_by_name("mesh wireframe")
-                    .expect("could not find `mesh wireframe` pass in mesh part material");
+                    .find_pass_by_phase::<WireframeRenderPhase>()
+                    .expect("could not find a wireframe pass in mesh part material");
 
                 Some(MeshAssetPart {
                     material_instance: material_instance.clone(),
-                    textured_pass_index,
-                    untextured_pass_index,
-                    wireframe_pass_index,
+                    depth_write_keyword,
                     vertex_buffer_offset_in_bytes: mesh_part.vertex_buffer_offset_in_bytes,
                     vertex_buffer_size_in_bytes: mesh_part.vertex_buffer_size_in_bytes,
                     index_buffer_offset_in_bytes: mesh_part.index_buffer_offset_in_bytes,
                     index_buffer_size_in_bytes: mesh_part.index_buffer_size_in_bytes,
                     index_type: mesh_part.index_type,
+                    uv_density: mesh_part.uv_density,
                 })
             })
             .collect();
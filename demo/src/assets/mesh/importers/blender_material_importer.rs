@@ -41,7 +41,7 @@ impl Importer for BlenderMaterialImporter {
     where
         Self: Sized,
     {
-        3
+        4
     }
 
     fn version(&self) -> u32 {
@@ -158,6 +158,7 @@ impl Importer for BlenderMaterialImporter {
         let asset_data = MaterialInstanceAssetData {
             material: material_handle.clone(),
             slot_assignments,
+            uniform_params: vec![],
         };
 
         Ok(ImporterValue {
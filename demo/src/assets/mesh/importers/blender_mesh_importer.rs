@@ -179,6 +179,11 @@ impl Importer for BlenderMeshImporter {
                 index_buffer_offset_in_bytes: indices_offset as u32,
                 index_buffer_size_in_bytes: indices_size as u32,
                 index_type,
+                uv_density: MeshPartAssetData::calculate_uv_density(
+                    positions,
+                    tex_coords,
+                    &part_indices.iter().map(|&x| x as u32).collect::<Vec<_>>(),
+                ),
             })
         }
 
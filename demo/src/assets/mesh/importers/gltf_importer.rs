@@ -189,7 +189,7 @@ impl Importer for GltfImporter {
     where
         Self: Sized,
     {
-        29
+        30
     }
 
     fn version(&self) -> u32 {
@@ -368,6 +368,7 @@ impl Importer for GltfImporter {
             let material_instance_asset = MaterialInstanceAssetData {
                 material: material_handle.clone(),
                 slot_assignments,
+                uniform_params: vec![],
             };
 
             log::debug!(
@@ -864,6 +865,11 @@ fn extract_meshes_to_import(
                         index_buffer_offset_in_bytes: indices_offset as u32,
                         index_buffer_size_in_bytes: indices_size as u32,
                         index_type: RafxIndexType::Uint16,
+                        uv_density: MeshPartAssetData::calculate_uv_density(
+                            &positions,
+                            &tex_coords,
+                            &indices_u32,
+                        ),
                     })
                 } else {
                     log::error!(
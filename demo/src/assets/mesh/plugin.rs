@@ -1,18 +1,17 @@
 use crate::assets::mesh::{MeshAssetType, ModelAssetType, PrefabAssetType};
-use rafx::assets::distill_impl::AssetResource;
+use rafx::assets::distill_impl::{AssetResource, ImporterRegistry};
 use rafx::assets::AssetManager;
-use rafx::distill::daemon::AssetDaemon;
 use rafx::renderer::RendererAssetPlugin;
 
 pub struct GltfAssetTypeRendererPlugin;
 
 impl RendererAssetPlugin for GltfAssetTypeRendererPlugin {
     //
-    fn configure_asset_daemon(
+    fn configure_importers(
         &self,
-        asset_daemon: AssetDaemon,
-    ) -> AssetDaemon {
-        asset_daemon
+        importers: ImporterRegistry,
+    ) -> ImporterRegistry {
+        importers
             .with_importer("gltf", super::GltfImporter)
             .with_importer("glb", super::GltfImporter)
             .with_importer("blender_material", super::BlenderMaterialImporter)
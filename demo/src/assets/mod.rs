@@ -1,3 +1,4 @@
+pub mod environment_lighting;
 pub mod font;
 pub mod ldtk;
 pub mod mesh;
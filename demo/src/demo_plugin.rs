@@ -5,7 +5,7 @@ use crate::phases::{
 use rafx::api::extra::upload::RafxTransferUpload;
 use rafx::api::RafxResult;
 use rafx::assets::distill_impl::AssetResource;
-use rafx::assets::{AssetManager, MaterialAsset};
+use rafx::assets::{AssetManager, MaterialAsset, RenderGraphAsset};
 use rafx::base::resource_map::ResourceMap;
 use rafx::distill::loader::handle::Handle;
 use rafx::render_features::{ExtractResources, RenderRegistryBuilder};
@@ -17,6 +17,7 @@ pub struct DemoStaticResources {
     pub bloom_extract_material: Handle<MaterialAsset>,
     pub bloom_blur_material: Handle<MaterialAsset>,
     pub bloom_combine_material: Handle<MaterialAsset>,
+    pub render_graph: Handle<RenderGraphAsset>,
 }
 
 pub struct DemoRendererPlugin;
@@ -65,6 +66,12 @@ impl RendererAssetPlugin for DemoRendererPlugin {
         let bloom_combine_material =
             asset_resource.load_asset_path::<MaterialAsset, _>("materials/bloom_combine.material");
 
+        //
+        // Render graph used when RenderOptions::use_render_graph_asset is set
+        //
+        let render_graph = asset_resource
+            .load_asset_path::<RenderGraphAsset, _>("render_graphs/default.rendergraph");
+
         asset_manager.wait_for_asset_to_load(
             &bloom_extract_material,
             asset_resource,
@@ -83,10 +90,13 @@ impl RendererAssetPlugin for DemoRendererPlugin {
             "bloom combine material",
         )?;
 
+        asset_manager.wait_for_asset_to_load(&render_graph, asset_resource, "render graph")?;
+
         render_resources.insert(DemoStaticResources {
             bloom_extract_material,
             bloom_blur_material,
             bloom_combine_material,
+            render_graph,
         });
 
         Ok(())
@@ -1,6 +1,8 @@
 use super::*;
 use rafx::framework::render_features::render_features_prelude::*;
-use rafx::framework::{BufferResource, DescriptorSetArc, MaterialPassResource, ResourceArc};
+use rafx::framework::{
+    DescriptorSetArc, DynRingBufferAllocation, MaterialPassResource, ResourceArc,
+};
 
 pub struct Debug3DRenderFeatureTypes;
 
@@ -50,13 +52,14 @@ pub type Debug3DUniformBufferObject = shaders::debug_vert::PerFrameUboUniform;
 
 #[derive(Default)]
 pub struct Debug3DPerFrameSubmitData {
-    pub vertex_buffer: Option<ResourceArc<BufferResource>>,
+    pub vertex_buffer: Option<DynRingBufferAllocation>,
     pub vertex_list: Vec<Debug3DVertex>,
     pub draw_calls: Vec<Debug3DDrawCall>,
 }
 
 pub struct Debug3DPerViewSubmitData {
     pub descriptor_set_arc: Option<DescriptorSetArc>,
+    pub dynamic_offset: u32,
 }
 
 pub struct Debug3DDrawCall {
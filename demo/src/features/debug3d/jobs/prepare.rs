@@ -2,12 +2,17 @@ use rafx::render_feature_prepare_job_predule::*;
 
 use super::*;
 use crate::phases::WireframeRenderPhase;
-use rafx::api::{RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResourceType};
-use rafx::framework::ResourceContext;
+use rafx::framework::{
+    BufferResource, DescriptorSetArc, DescriptorSetLayoutResource, DynRingBufferAllocation,
+    ResourceArc, ResourceContext,
+};
+use std::sync::Mutex;
 
 pub struct Debug3DPrepareJob {
     resource_context: ResourceContext,
-    device_context: RafxDeviceContext,
+    // Views whose data was allocated in the same buffer share a descriptor set and bind it with
+    // their own dynamic offset
+    per_view_descriptor_sets: Mutex<Vec<(ResourceArc<BufferResource>, u64, DescriptorSetArc)>>,
 }
 
 impl Debug3DPrepareJob {
@@ -19,12 +24,47 @@ impl Debug3DPrepareJob {
         Arc::new(PrepareJob::new(
             Self {
                 resource_context: prepare_context.resource_context.clone(),
-                device_context: prepare_context.device_context.clone(),
+                per_view_descriptor_sets: Default::default(),
             },
             frame_packet,
             submit_packet,
         ))
     }
+
+    fn per_view_descriptor_set(
+        &self,
+        per_view_descriptor_set_layout: &ResourceArc<DescriptorSetLayoutResource>,
+        per_view_data: &DynRingBufferAllocation,
+    ) -> Option<DescriptorSetArc> {
+        let mut per_view_descriptor_sets = self.per_view_descriptor_sets.lock().unwrap();
+        let existing = per_view_descriptor_sets
+            .iter()
+            .find(|(buffer, byte_offset, _)| {
+                buffer == per_view_data.buffer()
+                    && *byte_offset == per_view_data.descriptor_byte_offset()
+            })
+            .map(|(_, _, descriptor_set)| descriptor_set.clone());
+        if existing.is_some() {
+            return existing;
+        }
+
+        let mut descriptor_set_allocator = self.resource_context.create_descriptor_set_allocator();
+        let descriptor_set = descriptor_set_allocator
+            .create_descriptor_set_with_writer(
+                per_view_descriptor_set_layout,
+                shaders::debug_vert::DescriptorSet0Args {
+                    per_frame_data: per_view_data,
+                },
+            )
+            .ok()?;
+
+        per_view_descriptor_sets.push((
+            per_view_data.buffer().clone(),
+            per_view_data.descriptor_byte_offset(),
+            descriptor_set.clone(),
+        ));
+        Some(descriptor_set)
+    }
 }
 
 impl<'prepare> PrepareJobEntryPoints<'prepare> for Debug3DPrepareJob {
@@ -56,26 +96,13 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for Debug3DPrepareJob {
 
         // We would probably want to support multiple buffers at some point
 
-        let dyn_resource_allocator_set = self.resource_context.create_dyn_resource_allocator_set();
         per_frame_submit_data.vertex_buffer = if !per_frame_submit_data.draw_calls.is_empty() {
-            let vertex_buffer_size = per_frame_submit_data.vertex_list.len() as u64
-                * std::mem::size_of::<Debug3DVertex>() as u64;
-
-            let vertex_buffer = self
-                .device_context
-                .create_buffer(&RafxBufferDef {
-                    size: vertex_buffer_size,
-                    memory_usage: RafxMemoryUsage::CpuToGpu,
-                    resource_type: RafxResourceType::VERTEX_BUFFER,
-                    ..Default::default()
-                })
-                .unwrap();
-
-            vertex_buffer
-                .copy_to_host_visible_buffer(per_frame_submit_data.vertex_list.as_slice())
-                .unwrap();
-
-            Some(dyn_resource_allocator_set.insert_buffer(vertex_buffer))
+            Some(
+                self.resource_context
+                    .dyn_ring_buffer_allocator()
+                    .allocate(per_frame_submit_data.vertex_list.as_slice())
+                    .unwrap(),
+            )
         } else {
             None
         };
@@ -105,19 +132,19 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for Debug3DPrepareJob {
             view_proj: (view.projection_matrix() * view.view_matrix()).to_cols_array_2d(),
         };
 
-        let mut descriptor_set_allocator = self.resource_context.create_descriptor_set_allocator();
+        let per_view_data = self
+            .resource_context
+            .dyn_ring_buffer_allocator()
+            .allocate_uniform(&debug3d_view)
+            .unwrap();
+
         context
             .view_submit_packet()
             .per_view_submit_data()
             .set(Debug3DPerViewSubmitData {
-                descriptor_set_arc: descriptor_set_allocator
-                    .create_descriptor_set_with_writer(
-                        per_view_descriptor_set_layout,
-                        shaders::debug_vert::DescriptorSet0Args {
-                            per_frame_data: &debug3d_view,
-                        },
-                    )
-                    .ok(),
+                descriptor_set_arc: self
+                    .per_view_descriptor_set(per_view_descriptor_set_layout, &per_view_data),
+                dynamic_offset: per_view_data.dynamic_offset(),
             });
 
         context
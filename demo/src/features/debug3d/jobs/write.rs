@@ -93,13 +93,16 @@ impl<'write> RenderFeatureWriteJob<'write> for Debug3DWriteJob<'write> {
                     .descriptor_set_arc
                     .as_ref()
                     .unwrap()
-                    .bind(command_buffer)?;
+                    .bind_with_dynamic_offsets(
+                        command_buffer,
+                        &[per_view_submit_data.dynamic_offset],
+                    )?;
 
                 command_buffer.cmd_bind_vertex_buffers(
                     0,
                     &[RafxVertexBufferBinding {
-                        buffer: &*vertex_buffer.get_raw().buffer,
-                        byte_offset: 0,
+                        buffer: &*vertex_buffer.buffer().get_raw().buffer,
+                        byte_offset: vertex_buffer.byte_offset(),
                     }],
                 )?;
             }
@@ -0,0 +1,23 @@
+use crate::assets::environment_lighting::EnvironmentLightingAsset;
+use distill::loader::handle::Handle;
+use glam::Quat;
+
+// Image based lighting for lit meshes. When no environment is set (or it has not loaded yet), meshes
+// use a constant ambient light instead.
+pub struct MeshEnvironmentLightingResource {
+    pub environment_lighting: Option<Handle<EnvironmentLightingAsset>>,
+    pub intensity: f32,
+    // Rotates the environment into world space. The default matches the skybox, which maps the
+    // cubemap's +Y up axis to the demo's +Z up axis.
+    pub rotation: Quat,
+}
+
+impl Default for MeshEnvironmentLightingResource {
+    fn default() -> Self {
+        MeshEnvironmentLightingResource {
+            environment_lighting: None,
+            intensity: 1.0,
+            rotation: Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        }
+    }
+}
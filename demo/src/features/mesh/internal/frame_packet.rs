@@ -1,13 +1,16 @@
 use super::*;
+use crate::assets::environment_lighting::EnvironmentLightingAsset;
 use crate::assets::mesh::MeshAsset;
 use crate::components::{
     DirectionalLightComponent, PointLightComponent, SpotLightComponent, TransformComponent,
 };
 use glam::{Quat, Vec3};
+use rafx::assets::TextureStreamingFeedback;
 use rafx::framework::render_features::render_features_prelude::*;
 use rafx::framework::{
     BufferResource, DescriptorSetArc, ImageViewResource, MaterialPassResource, ResourceArc,
 };
+use std::sync::Arc;
 
 pub struct MeshRenderFeatureTypes;
 
@@ -19,6 +22,14 @@ pub type MeshRenderObjectStaticData = MeshRenderObject;
 
 pub struct MeshPerFrameData {
     pub depth_material_pass: Option<ResourceArc<MaterialPassResource>>,
+    pub environment_lighting: Option<ExtractedEnvironmentLighting>,
+    pub texture_streaming_feedback: TextureStreamingFeedback,
+}
+
+pub struct ExtractedEnvironmentLighting {
+    pub asset: EnvironmentLightingAsset,
+    pub intensity: f32,
+    pub rotation: Quat,
 }
 
 pub struct MeshRenderObjectInstanceData {
@@ -89,6 +100,9 @@ pub struct MeshPerFrameSubmitData {
         [shaders::mesh_textured_frag::ShadowMapCubeDataStd140; MAX_SHADOW_MAPS_CUBE],
     pub shadow_map_cube_image_views: [Option<ResourceArc<ImageViewResource>>; MAX_SHADOW_MAPS_CUBE],
     pub shadow_map_image_index_remap: [Option<usize>; MAX_SHADOW_MAPS_2D + MAX_SHADOW_MAPS_CUBE],
+    // Indexed by MeshRenderObjectInstanceSubmitData::model_matrix_offset. Batched draw calls gather
+    // the model matrices of their instances from here
+    pub model_matrices: Arc<AtomicOnceCellStack<[[f32; 4]; 4]>>,
     pub model_matrix_buffer: TrustCell<Option<ResourceArc<BufferResource>>>,
 }
 
@@ -13,6 +13,7 @@ pub struct MeshExtractJob<'extract> {
     world: ResourceRefBorrow<'extract, World>,
     asset_manager: AssetManagerExtractRef,
     depth_material: Handle<MaterialAsset>,
+    environment_lighting: ResourceRefBorrow<'extract, MeshEnvironmentLightingResource>,
     render_objects: MeshRenderObjectSet,
 }
 
@@ -31,6 +32,9 @@ impl<'extract> MeshExtractJob<'extract> {
                     .fetch::<AssetManagerRenderResource>()
                     .extract_ref(),
                 depth_material,
+                environment_lighting: extract_context
+                    .extract_resources
+                    .fetch::<MeshEnvironmentLightingResource>(),
                 render_objects,
             },
             frame_packet,
@@ -53,6 +57,17 @@ impl<'extract> ExtractJobEntryPoints<'extract> for MeshExtractJob<'extract> {
                     .unwrap()
                     .get_single_material_pass()
                     .ok(),
+                environment_lighting: self
+                    .environment_lighting
+                    .environment_lighting
+                    .as_ref()
+                    .and_then(|handle| self.asset_manager.committed_asset(handle))
+                    .map(|asset| ExtractedEnvironmentLighting {
+                        asset: asset.clone(),
+                        intensity: self.environment_lighting.intensity,
+                        rotation: self.environment_lighting.rotation,
+                    }),
+                texture_streaming_feedback: self.asset_manager.texture_streaming_feedback().clone(),
             });
     }
 
@@ -72,8 +87,17 @@ impl<'extract> ExtractJobEntryPoints<'extract> for MeshExtractJob<'extract> {
         context.set_render_object_instance_data(mesh_asset.and_then(|mesh_asset| {
             let entry = self.world.entry_ref(context.object_id().into()).unwrap();
             let transform_component = entry.get_component::<TransformComponent>().unwrap();
+
+            // Fetched every frame, the derived material instance is replaced when it reloads
+            let mesh_asset = match &render_object_static_data.material_override {
+                Some(material_override) => {
+                    mesh_asset.with_material_instance(&material_override.material_instance())
+                }
+                None => mesh_asset.clone(),
+            };
+
             Some(MeshRenderObjectInstanceData {
-                mesh_asset: mesh_asset.clone(),
+                mesh_asset,
                 translation: transform_component.translation,
                 rotation: transform_component.rotation,
                 scale: transform_component.scale,
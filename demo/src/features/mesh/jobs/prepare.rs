@@ -7,20 +7,68 @@ use crate::components::{
 use crate::phases::{
     DepthPrepassRenderPhase, OpaqueRenderPhase, ShadowMapRenderPhase, WireframeRenderPhase,
 };
+use rafx::assets::TextureStreamingFeedback;
 use rafx::base::resource_map::ReadBorrow;
 use rafx::framework::{MaterialPassResource, ResourceArc, ResourceContext};
 
+use crate::assets::mesh::MeshAsset;
+use fnv::FnvHasher;
 use glam::Mat4;
 use rafx::api::{RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResourceType};
+use rafx::render_features::SubmitNodeSortKeyBuilder;
 use rafx::renderer::InvalidResources;
 use shaders::depth_vert::PerViewDataUniform as ShadowPerViewShaderParam;
 use shaders::mesh_textured_frag::PerViewDataUniform as MeshPerViewFragmentShaderParam;
+use std::hash::{Hash, Hasher};
 
 const PER_VIEW_DESCRIPTOR_SET_INDEX: u32 =
     shaders::mesh_textured_frag::PER_VIEW_DATA_DESCRIPTOR_SET_INDEX as u32;
 const PER_MATERIAL_DESCRIPTOR_SET_INDEX: u32 =
     shaders::mesh_textured_frag::PER_MATERIAL_DATA_DESCRIPTOR_SET_INDEX as u32;
 
+// Draw calls with the same batch key bind the same pipeline, vertex and index buffers, and
+// descriptor sets, so consecutive ones can be drawn as one instanced draw call. The key is a hash,
+// so MeshWriteJob::render_submit_node_batch compares the draw calls before drawing them instanced.
+fn mesh_draw_call_batch_key(
+    draw_call: &MeshDrawCall,
+    mesh_asset: &MeshAsset,
+) -> SubmitNodeBatchKey {
+    let mut hasher = FnvHasher::default();
+    draw_call.material_pass_resource.hash(&mut hasher);
+    draw_call.per_material_descriptor_set.hash(&mut hasher);
+    Arc::as_ptr(&mesh_asset.inner).hash(&mut hasher);
+    draw_call.mesh_part_index.hash(&mut hasher);
+    hasher.finish()
+}
+
+// How many pixels a unit of distance covers on screen at the point of the mesh's bounds nearest to
+// the view
+fn projected_pixels_per_world_unit(
+    view: &RenderView,
+    extracted_data: &MeshRenderObjectInstanceData,
+) -> f32 {
+    let projection = view.projection_matrix();
+    let pixels_per_world_unit = projection.y_axis.y.abs() * view.extents_height() as f32 * 0.5;
+
+    // Orthographic projections don't shrink with distance
+    if projection.w_axis.w != 0.0 {
+        return pixels_per_world_unit;
+    }
+
+    let bounding_sphere = &extracted_data
+        .mesh_asset
+        .inner
+        .asset_data
+        .visible_bounds
+        .bounding_sphere;
+    let center = extracted_data.translation
+        + extracted_data.rotation * (extracted_data.scale * bounding_sphere.position);
+    let radius = bounding_sphere.radius * extracted_data.scale.max_element();
+    let distance = ((view.eye_position() - center).length() - radius).max(0.01);
+
+    pixels_per_world_unit / distance
+}
+
 struct PreparedDirectionalLight<'a> {
     light: &'a DirectionalLightComponent,
     shadow_map_index: Option<usize>,
@@ -48,6 +96,7 @@ pub struct MeshPrepareJob<'prepare> {
     invalid_resources: ReadBorrow<'prepare, InvalidResources>,
     render_object_instance_transforms: Arc<AtomicOnceCellStack<[[f32; 4]; 4]>>,
     render_objects: MeshRenderObjectSet,
+    texture_streaming_feedback: TextureStreamingFeedback,
 }
 
 impl<'prepare> MeshPrepareJob<'prepare> {
@@ -88,6 +137,13 @@ impl<'prepare> MeshPrepareJob<'prepare> {
                         .depth_material_pass
                         .clone()
                 },
+                texture_streaming_feedback: {
+                    frame_packet
+                        .per_frame_data()
+                        .get()
+                        .texture_streaming_feedback
+                        .clone()
+                },
                 shadow_map_data: {
                     prepare_context
                         .render_resources
@@ -117,6 +173,7 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
             shadow_map_cube_data: Default::default(),
             shadow_map_cube_image_views: Default::default(),
             shadow_map_image_index_remap: [None; MAX_SHADOW_MAPS_CUBE + MAX_SHADOW_MAPS_2D],
+            model_matrices: self.render_object_instance_transforms.clone(),
             model_matrix_buffer: Default::default(),
         });
 
@@ -250,105 +307,136 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
                 .render_object_instance_submit_data()
                 .model_matrix_offset;
 
-            for (mesh_part_index, mesh_part) in extracted_data
-                .mesh_asset
-                .inner
-                .mesh_parts
-                .iter()
-                .enumerate()
-            {
+            let mesh_asset = &extracted_data.mesh_asset;
+
+            // Streamed textures are loaded at the resolution the opaque pass draws them at
+            let pixels_per_world_unit = if view.phase_is_relevant::<OpaqueRenderPhase>() {
+                Some(projected_pixels_per_world_unit(view, extracted_data))
+            } else {
+                None
+            };
+
+            for (mesh_part_index, mesh_part) in mesh_asset.inner.mesh_parts.iter().enumerate() {
                 if mesh_part.is_none() {
                     continue;
                 }
 
                 let mesh_part = mesh_part.as_ref().unwrap();
 
+                if let Some(pixels_per_world_unit) = pixels_per_world_unit {
+                    // Without a UV density, assume the texture is stretched across the mesh once
+                    let uv_density = if mesh_part.uv_density > 0.0 {
+                        mesh_part.uv_density
+                    } else {
+                        0.5 / mesh_asset
+                            .inner
+                            .asset_data
+                            .visible_bounds
+                            .bounding_sphere
+                            .radius
+                            .max(f32::EPSILON)
+                    };
+
+                    let world_units_per_uv = extracted_data.scale.max_element() / uv_density;
+                    self.texture_streaming_feedback.request_material_instance(
+                        &mesh_part.material_instance,
+                        pixels_per_world_unit * world_units_per_uv,
+                    );
+                }
+
                 let depth_material_pass = self.depth_material_pass.as_ref().unwrap();
 
                 if view.phase_is_relevant::<DepthPrepassRenderPhase>() {
-                    context.push_submit_node::<DepthPrepassRenderPhase>(
-                        MeshDrawCall {
-                            render_object_instance_id,
-                            material_pass_resource: depth_material_pass.clone(),
-                            per_material_descriptor_set: None,
-                            mesh_part_index,
-                            model_matrix_offset,
-                        },
-                        0,
-                        distance,
+                    let draw_call = MeshDrawCall {
+                        render_object_instance_id,
+                        material_pass_resource: depth_material_pass.clone(),
+                        per_material_descriptor_set: None,
+                        mesh_part_index,
+                        model_matrix_offset,
+                    };
+
+                    let batch_key = mesh_draw_call_batch_key(&draw_call, mesh_asset);
+                    context.push_batchable_submit_node::<DepthPrepassRenderPhase>(
+                        draw_call, 0, distance, batch_key,
                     );
                 }
 
                 if view.phase_is_relevant::<ShadowMapRenderPhase>() {
-                    context.push_submit_node::<ShadowMapRenderPhase>(
-                        MeshDrawCall {
-                            render_object_instance_id,
-                            material_pass_resource: depth_material_pass.clone(),
-                            per_material_descriptor_set: None,
-                            mesh_part_index,
-                            model_matrix_offset,
-                        },
-                        0,
-                        distance,
+                    let draw_call = MeshDrawCall {
+                        render_object_instance_id,
+                        material_pass_resource: depth_material_pass.clone(),
+                        per_material_descriptor_set: None,
+                        mesh_part_index,
+                        model_matrix_offset,
+                    };
+
+                    let batch_key = mesh_draw_call_batch_key(&draw_call, mesh_asset);
+                    context.push_batchable_submit_node::<ShadowMapRenderPhase>(
+                        draw_call, 0, distance, batch_key,
                     );
                 }
 
-                if view.phase_is_relevant::<OpaqueRenderPhase>() {
-                    let material_pass_resource = mesh_part
+                let opaque_material_pass_resource = if view.phase_is_relevant::<OpaqueRenderPhase>()
+                {
+                    mesh_part
                         .get_material_pass_resource(view, OpaqueRenderPhase::render_phase_index())
-                        .clone();
-
-                    let per_material_descriptor_set = Some(
-                        mesh_part
-                            .get_material_descriptor_set(
-                                view,
-                                OpaqueRenderPhase::render_phase_index(),
-                            )
-                            .clone(),
-                    );
+                } else {
+                    None
+                };
+
+                if let Some(material_pass_resource) = opaque_material_pass_resource {
+                    let material_pass_resource = material_pass_resource.clone();
+                    let per_material_descriptor_set = mesh_part
+                        .get_material_descriptor_set(view, OpaqueRenderPhase::render_phase_index());
+
+                    let draw_call = MeshDrawCall {
+                        render_object_instance_id,
+                        material_pass_resource,
+                        per_material_descriptor_set,
+                        mesh_part_index,
+                        model_matrix_offset,
+                    };
 
-                    context.push_submit_node::<OpaqueRenderPhase>(
-                        MeshDrawCall {
-                            render_object_instance_id,
-                            material_pass_resource,
-                            per_material_descriptor_set,
-                            mesh_part_index,
-                            model_matrix_offset,
-                        },
-                        0,
-                        distance,
+                    let sort_key = SubmitNodeSortKeyBuilder::new()
+                        .pipeline(&draw_call.material_pass_resource)
+                        .material(&draw_call.per_material_descriptor_set)
+                        .feature(super::render_feature_index())
+                        .build_state_sorted();
+
+                    let batch_key = mesh_draw_call_batch_key(&draw_call, mesh_asset);
+                    context.push_batchable_submit_node::<OpaqueRenderPhase>(
+                        draw_call, sort_key, distance, batch_key,
                     );
                 }
 
-                if view.phase_is_relevant::<WireframeRenderPhase>()
+                let wireframe_material_pass_resource = if view
+                    .phase_is_relevant::<WireframeRenderPhase>()
                     && view.feature_flag_is_relevant::<MeshWireframeRenderFeatureFlag>()
                 {
-                    let material_pass_resource = mesh_part
-                        .get_material_pass_resource(
-                            view,
-                            WireframeRenderPhase::render_phase_index(),
-                        )
-                        .clone();
-
-                    let per_material_descriptor_set = Some(
-                        mesh_part
-                            .get_material_descriptor_set(
-                                view,
-                                OpaqueRenderPhase::render_phase_index(),
-                            )
-                            .clone(),
-                    );
+                    mesh_part.get_material_pass_resource(
+                        view,
+                        WireframeRenderPhase::render_phase_index(),
+                    )
+                } else {
+                    None
+                };
 
-                    context.push_submit_node::<WireframeRenderPhase>(
-                        MeshDrawCall {
-                            render_object_instance_id,
-                            material_pass_resource,
-                            per_material_descriptor_set,
-                            mesh_part_index,
-                            model_matrix_offset,
-                        },
-                        0,
-                        distance,
+                if let Some(material_pass_resource) = wireframe_material_pass_resource {
+                    let material_pass_resource = material_pass_resource.clone();
+                    let per_material_descriptor_set = mesh_part
+                        .get_material_descriptor_set(view, OpaqueRenderPhase::render_phase_index());
+
+                    let draw_call = MeshDrawCall {
+                        render_object_instance_id,
+                        material_pass_resource,
+                        per_material_descriptor_set,
+                        mesh_part_index,
+                        model_matrix_offset,
+                    };
+
+                    let batch_key = mesh_draw_call_batch_key(&draw_call, mesh_asset);
+                    context.push_batchable_submit_node::<WireframeRenderPhase>(
+                        draw_call, 0, distance, batch_key,
                     );
                 }
             }
@@ -368,6 +456,7 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
         let view = context.view();
         let is_lit = !view.feature_flag_is_relevant::<MeshUnlitRenderFeatureFlag>();
         let has_shadows = !view.feature_flag_is_relevant::<MeshNoShadowsRenderFeatureFlag>();
+        let environment_lighting = context.per_frame_data().environment_lighting.as_ref();
 
         let opaque_descriptor_set = if view.phase_is_relevant::<OpaqueRenderPhase>() {
             let per_view_frag_data = {
@@ -505,9 +594,35 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
                 per_view_frag_data.shadow_map_cube_data =
                     per_frame_submit_data.shadow_map_cube_data;
 
+                if let Some(environment_lighting) = environment_lighting.filter(|_| is_lit) {
+                    let environment_from_view = (view.view_matrix()
+                        * Mat4::from_quat(environment_lighting.rotation))
+                    .inverse();
+                    per_view_frag_data.environment_from_view =
+                        environment_from_view.to_cols_array_2d();
+                    per_view_frag_data.irradiance_sh =
+                        environment_lighting.asset.inner.irradiance_sh;
+                    per_view_frag_data.environment_intensity = environment_lighting.intensity;
+                    per_view_frag_data.specular_mip_count =
+                        environment_lighting.asset.inner.specular_mip_count;
+                    per_view_frag_data.has_environment_lighting = 1;
+                }
+
                 per_view_frag_data
             };
 
+            // The bindings must be populated even if the view doesn't use environment lighting
+            let (environment_specular, environment_brdf_lut) = match environment_lighting {
+                Some(environment_lighting) => (
+                    &environment_lighting.asset.inner.specular_image_view,
+                    &environment_lighting.asset.inner.brdf_lut_image_view,
+                ),
+                None => (
+                    &self.invalid_resources.invalid_cube_map_image_color,
+                    &self.invalid_resources.invalid_image_color,
+                ),
+            };
+
             let shadow_map_images = &mut [None; MAX_SHADOW_MAPS_2D];
             for index in 0..MAX_SHADOW_MAPS_2D {
                 let image_view = per_frame_submit_data.shadow_map_2d_image_views[index]
@@ -533,15 +648,16 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
                     if let Some(extracted_data) = context.render_object_instances_data().get(id) {
                         let mesh_parts = &extracted_data.mesh_asset.inner.mesh_parts;
                         for mesh_part in mesh_parts {
-                            if let Some(mesh_part) = mesh_part {
+                            let material_pass_resource = mesh_part.as_ref().and_then(|mesh_part| {
+                                mesh_part.get_material_pass_resource(
+                                    view,
+                                    OpaqueRenderPhase::render_phase_index(),
+                                )
+                            });
+
+                            if let Some(material_pass_resource) = material_pass_resource {
                                 opaque_per_view_descriptor_set_layout = Some(
-                                    mesh_part
-                                        .get_material_pass_resource(
-                                            view,
-                                            OpaqueRenderPhase::render_phase_index(),
-                                        )
-                                        .get_raw()
-                                        .descriptor_set_layouts
+                                    material_pass_resource.get_raw().descriptor_set_layouts
                                         [PER_VIEW_DESCRIPTOR_SET_INDEX as usize]
                                         .clone(),
                                 );
@@ -566,6 +682,8 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
                             shaders::mesh_textured_frag::DescriptorSet0Args {
                                 shadow_map_images,
                                 shadow_map_images_cube,
+                                environment_specular,
+                                environment_brdf_lut,
                                 per_view_data: &per_view_frag_data,
                             },
                         )
@@ -612,10 +730,8 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
         &self,
         context: &PreparePerFrameContext<'prepare, '_, Self>,
     ) {
-        let mut model_matrix_buffer = context
-            .per_frame_submit_data()
-            .model_matrix_buffer
-            .borrow_mut();
+        let per_frame_submit_data = context.per_frame_submit_data();
+        let mut model_matrix_buffer = per_frame_submit_data.model_matrix_buffer.borrow_mut();
 
         *model_matrix_buffer = if self.render_object_instance_transforms.len() > 0 {
             let dyn_resource_allocator_set =
@@ -637,7 +753,9 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for MeshPrepareJob<'prepare> {
             // TODO(dvd): Get rid of this copy.
             let mut data = Vec::with_capacity(self.render_object_instance_transforms.len());
             for ii in 0..data.capacity() {
-                data.push(self.render_object_instance_transforms.get(ii).clone());
+                data.push(MeshModelMatrix {
+                    model_matrix: self.render_object_instance_transforms.get(ii).clone(),
+                });
             }
 
             vertex_buffer
@@ -4,7 +4,7 @@ use super::*;
 use crate::phases::{DepthPrepassRenderPhase, ShadowMapRenderPhase, WireframeRenderPhase};
 use rafx::api::RafxPrimitiveTopology;
 use rafx::api::{RafxIndexBufferBinding, RafxVertexAttributeRate, RafxVertexBufferBinding};
-use rafx::framework::{MaterialPassResource, ResourceArc};
+use rafx::framework::{BufferResource, MaterialPassResource, ResourceArc};
 use rafx::framework::{VertexDataLayout, VertexDataSetLayout};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
@@ -87,23 +87,42 @@ impl<'write> MeshWriteJob<'write> {
     }
 }
 
-impl<'write> RenderFeatureWriteJob<'write> for MeshWriteJob<'write> {
-    fn view_frame_index(
+impl<'write> MeshWriteJob<'write> {
+    // True if both draw calls bind the same pipeline, buffers and descriptor sets
+    fn can_draw_instanced(
         &self,
-        view: &RenderView,
-    ) -> u32 {
-        self.frame_packet.view_frame_index(view)
+        lhs: &MeshDrawCall,
+        rhs: &MeshDrawCall,
+    ) -> bool {
+        let render_object_instances_data = self.frame_packet.render_object_instances_data();
+        let lhs_mesh_asset = &render_object_instances_data
+            .get(lhs.render_object_instance_id as usize)
+            .as_ref()
+            .unwrap()
+            .mesh_asset;
+        let rhs_mesh_asset = &render_object_instances_data
+            .get(rhs.render_object_instance_id as usize)
+            .as_ref()
+            .unwrap()
+            .mesh_asset;
+
+        lhs.material_pass_resource == rhs.material_pass_resource
+            && lhs.per_material_descriptor_set == rhs.per_material_descriptor_set
+            && lhs.mesh_part_index == rhs.mesh_part_index
+            && Arc::ptr_eq(&lhs_mesh_asset.inner, &rhs_mesh_asset.inner)
     }
 
-    fn render_submit_node(
+    // Draws the mesh part referenced by the submit node once per model matrix in the instance buffer
+    fn draw_mesh_part(
         &self,
         write_context: &mut RenderJobCommandBufferContext,
         view_frame_index: ViewFrameIndex,
         render_phase_index: RenderPhaseIndex,
-        submit_node_id: SubmitNodeId,
+        submit_node_data: &MeshDrawCall,
+        instance_buffer: &ResourceArc<BufferResource>,
+        instance_buffer_byte_offset: u64,
+        instance_count: u32,
     ) -> RafxResult<()> {
-        profiling::scope!(super::render_feature_debug_constants().render_submit_node);
-
         let is_wireframe = render_phase_index == self.wireframe_index;
         let is_depth_render_phase = render_phase_index == self.depth_prepass_index
             || render_phase_index == self.shadow_map_index;
@@ -112,16 +131,6 @@ impl<'write> RenderFeatureWriteJob<'write> for MeshWriteJob<'write> {
 
         let command_buffer = &write_context.command_buffer;
 
-        let model_matrix_buffer = self
-            .submit_packet
-            .per_frame_submit_data()
-            .get()
-            .model_matrix_buffer
-            .borrow();
-
-        let submit_node_data = view_submit_packet
-            .get_submit_node_data_from_render_phase(render_phase_index, submit_node_id);
-
         let render_object_instance = self
             .frame_packet
             .render_object_instances_data()
@@ -186,10 +195,8 @@ impl<'write> RenderFeatureWriteJob<'write> for MeshWriteJob<'write> {
                 // well supported on rafx backends. A third option would be some type of dynamic
                 // uniform buffer, but we'd still need to pass in an index to the shader for each instance.
                 RafxVertexBufferBinding {
-                    buffer: &model_matrix_buffer.as_ref().unwrap().get_raw().buffer,
-                    byte_offset: (std::mem::size_of::<MeshModelMatrix>()
-                        * submit_node_data.model_matrix_offset)
-                        as u64,
+                    buffer: &instance_buffer.get_raw().buffer,
+                    byte_offset: instance_buffer_byte_offset,
                 },
             ],
         )?;
@@ -200,14 +207,125 @@ impl<'write> RenderFeatureWriteJob<'write> for MeshWriteJob<'write> {
             index_type: mesh_part.index_type,
         })?;
 
-        command_buffer.cmd_draw_indexed(
+        command_buffer.cmd_draw_indexed_instanced(
             mesh_part.index_buffer_size_in_bytes / 2, //sizeof(u16)
             0,
+            instance_count,
+            0,
             0,
         )?;
 
         Ok(())
     }
+}
+
+impl<'write> RenderFeatureWriteJob<'write> for MeshWriteJob<'write> {
+    fn view_frame_index(
+        &self,
+        view: &RenderView,
+    ) -> u32 {
+        self.frame_packet.view_frame_index(view)
+    }
+
+    fn render_submit_node(
+        &self,
+        write_context: &mut RenderJobCommandBufferContext,
+        view_frame_index: ViewFrameIndex,
+        render_phase_index: RenderPhaseIndex,
+        submit_node_id: SubmitNodeId,
+    ) -> RafxResult<()> {
+        profiling::scope!(super::render_feature_debug_constants().render_submit_node);
+
+        let view_submit_packet = self.submit_packet.view_submit_packet(view_frame_index);
+
+        let model_matrix_buffer = self
+            .submit_packet
+            .per_frame_submit_data()
+            .get()
+            .model_matrix_buffer
+            .borrow();
+
+        let submit_node_data = view_submit_packet
+            .get_submit_node_data_from_render_phase(render_phase_index, submit_node_id);
+
+        self.draw_mesh_part(
+            write_context,
+            view_frame_index,
+            render_phase_index,
+            submit_node_data,
+            model_matrix_buffer.as_ref().unwrap(),
+            (std::mem::size_of::<MeshModelMatrix>() * submit_node_data.model_matrix_offset) as u64,
+            1,
+        )
+    }
+
+    fn render_submit_node_batch(
+        &self,
+        write_context: &mut RenderJobCommandBufferContext,
+        view_frame_index: ViewFrameIndex,
+        render_phase_index: RenderPhaseIndex,
+        submit_nodes: &[RenderFeatureSubmitNode],
+    ) -> RafxResult<()> {
+        profiling::scope!(super::render_feature_debug_constants().render_submit_node);
+
+        let view_submit_packet = self.submit_packet.view_submit_packet(view_frame_index);
+        let model_matrices = &self
+            .submit_packet
+            .per_frame_submit_data()
+            .get()
+            .model_matrices;
+
+        // Batch keys are hashes, so confirm that the submit nodes really do share everything except
+        // their model matrix. Each run of matching nodes is drawn with one instanced draw call.
+        let mut first_index = 0;
+        while first_index < submit_nodes.len() {
+            let first_data = view_submit_packet.get_submit_node_data_from_render_phase(
+                render_phase_index,
+                submit_nodes[first_index].submit_node_id(),
+            );
+
+            let run_length = submit_nodes[first_index..]
+                .iter()
+                .take_while(|submit_node| {
+                    let submit_node_data = view_submit_packet
+                        .get_submit_node_data_from_render_phase(
+                            render_phase_index,
+                            submit_node.submit_node_id(),
+                        );
+                    self.can_draw_instanced(first_data, submit_node_data)
+                })
+                .count();
+
+            let run = &submit_nodes[first_index..first_index + run_length];
+            let instance_allocation = write_context
+                .resource_context
+                .dyn_ring_buffer_allocator()
+                .allocate_from_iter(run.iter().map(|submit_node| {
+                    let submit_node_data = view_submit_packet
+                        .get_submit_node_data_from_render_phase(
+                            render_phase_index,
+                            submit_node.submit_node_id(),
+                        );
+                    MeshModelMatrix {
+                        model_matrix: *model_matrices.get(submit_node_data.model_matrix_offset),
+                    }
+                }))?;
+
+            self.draw_mesh_part(
+                write_context,
+                view_frame_index,
+                render_phase_index,
+                first_data,
+                instance_allocation.buffer(),
+                instance_allocation.byte_offset(),
+                run_length as u32,
+            )?;
+
+            first_index += run_length;
+        }
+
+        Ok(())
+    }
 
     fn feature_debug_constants(&self) -> &'static RenderFeatureDebugConstants {
         super::render_feature_debug_constants()
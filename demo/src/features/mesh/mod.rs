@@ -19,6 +19,13 @@ rafx::declare_render_feature_flag!(MeshUnlitRenderFeatureFlag, MESH_UNLIT_FLAG_I
 // Ignores shadows when the `MeshNoShadowsRenderFeatureFlag` is registered on the view.
 rafx::declare_render_feature_flag!(MeshNoShadowsRenderFeatureFlag, MESH_NO_SHADOWS_FLAG_INDEX);
 
+// Selects the untextured variant of the opaque mesh pass. Enabled by `MeshUntexturedRenderFeatureFlag`.
+pub const MESH_UNTEXTURED_KEYWORD: &str = "UNTEXTURED";
+
+// Selects the variant of the opaque mesh pass that writes depth. Enabled per mesh part when the view
+// does not have a depth prepass.
+pub const MESH_DEPTH_WRITE_KEYWORD: &str = "DEPTH_WRITE";
+
 // Public API
 
 mod plugin;
@@ -31,3 +38,6 @@ pub use render_object::*;
 
 mod shadow_map_resource;
 pub use shadow_map_resource::*;
+
+mod environment_lighting_resource;
+pub use environment_lighting_resource::*;
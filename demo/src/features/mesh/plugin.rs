@@ -30,10 +30,12 @@ impl MeshRendererPlugin {
         resources: &mut legion::Resources,
     ) {
         resources.insert(self.render_objects.clone());
+        resources.insert(MeshEnvironmentLightingResource::default());
     }
 
     pub fn legion_destroy(resources: &mut legion::Resources) {
         resources.remove::<MeshRenderObjectSet>();
+        resources.remove::<MeshEnvironmentLightingResource>();
     }
 }
 
@@ -71,6 +73,10 @@ impl RenderFeaturePlugin for MeshRendererPlugin {
             .register_feature_flag::<MeshUntexturedRenderFeatureFlag>()
             .register_feature_flag::<MeshUnlitRenderFeatureFlag>()
             .register_feature_flag::<MeshNoShadowsRenderFeatureFlag>()
+            .register_feature_flag_keyword::<MeshUntexturedRenderFeatureFlag>(
+                MESH_UNTEXTURED_KEYWORD,
+            )
+            .register_shader_keyword(MESH_DEPTH_WRITE_KEYWORD)
     }
 
     fn initialize_static_resources(
@@ -1,11 +1,14 @@
 use super::MeshRenderFeature;
 use crate::assets::mesh::MeshAsset;
 use distill::loader::handle::Handle;
+use rafx::assets::DerivedMaterialInstance;
 use rafx::render_features::RenderObjectSet;
 
 #[derive(Clone)]
 pub struct MeshRenderObject {
     pub mesh: Handle<MeshAsset>,
+    // Draws every part of the mesh with this material instance instead of its own
+    pub material_override: Option<DerivedMaterialInstance>,
 }
 
 pub type MeshRenderObjectSet = RenderObjectSet<MeshRenderFeature, MeshRenderObject>;
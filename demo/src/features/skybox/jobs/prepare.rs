@@ -3,6 +3,7 @@ use rafx::render_feature_prepare_job_predule::*;
 use super::*;
 use crate::phases::OpaqueRenderPhase;
 use rafx::framework::ResourceContext;
+use rafx::render_features::SubmitNodeSortKeyBuilder;
 
 pub struct SkyboxPrepareJob {
     resource_context: ResourceContext,
@@ -66,9 +67,16 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for SkyboxPrepareJob {
                             .ok(),
                     });
 
+                // Draw the skybox after the rest of the opaque geometry so that covered pixels fail
+                // the depth test
+                let sort_key = SubmitNodeSortKeyBuilder::new()
+                    .layer(1)
+                    .feature(super::render_feature_index())
+                    .build_state_sorted();
+
                 context
                     .view_submit_packet()
-                    .push_submit_node::<OpaqueRenderPhase>((), 0, 0.);
+                    .push_submit_node::<OpaqueRenderPhase>((), sort_key, 0.);
             }
         }
     }
@@ -6,6 +6,7 @@ use fnv::FnvHashMap;
 use rafx::api::{RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResourceType};
 use rafx::base::DecimalF32;
 use rafx::framework::{ImageViewResource, ResourceArc, ResourceContext};
+use rafx::render_features::SubmitNodeSortKeyBuilder;
 use std::sync::atomic::{AtomicU32, Ordering};
 
 /// Used as static data to represent a quad
@@ -289,6 +290,11 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for SpritePrepareJob {
                             index_count,
                         };
 
+                        let sort_key = SubmitNodeSortKeyBuilder::new()
+                            .material(&batch_index)
+                            .feature(super::render_feature_index())
+                            .build_state_sorted();
+
                         let (render_phase_index, distance) = if sprite.color.w >= 1.0 {
                             // non-transparent can just batch by material
                             (OpaqueRenderPhase::render_phase_index(), 0.)
@@ -303,7 +309,7 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for SpritePrepareJob {
                             view_submit_packet.push_submit_node_into_render_phase(
                                 render_phase_index,
                                 submit_node_data,
-                                batch_index,
+                                sort_key,
                                 distance,
                             ),
                         ));
@@ -3,7 +3,9 @@ use crate::assets::font::FontAsset;
 use distill::loader::LoadHandle;
 use fnv::FnvHashMap;
 use rafx::framework::render_features::render_features_prelude::*;
-use rafx::framework::{BufferResource, DescriptorSetArc, MaterialPassResource, ResourceArc};
+use rafx::framework::{
+    DescriptorSetArc, DynRingBufferAllocation, MaterialPassResource, ResourceArc,
+};
 
 pub struct TextRenderFeatureTypes;
 
@@ -17,6 +19,8 @@ pub struct TextPerFrameData {
     pub text_material_pass: Option<ResourceArc<MaterialPassResource>>,
     pub text_draw_commands: Vec<TextDrawCommand>,
     pub font_assets: FnvHashMap<LoadHandle, FontAsset>,
+    // Text is positioned in window pixels but the main view is rendered at this scale
+    pub render_scale: f32,
 }
 
 impl FramePacketData for TextRenderFeatureTypes {
@@ -69,8 +73,8 @@ pub struct TextDrawCallMeta {
 }
 
 pub struct TextDrawCallBuffers {
-    pub vertex_buffer: ResourceArc<BufferResource>,
-    pub index_buffer: ResourceArc<BufferResource>,
+    pub vertex_buffer: DynRingBufferAllocation,
+    pub index_buffer: DynRingBufferAllocation,
 }
 
 pub struct TextPerViewSubmitData {
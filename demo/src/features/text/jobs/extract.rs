@@ -5,11 +5,13 @@ use fnv::FnvHashMap;
 use rafx::assets::{AssetManagerExtractRef, AssetManagerRenderResource, MaterialAsset};
 use rafx::base::resource_ref_map::ResourceRefBorrowMut;
 use rafx::distill::loader::handle::Handle;
+use rafx::renderer::RendererConfigResource;
 
 pub struct TextExtractJob<'extract> {
     text_resource: TrustCell<ResourceRefBorrowMut<'extract, TextResource>>,
     asset_manager: AssetManagerExtractRef,
     text_material: Handle<MaterialAsset>,
+    render_scale: f32,
 }
 
 impl<'extract> TextExtractJob<'extract> {
@@ -30,6 +32,11 @@ impl<'extract> TextExtractJob<'extract> {
                     .fetch::<AssetManagerRenderResource>()
                     .extract_ref(),
                 text_material,
+                render_scale: extract_context
+                    .extract_resources
+                    .try_fetch::<RendererConfigResource>()
+                    .map(|x| x.clamped_render_scale())
+                    .unwrap_or(1.0),
             },
             frame_packet,
         ))
@@ -62,6 +69,7 @@ impl<'extract> ExtractJobEntryPoints<'extract> for TextExtractJob<'extract> {
                     .ok(),
                 text_draw_commands: text_draw_data.text_draw_commands,
                 font_assets,
+                render_scale: self.render_scale,
             })
     }
 
@@ -2,14 +2,12 @@ use rafx::render_feature_prepare_job_predule::*;
 
 use super::*;
 use crate::phases::UiRenderPhase;
-use rafx::api::{RafxBufferDef, RafxDeviceContext};
 use rafx::base::resource_map::WriteBorrow;
 use rafx::framework::ResourceContext;
 
 pub struct TextPrepareJob<'prepare> {
     font_atlas_cache: TrustCell<WriteBorrow<'prepare, FontAtlasCache>>,
     resource_context: ResourceContext,
-    device_context: RafxDeviceContext,
 }
 
 impl<'prepare> TextPrepareJob<'prepare> {
@@ -28,7 +26,6 @@ impl<'prepare> TextPrepareJob<'prepare> {
                             .fetch_mut::<FontAtlasCache>(),
                     )
                 },
-                device_context: prepare_context.device_context.clone(),
             },
             frame_packet,
             submit_packet,
@@ -91,33 +88,16 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for TextPrepareJob<'prepare> {
         // Update the vertex buffers
         //
 
+        let dyn_ring_buffer_allocator = self.resource_context.dyn_ring_buffer_allocator();
         for draw_call in draw_vertices_result.draw_call_buffer_data {
-            let vertex_buffer = self
-                .device_context
-                .create_buffer(&RafxBufferDef::for_staging_vertex_buffer_data(
-                    &draw_call.vertices,
-                ))
+            let vertex_buffer = dyn_ring_buffer_allocator
+                .allocate(draw_call.vertices.as_slice())
                 .unwrap();
 
-            vertex_buffer
-                .copy_to_host_visible_buffer(draw_call.vertices.as_slice())
+            let index_buffer = dyn_ring_buffer_allocator
+                .allocate(draw_call.indices.as_slice())
                 .unwrap();
 
-            let vertex_buffer = dyn_resource_allocator_set.insert_buffer(vertex_buffer);
-
-            let index_buffer = self
-                .device_context
-                .create_buffer(&RafxBufferDef::for_staging_index_buffer_data(
-                    &draw_call.indices,
-                ))
-                .unwrap();
-
-            index_buffer
-                .copy_to_host_visible_buffer(draw_call.indices.as_slice())
-                .unwrap();
-
-            let index_buffer = dyn_resource_allocator_set.insert_buffer(index_buffer);
-
             per_frame_submit_data
                 .draw_call_buffers
                 .push(TextDrawCallBuffers {
@@ -149,11 +129,12 @@ impl<'prepare> PrepareJobEntryPoints<'prepare> for TextPrepareJob<'prepare> {
         // Setup the vertex shader descriptor set
         //
 
+        // The UI pass is drawn after the main view is upscaled, so use the window size
         let view = context.view();
         let proj = glam::Mat4::orthographic_rh(
             0.0,
-            view.extents_width() as f32,
-            view.extents_height() as f32,
+            view.extents_width() as f32 / per_frame_data.render_scale,
+            view.extents_height() as f32 / per_frame_data.render_scale,
             0.0,
             -1000.0,
             100.0,
@@ -209,15 +209,15 @@ impl<'write> RenderFeatureWriteJob<'write> for TextWriteJob<'write> {
         command_buffer.cmd_bind_vertex_buffers(
             0,
             &[RafxVertexBufferBinding {
-                buffer: &buffers.vertex_buffer.get_raw().buffer,
-                byte_offset: 0,
+                buffer: &buffers.vertex_buffer.buffer().get_raw().buffer,
+                byte_offset: buffers.vertex_buffer.byte_offset(),
             }],
         )?;
 
         command_buffer.cmd_bind_index_buffer(&RafxIndexBufferBinding {
-            buffer: &buffers.index_buffer.get_raw().buffer,
+            buffer: &buffers.index_buffer.buffer().get_raw().buffer,
             index_type: RafxIndexType::Uint16,
-            byte_offset: 0,
+            byte_offset: buffers.index_buffer.byte_offset(),
         })?;
 
         per_frame_submit_data.per_font_descriptor_sets[draw_call.font_descriptor_index as usize]
@@ -1,3 +1,4 @@
+use crate::assets::environment_lighting::EnvironmentLightingAssetTypeRendererPlugin;
 use crate::assets::font::FontAssetTypeRendererPlugin;
 use crate::assets::ldtk::LdtkAssetTypeRendererPlugin;
 use crate::assets::mesh::GltfAssetTypeRendererPlugin;
@@ -12,7 +13,7 @@ use crate::DemoRendererPlugin;
 use legion::Resources;
 use rafx::api::{RafxApi, RafxDeviceContext, RafxResult, RafxSwapchainHelper};
 use rafx::assets::distill_impl::AssetResource;
-use rafx::assets::AssetManager;
+use rafx::assets::{AssetManager, TextureStreamingConfig};
 use rafx::framework::visibility::VisibilityRegion;
 use rafx::render_features::{ExtractResources, RenderRegistry};
 use rafx::renderer::{
@@ -22,9 +23,20 @@ use rafx::renderer::{
 use raw_window_handle::HasRawWindowHandle;
 use std::sync::Arc;
 
+// Pipelines created by the demo and the driver's pipeline cache are saved to this directory on
+// shutdown and loaded on startup so that pipelines can be created before the first frame
+#[cfg(not(target_arch = "wasm32"))]
+struct PipelineCacheDir(std::path::PathBuf);
+
+// Relative to the working directory
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_PIPELINE_CACHE_DIR: &str = "pipeline_cache";
+
 pub fn rendering_init(
     resources: &mut Resources,
     asset_source: AssetSource,
+    texture_streaming_config: TextureStreamingConfig,
+    pipeline_cache_dir: Option<std::path::PathBuf>,
     window: &dyn HasRawWindowHandle,
     window_width: u32,
     window_height: u32,
@@ -64,9 +76,19 @@ pub fn rendering_init(
         true
     };
 
+    // Environment lighting manifests reference images by their path within the asset dirs
+    let asset_dirs = match &asset_source {
+        AssetSource::Daemon { daemon_args, .. } => daemon_args.asset_dirs.clone(),
+        AssetSource::Direct { asset_dirs, .. } => asset_dirs.clone(),
+        _ => Vec::default(),
+    };
+
     let mut renderer_builder = RendererBuilder::default();
     renderer_builder = renderer_builder
         .add_asset(Arc::new(FontAssetTypeRendererPlugin))
+        .add_asset(Arc::new(EnvironmentLightingAssetTypeRendererPlugin::new(
+            asset_dirs,
+        )))
         .add_asset(Arc::new(GltfAssetTypeRendererPlugin))
         .add_asset(Arc::new(LdtkAssetTypeRendererPlugin))
         .add_asset(Arc::new(DemoRendererPlugin))
@@ -76,7 +98,8 @@ pub fn rendering_init(
         .add_render_feature(tile_layer_renderer_plugin)
         .add_render_feature(debug3d_renderer_plugin)
         .add_render_feature(text_renderer_plugin)
-        .allow_use_render_thread(allow_use_render_thread);
+        .allow_use_render_thread(allow_use_render_thread)
+        .texture_streaming_config(texture_streaming_config);
 
     #[cfg(feature = "egui")]
     {
@@ -100,6 +123,26 @@ pub fn rendering_init(
         )
     }?;
 
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let pipeline_cache_dir = pipeline_cache_dir
+            .unwrap_or_else(|| std::path::PathBuf::from(DEFAULT_PIPELINE_CACHE_DIR));
+
+        if let Err(e) = renderer_builder_result
+            .asset_manager
+            .resource_manager()
+            .graphics_pipeline_cache()
+            .load_from_disk(&pipeline_cache_dir)
+        {
+            log::warn!("Failed to load pipeline cache: {}", e);
+        }
+
+        resources.insert(PipelineCacheDir(pipeline_cache_dir));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    let _ = pipeline_cache_dir;
+
     let swapchain_helper = SwapchainHandler::create_swapchain(
         &mut renderer_builder_result.asset_manager,
         &mut renderer_builder_result.renderer,
@@ -131,8 +174,22 @@ pub fn rendering_destroy(resources: &mut Resources) -> RafxResult<()> {
     {
         {
             let swapchain_helper = resources.remove::<RafxSwapchainHelper>().unwrap();
+            #[cfg(not(target_arch = "wasm32"))]
+            let pipeline_cache_dir = resources.remove::<PipelineCacheDir>();
             let mut asset_manager = resources.get_mut::<AssetManager>().unwrap();
             let renderer = resources.get::<Renderer>().unwrap();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(pipeline_cache_dir) = pipeline_cache_dir {
+                if let Err(e) = asset_manager
+                    .resource_manager()
+                    .graphics_pipeline_cache()
+                    .save_to_disk(&pipeline_cache_dir.0)
+                {
+                    log::warn!("Failed to save pipeline cache: {}", e);
+                }
+            }
+
             SwapchainHandler::destroy_swapchain(swapchain_helper, &mut *asset_manager, &*renderer)?;
         }
 
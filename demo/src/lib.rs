@@ -8,7 +8,7 @@ use legion::*;
 use structopt::StructOpt;
 
 use rafx::api::{RafxExtents2D, RafxResult, RafxSwapchainHelper};
-use rafx::assets::AssetManager;
+use rafx::assets::{AssetManager, TextureStreamingConfig};
 
 pub use crate::daemon_args::AssetDaemonArgs;
 use crate::scenes::SceneManager;
@@ -16,7 +16,7 @@ use crate::time::{PeriodicEvent, TimeState};
 use rafx::assets::distill_impl::AssetResource;
 use rafx::render_features::ExtractResources;
 use rafx::renderer::{AssetSource, Renderer};
-use rafx::renderer::{RendererConfigResource, ViewportsResource};
+use rafx::renderer::{DynamicResolutionController, RendererConfigResource, ViewportsResource};
 use rafx::visibility::VisibilityRegion;
 
 pub mod assets;
@@ -143,6 +143,10 @@ pub struct RenderOptions {
     pub blur_pass_count: usize,
     pub tonemapper_type: TonemapperType,
     pub enable_visibility_update: bool,
+    pub enable_dynamic_resolution: bool,
+    pub render_scale: f32,
+    // Build the render graph from render_graphs/default.rendergraph instead of code
+    pub use_render_graph_asset: bool,
 }
 
 impl RenderOptions {
@@ -163,6 +167,9 @@ impl RenderOptions {
             blur_pass_count: 0,
             tonemapper_type: TonemapperType::None,
             enable_visibility_update: true,
+            enable_dynamic_resolution: false,
+            render_scale: 1.0,
+            use_render_graph_asset: false,
         }
     }
 
@@ -183,6 +190,9 @@ impl RenderOptions {
             blur_pass_count: 5,
             tonemapper_type: TonemapperType::LogDerivative,
             enable_visibility_update: true,
+            enable_dynamic_resolution: false,
+            render_scale: 1.0,
+            use_render_graph_asset: false,
         }
     }
 }
@@ -254,6 +264,25 @@ impl RenderOptions {
             &mut self.enable_visibility_update,
             "enable_visibility_update",
         );
+
+        ui.checkbox(
+            &mut self.enable_dynamic_resolution,
+            "enable_dynamic_resolution",
+        );
+        if !self.enable_dynamic_resolution {
+            ui.indent("", |ui| {
+                ui.add(
+                    egui::Slider::new(
+                        &mut self.render_scale,
+                        rafx::renderer::MIN_RENDER_SCALE..=rafx::renderer::MAX_RENDER_SCALE,
+                    )
+                    .clamp_to_range(true)
+                    .text("render_scale"),
+                );
+            });
+        }
+
+        ui.checkbox(&mut self.use_render_graph_asset, "use_render_graph_asset");
     }
 }
 
@@ -278,6 +307,19 @@ pub struct DemoArgs {
     #[structopt(name = "external-daemon", long)]
     pub external_daemon: bool,
 
+    /// Import assets in-process instead of using a daemon, caching the results in this directory
+    #[structopt(name = "direct", long, parse(from_os_str))]
+    pub direct_cache_dir: Option<std::path::PathBuf>,
+
+    /// Load the largest mip levels of textures only when they are large enough on screen
+    #[structopt(name = "texture-streaming", long)]
+    pub texture_streaming: bool,
+
+    /// Directory the pipeline cache is loaded from on startup and saved to on shutdown. Defaults
+    /// to pipeline_cache in the working directory.
+    #[structopt(name = "pipeline-cache", long, parse(from_os_str))]
+    pub pipeline_cache_dir: Option<std::path::PathBuf>,
+
     #[structopt(flatten)]
     pub daemon_args: AssetDaemonArgs,
 }
@@ -289,6 +331,14 @@ impl DemoArgs {
             return Some(AssetSource::Packfile(packfile.to_path_buf()));
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(direct_cache_dir) = &self.direct_cache_dir {
+            return Some(AssetSource::Direct {
+                asset_dirs: self.daemon_args.asset_dirs.clone(),
+                cache_dir: direct_cache_dir.to_path_buf(),
+            });
+        }
+
         {
             return Some(AssetSource::Daemon {
                 external_daemon: self.external_daemon,
@@ -323,13 +373,21 @@ impl DemoApp {
         resources.insert(InputResource::new());
         resources.insert(RenderOptions::default_2d());
         resources.insert(DebugUiState::default());
+        resources.insert(DynamicResolutionController::new(60.0));
 
         let asset_source = args.asset_source().unwrap();
 
+        let texture_streaming_config = TextureStreamingConfig {
+            enabled: args.texture_streaming,
+            ..Default::default()
+        };
+
         let physical_size = window.inner_size();
         init::rendering_init(
             &mut resources,
             asset_source,
+            texture_streaming_config,
+            args.pipeline_cache_dir.clone(),
             window,
             physical_size.width,
             physical_size.height,
@@ -425,8 +483,10 @@ impl DemoApp {
         //
         {
             profiling::scope!("update asset loaders");
+            let asset_resource = self.resources.get::<AssetResource>().unwrap();
             let mut asset_manager = self.resources.get_mut::<AssetManager>().unwrap();
 
+            asset_manager.cancel_unreferenced_uploads(&*asset_resource);
             asset_manager.update_asset_loaders().unwrap();
         }
 
@@ -553,6 +613,31 @@ impl DemoApp {
                 .enable_visibility_update = render_options.enable_visibility_update;
         }
 
+        //
+        // Pick the resolution of the main view
+        //
+        {
+            let time_state = self.resources.get::<TimeState>().unwrap();
+            let renderer = self.resources.get::<Renderer>().unwrap();
+            let render_options = self.resources.get::<RenderOptions>().unwrap();
+            let mut dynamic_resolution_controller = self
+                .resources
+                .get_mut::<DynamicResolutionController>()
+                .unwrap();
+            let mut render_config_resource =
+                self.resources.get_mut::<RendererConfigResource>().unwrap();
+
+            render_config_resource.render_scale = if render_options.enable_dynamic_resolution {
+                // Time spent waiting on vsync is not time spent producing the frame
+                let frame_time =
+                    time_state.previous_update_dt() - renderer.previous_frame_wait_time();
+                dynamic_resolution_controller.update(frame_time)
+            } else {
+                dynamic_resolution_controller.reset(render_options.render_scale);
+                render_options.render_scale
+            };
+        }
+
         //
         // Close egui input for this frame
         //
@@ -605,6 +690,10 @@ impl DemoApp {
                 crate::features::mesh::MeshRenderObjectSet,
                 mesh_render_object_set
             );
+            add_to_extract_resources!(
+                crate::features::mesh::MeshEnvironmentLightingResource,
+                mesh_environment_lighting_resource
+            );
             add_to_extract_resources!(
                 crate::features::tile_layer::TileLayerRenderObjectSet,
                 tile_layer_render_object_set
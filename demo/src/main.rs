@@ -50,6 +50,9 @@ fn main() {
     demo::main_native(&DemoArgs {
         external_daemon: false,
         packfile: None,
+        direct_cache_dir: None,
+        texture_streaming: false,
+        pipeline_cache_dir: None,
         packbuffer: None,
         daemon_args: demo::AssetDaemonArgs::from_iter(&[""]),
     })
@@ -4,10 +4,5 @@ use rafx::render_features::{RenderFeatureSubmitNode, RenderPhaseIndex};
 rafx::declare_render_phase!(
     OpaqueRenderPhase,
     OPAQUE_RENDER_PHASE_INDEX,
-    opaque_render_phase_sort_submit_nodes
+    rafx::render_features::sort_submit_nodes_by_state
 );
-
-#[profiling::function]
-fn opaque_render_phase_sort_submit_nodes(_submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
-    // Sorting is unnecessary because of the depth pre-pass.
-}
@@ -4,15 +4,5 @@ use rafx::render_features::{RenderFeatureSubmitNode, RenderPhaseIndex};
 rafx::declare_render_phase!(
     ShadowMapRenderPhase,
     SHADOW_MAP_RENDER_PHASE_INDEX,
-    shadow_map_render_phase_sort_submit_nodes
+    rafx::render_features::sort_submit_nodes_front_to_back
 );
-
-#[profiling::function]
-fn shadow_map_render_phase_sort_submit_nodes(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
-    // Sort by distance from camera front to back
-    log::trace!(
-        "Sort phase {}",
-        ShadowMapRenderPhase::render_phase_debug_name()
-    );
-    submit_nodes.sort_unstable_by(|a, b| a.distance().partial_cmp(&b.distance()).unwrap());
-}
@@ -29,6 +29,8 @@ pub(super) fn bloom_combine_pass(
         Default::default(),
         RenderGraphImageConstraint {
             format: Some(context.graph_config.swapchain_format),
+            // Upscales to the swapchain resolution if the main view is rendered at a different scale
+            extents: Some(context.graph_config.output_extents),
             ..Default::default()
         },
         Default::default(),
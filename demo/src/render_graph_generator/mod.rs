@@ -1,15 +1,16 @@
+use crate::phases::{DepthPrepassRenderPhase, OpaqueRenderPhase, TransparentRenderPhase};
 use rafx::api::{
-    RafxFormat, RafxPrimitiveTopology, RafxResourceState, RafxResourceType, RafxResult,
-    RafxSampleCount,
+    RafxColorClearValue, RafxFormat, RafxPrimitiveTopology, RafxResourceState, RafxResourceType,
+    RafxResult, RafxSampleCount,
 };
 use rafx::framework::VertexDataSetLayout;
 use rafx::framework::{ImageViewResource, ResourceArc};
 use rafx::framework::{RenderResources, ResourceContext};
 use rafx::graph::*;
-use rafx::render_features::{ExtractResources, RenderView};
+use rafx::render_features::{ExtractResources, RenderPhase, RenderView};
 
 mod shadow_map_pass;
-use shadow_map_pass::ShadowMapImageResources;
+use shadow_map_pass::{ShadowMapImageResources, ShadowMapViewProvider};
 
 mod opaque_pass;
 use opaque_pass::OpaquePass;
@@ -22,8 +23,11 @@ use crate::features::mesh::ShadowMapResource;
 use crate::RenderOptions;
 use bloom_extract_pass::BloomExtractPass;
 use rafx::assets::AssetManager;
-use rafx::renderer::RenderGraphGenerator;
-use rafx::renderer::SwapchainRenderResource;
+use rafx::renderer::{
+    add_render_to_texture_view_node, add_render_window_view_node, AssetRenderGraphGenerator,
+    RenderGraphGenerator, RenderToTextureView, RenderWindowView,
+};
+use rafx::renderer::{RendererConfigResource, SwapchainRenderResource};
 
 mod bloom_blur_pass;
 
@@ -31,6 +35,8 @@ mod bloom_combine_pass;
 
 mod ui_pass;
 
+mod upscale_pass;
+
 lazy_static::lazy_static! {
     pub static ref EMPTY_VERTEX_LAYOUT : VertexDataSetLayout = {
         VertexDataSetLayout::new(vec![], RafxPrimitiveTopology::TriangleList)
@@ -42,6 +48,11 @@ pub struct RenderGraphConfig {
     pub color_format: RafxFormat,
     pub depth_format: RafxFormat,
     pub swapchain_format: RafxFormat,
+    // Extents of the image written to the swapchain. Everything else matches the surface, which is
+    // scaled by the render scale.
+    pub output_extents: RenderGraphImageExtents,
+    pub is_render_scaled: bool,
+    pub output_image_state: RafxResourceState,
     pub samples: RafxSampleCount,
     pub enable_hdr: bool,
     pub enable_bloom: bool,
@@ -67,9 +78,33 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
         asset_manager: &AssetManager,
         swapchain_image: ResourceArc<ImageViewResource>,
         main_view: RenderView,
+        render_to_texture_views: &[RenderToTextureView],
+        window_views: &[RenderWindowView],
         extract_resources: &ExtractResources,
         render_resources: &RenderResources,
     ) -> RafxResult<PreparedRenderGraph> {
+        if extract_resources
+            .fetch::<RenderOptions>()
+            .use_render_graph_asset
+        {
+            let render_graph = render_resources
+                .fetch::<DemoStaticResources>()
+                .render_graph
+                .clone();
+
+            return AssetRenderGraphGenerator::new(render_graph)
+                .add_view_provider("shadow_maps", ShadowMapViewProvider)
+                .generate_render_graph(
+                    asset_manager,
+                    swapchain_image,
+                    main_view,
+                    render_to_texture_views,
+                    window_views,
+                    extract_resources,
+                    render_resources,
+                );
+        }
+
         profiling::scope!("Build Render Graph");
 
         let device_context = asset_manager.device_context();
@@ -78,6 +113,13 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
         let swapchain_info = swapchain_render_resource.get();
         let static_resources = render_resources.fetch::<DemoStaticResources>();
 
+        let renderer_config = extract_resources
+            .try_fetch::<RendererConfigResource>()
+            .map(|x| *x)
+            .unwrap_or_default();
+        let surface_info =
+            renderer_config.scaled_surface_info(&swapchain_info.swapchain_surface_info);
+
         let graph_config = {
             let render_options = extract_resources.fetch::<RenderOptions>().clone();
             let swapchain_format = swapchain_info.swapchain_surface_info.format;
@@ -99,6 +141,10 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
                 samples: sample_count,
                 enable_hdr: render_options.enable_hdr,
                 swapchain_format,
+                output_extents: renderer_config
+                    .swapchain_output_extents(&swapchain_info.swapchain_surface_info),
+                is_render_scaled: renderer_config.is_render_scaled(),
+                output_image_state: swapchain_info.output_image_state,
                 enable_bloom: render_options.enable_bloom,
                 show_surfaces: render_options.show_surfaces,
                 blur_pass_count: render_options.blur_pass_count,
@@ -107,6 +153,10 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
 
         let mut graph = RenderGraphBuilder::default();
 
+        // Describing the plan has a cost, so only do it when the description will be logged
+        let describe_plan = log::log_enabled!(log::Level::Trace);
+        graph.set_describe_plan(describe_plan);
+
         let mut graph_context = RenderGraphContext {
             graph: &mut graph,
             resource_context: &resource_context,
@@ -122,6 +172,50 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
 
         let opaque_pass = opaque_pass::opaque_pass(&mut graph_context, depth_prepass, &shadow_maps);
 
+        // Render-to-texture views and additional windows skip post-processing and UI. The depth
+        // prepass is drawn into the same depth buffer first because the opaque mesh pass expects it.
+        let secondary_view_phases = [
+            DepthPrepassRenderPhase::render_phase_index(),
+            OpaqueRenderPhase::render_phase_index(),
+            TransparentRenderPhase::render_phase_index(),
+        ];
+
+        let mut secondary_view_nodes = Vec::default();
+        for render_to_texture_view in render_to_texture_views {
+            secondary_view_nodes.push(add_render_to_texture_view_node(
+                graph_context.graph,
+                render_to_texture_view,
+                &secondary_view_phases,
+                graph_config.depth_format,
+                RafxColorClearValue([0.0, 0.0, 0.0, 1.0]),
+            ));
+        }
+
+        for window_view in window_views {
+            secondary_view_nodes.push(add_render_window_view_node(
+                graph_context.graph,
+                window_view,
+                &secondary_view_phases,
+                graph_config.depth_format,
+                RafxColorClearValue([0.0, 0.0, 0.0, 1.0]),
+            ));
+        }
+
+        for &node in &secondary_view_nodes {
+            for shadow_map in &shadow_maps {
+                let image = match shadow_map {
+                    ShadowMapImageResources::Single(image) => *image,
+                    ShadowMapImageResources::Cube(cube_map_image) => *cube_map_image,
+                };
+                graph_context.graph.sample_image(
+                    node,
+                    image,
+                    Default::default(),
+                    Default::default(),
+                );
+            }
+        }
+
         let previous_pass_color = if graph_config.enable_hdr {
             let bloom_extract_material_pass = asset_manager
                 .committed_asset(&static_resources.bloom_extract_material)
@@ -166,6 +260,20 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
             );
 
             bloom_combine_pass.color
+        } else if graph_config.is_render_scaled {
+            let bloom_combine_material_pass = asset_manager
+                .committed_asset(&static_resources.bloom_combine_material)
+                .unwrap()
+                .get_single_material_pass()
+                .unwrap();
+
+            let upscale_pass = upscale_pass::upscale_pass(
+                &mut graph_context,
+                bloom_combine_material_pass,
+                opaque_pass.color,
+            );
+
+            upscale_pass.color
         } else {
             opaque_pass.color
         };
@@ -179,20 +287,20 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
                 samples: RafxSampleCount::SampleCount1,
                 format: graph_config.swapchain_format,
                 resource_type: RafxResourceType::TEXTURE | RafxResourceType::RENDER_TARGET_COLOR,
-                extents: RenderGraphImageExtents::MatchSurface,
+                extents: graph_config.output_extents,
                 layer_count: 1,
                 mip_count: 1,
             },
             Default::default(),
-            RafxResourceState::PRESENT,
+            graph_config.output_image_state,
         );
 
-        let prepared_render_graph = PreparedRenderGraph::new(
-            &device_context,
-            &resource_context,
-            graph,
-            &swapchain_info.swapchain_surface_info,
-        )?;
+        let prepared_render_graph =
+            PreparedRenderGraph::new(&device_context, &resource_context, graph, &surface_info)?;
+
+        if let Some(dot) = prepared_render_graph.graph_plan().to_dot() {
+            log::trace!("Render graph plan:\n{}", dot);
+        }
 
         render_resources
             .fetch_mut::<ShadowMapResource>()
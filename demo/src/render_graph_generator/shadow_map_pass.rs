@@ -3,8 +3,10 @@ use crate::features::mesh::ShadowMapRenderView;
 use crate::features::mesh::ShadowMapResource;
 use crate::phases::ShadowMapRenderPhase;
 use rafx::api::{RafxDepthStencilClearValue, RafxResourceType};
+use rafx::framework::RenderResources;
 use rafx::graph::*;
 use rafx::render_features::{RenderJobCommandBufferContext, RenderView};
+use rafx::renderer::{RenderGraphAssetViewImage, RenderGraphAssetViewProvider};
 
 pub(super) struct ShadowMapPass {
     pub(super) node: RenderGraphNodeId,
@@ -129,3 +131,40 @@ fn shadow_map_pass(
 
     ShadowMapPass { node, depth }
 }
+
+// Supplies the shadow map views to the DrawViews nodes of render graph assets
+pub(super) struct ShadowMapViewProvider;
+
+impl RenderGraphAssetViewProvider for ShadowMapViewProvider {
+    fn view_images(
+        &self,
+        render_resources: &RenderResources,
+    ) -> Vec<RenderGraphAssetViewImage> {
+        let shadow_map_resource = render_resources.fetch::<ShadowMapResource>();
+        shadow_map_resource
+            .shadow_map_render_views()
+            .iter()
+            .map(|shadow_map_view| match shadow_map_view {
+                ShadowMapRenderView::Single(render_view) => RenderGraphAssetViewImage {
+                    views: vec![render_view.clone()],
+                    resource_type: RafxResourceType::empty(),
+                },
+                ShadowMapRenderView::Cube(render_views) => RenderGraphAssetViewImage {
+                    views: render_views.to_vec(),
+                    resource_type: RafxResourceType::TEXTURE_CUBE,
+                },
+            })
+            .collect()
+    }
+
+    fn on_render_graph_prepared(
+        &self,
+        render_resources: &RenderResources,
+        prepared_render_graph: &PreparedRenderGraph,
+        images: &[RenderGraphImageUsageId],
+    ) {
+        let mut shadow_map_resource = render_resources.fetch_mut::<ShadowMapResource>();
+        shadow_map_resource.set_shadow_map_image_usage_ids(images.to_vec());
+        shadow_map_resource.set_shadow_map_image_views(prepared_render_graph);
+    }
+}
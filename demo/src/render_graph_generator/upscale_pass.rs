@@ -0,0 +1,130 @@
+use crate::phases::PostProcessRenderPhase;
+use crate::TonemapperType;
+use rafx::api::{RafxColorClearValue, RafxFormat, RafxSampleCount};
+use rafx::framework::{MaterialPassResource, ResourceArc};
+use rafx::graph::*;
+use rafx::render_features::RenderPhase;
+
+use super::RenderGraphContext;
+use super::EMPTY_VERTEX_LAYOUT;
+
+pub(super) struct UpscalePass {
+    pub(super) node: RenderGraphNodeId,
+    pub(super) color: RenderGraphImageUsageId,
+}
+
+// Resamples the main view from the render resolution to the swapchain resolution. This is only
+// needed when HDR is disabled, otherwise the bloom combine pass writes at the swapchain resolution.
+// The bloom combine material is reused with tonemapping disabled and a cleared 1x1 image in place
+// of the blurred image, so it is a plain copy.
+pub(super) fn upscale_pass(
+    context: &mut RenderGraphContext,
+    bloom_combine_material_pass: ResourceArc<MaterialPassResource>,
+    color: RenderGraphImageUsageId,
+) -> UpscalePass {
+    let clear_node = context
+        .graph
+        .add_node("UpscaleClearBlur", RenderGraphQueue::DefaultGraphics);
+
+    let cleared_image = context.graph.create_color_attachment(
+        clear_node,
+        0,
+        Some(RafxColorClearValue([0.0, 0.0, 0.0, 0.0])),
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            format: Some(RafxFormat::R8G8B8A8_UNORM),
+            extents: Some(RenderGraphImageExtents::Custom(1, 1, 1)),
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    context.graph.set_image_name(cleared_image, "cleared");
+
+    let node = context
+        .graph
+        .add_node("Upscale", RenderGraphQueue::DefaultGraphics);
+
+    let upscaled_color = context.graph.create_color_attachment(
+        node,
+        0,
+        Default::default(),
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            format: Some(context.graph_config.swapchain_format),
+            extents: Some(context.graph_config.output_extents),
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    context.graph.set_image_name(upscaled_color, "color");
+
+    let color_image = context.graph.sample_image(
+        node,
+        color,
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    context.graph.set_image_name(color_image, "color");
+
+    let blur_image =
+        context
+            .graph
+            .sample_image(node, cleared_image, Default::default(), Default::default());
+    context.graph.set_image_name(blur_image, "blur");
+
+    context.graph.set_renderpass_callback(node, move |args| {
+        let color_image = args.graph_context.image_view(color_image).unwrap();
+        let blur_image = args.graph_context.image_view(blur_image).unwrap();
+
+        // Get the pipeline
+        let pipeline = args
+            .graph_context
+            .resource_context()
+            .graphics_pipeline_cache()
+            .get_or_create_graphics_pipeline(
+                PostProcessRenderPhase::render_phase_index(),
+                &bloom_combine_material_pass,
+                &args.render_target_meta,
+                &EMPTY_VERTEX_LAYOUT,
+            )?;
+
+        // Set up a descriptor set pointing at the images so we can sample from them
+        let mut descriptor_set_allocator = args
+            .graph_context
+            .resource_context()
+            .create_descriptor_set_allocator();
+
+        let descriptor_set_layouts = &pipeline.get_raw().descriptor_set_layouts;
+        let upscale_material_dyn_set = descriptor_set_allocator.create_descriptor_set(
+            &descriptor_set_layouts[shaders::bloom_combine_frag::IN_COLOR_DESCRIPTOR_SET_INDEX],
+            shaders::bloom_combine_frag::DescriptorSet0Args {
+                in_color: &color_image,
+                in_blur: &blur_image,
+                config: &shaders::bloom_combine_frag::ConfigStd140 {
+                    tonemapper_type: TonemapperType::None as i32,
+                    ..Default::default()
+                },
+            },
+        )?;
+
+        descriptor_set_allocator.flush_changes()?;
+
+        // Draw calls
+        let command_buffer = &args.command_buffer;
+        command_buffer
+            .cmd_bind_pipeline(&*pipeline.get_raw().pipeline)
+            .unwrap();
+        upscale_material_dyn_set.bind(command_buffer).unwrap();
+        command_buffer.cmd_draw(3, 0).unwrap();
+
+        Ok(())
+    });
+
+    UpscalePass {
+        node,
+        color: upscaled_color,
+    }
+}
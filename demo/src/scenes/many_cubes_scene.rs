@@ -58,6 +58,7 @@ impl ManyCubesScene {
         let container_1_asset = asset_resource.load_asset_path("blender/storage_container1.glb");
         let cube_render_object = mesh_render_objects.register_render_object(MeshRenderObject {
             mesh: container_1_asset.clone(),
+            material_override: None,
         });
 
         let mut load_visible_bounds = |asset_handle: &Handle<MeshAsset>| {
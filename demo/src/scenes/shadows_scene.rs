@@ -23,7 +23,8 @@ use glam::Vec3;
 use legion::IntoQuery;
 use legion::{Read, Resources, World, Write};
 use rafx::assets::distill_impl::AssetResource;
-use rafx::assets::AssetManager;
+use rafx::assets::{AssetManager, MaterialInstanceOverrides};
+use rafx::framework::UniformParamValue;
 use rafx::rafx_visibility::{DepthRange, PerspectiveParameters, Projection};
 use rafx::render_features::{
     RenderFeatureFlagMaskBuilder, RenderFeatureMaskBuilder, RenderPhaseMaskBuilder,
@@ -59,6 +60,32 @@ impl ShadowsScene {
         let blue_icosphere_asset =
             asset_resource.load_asset::<MeshAsset>("d5aed900-1e31-4f47-94ba-e356b0b0b8b0".into());
 
+        // Some of the icospheres are drawn red with a material instance derived from the one the
+        // mesh was imported with
+        asset_manager
+            .wait_for_asset_to_load(&blue_icosphere_asset, &mut asset_resource, "")
+            .unwrap();
+        let red_icosphere_material = {
+            let base = asset_manager
+                .committed_asset(&blue_icosphere_asset)
+                .unwrap()
+                .inner
+                .asset_data
+                .mesh_parts[0]
+                .material_instance
+                .clone();
+
+            let mut overrides = MaterialInstanceOverrides::default();
+            overrides.set_uniform_param(
+                "per_material_data.data.base_color_factor",
+                UniformParamValue::Vec4([0.8, 0.1, 0.1, 1.0]),
+            );
+
+            asset_manager
+                .create_derived_material_instance(&base, overrides)
+                .unwrap()
+        };
+
         let mut load_visible_bounds = |asset_handle: &Handle<MeshAsset>| {
             asset_manager
                 .wait_for_asset_to_load(asset_handle, &mut asset_resource, "")
@@ -81,6 +108,7 @@ impl ShadowsScene {
             let floor_mesh_render_object =
                 mesh_render_objects.register_render_object(MeshRenderObject {
                     mesh: floor_mesh_asset.clone(),
+                    material_override: None,
                 });
 
             let transform_component = TransformComponent {
@@ -122,6 +150,7 @@ impl ShadowsScene {
                 meshes.push(
                     mesh_render_objects.register_render_object(MeshRenderObject {
                         mesh: container_1_asset,
+                        material_override: None,
                     }),
                 );
 
@@ -129,13 +158,23 @@ impl ShadowsScene {
                 meshes.push(
                     mesh_render_objects.register_render_object(MeshRenderObject {
                         mesh: container_2_asset,
+                        material_override: None,
                     }),
                 );
 
                 // blue icosphere - load by UUID since it's one of several meshes in the file
+                meshes.push(
+                    mesh_render_objects.register_render_object(MeshRenderObject {
+                        mesh: blue_icosphere_asset.clone(),
+                        material_override: None,
+                    }),
+                );
+
+                // red icosphere
                 meshes.push(
                     mesh_render_objects.register_render_object(MeshRenderObject {
                         mesh: blue_icosphere_asset,
+                        material_override: Some(red_icosphere_material),
                     }),
                 );
 
@@ -135,6 +135,8 @@ impl RafxCommandBufferEmpty {
     pub fn return_to_pool(&self) -> RafxResult<()> { unimplemented!() }
 
     pub fn cmd_begin_render_pass(&self, color_targets: &[RafxColorRenderTargetBinding], depth_target: Option<RafxDepthStencilRenderTargetBinding>) -> RafxResult<()> { unimplemented!() }
+    pub fn cmd_begin_render_pass_with_subpasses(&self, color_targets: &[RafxColorRenderTargetBinding], depth_target: Option<RafxDepthStencilRenderTargetBinding>, subpasses: &[RafxSubpassDef]) -> RafxResult<()> { unimplemented!() }
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> { unimplemented!() }
 
     pub fn cmd_set_viewport(&self, x: f32, y: f32, width: f32, height: f32, depth_min: f32, depth_max: f32) -> RafxResult<()> { unimplemented!() }
@@ -146,6 +148,7 @@ impl RafxCommandBufferEmpty {
     pub fn cmd_bind_index_buffer(&self, binding: &RafxIndexBufferBinding) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_bind_descriptor_set(&self, descriptor_set_array: &RafxDescriptorSetArrayEmpty, index: u32) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_bind_descriptor_set_handle(&self, root_signature: &RafxRootSignatureEmpty, set_index: u32, descriptor_set_handle: &RafxDescriptorSetHandleEmpty) -> RafxResult<()> { unimplemented!() }
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(&self, root_signature: &RafxRootSignatureEmpty, set_index: u32, descriptor_set_handle: &RafxDescriptorSetHandleEmpty, dynamic_offsets: &[u32]) -> RafxResult<()> { unimplemented!() }
 
     pub fn cmd_draw(&self, vertex_count: u32, first_vertex: u32) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_draw_instanced(&self, vertex_count: u32, first_vertex: u32, instance_count: u32, first_instance: u32) -> RafxResult<()> { unimplemented!() }
@@ -157,6 +160,7 @@ impl RafxCommandBufferEmpty {
     pub fn cmd_resource_barrier(&self, buffer_barriers: &[RafxBufferBarrier], texture_barriers: &[RafxTextureBarrier]) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_copy_buffer_to_buffer(&self, src_buffer: &RafxBufferEmpty, dst_buffer: &RafxBufferEmpty, src_offset: u64, dst_offset: u64, size: u64) -> RafxResult<()> { unimplemented!() }
     pub fn cmd_copy_buffer_to_texture(&self, src_buffer: &RafxBufferEmpty, dst_texture: &RafxTextureEmpty, params: &RafxCmdCopyBufferToTextureParams) -> RafxResult<()> { unimplemented!() }
+    pub fn cmd_copy_texture_to_buffer(&self, src_texture: &RafxTextureEmpty, dst_buffer: &RafxBufferEmpty, params: &RafxCmdCopyTextureToBufferParams) -> RafxResult<()> { unimplemented!() }
 }
 
 //
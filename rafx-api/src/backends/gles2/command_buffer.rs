@@ -6,10 +6,11 @@ use crate::gles2::{
     RafxTextureGles2, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM, NONE_TEXTURE,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorFlags,
-    RafxColorRenderTargetBinding, RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding,
-    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxResourceType, RafxResult,
-    RafxTextureBarrier, RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxCmdCopyTextureToBufferParams,
+    RafxColorFlags, RafxColorRenderTargetBinding, RafxCommandBufferDef,
+    RafxDepthStencilRenderTargetBinding, RafxExtents3D, RafxIndexBufferBinding, RafxIndexType,
+    RafxLoadOp, RafxResourceType, RafxResult, RafxSubpassDef, RafxTextureBarrier,
+    RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 
 use rafx_base::trust_cell::TrustCell;
@@ -288,6 +289,19 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on GL ES 2.0")?
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on GL ES 2.0")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -707,9 +721,8 @@ impl RafxCommandBufferGles2 {
                 .unwrap(),
             descriptor_set_array.set_index(),
             index,
-        );
-
-        Ok(())
+            &[],
+        )
     }
 
     pub fn cmd_bind_descriptor_set_handle(
@@ -717,6 +730,21 @@ impl RafxCommandBufferGles2 {
         root_signature: &RafxRootSignatureGles2,
         set_index: u32,
         descriptor_set_handle: &RafxDescriptorSetHandleGles2,
+    ) -> RafxResult<()> {
+        self.cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+            root_signature,
+            set_index,
+            descriptor_set_handle,
+            &[],
+        )
+    }
+
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+        &self,
+        root_signature: &RafxRootSignatureGles2,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandleGles2,
+        dynamic_offsets: &[u32],
     ) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -727,8 +755,8 @@ impl RafxCommandBufferGles2 {
             root_signature,
             set_index,
             descriptor_set_handle.array_index(),
-        );
-        Ok(())
+            dynamic_offsets,
+        )
     }
 
     // This does not affect the program right away, we wait until we try to draw, then update the
@@ -740,7 +768,20 @@ impl RafxCommandBufferGles2 {
         root_signature: &RafxRootSignatureGles2,
         set_index: u32,
         array_index: u32,
-    ) {
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        let dynamic_descriptor_count = root_signature.inner.layouts[set_index as usize]
+            .dynamic_descriptor_indexes
+            .len();
+        if dynamic_offsets.len() != dynamic_descriptor_count {
+            Err(format!(
+                "Descriptor set {} has {} dynamic descriptors but {} dynamic offsets were provided",
+                set_index,
+                dynamic_descriptor_count,
+                dynamic_offsets.len()
+            ))?;
+        }
+
         // If we bind a descriptor set with a different root signature, clear the other bindings
         if let Some(current_root_signature) = &state.bound_descriptor_sets_root_signature {
             if current_root_signature != root_signature {
@@ -755,8 +796,10 @@ impl RafxCommandBufferGles2 {
         state.bound_descriptor_sets[set_index as usize] = Some(BoundDescriptorSet {
             data: data.clone(),
             array_index,
+            dynamic_offsets: dynamic_offsets.to_vec(),
         });
         state.descriptor_sets_update_index[set_index as usize] += 1;
+        Ok(())
     }
 
     // Call right before drawing, this just checks that the program is up-to-date with the latest
@@ -796,6 +839,7 @@ impl RafxCommandBufferGles2 {
                                 &*bound_descriptor_set.data.borrow(),
                                 set_index as u32,
                                 bound_descriptor_set.array_index,
+                                &bound_descriptor_set.dynamic_offsets,
                             )?;
 
                             last_descriptor_updates[set_index] =
@@ -816,6 +860,7 @@ impl RafxCommandBufferGles2 {
         data: &DescriptorSetArrayData,
         set_index: u32,
         array_index: u32,
+        dynamic_offsets: &[u32],
     ) -> RafxResult<()> {
         let root_signature = &pipeline_info.root_signature;
         let uniform_reflection_data = root_signature.uniform_reflection_data();
@@ -926,7 +971,7 @@ impl RafxCommandBufferGles2 {
                         }
                     }
                 }
-                RafxResourceType::UNIFORM_BUFFER => {
+                RafxResourceType::UNIFORM_BUFFER | RafxResourceType::UNIFORM_BUFFER_DYNAMIC => {
                     if let Some(uniform_index) = descriptor.uniform_index {
                         // Find where the buffers states begin for this resource in this descriptor set
                         let base_buffer_state_index = array_index * data.buffer_states_per_set
@@ -938,6 +983,13 @@ impl RafxCommandBufferGles2 {
                                 .as_ref()
                                 .unwrap();
 
+                            // Dynamic offsets are added to the offset the buffer was written with
+                            let offset = buffer_state.offset
+                                + descriptor
+                                    .dynamic_offset_index
+                                    .map(|x| dynamic_offsets[x as usize] as u64)
+                                    .unwrap_or(0);
+
                             // Get a ptr to the start of the uniform data we're binding
                             let uniform_data_ptr = unsafe {
                                 buffer_state
@@ -946,7 +998,7 @@ impl RafxCommandBufferGles2 {
                                     .unwrap()
                                     .try_as_ptr()
                                     .expect("bound uniform buffer must be CPU-visible")
-                                    .add(buffer_state.offset as usize)
+                                    .add(offset as usize)
                             };
 
                             let fields = uniform_reflection_data.uniform_fields(uniform_index);
@@ -1142,4 +1194,13 @@ impl RafxCommandBufferGles2 {
         )?;
         gl_context.gl_bind_texture(dst_texture.gl_target(), NONE_TEXTURE)
     }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        _src_texture: &RafxTextureGles2,
+        _dst_buffer: &RafxBufferGles2,
+        _params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        Err("cmd_copy_texture_to_buffer is not supported on GL ES")?
+    }
 }
@@ -343,6 +343,7 @@ impl RafxDescriptorSetArrayGles2 {
                 //         }
             }
             RafxResourceType::UNIFORM_BUFFER
+            | RafxResourceType::UNIFORM_BUFFER_DYNAMIC
             | RafxResourceType::BUFFER
             | RafxResourceType::BUFFER_READ_WRITE => {
                 let buffers = update.elements.buffers.ok_or_else(||
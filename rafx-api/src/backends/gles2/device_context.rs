@@ -84,6 +84,8 @@ impl RafxDeviceContextGles2Inner {
             upload_buffer_texture_alignment: pack_alignment,
             upload_buffer_texture_row_alignment: pack_alignment,
             supports_clamp_to_border_color: false, // requires GLES 3.2 or an extension
+            supports_subpasses: false,
+            supports_dynamic_uniform_buffers: true,
             max_vertex_attribute_count,
         };
 
@@ -318,6 +320,7 @@ impl RafxDeviceContextGles2 {
     ) -> Option<RafxFormat> {
         if resource_type.intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
             || resource_type.intersects(RafxResourceType::RENDER_TARGET_COLOR)
+            || resource_type.intersects(RafxResourceType::TEXTURE)
         {
             for &candidate in candidates {
                 if candidate.gles2_texture_format_info().is_some() {
@@ -100,6 +100,10 @@ impl RafxPipelineGles2 {
         device_context: &RafxDeviceContextGles2,
         pipeline_def: &RafxGraphicsPipelineDef,
     ) -> RafxResult<Self> {
+        if pipeline_def.subpass.is_some() {
+            Err("Renderpasses with multiple subpasses are not supported on GL ES 2.0")?;
+        }
+
         let gl_context = device_context.gl_context();
         let shader = pipeline_def.shader.gles2_shader().unwrap();
 
@@ -57,6 +57,10 @@ pub(crate) struct DescriptorInfo {
     // A quick lookup to get the sampler associated with a texture
     pub(crate) sampler_descriptor_index: Option<RafxSamplerIndexGles2>,
 
+    // Index into the dynamic offsets provided when binding the descriptor set. Only set for
+    // dynamic uniform buffers
+    pub(crate) dynamic_offset_index: Option<u32>,
+
     pub(crate) gl_name: CString,
 
     // Indexes into location_names
@@ -69,6 +73,8 @@ pub(crate) struct DescriptorSetLayoutInfo {
     pub(crate) descriptors: Vec<RafxDescriptorIndex>,
     // Indexes binding index to the descriptors list
     pub(crate) binding_to_descriptor_index: FnvHashMap<u32, RafxDescriptorIndex>,
+    // Dynamic uniform buffers, ordered by binding (the order dynamic offsets are provided in)
+    pub(crate) dynamic_descriptor_indexes: Vec<RafxDescriptorIndex>,
 
     // // --- gl-specific ---
     // // Now embedded by spirv_cross in the shader
@@ -332,7 +338,7 @@ impl RafxRootSignatureGles2 {
             } else {
                 let descriptor_index = RafxDescriptorIndex(descriptors.len() as u32);
 
-                let uniform_index = if resource.resource_type == RafxResourceType::UNIFORM_BUFFER {
+                let uniform_index = if resource.resource_type.is_uniform_buffer() {
                     // May be none if the variable is not active in any shader
                     uniform_reflection.uniform_index(gl_name)
                 } else {
@@ -352,6 +358,7 @@ impl RafxRootSignatureGles2 {
                     uniform_index,
                     descriptor_data_offset_in_set,
                     sampler_descriptor_index: None, // we set this later
+                    dynamic_offset_index: None,     // we set this later
                     gl_name: gl_name_cstr,
                     first_location_index,
                 });
@@ -365,6 +372,10 @@ impl RafxRootSignatureGles2 {
                     .binding_to_descriptor_index
                     .insert(resource.binding, descriptor_index);
 
+                if resource.resource_type.is_dynamic_uniform_buffer() {
+                    layout.dynamic_descriptor_indexes.push(descriptor_index);
+                }
+
                 if resource
                     .resource_type
                     .intersects(RafxResourceType::TEXTURE | RafxResourceType::TEXTURE_READ_WRITE)
@@ -407,6 +418,15 @@ impl RafxRootSignatureGles2 {
                 Some(sampler_descriptor_index);
         }
 
+        for layout in &mut layouts {
+            layout
+                .dynamic_descriptor_indexes
+                .sort_by_key(|x| descriptors[x.0 as usize].binding);
+            for (i, descriptor_index) in layout.dynamic_descriptor_indexes.iter().enumerate() {
+                descriptors[descriptor_index.0 as usize].dynamic_offset_index = Some(i as u32);
+            }
+        }
+
         let inner = RafxRootSignatureGles2Inner {
             device_context: device_context.clone(),
             pipeline_type,
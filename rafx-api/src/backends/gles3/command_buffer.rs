@@ -6,10 +6,11 @@ use crate::gles3::{
     RafxTextureGles3, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM, NONE_TEXTURE,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorFlags,
-    RafxColorRenderTargetBinding, RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding,
-    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxResourceType, RafxResult,
-    RafxTextureBarrier, RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxCmdCopyTextureToBufferParams,
+    RafxColorFlags, RafxColorRenderTargetBinding, RafxCommandBufferDef,
+    RafxDepthStencilRenderTargetBinding, RafxExtents3D, RafxIndexBufferBinding, RafxIndexType,
+    RafxLoadOp, RafxResourceType, RafxResult, RafxSubpassDef, RafxTextureBarrier,
+    RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 
 use rafx_base::trust_cell::TrustCell;
@@ -288,6 +289,19 @@ impl RafxCommandBufferGles3 {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on GL ES 3.0")?
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on GL ES 3.0")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -710,9 +724,8 @@ impl RafxCommandBufferGles3 {
                 .unwrap(),
             descriptor_set_array.set_index(),
             index,
-        );
-
-        Ok(())
+            &[],
+        )
     }
 
     pub fn cmd_bind_descriptor_set_handle(
@@ -720,6 +733,21 @@ impl RafxCommandBufferGles3 {
         root_signature: &RafxRootSignatureGles3,
         set_index: u32,
         descriptor_set_handle: &RafxDescriptorSetHandleGles3,
+    ) -> RafxResult<()> {
+        self.cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+            root_signature,
+            set_index,
+            descriptor_set_handle,
+            &[],
+        )
+    }
+
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+        &self,
+        root_signature: &RafxRootSignatureGles3,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandleGles3,
+        dynamic_offsets: &[u32],
     ) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -730,8 +758,8 @@ impl RafxCommandBufferGles3 {
             root_signature,
             set_index,
             descriptor_set_handle.array_index(),
-        );
-        Ok(())
+            dynamic_offsets,
+        )
     }
 
     // This does not affect the program right away, we wait until we try to draw, then update the
@@ -743,7 +771,20 @@ impl RafxCommandBufferGles3 {
         root_signature: &RafxRootSignatureGles3,
         set_index: u32,
         array_index: u32,
-    ) {
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        let dynamic_descriptor_count = root_signature.inner.layouts[set_index as usize]
+            .dynamic_descriptor_indexes
+            .len();
+        if dynamic_offsets.len() != dynamic_descriptor_count {
+            Err(format!(
+                "Descriptor set {} has {} dynamic descriptors but {} dynamic offsets were provided",
+                set_index,
+                dynamic_descriptor_count,
+                dynamic_offsets.len()
+            ))?;
+        }
+
         // If we bind a descriptor set with a different root signature, clear the other bindings
         if let Some(current_root_signature) = &state.bound_descriptor_sets_root_signature {
             if current_root_signature != root_signature {
@@ -758,8 +799,10 @@ impl RafxCommandBufferGles3 {
         state.bound_descriptor_sets[set_index as usize] = Some(BoundDescriptorSet {
             data: data.clone(),
             array_index,
+            dynamic_offsets: dynamic_offsets.to_vec(),
         });
         state.descriptor_sets_update_index[set_index as usize] += 1;
+        Ok(())
     }
 
     // Call right before drawing, this just checks that the program is up-to-date with the latest
@@ -799,6 +842,7 @@ impl RafxCommandBufferGles3 {
                                 &*bound_descriptor_set.data.borrow(),
                                 set_index as u32,
                                 bound_descriptor_set.array_index,
+                                &bound_descriptor_set.dynamic_offsets,
                             )?;
 
                             last_descriptor_updates[set_index] =
@@ -819,6 +863,7 @@ impl RafxCommandBufferGles3 {
         data: &DescriptorSetArrayData,
         set_index: u32,
         array_index: u32,
+        dynamic_offsets: &[u32],
     ) -> RafxResult<()> {
         let root_signature = &pipeline_info.root_signature;
         for descriptor_index in &root_signature.inner.layouts[set_index as usize].descriptors {
@@ -958,7 +1003,7 @@ impl RafxCommandBufferGles3 {
                         }
                     }
                 }
-                RafxResourceType::UNIFORM_BUFFER => {
+                RafxResourceType::UNIFORM_BUFFER | RafxResourceType::UNIFORM_BUFFER_DYNAMIC => {
                     if let Some(uniform_block_binding) = descriptor.uniform_block_binding {
                         if let Some(uniform_block_size) =
                             pipeline_info.uniform_block_sizes[uniform_block_binding as usize]
@@ -973,15 +1018,19 @@ impl RafxCommandBufferGles3 {
                                     .as_ref()
                                     .unwrap();
 
+                                // Dynamic offsets are added to the offset the buffer was written with
+                                let offset = buffer_state.offset
+                                    + descriptor
+                                        .dynamic_offset_index
+                                        .map(|x| dynamic_offsets[x as usize] as u64)
+                                        .unwrap_or(0);
+
                                 let allocation_size = buffer_state
                                     .buffer_contents
                                     .as_ref()
                                     .unwrap()
                                     .allocation_size();
-                                assert!(
-                                    buffer_state.offset + uniform_block_size as u64
-                                        <= allocation_size
-                                );
+                                assert!(offset + uniform_block_size as u64 <= allocation_size);
                                 gl_context.gl_bind_buffer_range(
                                     gles3_bindings::UNIFORM_BUFFER,
                                     uniform_block_binding,
@@ -990,7 +1039,7 @@ impl RafxCommandBufferGles3 {
                                         .as_ref()
                                         .unwrap()
                                         .as_buffer_id(),
-                                    buffer_state.offset,
+                                    offset,
                                     uniform_block_size,
                                 )?;
                             }
@@ -1169,4 +1218,13 @@ impl RafxCommandBufferGles3 {
         )?;
         gl_context.gl_bind_texture(dst_texture.gl_target(), NONE_TEXTURE)
     }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        _src_texture: &RafxTextureGles3,
+        _dst_buffer: &RafxBufferGles3,
+        _params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        Err("cmd_copy_texture_to_buffer is not supported on GL ES")?
+    }
 }
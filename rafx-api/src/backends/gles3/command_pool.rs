@@ -17,6 +17,7 @@ pub(crate) type AttributeEnabledBits = u32;
 pub(crate) struct BoundDescriptorSet {
     pub(crate) data: Arc<TrustCell<DescriptorSetArrayData>>,
     pub(crate) array_index: u32,
+    pub(crate) dynamic_offsets: Vec<u32>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -88,6 +88,8 @@ impl RafxDeviceContextGles3Inner {
             upload_buffer_texture_alignment: pack_alignment,
             upload_buffer_texture_row_alignment: pack_alignment,
             supports_clamp_to_border_color: false, // requires GLES 3.2 or an extension
+            supports_subpasses: false,
+            supports_dynamic_uniform_buffers: true,
             max_vertex_attribute_count,
         };
 
@@ -330,6 +332,7 @@ impl RafxDeviceContextGles3 {
     ) -> Option<RafxFormat> {
         if resource_type.intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
             || resource_type.intersects(RafxResourceType::RENDER_TARGET_COLOR)
+            || resource_type.intersects(RafxResourceType::TEXTURE)
         {
             for &candidate in candidates {
                 if candidate.gles3_texture_format_info().is_some() {
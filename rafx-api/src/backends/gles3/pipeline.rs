@@ -94,6 +94,10 @@ impl RafxPipelineGles3 {
         device_context: &RafxDeviceContextGles3,
         pipeline_def: &RafxGraphicsPipelineDef,
     ) -> RafxResult<Self> {
+        if pipeline_def.subpass.is_some() {
+            Err("Renderpasses with multiple subpasses are not supported on GL ES 3.0")?;
+        }
+
         let gl_context = device_context.gl_context();
         let shader = pipeline_def.shader.gles3_shader().unwrap();
 
@@ -55,6 +55,10 @@ pub(crate) struct DescriptorInfo {
     // A quick lookup to get the sampler associated with a texture
     pub(crate) sampler_descriptor_index: Option<RafxSamplerIndexGles3>,
 
+    // Index into the dynamic offsets provided when binding the descriptor set. Only set for
+    // dynamic uniform buffers
+    pub(crate) dynamic_offset_index: Option<u32>,
+
     pub(crate) gl_name: CString,
 
     // Indexes into location_names
@@ -67,6 +71,8 @@ pub(crate) struct DescriptorSetLayoutInfo {
     pub(crate) descriptors: Vec<RafxDescriptorIndex>,
     // Indexes binding index to the descriptors list
     pub(crate) binding_to_descriptor_index: FnvHashMap<u32, RafxDescriptorIndex>,
+    // Dynamic uniform buffers, ordered by binding (the order dynamic offsets are provided in)
+    pub(crate) dynamic_descriptor_indexes: Vec<RafxDescriptorIndex>,
 
     // // --- gl-specific ---
     // // Now embedded by spirv_cross in the shader
@@ -315,14 +321,13 @@ impl RafxRootSignatureGles3 {
             } else {
                 let descriptor_index = RafxDescriptorIndex(descriptors.len() as u32);
 
-                let uniform_block_binding =
-                    if resource.resource_type == RafxResourceType::UNIFORM_BUFFER {
-                        let uniform_block_binding = uniform_block_descriptors.len() as u32;
-                        uniform_block_descriptors.push(descriptor_index);
-                        Some(uniform_block_binding)
-                    } else {
-                        None
-                    };
+                let uniform_block_binding = if resource.resource_type.is_uniform_buffer() {
+                    let uniform_block_binding = uniform_block_descriptors.len() as u32;
+                    uniform_block_descriptors.push(descriptor_index);
+                    Some(uniform_block_binding)
+                } else {
+                    None
+                };
 
                 // Add it to the descriptor list
                 descriptors.push(DescriptorInfo {
@@ -337,6 +342,7 @@ impl RafxRootSignatureGles3 {
                     uniform_block_binding,
                     descriptor_data_offset_in_set,
                     sampler_descriptor_index: None, // we set this later
+                    dynamic_offset_index: None,     // we set this later
                     gl_name: gl_name_cstr,
                     first_location_index,
                 });
@@ -350,6 +356,10 @@ impl RafxRootSignatureGles3 {
                     .binding_to_descriptor_index
                     .insert(resource.binding, descriptor_index);
 
+                if resource.resource_type.is_dynamic_uniform_buffer() {
+                    layout.dynamic_descriptor_indexes.push(descriptor_index);
+                }
+
                 if resource
                     .resource_type
                     .intersects(RafxResourceType::TEXTURE | RafxResourceType::TEXTURE_READ_WRITE)
@@ -392,6 +402,15 @@ impl RafxRootSignatureGles3 {
                 Some(sampler_descriptor_index);
         }
 
+        for layout in &mut layouts {
+            layout
+                .dynamic_descriptor_indexes
+                .sort_by_key(|x| descriptors[x.0 as usize].binding);
+            for (i, descriptor_index) in layout.dynamic_descriptor_indexes.iter().enumerate() {
+                descriptors[descriptor_index.0 as usize].dynamic_offset_index = Some(i as u32);
+            }
+        }
+
         let inner = RafxRootSignatureGles3Inner {
             device_context: device_context.clone(),
             pipeline_type,
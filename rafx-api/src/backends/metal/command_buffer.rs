@@ -4,10 +4,10 @@ use crate::metal::{
     RafxRootSignatureMetal, RafxTextureMetal,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorRenderTargetBinding,
-    RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding, RafxExtents3D,
-    RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPipelineType, RafxResourceState,
-    RafxResult, RafxTextureBarrier, RafxVertexBufferBinding,
+    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxCmdCopyTextureToBufferParams,
+    RafxColorRenderTargetBinding, RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding,
+    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPipelineType,
+    RafxResourceState, RafxResult, RafxSubpassDef, RafxTextureBarrier, RafxVertexBufferBinding,
 };
 use fnv::FnvHashSet;
 use metal_rs::{
@@ -335,6 +335,19 @@ impl RafxCommandBufferMetal {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on metal")?
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Renderpasses with multiple subpasses are not supported on metal")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         // no action necessary
         Ok(())
@@ -582,6 +595,22 @@ impl RafxCommandBufferMetal {
         )
     }
 
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+        &self,
+        root_signature: &RafxRootSignatureMetal,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandleMetal,
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        // Buffers are referenced by the argument buffer, so there is no way to offset them without
+        // encoding the argument buffer again. Offsets must be baked into the descriptor set instead
+        if dynamic_offsets.iter().any(|&x| x != 0) {
+            Err("Non-zero dynamic uniform buffer offsets are not supported on metal")?;
+        }
+
+        self.cmd_bind_descriptor_set_handle(root_signature, set_index, descriptor_set_handle)
+    }
+
     fn do_bind_descriptor_set(
         &self,
         inner: &RafxCommandBufferMetalInner,
@@ -947,4 +976,67 @@ impl RafxCommandBufferMetal {
         );
         Ok(())
     }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureMetal,
+        dst_buffer: &RafxBufferMetal,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let mut inner = self.inner.borrow_mut();
+        let blit_encoder = inner.blit_encoder.as_ref();
+        let blit_encoder = match blit_encoder {
+            Some(x) => x,
+            None => {
+                let result: RafxResult<&metal_rs::BlitCommandEncoderRef> =
+                    objc::rc::autoreleasepool(|| {
+                        Self::do_end_current_encoders(&self.queue, &mut *inner, false)?;
+                        let encoder = inner
+                            .command_buffer
+                            .as_ref()
+                            .unwrap()
+                            .new_blit_command_encoder();
+                        inner.blit_encoder = Some(encoder.to_owned());
+                        Ok(inner.blit_encoder.as_ref().unwrap().as_ref())
+                    });
+                result?
+            }
+        };
+
+        let texture_def = src_texture.texture_def();
+        let width = 1.max(texture_def.extents.width >> params.mip_level);
+        let height = 1.max(texture_def.extents.height >> params.mip_level);
+        let depth = 1.max(texture_def.extents.depth >> params.mip_level);
+
+        // Rows are tightly packed in the destination buffer
+        let format = texture_def.format;
+        let block_size_in_bytes = format.block_or_pixel_size_in_bytes();
+        let block_width_in_pixels = format.block_width_in_pixels();
+        let texture_width_in_blocks =
+            rafx_base::memory::round_size_up_to_alignment_u32(width, block_width_in_pixels)
+                / block_width_in_pixels;
+
+        let destination_bytes_per_row = texture_width_in_blocks * block_size_in_bytes;
+        let destination_bytes_per_image = height * destination_bytes_per_row;
+
+        let source_size = MTLSize {
+            width: width as _,
+            height: height as _,
+            depth: depth as _,
+        };
+
+        blit_encoder.copy_from_texture_to_buffer(
+            src_texture.metal_texture(),
+            params.array_layer as _,
+            params.mip_level as _,
+            MTLOrigin { x: 0, y: 0, z: 0 },
+            source_size,
+            dst_buffer.metal_buffer(),
+            params.buffer_offset as _,
+            destination_bytes_per_row as _,
+            destination_bytes_per_image as _,
+            MTLBlitOption::empty(),
+        );
+        Ok(())
+    }
 }
@@ -431,6 +431,7 @@ impl RafxDescriptorSetArrayMetal {
                 }
             }
             RafxResourceType::UNIFORM_BUFFER
+            | RafxResourceType::UNIFORM_BUFFER_DYNAMIC
             | RafxResourceType::BUFFER
             | RafxResourceType::BUFFER_READ_WRITE => {
                 let buffers = update.elements.buffers.ok_or_else(||
@@ -57,6 +57,8 @@ impl RafxDeviceContextMetalInner {
             upload_buffer_texture_alignment: 16,
             upload_buffer_texture_row_alignment: 1,
             supports_clamp_to_border_color: true, //TODO: Check for iOS support
+            supports_subpasses: false,
+            supports_dynamic_uniform_buffers: false,
             max_vertex_attribute_count: 31,
         };
 
@@ -316,7 +318,12 @@ impl RafxDeviceContextMetal {
                 .inner
                 .metal_features
                 .pixel_format_capabilities(candidate.into());
-            if capabilities.contains(required_capabilities) {
+            // Formats the device doesn't support at all (i.e. BC formats on iOS) have no
+            // capabilities
+            let can_sample =
+                !resource_type.intersects(RafxResourceType::TEXTURE) || !capabilities.is_empty();
+
+            if capabilities.contains(required_capabilities) && can_sample {
                 return Some(candidate);
             }
         }
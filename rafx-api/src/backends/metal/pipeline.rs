@@ -84,6 +84,10 @@ impl RafxPipelineMetal {
         device_context: &RafxDeviceContextMetal,
         pipeline_def: &RafxGraphicsPipelineDef,
     ) -> RafxResult<Self> {
+        if pipeline_def.subpass.is_some() {
+            Err("Renderpasses with multiple subpasses are not supported on metal")?;
+        }
+
         let pipeline = metal_rs::RenderPipelineDescriptor::new();
 
         let mut vertex_function = None;
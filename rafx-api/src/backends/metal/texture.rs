@@ -158,6 +158,15 @@ impl RafxTextureMetal {
             descriptor.set_storage_mode(RafxMemoryUsage::GpuOnly.mtl_storage_mode());
             descriptor.set_cpu_cache_mode(RafxMemoryUsage::GpuOnly.mtl_cpu_cache_mode());
             descriptor.set_resource_options(RafxMemoryUsage::GpuOnly.mtl_resource_options());
+
+            // Memoryless textures are only supported on iOS
+            #[cfg(target_os = "ios")]
+            if texture_def
+                .resource_type
+                .intersects(RafxResourceType::TRANSIENT_ATTACHMENT)
+            {
+                descriptor.set_storage_mode(metal_rs::MTLStorageMode::Memoryless);
+            }
             descriptor.set_texture_type(mtl_texture_type);
             descriptor.set_array_length(mtl_array_length as _);
             descriptor.set_sample_count(texture_def.sample_count.into());
@@ -0,0 +1,96 @@
+use crate::vulkan::RafxDeviceContextVulkan;
+use crate::*;
+use ash::vk;
+use std::sync::Arc;
+
+/// A single allocation shared by multiple images and/or buffers. Every resource placed in it holds
+/// a reference, so the memory is freed once all of them have been destroyed.
+#[derive(Debug)]
+pub struct RafxAliasedAllocationVulkan {
+    device_context: RafxDeviceContextVulkan,
+    allocation: vk_mem::Allocation,
+    allocation_info: vk_mem::AllocationInfo,
+}
+
+impl RafxAliasedAllocationVulkan {
+    // Allocates memory large enough and suitably aligned for all the given requirements. Returns
+    // None if no memory type is supported by all of them.
+    pub(crate) fn new(
+        device_context: &RafxDeviceContextVulkan,
+        memory_requirements: &[vk::MemoryRequirements],
+    ) -> RafxResult<Option<Arc<Self>>> {
+        let mut combined_requirements = vk::MemoryRequirements {
+            size: 0,
+            alignment: 1,
+            memory_type_bits: !0,
+        };
+
+        for requirements in memory_requirements {
+            combined_requirements.size = combined_requirements.size.max(requirements.size);
+            combined_requirements.alignment =
+                combined_requirements.alignment.max(requirements.alignment);
+            combined_requirements.memory_type_bits &= requirements.memory_type_bits;
+        }
+
+        if combined_requirements.memory_type_bits == 0 {
+            return Ok(None);
+        }
+
+        let allocation_create_info = vk_mem::AllocationCreateInfo {
+            usage: vk_mem::MemoryUsage::GpuOnly,
+            flags: vk_mem::AllocationCreateFlags::NONE,
+            required_flags: vk::MemoryPropertyFlags::empty(),
+            preferred_flags: vk::MemoryPropertyFlags::empty(),
+            memory_type_bits: 0, // Already restricted by the memory requirements
+            pool: None,
+            user_data: None,
+        };
+
+        let (allocation, allocation_info) = device_context
+            .allocator()
+            .allocate_memory(&combined_requirements, &allocation_create_info)
+            .map_err(|e| {
+                log::error!("Error allocating aliased memory {:?}", e);
+                vk::Result::ERROR_UNKNOWN
+            })?;
+
+        log::trace!(
+            "Allocated {} bytes of aliased memory for {} resources",
+            combined_requirements.size,
+            memory_requirements.len()
+        );
+
+        Ok(Some(Arc::new(RafxAliasedAllocationVulkan {
+            device_context: device_context.clone(),
+            allocation,
+            allocation_info,
+        })))
+    }
+
+    pub fn allocation(&self) -> vk_mem::Allocation {
+        self.allocation
+    }
+
+    pub fn allocation_info(&self) -> &vk_mem::AllocationInfo {
+        &self.allocation_info
+    }
+
+    pub fn vk_device_memory(&self) -> vk::DeviceMemory {
+        self.allocation_info.get_device_memory()
+    }
+
+    pub fn vk_offset(&self) -> vk::DeviceSize {
+        self.allocation_info.get_offset() as vk::DeviceSize
+    }
+}
+
+impl Drop for RafxAliasedAllocationVulkan {
+    fn drop(&mut self) {
+        log::trace!("destroying RafxAliasedAllocationVulkan");
+        self.device_context
+            .allocator()
+            .free_memory(&self.allocation)
+            .unwrap();
+        log::trace!("destroyed RafxAliasedAllocationVulkan");
+    }
+}
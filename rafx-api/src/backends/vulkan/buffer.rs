@@ -1,8 +1,9 @@
-use crate::vulkan::RafxDeviceContextVulkan;
+use crate::vulkan::{RafxAliasedAllocationVulkan, RafxDeviceContextVulkan};
 use crate::*;
 use ash::version::DeviceV1_0;
 use ash::vk;
 use rafx_base::trust_cell::TrustCell;
+use std::sync::Arc;
 
 #[derive(Copy, Clone, Debug)]
 pub struct RafxBufferRaw {
@@ -15,6 +16,9 @@ pub struct RafxBufferVulkan {
     device_context: RafxDeviceContextVulkan,
     allocation_info: TrustCell<vk_mem::AllocationInfo>,
     buffer_raw: Option<RafxBufferRaw>,
+    // Set if the buffer shares its memory with other buffers, in which case buffer_raw.allocation
+    // is the shared allocation and is only freed once all of them are destroyed
+    aliased_allocation: Option<Arc<RafxAliasedAllocationVulkan>>,
 
     buffer_def: RafxBufferDef,
     uniform_texel_view: Option<vk::BufferView>,
@@ -34,7 +38,13 @@ impl RafxBufferVulkan {
         self.storage_texel_view
     }
 
+    // Buffers placed in an aliased allocation can't be taken, the allocation may still be used by
+    // other buffers
     pub fn take_raw(mut self) -> Option<RafxBufferRaw> {
+        if self.aliased_allocation.is_some() {
+            return None;
+        }
+
         let mut raw = None;
         std::mem::swap(&mut raw, &mut self.buffer_raw);
         raw
@@ -112,6 +122,126 @@ impl RafxBufferVulkan {
         buffer_def: &RafxBufferDef,
     ) -> RafxResult<Self> {
         buffer_def.verify();
+        let (buffer_info, allocation_create_info) =
+            Self::buffer_create_info(device_context, buffer_def);
+
+        //TODO: Better way of handling allocator errors
+        let (buffer, allocation, allocation_info) = device_context
+            .allocator()
+            .create_buffer(&buffer_info, &allocation_create_info)
+            .map_err(|e| {
+                log::error!("Error creating buffer {:?}", e);
+                vk::Result::ERROR_UNKNOWN
+            })?;
+
+        let buffer_raw = RafxBufferRaw { buffer, allocation };
+        Self::from_raw(
+            device_context,
+            buffer_def,
+            buffer_raw,
+            allocation_info,
+            None,
+        )
+    }
+
+    // Creates the buffers in a single allocation. Only GpuOnly buffers can be aliased. If no
+    // memory type can back all of them, they are created separately.
+    pub fn new_aliased(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<Self>> {
+        let device = device_context.device();
+
+        let mut buffers = Vec::with_capacity(buffer_defs.len());
+        for buffer_def in buffer_defs {
+            buffer_def.verify();
+            if buffer_def.memory_usage != RafxMemoryUsage::GpuOnly {
+                Self::destroy_unbound_buffers(device_context, &buffers);
+                Err("Only buffers with RafxMemoryUsage::GpuOnly can be aliased")?;
+            }
+
+            let (buffer_info, _) = Self::buffer_create_info(device_context, buffer_def);
+            match unsafe { device.create_buffer(&buffer_info, None) } {
+                Ok(buffer) => buffers.push(buffer),
+                Err(e) => {
+                    Self::destroy_unbound_buffers(device_context, &buffers);
+                    Err(e)?
+                }
+            }
+        }
+
+        let memory_requirements: Vec<_> = buffers
+            .iter()
+            .map(|buffer| unsafe { device.get_buffer_memory_requirements(*buffer) })
+            .collect();
+
+        let aliased_allocation =
+            match RafxAliasedAllocationVulkan::new(device_context, &memory_requirements) {
+                Ok(Some(aliased_allocation)) => aliased_allocation,
+                Ok(None) => {
+                    log::trace!("Buffers have no memory type in common, not aliasing them");
+                    Self::destroy_unbound_buffers(device_context, &buffers);
+                    return buffer_defs
+                        .iter()
+                        .map(|buffer_def| Self::new(device_context, buffer_def))
+                        .collect();
+                }
+                Err(e) => {
+                    Self::destroy_unbound_buffers(device_context, &buffers);
+                    Err(e)?
+                }
+            };
+
+        for &buffer in &buffers {
+            let result = unsafe {
+                device.bind_buffer_memory(
+                    buffer,
+                    aliased_allocation.vk_device_memory(),
+                    aliased_allocation.vk_offset(),
+                )
+            };
+
+            if let Err(e) = result {
+                Self::destroy_unbound_buffers(device_context, &buffers);
+                Err(e)?
+            }
+        }
+
+        let mut aliased_buffers = Vec::with_capacity(buffer_defs.len());
+        for (buffer, buffer_def) in buffers.into_iter().zip(buffer_defs) {
+            let buffer_raw = RafxBufferRaw {
+                buffer,
+                allocation: aliased_allocation.allocation(),
+            };
+
+            aliased_buffers.push(Self::from_raw(
+                device_context,
+                buffer_def,
+                buffer_raw,
+                aliased_allocation.allocation_info().clone(),
+                Some(aliased_allocation.clone()),
+            )?);
+        }
+
+        Ok(aliased_buffers)
+    }
+
+    fn destroy_unbound_buffers(
+        device_context: &RafxDeviceContextVulkan,
+        buffers: &[vk::Buffer],
+    ) {
+        for &buffer in buffers {
+            unsafe {
+                device_context.device().destroy_buffer(buffer, None);
+            }
+        }
+    }
+
+    // Determines how to create the buffer and the memory backing it
+    fn buffer_create_info(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_def: &RafxBufferDef,
+    ) -> (vk::BufferCreateInfo, vk_mem::AllocationCreateInfo) {
         let mut allocation_size = buffer_def.size;
         if buffer_def
             .resource_type
@@ -128,8 +258,10 @@ impl RafxBufferVulkan {
             buffer_def.format != RafxFormat::UNDEFINED,
         );
 
+        // GpuToCpu buffers are the destination of readback copies
         if buffer_def.memory_usage == RafxMemoryUsage::GpuOnly
             || buffer_def.memory_usage == RafxMemoryUsage::CpuToGpu
+            || buffer_def.memory_usage == RafxMemoryUsage::GpuToCpu
         {
             usage_flags |= vk::BufferUsageFlags::TRANSFER_DST;
         }
@@ -156,21 +288,25 @@ impl RafxBufferVulkan {
             .usage(usage_flags)
             .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
-        //TODO: Better way of handling allocator errors
-        let (buffer, allocation, allocation_info) = device_context
-            .allocator()
-            .create_buffer(&buffer_info, &allocation_create_info)
-            .map_err(|e| {
-                log::error!("Error creating buffer {:?}", e);
-                vk::Result::ERROR_UNKNOWN
-            })?;
+        (*buffer_info, allocation_create_info)
+    }
 
-        let buffer_raw = RafxBufferRaw { buffer, allocation };
+    fn from_raw(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_def: &RafxBufferDef,
+        buffer_raw: RafxBufferRaw,
+        allocation_info: vk_mem::AllocationInfo,
+        aliased_allocation: Option<Arc<RafxAliasedAllocationVulkan>>,
+    ) -> RafxResult<Self> {
+        let usage_flags = super::util::resource_type_buffer_usage_flags(
+            buffer_def.resource_type,
+            buffer_def.format != RafxFormat::UNDEFINED,
+        );
 
         log::trace!(
             "Buffer {:?} crated with size {} (always mapped: {:?})",
             buffer_raw.buffer,
-            buffer_info.size,
+            buffer_def.size,
             buffer_def.always_mapped
         );
 
@@ -233,6 +369,7 @@ impl RafxBufferVulkan {
             device_context: device_context.clone(),
             allocation_info: TrustCell::new(allocation_info),
             buffer_raw: Some(buffer_raw),
+            aliased_allocation,
             buffer_def: buffer_def.clone(),
             uniform_texel_view,
             storage_texel_view,
@@ -263,10 +400,17 @@ impl Drop for RafxBufferVulkan {
                 self.buffer_def.always_mapped
             );
 
-            self.device_context
-                .allocator()
-                .destroy_buffer(buffer_raw.buffer, &buffer_raw.allocation)
-                .unwrap();
+            if self.aliased_allocation.is_some() {
+                // The memory is freed when the last buffer placed in it drops the aliased allocation
+                unsafe {
+                    device.destroy_buffer(buffer_raw.buffer, None);
+                }
+            } else {
+                self.device_context
+                    .allocator()
+                    .destroy_buffer(buffer_raw.buffer, &buffer_raw.allocation)
+                    .unwrap();
+            }
         }
 
         log::trace!("destroyed RafxBufferVulkanInner");
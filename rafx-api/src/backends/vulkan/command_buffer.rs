@@ -110,6 +110,15 @@ impl RafxCommandBufferVulkan {
         &self,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+    ) -> RafxResult<()> {
+        self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, &[])
+    }
+
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<()> {
         if self.has_active_renderpass.load(Ordering::Relaxed) {
             self.cmd_end_render_pass()?;
@@ -127,12 +136,14 @@ impl RafxCommandBufferVulkan {
                 &self.device_context,
                 color_targets,
                 depth_target.as_ref(),
+                subpasses,
             )?;
             let framebuffer = resource_cache.framebuffer_cache.get_or_create_framebuffer(
                 &self.device_context,
                 &renderpass,
                 color_targets,
                 depth_target.as_ref(),
+                subpasses,
             )?;
 
             (renderpass, framebuffer)
@@ -250,6 +261,20 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        if !self.has_active_renderpass.load(Ordering::Relaxed) {
+            Err("cmd_next_subpass was called outside of a renderpass")?;
+        }
+
+        unsafe {
+            self.device_context
+                .device()
+                .cmd_next_subpass(self.vk_command_buffer, vk::SubpassContents::INLINE);
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         unsafe {
             self.device_context
@@ -405,6 +430,33 @@ impl RafxCommandBufferVulkan {
         set_index: u32,
         descriptor_set_handle: &RafxDescriptorSetHandleVulkan,
     ) -> RafxResult<()> {
+        self.cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+            root_signature,
+            set_index,
+            descriptor_set_handle,
+            &[],
+        )
+    }
+
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+        &self,
+        root_signature: &RafxRootSignatureVulkan,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandleVulkan,
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        let dynamic_descriptor_count = root_signature.inner.layouts[set_index as usize]
+            .dynamic_descriptor_indexes
+            .len();
+        if dynamic_offsets.len() != dynamic_descriptor_count {
+            Err(format!(
+                "Descriptor set {} has {} dynamic descriptors but {} dynamic offsets were provided",
+                set_index,
+                dynamic_descriptor_count,
+                dynamic_offsets.len()
+            ))?;
+        }
+
         let bind_point = root_signature.pipeline_type();
 
         unsafe {
@@ -414,7 +466,7 @@ impl RafxCommandBufferVulkan {
                 root_signature.vk_pipeline_layout(),
                 set_index,
                 &[descriptor_set_handle.0],
-                &[],
+                dynamic_offsets,
             )
         }
 
@@ -776,6 +828,47 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureVulkan,
+        dst_buffer: &RafxBufferVulkan,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let texture_def = src_texture.texture_def();
+
+        let width = 1.max(texture_def.extents.width >> params.mip_level);
+        let height = 1.max(texture_def.extents.height >> params.mip_level);
+        let depth = 1.max(texture_def.extents.depth >> params.mip_level);
+
+        unsafe {
+            self.device_context.device().cmd_copy_image_to_buffer(
+                self.vk_command_buffer,
+                src_texture.vk_image(),
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst_buffer.vk_buffer(),
+                &[vk::BufferImageCopy {
+                    image_extent: vk::Extent3D {
+                        width,
+                        height,
+                        depth,
+                    },
+                    image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: src_texture.vk_aspect_mask(),
+                        mip_level: params.mip_level as u32,
+                        base_array_layer: params.array_layer as u32,
+                        layer_count: 1,
+                    },
+                    buffer_offset: params.buffer_offset,
+                    buffer_image_height: 0,
+                    buffer_row_length: 0,
+                }],
+            );
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_blit_image(
         &self,
         src_texture: &RafxTextureVulkan,
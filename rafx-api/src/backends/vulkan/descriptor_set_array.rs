@@ -429,6 +429,55 @@ impl RafxDescriptorSetArrayVulkan {
                         .build(),
                 );
             }
+            RafxResourceType::INPUT_ATTACHMENT => {
+                let textures = update.elements.textures.ok_or_else(||
+                    format!(
+                        "Tried to update binding {:?} (set: {:?} binding: {} name: {:?} type: {:?}) but the texture element list was None",
+                        update.descriptor_key,
+                        descriptor.set_index,
+                        descriptor.binding,
+                        descriptor.name,
+                        descriptor.resource_type,
+                    )
+                )?;
+                let begin_index =
+                    (descriptor_first_update_data + update.dst_element_offset) as usize;
+                assert!(begin_index + textures.len() <= self.update_data.update_data_count);
+
+                // Modify the update data
+                let mut next_index = begin_index;
+                for texture in textures {
+                    let image_info = &mut self.update_data.image_infos[next_index];
+                    next_index += 1;
+
+                    image_info.image_view = texture.vk_texture().unwrap().vk_srv_view().ok_or_else(|| {
+                        format!(
+                            "Tried to update binding {:?} (set: {:?} binding: {} name: {:?} type: {:?}) as an input attachment but there is no srv view. The texture must be created with RafxResourceType::INPUT_ATTACHMENT",
+                            update.descriptor_key,
+                            descriptor.set_index,
+                            descriptor.binding,
+                            descriptor.name,
+                            descriptor.resource_type,
+                        )
+                    })?;
+
+                    // Must match the layout of the attachment reference in the subpass that
+                    // reads it
+                    image_info.image_layout = if texture.texture_def().format.has_depth_or_stencil()
+                    {
+                        vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+                    } else {
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+                    };
+                }
+
+                // Queue a descriptor write
+                self.pending_writes.push(
+                    write_descriptor_builder
+                        .image_info(&self.update_data.image_infos[begin_index..next_index])
+                        .build(),
+                );
+            }
             RafxResourceType::TEXTURE_READ_WRITE => {
                 let textures = update.elements.textures.ok_or_else(||
                     format!(
@@ -515,13 +564,9 @@ impl RafxDescriptorSetArrayVulkan {
                 );
             }
             RafxResourceType::UNIFORM_BUFFER
+            | RafxResourceType::UNIFORM_BUFFER_DYNAMIC
             | RafxResourceType::BUFFER
             | RafxResourceType::BUFFER_READ_WRITE => {
-                if descriptor.vk_type == vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC {
-                    //TODO: Add support for dynamic uniforms
-                    unimplemented!();
-                }
-
                 let buffers = update.elements.buffers.ok_or_else(||
                     format!(
                         "Tried to update binding {:?} (set: {:?} binding: {} name: {:?} type: {:?}) but the buffers element list was None",
@@ -555,6 +600,20 @@ impl RafxDescriptorSetArrayVulkan {
                             buffer_info.range = offset_size[buffer_index].size;
                         }
                     }
+
+                    // The dynamic offset is added to the offset when binding, so the range can't
+                    // extend to the end of the buffer
+                    if descriptor.vk_type == vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC
+                        && buffer_info.range == vk::WHOLE_SIZE
+                    {
+                        Err(format!(
+                            "Tried to update binding {:?} (set: {:?} binding: {} name: {:?}) but dynamic uniform buffers must be given a size",
+                            update.descriptor_key,
+                            descriptor.set_index,
+                            descriptor.binding,
+                            descriptor.name,
+                        ))?;
+                    }
                 }
 
                 // Queue a descriptor write
@@ -82,6 +82,9 @@ pub struct RafxDeviceContextVulkanInner {
 
     device: ash::Device,
     allocator: vk_mem::Allocator,
+    // Shared by all pipelines created with this device. The driver uses it to skip recompiling
+    // pipeline state it has already seen, and its contents can be saved and restored across runs.
+    pipeline_cache: vk::PipelineCache,
     destroyed: AtomicBool,
     entry: Arc<VkEntry>,
     instance: ash::Instance,
@@ -103,6 +106,8 @@ impl Drop for RafxDeviceContextVulkanInner {
             unsafe {
                 log::trace!("destroying device");
                 self.allocator.destroy();
+                self.device
+                    .destroy_pipeline_cache(self.pipeline_cache, None);
                 self.device.destroy_device(None);
                 //self.surface_loader.destroy_surface(self.surface, None);
                 log::trace!("destroyed device");
@@ -167,12 +172,18 @@ impl RafxDeviceContextVulkanInner {
             upload_buffer_texture_row_alignment: limits.optimal_buffer_copy_row_pitch_alignment
                 as u32,
             supports_clamp_to_border_color: true,
+            supports_subpasses: true,
+            supports_dynamic_uniform_buffers: true,
             max_vertex_attribute_count: limits.max_vertex_input_attributes,
         };
 
         let resource_cache = RafxDeviceVulkanResourceCache::default();
         let descriptor_heap = RafxDescriptorHeapVulkan::new(&logical_device)?;
 
+        let pipeline_cache = unsafe {
+            logical_device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::builder(), None)?
+        };
+
         #[cfg(debug_assertions)]
         #[cfg(feature = "track-device-contexts")]
         let all_contexts = {
@@ -194,6 +205,7 @@ impl RafxDeviceContextVulkanInner {
             physical_device_info,
             device: logical_device,
             allocator,
+            pipeline_cache,
             destroyed: AtomicBool::new(false),
 
             #[cfg(debug_assertions)]
@@ -319,6 +331,10 @@ impl RafxDeviceContextVulkan {
         &self.inner.allocator
     }
 
+    pub fn pipeline_cache(&self) -> vk::PipelineCache {
+        self.inner.pipeline_cache
+    }
+
     pub fn queue_allocator(&self) -> &VkQueueAllocatorSet {
         &self.inner.queue_allocator
     }
@@ -390,6 +406,20 @@ impl RafxDeviceContextVulkan {
         RafxTextureVulkan::new(self, texture_def)
     }
 
+    pub fn create_aliased_textures(
+        &self,
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTextureVulkan>> {
+        RafxTextureVulkan::new_aliased(self, texture_defs)
+    }
+
+    pub fn create_aliased_buffers(
+        &self,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<RafxBufferVulkan>> {
+        RafxBufferVulkan::new_aliased(self, buffer_defs)
+    }
+
     pub fn create_buffer(
         &self,
         buffer_def: &RafxBufferDef,
@@ -476,6 +506,10 @@ impl RafxDeviceContextVulkan {
             features |= vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT;
         }
 
+        if resource_type.intersects(RafxResourceType::TEXTURE) {
+            features |= vk::FormatFeatureFlags::SAMPLED_IMAGE;
+        }
+
         do_find_supported_format(
             &self.inner.instance,
             self.inner.physical_device,
@@ -491,6 +525,35 @@ impl RafxDeviceContextVulkan {
     ) -> Option<RafxSampleCount> {
         do_find_supported_sample_count(self.limits(), candidates)
     }
+
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        unsafe {
+            Ok(self
+                .device()
+                .get_pipeline_cache_data(self.inner.pipeline_cache)?)
+        }
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        // The driver validates the header of the data and treats data from a different driver or
+        // device as empty, so it's safe to pass anything previously returned by
+        // pipeline_cache_data()
+        let create_info = vk::PipelineCacheCreateInfo::builder().initial_data(data);
+
+        unsafe {
+            let src_cache = self.device().create_pipeline_cache(&*create_info, None)?;
+            let result = self
+                .device()
+                .merge_pipeline_caches(self.inner.pipeline_cache, &[src_cache]);
+            self.device().destroy_pipeline_cache(src_cache, None);
+            result?;
+        }
+
+        Ok(())
+    }
 }
 
 pub fn do_find_supported_format(
@@ -34,7 +34,7 @@ impl Default for RafxDescriptorHeapPoolConfig {
             storage_buffers: 1024,
             dynamic_uniform_buffers: 1024,
             dynamic_storage_buffers: 0,
-            input_attachments: 0,
+            input_attachments: 1024,
         }
     }
 }
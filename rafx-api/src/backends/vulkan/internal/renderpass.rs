@@ -1,5 +1,8 @@
 use crate::vulkan::RafxDeviceContextVulkan;
-use crate::{RafxFormat, RafxLoadOp, RafxResult, RafxSampleCount, RafxStoreOp};
+use crate::{
+    RafxFormat, RafxLoadOp, RafxResult, RafxSampleCount, RafxStoreOp, RafxSubpassDef,
+    RafxSubpassInputAttachment,
+};
 use ash::version::DeviceV1_0;
 use ash::vk;
 use std::sync::Arc;
@@ -31,6 +34,17 @@ pub(crate) struct RafxRenderpassVulkanDef {
     pub(crate) resolve_attachments: Vec<Option<RafxRenderpassVulkanResolveAttachment>>,
     pub(crate) depth_attachment: Option<RafxRenderpassVulkanDepthAttachment>,
     pub(crate) sample_count: RafxSampleCount,
+    // If empty, the renderpass has a single subpass that uses all the attachments
+    pub(crate) subpasses: Vec<RafxSubpassDef>,
+}
+
+#[derive(Default)]
+struct RafxRenderpassVulkanSubpassAttachmentRefs {
+    color_attachments: Vec<vk::AttachmentReference>,
+    input_attachments: Vec<vk::AttachmentReference>,
+    depth_stencil_attachment: Option<vk::AttachmentReference>,
+    preserve_attachments: Vec<u32>,
+    used_attachments: Vec<u32>,
 }
 
 pub(crate) struct RafxRenderpassVulkanInner {
@@ -168,24 +182,106 @@ impl RafxRenderpassVulkan {
             );
         }
 
-        let mut subpass_description = vk::SubpassDescription::builder()
-            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(&color_attachment_refs);
+        let renderpass_create_info;
+        let subpass_descriptions;
+        let subpass_attachment_refs;
+        let subpass_dependencies;
+        if renderpass_def.subpasses.is_empty() {
+            let mut subpass_description = vk::SubpassDescription::builder()
+                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                .color_attachments(&color_attachment_refs);
 
-        if !resolve_attachment_refs.is_empty() {
-            subpass_description = subpass_description.resolve_attachments(&resolve_attachment_refs);
-        }
+            if !resolve_attachment_refs.is_empty() {
+                subpass_description =
+                    subpass_description.resolve_attachments(&resolve_attachment_refs);
+            }
 
-        if let Some(depth_stencil_attachment_ref) = depth_stencil_attachment_ref.as_ref() {
-            subpass_description =
-                subpass_description.depth_stencil_attachment(depth_stencil_attachment_ref);
-        }
+            if let Some(depth_stencil_attachment_ref) = depth_stencil_attachment_ref.as_ref() {
+                subpass_description =
+                    subpass_description.depth_stencil_attachment(depth_stencil_attachment_ref);
+            }
+
+            subpass_descriptions = vec![subpass_description.build()];
+
+            renderpass_create_info = vk::RenderPassCreateInfo::builder()
+                .attachments(&attachments)
+                .subpasses(&subpass_descriptions);
+        } else {
+            if !resolve_attachment_refs.is_empty() {
+                Err(
+                    "Resolve attachments are not supported in renderpasses with multiple subpasses",
+                )?;
+            }
+
+            subpass_attachment_refs = Self::subpass_attachment_refs(
+                renderpass_def,
+                depth_stencil_attachment_ref.map(|x| x.attachment),
+                attachments.len(),
+            )?;
+
+            subpass_descriptions = subpass_attachment_refs
+                .iter()
+                .map(|refs| {
+                    let mut subpass_description = vk::SubpassDescription::builder()
+                        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                        .color_attachments(&refs.color_attachments)
+                        .input_attachments(&refs.input_attachments)
+                        .preserve_attachments(&refs.preserve_attachments);
+
+                    if let Some(depth_stencil_attachment) = refs.depth_stencil_attachment.as_ref() {
+                        subpass_description =
+                            subpass_description.depth_stencil_attachment(depth_stencil_attachment);
+                    }
+
+                    subpass_description.build()
+                })
+                .collect::<Vec<_>>();
 
-        let subpass_descriptions = [subpass_description.build()];
+            // Anything a subpass writes to an attachment must be visible to all later subpasses,
+            // whether they read it as an input attachment or continue rendering to it. Barriers
+            // before the renderpass are handled by the caller, like for single subpass
+            // renderpasses.
+            let mut dependencies = Vec::default();
+            for dst_subpass in 1..subpass_descriptions.len() {
+                for src_subpass in 0..dst_subpass {
+                    dependencies.push(
+                        vk::SubpassDependency::builder()
+                            .src_subpass(src_subpass as u32)
+                            .dst_subpass(dst_subpass as u32)
+                            .src_stage_mask(
+                                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                                    | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                                    | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                            )
+                            .src_access_mask(
+                                vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                            )
+                            .dst_stage_mask(
+                                vk::PipelineStageFlags::FRAGMENT_SHADER
+                                    | vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                                    | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                                    | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                            )
+                            .dst_access_mask(
+                                vk::AccessFlags::INPUT_ATTACHMENT_READ
+                                    | vk::AccessFlags::COLOR_ATTACHMENT_READ
+                                    | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                            )
+                            .dependency_flags(vk::DependencyFlags::BY_REGION)
+                            .build(),
+                    );
+                }
+            }
+            subpass_dependencies = dependencies;
 
-        let renderpass_create_info = vk::RenderPassCreateInfo::builder()
-            .attachments(&attachments)
-            .subpasses(&subpass_descriptions);
+            renderpass_create_info = vk::RenderPassCreateInfo::builder()
+                .attachments(&attachments)
+                .subpasses(&subpass_descriptions)
+                .dependencies(&subpass_dependencies);
+        }
 
         let renderpass = unsafe {
             device_context
@@ -202,4 +298,129 @@ impl RafxRenderpassVulkan {
             inner: Arc::new(inner),
         })
     }
+
+    fn subpass_attachment_refs(
+        renderpass_def: &RafxRenderpassVulkanDef,
+        depth_stencil_attachment_index: Option<u32>,
+        attachment_count: usize,
+    ) -> RafxResult<Vec<RafxRenderpassVulkanSubpassAttachmentRefs>> {
+        let color_attachment_count = renderpass_def.color_attachments.len() as u32;
+        let mut subpass_attachment_refs = Vec::with_capacity(renderpass_def.subpasses.len());
+
+        // The first and last subpass that use each attachment, so that attachments can be
+        // preserved through the subpasses in between
+        let mut first_use = vec![None; attachment_count];
+        let mut last_use = vec![None; attachment_count];
+
+        for (subpass_index, subpass) in renderpass_def.subpasses.iter().enumerate() {
+            let mut refs = RafxRenderpassVulkanSubpassAttachmentRefs::default();
+            let mut used_attachments = Vec::default();
+
+            for &color_attachment in &subpass.color_attachments {
+                if color_attachment >= color_attachment_count {
+                    Err(format!(
+                        "Subpass {} uses color attachment {} but the renderpass only has {} color attachments",
+                        subpass_index, color_attachment, color_attachment_count
+                    ))?;
+                }
+
+                refs.color_attachments.push(
+                    vk::AttachmentReference::builder()
+                        .attachment(color_attachment)
+                        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                        .build(),
+                );
+                used_attachments.push(color_attachment);
+            }
+
+            if subpass.use_depth_stencil_attachment {
+                let depth_stencil_attachment_index =
+                    depth_stencil_attachment_index.ok_or_else(|| {
+                        format!(
+                            "Subpass {} uses the depth/stencil attachment but the renderpass does not have one",
+                            subpass_index
+                        )
+                    })?;
+
+                refs.depth_stencil_attachment = Some(
+                    vk::AttachmentReference::builder()
+                        .attachment(depth_stencil_attachment_index)
+                        .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+                        .build(),
+                );
+                used_attachments.push(depth_stencil_attachment_index);
+            }
+
+            for input_attachment in &subpass.input_attachments {
+                let (attachment, layout) = match input_attachment {
+                    RafxSubpassInputAttachment::Color(color_attachment) => {
+                        if *color_attachment >= color_attachment_count {
+                            Err(format!(
+                                "Subpass {} reads color attachment {} but the renderpass only has {} color attachments",
+                                subpass_index, color_attachment, color_attachment_count
+                            ))?;
+                        }
+
+                        (*color_attachment, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    }
+                    RafxSubpassInputAttachment::DepthStencil => {
+                        let depth_stencil_attachment_index = depth_stencil_attachment_index
+                            .ok_or_else(|| {
+                                format!(
+                                    "Subpass {} reads the depth/stencil attachment but the renderpass does not have one",
+                                    subpass_index
+                                )
+                            })?;
+
+                        (
+                            depth_stencil_attachment_index,
+                            vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+                        )
+                    }
+                };
+
+                // An attachment can only be in one layout within a subpass
+                if used_attachments.contains(&attachment) {
+                    Err(format!(
+                        "Subpass {} reads attachment {} as an input attachment but also renders to it",
+                        subpass_index, attachment
+                    ))?;
+                }
+
+                refs.input_attachments.push(
+                    vk::AttachmentReference::builder()
+                        .attachment(attachment)
+                        .layout(layout)
+                        .build(),
+                );
+                used_attachments.push(attachment);
+            }
+
+            for &attachment in &used_attachments {
+                first_use[attachment as usize].get_or_insert(subpass_index);
+                last_use[attachment as usize] = Some(subpass_index);
+            }
+
+            refs.used_attachments = used_attachments;
+            subpass_attachment_refs.push(refs);
+        }
+
+        // Attachments that are used before and after a subpass must be preserved by it
+        for (subpass_index, refs) in subpass_attachment_refs.iter_mut().enumerate() {
+            for attachment in 0..attachment_count {
+                if let (Some(first_use), Some(last_use)) =
+                    (first_use[attachment], last_use[attachment])
+                {
+                    if first_use < subpass_index
+                        && last_use > subpass_index
+                        && !refs.used_attachments.contains(&(attachment as u32))
+                    {
+                        refs.preserve_attachments.push(attachment as u32);
+                    }
+                }
+            }
+        }
+
+        Ok(subpass_attachment_refs)
+    }
 }
@@ -23,6 +23,7 @@ impl RafxRenderpassVulkanCache {
     pub(crate) fn renderpass_hash(
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> u64 {
         let mut hasher = FnvHasher::default();
         for color_target in color_targets {
@@ -31,6 +32,8 @@ impl RafxRenderpassVulkanCache {
             texture_def.sample_count.hash(&mut hasher);
             color_target.clear_value.hash(&mut hasher);
             color_target.load_op.hash(&mut hasher);
+            color_target.store_op.hash(&mut hasher);
+            color_target.resolve_target.is_some().hash(&mut hasher);
         }
 
         if let Some(depth_target) = &depth_target {
@@ -40,7 +43,11 @@ impl RafxRenderpassVulkanCache {
             depth_target.clear_value.hash(&mut hasher);
             depth_target.stencil_load_op.hash(&mut hasher);
             depth_target.depth_load_op.hash(&mut hasher);
+            depth_target.stencil_store_op.hash(&mut hasher);
+            depth_target.depth_store_op.hash(&mut hasher);
         }
+
+        subpasses.hash(&mut hasher);
         hasher.finish()
     }
 
@@ -48,6 +55,7 @@ impl RafxRenderpassVulkanCache {
         device_context: &RafxDeviceContextVulkan,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<RafxRenderpassVulkan> {
         let sample_count = if let Some(depth_target) = &depth_target {
             depth_target.texture.texture_def().sample_count
@@ -97,6 +105,7 @@ impl RafxRenderpassVulkanCache {
                 resolve_attachments,
                 depth_attachment,
                 sample_count,
+                subpasses: subpasses.to_vec(),
             },
         )
     }
@@ -106,14 +115,15 @@ impl RafxRenderpassVulkanCache {
         device_context: &RafxDeviceContextVulkan,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<RafxRenderpassVulkan> {
         //
         // Hash it
         //
-        let hash = Self::renderpass_hash(color_targets, depth_target);
+        let hash = Self::renderpass_hash(color_targets, depth_target, subpasses);
 
         self.cache.get_or_create(hash, || {
-            Self::create_renderpass(device_context, color_targets, depth_target)
+            Self::create_renderpass(device_context, color_targets, depth_target, subpasses)
         })
     }
 }
@@ -67,6 +67,10 @@ pub(crate) fn resource_type_image_usage_flags(
         usage_flags |= vk::ImageUsageFlags::STORAGE;
     }
 
+    if resource_type.intersects(RafxResourceType::INPUT_ATTACHMENT) {
+        usage_flags |= vk::ImageUsageFlags::INPUT_ATTACHMENT;
+    }
+
     usage_flags
 }
 
@@ -102,6 +106,9 @@ pub fn resource_type_to_descriptor_type(
         RafxResourceType::SAMPLER => Some(vk::DescriptorType::SAMPLER),
         RafxResourceType::TEXTURE => Some(vk::DescriptorType::SAMPLED_IMAGE),
         RafxResourceType::UNIFORM_BUFFER => Some(vk::DescriptorType::UNIFORM_BUFFER),
+        RafxResourceType::UNIFORM_BUFFER_DYNAMIC => {
+            Some(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+        }
         RafxResourceType::TEXTURE_READ_WRITE => Some(vk::DescriptorType::STORAGE_IMAGE),
         RafxResourceType::BUFFER => Some(vk::DescriptorType::STORAGE_BUFFER),
         RafxResourceType::BUFFER_READ_WRITE => Some(vk::DescriptorType::STORAGE_BUFFER),
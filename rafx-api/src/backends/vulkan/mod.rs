@@ -34,6 +34,9 @@ pub use texture::*;
 mod buffer;
 pub use buffer::*;
 
+mod aliased_allocation;
+pub use aliased_allocation::*;
+
 mod root_signature;
 pub use root_signature::*;
 
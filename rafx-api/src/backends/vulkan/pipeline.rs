@@ -79,12 +79,37 @@ impl RafxPipelineVulkan {
             None
         };
 
+        // Pipelines used in a renderpass with multiple subpasses must be created with a renderpass
+        // that has the same subpasses
+        let (subpasses, subpass_index, subpass_color_attachment_count) =
+            if let Some(subpass) = &pipeline_def.subpass {
+                let subpass_def = subpass
+                    .subpasses
+                    .get(subpass.subpass_index as usize)
+                    .ok_or_else(|| {
+                        format!(
+                            "Subpass index {} is out of range, the renderpass has {} subpasses",
+                            subpass.subpass_index,
+                            subpass.subpasses.len()
+                        )
+                    })?;
+
+                (
+                    subpass.subpasses.to_vec(),
+                    subpass.subpass_index,
+                    subpass_def.color_attachments.len(),
+                )
+            } else {
+                (vec![], 0, pipeline_def.color_formats.len())
+            };
+
         // Temporary renderpass, required to create pipeline but don't need to keep it
         let renderpass = device_context.create_renderpass(&RafxRenderpassVulkanDef {
             color_attachments,
             resolve_attachments,
             depth_attachment,
             sample_count: pipeline_def.sample_count,
+            subpasses,
         })?;
 
         let mut entry_point_names = vec![];
@@ -170,7 +195,7 @@ impl RafxPipelineVulkan {
         let depth_state = super::util::depth_state_to_create_info(pipeline_def.depth_state);
         let blend_state = super::util::blend_state_to_create_info(
             pipeline_def.blend_state,
-            pipeline_def.color_formats.len(),
+            subpass_color_attachment_count,
         );
 
         let dynamic_states = [
@@ -196,7 +221,7 @@ impl RafxPipelineVulkan {
             .dynamic_state(&dynamic_states_create_info)
             .layout(vk_root_signature.vk_pipeline_layout())
             .render_pass(renderpass.vk_renderpass())
-            .subpass(0)
+            .subpass(subpass_index)
             .base_pipeline_handle(vk::Pipeline::null())
             .base_pipeline_index(-1)
             .build();
@@ -210,7 +235,7 @@ impl RafxPipelineVulkan {
 
         let pipeline = unsafe {
             match device_context.device().create_graphics_pipelines(
-                vk::PipelineCache::null(),
+                device_context.pipeline_cache(),
                 &[pipeline_create_info],
                 None,
             ) {
@@ -268,7 +293,7 @@ impl RafxPipelineVulkan {
 
         let pipeline = unsafe {
             match device_context.device().create_compute_pipelines(
-                vk::PipelineCache::null(),
+                device_context.pipeline_cache(),
                 &[pipeline_create_info],
                 None,
             ) {
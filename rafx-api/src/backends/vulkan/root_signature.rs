@@ -214,19 +214,6 @@ impl RafxRootSignatureVulkan {
                     .descriptor_type(vk_descriptor_type)
                     .stage_flags(vk_stage_flags);
 
-                // Determine if flagged as root constant buffer/dynamic uniform buffer. If so, update
-                // the type. This was being done by detecting a pattern in the name string. For now
-                // this is dead code. It should probably be done by checking the descriptor type.
-                // let is_dynamic_uniform_buffer = false;
-                // if is_dynamic_uniform_buffer {
-                //     if resource.descriptor_count == 1 {
-                //         binding =
-                //             binding.descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC);
-                //     } else {
-                //         Err("Cannot use dynamic uniform buffer an array")?;
-                //     }
-                // }
-
                 let immutable_sampler = crate::internal_shared::find_immutable_sampler_index(
                     root_signature_def.immutable_samplers,
                     &resource.name,
@@ -282,22 +269,14 @@ impl RafxRootSignatureVulkan {
                         vk::DescriptorType::STORAGE_BUFFER_DYNAMIC
                     );
 
-                    // More than one dynamic descriptor not supported right now
-                    assert!(layout.dynamic_descriptor_indexes.is_empty());
-
                     //
-                    // Keep a lookup for dynamic descriptors
+                    // Keep a lookup for dynamic descriptors. The index is assigned once all
+                    // descriptors are known because dynamic offsets are ordered by binding
                     //
                     let descriptor_index = RafxDescriptorIndex(descriptors.len() as u32);
-                    let dynamic_descriptor_index =
-                        if binding.descriptor_type == vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC {
-                            layout.dynamic_descriptor_indexes.push(descriptor_index);
-                            Some(DynamicDescriptorIndex(
-                                (layout.dynamic_descriptor_indexes.len() - 1) as u32,
-                            ))
-                        } else {
-                            None
-                        };
+                    if binding.descriptor_type == vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC {
+                        layout.dynamic_descriptor_indexes.push(descriptor_index);
+                    }
 
                     let update_data_offset_in_set = Some(layout.update_data_count_per_set);
 
@@ -310,7 +289,7 @@ impl RafxRootSignatureVulkan {
                         binding: resource.binding,
                         element_count: resource.element_count_normalized(),
                         descriptor_index,
-                        dynamic_descriptor_index,
+                        dynamic_descriptor_index: None,
                         update_data_offset_in_set,
                         has_immutable_sampler: immutable_sampler.is_some(),
                         vk_type: binding.descriptor_type,
@@ -354,6 +333,19 @@ impl RafxRootSignatureVulkan {
             }
         }
 
+        //
+        // Dynamic offsets are passed in binding order when binding a descriptor set
+        //
+        for layout in &mut layouts {
+            layout
+                .dynamic_descriptor_indexes
+                .sort_by_key(|x| descriptors[x.0 as usize].binding);
+            for (i, descriptor_index) in layout.dynamic_descriptor_indexes.iter().enumerate() {
+                descriptors[descriptor_index.0 as usize].dynamic_descriptor_index =
+                    Some(DynamicDescriptorIndex(i as u32));
+            }
+        }
+
         //
         // Create descriptor set layouts
         //
@@ -408,6 +408,7 @@ impl RafxSwapchainVulkanInstance {
             let raw_image = RafxRawImageVulkan {
                 image: *image,
                 allocation: None,
+                aliased_allocation: None,
             };
 
             let format: RafxFormat = self.swapchain_info.surface_format.format.into();
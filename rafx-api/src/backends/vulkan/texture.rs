@@ -1,5 +1,5 @@
 use crate::types::RafxTextureDimensions;
-use crate::vulkan::RafxDeviceContextVulkan;
+use crate::vulkan::{RafxAliasedAllocationVulkan, RafxDeviceContextVulkan};
 use crate::*;
 use ash::version::DeviceV1_0;
 use ash::vk;
@@ -8,12 +8,14 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 // This is used to allow the underlying image/allocation to be removed from a RafxTextureVulkan,
-// or to init a RafxTextureVulkan with an existing image/allocation. If the allocation is none, we
-// will not destroy the image when RafxRawImageVulkan is dropped
+// or to init a RafxTextureVulkan with an existing image/allocation. If neither allocation is set,
+// we will not destroy the image when RafxRawImageVulkan is dropped. An image placed in an aliased
+// allocation is destroyed, but the memory is only freed once every image sharing it is destroyed.
 #[derive(Debug)]
 pub struct RafxRawImageVulkan {
     pub image: vk::Image,
     pub allocation: Option<vk_mem::Allocation>,
+    pub aliased_allocation: Option<Arc<RafxAliasedAllocationVulkan>>,
 }
 
 impl RafxRawImageVulkan {
@@ -30,6 +32,16 @@ impl RafxRawImageVulkan {
                 .unwrap();
             self.image = vk::Image::null();
             log::trace!("destroyed RafxImageVulkan");
+        } else if let Some(aliased_allocation) = self.aliased_allocation.take() {
+            log::trace!("destroying aliased RafxImageVulkan");
+            assert_ne!(self.image, vk::Image::null());
+            unsafe {
+                device_context.device().destroy_image(self.image, None);
+            }
+            self.image = vk::Image::null();
+            // Frees the memory if this was the last image placed in it
+            std::mem::drop(aliased_allocation);
+            log::trace!("destroyed aliased RafxImageVulkan");
         } else {
             log::trace!(
                 "RafxImageVulkan has no allocation associated with it, not destroying image"
@@ -41,7 +53,8 @@ impl RafxRawImageVulkan {
 
 impl Drop for RafxRawImageVulkan {
     fn drop(&mut self) {
-        assert!(self.allocation.is_none())
+        assert!(self.allocation.is_none());
+        assert!(self.aliased_allocation.is_none());
     }
 }
 
@@ -220,6 +233,196 @@ impl RafxTextureVulkan {
         Self::from_existing(device_context, None, texture_def)
     }
 
+    // Creates the textures in a single allocation. If no memory type can back all of them, they
+    // are created separately.
+    pub fn new_aliased(
+        device_context: &RafxDeviceContextVulkan,
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTextureVulkan>> {
+        let device = device_context.device();
+
+        let mut images = Vec::with_capacity(texture_defs.len());
+        for texture_def in texture_defs {
+            texture_def.verify();
+            let (image_create_info, _) = Self::image_create_info(texture_def);
+            match unsafe { device.create_image(&image_create_info, None) } {
+                Ok(image) => images.push(image),
+                Err(e) => {
+                    Self::destroy_unbound_images(device_context, &images);
+                    Err(e)?
+                }
+            }
+        }
+
+        let memory_requirements: Vec<_> = images
+            .iter()
+            .map(|image| unsafe { device.get_image_memory_requirements(*image) })
+            .collect();
+
+        let aliased_allocation =
+            match RafxAliasedAllocationVulkan::new(device_context, &memory_requirements) {
+                Ok(Some(aliased_allocation)) => aliased_allocation,
+                Ok(None) => {
+                    log::trace!("Textures have no memory type in common, not aliasing them");
+                    Self::destroy_unbound_images(device_context, &images);
+                    return texture_defs
+                        .iter()
+                        .map(|texture_def| Self::new(device_context, texture_def))
+                        .collect();
+                }
+                Err(e) => {
+                    Self::destroy_unbound_images(device_context, &images);
+                    Err(e)?
+                }
+            };
+
+        for &image in &images {
+            let result = unsafe {
+                device.bind_image_memory(
+                    image,
+                    aliased_allocation.vk_device_memory(),
+                    aliased_allocation.vk_offset(),
+                )
+            };
+
+            if let Err(e) = result {
+                Self::destroy_unbound_images(device_context, &images);
+                Err(e)?
+            }
+        }
+
+        let mut textures = Vec::with_capacity(texture_defs.len());
+        for (image, texture_def) in images.into_iter().zip(texture_defs) {
+            let raw_image = RafxRawImageVulkan {
+                image,
+                allocation: None,
+                aliased_allocation: Some(aliased_allocation.clone()),
+            };
+
+            textures.push(Self::from_existing(
+                device_context,
+                Some(raw_image),
+                texture_def,
+            )?);
+        }
+
+        Ok(textures)
+    }
+
+    fn destroy_unbound_images(
+        device_context: &RafxDeviceContextVulkan,
+        images: &[vk::Image],
+    ) {
+        for &image in images {
+            unsafe {
+                device_context.device().destroy_image(image, None);
+            }
+        }
+    }
+
+    // Determines how to create the image and the memory backing it
+    fn image_create_info(
+        texture_def: &RafxTextureDef
+    ) -> (vk::ImageCreateInfo, vk_mem::AllocationCreateInfo) {
+        let dimensions = texture_def
+            .dimensions
+            .determine_dimensions(texture_def.extents);
+        let image_type = match dimensions {
+            RafxTextureDimensions::Dim1D => vk::ImageType::TYPE_1D,
+            RafxTextureDimensions::Dim2D => vk::ImageType::TYPE_2D,
+            RafxTextureDimensions::Dim3D => vk::ImageType::TYPE_3D,
+            RafxTextureDimensions::Auto => panic!("dimensions() should not return auto"),
+        };
+
+        let is_cubemap = texture_def
+            .resource_type
+            .contains(RafxResourceType::TEXTURE_CUBE);
+        let format_vk = texture_def.format.into();
+
+        //
+        // Determine image usage flags
+        //
+        let mut usage_flags =
+            super::util::resource_type_image_usage_flags(texture_def.resource_type);
+        if texture_def
+            .resource_type
+            .intersects(RafxResourceType::RENDER_TARGET_COLOR)
+        {
+            usage_flags |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+        } else if texture_def
+            .resource_type
+            .intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
+        {
+            usage_flags |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+        }
+
+        // Transient attachments can't have any usages other than as an attachment
+        let is_transient = texture_def
+            .resource_type
+            .intersects(RafxResourceType::TRANSIENT_ATTACHMENT)
+            && !usage_flags.intersects(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE);
+        if is_transient {
+            usage_flags |= vk::ImageUsageFlags::TRANSIENT_ATTACHMENT;
+        }
+
+        if usage_flags.intersects(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE) {
+            usage_flags |= vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
+        }
+
+        //
+        // Determine image create flags
+        //
+        let mut create_flags = vk::ImageCreateFlags::empty();
+        if is_cubemap {
+            create_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
+        }
+        if image_type == vk::ImageType::TYPE_3D {
+            create_flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE_KHR
+        }
+
+        //TODO: Could check vkGetPhysicalDeviceFormatProperties for if we support the format for
+        // the various ways we might use it
+
+        // Lazily allocated memory is generally only available on tile-based GPUs. If it's not
+        // available, a normal allocation is used.
+        let preferred_flags = if is_transient {
+            vk::MemoryPropertyFlags::LAZILY_ALLOCATED
+        } else {
+            vk::MemoryPropertyFlags::empty()
+        };
+
+        let allocation_create_info = vk_mem::AllocationCreateInfo {
+            usage: vk_mem::MemoryUsage::GpuOnly,
+            flags: vk_mem::AllocationCreateFlags::NONE,
+            required_flags: vk::MemoryPropertyFlags::empty(),
+            preferred_flags,
+            memory_type_bits: 0, // Do not exclude any memory types
+            pool: None,
+            user_data: None,
+        };
+
+        let extent = vk::Extent3D {
+            width: texture_def.extents.width,
+            height: texture_def.extents.height,
+            depth: texture_def.extents.depth,
+        };
+
+        let image_create_info = vk::ImageCreateInfo::builder()
+            .image_type(image_type)
+            .extent(extent)
+            .mip_levels(texture_def.mip_count)
+            .array_layers(texture_def.array_length)
+            .format(format_vk)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .usage(usage_flags)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .samples(texture_def.sample_count.into())
+            .flags(create_flags);
+
+        (*image_create_info, allocation_create_info)
+    }
+
     // This path is mostly so we can wrap a provided swapchain image
     pub fn from_existing(
         device_context: &RafxDeviceContextVulkan,
@@ -252,70 +455,7 @@ impl RafxTextureVulkan {
         let image = if let Some(existing_image) = existing_image {
             existing_image
         } else {
-            //
-            // Determine image usage flags
-            //
-            let mut usage_flags =
-                super::util::resource_type_image_usage_flags(texture_def.resource_type);
-            if texture_def
-                .resource_type
-                .intersects(RafxResourceType::RENDER_TARGET_COLOR)
-            {
-                usage_flags |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
-            } else if texture_def
-                .resource_type
-                .intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
-            {
-                usage_flags |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
-            }
-
-            if usage_flags.intersects(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE) {
-                usage_flags |=
-                    vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
-            }
-
-            //
-            // Determine image create flags
-            //
-            let mut create_flags = vk::ImageCreateFlags::empty();
-            if is_cubemap {
-                create_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
-            }
-            if image_type == vk::ImageType::TYPE_3D {
-                create_flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE_KHR
-            }
-
-            //TODO: Could check vkGetPhysicalDeviceFormatProperties for if we support the format for
-            // the various ways we might use it
-
-            let allocation_create_info = vk_mem::AllocationCreateInfo {
-                usage: vk_mem::MemoryUsage::GpuOnly,
-                flags: vk_mem::AllocationCreateFlags::NONE,
-                required_flags: vk::MemoryPropertyFlags::empty(),
-                preferred_flags: vk::MemoryPropertyFlags::empty(),
-                memory_type_bits: 0, // Do not exclude any memory types
-                pool: None,
-                user_data: None,
-            };
-
-            let extent = vk::Extent3D {
-                width: texture_def.extents.width,
-                height: texture_def.extents.height,
-                depth: texture_def.extents.depth,
-            };
-
-            let image_create_info = vk::ImageCreateInfo::builder()
-                .image_type(image_type)
-                .extent(extent)
-                .mip_levels(texture_def.mip_count)
-                .array_layers(texture_def.array_length)
-                .format(format_vk)
-                .tiling(vk::ImageTiling::OPTIMAL)
-                .initial_layout(vk::ImageLayout::UNDEFINED)
-                .usage(usage_flags)
-                .sharing_mode(vk::SharingMode::EXCLUSIVE)
-                .samples(texture_def.sample_count.into())
-                .flags(create_flags);
+            let (image_create_info, allocation_create_info) = Self::image_create_info(texture_def);
 
             //let allocator = device.allocator().clone();
             let (image, allocation, _allocation_info) = device_context
@@ -329,6 +469,7 @@ impl RafxTextureVulkan {
             RafxRawImageVulkan {
                 image,
                 allocation: Some(allocation),
+                aliased_allocation: None,
             }
         };
 
@@ -374,10 +515,10 @@ impl RafxTextureVulkan {
             .components(vk::ComponentMapping::default())
             .subresource_range(*subresource_range);
 
-        // Create SRV without stencil
+        // Create SRV without stencil. Input attachments are also bound with this view.
         let srv_view = if texture_def
             .resource_type
-            .intersects(RafxResourceType::TEXTURE)
+            .intersects(RafxResourceType::TEXTURE | RafxResourceType::INPUT_ATTACHMENT)
         {
             image_view_create_info.subresource_range.aspect_mask &= !vk::ImageAspectFlags::STENCIL;
             unsafe {
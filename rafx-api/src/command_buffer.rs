@@ -17,10 +17,11 @@ use crate::metal::RafxCommandBufferMetal;
 #[cfg(feature = "rafx-vulkan")]
 use crate::vulkan::RafxCommandBufferVulkan;
 use crate::{
-    RafxBuffer, RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorRenderTargetBinding,
+    RafxBuffer, RafxBufferBarrier, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorRenderTargetBinding,
     RafxDepthStencilRenderTargetBinding, RafxDescriptorSetArray, RafxDescriptorSetHandle,
-    RafxIndexBufferBinding, RafxPipeline, RafxResult, RafxRootSignature, RafxTexture,
-    RafxTextureBarrier, RafxVertexBufferBinding,
+    RafxIndexBufferBinding, RafxPipeline, RafxResult, RafxRootSignature, RafxSubpassDef,
+    RafxTexture, RafxTextureBarrier, RafxVertexBufferBinding,
 };
 
 /// A list of commands recorded by the CPU and submitted to the GPU.
@@ -192,6 +193,74 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Begin a renderpass with multiple subpasses. The first subpass is started, use
+    /// `cmd_next_subpass` to advance to the next one. Only supported if
+    /// `RafxDeviceInfo::supports_subpasses` is true.
+    ///
+    /// Pipelines used within the renderpass must be created with `RafxGraphicsPipelineDef::subpass`
+    /// set to the same subpasses. Resolve targets are not supported.
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+        }
+    }
+
+    /// Advance to the next subpass of a renderpass begun with `cmd_begin_render_pass_with_subpasses`
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_next_subpass(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_next_subpass(),
+        }
+    }
+
     /// Finish the renderpass.
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         match self {
@@ -526,6 +595,71 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Binds a descriptor set for use by the shader in the currently bound pipeline, providing an
+    /// offset for each UNIFORM_BUFFER_DYNAMIC descriptor in the set. Offsets are in order of the
+    /// descriptors' binding index and are added to the offset the buffer was written with. They
+    /// must respect `RafxDeviceInfo::min_uniform_buffer_offset_alignment`.
+    ///
+    /// Only supported if `RafxDeviceInfo::supports_dynamic_uniform_buffers` is true.
+    pub fn cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+        &self,
+        root_signature: &RafxRootSignature,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandle,
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner
+                .cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+                    root_signature.vk_root_signature().unwrap(),
+                    set_index,
+                    descriptor_set_handle.vk_descriptor_set_handle().unwrap(),
+                    dynamic_offsets,
+                ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner
+                .cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+                    root_signature.metal_root_signature().unwrap(),
+                    set_index,
+                    descriptor_set_handle.metal_descriptor_set_handle().unwrap(),
+                    dynamic_offsets,
+                ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner
+                .cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+                    root_signature.gles2_root_signature().unwrap(),
+                    set_index,
+                    descriptor_set_handle.gles2_descriptor_set_handle().unwrap(),
+                    dynamic_offsets,
+                ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner
+                .cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+                    root_signature.gles3_root_signature().unwrap(),
+                    set_index,
+                    descriptor_set_handle.gles3_descriptor_set_handle().unwrap(),
+                    dynamic_offsets,
+                ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner
+                .cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+                    root_signature.empty_root_signature().unwrap(),
+                    set_index,
+                    descriptor_set_handle.empty_descriptor_set_handle().unwrap(),
+                    dynamic_offsets,
+                ),
+        }
+    }
+
     /// Draw primitives using the currently bound pipeline and vertex buffer
     pub fn cmd_draw(
         &self,
@@ -886,6 +1020,57 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Copy the contents of a texture into a buffer. This occurs on the GPU and allows reading
+    /// back resources that are not accessible to the CPU, i.e. render targets. The texture must be
+    /// in the COPY_SRC state. Not supported on GL ES.
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTexture,
+        dst_buffer: &RafxBuffer,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.vk_texture().unwrap(),
+                dst_buffer.vk_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.metal_texture().unwrap(),
+                dst_buffer.metal_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.gles2_texture().unwrap(),
+                dst_buffer.gles2_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.gles3_texture().unwrap(),
+                dst_buffer.gles3_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.empty_texture().unwrap(),
+                dst_buffer.empty_buffer().unwrap(),
+                params,
+            ),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
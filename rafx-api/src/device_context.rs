@@ -384,6 +384,96 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Create textures that share the same memory. Only one of them may be in use at a time. A
+    /// texture's contents are undefined after any of the others has been used, so it must be
+    /// transitioned from RafxResourceState::UNDEFINED. Backends that can't alias memory, or
+    /// textures with incompatible memory requirements, get separate allocations.
+    pub fn create_aliased_textures(
+        &self,
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTexture>> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner
+                .create_aliased_textures(texture_defs)?
+                .into_iter()
+                .map(RafxTexture::Vk)
+                .collect(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => texture_defs
+                .iter()
+                .map(|def| self.create_texture(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => texture_defs
+                .iter()
+                .map(|def| self.create_texture(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => texture_defs
+                .iter()
+                .map(|def| self.create_texture(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => texture_defs
+                .iter()
+                .map(|def| self.create_texture(def))
+                .collect::<RafxResult<_>>()?,
+        })
+    }
+
+    /// Create buffers that share the same memory, with the same restrictions as
+    /// create_aliased_textures(). Only RafxMemoryUsage::GpuOnly buffers can be aliased.
+    pub fn create_aliased_buffers(
+        &self,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<RafxBuffer>> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner
+                .create_aliased_buffers(buffer_defs)?
+                .into_iter()
+                .map(RafxBuffer::Vk)
+                .collect(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => buffer_defs
+                .iter()
+                .map(|def| self.create_buffer(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => buffer_defs
+                .iter()
+                .map(|def| self.create_buffer(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => buffer_defs
+                .iter()
+                .map(|def| self.create_buffer(def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => buffer_defs
+                .iter()
+                .map(|def| self.create_buffer(def))
+                .collect::<RafxResult<_>>()?,
+        })
+    }
+
     pub fn create_shader_module(
         &self,
         shader_module_def: RafxShaderModuleDef,
@@ -596,6 +686,62 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Returns the contents of the driver's pipeline cache. This can be written to disk and passed
+    /// to merge_pipeline_cache_data() on a later run so that pipelines are not compiled from
+    /// scratch. Backends without a driver pipeline cache return an empty vec.
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner.pipeline_cache_data()?,
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => Vec::default(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => Vec::default(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => Vec::default(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => Vec::default(),
+        })
+    }
+
+    /// Merges data previously returned by pipeline_cache_data() into the driver's pipeline cache.
+    /// Data produced by a different driver or device is ignored. This should be called before
+    /// creating pipelines.
+    #[allow(unused_variables)]
+    pub fn merge_pipeline_cache_data(
+        &self,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner.merge_pipeline_cache_data(data),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => Ok(()),
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => Ok(()),
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => Ok(()),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => Ok(()),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
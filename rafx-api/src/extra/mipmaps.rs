@@ -1,4 +1,4 @@
-use crate::{RafxCommandBuffer, RafxResult, RafxTexture};
+use crate::{RafxCommandBuffer, RafxDeviceContext, RafxFormat, RafxResult, RafxTexture};
 
 #[cfg(feature = "rafx-gles2")]
 use crate::gles2::RafxCommandBufferGles2;
@@ -20,8 +20,70 @@ pub fn mip_level_max_count_for_image_size(
     width: u32,
     height: u32,
 ) -> u32 {
-    let max_dimension = std::cmp::max(width, height);
-    (max_dimension as f32).log2().floor() as u32 + 1
+    // floor(log2(max_dimension)) + 1, computed with integers so that large sizes don't round up
+    let max_dimension = std::cmp::max(width, height).max(1);
+    32 - max_dimension.leading_zeros()
+}
+
+/// True if `generate_mipmaps` can produce the mips of a texture with the given format. Mips are
+/// generated by linearly filtered blits, which some formats don't support (i.e. 32-bit float
+/// formats on many mobile GPUs).
+pub fn format_supports_mipmap_generation(
+    device_context: &RafxDeviceContext,
+    format: RafxFormat,
+) -> bool {
+    match device_context {
+        #[cfg(feature = "rafx-vulkan")]
+        RafxDeviceContext::Vk(inner) => {
+            use ash::version::InstanceV1_0;
+            use ash::vk;
+            let required_features = vk::FormatFeatureFlags::BLIT_SRC
+                | vk::FormatFeatureFlags::BLIT_DST
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR;
+            let props = unsafe {
+                inner
+                    .instance()
+                    .get_physical_device_format_properties(inner.physical_device(), format.into())
+            };
+            props.optimal_tiling_features.contains(required_features)
+        }
+        #[cfg(feature = "rafx-metal")]
+        RafxDeviceContext::Metal(inner) => {
+            // generateMipmaps requires a color renderable, filterable format
+            use metal_rs::PixelFormatCapabilities;
+            inner
+                .metal_features()
+                .pixel_format_capabilities(format.into())
+                .contains(PixelFormatCapabilities::Color | PixelFormatCapabilities::Filter)
+        }
+        #[cfg(feature = "rafx-gles2")]
+        RafxDeviceContext::Gles2(_) => !is_32_bit_float_format(format),
+        #[cfg(feature = "rafx-gles3")]
+        RafxDeviceContext::Gles3(_) => !is_32_bit_float_format(format),
+        #[cfg(any(
+            feature = "rafx-empty",
+            not(any(
+                feature = "rafx-metal",
+                feature = "rafx-vulkan",
+                feature = "rafx-gles2",
+                feature = "rafx-gles3"
+            ))
+        ))]
+        RafxDeviceContext::Empty(_) => false,
+    }
+}
+
+// 32-bit float formats are not filterable in core GL ES, so glGenerateMipmap can't be used with
+// them
+#[cfg(any(feature = "rafx-gles2", feature = "rafx-gles3"))]
+fn is_32_bit_float_format(format: RafxFormat) -> bool {
+    match format {
+        RafxFormat::R32_SFLOAT
+        | RafxFormat::R32G32_SFLOAT
+        | RafxFormat::R32G32B32_SFLOAT
+        | RafxFormat::R32G32B32A32_SFLOAT => true,
+        _ => false,
+    }
 }
 
 // Texture must be in COPY_SRC state
@@ -82,6 +82,8 @@ pub struct RafxPresentableFrame {
     shared_state: Option<Arc<RafxSwapchainHelperSharedState>>,
     swapchain_image: RafxSwapchainImage,
     sync_frame_index: usize,
+    // Set by submit_for_frames once the frame's semaphores have been submitted
+    submitted: bool,
 }
 
 impl RafxPresentableFrame {
@@ -137,13 +139,89 @@ impl RafxPresentableFrame {
 
         //TODO: Might be able to do this without presenting by having command buffers that can be
         // submitted that trigger the semaphore.
-        let _ = self.do_present(queue, &mut []);
+        if self.submitted {
+            // The frame's semaphores were already submitted by submit_for_frames
+            let _ = self.do_present_image(queue);
+        } else {
+            let _ = self.do_present(queue, &mut []);
+        }
 
         // Let the shared state arc drop, this will unblock the next frame
         let shared_state = self.shared_state.take().unwrap();
         shared_state.result_tx.send(Err(error)).unwrap();
     }
 
+    /// Submits the given command buffers once for several frames that they all render to, i.e.
+    /// the swapchains of several windows. The frames may come from different swapchain helpers.
+    /// The command buffers wait for every frame's image to be acquired. Each frame must then be
+    /// presented on its own with `present_submitted` (or `present_with_error` if this fails), so
+    /// that presenting one frame does not affect the others.
+    pub fn submit_for_frames(
+        frames: &mut [&mut RafxPresentableFrame],
+        queue: &RafxQueue,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<()> {
+        log::trace!(
+            "Calling RafxPresentableFrame::submit_for_frames with {} frames and {} command buffers",
+            frames.len(),
+            command_buffers.len()
+        );
+
+        let mut wait_semaphores = Vec::with_capacity(frames.len());
+        let mut signal_semaphores = Vec::with_capacity(frames.len());
+        for frame in frames.iter() {
+            // A present can only occur using the result from the previous acquire_next_image call
+            let shared_state = frame.shared_state.as_ref().unwrap();
+            let sync_frame_index = shared_state.sync_frame_index.load(Ordering::Relaxed);
+            assert!(frame.sync_frame_index == sync_frame_index);
+            assert!(!frame.submitted);
+
+            wait_semaphores.push(&shared_state.image_available_semaphores[sync_frame_index]);
+            signal_semaphores.push(&shared_state.render_finished_semaphores[sync_frame_index]);
+        }
+
+        // Only one fence can be signaled per submit. Fences signal after all work submitted before
+        // them, so empty submits are enough to signal the other frames' fences
+        for (i, frame) in frames.iter().enumerate() {
+            let shared_state = frame.shared_state.as_ref().unwrap();
+            let frame_fence = &shared_state.in_flight_fences[frame.sync_frame_index];
+            if i == 0 {
+                queue.submit(
+                    command_buffers,
+                    &wait_semaphores,
+                    &signal_semaphores,
+                    Some(frame_fence),
+                )?;
+            } else {
+                queue.submit(&[], &[], &[], Some(frame_fence))?;
+            }
+        }
+
+        for frame in frames.iter_mut() {
+            frame.submitted = true;
+        }
+
+        Ok(())
+    }
+
+    /// Presents a frame whose command buffers were submitted with `submit_for_frames`. The result
+    /// only applies to this frame's swapchain.
+    pub fn present_submitted(
+        mut self,
+        queue: &RafxQueue,
+    ) -> RafxResult<RafxPresentSuccessResult> {
+        log::trace!("Calling RafxPresentableFrame::present_submitted");
+
+        assert!(self.submitted);
+        let result = self.do_present_image(queue);
+
+        // Let the shared state arc drop, this will unblock the next frame
+        let shared_state = self.shared_state.take().unwrap();
+        shared_state.result_tx.send(result.clone()).unwrap();
+
+        result
+    }
+
     pub fn do_present(
         &mut self,
         queue: &RafxQueue,
@@ -153,6 +231,7 @@ impl RafxPresentableFrame {
         let shared_state = self.shared_state.as_ref().unwrap();
         let sync_frame_index = shared_state.sync_frame_index.load(Ordering::Relaxed);
         assert!(self.sync_frame_index == sync_frame_index);
+        assert!(!self.submitted);
 
         let frame_fence = &shared_state.in_flight_fences[sync_frame_index];
         let wait_semaphores = [&shared_state.image_available_semaphores[sync_frame_index]];
@@ -165,6 +244,18 @@ impl RafxPresentableFrame {
             Some(frame_fence),
         )?;
 
+        self.do_present_image(queue)
+    }
+
+    // Presents the image after the frame's render finished semaphore has been submitted
+    fn do_present_image(
+        &self,
+        queue: &RafxQueue,
+    ) -> RafxResult<RafxPresentSuccessResult> {
+        let shared_state = self.shared_state.as_ref().unwrap();
+        let sync_frame_index = self.sync_frame_index;
+        let signal_semaphores = [&shared_state.render_finished_semaphores[sync_frame_index]];
+
         let swapchain = shared_state.swapchain.lock().unwrap();
 
         let result = queue.present(
@@ -474,6 +565,7 @@ impl RafxSwapchainHelper {
             shared_state: Some(shared_state.clone()),
             swapchain_image,
             sync_frame_index,
+            submitted: false,
         }))
     }
 
@@ -249,6 +249,17 @@ impl RafxUpload {
     fn wait_for_idle(&self) -> RafxResult<()> {
         self.fence.wait()
     }
+
+    /// Waits for the upload to finish if it was submitted and makes it writable again, so the
+    /// staging buffer and command buffer can be reused for another upload
+    pub fn reset(&mut self) -> RafxResult<()> {
+        self.wait_for_idle()?;
+        self.command_pool.reset_command_pool()?;
+        self.command_buffer.begin()?;
+        self.buffer_write_pointer = self.buffer_begin;
+        self.writable = true;
+        Ok(())
+    }
 }
 
 impl Drop for RafxUpload {
@@ -431,6 +442,17 @@ impl RafxTransferUpload {
         }
     }
 
+    /// Waits for the upload to finish on both queues and makes it writable again, so the staging
+    /// buffer and command buffers can be reused for another upload
+    pub fn reset(&mut self) -> RafxResult<()> {
+        self.upload.reset()?;
+        self.wait_for_idle()?;
+        self.dst_command_pool.reset_command_pool()?;
+        self.dst_command_buffer.begin()?;
+        self.sent_to_dst_queue = false;
+        Ok(())
+    }
+
     pub fn block_until_upload_complete(&mut self) -> RafxResult<()> {
         log::trace!("wait on transfer queue {:?}", self.upload.queue);
         self.submit_transfer()?;
@@ -128,6 +128,15 @@ impl RafxShaderResource {
                     self.set_index, self.binding, self.name,
                 ))?;
             }
+
+            if self.resource_type == RafxResourceType::UNIFORM_BUFFER_DYNAMIC
+                && self.element_count_normalized() != 1
+            {
+                Err(format!(
+                    "Descriptor (set={:?} binding={:?}) named {:?} is a dynamic uniform buffer array. This is not supported",
+                    self.set_index, self.binding, self.name,
+                ))?;
+            }
         }
 
         Ok(())
@@ -654,9 +654,21 @@ pub struct RafxGraphicsPipelineDef<'a> {
     pub color_formats: &'a [RafxFormat],
     pub depth_stencil_format: Option<RafxFormat>,
     pub sample_count: RafxSampleCount,
+    /// Set when the pipeline is used in a renderpass with multiple subpasses. color_formats and
+    /// depth_stencil_format must then describe all the targets of the renderpass, not just the
+    /// ones used by the subpass.
+    pub subpass: Option<RafxGraphicsPipelineSubpass<'a>>,
     //indirect_commands_enable: bool
 }
 
+/// The subpass a graphics pipeline will be used in. See `RafxGraphicsPipelineDef::subpass`
+#[derive(Debug, Copy, Clone)]
+pub struct RafxGraphicsPipelineSubpass<'a> {
+    /// All the subpasses of the renderpass, as passed to `cmd_begin_render_pass_with_subpasses`
+    pub subpasses: &'a [RafxSubpassDef],
+    pub subpass_index: u32,
+}
+
 /// Used to create a `RafxPipeline` for compute operations
 #[derive(Debug)]
 pub struct RafxComputePipelineDef<'a> {
@@ -46,6 +46,16 @@ pub struct RafxDeviceInfo {
     // Requires iOS 14.0, macOS 10.12
     pub supports_clamp_to_border_color: bool,
 
+    // Renderpasses with multiple subpasses and input attachments. Only implemented on vulkan. Metal
+    // could keep attachments in tile memory between passes with programmable blending and tile
+    // shaders but doesn't yet, so on metal and GL ES the render graph puts nodes with different
+    // attachments in separate renderpasses and rejects input attachments.
+    pub supports_subpasses: bool,
+
+    // Offsets passed when binding descriptor sets with UNIFORM_BUFFER_DYNAMIC descriptors (not
+    // supported on metal)
+    pub supports_dynamic_uniform_buffers: bool,
+
     pub max_vertex_attribute_count: u32,
     //max_vertex_input_binding_count: u32,
     // max_root_signature_dwords: u32,
@@ -213,6 +223,14 @@ bitflags::bitflags! {
         const RENDER_TARGET_COLOR = 1<<23;
         /// A depth/stencil attachment in a renderpass
         const RENDER_TARGET_DEPTH_STENCIL = 1<<24;
+        /// A render target whose contents are only needed within a single renderpass. It must
+        /// not be loaded or stored. Backends may avoid backing it with memory (lazily allocated
+        /// memory on vulkan, memoryless storage on iOS)
+        const TRANSIENT_ATTACHMENT = 1<<25;
+        /// Similar to vulkan UNIFORM_BUFFER_DYNAMIC descriptor type. The offset into the buffer is
+        /// provided when binding the descriptor set, so one descriptor set can be used to access
+        /// data for many draws
+        const UNIFORM_BUFFER_DYNAMIC = 1<<26 | RafxResourceType::UNIFORM_BUFFER.bits();
     }
 }
 
@@ -221,6 +239,10 @@ impl RafxResourceType {
         self.intersects(RafxResourceType::UNIFORM_BUFFER)
     }
 
+    pub fn is_dynamic_uniform_buffer(self) -> bool {
+        self.contains(RafxResourceType::UNIFORM_BUFFER_DYNAMIC)
+    }
+
     pub fn is_storage_buffer(self) -> bool {
         self.intersects(RafxResourceType::BUFFER | RafxResourceType::BUFFER_READ_WRITE)
     }
@@ -357,6 +379,7 @@ pub enum RafxPipelineType {
 
 /// Affects how quickly vertex attributes are consumed from buffers, similar to VkVertexInputRate
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
 pub enum RafxVertexAttributeRate {
     Vertex,
     Instance,
@@ -740,6 +763,33 @@ pub struct RafxDepthStencilRenderTargetBinding<'a> {
     pub clear_value: RafxDepthStencilClearValue,
 }
 
+/// An attachment that a subpass reads as an input attachment
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+pub enum RafxSubpassInputAttachment {
+    /// Index into the color targets of the renderpass
+    Color(u32),
+    /// The depth/stencil target of the renderpass
+    DepthStencil,
+}
+
+/// Describes a subpass of a renderpass begun with `cmd_begin_render_pass_with_subpasses`. Color
+/// and input attachments refer to the color targets of the renderpass by index. Attachments
+/// written by a subpass can be read by later subpasses as input attachments without leaving tile
+/// memory, which saves bandwidth on tile-based GPUs. Only supported on vulkan, see
+/// `RafxDeviceInfo::supports_subpasses`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+pub struct RafxSubpassDef {
+    /// The color targets written by the subpass, in the order of the shader's outputs
+    pub color_attachments: Vec<u32>,
+    /// The attachments read by the subpass, in the order of the shader's input attachment indices.
+    /// An attachment may not be both written and read by the same subpass.
+    pub input_attachments: Vec<RafxSubpassInputAttachment>,
+    /// If true, the depth/stencil target is used for depth/stencil testing in this subpass
+    pub use_depth_stencil_attachment: bool,
+}
+
 /// A vertex buffer to be bound during a renderpass
 pub struct RafxVertexBufferBinding<'a> {
     pub buffer: &'a RafxBuffer,
@@ -761,6 +811,14 @@ pub struct RafxCmdCopyBufferToTextureParams {
     pub mip_level: u8,
 }
 
+/// Parameters for copying a texture to a buffer. Rows are tightly packed in the buffer.
+#[derive(Default)]
+pub struct RafxCmdCopyTextureToBufferParams {
+    pub buffer_offset: u64,
+    pub array_layer: u16,
+    pub mip_level: u8,
+}
+
 /// Parameters for blitting one image to another (vulkan backend only)
 pub struct RafxCmdBlitParams {
     pub src_state: RafxResourceState,
@@ -1,32 +1,44 @@
 use crate::assets::ImageAssetData;
 use crate::assets::{BufferAsset, ImageAsset, MaterialAsset};
 use crate::{
-    AssetLookup, AssetTypeHandler, AssetTypeHandlerFactory, BufferAssetData, GenericLoader,
-    MaterialInstanceSlotAssignment, UploadQueueConfig,
+    AssetLookup, AssetTypeHandler, AssetTypeHandlerFactory, BufferAssetData,
+    DerivedMaterialInstance, GenericLoader, MaterialInstanceAsset, MaterialInstanceOverrides,
+    MaterialInstanceSlotAssignment, MaterialInstanceUniformParam, TextureStreaming,
+    TextureStreamingConfig, TextureStreamingFeedback, TextureStreamingMetrics, UploadPriority,
+    UploadQueueConfig, UploadQueueMetrics,
 };
 use distill::loader::handle::Handle;
 use rafx_framework::{
     DescriptorSetAllocatorMetrics, DescriptorSetAllocatorProvider, DescriptorSetAllocatorRef,
     DescriptorSetLayoutResource, DescriptorSetWriteSet, DynResourceAllocatorSet,
-    GraphicsPipelineCache, MaterialPass, ResourceArc, SlotNameLookup,
+    GraphicsPipelineCache, ImageViewResource, MaterialPass, ResourceArc, SlotNameLookup,
+    WeakResourceArc,
 };
 
+use super::load_queue::LoadRequest;
 use super::upload::UploadManager;
 use crate::assets::buffer::BufferAssetTypeHandler;
 use crate::assets::compute_pipeline::ComputePipelineAssetTypeHandler;
+use crate::assets::derived_material_instance::{
+    apply_uniform_param_overrides, DerivedMaterialInstanceInner, DerivedMaterialInstanceState,
+    MaterialInstanceSlotOverrideValue,
+};
 use crate::assets::graphics_pipeline::{
-    MaterialAssetTypeHandler, MaterialInstanceAssetTypeHandler, SamplerAssetTypeHandler,
+    MaterialAssetTypeHandler, MaterialInstanceAssetTypeHandler, MaterialInstanceDescriptorSets,
+    SamplerAssetTypeHandler,
 };
 use crate::assets::image::ImageAssetTypeHandler;
+use crate::assets::render_graph::RenderGraphAssetTypeHandler;
 use crate::assets::shader::ShaderAssetTypeHandler;
 use crate::distill_impl::AssetResource;
 use distill::loader::handle::AssetHandle;
-use distill::loader::storage::LoadStatus;
+use distill::loader::storage::{IndirectionTable, LoadStatus};
+use distill::loader::LoadHandle;
 use fnv::FnvHashMap;
 use rafx_api::{RafxDeviceContext, RafxQueue, RafxResult};
 use rafx_framework::descriptor_sets::{
     DescriptorSetElementKey, DescriptorSetWriteElementBuffer, DescriptorSetWriteElementBufferData,
-    DescriptorSetWriteElementImage,
+    DescriptorSetWriteElementImage, DescriptorSetWriteElementImageValue,
 };
 use rafx_framework::render_features::RenderRegistry;
 use rafx_framework::DescriptorSetAllocator;
@@ -35,12 +47,14 @@ use rafx_framework::DynResourceAllocatorSetProvider;
 use rafx_framework::ResourceLookupSet;
 use rafx_framework::{ResourceManager, ResourceManagerMetrics};
 use std::any::TypeId;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
 
 #[derive(Debug)]
 pub struct AssetManagerMetrics {
     pub resource_manager_metrics: ResourceManagerMetrics,
     pub material_instance_descriptor_sets_metrics: DescriptorSetAllocatorMetrics,
+    pub upload_queue_metrics: UploadQueueMetrics,
+    pub texture_streaming_metrics: TextureStreamingMetrics,
     //TODO: Metrics per asset type
 }
 
@@ -53,6 +67,7 @@ pub struct AssetManager {
     device_context: RafxDeviceContext,
     resource_manager: ResourceManager,
     upload_manager: UploadManager,
+    texture_streaming: TextureStreaming,
     material_instance_descriptor_sets: DescriptorSetAllocator,
     graphics_queue: RafxQueue,
     transfer_queue: RafxQueue,
@@ -62,6 +77,14 @@ pub struct AssetManager {
     // having a mut reference to asset manager. Better to just reallocate the vec every time we
     // register an asset type than clone every time we do a frame update.
     asset_registration_order: Arc<Vec<TypeId>>,
+
+    // Used to find the asset an indirect handle (i.e. from load_asset_path) refers to
+    indirection_table: Option<IndirectionTable>,
+    // Priorities set on indirect handles that have not been resolved yet
+    unresolved_upload_priorities: Vec<(LoadHandle, UploadPriority)>,
+
+    // Recreated when their base asset reloads
+    derived_material_instances: Vec<Weak<DerivedMaterialInstanceInner>>,
 }
 
 impl AssetManager {
@@ -83,12 +106,21 @@ impl AssetManager {
                 graphics_queue.clone(),
                 transfer_queue.clone(),
             ),
+            texture_streaming: TextureStreaming::new(
+                device_context,
+                TextureStreamingConfig::default(),
+                graphics_queue,
+                transfer_queue,
+            ),
             material_instance_descriptor_sets: DescriptorSetAllocator::new(device_context),
             graphics_queue: graphics_queue.clone(),
             transfer_queue: transfer_queue.clone(),
 
             asset_types: Default::default(),
             asset_registration_order: Default::default(),
+            indirection_table: None,
+            unresolved_upload_priorities: Default::default(),
+            derived_material_instances: Default::default(),
         }
     }
 
@@ -96,6 +128,10 @@ impl AssetManager {
         &mut self,
         asset_resource: &mut AssetResource,
     ) {
+        if self.indirection_table.is_none() {
+            self.indirection_table = Some(asset_resource.loader().indirection_table());
+        }
+
         let asset_type = AssetTypeFactoryT::create(asset_resource);
         let mut asset_registration_order = (*self.asset_registration_order).clone();
         asset_registration_order.push(asset_type.asset_type_id());
@@ -117,6 +153,7 @@ impl AssetManager {
         self.register_asset_type::<SamplerAssetTypeHandler>(asset_resource);
         self.register_asset_type::<ImageAssetTypeHandler>(asset_resource);
         self.register_asset_type::<BufferAssetTypeHandler>(asset_resource);
+        self.register_asset_type::<RenderGraphAssetTypeHandler>(asset_resource);
     }
 
     pub fn committed_asset<AssetT: 'static>(
@@ -173,6 +210,7 @@ impl AssetManager {
 
         loop {
             asset_resource.update();
+            self.cancel_unreferenced_uploads(asset_resource);
             self.update_asset_loaders()?;
             match asset_resource.load_status(&asset_handle) {
                 LoadStatus::NotRequested => {
@@ -272,6 +310,103 @@ impl AssetManager {
         &self.upload_manager
     }
 
+    pub(crate) fn upload_image(
+        &mut self,
+        request: LoadRequest<ImageAssetData, ImageAsset>,
+    ) -> RafxResult<()> {
+        self.upload_manager
+            .upload_image(request, &mut self.texture_streaming)
+    }
+
+    pub fn texture_streaming(&self) -> &TextureStreaming {
+        &self.texture_streaming
+    }
+
+    pub(crate) fn texture_streaming_mut(&mut self) -> &mut TextureStreaming {
+        &mut self.texture_streaming
+    }
+
+    // Streaming is off by default. Set the config before loading images, images that already loaded
+    // are not split into streamed mip levels.
+    pub fn set_texture_streaming_config(
+        &mut self,
+        config: TextureStreamingConfig,
+    ) {
+        self.texture_streaming.set_config(config);
+    }
+
+    // Render jobs report how large streamed images appear on screen through this
+    pub fn texture_streaming_feedback(&self) -> &TextureStreamingFeedback {
+        self.texture_streaming.feedback()
+    }
+
+    // Uploads with a higher priority are started before others, i.e. textures near the camera
+    // during a level load. The priority also applies when the asset is reloaded.
+    pub fn set_upload_priority<T: AssetHandle>(
+        &mut self,
+        handle: &T,
+        priority: UploadPriority,
+    ) {
+        let load_handle = handle.load_handle();
+        if load_handle.is_indirect() {
+            // The indirect handle may not be resolved yet, try again every update
+            self.unresolved_upload_priorities
+                .retain(|(x, _)| *x != load_handle);
+            self.unresolved_upload_priorities
+                .push((load_handle, priority));
+            self.resolve_upload_priorities();
+        } else {
+            self.upload_manager
+                .set_upload_priority(load_handle, priority);
+        }
+    }
+
+    fn resolve_upload_priorities(&mut self) {
+        if self.unresolved_upload_priorities.is_empty() {
+            return;
+        }
+
+        let indirection_table = match &self.indirection_table {
+            Some(indirection_table) => indirection_table,
+            None => return,
+        };
+
+        let upload_manager = &mut self.upload_manager;
+        self.unresolved_upload_priorities
+            .retain(|(load_handle, priority)| {
+                if let Some(resolved) = indirection_table.resolve(*load_handle) {
+                    upload_manager.set_upload_priority(resolved, *priority);
+                    false
+                } else {
+                    true
+                }
+            });
+    }
+
+    // Cancels uploads that have not started yet for assets that are no longer referenced, and
+    // forgets their upload priorities. Call after AssetResource::update() so that dropped handles
+    // have been processed.
+    pub fn cancel_unreferenced_uploads(
+        &mut self,
+        asset_resource: &AssetResource,
+    ) {
+        let loader = asset_resource.loader();
+        self.upload_manager.retain_uploads(|load_handle| {
+            loader
+                .get_load_info(load_handle)
+                .map(|load_info| load_info.refs > 0)
+                .unwrap_or(true)
+        });
+
+        // Priorities are kept while the asset is referenced so that they apply to reloads
+        self.upload_manager.retain_upload_priorities(|load_handle| {
+            loader
+                .get_load_info(load_handle)
+                .map(|load_info| load_info.refs > 0)
+                .unwrap_or(false)
+        });
+    }
+
     //
     // Loaders
     //
@@ -299,7 +434,13 @@ impl AssetManager {
                 .insert(asset_type.asset_type_id(), asset_type);
         }
 
+        self.resolve_upload_priorities();
+        self.update_derived_material_instances();
         self.upload_manager.update()?;
+        self.texture_streaming.update(
+            self.resource_manager.resources(),
+            &mut self.material_instance_descriptor_sets,
+        )?;
 
         Ok(())
     }
@@ -326,6 +467,8 @@ impl AssetManager {
             resource_manager_metrics,
             //loaded_asset_metrics,
             material_instance_descriptor_sets_metrics,
+            upload_queue_metrics: self.upload_manager.metrics(),
+            texture_streaming_metrics: self.texture_streaming.metrics(),
         }
     }
 
@@ -405,6 +548,7 @@ impl AssetManager {
         &self,
         pass: &MaterialPass,
         slots: &[MaterialInstanceSlotAssignment],
+        uniform_params: &[MaterialInstanceUniformParam],
         resources: &ResourceLookupSet,
     ) -> RafxResult<Vec<DescriptorSetWriteSet>> {
         let mut pass_descriptor_set_writes =
@@ -422,8 +566,318 @@ impl AssetManager {
             )?;
         }
 
+        // Not every pass uses every uniform buffer, so params that aren't in this pass are skipped
+        let descriptor_set_layouts = &pass.material_pass_resource.get_raw().descriptor_set_layouts;
+        for uniform_param in uniform_params {
+            for (layout, write_set) in descriptor_set_layouts
+                .iter()
+                .zip(&mut pass_descriptor_set_writes)
+            {
+                let written = rafx_framework::descriptor_sets::write_uniform_param_to_write_set(
+                    &layout.get_raw().descriptor_set_layout_def,
+                    write_set,
+                    &uniform_param.path,
+                    &uniform_param.value,
+                )?;
+
+                if written.is_some() {
+                    break;
+                }
+            }
+        }
+
         Ok(pass_descriptor_set_writes)
     }
+
+    // Creates the descriptor sets for the writes of a material instance, indexed by pass and then
+    // by set within the pass. They are recreated when a streamed image they bind changes resolution.
+    pub(crate) fn create_material_instance_descriptor_sets(
+        &mut self,
+        material: &MaterialAsset,
+        descriptor_set_writes: &[Vec<DescriptorSetWriteSet>],
+    ) -> RafxResult<Arc<MaterialInstanceDescriptorSets>> {
+        let descriptor_set_layouts: Vec<_> = material
+            .passes
+            .iter()
+            .map(|pass| {
+                pass.material_pass_resource
+                    .get_raw()
+                    .descriptor_set_layouts
+                    .clone()
+            })
+            .collect();
+
+        let descriptor_sets = self.texture_streaming.create_material_descriptor_sets(
+            &mut self.material_instance_descriptor_sets,
+            &descriptor_set_layouts,
+            descriptor_set_writes,
+        )?;
+
+        let streamed_image_views = self
+            .texture_streaming
+            .find_streamed_image_views(descriptor_set_writes);
+
+        // Put these in an arc to avoid cloning the underlying data repeatedly
+        let descriptor_sets = Arc::new(MaterialInstanceDescriptorSets::new(
+            descriptor_sets,
+            streamed_image_views,
+        ));
+
+        if !descriptor_sets.streamed_image_views().is_empty() {
+            self.texture_streaming.register_material_instance(
+                &descriptor_sets,
+                descriptor_set_layouts,
+                descriptor_set_writes.to_vec(),
+            );
+        }
+
+        Ok(descriptor_sets)
+    }
+
+    //
+    // Derived material instances
+    //
+
+    // Creates a material instance from a loaded one with some of its slots and uniform params
+    // replaced, i.e. to tint a single object or to sample a render target. It gets its own
+    // descriptor sets and is recreated with the same overrides when the base asset reloads.
+    pub fn create_derived_material_instance(
+        &mut self,
+        base: &Handle<MaterialInstanceAsset>,
+        overrides: MaterialInstanceOverrides,
+    ) -> RafxResult<DerivedMaterialInstance> {
+        let base_asset = self
+            .committed_asset(base)
+            .ok_or("The base material instance of a derived material instance is not loaded")?
+            .clone();
+
+        let material_instance =
+            self.create_material_instance_with_overrides(&base_asset, &overrides)?;
+        let image_versions = self.derived_material_instance_image_versions(&overrides);
+
+        let derived = DerivedMaterialInstance {
+            inner: Arc::new(DerivedMaterialInstanceInner {
+                base: base.clone(),
+                state: Mutex::new(DerivedMaterialInstanceState {
+                    overrides,
+                    material_instance,
+                    base_version: base_asset.inner.clone(),
+                    image_versions,
+                }),
+            }),
+        };
+
+        self.derived_material_instances
+            .push(Arc::downgrade(&derived.inner));
+
+        Ok(derived)
+    }
+
+    // Replaces all overrides of a derived material instance. On error the material instance is left
+    // unchanged.
+    pub fn set_derived_material_instance_overrides(
+        &mut self,
+        derived: &DerivedMaterialInstance,
+        overrides: MaterialInstanceOverrides,
+    ) -> RafxResult<()> {
+        let base_asset = self
+            .committed_asset(derived.base())
+            .ok_or("The base material instance of a derived material instance is not loaded")?
+            .clone();
+
+        let material_instance =
+            self.create_material_instance_with_overrides(&base_asset, &overrides)?;
+        let image_versions = self.derived_material_instance_image_versions(&overrides);
+
+        let mut state = derived.inner.state.lock().unwrap();
+        state.overrides = overrides;
+        state.material_instance = material_instance;
+        state.base_version = base_asset.inner.clone();
+        state.image_versions = image_versions;
+        Ok(())
+    }
+
+    // The views of the image assets the overrides bind, compared each frame to find derived material
+    // instances that need to be recreated because an image reloaded
+    fn derived_material_instance_image_versions(
+        &self,
+        overrides: &MaterialInstanceOverrides,
+    ) -> Vec<WeakResourceArc<ImageViewResource>> {
+        overrides
+            .slots
+            .iter()
+            .filter_map(|slot| match &slot.value {
+                MaterialInstanceSlotOverrideValue::ImageAsset(image) => self
+                    .latest_asset(image)
+                    .map(|image| image.image_view.downgrade()),
+                MaterialInstanceSlotOverrideValue::Image(_)
+                | MaterialInstanceSlotOverrideValue::Sampler(_)
+                | MaterialInstanceSlotOverrideValue::BufferData(_) => None,
+            })
+            .collect()
+    }
+
+    fn create_material_instance_with_overrides(
+        &mut self,
+        base: &MaterialInstanceAsset,
+        overrides: &MaterialInstanceOverrides,
+    ) -> RafxResult<MaterialInstanceAsset> {
+        let mut descriptor_set_writes = base.descriptor_set_writes.clone();
+        for (pass, pass_descriptor_set_writes) in
+            base.material.passes.iter().zip(&mut descriptor_set_writes)
+        {
+            self.apply_material_instance_overrides(pass, overrides, pass_descriptor_set_writes)?;
+        }
+
+        let pass_descriptor_set_layouts: Vec<Vec<_>> = base
+            .material
+            .passes
+            .iter()
+            .map(|pass| {
+                pass.material_pass_resource
+                    .get_raw()
+                    .descriptor_set_layouts
+                    .iter()
+                    .map(|layout| layout.get_raw().descriptor_set_layout_def)
+                    .collect()
+            })
+            .collect();
+        apply_uniform_param_overrides(
+            &pass_descriptor_set_layouts,
+            &overrides.uniform_params,
+            &mut descriptor_set_writes,
+        )?;
+
+        let descriptor_sets =
+            self.create_material_instance_descriptor_sets(&base.material, &descriptor_set_writes)?;
+
+        Ok(MaterialInstanceAsset::new(
+            base.material_handle.clone(),
+            base.material.clone(),
+            descriptor_sets,
+            base.slot_assignments.clone(),
+            descriptor_set_writes,
+        ))
+    }
+
+    fn apply_material_instance_overrides(
+        &self,
+        pass: &MaterialPass,
+        overrides: &MaterialInstanceOverrides,
+        material_pass_write_set: &mut Vec<DescriptorSetWriteSet>,
+    ) -> RafxResult<()> {
+        for slot in &overrides.slots {
+            let slot_locations = match pass.pass_slot_name_lookup.get(&slot.slot_name) {
+                Some(slot_locations) => slot_locations,
+                // Not every pass uses every slot
+                None => continue,
+            };
+
+            for location in slot_locations {
+                let write = material_pass_write_set[location.layout_index as usize]
+                    .elements
+                    .get_mut(&DescriptorSetElementKey {
+                        dst_binding: location.binding_index,
+                        array_index: slot.array_index,
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "Could not override slot {}, array index {} is out of range",
+                            slot.slot_name, slot.array_index
+                        )
+                    })?;
+
+                let what_to_bind = rafx_framework::descriptor_sets::what_to_bind(write);
+
+                match &slot.value {
+                    MaterialInstanceSlotOverrideValue::Image(image_view) => {
+                        if what_to_bind.bind_images {
+                            write.image_info.image_view = Some(
+                                DescriptorSetWriteElementImageValue::Resource(image_view.clone()),
+                            );
+                        }
+                    }
+                    MaterialInstanceSlotOverrideValue::ImageAsset(image) => {
+                        if what_to_bind.bind_images {
+                            let image = self.latest_asset(image).ok_or_else(|| {
+                                format!(
+                                    "Could not override slot {}, the image is not loaded",
+                                    slot.slot_name
+                                )
+                            })?;
+                            write.image_info.image_view =
+                                Some(DescriptorSetWriteElementImageValue::Resource(
+                                    image.image_view.clone(),
+                                ));
+                        }
+                    }
+                    MaterialInstanceSlotOverrideValue::Sampler(sampler) => {
+                        if what_to_bind.bind_samplers {
+                            write.image_info.sampler = Some(sampler.clone());
+                        }
+                    }
+                    MaterialInstanceSlotOverrideValue::BufferData(buffer_data) => {
+                        if what_to_bind.bind_buffers {
+                            write.buffer_info.buffer = Some(
+                                DescriptorSetWriteElementBufferData::Data(buffer_data.clone()),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Recreates derived material instances whose base asset or image assets reloaded and forgets
+    // the ones that were dropped
+    fn update_derived_material_instances(&mut self) {
+        self.derived_material_instances
+            .retain(|x| x.strong_count() > 0);
+
+        for derived in self.derived_material_instances.clone() {
+            let derived = match derived.upgrade() {
+                Some(derived) => derived,
+                None => continue,
+            };
+
+            let base_asset = match self.committed_asset(&derived.base) {
+                Some(base_asset) => base_asset.clone(),
+                None => continue,
+            };
+
+            let (overrides, image_versions) = {
+                let state = derived.state.lock().unwrap();
+                let image_versions =
+                    self.derived_material_instance_image_versions(&state.overrides);
+                if Arc::ptr_eq(&state.base_version, &base_asset.inner)
+                    && state.image_versions == image_versions
+                {
+                    continue;
+                }
+
+                (state.overrides.clone(), image_versions)
+            };
+
+            let result = self.create_material_instance_with_overrides(&base_asset, &overrides);
+
+            let mut state = derived.state.lock().unwrap();
+            match result {
+                Ok(material_instance) => state.material_instance = material_instance,
+                // Keep the old material instance, i.e. if the reloaded material no longer has an
+                // overridden slot
+                Err(err) => log::error!(
+                    "Failed to recreate derived material instance after an asset it uses reloaded: {:?}",
+                    err
+                ),
+            }
+
+            // Don't retry until an asset it uses reloads again
+            state.base_version = base_asset.inner.clone();
+            state.image_versions = image_versions;
+        }
+    }
 }
 
 impl Drop for AssetManager {
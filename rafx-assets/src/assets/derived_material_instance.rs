@@ -0,0 +1,504 @@
+use crate::assets::graphics_pipeline::MaterialInstanceAssetInner;
+use crate::{ImageAsset, MaterialInstanceAsset, MaterialInstanceUniformParam};
+use distill::loader::handle::Handle;
+use rafx_api::RafxResult;
+use rafx_framework::{
+    DescriptorSetLayout, DescriptorSetWriteSet, ImageViewResource, ResourceArc, SamplerResource,
+    UniformParamValue, WeakResourceArc,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+pub(crate) enum MaterialInstanceSlotOverrideValue {
+    Image(ResourceArc<ImageViewResource>),
+    ImageAsset(Handle<ImageAsset>),
+    Sampler(ResourceArc<SamplerResource>),
+    BufferData(Vec<u8>),
+}
+
+// An image and a sampler can both be bound to a combined image sampler slot, so a slot may have one
+// override of each kind
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum MaterialInstanceSlotOverrideKind {
+    Image,
+    Sampler,
+    BufferData,
+}
+
+impl MaterialInstanceSlotOverrideValue {
+    pub(crate) fn kind(&self) -> MaterialInstanceSlotOverrideKind {
+        match self {
+            MaterialInstanceSlotOverrideValue::Image(_)
+            | MaterialInstanceSlotOverrideValue::ImageAsset(_) => {
+                MaterialInstanceSlotOverrideKind::Image
+            }
+            MaterialInstanceSlotOverrideValue::Sampler(_) => {
+                MaterialInstanceSlotOverrideKind::Sampler
+            }
+            MaterialInstanceSlotOverrideValue::BufferData(_) => {
+                MaterialInstanceSlotOverrideKind::BufferData
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct MaterialInstanceSlotOverride {
+    pub(crate) slot_name: String,
+    pub(crate) array_index: usize,
+    pub(crate) value: MaterialInstanceSlotOverrideValue,
+}
+
+// Values that replace what a material instance binds. Slots are applied first, then uniform params,
+// so a uniform param can change a single member of a uniform buffer that a slot override replaced.
+#[derive(Clone, Default)]
+pub struct MaterialInstanceOverrides {
+    pub(crate) slots: Vec<MaterialInstanceSlotOverride>,
+    pub(crate) uniform_params: Vec<MaterialInstanceUniformParam>,
+}
+
+impl MaterialInstanceOverrides {
+    fn set_slot(
+        &mut self,
+        slot_name: &str,
+        array_index: usize,
+        value: MaterialInstanceSlotOverrideValue,
+    ) {
+        // Only a value of the same kind replaces the old one
+        self.slots.retain(|x| {
+            let replaced = x.slot_name == slot_name
+                && x.array_index == array_index
+                && x.value.kind() == value.kind();
+            !replaced
+        });
+
+        self.slots.push(MaterialInstanceSlotOverride {
+            slot_name: slot_name.to_string(),
+            array_index,
+            value,
+        });
+    }
+
+    /// Binds an image view to the slot, i.e. a render target
+    pub fn set_image(
+        &mut self,
+        slot_name: &str,
+        image_view: &ResourceArc<ImageViewResource>,
+    ) {
+        self.set_image_at_index(slot_name, 0, image_view);
+    }
+
+    pub fn set_image_at_index(
+        &mut self,
+        slot_name: &str,
+        array_index: usize,
+        image_view: &ResourceArc<ImageViewResource>,
+    ) {
+        self.set_slot(
+            slot_name,
+            array_index,
+            MaterialInstanceSlotOverrideValue::Image(image_view.clone()),
+        );
+    }
+
+    /// Binds an image asset to the slot. The image must be loaded when the overrides are applied.
+    /// The material instance is recreated when the image reloads.
+    pub fn set_image_asset(
+        &mut self,
+        slot_name: &str,
+        image: &Handle<ImageAsset>,
+    ) {
+        self.set_slot(
+            slot_name,
+            0,
+            MaterialInstanceSlotOverrideValue::ImageAsset(image.clone()),
+        );
+    }
+
+    pub fn set_sampler(
+        &mut self,
+        slot_name: &str,
+        sampler: &ResourceArc<SamplerResource>,
+    ) {
+        self.set_slot(
+            slot_name,
+            0,
+            MaterialInstanceSlotOverrideValue::Sampler(sampler.clone()),
+        );
+    }
+
+    /// Replaces the whole buffer bound to the slot
+    // Requiring 'static helps us catch accidentally trying to store a reference in the buffer
+    pub fn set_buffer_data<T: Copy + 'static>(
+        &mut self,
+        slot_name: &str,
+        data: &T,
+    ) {
+        self.set_slot(
+            slot_name,
+            0,
+            MaterialInstanceSlotOverrideValue::BufferData(
+                rafx_base::memory::any_as_bytes(data).to_vec(),
+            ),
+        );
+    }
+
+    /// Sets a member of a uniform buffer by name, i.e. "base_color_factor" or "light.range". See
+    /// MaterialInstanceUniformParam
+    pub fn set_uniform_param(
+        &mut self,
+        path: &str,
+        value: UniformParamValue,
+    ) {
+        self.uniform_params.retain(|x| x.path != path);
+        self.uniform_params.push(MaterialInstanceUniformParam {
+            path: path.to_string(),
+            value,
+        });
+    }
+}
+
+// Writes uniform param overrides to the descriptor set writes of each pass, given the layouts of
+// each pass. A uniform param only needs to exist in one pass, same as DynMaterialInstance.
+pub(crate) fn apply_uniform_param_overrides(
+    pass_descriptor_set_layouts: &[Vec<Arc<DescriptorSetLayout>>],
+    uniform_params: &[MaterialInstanceUniformParam],
+    pass_descriptor_set_writes: &mut [Vec<DescriptorSetWriteSet>],
+) -> RafxResult<()> {
+    for uniform_param in uniform_params {
+        let mut written = false;
+        for (descriptor_set_layouts, descriptor_set_writes) in pass_descriptor_set_layouts
+            .iter()
+            .zip(pass_descriptor_set_writes.iter_mut())
+        {
+            for (layout, write_set) in descriptor_set_layouts
+                .iter()
+                .zip(descriptor_set_writes.iter_mut())
+            {
+                let key = rafx_framework::descriptor_sets::write_uniform_param_to_write_set(
+                    layout,
+                    write_set,
+                    &uniform_param.path,
+                    &uniform_param.value,
+                )?;
+
+                if key.is_some() {
+                    written = true;
+                    break;
+                }
+            }
+        }
+
+        if !written {
+            Err(format!(
+                "Could not override uniform param {}, no pass of the material has it",
+                uniform_param.path
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) struct DerivedMaterialInstanceState {
+    pub(crate) overrides: MaterialInstanceOverrides,
+    pub(crate) material_instance: MaterialInstanceAsset,
+    // The version of the base asset the material instance was created from. When the base asset
+    // reloads the material instance is recreated from the new version.
+    pub(crate) base_version: Arc<MaterialInstanceAssetInner>,
+    // The views of the image assets bound by the overrides when the material instance was created.
+    // When one of them reloads the material instance is recreated with the new view.
+    pub(crate) image_versions: Vec<WeakResourceArc<ImageViewResource>>,
+}
+
+pub(crate) struct DerivedMaterialInstanceInner {
+    pub(crate) base: Handle<MaterialInstanceAsset>,
+    pub(crate) state: Mutex<DerivedMaterialInstanceState>,
+}
+
+//
+// A material instance created at runtime from a loaded one, with some of its slots and uniform
+// params overridden. See AssetManager::create_derived_material_instance. Clones refer to the same
+// material instance, it is freed when the last clone is dropped. The base asset is kept loaded
+// while the derived material instance exists. It is recreated when the base asset or an image set
+// with set_image_asset reloads.
+//
+#[derive(Clone)]
+pub struct DerivedMaterialInstance {
+    pub(crate) inner: Arc<DerivedMaterialInstanceInner>,
+}
+
+impl DerivedMaterialInstance {
+    pub fn base(&self) -> &Handle<MaterialInstanceAsset> {
+        &self.inner.base
+    }
+
+    /// The material instance is replaced when the base asset reloads or the overrides change, so
+    /// fetch it when drawing rather than holding on to it.
+    pub fn material_instance(&self) -> MaterialInstanceAsset {
+        self.inner.state.lock().unwrap().material_instance.clone()
+    }
+
+    pub fn overrides(&self) -> MaterialInstanceOverrides {
+        self.inner.state.lock().unwrap().overrides.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossbeam_channel::{Receiver, Sender};
+    use distill::loader::handle::{AssetHandle, RefOp};
+    use distill::loader::LoadHandle;
+    use rafx_api::{RafxResourceType, RafxShaderResource};
+    use rafx_framework::descriptor_sets::{
+        create_uninitialized_write_set_for_layout, DescriptorSetElementKey,
+        DescriptorSetWriteElementBufferData,
+    };
+    use rafx_framework::{
+        DescriptorSetLayoutBinding, ReflectedUniformMember, ReflectedUniformMemberType,
+    };
+
+    // Image views and samplers can't be created without a device. Image assets are overridden the
+    // same way as image views, and buffer data stands in for a value of another kind bound to the
+    // same slot.
+    struct TestHandles {
+        refop_sender: Sender<RefOp>,
+        // Dropped handles send to this, so it must outlive them
+        _refop_receiver: Receiver<RefOp>,
+    }
+
+    impl TestHandles {
+        fn new() -> Self {
+            let (refop_sender, _refop_receiver) = crossbeam_channel::unbounded();
+            TestHandles {
+                refop_sender,
+                _refop_receiver,
+            }
+        }
+
+        fn image(
+            &self,
+            load_handle: u64,
+        ) -> Handle<ImageAsset> {
+            Handle::new(self.refop_sender.clone(), LoadHandle(load_handle))
+        }
+    }
+
+    fn slot_overrides(
+        overrides: &MaterialInstanceOverrides
+    ) -> Vec<(&str, usize, MaterialInstanceSlotOverrideKind)> {
+        overrides
+            .slots
+            .iter()
+            .map(|x| (x.slot_name.as_str(), x.array_index, x.value.kind()))
+            .collect()
+    }
+
+    fn image_load_handle(
+        overrides: &MaterialInstanceOverrides,
+        slot_name: &str,
+    ) -> LoadHandle {
+        overrides
+            .slots
+            .iter()
+            .find_map(|x| match &x.value {
+                MaterialInstanceSlotOverrideValue::ImageAsset(image)
+                    if x.slot_name == slot_name =>
+                {
+                    Some(image.load_handle())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    // A single uniform buffer containing "vec4 base_color_factor; float roughness;"
+    fn layout(slot_name: &str) -> Arc<DescriptorSetLayout> {
+        let member = |path: &str, member_type, offset| ReflectedUniformMember {
+            path: path.to_string(),
+            member_type,
+            offset,
+            array_length: 0,
+            array_stride: 0,
+            matrix_stride: 0,
+        };
+
+        Arc::new(DescriptorSetLayout {
+            bindings: vec![DescriptorSetLayoutBinding {
+                resource: RafxShaderResource {
+                    resource_type: RafxResourceType::UNIFORM_BUFFER,
+                    binding: 0,
+                    name: Some(slot_name.to_string()),
+                    ..Default::default()
+                },
+                immutable_samplers: None,
+                internal_buffer_per_descriptor_size: Some(32),
+                uniform_members: vec![
+                    member("base_color_factor", ReflectedUniformMemberType::Vec4, 0),
+                    member("roughness", ReflectedUniformMemberType::Float, 16),
+                ],
+            }],
+        })
+    }
+
+    fn buffer_data(write_set: &DescriptorSetWriteSet) -> Option<&Vec<u8>> {
+        let key = DescriptorSetElementKey {
+            dst_binding: 0,
+            array_index: 0,
+        };
+        match &write_set.elements[&key].buffer_info.buffer {
+            Some(DescriptorSetWriteElementBufferData::Data(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_set_slot_replaces_value_of_same_kind() {
+        let handles = TestHandles::new();
+        let mut overrides = MaterialInstanceOverrides::default();
+
+        overrides.set_image_asset("albedo", &handles.image(1));
+        overrides.set_image_asset("albedo", &handles.image(2));
+        assert_eq!(
+            slot_overrides(&overrides),
+            vec![("albedo", 0, MaterialInstanceSlotOverrideKind::Image)]
+        );
+        assert_eq!(image_load_handle(&overrides, "albedo"), LoadHandle(2));
+
+        overrides.set_buffer_data("per_material_data", &1u32);
+        overrides.set_buffer_data("per_material_data", &2u32);
+        assert_eq!(overrides.slots.len(), 2);
+        match &overrides.slots[1].value {
+            MaterialInstanceSlotOverrideValue::BufferData(data) => {
+                assert_eq!(data, &2u32.to_ne_bytes().to_vec())
+            }
+            _ => panic!("expected buffer data"),
+        }
+    }
+
+    #[test]
+    fn test_set_slot_keeps_values_of_other_kinds() {
+        let handles = TestHandles::new();
+        let mut overrides = MaterialInstanceOverrides::default();
+
+        // Like an image and a sampler bound to the same combined image sampler slot
+        overrides.set_image_asset("albedo", &handles.image(1));
+        overrides.set_buffer_data("albedo", &1u32);
+        assert_eq!(
+            slot_overrides(&overrides),
+            vec![
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::Image),
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::BufferData),
+            ]
+        );
+
+        // Replacing the image leaves the other value alone
+        overrides.set_image_asset("albedo", &handles.image(2));
+        assert_eq!(
+            slot_overrides(&overrides),
+            vec![
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::BufferData),
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::Image),
+            ]
+        );
+        assert_eq!(image_load_handle(&overrides, "albedo"), LoadHandle(2));
+
+        // Other slots and other array indices of the same slot are separate
+        overrides.set_image_asset("normal", &handles.image(3));
+        overrides.set_slot(
+            "albedo",
+            1,
+            MaterialInstanceSlotOverrideValue::ImageAsset(handles.image(4)),
+        );
+        assert_eq!(
+            slot_overrides(&overrides),
+            vec![
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::BufferData),
+                ("albedo", 0, MaterialInstanceSlotOverrideKind::Image),
+                ("normal", 0, MaterialInstanceSlotOverrideKind::Image),
+                ("albedo", 1, MaterialInstanceSlotOverrideKind::Image),
+            ]
+        );
+        assert_eq!(image_load_handle(&overrides, "albedo"), LoadHandle(2));
+    }
+
+    #[test]
+    fn test_set_uniform_param() {
+        let mut overrides = MaterialInstanceOverrides::default();
+        overrides.set_uniform_param("roughness", UniformParamValue::Float(0.5));
+        overrides.set_uniform_param(
+            "base_color_factor",
+            UniformParamValue::Vec4([1.0, 0.0, 0.0, 1.0]),
+        );
+        overrides.set_uniform_param("roughness", UniformParamValue::Float(0.25));
+
+        assert_eq!(
+            overrides.uniform_params,
+            vec![
+                MaterialInstanceUniformParam {
+                    path: "base_color_factor".to_string(),
+                    value: UniformParamValue::Vec4([1.0, 0.0, 0.0, 1.0]),
+                },
+                MaterialInstanceUniformParam {
+                    path: "roughness".to_string(),
+                    value: UniformParamValue::Float(0.25),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_uniform_param_overrides() {
+        // Two passes, only the second one has the uniform buffer
+        let pass_descriptor_set_layouts = vec![vec![], vec![layout("per_material_data")]];
+        let new_writes = || {
+            vec![
+                vec![],
+                vec![create_uninitialized_write_set_for_layout(&layout(
+                    "per_material_data",
+                ))],
+            ]
+        };
+
+        let mut overrides = MaterialInstanceOverrides::default();
+        overrides.set_uniform_param("per_material_data.roughness", UniformParamValue::Float(0.5));
+
+        let mut writes = new_writes();
+        apply_uniform_param_overrides(
+            &pass_descriptor_set_layouts,
+            &overrides.uniform_params,
+            &mut writes,
+        )
+        .unwrap();
+        let data = buffer_data(&writes[1][0]).unwrap();
+        assert_eq!(data.len(), 32);
+        assert_eq!(&data[16..20], &0.5f32.to_ne_bytes());
+
+        // A param no pass has is an error
+        overrides.set_uniform_param("metalness", UniformParamValue::Float(1.0));
+        let mut writes = new_writes();
+        let error = apply_uniform_param_overrides(
+            &pass_descriptor_set_layouts,
+            &overrides.uniform_params,
+            &mut writes,
+        )
+        .unwrap_err();
+        assert!(
+            format!("{:?}", error).contains("no pass of the material has it"),
+            "{:?}",
+            error
+        );
+
+        // So is a param of the wrong type
+        let mut overrides = MaterialInstanceOverrides::default();
+        overrides.set_uniform_param("roughness", UniformParamValue::Vec2([0.0, 1.0]));
+        let mut writes = new_writes();
+        assert!(apply_uniform_param_overrides(
+            &pass_descriptor_set_layouts,
+            &overrides.uniform_params,
+            &mut writes,
+        )
+        .is_err());
+    }
+}
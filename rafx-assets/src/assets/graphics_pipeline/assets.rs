@@ -4,20 +4,24 @@ use type_uuid::*;
 use crate::{
     AssetManager, DefaultAssetTypeHandler, DefaultAssetTypeLoadHandler, ImageAsset, ShaderAsset,
 };
+use arc_swap::ArcSwap;
 use distill::loader::handle::Handle;
 use fnv::FnvHashMap;
 use rafx_api::{
     RafxBlendState, RafxBlendStateRenderTarget, RafxCompareOp, RafxCullMode, RafxDepthState,
     RafxError, RafxFillMode, RafxFrontFace, RafxRasterizerState, RafxResult, RafxSamplerDef,
 };
-use rafx_framework::render_features::{RenderPhase, RenderPhaseIndex};
+use rafx_framework::render_features::{
+    RenderFeatureFlagMask, RenderPhase, RenderPhaseIndex, RenderView, ShaderKeywordMask,
+    ShaderVariantKeywords,
+};
 pub use rafx_framework::DescriptorSetLayoutResource;
 pub use rafx_framework::GraphicsPipelineResource;
 use rafx_framework::{
-    DescriptorSetArc, FixedFunctionState, MaterialPass, MaterialPassResource, MaterialShaderStage,
-    ResourceArc,
+    DescriptorSetArc, FixedFunctionState, ImageViewResource, MaterialPass, MaterialPassResource,
+    MaterialShaderStage, ResourceArc, WeakResourceArc,
 };
-use rafx_framework::{DescriptorSetWriteSet, SamplerResource};
+use rafx_framework::{DescriptorSetWriteSet, SamplerResource, UniformParamValue};
 use std::hash::Hash;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -206,6 +210,11 @@ pub struct MaterialPassData {
     pub phase: Option<String>,
     pub fixed_function_state: FixedFunctionStateData,
     pub shaders: Vec<GraphicsPipelineShaderStage>,
+    // Passes in the same phase with different keywords are variants of each other, usually with
+    // shaders compiled for each permutation of the keywords. The keywords must be registered with
+    // the render registry. See MaterialAsset::find_pass_variant
+    #[serde(default)]
+    pub keywords: Vec<String>,
 }
 
 impl MaterialPassData {
@@ -318,13 +327,21 @@ pub struct MaterialAssetData {
     pub passes: Vec<MaterialPassData>,
 }
 
+#[derive(Debug, Clone)]
+pub struct MaterialPassVariant {
+    pub keywords: ShaderVariantKeywords,
+    pub pass_index: usize,
+}
+
 pub struct MaterialAssetInner {
     //TODO: Consider making this named
     //TODO: Get cached graphics pipelines working
     //TODO: Could consider decoupling render cache from phases
     pub passes: Vec<MaterialPass>,
     pub pass_name_to_index: FnvHashMap<String, usize>,
+    // Only passes without keywords are included here
     pub pass_phase_to_index: FnvHashMap<RenderPhaseIndex, usize>,
+    pub pass_phase_to_variants: FnvHashMap<RenderPhaseIndex, Vec<MaterialPassVariant>>,
 }
 
 #[derive(TypeUuid, Clone)]
@@ -338,11 +355,13 @@ impl MaterialAsset {
         passes: Vec<MaterialPass>,
         pass_name_to_index: FnvHashMap<String, usize>,
         pass_phase_to_index: FnvHashMap<RenderPhaseIndex, usize>,
+        pass_phase_to_variants: FnvHashMap<RenderPhaseIndex, Vec<MaterialPassVariant>>,
     ) -> Self {
         let inner = MaterialAssetInner {
             passes,
             pass_name_to_index,
             pass_phase_to_index,
+            pass_phase_to_variants,
         };
 
         MaterialAsset {
@@ -371,6 +390,35 @@ impl MaterialAsset {
         self.inner.pass_phase_to_index.get(&index).copied()
     }
 
+    /// Picks the variant of the pass for the given phase that best matches the enabled keywords.
+    /// Keywords registered for feature flags come from the view's feature flags, other keywords
+    /// are provided per instance. Each variant is a separate material pass, so pipelines are cached
+    /// per variant.
+    pub fn find_pass_variant(
+        &self,
+        index: RenderPhaseIndex,
+        feature_flags: RenderFeatureFlagMask,
+        keywords: ShaderKeywordMask,
+    ) -> Option<usize> {
+        let variants = self.inner.pass_phase_to_variants.get(&index)?;
+        let variant_index = rafx_framework::render_features::select_shader_variant(
+            variants.iter().map(|x| &x.keywords),
+            feature_flags,
+            keywords,
+        )?;
+
+        Some(variants[variant_index].pass_index)
+    }
+
+    pub fn find_pass_variant_for_view(
+        &self,
+        view: &RenderView,
+        index: RenderPhaseIndex,
+        keywords: ShaderKeywordMask,
+    ) -> Option<usize> {
+        self.find_pass_variant(index, view.render_feature_flag_mask(), keywords)
+    }
+
     pub fn get_single_material_pass(
         &self
     ) -> Result<ResourceArc<MaterialPassResource>, &'static str> {
@@ -432,11 +480,77 @@ pub struct MaterialInstanceSlotAssignment {
     pub buffer_data: Option<Vec<u8>>,
 }
 
+/// Sets a member of a uniform buffer by name, i.e. "base_color_factor" or "light.range". Applied
+/// after slot assignments, so it overrides buffer_data for the same buffer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaterialInstanceUniformParam {
+    pub path: String,
+    pub value: UniformParamValue,
+}
+
 #[derive(TypeUuid, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[uuid = "0d8cacf7-79df-4aa6-b99e-659a9c3b5e6b"]
 pub struct MaterialInstanceAssetData {
     pub material: Handle<MaterialAsset>,
     pub slot_assignments: Vec<MaterialInstanceSlotAssignment>,
+    #[serde(default)]
+    pub uniform_params: Vec<MaterialInstanceUniformParam>,
+}
+
+// The descriptor sets of a material instance, indexed by pass and then by set within the pass.
+// They are replaced when a streamed image the material instance uses changes resolution, so fetch
+// them when drawing rather than holding on to them. Fetching them doesn't lock, they are swapped
+// atomically.
+pub struct MaterialInstanceDescriptorSets {
+    descriptor_sets: ArcSwap<Vec<Vec<Option<DescriptorSetArc>>>>,
+    // The image views the material instance was created with that belong to streamed images
+    streamed_image_views: Vec<WeakResourceArc<ImageViewResource>>,
+}
+
+impl MaterialInstanceDescriptorSets {
+    pub fn new(
+        descriptor_sets: Vec<Vec<Option<DescriptorSetArc>>>,
+        streamed_image_views: Vec<WeakResourceArc<ImageViewResource>>,
+    ) -> Self {
+        MaterialInstanceDescriptorSets {
+            descriptor_sets: ArcSwap::from_pointee(descriptor_sets),
+            streamed_image_views,
+        }
+    }
+
+    pub fn get(&self) -> Arc<Vec<Vec<Option<DescriptorSetArc>>>> {
+        self.descriptor_sets.load_full()
+    }
+
+    pub fn get_descriptor_set(
+        &self,
+        pass_index: usize,
+        layout_index: usize,
+    ) -> Option<DescriptorSetArc> {
+        self.descriptor_sets.load()[pass_index][layout_index].clone()
+    }
+
+    pub fn streamed_image_views(&self) -> &[WeakResourceArc<ImageViewResource>] {
+        &self.streamed_image_views
+    }
+
+    pub(crate) fn set(
+        &self,
+        descriptor_sets: Vec<Vec<Option<DescriptorSetArc>>>,
+    ) {
+        self.descriptor_sets.store(Arc::new(descriptor_sets));
+    }
+}
+
+impl std::fmt::Debug for MaterialInstanceDescriptorSets {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("MaterialInstanceDescriptorSets")
+            .field("descriptor_sets", &self.get())
+            .finish()
+    }
 }
 
 pub struct MaterialInstanceAssetInner {
@@ -444,7 +558,7 @@ pub struct MaterialInstanceAssetInner {
     pub material: MaterialAsset,
 
     // Arc these individually because some downstream systems care only about the descriptor sets
-    pub material_descriptor_sets: Arc<Vec<Vec<Option<DescriptorSetArc>>>>,
+    pub material_descriptor_sets: Arc<MaterialInstanceDescriptorSets>,
     pub slot_assignments: Vec<MaterialInstanceSlotAssignment>,
     pub descriptor_set_writes: Vec<Vec<DescriptorSetWriteSet>>,
 }
@@ -459,7 +573,7 @@ impl MaterialInstanceAsset {
     pub fn new(
         material: Handle<MaterialAsset>,
         material_asset: MaterialAsset,
-        material_descriptor_sets: Arc<Vec<Vec<Option<DescriptorSetArc>>>>,
+        material_descriptor_sets: Arc<MaterialInstanceDescriptorSets>,
         slot_assignments: Vec<MaterialInstanceSlotAssignment>,
         descriptor_set_writes: Vec<Vec<DescriptorSetWriteSet>>,
     ) -> Self {
@@ -496,6 +610,7 @@ impl DefaultAssetTypeLoadHandler<MaterialAssetData, MaterialAsset> for MaterialL
         let mut passes = Vec::with_capacity(asset_data.passes.len());
         let mut pass_name_to_index = FnvHashMap::default();
         let mut pass_phase_to_index = FnvHashMap::default();
+        let mut pass_phase_to_variants = FnvHashMap::<_, Vec<MaterialPassVariant>>::default();
 
         for pass_data in &asset_data.passes {
             let pass = pass_data.create_material_pass(asset_manager)?;
@@ -508,14 +623,44 @@ impl DefaultAssetTypeLoadHandler<MaterialAssetData, MaterialAsset> for MaterialL
                 assert!(old.is_none());
             }
 
+            let render_registry = asset_manager.resource_manager().render_registry();
+            let mut keywords = Vec::with_capacity(pass_data.keywords.len());
+            for keyword_name in &pass_data.keywords {
+                if let Some(keyword) = render_registry.shader_keyword_from_name(keyword_name) {
+                    keywords.push(keyword);
+                } else {
+                    let error = format!(
+                        "Load Material Failed - Pass refers to shader keyword {}, but this keyword was not registered",
+                        keyword_name
+                    );
+                    log::error!("{}", error);
+                    return Err(error)?;
+                }
+            }
+
             if let Some(phase_name) = &pass_data.phase {
-                if let Some(phase_index) = asset_manager
-                    .resource_manager()
-                    .render_registry()
-                    .render_phase_index_from_name(phase_name)
+                if let Some(phase_index) = render_registry.render_phase_index_from_name(phase_name)
                 {
-                    let old = pass_phase_to_index.insert(phase_index, pass_index);
-                    assert!(old.is_none());
+                    if keywords.is_empty() {
+                        let old = pass_phase_to_index.insert(phase_index, pass_index);
+                        assert!(old.is_none());
+                    }
+
+                    let keywords = ShaderVariantKeywords::new(&keywords);
+                    let variants = pass_phase_to_variants.entry(phase_index).or_default();
+                    if variants.iter().any(|x| x.keywords == keywords) {
+                        let error = format!(
+                            "Load Material Failed - More than one pass for phase {} has keywords {:?}",
+                            phase_name, pass_data.keywords
+                        );
+                        log::error!("{}", error);
+                        return Err(error)?;
+                    }
+
+                    variants.push(MaterialPassVariant {
+                        keywords,
+                        pass_index,
+                    });
                 } else {
                     let error = format!(
                         "Load Material Failed - Pass refers to phase name {}, but this phase name was not registered",
@@ -524,6 +669,13 @@ impl DefaultAssetTypeLoadHandler<MaterialAssetData, MaterialAsset> for MaterialL
                     log::error!("{}", error);
                     return Err(error)?;
                 }
+            } else if !keywords.is_empty() {
+                let error = format!(
+                    "Load Material Failed - Pass {:?} has keywords but no phase",
+                    pass_data.name
+                );
+                log::error!("{}", error);
+                return Err(error)?;
             }
         }
 
@@ -531,6 +683,7 @@ impl DefaultAssetTypeLoadHandler<MaterialAssetData, MaterialAsset> for MaterialL
             passes,
             pass_name_to_index,
             pass_phase_to_index,
+            pass_phase_to_variants,
         ))
     }
 }
@@ -562,13 +715,12 @@ impl DefaultAssetTypeLoadHandler<MaterialInstanceAssetData, MaterialInstanceAsse
             asset_data.slot_assignments
         );
 
-        // This will be references to descriptor sets. Indexed by pass, and then by set within the pass.
-        let mut material_descriptor_sets = Vec::with_capacity(material_asset.passes.len());
         for pass in &*material_asset.passes {
             let pass_descriptor_set_writes = asset_manager
                 .create_write_sets_for_material_instance_pass(
                     pass,
                     &asset_data.slot_assignments,
+                    &asset_data.uniform_params,
                     asset_manager.resources(),
                 )?;
 
@@ -577,42 +729,16 @@ impl DefaultAssetTypeLoadHandler<MaterialInstanceAssetData, MaterialInstanceAsse
                 pass_descriptor_set_writes
             );
 
-            material_instance_descriptor_set_writes.push(pass_descriptor_set_writes.clone());
-
-            // This will contain the descriptor sets created for this pass, one for each set within the pass
-            let mut pass_descriptor_sets = Vec::with_capacity(pass_descriptor_set_writes.len());
-
-            let material_pass_descriptor_set_layouts =
-                &pass.material_pass_resource.get_raw().descriptor_set_layouts;
-
-            //
-            // Register the writes into the correct descriptor set pools
-            //
-            for (layout_index, layout_writes) in pass_descriptor_set_writes.into_iter().enumerate()
-            {
-                if !layout_writes.elements.is_empty() {
-                    let descriptor_set = asset_manager
-                        .material_instance_descriptor_sets_mut()
-                        .create_descriptor_set_with_writes(
-                            &material_pass_descriptor_set_layouts[layout_index],
-                            layout_writes,
-                        )?;
-
-                    pass_descriptor_sets.push(Some(descriptor_set));
-                } else {
-                    // If there are no descriptors in this layout index, assume the layout does not
-                    // exist
-                    pass_descriptor_sets.push(None);
-                }
-            }
-
-            material_descriptor_sets.push(pass_descriptor_sets);
+            material_instance_descriptor_set_writes.push(pass_descriptor_set_writes);
         }
 
+        let material_descriptor_sets = asset_manager.create_material_instance_descriptor_sets(
+            &material_asset,
+            &material_instance_descriptor_set_writes,
+        )?;
+
         log::trace!("Loaded material\n{:#?}", material_descriptor_sets);
 
-        // Put these in an arc to avoid cloning the underlying data repeatedly
-        let material_descriptor_sets = Arc::new(material_descriptor_sets);
         Ok(MaterialInstanceAsset::new(
             asset_data.material,
             material_asset.clone(),
@@ -72,7 +72,7 @@ impl Importer for MaterialImporter {
     where
         Self: Sized,
     {
-        2
+        3
     }
 
     fn version(&self) -> u32 {
@@ -125,7 +125,7 @@ impl Importer for MaterialInstanceImporter {
     where
         Self: Sized,
     {
-        6
+        7
     }
 
     fn version(&self) -> u32 {
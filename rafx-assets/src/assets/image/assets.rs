@@ -1,10 +1,15 @@
+use super::float_formats;
+use super::mip_generation::{
+    generate_mip_chain, rgba32f_to_rgba8, rgba8_to_rgba32f, ImageAssetMipSettings,
+};
 use crate::assets::upload::ImageUploadOpResult;
 use crate::distill_impl::{AssetResource, ResourceAssetLoader};
 use crate::{
     AssetLookup, AssetManager, AssetTypeHandler, AssetTypeHandlerFactory, DynAssetLookup,
-    LoadQueues,
+    GpuImageData, LoadQueues,
 };
-use rafx_api::{RafxResourceType, RafxResult, RafxTexture};
+use distill::loader::LoadHandle;
+use rafx_api::{RafxFormat, RafxResourceType, RafxResult, RafxTexture};
 use rafx_framework::{ImageResource, ImageViewResource, ResourceArc};
 use serde::{Deserialize, Serialize};
 use std::any::TypeId;
@@ -50,8 +55,9 @@ impl Into<basis_universal::BasisTextureFormat> for ImageAssetBasisCompressionTyp
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct ImageAssetBasisCompressionSettings {
-    compression_type: ImageAssetBasisCompressionType,
-    quality: u32,
+    pub compression_type: ImageAssetBasisCompressionType,
+    // 1..=255 for ETC1S, 0..=4 for UASTC
+    pub quality: u32,
 }
 
 #[cfg(feature = "basis-universal")]
@@ -75,12 +81,48 @@ impl ImageAssetBasisCompressionSettings {
 pub enum ImageAssetDataFormat {
     RawRGBA32,
     BasisCompressed,
+    // Floating point formats are always linear
+    RawRGBA16Float,
+    RawRGBA32Float,
+    RawB10G11R11Float,
+    // Data in a GPU format with every layer and mip level precomputed (see `from_precomputed`)
+    Precomputed {
+        format: RafxFormat,
+        layer_count: u32,
+        mip_count: u32,
+    },
+}
+
+impl ImageAssetDataFormat {
+    // The format of data that is uploaded as is. Basis compressed data is transcoded to a format
+    // the device supports when it is uploaded.
+    pub fn raw_format(
+        self,
+        color_space: ImageAssetColorSpace,
+    ) -> Option<RafxFormat> {
+        let color_space: crate::GpuImageDataColorSpace = color_space.into();
+        match self {
+            ImageAssetDataFormat::RawRGBA32 => Some(color_space.rgba8()),
+            ImageAssetDataFormat::BasisCompressed => None,
+            ImageAssetDataFormat::RawRGBA16Float => Some(RafxFormat::R16G16B16A16_SFLOAT),
+            ImageAssetDataFormat::RawRGBA32Float => Some(RafxFormat::R32G32B32A32_SFLOAT),
+            ImageAssetDataFormat::RawB10G11R11Float => Some(RafxFormat::B10G11R11_UFLOAT_PACK32),
+            ImageAssetDataFormat::Precomputed { format, .. } => Some(format),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum ImageAssetDataFormatConfig {
     RawRGBA32,
     BasisCompressed(ImageAssetBasisCompressionSettings),
+    // Floating point formats are only produced from HDR sources (see `from_raw_rgba32f`). Vulkan
+    // only guarantees linear filtering (needed for runtime mip generation) for RawRGBA16Float and
+    // RawB10G11R11Float.
+    RawRGBA16Float,
+    RawRGBA32Float,
+    // Drops alpha
+    RawB10G11R11Float,
 }
 
 #[derive(TypeUuid, Serialize, Deserialize, Clone)]
@@ -144,13 +186,63 @@ impl ImageAssetData {
         mip_generation: ImageAssetMipGeneration,
         resource_type: RafxResourceType,
         raw_rgba32: &[u8],
+    ) -> RafxResult<ImageAssetData> {
+        Self::from_raw_rgba32_with_mip_settings(
+            width,
+            height,
+            color_space,
+            format_config,
+            mip_generation,
+            &ImageAssetMipSettings::default(),
+            resource_type,
+            raw_rgba32,
+        )
+    }
+
+    // mip_settings controls how mips are generated for RawRGBA32 with
+    // ImageAssetMipGeneration::Precomupted. Basis compressed images generate their own mips.
+    pub fn from_raw_rgba32_with_mip_settings(
+        width: u32,
+        height: u32,
+        color_space: ImageAssetColorSpace,
+        format_config: ImageAssetDataFormatConfig,
+        mip_generation: ImageAssetMipGeneration,
+        mip_settings: &ImageAssetMipSettings,
+        resource_type: RafxResourceType,
+        raw_rgba32: &[u8],
     ) -> RafxResult<ImageAssetData> {
         match format_config {
             ImageAssetDataFormatConfig::RawRGBA32 => {
                 let generate_mips_at_runtime = match mip_generation {
                     ImageAssetMipGeneration::NoMips => false,
                     ImageAssetMipGeneration::Precomupted => {
-                        Err("RawRGBA32 cannot store precomputed mipmaps")?
+                        let levels = generate_mip_chain(
+                            width,
+                            height,
+                            rgba8_to_rgba32f(raw_rgba32, color_space),
+                            mip_settings,
+                        );
+
+                        // The first level is stored as is to avoid rounding errors
+                        let mut data = raw_rgba32.to_vec();
+                        for level in &levels[1..] {
+                            data.extend(rgba32f_to_rgba8(&level.rgba, color_space));
+                        }
+
+                        let gpu_color_space: crate::GpuImageDataColorSpace = color_space.into();
+                        return Ok(ImageAssetData {
+                            width,
+                            height,
+                            color_space,
+                            format: ImageAssetDataFormat::Precomputed {
+                                format: gpu_color_space.rgba8(),
+                                layer_count: 1,
+                                mip_count: levels.len() as u32,
+                            },
+                            generate_mips_at_runtime: false,
+                            resource_type,
+                            data,
+                        });
                     }
                     ImageAssetMipGeneration::Runtime => true,
                 };
@@ -165,6 +257,12 @@ impl ImageAssetData {
                     data: raw_rgba32.to_vec(),
                 })
             }
+            ImageAssetDataFormatConfig::RawRGBA16Float
+            | ImageAssetDataFormatConfig::RawRGBA32Float
+            | ImageAssetDataFormatConfig::RawB10G11R11Float => Err(format!(
+                "{:?} requires HDR source data, use from_raw_rgba32f",
+                format_config
+            ))?,
             #[cfg(not(feature = "basis-universal"))]
             ImageAssetDataFormatConfig::BasisCompressed(settings) => {
                 unimplemented!("crate not built with basis-universal feature");
@@ -221,6 +319,296 @@ impl ImageAssetData {
             }
         }
     }
+
+    // Creates an image from linear, floating point RGBA data (i.e. a decoded .hdr or .exr file).
+    // Only the floating point formats are supported.
+    pub fn from_raw_rgba32f(
+        width: u32,
+        height: u32,
+        format_config: ImageAssetDataFormatConfig,
+        mip_generation: ImageAssetMipGeneration,
+        mip_settings: &ImageAssetMipSettings,
+        resource_type: RafxResourceType,
+        raw_rgba32f: &[f32],
+    ) -> RafxResult<ImageAssetData> {
+        if raw_rgba32f.len() != (width * height * 4) as usize {
+            Err(format!(
+                "Expected {} floats for a {}x{} RGBA image but got {}",
+                width * height * 4,
+                width,
+                height,
+                raw_rgba32f.len()
+            ))?;
+        }
+
+        let (format, convert): (ImageAssetDataFormat, fn(&[f32]) -> Vec<u8>) = match format_config {
+            ImageAssetDataFormatConfig::RawRGBA16Float => (
+                ImageAssetDataFormat::RawRGBA16Float,
+                float_formats::rgba32f_to_rgba16f_bytes,
+            ),
+            ImageAssetDataFormatConfig::RawRGBA32Float => (
+                ImageAssetDataFormat::RawRGBA32Float,
+                float_formats::rgba32f_to_rgba32f_bytes,
+            ),
+            ImageAssetDataFormatConfig::RawB10G11R11Float => (
+                ImageAssetDataFormat::RawB10G11R11Float,
+                float_formats::rgba32f_to_b10g11r11_bytes,
+            ),
+            ImageAssetDataFormatConfig::RawRGBA32
+            | ImageAssetDataFormatConfig::BasisCompressed(_) => Err(format!(
+                "HDR images must use a floating point format but {:?} was requested",
+                format_config
+            ))?,
+        };
+
+        let (format, generate_mips_at_runtime, data) = match mip_generation {
+            ImageAssetMipGeneration::NoMips => (format, false, convert(raw_rgba32f)),
+            ImageAssetMipGeneration::Precomupted => {
+                let levels = generate_mip_chain(width, height, raw_rgba32f.to_vec(), mip_settings);
+
+                let mut data = Vec::new();
+                for level in &levels {
+                    data.extend(convert(&level.rgba));
+                }
+
+                let format = ImageAssetDataFormat::Precomputed {
+                    format: format.raw_format(ImageAssetColorSpace::Linear).unwrap(),
+                    layer_count: 1,
+                    mip_count: levels.len() as u32,
+                };
+                (format, false, data)
+            }
+            ImageAssetMipGeneration::Runtime => (format, true, convert(raw_rgba32f)),
+        };
+
+        Ok(ImageAssetData {
+            width,
+            height,
+            color_space: ImageAssetColorSpace::Linear,
+            format,
+            generate_mips_at_runtime,
+            resource_type,
+            data,
+        })
+    }
+
+    // Creates an image with several layers (i.e. the faces of a cubemap) from linear RGBA floats.
+    // RawRGBA32 is stored in the given color space, the floating point formats are always linear.
+    pub fn from_rgba32f_layers(
+        width: u32,
+        height: u32,
+        color_space: ImageAssetColorSpace,
+        format_config: ImageAssetDataFormatConfig,
+        mip_generation: ImageAssetMipGeneration,
+        mip_settings: &ImageAssetMipSettings,
+        resource_type: RafxResourceType,
+        layers: Vec<Vec<f32>>,
+    ) -> RafxResult<ImageAssetData> {
+        for layer in &layers {
+            if layer.len() != (width * height * 4) as usize {
+                Err(format!(
+                    "Expected {} floats for each layer of a {}x{} RGBA image but got {}",
+                    width * height * 4,
+                    width,
+                    height,
+                    layer.len()
+                ))?;
+            }
+        }
+
+        let mip_chains = layers
+            .into_iter()
+            .map(|layer| {
+                if mip_generation == ImageAssetMipGeneration::Precomupted {
+                    generate_mip_chain(width, height, layer, mip_settings)
+                        .into_iter()
+                        .map(|level| level.rgba)
+                        .collect()
+                } else {
+                    vec![layer]
+                }
+            })
+            .collect();
+
+        let mut asset_data = Self::from_rgba32f_mip_chains(
+            width,
+            height,
+            color_space,
+            format_config,
+            resource_type,
+            mip_chains,
+        )?;
+        asset_data.generate_mips_at_runtime = mip_generation == ImageAssetMipGeneration::Runtime;
+        Ok(asset_data)
+    }
+
+    // Like from_rgba32f_layers, but with mip levels that were built by the caller. Each layer is a
+    // list of levels from largest to smallest, each half the size of the previous one. This is for
+    // mips that are not a plain downsample, i.e. a prefiltered environment map.
+    pub fn from_rgba32f_mip_chains(
+        width: u32,
+        height: u32,
+        color_space: ImageAssetColorSpace,
+        format_config: ImageAssetDataFormatConfig,
+        resource_type: RafxResourceType,
+        layers: Vec<Vec<Vec<f32>>>,
+    ) -> RafxResult<ImageAssetData> {
+        let (format, convert): (ImageAssetDataFormat, Box<dyn Fn(&[f32]) -> Vec<u8>>) =
+            match format_config {
+                ImageAssetDataFormatConfig::RawRGBA32 => (
+                    ImageAssetDataFormat::RawRGBA32,
+                    Box::new(move |rgba32f| rgba32f_to_rgba8(rgba32f, color_space)),
+                ),
+                ImageAssetDataFormatConfig::RawRGBA16Float => (
+                    ImageAssetDataFormat::RawRGBA16Float,
+                    Box::new(float_formats::rgba32f_to_rgba16f_bytes),
+                ),
+                ImageAssetDataFormatConfig::RawRGBA32Float => (
+                    ImageAssetDataFormat::RawRGBA32Float,
+                    Box::new(float_formats::rgba32f_to_rgba32f_bytes),
+                ),
+                ImageAssetDataFormatConfig::RawB10G11R11Float => (
+                    ImageAssetDataFormat::RawB10G11R11Float,
+                    Box::new(float_formats::rgba32f_to_b10g11r11_bytes),
+                ),
+                ImageAssetDataFormatConfig::BasisCompressed(_) => {
+                    Err("Basis compression is not supported for images built from layers")?
+                }
+            };
+
+        let layer_count = layers.len() as u32;
+        let mip_count = layers.first().map(|levels| levels.len()).unwrap_or(0) as u32;
+        let mut data = Vec::new();
+        for levels in layers {
+            if levels.len() as u32 != mip_count {
+                Err("All layers must have the same number of mip levels")?;
+            }
+
+            for (level, rgba32f) in levels.iter().enumerate() {
+                let level_width = (width >> level).max(1);
+                let level_height = (height >> level).max(1);
+                if rgba32f.len() != (level_width * level_height * 4) as usize {
+                    Err(format!(
+                        "Expected {} floats for mip level {} ({}x{}) but got {}",
+                        level_width * level_height * 4,
+                        level,
+                        level_width,
+                        level_height,
+                        rgba32f.len()
+                    ))?;
+                }
+
+                data.extend(convert(rgba32f));
+            }
+        }
+
+        Self::from_precomputed(
+            width,
+            height,
+            format.raw_format(color_space).unwrap(),
+            layer_count,
+            mip_count,
+            resource_type,
+            data,
+        )
+    }
+
+    // Creates an image from data that is already in a GPU format, i.e. a KTX2 or DDS file. Layers
+    // (array elements, or the faces of cubemaps) are stored one after another, each with its mip
+    // levels from largest to smallest and no padding.
+    pub fn from_precomputed(
+        width: u32,
+        height: u32,
+        format: RafxFormat,
+        layer_count: u32,
+        mip_count: u32,
+        resource_type: RafxResourceType,
+        data: Vec<u8>,
+    ) -> RafxResult<ImageAssetData> {
+        if format.is_undefined() || format.has_depth_or_stencil() {
+            Err(format!("Image format {:?} cannot be imported", format))?;
+        }
+
+        if layer_count == 0 || mip_count == 0 {
+            Err("Images must have at least one layer and one mip level")?;
+        }
+
+        if resource_type.contains(RafxResourceType::TEXTURE_CUBE) && layer_count % 6 != 0 {
+            Err(format!(
+                "Cubemaps must have a multiple of 6 layers but {} were provided",
+                layer_count
+            ))?;
+        }
+
+        Self::check_precomputed_mip_count(width, height, mip_count)?;
+
+        let expected_size =
+            Self::precomputed_layer_size(width, height, format, mip_count) * layer_count as usize;
+        if data.len() != expected_size {
+            Err(format!(
+                "Expected {} bytes for a {}x{} {:?} image with {} layers and {} mips but got {}",
+                expected_size,
+                width,
+                height,
+                format,
+                layer_count,
+                mip_count,
+                data.len()
+            ))?;
+        }
+
+        Ok(ImageAssetData {
+            width,
+            height,
+            // Unused, the format determines the color space
+            color_space: ImageAssetColorSpace::Linear,
+            format: ImageAssetDataFormat::Precomputed {
+                format,
+                layer_count,
+                mip_count,
+            },
+            generate_mips_at_runtime: false,
+            resource_type,
+            data,
+        })
+    }
+
+    // Mip levels smaller than 1x1 don't exist, and computing their size would overflow the shifts
+    // in precomputed_layer_size
+    pub fn check_precomputed_mip_count(
+        width: u32,
+        height: u32,
+        mip_count: u32,
+    ) -> RafxResult<()> {
+        let max_mip_count =
+            rafx_api::extra::mipmaps::mip_level_max_count_for_image_size(width, height);
+        if mip_count > max_mip_count {
+            Err(format!(
+                "A {}x{} image can have at most {} mip levels but {} were provided",
+                width, height, max_mip_count, mip_count
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    // The size of a layer with all of its mip levels
+    pub fn precomputed_layer_size(
+        width: u32,
+        height: u32,
+        format: RafxFormat,
+        mip_count: u32,
+    ) -> usize {
+        (0..mip_count)
+            .map(|mip| {
+                GpuImageData::mip_level_size_in_bytes(
+                    format,
+                    (width >> mip).max(1),
+                    (height >> mip).max(1),
+                )
+            })
+            .sum()
+    }
 }
 
 #[derive(TypeUuid, Clone)]
@@ -258,7 +646,7 @@ impl AssetTypeHandler for ImageAssetTypeHandler {
         for request in self.load_queues.take_load_requests() {
             //TODO: Route the request directly to the upload queue
             log::trace!("Uploading image {:?}", request.load_handle);
-            asset_manager.upload_manager().upload_image(request)?;
+            asset_manager.upload_image(request)?;
         }
 
         let results: Vec<_> = asset_manager
@@ -270,7 +658,8 @@ impl AssetTypeHandler for ImageAssetTypeHandler {
             match result {
                 ImageUploadOpResult::UploadComplete(load_op, result_tx, texture) => {
                     log::trace!("Uploading image {:?} complete", load_op.load_handle());
-                    let loaded_asset = finish_load_image(asset_manager, texture);
+                    let loaded_asset =
+                        finish_load_image(asset_manager, load_op.load_handle(), texture);
                     crate::assets::asset_type_handler::handle_load_result(
                         load_op,
                         loaded_asset,
@@ -280,10 +669,16 @@ impl AssetTypeHandler for ImageAssetTypeHandler {
                 }
                 ImageUploadOpResult::UploadError(load_handle) => {
                     log::trace!("Uploading image {:?} failed", load_handle);
+                    asset_manager
+                        .texture_streaming_mut()
+                        .remove_pending_image(load_handle);
                     // Don't need to do anything - the uploaded should have triggered an error on the load_op
                 }
                 ImageUploadOpResult::UploadDrop(load_handle) => {
                     log::trace!("Uploading image {:?} cancelled", load_handle);
+                    asset_manager
+                        .texture_streaming_mut()
+                        .remove_pending_image(load_handle);
                     // Don't need to do anything - the uploaded should have triggered an error on the load_op
                 }
             }
@@ -312,6 +707,7 @@ impl AssetTypeHandler for ImageAssetTypeHandler {
 #[profiling::function]
 fn finish_load_image(
     asset_manager: &mut AssetManager,
+    load_handle: LoadHandle,
     texture: RafxTexture,
 ) -> RafxResult<ImageAsset> {
     let image = asset_manager.resources().insert_image(texture);
@@ -320,5 +716,10 @@ fn finish_load_image(
         .resources()
         .get_or_create_image_view(&image, None)?;
 
+    // If the image is streamed, this view only has the resident mip levels
+    asset_manager
+        .texture_streaming_mut()
+        .register_image(load_handle, &image_view);
+
     Ok(ImageAsset { image, image_view })
 }
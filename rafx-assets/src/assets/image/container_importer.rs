@@ -0,0 +1,1039 @@
+// Importers for KTX2 and DDS files. These containers store data that is already in a GPU format,
+// so every layer, cube face and mip level is kept as is and uploaded without conversion.
+use crate::assets::image::ImageAssetData;
+use distill::importer::{Error, ImportedAsset, Importer, ImporterValue};
+use distill::{core::AssetUuid, importer::ImportOp};
+use rafx_api::{RafxFormat, RafxResourceType, RafxResult};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use type_uuid::*;
+
+fn read_u32(
+    bytes: &[u8],
+    offset: usize,
+) -> RafxResult<u32> {
+    let data = bytes
+        .get(offset..offset + 4)
+        .ok_or("Unexpected end of file")?;
+    Ok(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+}
+
+fn read_u64(
+    bytes: &[u8],
+    offset: usize,
+) -> RafxResult<u64> {
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+fn get_range(
+    bytes: &[u8],
+    offset: usize,
+    length: usize,
+) -> RafxResult<&[u8]> {
+    Ok(bytes
+        .get(offset..offset.checked_add(length).ok_or("Invalid data range")?)
+        .ok_or("Unexpected end of file")?)
+}
+
+fn mip_level_size(
+    width: u32,
+    height: u32,
+    format: RafxFormat,
+    mip: u32,
+) -> usize {
+    crate::GpuImageData::mip_level_size_in_bytes(
+        format,
+        (width >> mip).max(1),
+        (height >> mip).max(1),
+    )
+}
+
+//
+// KTX2
+//
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+// RafxFormat is declared in the same order as VkFormat, which KTX2 uses to describe its data
+fn rafx_format_from_vk_format(vk_format: u32) -> Option<RafxFormat> {
+    Some(match vk_format {
+        1 => RafxFormat::R4G4_UNORM_PACK8,
+        2 => RafxFormat::R4G4B4A4_UNORM_PACK16,
+        3 => RafxFormat::B4G4R4A4_UNORM_PACK16,
+        4 => RafxFormat::R5G6B5_UNORM_PACK16,
+        5 => RafxFormat::B5G6R5_UNORM_PACK16,
+        6 => RafxFormat::R5G5B5A1_UNORM_PACK16,
+        7 => RafxFormat::B5G5R5A1_UNORM_PACK16,
+        8 => RafxFormat::A1R5G5B5_UNORM_PACK16,
+        9 => RafxFormat::R8_UNORM,
+        10 => RafxFormat::R8_SNORM,
+        11 => RafxFormat::R8_USCALED,
+        12 => RafxFormat::R8_SSCALED,
+        13 => RafxFormat::R8_UINT,
+        14 => RafxFormat::R8_SINT,
+        15 => RafxFormat::R8_SRGB,
+        16 => RafxFormat::R8G8_UNORM,
+        17 => RafxFormat::R8G8_SNORM,
+        18 => RafxFormat::R8G8_USCALED,
+        19 => RafxFormat::R8G8_SSCALED,
+        20 => RafxFormat::R8G8_UINT,
+        21 => RafxFormat::R8G8_SINT,
+        22 => RafxFormat::R8G8_SRGB,
+        23 => RafxFormat::R8G8B8_UNORM,
+        24 => RafxFormat::R8G8B8_SNORM,
+        25 => RafxFormat::R8G8B8_USCALED,
+        26 => RafxFormat::R8G8B8_SSCALED,
+        27 => RafxFormat::R8G8B8_UINT,
+        28 => RafxFormat::R8G8B8_SINT,
+        29 => RafxFormat::R8G8B8_SRGB,
+        30 => RafxFormat::B8G8R8_UNORM,
+        31 => RafxFormat::B8G8R8_SNORM,
+        32 => RafxFormat::B8G8R8_USCALED,
+        33 => RafxFormat::B8G8R8_SSCALED,
+        34 => RafxFormat::B8G8R8_UINT,
+        35 => RafxFormat::B8G8R8_SINT,
+        36 => RafxFormat::B8G8R8_SRGB,
+        37 => RafxFormat::R8G8B8A8_UNORM,
+        38 => RafxFormat::R8G8B8A8_SNORM,
+        39 => RafxFormat::R8G8B8A8_USCALED,
+        40 => RafxFormat::R8G8B8A8_SSCALED,
+        41 => RafxFormat::R8G8B8A8_UINT,
+        42 => RafxFormat::R8G8B8A8_SINT,
+        43 => RafxFormat::R8G8B8A8_SRGB,
+        44 => RafxFormat::B8G8R8A8_UNORM,
+        45 => RafxFormat::B8G8R8A8_SNORM,
+        46 => RafxFormat::B8G8R8A8_USCALED,
+        47 => RafxFormat::B8G8R8A8_SSCALED,
+        48 => RafxFormat::B8G8R8A8_UINT,
+        49 => RafxFormat::B8G8R8A8_SINT,
+        50 => RafxFormat::B8G8R8A8_SRGB,
+        51 => RafxFormat::A8B8G8R8_UNORM_PACK32,
+        52 => RafxFormat::A8B8G8R8_SNORM_PACK32,
+        53 => RafxFormat::A8B8G8R8_USCALED_PACK32,
+        54 => RafxFormat::A8B8G8R8_SSCALED_PACK32,
+        55 => RafxFormat::A8B8G8R8_UINT_PACK32,
+        56 => RafxFormat::A8B8G8R8_SINT_PACK32,
+        57 => RafxFormat::A8B8G8R8_SRGB_PACK32,
+        58 => RafxFormat::A2R10G10B10_UNORM_PACK32,
+        59 => RafxFormat::A2R10G10B10_SNORM_PACK32,
+        60 => RafxFormat::A2R10G10B10_USCALED_PACK32,
+        61 => RafxFormat::A2R10G10B10_SSCALED_PACK32,
+        62 => RafxFormat::A2R10G10B10_UINT_PACK32,
+        63 => RafxFormat::A2R10G10B10_SINT_PACK32,
+        64 => RafxFormat::A2B10G10R10_UNORM_PACK32,
+        65 => RafxFormat::A2B10G10R10_SNORM_PACK32,
+        66 => RafxFormat::A2B10G10R10_USCALED_PACK32,
+        67 => RafxFormat::A2B10G10R10_SSCALED_PACK32,
+        68 => RafxFormat::A2B10G10R10_UINT_PACK32,
+        69 => RafxFormat::A2B10G10R10_SINT_PACK32,
+        70 => RafxFormat::R16_UNORM,
+        71 => RafxFormat::R16_SNORM,
+        72 => RafxFormat::R16_USCALED,
+        73 => RafxFormat::R16_SSCALED,
+        74 => RafxFormat::R16_UINT,
+        75 => RafxFormat::R16_SINT,
+        76 => RafxFormat::R16_SFLOAT,
+        77 => RafxFormat::R16G16_UNORM,
+        78 => RafxFormat::R16G16_SNORM,
+        79 => RafxFormat::R16G16_USCALED,
+        80 => RafxFormat::R16G16_SSCALED,
+        81 => RafxFormat::R16G16_UINT,
+        82 => RafxFormat::R16G16_SINT,
+        83 => RafxFormat::R16G16_SFLOAT,
+        84 => RafxFormat::R16G16B16_UNORM,
+        85 => RafxFormat::R16G16B16_SNORM,
+        86 => RafxFormat::R16G16B16_USCALED,
+        87 => RafxFormat::R16G16B16_SSCALED,
+        88 => RafxFormat::R16G16B16_UINT,
+        89 => RafxFormat::R16G16B16_SINT,
+        90 => RafxFormat::R16G16B16_SFLOAT,
+        91 => RafxFormat::R16G16B16A16_UNORM,
+        92 => RafxFormat::R16G16B16A16_SNORM,
+        93 => RafxFormat::R16G16B16A16_USCALED,
+        94 => RafxFormat::R16G16B16A16_SSCALED,
+        95 => RafxFormat::R16G16B16A16_UINT,
+        96 => RafxFormat::R16G16B16A16_SINT,
+        97 => RafxFormat::R16G16B16A16_SFLOAT,
+        98 => RafxFormat::R32_UINT,
+        99 => RafxFormat::R32_SINT,
+        100 => RafxFormat::R32_SFLOAT,
+        101 => RafxFormat::R32G32_UINT,
+        102 => RafxFormat::R32G32_SINT,
+        103 => RafxFormat::R32G32_SFLOAT,
+        104 => RafxFormat::R32G32B32_UINT,
+        105 => RafxFormat::R32G32B32_SINT,
+        106 => RafxFormat::R32G32B32_SFLOAT,
+        107 => RafxFormat::R32G32B32A32_UINT,
+        108 => RafxFormat::R32G32B32A32_SINT,
+        109 => RafxFormat::R32G32B32A32_SFLOAT,
+        110 => RafxFormat::R64_UINT,
+        111 => RafxFormat::R64_SINT,
+        112 => RafxFormat::R64_SFLOAT,
+        113 => RafxFormat::R64G64_UINT,
+        114 => RafxFormat::R64G64_SINT,
+        115 => RafxFormat::R64G64_SFLOAT,
+        116 => RafxFormat::R64G64B64_UINT,
+        117 => RafxFormat::R64G64B64_SINT,
+        118 => RafxFormat::R64G64B64_SFLOAT,
+        119 => RafxFormat::R64G64B64A64_UINT,
+        120 => RafxFormat::R64G64B64A64_SINT,
+        121 => RafxFormat::R64G64B64A64_SFLOAT,
+        122 => RafxFormat::B10G11R11_UFLOAT_PACK32,
+        123 => RafxFormat::E5B9G9R9_UFLOAT_PACK32,
+        124 => RafxFormat::D16_UNORM,
+        125 => RafxFormat::X8_D24_UNORM_PACK32,
+        126 => RafxFormat::D32_SFLOAT,
+        127 => RafxFormat::S8_UINT,
+        128 => RafxFormat::D16_UNORM_S8_UINT,
+        129 => RafxFormat::D24_UNORM_S8_UINT,
+        130 => RafxFormat::D32_SFLOAT_S8_UINT,
+        131 => RafxFormat::BC1_RGB_UNORM_BLOCK,
+        132 => RafxFormat::BC1_RGB_SRGB_BLOCK,
+        133 => RafxFormat::BC1_RGBA_UNORM_BLOCK,
+        134 => RafxFormat::BC1_RGBA_SRGB_BLOCK,
+        135 => RafxFormat::BC2_UNORM_BLOCK,
+        136 => RafxFormat::BC2_SRGB_BLOCK,
+        137 => RafxFormat::BC3_UNORM_BLOCK,
+        138 => RafxFormat::BC3_SRGB_BLOCK,
+        139 => RafxFormat::BC4_UNORM_BLOCK,
+        140 => RafxFormat::BC4_SNORM_BLOCK,
+        141 => RafxFormat::BC5_UNORM_BLOCK,
+        142 => RafxFormat::BC5_SNORM_BLOCK,
+        143 => RafxFormat::BC6H_UFLOAT_BLOCK,
+        144 => RafxFormat::BC6H_SFLOAT_BLOCK,
+        145 => RafxFormat::BC7_UNORM_BLOCK,
+        146 => RafxFormat::BC7_SRGB_BLOCK,
+        147 => RafxFormat::ETC2_R8G8B8_UNORM_BLOCK,
+        148 => RafxFormat::ETC2_R8G8B8_SRGB_BLOCK,
+        149 => RafxFormat::ETC2_R8G8B8A1_UNORM_BLOCK,
+        150 => RafxFormat::ETC2_R8G8B8A1_SRGB_BLOCK,
+        151 => RafxFormat::ETC2_R8G8B8A8_UNORM_BLOCK,
+        152 => RafxFormat::ETC2_R8G8B8A8_SRGB_BLOCK,
+        153 => RafxFormat::EAC_R11_UNORM_BLOCK,
+        154 => RafxFormat::EAC_R11_SNORM_BLOCK,
+        155 => RafxFormat::EAC_R11G11_UNORM_BLOCK,
+        156 => RafxFormat::EAC_R11G11_SNORM_BLOCK,
+        157 => RafxFormat::ASTC_4X4_UNORM_BLOCK,
+        158 => RafxFormat::ASTC_4X4_SRGB_BLOCK,
+        159 => RafxFormat::ASTC_5X4_UNORM_BLOCK,
+        160 => RafxFormat::ASTC_5X4_SRGB_BLOCK,
+        161 => RafxFormat::ASTC_5X5_UNORM_BLOCK,
+        162 => RafxFormat::ASTC_5X5_SRGB_BLOCK,
+        163 => RafxFormat::ASTC_6X5_UNORM_BLOCK,
+        164 => RafxFormat::ASTC_6X5_SRGB_BLOCK,
+        165 => RafxFormat::ASTC_6X6_UNORM_BLOCK,
+        166 => RafxFormat::ASTC_6X6_SRGB_BLOCK,
+        167 => RafxFormat::ASTC_8X5_UNORM_BLOCK,
+        168 => RafxFormat::ASTC_8X5_SRGB_BLOCK,
+        169 => RafxFormat::ASTC_8X6_UNORM_BLOCK,
+        170 => RafxFormat::ASTC_8X6_SRGB_BLOCK,
+        171 => RafxFormat::ASTC_8X8_UNORM_BLOCK,
+        172 => RafxFormat::ASTC_8X8_SRGB_BLOCK,
+        173 => RafxFormat::ASTC_10X5_UNORM_BLOCK,
+        174 => RafxFormat::ASTC_10X5_SRGB_BLOCK,
+        175 => RafxFormat::ASTC_10X6_UNORM_BLOCK,
+        176 => RafxFormat::ASTC_10X6_SRGB_BLOCK,
+        177 => RafxFormat::ASTC_10X8_UNORM_BLOCK,
+        178 => RafxFormat::ASTC_10X8_SRGB_BLOCK,
+        179 => RafxFormat::ASTC_10X10_UNORM_BLOCK,
+        180 => RafxFormat::ASTC_10X10_SRGB_BLOCK,
+        181 => RafxFormat::ASTC_12X10_UNORM_BLOCK,
+        182 => RafxFormat::ASTC_12X10_SRGB_BLOCK,
+        183 => RafxFormat::ASTC_12X12_UNORM_BLOCK,
+        184 => RafxFormat::ASTC_12X12_SRGB_BLOCK,
+        _ => return None,
+    })
+}
+
+/// Reads a KTX2 file that is not supercompressed. 3D textures are not supported.
+pub fn image_asset_data_from_ktx2(bytes: &[u8]) -> RafxResult<ImageAssetData> {
+    if bytes.len() < KTX2_HEADER_SIZE || bytes[0..12] != KTX2_IDENTIFIER {
+        Err("Not a KTX2 file")?;
+    }
+
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?.max(1);
+    let depth = read_u32(bytes, 28)?;
+    let layer_count = read_u32(bytes, 32)?.max(1);
+    let face_count = read_u32(bytes, 36)?;
+    let level_count = read_u32(bytes, 40)?.max(1);
+    let supercompression_scheme = read_u32(bytes, 44)?;
+
+    if vk_format == 0 {
+        Err("KTX2 files containing Basis Universal data are not supported, import a .basis file instead")?;
+    }
+
+    let format = rafx_format_from_vk_format(vk_format)
+        .ok_or_else(|| format!("KTX2 file has unsupported vkFormat {}", vk_format))?;
+
+    if supercompression_scheme != 0 {
+        Err(format!(
+            "KTX2 file uses supercompression scheme {} which is not supported",
+            supercompression_scheme
+        ))?;
+    }
+
+    if width == 0 {
+        Err("KTX2 file has a width of 0")?;
+    }
+
+    if depth > 1 {
+        Err("KTX2 file is a 3D texture which is not supported")?;
+    }
+
+    if face_count != 1 && face_count != 6 {
+        Err(format!(
+            "KTX2 file has {} faces, expected 1 or 6",
+            face_count
+        ))?;
+    }
+
+    ImageAssetData::check_precomputed_mip_count(width, height, level_count)?;
+
+    // Each level stores every layer, and within a layer every face. Reorder it so that each layer
+    // (or face) has all of its mip levels together.
+    let image_count = layer_count
+        .checked_mul(face_count)
+        .ok_or("KTX2 file has too many layers")? as usize;
+    let mut levels = Vec::with_capacity(level_count as usize);
+    for level in 0..level_count {
+        let entry_offset = KTX2_HEADER_SIZE + level as usize * KTX2_LEVEL_INDEX_ENTRY_SIZE;
+        let byte_offset = read_u64(bytes, entry_offset)? as usize;
+        let byte_length = read_u64(bytes, entry_offset + 8)? as usize;
+
+        let image_size = mip_level_size(width, height, format, level);
+        let expected_byte_length = image_size
+            .checked_mul(image_count)
+            .ok_or_else(|| format!("KTX2 mip level {} is too large", level))?;
+        if byte_length != expected_byte_length {
+            Err(format!(
+                "KTX2 mip level {} is {} bytes, expected {} bytes",
+                level, byte_length, expected_byte_length
+            ))?;
+        }
+
+        levels.push((get_range(bytes, byte_offset, byte_length)?, image_size));
+    }
+
+    let mut data = Vec::with_capacity(levels.iter().map(|(level_data, _)| level_data.len()).sum());
+    for image_index in 0..image_count {
+        for (level_data, image_size) in &levels {
+            let offset = image_index * image_size;
+            data.extend_from_slice(&level_data[offset..offset + image_size]);
+        }
+    }
+
+    let resource_type = if face_count == 6 {
+        RafxResourceType::TEXTURE_CUBE
+    } else {
+        RafxResourceType::TEXTURE
+    };
+
+    ImageAssetData::from_precomputed(
+        width,
+        height,
+        format,
+        image_count as u32,
+        level_count,
+        resource_type,
+        data,
+    )
+}
+
+//
+// DDS
+//
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const DDS_HEADER_OFFSET: usize = 4;
+const DDS_HEADER_SIZE: usize = 124;
+const DDS_DX10_HEADER_SIZE: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+const DDS_DIMENSION_TEXTURE3D: u32 = 4;
+
+const fn four_cc(code: &[u8; 4]) -> u32 {
+    code[0] as u32 | (code[1] as u32) << 8 | (code[2] as u32) << 16 | (code[3] as u32) << 24
+}
+
+fn rafx_format_from_dxgi_format(dxgi_format: u32) -> Option<RafxFormat> {
+    Some(match dxgi_format {
+        2 => RafxFormat::R32G32B32A32_SFLOAT,
+        10 => RafxFormat::R16G16B16A16_SFLOAT,
+        11 => RafxFormat::R16G16B16A16_UNORM,
+        16 => RafxFormat::R32G32_SFLOAT,
+        24 => RafxFormat::A2B10G10R10_UNORM_PACK32,
+        26 => RafxFormat::B10G11R11_UFLOAT_PACK32,
+        28 => RafxFormat::R8G8B8A8_UNORM,
+        29 => RafxFormat::R8G8B8A8_SRGB,
+        34 => RafxFormat::R16G16_SFLOAT,
+        35 => RafxFormat::R16G16_UNORM,
+        41 => RafxFormat::R32_SFLOAT,
+        49 => RafxFormat::R8G8_UNORM,
+        54 => RafxFormat::R16_SFLOAT,
+        56 => RafxFormat::R16_UNORM,
+        61 => RafxFormat::R8_UNORM,
+        67 => RafxFormat::E5B9G9R9_UFLOAT_PACK32,
+        71 => RafxFormat::BC1_RGBA_UNORM_BLOCK,
+        72 => RafxFormat::BC1_RGBA_SRGB_BLOCK,
+        74 => RafxFormat::BC2_UNORM_BLOCK,
+        75 => RafxFormat::BC2_SRGB_BLOCK,
+        77 => RafxFormat::BC3_UNORM_BLOCK,
+        78 => RafxFormat::BC3_SRGB_BLOCK,
+        80 => RafxFormat::BC4_UNORM_BLOCK,
+        81 => RafxFormat::BC4_SNORM_BLOCK,
+        83 => RafxFormat::BC5_UNORM_BLOCK,
+        84 => RafxFormat::BC5_SNORM_BLOCK,
+        87 => RafxFormat::B8G8R8A8_UNORM,
+        91 => RafxFormat::B8G8R8A8_SRGB,
+        95 => RafxFormat::BC6H_UFLOAT_BLOCK,
+        96 => RafxFormat::BC6H_SFLOAT_BLOCK,
+        98 => RafxFormat::BC7_UNORM_BLOCK,
+        99 => RafxFormat::BC7_SRGB_BLOCK,
+        _ => return None,
+    })
+}
+
+// Files written without the DX10 header have no way to mark data as sRGB, so they are imported as
+// UNORM formats
+fn rafx_format_from_dds_pixel_format(
+    flags: u32,
+    four_cc_code: u32,
+    bit_count: u32,
+    masks: [u32; 4],
+) -> Option<RafxFormat> {
+    if flags & DDPF_FOURCC != 0 {
+        return Some(match four_cc_code {
+            x if x == four_cc(b"DXT1") => RafxFormat::BC1_RGBA_UNORM_BLOCK,
+            x if x == four_cc(b"DXT2") || x == four_cc(b"DXT3") => RafxFormat::BC2_UNORM_BLOCK,
+            x if x == four_cc(b"DXT4") || x == four_cc(b"DXT5") => RafxFormat::BC3_UNORM_BLOCK,
+            x if x == four_cc(b"ATI1") || x == four_cc(b"BC4U") => RafxFormat::BC4_UNORM_BLOCK,
+            x if x == four_cc(b"BC4S") => RafxFormat::BC4_SNORM_BLOCK,
+            x if x == four_cc(b"ATI2") || x == four_cc(b"BC5U") => RafxFormat::BC5_UNORM_BLOCK,
+            x if x == four_cc(b"BC5S") => RafxFormat::BC5_SNORM_BLOCK,
+            // D3DFMT values stored in the fourCC field
+            36 => RafxFormat::R16G16B16A16_UNORM,
+            111 => RafxFormat::R16_SFLOAT,
+            112 => RafxFormat::R16G16_SFLOAT,
+            113 => RafxFormat::R16G16B16A16_SFLOAT,
+            114 => RafxFormat::R32_SFLOAT,
+            115 => RafxFormat::R32G32_SFLOAT,
+            116 => RafxFormat::R32G32B32A32_SFLOAT,
+            _ => return None,
+        });
+    }
+
+    if flags & DDPF_RGB != 0 && bit_count == 32 {
+        let has_alpha = flags & DDPF_ALPHAPIXELS != 0;
+        let alpha_mask = if has_alpha { masks[3] } else { 0xff000000 };
+        return match (masks[0], masks[1], masks[2], alpha_mask) {
+            (0x000000ff, 0x0000ff00, 0x00ff0000, 0xff000000) => Some(RafxFormat::R8G8B8A8_UNORM),
+            (0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000) => Some(RafxFormat::B8G8R8A8_UNORM),
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// Reads a DDS file containing a 2D texture, texture array or cubemap
+pub fn image_asset_data_from_dds(bytes: &[u8]) -> RafxResult<ImageAssetData> {
+    if bytes.len() < DDS_HEADER_OFFSET + DDS_HEADER_SIZE || &bytes[0..4] != DDS_MAGIC {
+        Err("Not a DDS file")?;
+    }
+
+    let header = &bytes[DDS_HEADER_OFFSET..];
+    if read_u32(header, 0)? as usize != DDS_HEADER_SIZE {
+        Err("DDS file has an invalid header size")?;
+    }
+
+    let flags = read_u32(header, 4)?;
+    let height = read_u32(header, 8)?.max(1);
+    let width = read_u32(header, 12)?.max(1);
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(header, 24)?.max(1)
+    } else {
+        1
+    };
+    let pixel_format_flags = read_u32(header, 76)?;
+    let four_cc_code = read_u32(header, 80)?;
+    let caps2 = read_u32(header, 108)?;
+
+    let mut data_offset = DDS_HEADER_OFFSET + DDS_HEADER_SIZE;
+    let (format, layer_count, is_cube) =
+        if pixel_format_flags & DDPF_FOURCC != 0 && four_cc_code == four_cc(b"DX10") {
+            let dx10_header = get_range(bytes, data_offset, DDS_DX10_HEADER_SIZE)?;
+            data_offset += DDS_DX10_HEADER_SIZE;
+
+            let dxgi_format = read_u32(dx10_header, 0)?;
+            let dimension = read_u32(dx10_header, 4)?;
+            let misc_flag = read_u32(dx10_header, 8)?;
+            let array_size = read_u32(dx10_header, 12)?.max(1);
+
+            if dimension == DDS_DIMENSION_TEXTURE3D {
+                Err("DDS file is a 3D texture which is not supported")?;
+            }
+
+            let format = rafx_format_from_dxgi_format(dxgi_format)
+                .ok_or_else(|| format!("DDS file has unsupported DXGI format {}", dxgi_format))?;
+
+            // The array size of a cubemap counts cubes rather than faces
+            let is_cube = misc_flag & DDS_RESOURCE_MISC_TEXTURECUBE != 0;
+            let layer_count = if is_cube {
+                array_size
+                    .checked_mul(6)
+                    .ok_or("DDS file has too many cubemaps")?
+            } else {
+                array_size
+            };
+            (format, layer_count, is_cube)
+        } else {
+            if caps2 & DDSCAPS2_VOLUME != 0 {
+                Err("DDS file is a 3D texture which is not supported")?;
+            }
+
+            let is_cube = caps2 & DDSCAPS2_CUBEMAP != 0;
+            if is_cube && caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+                Err("DDS cubemaps must contain all 6 faces")?;
+            }
+
+            let masks = [
+                read_u32(header, 88)?,
+                read_u32(header, 92)?,
+                read_u32(header, 96)?,
+                read_u32(header, 100)?,
+            ];
+            let format = rafx_format_from_dds_pixel_format(
+                pixel_format_flags,
+                four_cc_code,
+                read_u32(header, 84)?,
+                masks,
+            )
+            .ok_or("DDS file has an unsupported pixel format")?;
+
+            (format, if is_cube { 6 } else { 1 }, is_cube)
+        };
+
+    // DDS stores each layer (or face) with all of its mip levels, which is the layout we use
+    ImageAssetData::check_precomputed_mip_count(width, height, mip_count)?;
+    let layer_size = ImageAssetData::precomputed_layer_size(width, height, format, mip_count);
+    let data_size = layer_size
+        .checked_mul(layer_count as usize)
+        .ok_or("DDS file has too many layers")?;
+    let data = get_range(bytes, data_offset, data_size)?.to_vec();
+
+    let resource_type = if is_cube {
+        RafxResourceType::TEXTURE_CUBE
+    } else {
+        RafxResourceType::TEXTURE
+    };
+
+    ImageAssetData::from_precomputed(
+        width,
+        height,
+        format,
+        layer_count,
+        mip_count,
+        resource_type,
+        data,
+    )
+}
+
+//
+// Importers
+//
+fn import_precomputed(
+    bytes: &[u8],
+    id: AssetUuid,
+    parse: fn(&[u8]) -> RafxResult<ImageAssetData>,
+) -> distill::importer::Result<ImporterValue> {
+    let asset_data = parse(bytes).map_err(|e| Error::Boxed(Box::new(e)))?;
+
+    Ok(ImporterValue {
+        assets: vec![ImportedAsset {
+            id,
+            search_tags: vec![],
+            build_deps: vec![],
+            load_deps: vec![],
+            build_pipeline: None,
+            asset_data: Box::new(asset_data),
+        }],
+    })
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "de1c2e59-880f-40ca-9aec-8e0775de59da"]
+pub struct Ktx2ImageImporterState(Option<AssetUuid>);
+
+#[derive(TypeUuid)]
+#[uuid = "782df525-9a2a-4b77-a92e-f565ff715db4"]
+pub struct Ktx2ImageImporter;
+impl Importer for Ktx2ImageImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = ();
+
+    type State = Ktx2ImageImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        _options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = Ktx2ImageImporterState(Some(id));
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+
+        import_precomputed(&bytes, id, image_asset_data_from_ktx2)
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "d7b2eb09-a8b7-4edf-8079-1459385e277b"]
+pub struct DdsImageImporterState(Option<AssetUuid>);
+
+#[derive(TypeUuid)]
+#[uuid = "c39f1ae9-3ac8-42fc-b7b2-49c4e5c7b155"]
+pub struct DdsImageImporter;
+impl Importer for DdsImageImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = ();
+
+    type State = DdsImageImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        _options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = DdsImageImporterState(Some(id));
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+
+        import_precomputed(&bytes, id, image_asset_data_from_dds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assets::image::ImageAssetDataFormat;
+
+    fn write_u32(
+        bytes: &mut [u8],
+        offset: usize,
+        value: u32,
+    ) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn precomputed_format(asset_data: &ImageAssetData) -> (RafxFormat, u32, u32) {
+        match asset_data.format {
+            ImageAssetDataFormat::Precomputed {
+                format,
+                layer_count,
+                mip_count,
+            } => (format, layer_count, mip_count),
+            _ => panic!("Expected precomputed image data"),
+        }
+    }
+
+    // A KTX2 file with one level index entry per element of levels, each level already containing
+    // every layer and face
+    fn ktx2_file(
+        vk_format: u32,
+        width: u32,
+        height: u32,
+        layer_count: u32,
+        face_count: u32,
+        levels: &[Vec<u8>],
+    ) -> Vec<u8> {
+        let mut bytes = vec![0; KTX2_HEADER_SIZE + levels.len() * KTX2_LEVEL_INDEX_ENTRY_SIZE];
+        bytes[0..12].copy_from_slice(&KTX2_IDENTIFIER);
+        write_u32(&mut bytes, 12, vk_format);
+        write_u32(&mut bytes, 16, 1);
+        write_u32(&mut bytes, 20, width);
+        write_u32(&mut bytes, 24, height);
+        write_u32(&mut bytes, 32, layer_count);
+        write_u32(&mut bytes, 36, face_count);
+        write_u32(&mut bytes, 40, levels.len() as u32);
+
+        for (level_index, level) in levels.iter().enumerate() {
+            let entry_offset = KTX2_HEADER_SIZE + level_index * KTX2_LEVEL_INDEX_ENTRY_SIZE;
+            let byte_offset = bytes.len() as u32;
+            write_u32(&mut bytes, entry_offset, byte_offset);
+            write_u32(&mut bytes, entry_offset + 8, level.len() as u32);
+            write_u32(&mut bytes, entry_offset + 16, level.len() as u32);
+            bytes.extend_from_slice(level);
+        }
+
+        bytes
+    }
+
+    // A DDS file with only the legacy header, data is appended by the caller
+    fn dds_file(
+        width: u32,
+        height: u32,
+        mip_count: u32,
+        pixel_format_flags: u32,
+        four_cc_code: u32,
+        caps2: u32,
+    ) -> Vec<u8> {
+        let mut bytes = vec![0; DDS_HEADER_OFFSET + DDS_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(DDS_MAGIC);
+        let header = &mut bytes[DDS_HEADER_OFFSET..];
+        write_u32(header, 0, DDS_HEADER_SIZE as u32);
+        write_u32(header, 4, if mip_count > 1 { DDSD_MIPMAPCOUNT } else { 0 });
+        write_u32(header, 8, height);
+        write_u32(header, 12, width);
+        write_u32(header, 24, mip_count);
+        write_u32(header, 72, 32);
+        write_u32(header, 76, pixel_format_flags);
+        write_u32(header, 80, four_cc_code);
+        write_u32(header, 108, caps2);
+        bytes
+    }
+
+    fn dds_rgba8_file(
+        width: u32,
+        height: u32,
+        mip_count: u32,
+        caps2: u32,
+    ) -> Vec<u8> {
+        let mut bytes = dds_file(
+            width,
+            height,
+            mip_count,
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            caps2,
+        );
+        let header = &mut bytes[DDS_HEADER_OFFSET..];
+        write_u32(header, 84, 32);
+        write_u32(header, 88, 0x000000ff);
+        write_u32(header, 92, 0x0000ff00);
+        write_u32(header, 96, 0x00ff0000);
+        write_u32(header, 100, 0xff000000);
+        bytes
+    }
+
+    fn dds_dx10_file(
+        width: u32,
+        height: u32,
+        dxgi_format: u32,
+        misc_flag: u32,
+        array_size: u32,
+    ) -> Vec<u8> {
+        let mut bytes = dds_file(width, height, 1, DDPF_FOURCC, four_cc(b"DX10"), 0);
+        let mut dx10_header = vec![0; DDS_DX10_HEADER_SIZE];
+        write_u32(&mut dx10_header, 0, dxgi_format);
+        write_u32(&mut dx10_header, 4, 3);
+        write_u32(&mut dx10_header, 8, misc_flag);
+        write_u32(&mut dx10_header, 12, array_size);
+        bytes.extend_from_slice(&dx10_header);
+        bytes
+    }
+
+    #[test]
+    fn test_ktx2_layers_are_stored_with_their_mips() {
+        // R8_UNORM, 2x2 with 2 mip levels and 2 layers
+        let levels = vec![vec![0, 1, 2, 3, 4, 5, 6, 7], vec![8, 9]];
+        let bytes = ktx2_file(9, 2, 2, 2, 1, &levels);
+
+        let asset_data = image_asset_data_from_ktx2(&bytes).unwrap();
+        assert_eq!(asset_data.width, 2);
+        assert_eq!(asset_data.height, 2);
+        assert_eq!(asset_data.resource_type, RafxResourceType::TEXTURE);
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::R8_UNORM, 2, 2)
+        );
+        assert_eq!(asset_data.data, vec![0, 1, 2, 3, 8, 4, 5, 6, 7, 9]);
+    }
+
+    #[test]
+    fn test_ktx2_cubemap() {
+        // R8G8B8A8_UNORM, 1x1 with 6 faces
+        let levels = vec![(0..24).collect::<Vec<u8>>()];
+        let bytes = ktx2_file(37, 1, 1, 0, 6, &levels);
+
+        let asset_data = image_asset_data_from_ktx2(&bytes).unwrap();
+        assert_eq!(asset_data.resource_type, RafxResourceType::TEXTURE_CUBE);
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::R8G8B8A8_UNORM, 6, 1)
+        );
+        assert_eq!(asset_data.data, levels[0]);
+    }
+
+    #[test]
+    fn test_ktx2_block_compressed() {
+        // BC1_RGBA_UNORM_BLOCK, a 6x6 image is 2x2 blocks, then 1x1 blocks for the 3x3 and 1x1 mips
+        let levels = vec![vec![1; 32], vec![2; 8], vec![3; 8]];
+        let bytes = ktx2_file(133, 6, 6, 0, 1, &levels);
+
+        let asset_data = image_asset_data_from_ktx2(&bytes).unwrap();
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::BC1_RGBA_UNORM_BLOCK, 1, 3)
+        );
+        assert_eq!(asset_data.data.len(), 48);
+    }
+
+    #[test]
+    fn test_ktx2_rejects_invalid_files() {
+        let levels = vec![vec![0, 1, 2, 3]];
+        let valid = ktx2_file(9, 2, 2, 0, 1, &levels);
+        assert!(image_asset_data_from_ktx2(&valid).is_ok());
+
+        // Bad identifier
+        let mut bytes = valid.clone();
+        bytes[1] = b'X';
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+
+        // Shorter than the header
+        assert!(image_asset_data_from_ktx2(&valid[0..KTX2_HEADER_SIZE - 1]).is_err());
+
+        // Truncated level data
+        assert!(image_asset_data_from_ktx2(&valid[0..valid.len() - 1]).is_err());
+
+        // Basis Universal data
+        assert!(image_asset_data_from_ktx2(&ktx2_file(0, 2, 2, 0, 1, &levels)).is_err());
+
+        // Unknown vkFormat
+        assert!(image_asset_data_from_ktx2(&ktx2_file(1000, 2, 2, 0, 1, &levels)).is_err());
+
+        // Supercompressed
+        let mut bytes = valid.clone();
+        write_u32(&mut bytes, 44, 2);
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+
+        // 3D texture
+        let mut bytes = valid.clone();
+        write_u32(&mut bytes, 28, 2);
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+
+        // Neither 1 nor 6 faces
+        assert!(image_asset_data_from_ktx2(&ktx2_file(9, 2, 2, 0, 2, &levels)).is_err());
+
+        // Level size doesn't match the image size
+        assert!(image_asset_data_from_ktx2(&ktx2_file(9, 2, 2, 0, 1, &[vec![0; 3]])).is_err());
+    }
+
+    #[test]
+    fn test_ktx2_rejects_too_many_layers() {
+        // The layer count times the face count doesn't fit in a u32
+        let levels = vec![vec![0; 4]];
+        let bytes = ktx2_file(9, 2, 2, 0x8000_0000, 6, &levels);
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+
+        // The size of a level doesn't fit in a usize
+        let bytes = ktx2_file(37, 0x1_0000, 0x1_0000, 0xffff_ffff, 1, &levels);
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_ktx2_rejects_too_many_mips() {
+        // A 2x2 image can have at most 2 mips
+        let levels = vec![vec![0; 4], vec![0; 1], vec![0; 1]];
+        assert!(image_asset_data_from_ktx2(&ktx2_file(9, 2, 2, 0, 1, &levels)).is_err());
+
+        // Must fail without reading the level index rather than overflowing the mip size shifts
+        let mut bytes = ktx2_file(9, 2, 2, 0, 1, &[]);
+        write_u32(&mut bytes, 40, 40);
+        assert!(image_asset_data_from_ktx2(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_dds_rgba8_with_mips() {
+        let mut bytes = dds_rgba8_file(2, 2, 2, 0);
+        let data: Vec<u8> = (0..20).collect();
+        bytes.extend_from_slice(&data);
+
+        let asset_data = image_asset_data_from_dds(&bytes).unwrap();
+        assert_eq!(asset_data.width, 2);
+        assert_eq!(asset_data.height, 2);
+        assert_eq!(asset_data.resource_type, RafxResourceType::TEXTURE);
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::R8G8B8A8_UNORM, 1, 2)
+        );
+        assert_eq!(asset_data.data, data);
+    }
+
+    #[test]
+    fn test_dds_pixel_formats() {
+        assert_eq!(
+            rafx_format_from_dds_pixel_format(
+                DDPF_RGB,
+                0,
+                32,
+                [0x00ff0000, 0x0000ff00, 0x000000ff, 0]
+            ),
+            Some(RafxFormat::B8G8R8A8_UNORM)
+        );
+        assert_eq!(
+            rafx_format_from_dds_pixel_format(DDPF_FOURCC, four_cc(b"DXT5"), 0, [0; 4]),
+            Some(RafxFormat::BC3_UNORM_BLOCK)
+        );
+        assert_eq!(
+            rafx_format_from_dds_pixel_format(DDPF_FOURCC, 113, 0, [0; 4]),
+            Some(RafxFormat::R16G16B16A16_SFLOAT)
+        );
+        assert_eq!(
+            rafx_format_from_dds_pixel_format(DDPF_RGB, 0, 24, [0xff, 0xff00, 0xff0000, 0]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dds_dxt1() {
+        let mut bytes = dds_file(4, 4, 1, DDPF_FOURCC, four_cc(b"DXT1"), 0);
+        bytes.extend_from_slice(&[7; 8]);
+
+        let asset_data = image_asset_data_from_dds(&bytes).unwrap();
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::BC1_RGBA_UNORM_BLOCK, 1, 1)
+        );
+        assert_eq!(asset_data.data, vec![7; 8]);
+    }
+
+    #[test]
+    fn test_dds_cubemaps() {
+        // Legacy header
+        let mut bytes = dds_rgba8_file(1, 1, 1, DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALLFACES);
+        bytes.extend_from_slice(&[0; 24]);
+        let asset_data = image_asset_data_from_dds(&bytes).unwrap();
+        assert_eq!(asset_data.resource_type, RafxResourceType::TEXTURE_CUBE);
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::R8G8B8A8_UNORM, 6, 1)
+        );
+
+        // DX10 header with an array of 2 cubes, which is 12 faces
+        let mut bytes = dds_dx10_file(1, 1, 29, DDS_RESOURCE_MISC_TEXTURECUBE, 2);
+        bytes.extend_from_slice(&[0; 48]);
+        let asset_data = image_asset_data_from_dds(&bytes).unwrap();
+        assert_eq!(asset_data.resource_type, RafxResourceType::TEXTURE_CUBE);
+        assert_eq!(
+            precomputed_format(&asset_data),
+            (RafxFormat::R8G8B8A8_SRGB, 12, 1)
+        );
+    }
+
+    #[test]
+    fn test_dds_rejects_invalid_files() {
+        let mut valid = dds_rgba8_file(1, 1, 1, 0);
+        valid.extend_from_slice(&[0; 4]);
+        assert!(image_asset_data_from_dds(&valid).is_ok());
+
+        // Bad magic
+        let mut bytes = valid.clone();
+        bytes[0] = b'X';
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // Bad header size
+        let mut bytes = valid.clone();
+        write_u32(&mut bytes, DDS_HEADER_OFFSET, 100);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // Truncated data
+        assert!(image_asset_data_from_dds(&valid[0..valid.len() - 1]).is_err());
+
+        // Cubemap without every face
+        let mut bytes = dds_rgba8_file(1, 1, 1, DDSCAPS2_CUBEMAP | 0x400);
+        bytes.extend_from_slice(&[0; 24]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // 3D textures
+        let mut bytes = dds_rgba8_file(1, 1, 1, DDSCAPS2_VOLUME);
+        bytes.extend_from_slice(&[0; 4]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+        let mut bytes = dds_dx10_file(1, 1, 28, 0, 1);
+        write_u32(
+            &mut bytes,
+            DDS_HEADER_OFFSET + DDS_HEADER_SIZE + 4,
+            DDS_DIMENSION_TEXTURE3D,
+        );
+        bytes.extend_from_slice(&[0; 4]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // Unknown DXGI format
+        let mut bytes = dds_dx10_file(1, 1, 1000, 0, 1);
+        bytes.extend_from_slice(&[0; 4]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // DX10 header missing
+        assert!(
+            image_asset_data_from_dds(&dds_file(1, 1, 1, DDPF_FOURCC, four_cc(b"DX10"), 0))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dds_rejects_too_many_mips() {
+        // A 2x2 image can have at most 2 mips. This must fail before computing the size of the
+        // data rather than overflowing the mip size shifts.
+        let mut bytes = dds_rgba8_file(2, 2, 40, 0);
+        bytes.extend_from_slice(&[0; 24]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_dds_rejects_too_many_layers() {
+        // The array size of a cubemap times 6 doesn't fit in a u32
+        let mut bytes = dds_dx10_file(1, 1, 28, DDS_RESOURCE_MISC_TEXTURECUBE, 0x8000_0000);
+        bytes.extend_from_slice(&[0; 24]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+
+        // The size of the data doesn't fit in a usize
+        let mut bytes = dds_dx10_file(0x1_0000, 0x1_0000, 28, 0, 0xffff_ffff);
+        bytes.extend_from_slice(&[0; 4]);
+        assert!(image_asset_data_from_dds(&bytes).is_err());
+    }
+}
@@ -0,0 +1,619 @@
+// Imports cubemaps described by a RON manifest (.cubemap) that references either six face images or
+// an equirectangular panorama. The result is an image asset with TEXTURE_CUBE resource type and six
+// layers.
+//
+// The referenced images are read directly from disk and declared as build dependencies of the
+// cubemap, so changing them re-imports it.
+#[cfg(feature = "exr")]
+use super::importer::decode_exr;
+use super::importer::decode_hdr;
+use super::mip_generation::rgba8_to_rgba32f;
+use crate::assets::image::{
+    ImageAssetColorSpace, ImageAssetData, ImageAssetDataFormatConfig, ImageAssetMipFilter,
+    ImageAssetMipGeneration, ImageAssetMipSettings,
+};
+use distill::core::{AssetRef, AssetUuid};
+use distill::importer::ImportOp;
+use distill::importer::{Error, ImportedAsset, Importer, ImporterValue};
+use rafx_api::{RafxError, RafxResourceType};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use type_uuid::*;
+
+// Paths are relative to one of the asset directories, like the paths passed to
+// AssetResource::load_asset_path
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum CubemapSource {
+    // All faces must be square and the same size. Faces follow the Vulkan/D3D cubemap convention.
+    Faces {
+        positive_x: PathBuf,
+        negative_x: PathBuf,
+        positive_y: PathBuf,
+        negative_y: PathBuf,
+        positive_z: PathBuf,
+        negative_z: PathBuf,
+    },
+    // A panorama with longitude along X and latitude along Y. The center of the image is -Z.
+    Equirectangular {
+        path: PathBuf,
+        // Defaults to a quarter of the panorama's width
+        #[serde(default)]
+        face_size: Option<u32>,
+    },
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "5be1a7f8-2c4f-4a66-8d8b-0f3a9e6c1d57"]
+#[serde(default)]
+pub struct CubemapImporterOptions {
+    // The color space of LDR source images. HDR source images (.hdr, .exr) are always linear.
+    pub color_space: ImageAssetColorSpace,
+    // Defaults to RawRGBA32 for LDR sources and RawRGBA16Float for HDR sources. Basis compression is
+    // not supported.
+    pub format: Option<ImageAssetDataFormatConfig>,
+    pub mip_generation: ImageAssetMipGeneration,
+    // Used when mip_generation is Precomupted. Each face is filtered separately.
+    pub mip_filter: ImageAssetMipFilter,
+}
+
+impl Default for CubemapImporterOptions {
+    fn default() -> Self {
+        CubemapImporterOptions {
+            color_space: ImageAssetColorSpace::Srgb,
+            format: None,
+            mip_generation: ImageAssetMipGeneration::Precomupted,
+            mip_filter: ImageAssetMipFilter::Kaiser,
+        }
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "a0e7c3d2-61b4-4f0e-9a57-3c8d2b1e4f96"]
+pub struct CubemapImporterState(Option<AssetUuid>);
+
+struct SourceImage {
+    width: u32,
+    height: u32,
+    // Linear RGBA
+    rgba32f: Vec<f32>,
+    is_hdr: bool,
+}
+
+fn import_error(message: String) -> Error {
+    Error::Boxed(Box::new(RafxError::StringError(message)))
+}
+
+fn load_source_image(
+    path: &Path,
+    color_space: ImageAssetColorSpace,
+) -> distill::importer::Result<SourceImage> {
+    let bytes = std::fs::read(path)?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let (width, height, rgba32f, is_hdr) = match extension.as_deref() {
+        Some("hdr") => {
+            let (width, height, rgba32f) = decode_hdr(bytes)?;
+            (width, height, rgba32f, true)
+        }
+        #[cfg(feature = "exr")]
+        Some("exr") => {
+            let (width, height, rgba32f) = decode_exr(bytes)?;
+            (width, height, rgba32f, true)
+        }
+        #[cfg(not(feature = "exr"))]
+        Some("exr") => Err(import_error(format!(
+            "Cannot read {:?}, not built with the exr feature",
+            path
+        )))?,
+        _ => {
+            let rgba_image = image::load_from_memory(&bytes)
+                .map_err(|e| Error::Boxed(Box::new(e)))?
+                .into_rgba8();
+            let (width, height) = rgba_image.dimensions();
+            let rgba32f = rgba8_to_rgba32f(rgba_image.as_raw(), color_space);
+            (width, height, rgba32f, false)
+        }
+    };
+
+    Ok(SourceImage {
+        width,
+        height,
+        rgba32f,
+        is_hdr,
+    })
+}
+
+// Direction through the point (x, y) of a cube face, in texels. Pass texel + 0.5 for the center of a
+// texel. Faces are in layer order (+X, -X, +Y, -Y, +Z, -Z). The direction is not normalized.
+pub fn cube_face_direction(
+    face: usize,
+    x: f32,
+    y: f32,
+    face_size: u32,
+) -> [f32; 3] {
+    let u = 2.0 * x / face_size as f32 - 1.0;
+    let v = 2.0 * y / face_size as f32 - 1.0;
+    match face {
+        0 => [1.0, -v, -u],
+        1 => [-1.0, -v, u],
+        2 => [u, 1.0, v],
+        3 => [u, -1.0, -v],
+        4 => [u, -v, 1.0],
+        _ => [-u, -v, -1.0],
+    }
+}
+
+// Bilinear sample, wrapping horizontally and clamping vertically
+fn sample_equirectangular(
+    panorama: &SourceImage,
+    direction: [f32; 3],
+) -> [f32; 4] {
+    let [x, y, z] = direction;
+    let length = (x * x + y * y + z * z).sqrt();
+    let longitude = x.atan2(-z);
+    let latitude = (y / length).max(-1.0).min(1.0).acos();
+
+    let u = 0.5 + longitude / (2.0 * std::f32::consts::PI);
+    let v = latitude / std::f32::consts::PI;
+
+    let px = u * panorama.width as f32 - 0.5;
+    let py = v * panorama.height as f32 - 0.5;
+    let x0 = px.floor();
+    let y0 = py.floor();
+    let fx = px - x0;
+    let fy = py - y0;
+
+    let width = panorama.width as i64;
+    let height = panorama.height as i64;
+    let texel = |x: i64, y: i64| {
+        let x = x.rem_euclid(width);
+        let y = y.max(0).min(height - 1);
+        let index = ((y * width + x) * 4) as usize;
+        &panorama.rgba32f[index..index + 4]
+    };
+
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let mut result = [0.0; 4];
+    for (tx, ty, weight) in [
+        (x0, y0, (1.0 - fx) * (1.0 - fy)),
+        (x0 + 1, y0, fx * (1.0 - fy)),
+        (x0, y0 + 1, (1.0 - fx) * fy),
+        (x0 + 1, y0 + 1, fx * fy),
+    ]
+    .iter()
+    {
+        let value = texel(*tx, *ty);
+        for channel in 0..4 {
+            result[channel] += value[channel] * weight;
+        }
+    }
+
+    result
+}
+
+// Takes 2x2 samples per texel to reduce aliasing when the faces are smaller than the panorama
+fn reproject_equirectangular(
+    panorama: &SourceImage,
+    face_size: u32,
+) -> Vec<Vec<f32>> {
+    const SUBSAMPLE_OFFSETS: [f32; 2] = [0.25, 0.75];
+
+    (0..6)
+        .map(|face| {
+            let mut rgba32f = Vec::with_capacity((face_size * face_size * 4) as usize);
+            for y in 0..face_size {
+                for x in 0..face_size {
+                    let mut texel = [0.0; 4];
+                    for &offset_y in &SUBSAMPLE_OFFSETS {
+                        for &offset_x in &SUBSAMPLE_OFFSETS {
+                            let direction = cube_face_direction(
+                                face,
+                                x as f32 + offset_x,
+                                y as f32 + offset_y,
+                                face_size,
+                            );
+                            let sample = sample_equirectangular(panorama, direction);
+                            for channel in 0..4 {
+                                texel[channel] += sample[channel] * 0.25;
+                            }
+                        }
+                    }
+
+                    rgba32f.extend_from_slice(&texel);
+                }
+            }
+
+            rgba32f
+        })
+        .collect()
+}
+
+// The faces of a cubemap in layer order (+X, -X, +Y, -Y, +Z, -Z), as linear RGBA floats
+pub struct CubemapFaces {
+    pub face_size: u32,
+    pub faces: Vec<Vec<f32>>,
+    // True if any source image was .hdr or .exr
+    pub is_hdr: bool,
+    // The resolved paths of the images the faces were read from
+    pub source_paths: Vec<PathBuf>,
+}
+
+impl CubemapFaces {
+    // Assets produced from the faces should list these so that they are re-imported when one of the
+    // source images changes. Paths are made absolute since relative path references are resolved
+    // relative to the file that contains them rather than the asset directories.
+    pub fn build_deps(&self) -> Vec<AssetRef> {
+        self.source_paths
+            .iter()
+            .map(|path| AssetRef::Path(path.canonicalize().unwrap_or_else(|_| path.clone())))
+            .collect()
+    }
+}
+
+#[derive(TypeUuid)]
+#[uuid = "e3b86a1c-4d7f-4b09-b2a5-9f61c08d3e72"]
+pub struct CubemapImporter {
+    asset_dirs: Vec<PathBuf>,
+}
+
+impl CubemapImporter {
+    // Paths in manifests are looked up in these directories, normally the same directories the
+    // asset daemon watches
+    pub fn new(asset_dirs: Vec<PathBuf>) -> Self {
+        CubemapImporter { asset_dirs }
+    }
+
+    fn resolve_path(
+        &self,
+        path: &Path,
+    ) -> distill::importer::Result<PathBuf> {
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+
+        self.asset_dirs
+            .iter()
+            .map(|asset_dir| asset_dir.join(path))
+            .find(|resolved_path| resolved_path.exists())
+            .ok_or_else(|| {
+                import_error(format!(
+                    "Could not find {:?} in asset directories {:?}",
+                    path, self.asset_dirs
+                ))
+            })
+    }
+
+    // Loads the images referenced by a cubemap manifest. Equirectangular panoramas are reprojected
+    // onto the faces.
+    pub fn load_faces(
+        &self,
+        cubemap_source: &CubemapSource,
+        color_space: ImageAssetColorSpace,
+    ) -> distill::importer::Result<CubemapFaces> {
+        let mut source_paths = vec![];
+        let (face_size, faces, is_hdr) = match cubemap_source {
+            CubemapSource::Faces {
+                positive_x,
+                negative_x,
+                positive_y,
+                negative_y,
+                positive_z,
+                negative_z,
+            } => {
+                let paths = [
+                    positive_x, negative_x, positive_y, negative_y, positive_z, negative_z,
+                ];
+
+                let mut faces = Vec::with_capacity(6);
+                for path in &paths {
+                    let path = self.resolve_path(path)?;
+                    faces.push(load_source_image(&path, color_space)?);
+                    source_paths.push(path);
+                }
+
+                let face_size = faces[0].width;
+                for (face, path) in faces.iter().zip(&paths) {
+                    if face.width != face_size || face.height != face_size {
+                        Err(import_error(format!(
+                            "Cubemap face {:?} is {}x{}, all faces must be {}x{}",
+                            path, face.width, face.height, face_size, face_size
+                        )))?;
+                    }
+                }
+
+                let is_hdr = faces.iter().any(|face| face.is_hdr);
+                let faces = faces.into_iter().map(|face| face.rgba32f).collect();
+                (face_size, faces, is_hdr)
+            }
+            CubemapSource::Equirectangular { path, face_size } => {
+                let path = self.resolve_path(path)?;
+                let panorama = load_source_image(&path, color_space)?;
+                source_paths.push(path);
+                let face_size = face_size.unwrap_or(panorama.width / 4).max(1);
+                let faces = reproject_equirectangular(&panorama, face_size);
+                (face_size, faces, panorama.is_hdr)
+            }
+        };
+
+        Ok(CubemapFaces {
+            face_size,
+            faces,
+            is_hdr,
+            source_paths,
+        })
+    }
+}
+
+impl Importer for CubemapImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = CubemapImporterOptions;
+
+    type State = CubemapImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = CubemapImporterState(Some(id));
+
+        let cubemap_source = ron::de::from_reader::<_, CubemapSource>(source)
+            .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        let cubemap_faces = self.load_faces(&cubemap_source, options.color_space)?;
+        let build_deps = cubemap_faces.build_deps();
+        let CubemapFaces {
+            face_size,
+            faces,
+            is_hdr,
+            ..
+        } = cubemap_faces;
+
+        let format = options.format.unwrap_or(if is_hdr {
+            ImageAssetDataFormatConfig::RawRGBA16Float
+        } else {
+            ImageAssetDataFormatConfig::RawRGBA32
+        });
+
+        let asset_data = ImageAssetData::from_rgba32f_layers(
+            face_size,
+            face_size,
+            options.color_space,
+            format,
+            options.mip_generation,
+            &ImageAssetMipSettings {
+                filter: options.mip_filter,
+                ..Default::default()
+            },
+            RafxResourceType::TEXTURE_CUBE,
+            faces,
+        )
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id,
+                search_tags: vec![],
+                build_deps,
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(asset_data),
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(
+        actual: f32,
+        expected: f32,
+    ) {
+        assert!(
+            (actual - expected).abs() <= 1e-4,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    // The face and texture coordinates a sampler reads for a direction, from the cube map face
+    // selection table in the Vulkan spec
+    fn sampled_face_and_uv(direction: [f32; 3]) -> (usize, f32, f32) {
+        let [x, y, z] = direction;
+        let (face, sc, tc, ma) = if x.abs() >= y.abs() && x.abs() >= z.abs() {
+            if x > 0.0 {
+                (0, -z, -y, x)
+            } else {
+                (1, z, -y, x)
+            }
+        } else if y.abs() >= z.abs() {
+            if y > 0.0 {
+                (2, x, z, y)
+            } else {
+                (3, x, -z, y)
+            }
+        } else if z > 0.0 {
+            (4, x, -y, z)
+        } else {
+            (5, -x, -y, z)
+        };
+
+        (
+            face,
+            0.5 * (sc / ma.abs() + 1.0),
+            0.5 * (tc / ma.abs() + 1.0),
+        )
+    }
+
+    // A panorama whose red channel is the texel's column and green channel is its row
+    fn coordinate_panorama(
+        width: u32,
+        height: u32,
+    ) -> SourceImage {
+        let mut rgba32f = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                rgba32f.extend_from_slice(&[x as f32, y as f32, 0.0, 1.0]);
+            }
+        }
+
+        SourceImage {
+            width,
+            height,
+            rgba32f,
+            is_hdr: false,
+        }
+    }
+
+    #[test]
+    fn test_cube_face_centers() {
+        let expected = [
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0],
+        ];
+
+        for (face, expected) in expected.iter().enumerate() {
+            let direction = cube_face_direction(face, 2.0, 2.0, 4);
+            for axis in 0..3 {
+                assert_near(direction[axis], expected[axis]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_face_direction_matches_sampling() {
+        // Every texel center must be read back from the same face and position by a sampler
+        let face_size = 8;
+        for face in 0..6 {
+            for y in 0..face_size {
+                for x in 0..face_size {
+                    let px = x as f32 + 0.5;
+                    let py = y as f32 + 0.5;
+                    let direction = cube_face_direction(face, px, py, face_size);
+                    let (sampled_face, u, v) = sampled_face_and_uv(direction);
+                    assert_eq!(sampled_face, face);
+                    assert_near(u, px / face_size as f32);
+                    assert_near(v, py / face_size as f32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_faces_share_edges() {
+        // The right edge of +Z is the left edge of +X, and the top edge of +Z is the bottom edge
+        // of +Y
+        for i in 0..=4 {
+            let t = i as f32;
+            assert_eq!(
+                cube_face_direction(4, 4.0, t, 4),
+                cube_face_direction(0, 0.0, t, 4)
+            );
+            assert_eq!(
+                cube_face_direction(4, t, 0.0, 4),
+                cube_face_direction(2, t, 4.0, 4)
+            );
+        }
+    }
+
+    #[test]
+    fn test_equirectangular_mapping() {
+        let panorama = coordinate_panorama(8, 4);
+
+        // The center of the image is -Z, with +X to the right of it and -X to the left. Samples are
+        // at texel corners, so they blend neighbouring texels.
+        let sample = sample_equirectangular(&panorama, [0.0, 0.0, -1.0]);
+        assert_near(sample[0], 3.5);
+        assert_near(sample[1], 1.5);
+        assert_near(sample_equirectangular(&panorama, [1.0, 0.0, 0.0])[0], 5.5);
+        assert_near(sample_equirectangular(&panorama, [-1.0, 0.0, 0.0])[0], 1.5);
+
+        // +Z is on the left and right edges, and wraps horizontally to blend the first and last
+        // columns
+        assert_near(sample_equirectangular(&panorama, [0.0, 0.0, 1.0])[0], 3.5);
+
+        // The poles are the top and bottom rows, clamped vertically
+        assert_near(sample_equirectangular(&panorama, [0.0, 1.0, 0.0])[1], 0.0);
+        assert_near(sample_equirectangular(&panorama, [0.0, -1.0, 0.0])[1], 3.0);
+
+        // Halfway between the horizon and the top
+        let sample = sample_equirectangular(&panorama, [0.0, 1.0, -1.0]);
+        assert_near(sample[1], 0.5);
+
+        // Directions don't need to be normalized
+        assert_eq!(
+            sample_equirectangular(&panorama, [0.5, 0.25, -1.0]),
+            sample_equirectangular(&panorama, [2.0, 1.0, -4.0])
+        );
+    }
+
+    #[test]
+    fn test_reproject_equirectangular() {
+        // A constant panorama produces constant faces
+        let panorama = SourceImage {
+            width: 16,
+            height: 8,
+            rgba32f: [0.25, 0.5, 0.75, 1.0].repeat(16 * 8),
+            is_hdr: false,
+        };
+
+        let faces = reproject_equirectangular(&panorama, 4);
+        assert_eq!(faces.len(), 6);
+        for face in &faces {
+            assert_eq!(face.len(), 4 * 4 * 4);
+            for pixel in face.chunks_exact(4) {
+                assert_near(pixel[0], 0.25);
+                assert_near(pixel[1], 0.5);
+                assert_near(pixel[2], 0.75);
+                assert_near(pixel[3], 1.0);
+            }
+        }
+
+        // +Y reads the top rows of the panorama and -Y the bottom rows
+        let panorama = coordinate_panorama(16, 8);
+        let faces = reproject_equirectangular(&panorama, 4);
+        let max_row = |face: &Vec<f32>| {
+            face.chunks_exact(4)
+                .map(|pixel| pixel[1])
+                .fold(std::f32::MIN, f32::max)
+        };
+        let min_row = |face: &Vec<f32>| {
+            face.chunks_exact(4)
+                .map(|pixel| pixel[1])
+                .fold(std::f32::MAX, f32::min)
+        };
+        assert!(max_row(&faces[2]) < 2.0);
+        assert!(min_row(&faces[3]) > 5.0);
+        for face in &faces[0..2] {
+            assert!(min_row(face) > 1.0 && max_row(face) < 6.0);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Conversions from f32 to the floating point formats image assets can be stored in. Values are
+// rounded to nearest even when converting to half floats and truncated when packing B10G11R11.
+// Finite values too large for the format saturate to its largest finite value, only infinity stays
+// infinite.
+
+pub(super) fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Infinity or NaN
+        let nan_bit = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan_bit;
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        // Too large to represent, clamp to the largest finite half float
+        return sign | 0x7bff;
+    }
+
+    if half_exponent <= 0 {
+        // Too small to represent as a normal half float, produce a denormal or zero
+        if half_exponent < -10 {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let round_bit = 1 << (shift - 1);
+        let mut half_mantissa = mantissa >> shift;
+        if mantissa & round_bit != 0 && mantissa & (3 * round_bit - 1) != 0 {
+            half_mantissa += 1;
+        }
+
+        return sign | half_mantissa as u16;
+    }
+
+    // Rounding may carry into the exponent, which produces the correct result unless it rounds up
+    // to infinity
+    let round_bit = 0x1000;
+    let mut half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    if mantissa & round_bit != 0 && mantissa & (3 * round_bit - 1) != 0 {
+        half += 1;
+    }
+
+    sign | half.min(0x7bff) as u16
+}
+
+// Unsigned 11-bit float: 5-bit exponent, 6-bit mantissa. Negative values and NaN become 0.
+fn f32_to_uf11_bits(value: f32) -> u32 {
+    if !(value > 0.0) {
+        return 0;
+    }
+
+    // Half floats use the same exponent bias, so drop the sign and the low bits of the mantissa.
+    // Truncating the largest finite half float gives the largest finite uf11.
+    (f32_to_f16_bits(value) as u32 >> 4) & 0x7ff
+}
+
+// Unsigned 10-bit float: 5-bit exponent, 5-bit mantissa. Negative values and NaN become 0.
+fn f32_to_uf10_bits(value: f32) -> u32 {
+    if !(value > 0.0) {
+        return 0;
+    }
+
+    (f32_to_f16_bits(value) as u32 >> 5) & 0x3ff
+}
+
+pub(super) fn rgba32f_to_rgba16f_bytes(rgba32f: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(rgba32f.len() * 2);
+    for &value in rgba32f {
+        bytes.extend_from_slice(&f32_to_f16_bits(value).to_ne_bytes());
+    }
+
+    bytes
+}
+
+pub(super) fn rgba32f_to_rgba32f_bytes(rgba32f: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(rgba32f.len() * 4);
+    for &value in rgba32f {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+
+    bytes
+}
+
+// Alpha is dropped
+pub(super) fn rgba32f_to_b10g11r11_bytes(rgba32f: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(rgba32f.len());
+    for pixel in rgba32f.chunks_exact(4) {
+        let packed = f32_to_uf11_bits(pixel[0])
+            | (f32_to_uf11_bits(pixel[1]) << 11)
+            | (f32_to_uf10_bits(pixel[2]) << 22);
+        bytes.extend_from_slice(&packed.to_ne_bytes());
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_f16_exact_values() {
+        assert_eq!(f32_to_f16_bits(0.0), 0x0000);
+        assert_eq!(f32_to_f16_bits(-0.0), 0x8000);
+        assert_eq!(f32_to_f16_bits(1.0), 0x3c00);
+        assert_eq!(f32_to_f16_bits(-2.0), 0xc000);
+        assert_eq!(f32_to_f16_bits(0.5), 0x3800);
+        assert_eq!(f32_to_f16_bits(65504.0), 0x7bff);
+        // Smallest normal and denormal
+        assert_eq!(f32_to_f16_bits(2.0f32.powi(-14)), 0x0400);
+        assert_eq!(f32_to_f16_bits(2.0f32.powi(-24)), 0x0001);
+    }
+
+    #[test]
+    fn test_f16_special_values() {
+        assert_eq!(f32_to_f16_bits(std::f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16_bits(std::f32::NEG_INFINITY), 0xfc00);
+        assert_eq!(f32_to_f16_bits(std::f32::NAN) & 0x7c00, 0x7c00);
+        assert_ne!(f32_to_f16_bits(std::f32::NAN) & 0x03ff, 0);
+        // Too large for a half float, saturates to the largest finite value
+        assert_eq!(f32_to_f16_bits(1.0e6), 0x7bff);
+        assert_eq!(f32_to_f16_bits(-1.0e6), 0xfbff);
+        assert_eq!(f32_to_f16_bits(1.0e10), 0x7bff);
+        assert_eq!(f32_to_f16_bits(std::f32::MAX), 0x7bff);
+        // Too small for a half float denormal
+        assert_eq!(f32_to_f16_bits(1.0e-10), 0x0000);
+        assert_eq!(f32_to_f16_bits(-1.0e-10), 0x8000);
+    }
+
+    #[test]
+    fn test_f16_round_to_nearest_even() {
+        // The spacing of half floats between 1.0 and 2.0 is 2^-10
+        let ulp = 2.0f32.powi(-10);
+
+        // Ties round to the even mantissa
+        assert_eq!(f32_to_f16_bits(1.0 + 0.5 * ulp), 0x3c00);
+        assert_eq!(f32_to_f16_bits(1.0 + 1.5 * ulp), 0x3c02);
+        assert_eq!(f32_to_f16_bits(1.0 + 2.5 * ulp), 0x3c02);
+
+        // Values that aren't ties round to the nearest value
+        assert_eq!(f32_to_f16_bits(1.0 + 0.25 * ulp), 0x3c00);
+        assert_eq!(f32_to_f16_bits(1.0 + 0.75 * ulp), 0x3c01);
+        assert_eq!(f32_to_f16_bits(1.0 + 0.5 * ulp + 2.0f32.powi(-20)), 0x3c01);
+
+        // Rounding up may carry into the exponent, but saturates rather than overflowing to
+        // infinity
+        assert_eq!(f32_to_f16_bits(2.0 - 0.25 * ulp), 0x4000);
+        assert_eq!(f32_to_f16_bits(65519.0), 0x7bff);
+        assert_eq!(f32_to_f16_bits(65520.0), 0x7bff);
+        assert_eq!(f32_to_f16_bits(-65520.0), 0xfbff);
+
+        // Denormals round the same way
+        let denormal = 2.0f32.powi(-24);
+        assert_eq!(f32_to_f16_bits(0.5 * denormal), 0x0000);
+        assert_eq!(f32_to_f16_bits(1.5 * denormal), 0x0002);
+        assert_eq!(f32_to_f16_bits(2.5 * denormal), 0x0002);
+        assert_eq!(f32_to_f16_bits(0.75 * denormal), 0x0001);
+    }
+
+    #[test]
+    fn test_uf11_uf10() {
+        // Same exponent bias as half floats: 1.0 has an exponent of 15 and no mantissa
+        assert_eq!(f32_to_uf11_bits(1.0), 15 << 6);
+        assert_eq!(f32_to_uf10_bits(1.0), 15 << 5);
+        assert_eq!(f32_to_uf11_bits(1.5), (15 << 6) | (1 << 5));
+        assert_eq!(f32_to_uf10_bits(1.5), (15 << 5) | (1 << 4));
+
+        // Largest finite values
+        assert_eq!(f32_to_uf11_bits(65024.0), 0x7bf);
+        assert_eq!(f32_to_uf10_bits(64512.0), 0x3df);
+
+        // Low mantissa bits are truncated
+        assert_eq!(f32_to_uf11_bits(1.0 + 2.0f32.powi(-7)), 15 << 6);
+        assert_eq!(f32_to_uf10_bits(1.0 + 2.0f32.powi(-6)), 15 << 5);
+
+        // Values too large saturate to the largest finite values
+        assert_eq!(f32_to_uf11_bits(65536.0), 0x7bf);
+        assert_eq!(f32_to_uf10_bits(65536.0), 0x3df);
+        assert_eq!(f32_to_uf11_bits(1.0e6), 0x7bf);
+        assert_eq!(f32_to_uf10_bits(1.0e6), 0x3df);
+        assert_eq!(f32_to_uf11_bits(1.0e10), 0x7bf);
+
+        // Infinity, negative values, and NaN
+        assert_eq!(f32_to_uf11_bits(std::f32::INFINITY), 0x7c0);
+        assert_eq!(f32_to_uf10_bits(std::f32::INFINITY), 0x3e0);
+        assert_eq!(f32_to_uf11_bits(-1.0), 0);
+        assert_eq!(f32_to_uf10_bits(-1.0), 0);
+        assert_eq!(f32_to_uf11_bits(std::f32::NAN), 0);
+        assert_eq!(f32_to_uf10_bits(std::f32::NAN), 0);
+    }
+
+    #[test]
+    fn test_b10g11r11_packing() {
+        let bytes = rgba32f_to_b10g11r11_bytes(&[1.0, 2.0, 0.5, 123.0, 0.0, -1.0, 1.0, 0.0]);
+        assert_eq!(bytes.len(), 8);
+
+        let first = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        assert_eq!(first & 0x7ff, 15 << 6);
+        assert_eq!((first >> 11) & 0x7ff, 16 << 6);
+        assert_eq!(first >> 22, 14 << 5);
+
+        let second = u32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        assert_eq!(second, (15 << 5) << 22);
+    }
+
+    #[test]
+    fn test_rgba16f_bytes() {
+        let bytes = rgba32f_to_rgba16f_bytes(&[1.0, -2.0, 0.5, 0.0]);
+        let halfs: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|x| u16::from_ne_bytes([x[0], x[1]]))
+            .collect();
+        assert_eq!(halfs, vec![0x3c00, 0xc000, 0x3800, 0x0000]);
+    }
+}
@@ -1,19 +1,157 @@
-use crate::assets::image::{ImageAssetColorSpace, ImageAssetData};
+use super::mip_generation::{rgba32f_to_rgba8, rgba8_to_rgba32f};
+use crate::assets::image::{
+    ImageAssetColorSpace, ImageAssetData, ImageAssetDataFormatConfig, ImageAssetMipFilter,
+    ImageAssetMipGeneration, ImageAssetMipSettings,
+};
 use crate::ImageAssetDataFormat;
 #[cfg(feature = "basis-universal")]
 use basis_universal::BasisTextureType;
 use distill::importer::{Error, ImportedAsset, Importer, ImporterValue};
 use distill::{core::AssetUuid, importer::ImportOp};
-use image::GenericImageView;
-use rafx_api::RafxResourceType;
+use rafx_api::{RafxError, RafxResourceType};
 use serde::{Deserialize, Serialize};
 use std::io::Read;
 use type_uuid::*;
 
+// How the channels of a normal map are encoded in the source image. Normal maps are stored using
+// the OpenGL convention (+Y up, same as glTF).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ImageImporterNormalMapConvention {
+    // +Y up, stored as is
+    OpenGl,
+    // -Y up, the green channel is flipped on import
+    DirectX,
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "72c9fc52-923b-45df-b733-43fcb4e44598"]
+#[serde(default)]
+pub struct ImageImporterOptions {
+    // Use Linear for data that isn't color, i.e. normal, roughness, and metalness maps
+    pub color_space: ImageAssetColorSpace,
+    pub mip_generation: ImageAssetMipGeneration,
+    // Used when mip_generation is Precomupted and the image isn't basis compressed
+    pub mip_filter: ImageAssetMipFilter,
+    // Set for alpha tested images to keep the same fraction of texels passing the test in every mip
+    pub alpha_test_threshold: Option<f32>,
+    pub compression: ImageAssetDataFormatConfig,
+    // The image is downscaled (preserving aspect ratio) so that neither side exceeds this size
+    pub max_resolution: Option<u32>,
+    pub premultiply_alpha: bool,
+    // Set for normal maps. Normal maps are always imported with a linear color space.
+    pub normal_map: Option<ImageImporterNormalMapConvention>,
+}
+
+impl Default for ImageImporterOptions {
+    fn default() -> Self {
+        let (compression, mip_generation) = ImageAssetData::default_format_and_mip_generation();
+        ImageImporterOptions {
+            color_space: ImageAssetColorSpace::Srgb,
+            mip_generation,
+            mip_filter: ImageAssetMipFilter::Kaiser,
+            alpha_test_threshold: None,
+            compression,
+            max_resolution: None,
+            premultiply_alpha: false,
+            normal_map: None,
+        }
+    }
+}
+
+impl ImageImporterOptions {
+    pub fn color_space(&self) -> ImageAssetColorSpace {
+        if self.normal_map.is_some() {
+            ImageAssetColorSpace::Linear
+        } else {
+            self.color_space
+        }
+    }
+
+    pub fn mip_settings(&self) -> ImageAssetMipSettings {
+        ImageAssetMipSettings {
+            filter: self.mip_filter,
+            alpha_test_threshold: self.alpha_test_threshold,
+            normal_map: self.normal_map.is_some(),
+        }
+    }
+}
+
 #[derive(TypeUuid, Serialize, Deserialize, Default)]
 #[uuid = "23f90369-6916-4548-81d0-a76e0b162df2"]
 pub struct ImageImporterState(Option<AssetUuid>);
 
+// The size of the image after applying max_resolution, or None if it doesn't need to be resized
+fn resized_dimensions(
+    width: u32,
+    height: u32,
+    max_resolution: Option<u32>,
+) -> Option<(u32, u32)> {
+    let max_resolution = max_resolution?.max(1);
+    if width <= max_resolution && height <= max_resolution {
+        return None;
+    }
+
+    let scale = max_resolution as f32 / width.max(height) as f32;
+    let new_width = ((width as f32 * scale).round() as u32).max(1);
+    let new_height = ((height as f32 * scale).round() as u32).max(1);
+    Some((new_width, new_height))
+}
+
+// Applies the options that modify pixels before the image is encoded. Resizing and premultiplying
+// blend color values, so they are done on linear values and sRGB images are re-encoded afterwards.
+fn process_image(
+    decoded_image: image::DynamicImage,
+    options: &ImageImporterOptions,
+) -> image::RgbaImage {
+    let mut image = decoded_image.into_rgba8();
+
+    if options.normal_map == Some(ImageImporterNormalMapConvention::DirectX) {
+        for pixel in image.pixels_mut() {
+            pixel[1] = 255 - pixel[1];
+        }
+    }
+
+    let (width, height) = image.dimensions();
+    let resized_dimensions = resized_dimensions(width, height, options.max_resolution);
+    if resized_dimensions.is_none() && !options.premultiply_alpha {
+        return image;
+    }
+
+    let color_space = options.color_space();
+    let rgba32f = rgba8_to_rgba32f(&image, color_space);
+    let mut linear_image =
+        image::ImageBuffer::<image::Rgba<f32>, Vec<f32>>::from_raw(width, height, rgba32f).unwrap();
+
+    if let Some((new_width, new_height)) = resized_dimensions {
+        log::debug!(
+            "Downscaling image from {}x{} to {}x{}",
+            width,
+            height,
+            new_width,
+            new_height
+        );
+        linear_image = image::imageops::resize(
+            &linear_image,
+            new_width,
+            new_height,
+            image::imageops::FilterType::Triangle,
+        );
+    }
+
+    if options.premultiply_alpha {
+        for pixel in linear_image.pixels_mut() {
+            let alpha = pixel[3];
+            for channel in 0..3 {
+                pixel[channel] *= alpha;
+            }
+        }
+    }
+
+    let (width, height) = linear_image.dimensions();
+    let rgba8 = rgba32f_to_rgba8(&linear_image, color_space);
+    image::RgbaImage::from_raw(width, height, rgba8).unwrap()
+}
+
 #[derive(TypeUuid)]
 #[uuid = "4ae5ddc5-6805-4cf5-aa14-d44c6e0b8251"]
 pub struct ImageImporter(pub image::ImageFormat);
@@ -22,14 +160,14 @@ impl Importer for ImageImporter {
     where
         Self: Sized,
     {
-        2
+        5
     }
 
     fn version(&self) -> u32 {
         Self::version_static()
     }
 
-    type Options = ();
+    type Options = ImageImporterOptions;
 
     type State = ImageImporterState;
 
@@ -39,7 +177,7 @@ impl Importer for ImageImporter {
         &self,
         _op: &mut ImportOp,
         source: &mut dyn Read,
-        _options: &Self::Options,
+        options: &Self::Options,
         state: &mut Self::State,
     ) -> distill::importer::Result<ImporterValue> {
         let id = state
@@ -53,18 +191,19 @@ impl Importer for ImageImporter {
 
         let decoded_image = image::load_from_memory_with_format(&bytes, self.0)
             .map_err(|e| Error::Boxed(Box::new(e)))?;
-        let (width, height) = decoded_image.dimensions();
-        let (format, mip_generation) = ImageAssetData::default_format_and_mip_generation();
-        let asset_data = ImageAssetData::from_raw_rgba32(
+        let rgba_image = process_image(decoded_image, options);
+        let (width, height) = rgba_image.dimensions();
+        let asset_data = ImageAssetData::from_raw_rgba32_with_mip_settings(
             width,
             height,
-            ImageAssetColorSpace::Srgb,
-            format,
-            mip_generation,
+            options.color_space(),
+            options.compression,
+            options.mip_generation,
+            &options.mip_settings(),
             RafxResourceType::TEXTURE,
-            decoded_image.into_rgba8().as_bytes(),
+            rgba_image.as_bytes(),
         )
-        .unwrap();
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
 
         Ok(ImporterValue {
             assets: vec![ImportedAsset {
@@ -155,3 +294,375 @@ impl Importer for BasisImageImporter {
         })
     }
 }
+
+#[derive(TypeUuid, Serialize, Deserialize, Clone, Debug)]
+#[uuid = "e9a8c101-c713-4874-9c79-8e5599b13057"]
+#[serde(default)]
+pub struct HdrImageImporterOptions {
+    // Must be one of the floating point formats
+    pub format: ImageAssetDataFormatConfig,
+    pub mip_generation: ImageAssetMipGeneration,
+    // Used when mip_generation is Precomupted
+    pub mip_filter: ImageAssetMipFilter,
+    // The image is downscaled (preserving aspect ratio) so that neither side exceeds this size
+    pub max_resolution: Option<u32>,
+}
+
+impl Default for HdrImageImporterOptions {
+    fn default() -> Self {
+        HdrImageImporterOptions {
+            format: ImageAssetDataFormatConfig::RawRGBA16Float,
+            mip_generation: ImageAssetMipGeneration::Runtime,
+            mip_filter: ImageAssetMipFilter::Kaiser,
+            max_resolution: None,
+        }
+    }
+}
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "543cbe75-8f53-45ec-aa1b-a4da7f5c89a5"]
+pub struct HdrImageImporterState(Option<AssetUuid>);
+
+// Produces an image asset from linear RGBA floats
+fn import_rgba32f(
+    width: u32,
+    height: u32,
+    rgba32f: Vec<f32>,
+    options: &HdrImageImporterOptions,
+) -> distill::importer::Result<ImageAssetData> {
+    let mut rgba_image =
+        image::ImageBuffer::<image::Rgba<f32>, Vec<f32>>::from_raw(width, height, rgba32f)
+            .ok_or_else(|| {
+                Error::Boxed(Box::new(RafxError::StringError(format!(
+                    "Decoded HDR image does not match its size {}x{}",
+                    width, height
+                ))))
+            })?;
+
+    if let Some(max_resolution) = options.max_resolution {
+        let max_resolution = max_resolution.max(1);
+        if width > max_resolution || height > max_resolution {
+            let scale = max_resolution as f32 / width.max(height) as f32;
+            let new_width = ((width as f32 * scale).round() as u32).max(1);
+            let new_height = ((height as f32 * scale).round() as u32).max(1);
+            rgba_image = image::imageops::resize(
+                &rgba_image,
+                new_width,
+                new_height,
+                image::imageops::FilterType::Triangle,
+            );
+        }
+    }
+
+    let (width, height) = rgba_image.dimensions();
+    ImageAssetData::from_raw_rgba32f(
+        width,
+        height,
+        options.format,
+        options.mip_generation,
+        &ImageAssetMipSettings {
+            filter: options.mip_filter,
+            ..Default::default()
+        },
+        RafxResourceType::TEXTURE,
+        rgba_image.as_raw(),
+    )
+    .map_err(|e| Error::Boxed(Box::new(e)))
+}
+
+// Decodes a Radiance .hdr file to linear RGBA floats
+pub(super) fn decode_hdr(bytes: Vec<u8>) -> distill::importer::Result<(u32, u32, Vec<f32>)> {
+    let decoder = image::codecs::hdr::HdrDecoder::new(std::io::Cursor::new(bytes))
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+    let metadata = decoder.metadata();
+    let pixels = decoder
+        .read_image_hdr()
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+    let mut rgba32f = Vec::with_capacity(pixels.len() * 4);
+    for pixel in pixels {
+        rgba32f.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 1.0]);
+    }
+
+    Ok((metadata.width, metadata.height, rgba32f))
+}
+
+// Decodes the largest resolution level of the first layer of an OpenEXR file that has RGB(A)
+// channels to linear RGBA floats
+#[cfg(feature = "exr")]
+pub(super) fn decode_exr(bytes: Vec<u8>) -> distill::importer::Result<(u32, u32, Vec<f32>)> {
+    use exr::prelude::*;
+
+    // Pixels are stored with their resolution so the position can be converted to an index
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .rgba_channels(
+            |resolution, _channels: &RgbaChannels| {
+                (
+                    resolution,
+                    vec![0.0; resolution.width() * resolution.height() * 4],
+                )
+            },
+            |(resolution, pixels): &mut (Vec2<usize>, Vec<f32>),
+             position: Vec2<usize>,
+             (r, g, b, a): (f32, f32, f32, f32)| {
+                let index = (position.y() * resolution.width() + position.x()) * 4;
+                pixels[index..index + 4].copy_from_slice(&[r, g, b, a]);
+            },
+        )
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(std::io::Cursor::new(bytes))
+        .map_err(|e| Error::Boxed(Box::new(e)))?;
+
+    let (resolution, rgba32f) = image.layer_data.channel_data.pixels;
+    Ok((
+        resolution.width() as u32,
+        resolution.height() as u32,
+        rgba32f,
+    ))
+}
+
+// Imports Radiance .hdr files
+#[derive(TypeUuid)]
+#[uuid = "53ce24f8-0c7c-434a-8b52-7816a334a5ac"]
+pub struct HdrImageImporter;
+impl Importer for HdrImageImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        2
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = HdrImageImporterOptions;
+
+    type State = HdrImageImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = HdrImageImporterState(Some(id));
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+
+        let (width, height, rgba32f) = decode_hdr(bytes)?;
+        let asset_data = import_rgba32f(width, height, rgba32f, options)?;
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id,
+                search_tags: vec![],
+                build_deps: vec![],
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(asset_data),
+            }],
+        })
+    }
+}
+
+#[cfg(feature = "exr")]
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "3d27795c-8ef8-4491-b0b0-57bb4976e661"]
+pub struct ExrImageImporterState(Option<AssetUuid>);
+
+// Imports the largest resolution level of the first layer of OpenEXR files that have RGB(A)
+// channels
+#[cfg(feature = "exr")]
+#[derive(TypeUuid)]
+#[uuid = "c8635878-5f47-4e7e-9607-076fd5513636"]
+pub struct ExrImageImporter;
+
+#[cfg(feature = "exr")]
+impl Importer for ExrImageImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        2
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = HdrImageImporterOptions;
+
+    type State = ExrImageImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = ExrImageImporterState(Some(id));
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+
+        let (width, height, rgba32f) = decode_exr(bytes)?;
+        let asset_data = import_rgba32f(width, height, rgba32f, options)?;
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id,
+                search_tags: vec![],
+                build_deps: vec![],
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(asset_data),
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rgba_image(
+        width: u32,
+        height: u32,
+        pixels: &[[u8; 4]],
+    ) -> image::DynamicImage {
+        let rgba8 = pixels.iter().flatten().copied().collect();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(width, height, rgba8).unwrap())
+    }
+
+    fn assert_pixel_near(
+        image: &image::RgbaImage,
+        x: u32,
+        y: u32,
+        expected: [u8; 4],
+    ) {
+        let pixel = image.get_pixel(x, y).0;
+        for (&value, &expected_value) in pixel.iter().zip(&expected) {
+            assert!(
+                (value as i32 - expected_value as i32).abs() <= 1,
+                "pixel ({}, {}) is {:?}, expected {:?}",
+                x,
+                y,
+                pixel,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = ImageImporterOptions::default();
+        assert!(matches!(options.color_space, ImageAssetColorSpace::Srgb));
+        assert_eq!(options.mip_filter, ImageAssetMipFilter::Kaiser);
+        assert_eq!(options.alpha_test_threshold, None);
+        assert_eq!(options.max_resolution, None);
+        assert!(!options.premultiply_alpha);
+        assert_eq!(options.normal_map, None);
+        assert!(matches!(options.color_space(), ImageAssetColorSpace::Srgb));
+
+        // Normal maps are always linear
+        let options = ImageImporterOptions {
+            normal_map: Some(ImageImporterNormalMapConvention::OpenGl),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.color_space(),
+            ImageAssetColorSpace::Linear
+        ));
+        assert!(options.mip_settings().normal_map);
+
+        // The defaults leave pixels untouched
+        let pixels = [[0, 64, 128, 255], [255, 128, 0, 10]];
+        let image = process_image(rgba_image(2, 1, &pixels), &Default::default());
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, pixels[0]);
+        assert_eq!(image.get_pixel(1, 0).0, pixels[1]);
+    }
+
+    #[test]
+    fn test_resized_dimensions() {
+        assert_eq!(resized_dimensions(100, 50, None), None);
+        assert_eq!(resized_dimensions(100, 50, Some(100)), None);
+        assert_eq!(resized_dimensions(100, 50, Some(50)), Some((50, 25)));
+        assert_eq!(resized_dimensions(50, 100, Some(10)), Some((5, 10)));
+        // Neither side shrinks to 0
+        assert_eq!(resized_dimensions(1000, 1, Some(10)), Some((10, 1)));
+        assert_eq!(resized_dimensions(10, 10, Some(0)), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_resize_in_linear_space() {
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+        let pixels = [black, white, black, white];
+
+        // Averaging black and white is 0.5 in linear space, which is 188 when encoded as sRGB
+        let options = ImageImporterOptions {
+            max_resolution: Some(1),
+            ..Default::default()
+        };
+        let image = process_image(rgba_image(2, 2, &pixels), &options);
+        assert_eq!(image.dimensions(), (1, 1));
+        assert_pixel_near(&image, 0, 0, [188, 188, 188, 255]);
+
+        let options = ImageImporterOptions {
+            max_resolution: Some(1),
+            color_space: ImageAssetColorSpace::Linear,
+            ..Default::default()
+        };
+        let image = process_image(rgba_image(2, 2, &pixels), &options);
+        assert_pixel_near(&image, 0, 0, [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_premultiply_alpha_in_linear_space() {
+        let pixels = [[255, 128, 0, 128], [255, 255, 255, 0]];
+
+        let options = ImageImporterOptions {
+            premultiply_alpha: true,
+            ..Default::default()
+        };
+        let image = process_image(rgba_image(2, 1, &pixels), &options);
+        assert_pixel_near(&image, 0, 0, [188, 93, 0, 128]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0, 0]);
+
+        let options = ImageImporterOptions {
+            premultiply_alpha: true,
+            color_space: ImageAssetColorSpace::Linear,
+            ..Default::default()
+        };
+        let image = process_image(rgba_image(2, 1, &pixels), &options);
+        assert_pixel_near(&image, 0, 0, [128, 64, 0, 128]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_directx_normal_map() {
+        let options = ImageImporterOptions {
+            normal_map: Some(ImageImporterNormalMapConvention::DirectX),
+            ..Default::default()
+        };
+        let image = process_image(rgba_image(1, 1, &[[128, 64, 255, 255]]), &options);
+        assert_eq!(image.get_pixel(0, 0).0, [128, 191, 255, 255]);
+    }
+}
@@ -0,0 +1,608 @@
+// Builds mip chains on the CPU when images are imported, so that the full chain can be stored in the
+// asset. This is needed for formats and backends that can't generate mips at runtime (compressed
+// formats, GLES2). Filtering is done in linear space on f32 data.
+use super::ImageAssetColorSpace;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ImageAssetMipFilter {
+    // Averages the texels covered by each destination texel. Matches mips generated at runtime.
+    Box,
+    // Kaiser windowed sinc. Sharper than box with very little ringing.
+    Kaiser,
+    // Lanczos windowed sinc with 3 lobes. Sharpest, but may ring around high contrast edges.
+    Lanczos,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageAssetMipSettings {
+    pub filter: ImageAssetMipFilter,
+    // For alpha tested images. Alpha in each mip is scaled so that the fraction of texels passing
+    // the test matches the full resolution image, otherwise alpha tested geometry thins out as it
+    // gets further away.
+    pub alpha_test_threshold: Option<f32>,
+    // XYZ is stored as RGB * 2 - 1 and is renormalized after filtering
+    pub normal_map: bool,
+}
+
+impl Default for ImageAssetMipSettings {
+    fn default() -> Self {
+        ImageAssetMipSettings {
+            filter: ImageAssetMipFilter::Kaiser,
+            alpha_test_threshold: None,
+            normal_map: false,
+        }
+    }
+}
+
+pub(super) struct MipLevel {
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) rgba: Vec<f32>,
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        let x = x * std::f32::consts::PI;
+        x.sin() / x
+    }
+}
+
+// Zeroth order modified Bessel function of the first kind
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_x_squared = x * x * 0.25;
+    for k in 1..32 {
+        term *= half_x_squared / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-7 {
+            break;
+        }
+    }
+
+    sum
+}
+
+const KAISER_WIDTH: f32 = 3.0;
+const KAISER_ALPHA: f32 = 4.0;
+const LANCZOS_LOBES: f32 = 3.0;
+
+impl ImageAssetMipFilter {
+    // Radius of the filter, in destination texels
+    fn support(self) -> f32 {
+        match self {
+            ImageAssetMipFilter::Box => 0.5,
+            ImageAssetMipFilter::Kaiser => KAISER_WIDTH,
+            ImageAssetMipFilter::Lanczos => LANCZOS_LOBES,
+        }
+    }
+
+    fn evaluate(
+        self,
+        x: f32,
+    ) -> f32 {
+        match self {
+            ImageAssetMipFilter::Box => {
+                if x.abs() <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ImageAssetMipFilter::Kaiser => {
+                let t = x / KAISER_WIDTH;
+                if t.abs() >= 1.0 {
+                    0.0
+                } else {
+                    sinc(x) * bessel_i0(KAISER_ALPHA * (1.0 - t * t).sqrt())
+                        / bessel_i0(KAISER_ALPHA)
+                }
+            }
+            ImageAssetMipFilter::Lanczos => {
+                if x.abs() >= LANCZOS_LOBES {
+                    0.0
+                } else {
+                    sinc(x) * sinc(x / LANCZOS_LOBES)
+                }
+            }
+        }
+    }
+}
+
+// For each destination texel along one axis, the source texels it reads and their weights. Texels
+// past the edge of the image are clamped.
+fn compute_weights(
+    src_size: u32,
+    dst_size: u32,
+    filter: ImageAssetMipFilter,
+) -> Vec<Vec<(usize, f32)>> {
+    let scale = src_size as f32 / dst_size as f32;
+    let radius = filter.support() * scale;
+
+    (0..dst_size)
+        .map(|dst| {
+            let center = (dst as f32 + 0.5) * scale;
+            let first = (center - radius - 0.5).ceil() as i64;
+            let last = (center + radius - 0.5).floor() as i64;
+
+            let mut taps: Vec<(usize, f32)> = (first..=last)
+                .map(|src| {
+                    let weight = filter.evaluate((src as f32 + 0.5 - center) / scale);
+                    let src = src.max(0).min(src_size as i64 - 1) as usize;
+                    (src, weight)
+                })
+                .filter(|(_, weight)| *weight != 0.0)
+                .collect();
+
+            let weight_sum: f32 = taps.iter().map(|(_, weight)| weight).sum();
+            if weight_sum.abs() > 1e-6 {
+                for (_, weight) in &mut taps {
+                    *weight /= weight_sum;
+                }
+            } else {
+                let nearest = (center as usize).min(src_size as usize - 1);
+                taps = vec![(nearest, 1.0)];
+            }
+
+            taps
+        })
+        .collect()
+}
+
+fn downsample(
+    level: &MipLevel,
+    filter: ImageAssetMipFilter,
+) -> MipLevel {
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+
+    // Horizontal pass
+    let horizontal_weights = compute_weights(level.width, width, filter);
+    let mut horizontal = vec![0.0; (width * level.height * 4) as usize];
+    for y in 0..level.height as usize {
+        let src_row = &level.rgba[y * level.width as usize * 4..];
+        for (x, taps) in horizontal_weights.iter().enumerate() {
+            let dst = (y * width as usize + x) * 4;
+            for &(src_x, weight) in taps {
+                for channel in 0..4 {
+                    horizontal[dst + channel] += src_row[src_x * 4 + channel] * weight;
+                }
+            }
+        }
+    }
+
+    // Vertical pass
+    let vertical_weights = compute_weights(level.height, height, filter);
+    let mut rgba = vec![0.0; (width * height * 4) as usize];
+    for (y, taps) in vertical_weights.iter().enumerate() {
+        for &(src_y, weight) in taps {
+            let src_row = &horizontal[src_y * width as usize * 4..(src_y + 1) * width as usize * 4];
+            let dst_row = &mut rgba[y * width as usize * 4..(y + 1) * width as usize * 4];
+            for (dst, src) in dst_row.iter_mut().zip(src_row) {
+                *dst += src * weight;
+            }
+        }
+    }
+
+    // Sharper filters have negative lobes that can undershoot
+    for value in &mut rgba {
+        *value = value.max(0.0);
+    }
+
+    MipLevel {
+        width,
+        height,
+        rgba,
+    }
+}
+
+fn renormalize(rgba: &mut [f32]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let x = pixel[0] * 2.0 - 1.0;
+        let y = pixel[1] * 2.0 - 1.0;
+        let z = pixel[2] * 2.0 - 1.0;
+        let length = (x * x + y * y + z * z).sqrt();
+        if length > 1e-6 {
+            pixel[0] = (x / length) * 0.5 + 0.5;
+            pixel[1] = (y / length) * 0.5 + 0.5;
+            pixel[2] = (z / length) * 0.5 + 0.5;
+        }
+    }
+}
+
+fn alpha_coverage(
+    rgba: &[f32],
+    alpha_test_threshold: f32,
+    alpha_scale: f32,
+) -> f32 {
+    let pixel_count = rgba.len() / 4;
+    let passing = rgba
+        .chunks_exact(4)
+        .filter(|pixel| (pixel[3] * alpha_scale).min(1.0) >= alpha_test_threshold)
+        .count();
+    passing as f32 / pixel_count as f32
+}
+
+fn preserve_alpha_coverage(
+    rgba: &mut [f32],
+    alpha_test_threshold: f32,
+    target_coverage: f32,
+) {
+    // Coverage increases with the scale, so binary search for the scale that matches. Coverage only
+    // takes discrete values, so keep the closest one found.
+    let mut min_scale = 0.0;
+    let mut max_scale = 4.0;
+    let mut best_scale = 1.0;
+    let mut best_error = std::f32::MAX;
+    for _ in 0..16 {
+        let alpha_scale = (min_scale + max_scale) * 0.5;
+        let coverage = alpha_coverage(rgba, alpha_test_threshold, alpha_scale);
+        let error = (coverage - target_coverage).abs();
+        if error < best_error {
+            best_scale = alpha_scale;
+            best_error = error;
+        }
+
+        if coverage < target_coverage {
+            min_scale = alpha_scale;
+        } else if coverage > target_coverage {
+            max_scale = alpha_scale;
+        } else {
+            break;
+        }
+    }
+
+    for pixel in rgba.chunks_exact_mut(4) {
+        pixel[3] = (pixel[3] * best_scale).min(1.0);
+    }
+}
+
+// Returns every level from the given image down to 1x1. The first level is the given image.
+pub(super) fn generate_mip_chain(
+    width: u32,
+    height: u32,
+    rgba: Vec<f32>,
+    settings: &ImageAssetMipSettings,
+) -> Vec<MipLevel> {
+    let target_coverage = settings
+        .alpha_test_threshold
+        .map(|threshold| alpha_coverage(&rgba, threshold, 1.0));
+
+    let mut levels = vec![MipLevel {
+        width,
+        height,
+        rgba,
+    }];
+
+    loop {
+        let previous = levels.last().unwrap();
+        if previous.width == 1 && previous.height == 1 {
+            break;
+        }
+
+        let mut level = downsample(previous, settings.filter);
+
+        if settings.normal_map {
+            renormalize(&mut level.rgba);
+        }
+
+        if let (Some(threshold), Some(target_coverage)) =
+            (settings.alpha_test_threshold, target_coverage)
+        {
+            preserve_alpha_coverage(&mut level.rgba, threshold, target_coverage);
+        }
+
+        levels.push(level);
+    }
+
+    levels
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Alpha is always linear
+pub(super) fn rgba8_to_rgba32f(
+    rgba8: &[u8],
+    color_space: ImageAssetColorSpace,
+) -> Vec<f32> {
+    let mut rgb_table = [0.0; 256];
+    for (i, value) in rgb_table.iter_mut().enumerate() {
+        *value = match color_space {
+            ImageAssetColorSpace::Srgb => srgb_to_linear(i as f32 / 255.0),
+            ImageAssetColorSpace::Linear => i as f32 / 255.0,
+        };
+    }
+
+    let mut rgba32f = Vec::with_capacity(rgba8.len());
+    for pixel in rgba8.chunks_exact(4) {
+        rgba32f.extend_from_slice(&[
+            rgb_table[pixel[0] as usize],
+            rgb_table[pixel[1] as usize],
+            rgb_table[pixel[2] as usize],
+            pixel[3] as f32 / 255.0,
+        ]);
+    }
+
+    rgba32f
+}
+
+pub(super) fn rgba32f_to_rgba8(
+    rgba32f: &[f32],
+    color_space: ImageAssetColorSpace,
+) -> Vec<u8> {
+    let quantize = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+    let quantize_rgb = |value: f32| match color_space {
+        ImageAssetColorSpace::Srgb => quantize(linear_to_srgb(value.max(0.0))),
+        ImageAssetColorSpace::Linear => quantize(value),
+    };
+
+    let mut rgba8 = Vec::with_capacity(rgba32f.len());
+    for pixel in rgba32f.chunks_exact(4) {
+        rgba8.extend_from_slice(&[
+            quantize_rgb(pixel[0]),
+            quantize_rgb(pixel[1]),
+            quantize_rgb(pixel[2]),
+            quantize(pixel[3]),
+        ]);
+    }
+
+    rgba8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILTERS: [ImageAssetMipFilter; 3] = [
+        ImageAssetMipFilter::Box,
+        ImageAssetMipFilter::Kaiser,
+        ImageAssetMipFilter::Lanczos,
+    ];
+
+    fn assert_near(
+        actual: f32,
+        expected: f32,
+        tolerance: f32,
+    ) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    // Deterministic values in [0, 1)
+    fn pseudo_random_values(count: usize) -> Vec<f32> {
+        let mut state = 12345u32;
+        (0..count)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state >> 8) as f32 / (1 << 24) as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_weights_are_normalized() {
+        for &filter in &FILTERS {
+            for src_size in 1..40 {
+                let dst_size = (src_size / 2).max(1);
+                let weights = compute_weights(src_size, dst_size, filter);
+                assert_eq!(weights.len(), dst_size as usize);
+                for taps in &weights {
+                    assert!(!taps.is_empty());
+                    assert!(taps.iter().all(|(src, _)| *src < src_size as usize));
+                    let weight_sum: f32 = taps.iter().map(|(_, weight)| weight).sum();
+                    assert_near(weight_sum, 1.0, 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_box_weights_average_pairs() {
+        let weights = compute_weights(8, 4, ImageAssetMipFilter::Box);
+        for (dst, taps) in weights.iter().enumerate() {
+            assert_eq!(taps.len(), 2);
+            assert_eq!(taps[0].0, dst * 2);
+            assert_eq!(taps[1].0, dst * 2 + 1);
+            assert_near(taps[0].1, 0.5, 1e-6);
+            assert_near(taps[1].1, 0.5, 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_bessel_i0() {
+        assert_near(bessel_i0(0.0), 1.0, 1e-6);
+        assert_near(bessel_i0(1.0), 1.266_066, 1e-5);
+        assert_near(bessel_i0(KAISER_ALPHA), 11.301_922, 1e-4);
+    }
+
+    #[test]
+    fn test_windowed_sinc_filters() {
+        for &filter in &[ImageAssetMipFilter::Kaiser, ImageAssetMipFilter::Lanczos] {
+            assert_near(filter.evaluate(0.0), 1.0, 1e-6);
+
+            // Symmetric, zero at nonzero integers like sinc, and zero outside the support
+            for i in 1..4 {
+                let x = i as f32;
+                assert_near(filter.evaluate(x), 0.0, 1e-5);
+                assert_near(filter.evaluate(x - 0.5), filter.evaluate(0.5 - x), 1e-6);
+            }
+            assert_eq!(filter.evaluate(filter.support()), 0.0);
+            assert_eq!(filter.evaluate(-filter.support() - 0.1), 0.0);
+
+            // Negative lobe between 1 and 2
+            assert!(filter.evaluate(1.5) < 0.0);
+        }
+
+        // sinc(0.5) * sinc(0.5 / 3)
+        assert_near(ImageAssetMipFilter::Lanczos.evaluate(0.5), 0.607_927, 1e-5);
+
+        // sinc(0.5) * I0(4 * sqrt(1 - (0.5 / 3)^2)) / I0(4)
+        let expected = sinc(0.5) * bessel_i0(4.0 * (35.0f32 / 36.0).sqrt()) / bessel_i0(4.0);
+        assert_near(ImageAssetMipFilter::Kaiser.evaluate(0.5), expected, 1e-6);
+        assert!(
+            ImageAssetMipFilter::Kaiser.evaluate(0.5) < ImageAssetMipFilter::Lanczos.evaluate(0.5)
+        );
+    }
+
+    #[test]
+    fn test_downsample_preserves_constant_images() {
+        let color = [0.25, 0.5, 0.75, 1.0];
+        for &filter in &FILTERS {
+            let level = MipLevel {
+                width: 7,
+                height: 4,
+                rgba: color.iter().cloned().cycle().take(7 * 4 * 4).collect(),
+            };
+
+            let downsampled = downsample(&level, filter);
+            assert_eq!(downsampled.width, 3);
+            assert_eq!(downsampled.height, 2);
+            for pixel in downsampled.rgba.chunks_exact(4) {
+                for channel in 0..4 {
+                    assert_near(pixel[channel], color[channel], 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_downsample_clamps_undershoot() {
+        // A hard edge makes the negative lobes of the windowed sinc filters undershoot
+        let width = 16;
+        let rgba: Vec<f32> = (0..width)
+            .flat_map(|x| {
+                let value = if x < width / 2 { 0.0 } else { 1.0 };
+                vec![value, value, value, 1.0]
+            })
+            .collect();
+
+        for &filter in &FILTERS {
+            let level = MipLevel {
+                width,
+                height: 1,
+                rgba: rgba.clone(),
+            };
+            let downsampled = downsample(&level, filter);
+            assert!(downsampled.rgba.iter().all(|value| *value >= 0.0));
+        }
+    }
+
+    #[test]
+    fn test_mip_chain_sizes() {
+        let levels = generate_mip_chain(
+            8,
+            2,
+            vec![0.5; 8 * 2 * 4],
+            &ImageAssetMipSettings::default(),
+        );
+        let sizes: Vec<_> = levels
+            .iter()
+            .map(|level| (level.width, level.height, level.rgba.len()))
+            .collect();
+        assert_eq!(sizes, vec![(8, 2, 64), (4, 1, 16), (2, 1, 8), (1, 1, 4)]);
+    }
+
+    #[test]
+    fn test_alpha_coverage_is_preserved() {
+        let size = 64;
+        let alpha_test_threshold = 0.7;
+        let rgba: Vec<f32> = pseudo_random_values((size * size) as usize)
+            .into_iter()
+            .flat_map(|alpha| vec![1.0, 1.0, 1.0, alpha])
+            .collect();
+        let target_coverage = alpha_coverage(&rgba, alpha_test_threshold, 1.0);
+
+        let mut settings = ImageAssetMipSettings::default();
+        let unpreserved = generate_mip_chain(size, size, rgba.clone(), &settings);
+
+        settings.alpha_test_threshold = Some(alpha_test_threshold);
+        let preserved = generate_mip_chain(size, size, rgba, &settings);
+
+        // Averaging random alpha pulls it towards 0.5, so without scaling almost nothing passes.
+        // Levels smaller than 8x8 have too few texels to match closely.
+        for (unpreserved, preserved) in unpreserved.iter().zip(&preserved).skip(1) {
+            if preserved.width < 8 {
+                break;
+            }
+
+            let unpreserved_coverage = alpha_coverage(&unpreserved.rgba, alpha_test_threshold, 1.0);
+            let preserved_coverage = alpha_coverage(&preserved.rgba, alpha_test_threshold, 1.0);
+            assert!(unpreserved_coverage < target_coverage * 0.5);
+            assert_near(preserved_coverage, target_coverage, 0.05);
+            assert!(preserved.rgba.chunks_exact(4).all(|pixel| pixel[3] <= 1.0));
+        }
+    }
+
+    #[test]
+    fn test_normal_maps_are_renormalized() {
+        // Normals alternate between leaning left and right, so averaging them shortens them
+        let size = 8;
+        let tilt = std::f32::consts::FRAC_1_SQRT_2;
+        let rgba: Vec<f32> = (0..size * size)
+            .flat_map(|i| {
+                let x = if i % 2 == 0 { tilt } else { -tilt };
+                vec![x * 0.5 + 0.5, 0.5, tilt * 0.5 + 0.5, 1.0]
+            })
+            .collect();
+
+        let settings = ImageAssetMipSettings {
+            filter: ImageAssetMipFilter::Box,
+            alpha_test_threshold: None,
+            normal_map: true,
+        };
+        let levels = generate_mip_chain(size, size, rgba, &settings);
+
+        for level in &levels[1..] {
+            for pixel in level.rgba.chunks_exact(4) {
+                let x = pixel[0] * 2.0 - 1.0;
+                let y = pixel[1] * 2.0 - 1.0;
+                let z = pixel[2] * 2.0 - 1.0;
+                assert_near((x * x + y * y + z * z).sqrt(), 1.0, 1e-5);
+
+                // The averaged normal points straight up
+                assert_near(z, 1.0, 1e-5);
+            }
+        }
+
+        // Zero length normals are left alone rather than dividing by zero
+        let mut degenerate = vec![0.5, 0.5, 0.5, 1.0];
+        renormalize(&mut degenerate);
+        assert_eq!(degenerate, vec![0.5, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_rgba8_round_trip() {
+        let rgba8: Vec<u8> = (0..=255).flat_map(|i| vec![i, i, i, i]).collect();
+        for &color_space in &[ImageAssetColorSpace::Srgb, ImageAssetColorSpace::Linear] {
+            let rgba32f = rgba8_to_rgba32f(&rgba8, color_space);
+            assert_eq!(rgba32f_to_rgba8(&rgba32f, color_space), rgba8);
+        }
+
+        // Alpha is linear even for sRGB images
+        let rgba32f = rgba8_to_rgba32f(&[128, 128, 128, 128], ImageAssetColorSpace::Srgb);
+        assert_near(rgba32f[0], 0.215_861, 1e-5);
+        assert_near(rgba32f[3], 128.0 / 255.0, 1e-6);
+    }
+}
@@ -1,5 +1,19 @@
 pub mod assets;
 pub use assets::*;
 
+mod float_formats;
+
+mod mip_generation;
+pub use mip_generation::{ImageAssetMipFilter, ImageAssetMipSettings};
+
 mod importer;
 pub use importer::*;
+
+mod container_importer;
+pub use container_importer::*;
+
+mod cubemap_importer;
+pub use cubemap_importer::*;
+
+mod streaming;
+pub use streaming::*;
@@ -0,0 +1,980 @@
+use crate::assets::graphics_pipeline::MaterialInstanceDescriptorSets;
+use crate::image_upload::{ImageUploadParams, IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT};
+use crate::{GpuImageData, MaterialInstanceAsset};
+use distill::loader::LoadHandle;
+use fnv::FnvHashMap;
+use rafx_api::extra::upload::{RafxTransferUpload, RafxTransferUploadState, RafxUploadError};
+use rafx_api::{RafxDeviceContext, RafxQueue, RafxResourceType, RafxResult, RafxTexture};
+use rafx_framework::descriptor_sets::DescriptorSetWriteElementImageValue;
+use rafx_framework::{
+    DescriptorSetAllocator, DescriptorSetArc, DescriptorSetLayoutResource, DescriptorSetWriteSet,
+    ImageViewResource, ResourceArc, ResourceLookupSet, WeakResourceArc,
+};
+use std::sync::{Arc, Mutex, Weak};
+
+#[derive(Clone, Debug)]
+pub struct TextureStreamingConfig {
+    // Material instances swap in streamed textures automatically. Images bound any other way stay at
+    // their resident resolution.
+    pub enabled: bool,
+    // Mip levels no larger than this are uploaded when an image loads and are never evicted. Images
+    // that have no larger mip levels are not streamed. Only images with mip levels stored in the
+    // asset can be streamed (mips generated at runtime only exist on the GPU).
+    pub resident_mip_size: u32,
+    // Memory allowed for mip levels above the resident ones. When over budget, the images that were
+    // requested least recently are evicted first.
+    pub memory_budget_bytes: u64,
+    // An image that would need a larger staging buffer is streamed at a lower resolution
+    pub max_bytes_per_upload: u64,
+    // Each upload in flight keeps a staging buffer of max_bytes_per_upload, they are reused
+    pub max_uploads_in_flight: usize,
+    // Frames an image keeps a resolution after it was last requested at it
+    pub eviction_delay_frames: u64,
+}
+
+impl Default for TextureStreamingConfig {
+    fn default() -> Self {
+        TextureStreamingConfig {
+            enabled: false,
+            resident_mip_size: 128,
+            memory_budget_bytes: 256 * 1024 * 1024,
+            max_bytes_per_upload: 64 * 1024 * 1024,
+            max_uploads_in_flight: 2,
+            eviction_delay_frames: 120,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TextureStreamingMetrics {
+    pub streamed_image_count: usize,
+    // Images that currently have mip levels above the resident ones
+    pub upgraded_image_count: usize,
+    pub streamed_bytes: u64,
+    pub memory_budget_bytes: u64,
+    pub uploads_in_flight: usize,
+    // Totals since streaming was started
+    pub completed_upload_count: usize,
+    pub evicted_count: usize,
+}
+
+// Collects the resolution streamed images are needed at this frame. It can be cloned and written
+// to from render jobs. Demand is given in texels per unit of texture coordinate, i.e. how many
+// pixels a surface whose texture coordinates span 0..1 covers on screen.
+#[derive(Clone, Default)]
+pub struct TextureStreamingFeedback {
+    requests: Arc<Mutex<FnvHashMap<WeakResourceArc<ImageViewResource>, f32>>>,
+}
+
+impl TextureStreamingFeedback {
+    pub fn request_image(
+        &self,
+        image_view: &ResourceArc<ImageViewResource>,
+        texels_per_uv: f32,
+    ) {
+        let mut requests = self.requests.lock().unwrap();
+        let request = requests.entry(image_view.downgrade()).or_insert(0.0);
+        *request = request.max(texels_per_uv);
+    }
+
+    pub fn request_material_instance(
+        &self,
+        material_instance: &MaterialInstanceAsset,
+        texels_per_uv: f32,
+    ) {
+        let image_views = material_instance
+            .material_descriptor_sets
+            .streamed_image_views();
+        if image_views.is_empty() {
+            return;
+        }
+
+        let mut requests = self.requests.lock().unwrap();
+        for image_view in image_views {
+            let request = requests.entry(image_view.clone()).or_insert(0.0);
+            *request = request.max(texels_per_uv);
+        }
+    }
+
+    fn take_requests(&self) -> FnvHashMap<WeakResourceArc<ImageViewResource>, f32> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+// An image whose largest mip levels were held back when it was uploaded
+struct PendingStreamedImage {
+    image_data: GpuImageData,
+    resource_type: RafxResourceType,
+    resident_first_mip: u32,
+}
+
+// A texture with more mip levels than the resident one, bound in its place
+struct StreamedTexture {
+    image_view: ResourceArc<ImageViewResource>,
+    first_mip: u32,
+    size: u64,
+}
+
+struct StreamedImage {
+    // Every mip level, the GPU only has the ones starting at the current first mip
+    image_data: GpuImageData,
+    resource_type: RafxResourceType,
+    resident_first_mip: u32,
+    current: Option<StreamedTexture>,
+    requested_first_mip: u32,
+    last_requested_frame: u64,
+    // Set if an upload of this image could not be started, it stays at its current resolution
+    failed: bool,
+}
+
+impl StreamedImage {
+    fn current_first_mip(&self) -> u32 {
+        self.current
+            .as_ref()
+            .map(|x| x.first_mip)
+            .unwrap_or(self.resident_first_mip)
+    }
+
+    fn current_size(&self) -> u64 {
+        self.current.as_ref().map(|x| x.size).unwrap_or(0)
+    }
+
+    fn first_mip_for_demand(
+        &self,
+        texels_per_uv: f32,
+    ) -> u32 {
+        if texels_per_uv <= 0.0 {
+            return self.resident_first_mip;
+        }
+
+        let largest_dimension = self.image_data.width.max(self.image_data.height) as f32;
+        let first_mip = (largest_dimension / texels_per_uv).log2().floor().max(0.0) as u32;
+        first_mip.min(self.resident_first_mip)
+    }
+
+    // Size of the texture that starts at the given mip level
+    fn size_from_mip(
+        &self,
+        first_mip: u32,
+    ) -> u64 {
+        self.image_data
+            .layers
+            .iter()
+            .flat_map(|layer| &layer.mip_levels[first_mip as usize..])
+            .map(|level| {
+                rafx_base::memory::round_size_up_to_alignment_u64(
+                    level.data.len() as u64,
+                    IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT,
+                )
+            })
+            .sum()
+    }
+}
+
+// The first mip level that is uploaded when the image loads and never evicted. The smallest mip
+// level is resident if every level is larger than resident_mip_size.
+fn resident_first_mip(
+    image_data: &GpuImageData,
+    resident_mip_size: u32,
+) -> usize {
+    let mip_levels = &image_data.layers[0].mip_levels;
+    mip_levels
+        .iter()
+        .position(|level| level.width.max(level.height) <= resident_mip_size)
+        .unwrap_or(mip_levels.len() - 1)
+}
+
+// An image that has mip levels above its resident ones, which could be evicted to free memory
+struct EvictionCandidate<KeyT> {
+    key: KeyT,
+    last_requested_frame: u64,
+    requested_first_mip: u32,
+    size: u64,
+}
+
+// Returns the images to evict to get the streamed bytes under budget. The least recently requested
+// images go first, and of those the ones requested at the lowest resolution. Bytes of in flight
+// uploads can't be evicted, so the budget may still be exceeded after evicting every candidate.
+fn select_evictions<KeyT>(
+    mut candidates: Vec<EvictionCandidate<KeyT>>,
+    mut streamed_bytes: u64,
+    memory_budget_bytes: u64,
+) -> Vec<KeyT> {
+    candidates.sort_by_key(|candidate| {
+        (
+            candidate.last_requested_frame,
+            std::cmp::Reverse(candidate.requested_first_mip),
+        )
+    });
+
+    let mut evicted = Vec::default();
+    for candidate in candidates {
+        if streamed_bytes <= memory_budget_bytes {
+            break;
+        }
+
+        streamed_bytes -= candidate.size;
+        evicted.push(candidate.key);
+    }
+
+    evicted
+}
+
+// A material instance that binds streamed images. Its descriptor sets are recreated from the writes
+// it was loaded with, substituting the current texture of each streamed image.
+struct StreamedMaterialInstance {
+    descriptor_sets: Weak<MaterialInstanceDescriptorSets>,
+    // Indexed by pass, then by set within the pass
+    descriptor_set_layouts: Vec<Arc<Vec<ResourceArc<DescriptorSetLayoutResource>>>>,
+    descriptor_set_writes: Vec<Vec<DescriptorSetWriteSet>>,
+}
+
+struct InFlightStreamingUpload {
+    image: WeakResourceArc<ImageViewResource>,
+    first_mip: u32,
+    size: u64,
+    texture: RafxTexture,
+    upload: RafxTransferUpload,
+}
+
+enum StreamingUploadPollResult {
+    Pending,
+    Complete,
+    Error,
+}
+
+impl InFlightStreamingUpload {
+    fn poll(&mut self) -> StreamingUploadPollResult {
+        loop {
+            let result = match self.upload.state() {
+                Ok(RafxTransferUploadState::Writable) => self.upload.submit_transfer(),
+                Ok(RafxTransferUploadState::PendingSubmitDstQueue) => self.upload.submit_dst(),
+                Ok(RafxTransferUploadState::SentToTransferQueue)
+                | Ok(RafxTransferUploadState::SentToDstQueue) => {
+                    break StreamingUploadPollResult::Pending
+                }
+                Ok(RafxTransferUploadState::Complete) => break StreamingUploadPollResult::Complete,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                log::error!("Texture streaming upload failed: {:?}", err);
+                break StreamingUploadPollResult::Error;
+            }
+        }
+    }
+}
+
+//
+// Uploads the smallest mip levels of an image when it loads and streams larger ones in as they are
+// requested through TextureStreamingFeedback. A streamed image is bound through the view created
+// when it loaded, descriptor sets of material instances are recreated with the larger texture when
+// it arrives or is evicted.
+//
+pub struct TextureStreaming {
+    config: TextureStreamingConfig,
+    device_context: RafxDeviceContext,
+    graphics_queue: RafxQueue,
+    transfer_queue: RafxQueue,
+
+    feedback: TextureStreamingFeedback,
+
+    // Images that are uploading their resident mip levels
+    pending_images: FnvHashMap<LoadHandle, PendingStreamedImage>,
+    // Keyed by the view created when the image loaded
+    images: FnvHashMap<WeakResourceArc<ImageViewResource>, StreamedImage>,
+    material_instances: Vec<StreamedMaterialInstance>,
+
+    in_flight_uploads: Vec<InFlightStreamingUpload>,
+    // Uploads that finished, kept to reuse their staging buffers
+    free_uploads: Vec<RafxTransferUpload>,
+
+    frame_index: u64,
+    completed_upload_count: usize,
+    evicted_count: usize,
+}
+
+impl TextureStreaming {
+    pub fn new(
+        device_context: &RafxDeviceContext,
+        config: TextureStreamingConfig,
+        graphics_queue: &RafxQueue,
+        transfer_queue: &RafxQueue,
+    ) -> Self {
+        TextureStreaming {
+            config,
+            device_context: device_context.clone(),
+            graphics_queue: graphics_queue.clone(),
+            transfer_queue: transfer_queue.clone(),
+            feedback: Default::default(),
+            pending_images: Default::default(),
+            images: Default::default(),
+            material_instances: Default::default(),
+            in_flight_uploads: Default::default(),
+            free_uploads: Default::default(),
+            frame_index: 0,
+            completed_upload_count: 0,
+            evicted_count: 0,
+        }
+    }
+
+    pub fn config(&self) -> &TextureStreamingConfig {
+        &self.config
+    }
+
+    // Images that already loaded keep the resident mip levels they were split at
+    pub fn set_config(
+        &mut self,
+        config: TextureStreamingConfig,
+    ) {
+        self.config = config;
+        self.free_uploads
+            .truncate(self.config.max_uploads_in_flight);
+    }
+
+    pub fn feedback(&self) -> &TextureStreamingFeedback {
+        &self.feedback
+    }
+
+    pub fn is_streamed(
+        &self,
+        image_view: &ResourceArc<ImageViewResource>,
+    ) -> bool {
+        self.images.contains_key(&image_view.downgrade())
+    }
+
+    pub fn metrics(&self) -> TextureStreamingMetrics {
+        TextureStreamingMetrics {
+            streamed_image_count: self.images.len(),
+            upgraded_image_count: self.images.values().filter(|x| x.current.is_some()).count(),
+            streamed_bytes: self.streamed_bytes(),
+            memory_budget_bytes: self.config.memory_budget_bytes,
+            uploads_in_flight: self.in_flight_uploads.len(),
+            completed_upload_count: self.completed_upload_count,
+            evicted_count: self.evicted_count,
+        }
+    }
+
+    // Returns the image data to upload when the image loads. If the image is streamed, that is only
+    // its resident mip levels and the rest is kept until the image is registered.
+    pub(crate) fn split_streamed_image(
+        &mut self,
+        load_handle: LoadHandle,
+        image_data: GpuImageData,
+        resource_type: RafxResourceType,
+    ) -> GpuImageData {
+        self.pending_images.remove(&load_handle);
+        if !self.config.enabled {
+            return image_data;
+        }
+
+        let resident_first_mip = resident_first_mip(&image_data, self.config.resident_mip_size);
+        if resident_first_mip == 0 {
+            return image_data;
+        }
+
+        log::trace!(
+            "Streaming image {:?} {}x{}, {} mip levels held back",
+            load_handle,
+            image_data.width,
+            image_data.height,
+            resident_first_mip
+        );
+
+        let resident_image_data = image_data.copy_mip_levels_from(resident_first_mip);
+        self.pending_images.insert(
+            load_handle,
+            PendingStreamedImage {
+                image_data,
+                resource_type,
+                resident_first_mip: resident_first_mip as u32,
+            },
+        );
+
+        resident_image_data
+    }
+
+    pub(crate) fn remove_pending_image(
+        &mut self,
+        load_handle: LoadHandle,
+    ) {
+        self.pending_images.remove(&load_handle);
+    }
+
+    // Called with the view of the resident mip levels once they are uploaded
+    pub(crate) fn register_image(
+        &mut self,
+        load_handle: LoadHandle,
+        image_view: &ResourceArc<ImageViewResource>,
+    ) {
+        if let Some(pending_image) = self.pending_images.remove(&load_handle) {
+            self.images.insert(
+                image_view.downgrade(),
+                StreamedImage {
+                    image_data: pending_image.image_data,
+                    resource_type: pending_image.resource_type,
+                    resident_first_mip: pending_image.resident_first_mip,
+                    current: None,
+                    requested_first_mip: pending_image.resident_first_mip,
+                    last_requested_frame: self.frame_index,
+                    failed: false,
+                },
+            );
+        }
+    }
+
+    pub(crate) fn find_streamed_image_views(
+        &self,
+        descriptor_set_writes: &[Vec<DescriptorSetWriteSet>],
+    ) -> Vec<WeakResourceArc<ImageViewResource>> {
+        let mut streamed_image_views = Vec::default();
+        for write_set in descriptor_set_writes.iter().flatten() {
+            for element in write_set.elements.values() {
+                if let Some(DescriptorSetWriteElementImageValue::Resource(image_view)) =
+                    &element.image_info.image_view
+                {
+                    let image_view = image_view.downgrade();
+                    if self.images.contains_key(&image_view)
+                        && !streamed_image_views.contains(&image_view)
+                    {
+                        streamed_image_views.push(image_view);
+                    }
+                }
+            }
+        }
+
+        streamed_image_views
+    }
+
+    pub(crate) fn register_material_instance(
+        &mut self,
+        descriptor_sets: &Arc<MaterialInstanceDescriptorSets>,
+        descriptor_set_layouts: Vec<Arc<Vec<ResourceArc<DescriptorSetLayoutResource>>>>,
+        descriptor_set_writes: Vec<Vec<DescriptorSetWriteSet>>,
+    ) {
+        self.material_instances.push(StreamedMaterialInstance {
+            descriptor_sets: Arc::downgrade(descriptor_sets),
+            descriptor_set_layouts,
+            descriptor_set_writes,
+        });
+    }
+
+    fn streamed_bytes(&self) -> u64 {
+        let in_flight_bytes = self.in_flight_uploads.iter().map(|x| x.size).sum::<u64>();
+        self.images.values().map(|x| x.current_size()).sum::<u64>() + in_flight_bytes
+    }
+
+    #[profiling::function]
+    pub(crate) fn update(
+        &mut self,
+        resources: &ResourceLookupSet,
+        descriptor_set_allocator: &mut DescriptorSetAllocator,
+    ) -> RafxResult<()> {
+        self.frame_index += 1;
+
+        // Forget images and material instances that were freed
+        self.images
+            .retain(|image_view, _| image_view.upgrade().is_some());
+        self.material_instances
+            .retain(|x| x.descriptor_sets.strong_count() > 0);
+
+        if self.images.is_empty() && self.in_flight_uploads.is_empty() {
+            self.feedback.take_requests();
+            return Ok(());
+        }
+
+        self.apply_feedback();
+
+        let mut changed_images = Vec::default();
+        self.poll_in_flight_uploads(resources, &mut changed_images)?;
+        self.evict(&mut changed_images);
+
+        if !changed_images.is_empty() {
+            self.update_material_instances(&changed_images, descriptor_set_allocator)?;
+        }
+
+        while self.in_flight_uploads.len() < self.config.max_uploads_in_flight {
+            if !self.start_next_upload() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_feedback(&mut self) {
+        let frame_index = self.frame_index;
+        let eviction_delay_frames = self.config.eviction_delay_frames;
+
+        for (image_view, texels_per_uv) in self.feedback.take_requests() {
+            if let Some(image) = self.images.get_mut(&image_view) {
+                let first_mip = image.first_mip_for_demand(texels_per_uv);
+                // Raise resolution right away but only lower it once the higher resolution hasn't
+                // been requested for a while
+                if first_mip <= image.requested_first_mip
+                    || frame_index - image.last_requested_frame > eviction_delay_frames
+                {
+                    image.requested_first_mip = first_mip;
+                    image.last_requested_frame = frame_index;
+                }
+            }
+        }
+
+        for image in self.images.values_mut() {
+            if frame_index - image.last_requested_frame > eviction_delay_frames {
+                image.requested_first_mip = image.resident_first_mip;
+            }
+        }
+    }
+
+    fn poll_in_flight_uploads(
+        &mut self,
+        resources: &ResourceLookupSet,
+        changed_images: &mut Vec<WeakResourceArc<ImageViewResource>>,
+    ) -> RafxResult<()> {
+        let mut upload_index = 0;
+        while upload_index < self.in_flight_uploads.len() {
+            let result = self.in_flight_uploads[upload_index].poll();
+            if let StreamingUploadPollResult::Pending = result {
+                upload_index += 1;
+                continue;
+            }
+
+            let InFlightStreamingUpload {
+                image: image_view,
+                first_mip,
+                size,
+                texture,
+                upload,
+            } = self.in_flight_uploads.swap_remove(upload_index);
+
+            match result {
+                StreamingUploadPollResult::Pending => unreachable!(),
+                StreamingUploadPollResult::Error => {
+                    if let Some(image) = self.images.get_mut(&image_view) {
+                        image.failed = true;
+                    }
+                }
+                StreamingUploadPollResult::Complete => {
+                    self.completed_upload_count += 1;
+                    self.free_upload(upload);
+
+                    // The image may have been freed while uploading, the texture is dropped with it
+                    if let Some(image) = self.images.get_mut(&image_view) {
+                        log::trace!(
+                            "Streamed image {}x{} in at mip {}",
+                            image.image_data.width,
+                            image.image_data.height,
+                            first_mip
+                        );
+
+                        let image_resource = resources.insert_image(texture);
+                        let image_view_resource =
+                            resources.get_or_create_image_view(&image_resource, None)?;
+                        image.current = Some(StreamedTexture {
+                            image_view: image_view_resource,
+                            first_mip,
+                            size,
+                        });
+                        changed_images.push(image_view);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Keeps the staging buffer of a finished upload for the next one
+    fn free_upload(
+        &mut self,
+        mut upload: RafxTransferUpload,
+    ) {
+        if self.free_uploads.len() >= self.config.max_uploads_in_flight {
+            return;
+        }
+
+        match upload.reset() {
+            Ok(()) => self.free_uploads.push(upload),
+            Err(err) => log::warn!("Could not reuse texture streaming upload: {:?}", err),
+        }
+    }
+
+    fn evict(
+        &mut self,
+        changed_images: &mut Vec<WeakResourceArc<ImageViewResource>>,
+    ) {
+        // Images that are no longer requested above their resident mip levels
+        for (image_view, image) in &mut self.images {
+            if image.current.is_some() && image.requested_first_mip >= image.resident_first_mip {
+                image.current = None;
+                self.evicted_count += 1;
+                changed_images.push(image_view.clone());
+            }
+        }
+
+        // Then the least recently requested images until under budget
+        let candidates = self
+            .images
+            .iter()
+            .filter(|(_, image)| image.current.is_some())
+            .map(|(image_view, image)| EvictionCandidate {
+                key: image_view.clone(),
+                last_requested_frame: image.last_requested_frame,
+                requested_first_mip: image.requested_first_mip,
+                size: image.current_size(),
+            })
+            .collect();
+
+        for image_view in select_evictions(
+            candidates,
+            self.streamed_bytes(),
+            self.config.memory_budget_bytes,
+        ) {
+            if let Some(image) = self.images.get_mut(&image_view) {
+                image.current = None;
+                self.evicted_count += 1;
+                changed_images.push(image_view);
+            }
+        }
+    }
+
+    // Recreates the descriptor sets of material instances that bind the changed images
+    fn update_material_instances(
+        &self,
+        changed_images: &[WeakResourceArc<ImageViewResource>],
+        descriptor_set_allocator: &mut DescriptorSetAllocator,
+    ) -> RafxResult<()> {
+        for material_instance in &self.material_instances {
+            let descriptor_sets = match material_instance.descriptor_sets.upgrade() {
+                Some(descriptor_sets) => descriptor_sets,
+                None => continue,
+            };
+
+            if !descriptor_sets
+                .streamed_image_views()
+                .iter()
+                .any(|x| changed_images.contains(x))
+            {
+                continue;
+            }
+
+            let material_descriptor_sets = self.create_material_descriptor_sets(
+                descriptor_set_allocator,
+                &material_instance.descriptor_set_layouts,
+                &material_instance.descriptor_set_writes,
+            )?;
+
+            descriptor_sets.set(material_descriptor_sets);
+        }
+
+        Ok(())
+    }
+
+    // Creates the descriptor sets of a material instance from its writes, binding the current
+    // texture of each streamed image. Layouts with no writes are assumed to not exist.
+    pub(crate) fn create_material_descriptor_sets(
+        &self,
+        descriptor_set_allocator: &mut DescriptorSetAllocator,
+        descriptor_set_layouts: &[Arc<Vec<ResourceArc<DescriptorSetLayoutResource>>>],
+        descriptor_set_writes: &[Vec<DescriptorSetWriteSet>],
+    ) -> RafxResult<Vec<Vec<Option<DescriptorSetArc>>>> {
+        let mut material_descriptor_sets = Vec::with_capacity(descriptor_set_writes.len());
+        for (pass_writes, pass_layouts) in descriptor_set_writes.iter().zip(descriptor_set_layouts)
+        {
+            let mut pass_descriptor_sets = Vec::with_capacity(pass_writes.len());
+            for (layout_index, layout_writes) in pass_writes.iter().enumerate() {
+                if layout_writes.elements.is_empty() {
+                    pass_descriptor_sets.push(None);
+                    continue;
+                }
+
+                let mut layout_writes = layout_writes.clone();
+                for element in layout_writes.elements.values_mut() {
+                    if let Some(DescriptorSetWriteElementImageValue::Resource(image_view)) =
+                        &mut element.image_info.image_view
+                    {
+                        if let Some(current) = self
+                            .images
+                            .get(&image_view.downgrade())
+                            .and_then(|x| x.current.as_ref())
+                        {
+                            *image_view = current.image_view.clone();
+                        }
+                    }
+                }
+
+                let descriptor_set = descriptor_set_allocator.create_descriptor_set_with_writes(
+                    &pass_layouts[layout_index],
+                    layout_writes,
+                )?;
+                pass_descriptor_sets.push(Some(descriptor_set));
+            }
+
+            material_descriptor_sets.push(pass_descriptor_sets);
+        }
+
+        Ok(material_descriptor_sets)
+    }
+
+    // Returns false if no other upload can be started this frame
+    fn start_next_upload(&mut self) -> bool {
+        let memory_budget_bytes = self.config.memory_budget_bytes;
+        let max_bytes_per_upload = self.config.max_bytes_per_upload;
+        let streamed_bytes = self.streamed_bytes();
+
+        // Prefer the most recently requested image that is furthest from its requested resolution.
+        // Images that are requested at a lower resolution than they have are shrunk after that.
+        let in_flight_uploads = &self.in_flight_uploads;
+        let next = self
+            .images
+            .iter()
+            .filter(|(_, image)| !image.failed)
+            .filter(|(image_view, _)| !in_flight_uploads.iter().any(|x| x.image == **image_view))
+            .filter(|(_, image)| image.requested_first_mip != image.current_first_mip())
+            .max_by_key(|(_, image)| {
+                let is_upgrade = image.requested_first_mip < image.current_first_mip();
+                let mip_difference =
+                    (image.current_first_mip() as i32 - image.requested_first_mip as i32).abs();
+                (is_upgrade, image.last_requested_frame, mip_difference)
+            })
+            .map(|(image_view, _)| image_view.clone());
+
+        let image_view = match next {
+            Some(image_view) => image_view,
+            None => return false,
+        };
+
+        let device_context = &self.device_context;
+        let graphics_queue = &self.graphics_queue;
+        let transfer_queue = &self.transfer_queue;
+        let image = self.images.get_mut(&image_view).unwrap();
+
+        // Lower the resolution until the texture fits in the budget and the staging buffer. The
+        // texture it replaces is freed once the new one arrives.
+        let available_bytes =
+            memory_budget_bytes.saturating_sub(streamed_bytes - image.current_size());
+        let mut first_mip = image.requested_first_mip;
+        while first_mip < image.resident_first_mip {
+            let size = image.size_from_mip(first_mip);
+            if size <= available_bytes && size <= max_bytes_per_upload {
+                break;
+            }
+            first_mip += 1;
+        }
+
+        if first_mip >= image.current_first_mip()
+            && image.requested_first_mip < image.current_first_mip()
+        {
+            // Can't raise the resolution without evicting something more recently requested
+            return false;
+        }
+
+        if first_mip >= image.resident_first_mip {
+            // Shrinking to the resident mip levels doesn't need an upload
+            image.requested_first_mip = image.resident_first_mip;
+            return true;
+        }
+
+        let image_data = image.image_data.copy_mip_levels_from(first_mip as usize);
+        let params = ImageUploadParams {
+            resource_type: image.resource_type,
+            ..Default::default()
+        };
+
+        let size = crate::image_upload::image_upload_size(&image_data, &params);
+        let required_size = size + IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT;
+
+        // Reuse the staging buffer of a finished upload. New ones are made large enough for any
+        // image so they can be reused.
+        let upload = match self.free_uploads.pop() {
+            Some(upload) if upload.buffer_size() >= required_size => Ok(upload),
+            _ => RafxTransferUpload::new(
+                device_context,
+                transfer_queue,
+                graphics_queue,
+                required_size
+                    .max(max_bytes_per_upload + IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT),
+            ),
+        };
+
+        let result = upload
+            .map_err(RafxUploadError::from)
+            .and_then(|mut upload| {
+                let texture = crate::image_upload::enqueue_load_image(
+                    device_context,
+                    &mut upload,
+                    &image_data,
+                    params,
+                )?;
+                upload.submit_transfer()?;
+                Ok((upload, texture))
+            });
+
+        match result {
+            Ok((upload, texture)) => {
+                log::trace!(
+                    "Streaming image {}x{} at mip {} ({} bytes)",
+                    image.image_data.width,
+                    image.image_data.height,
+                    first_mip,
+                    size
+                );
+
+                self.in_flight_uploads.push(InFlightStreamingUpload {
+                    image: image_view,
+                    first_mip,
+                    size,
+                    texture,
+                    upload,
+                });
+            }
+            Err(err) => {
+                log::error!("Could not start texture streaming upload: {}", err);
+                image.failed = true;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{GpuImageDataLayer, GpuImageDataMipLevel};
+    use rafx_api::RafxFormat;
+
+    // An RGBA8 image with the given number of mip levels
+    fn image_data(
+        width: u32,
+        height: u32,
+        mip_count: u32,
+    ) -> GpuImageData {
+        let mip_levels = (0..mip_count)
+            .map(|mip| {
+                let width = (width >> mip).max(1);
+                let height = (height >> mip).max(1);
+                GpuImageDataMipLevel {
+                    width,
+                    height,
+                    data: vec![0; (width * height * 4) as usize],
+                }
+            })
+            .collect();
+
+        GpuImageData::new(
+            vec![GpuImageDataLayer::new(mip_levels)],
+            RafxFormat::R8G8B8A8_UNORM,
+        )
+    }
+
+    fn streamed_image(
+        image_data: GpuImageData,
+        resident_first_mip: u32,
+    ) -> StreamedImage {
+        StreamedImage {
+            image_data,
+            resource_type: RafxResourceType::TEXTURE,
+            resident_first_mip,
+            current: None,
+            requested_first_mip: resident_first_mip,
+            last_requested_frame: 0,
+            failed: false,
+        }
+    }
+
+    fn eviction_candidate(
+        key: &'static str,
+        last_requested_frame: u64,
+        requested_first_mip: u32,
+    ) -> EvictionCandidate<&'static str> {
+        EvictionCandidate {
+            key,
+            last_requested_frame,
+            requested_first_mip,
+            size: 100,
+        }
+    }
+
+    #[test]
+    fn test_resident_first_mip() {
+        // 1024x512 has mip levels 1024, 512, 256, 128, ...
+        let full_chain = image_data(1024, 512, 11);
+        assert_eq!(resident_first_mip(&full_chain, 128), 3);
+        assert_eq!(resident_first_mip(&full_chain, 200), 3);
+        assert_eq!(resident_first_mip(&full_chain, 256), 2);
+
+        // Small enough to be resident entirely, so it isn't streamed
+        assert_eq!(resident_first_mip(&full_chain, 1024), 0);
+
+        // Without a small enough mip level, the smallest one is resident
+        let partial_chain = image_data(1024, 1024, 3);
+        assert_eq!(resident_first_mip(&partial_chain, 128), 2);
+
+        // Without mip levels, nothing can be held back
+        let single_level = image_data(1024, 1024, 1);
+        assert_eq!(resident_first_mip(&single_level, 128), 0);
+    }
+
+    #[test]
+    fn test_first_mip_for_demand() {
+        let image = streamed_image(image_data(1024, 512, 11), 3);
+
+        // Not requested, so only the resident mip levels are needed
+        assert_eq!(image.first_mip_for_demand(0.0), 3);
+        assert_eq!(image.first_mip_for_demand(-1.0), 3);
+
+        // One texel per pixel along the largest dimension
+        assert_eq!(image.first_mip_for_demand(1024.0), 0);
+        assert_eq!(image.first_mip_for_demand(512.0), 1);
+        assert_eq!(image.first_mip_for_demand(256.0), 2);
+
+        // Demand between two mip levels picks the larger one
+        assert_eq!(image.first_mip_for_demand(700.0), 0);
+        assert_eq!(image.first_mip_for_demand(300.0), 1);
+
+        // Beyond the full resolution
+        assert_eq!(image.first_mip_for_demand(4096.0), 0);
+
+        // Never lower than the resident mip levels
+        assert_eq!(image.first_mip_for_demand(64.0), 3);
+        assert_eq!(image.first_mip_for_demand(1.0), 3);
+    }
+
+    #[test]
+    fn test_select_evictions() {
+        let candidates = || {
+            vec![
+                eviction_candidate("recent", 10, 0),
+                eviction_candidate("old_high_resolution", 5, 0),
+                eviction_candidate("old_low_resolution", 5, 2),
+                eviction_candidate("newest", 20, 1),
+            ]
+        };
+
+        // Under budget
+        assert!(select_evictions(candidates(), 400, 400).is_empty());
+
+        // The least recently requested go first, the lower resolution one before the other
+        assert_eq!(
+            select_evictions(candidates(), 400, 250),
+            vec!["old_low_resolution", "old_high_resolution"]
+        );
+        assert_eq!(
+            select_evictions(candidates(), 400, 399),
+            vec!["old_low_resolution"]
+        );
+
+        // Everything is evicted if in flight uploads keep it over budget
+        assert_eq!(
+            select_evictions(candidates(), 500, 50),
+            vec![
+                "old_low_resolution",
+                "old_high_resolution",
+                "recent",
+                "newest"
+            ]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use rafx_framework::ResourceArc;
 use rafx_framework::{DescriptorSetAllocator, RafxResult};
 use rafx_framework::{DescriptorSetBindings, ImageViewResource};
-use rafx_framework::{DynDescriptorSet, SlotNameLookup};
+use rafx_framework::{DynDescriptorSet, SlotNameLookup, UniformParamValue};
 use std::sync::Arc;
 
 pub struct DynPassMaterialInstance {
@@ -72,6 +72,27 @@ impl DynPassMaterialInstance {
             }
         }
     }
+
+    /// Sets a member of a uniform buffer by name, i.e. "base_color_factor" or "light.range". See
+    /// DynDescriptorSet::set_uniform_param
+    pub fn set_uniform_param(
+        &mut self,
+        path: &str,
+        value: &UniformParamValue,
+    ) -> RafxResult<()> {
+        let mut errors = Vec::default();
+        for dyn_descriptor_set in &mut self.descriptor_sets {
+            match dyn_descriptor_set.set_uniform_param(path, value) {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        Err(format!(
+            "Could not set uniform param {} in material pass: {:?}",
+            path, errors
+        ))?
+    }
 }
 
 pub struct DynMaterialInstance {
@@ -121,4 +142,25 @@ impl DynMaterialInstance {
             pass.set_buffer_data(slot_name, data)
         }
     }
+
+    /// Sets a member of a uniform buffer by name in every pass that has it. Fails if no pass does.
+    pub fn set_uniform_param(
+        &mut self,
+        path: &str,
+        value: &UniformParamValue,
+    ) -> RafxResult<()> {
+        let mut any_set = false;
+        let mut last_error = None;
+        for pass in &mut self.passes {
+            match pass.set_uniform_param(path, value) {
+                Ok(()) => any_set = true,
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match last_error {
+            Some(e) if !any_set => Err(e),
+            _ => Ok(()),
+        }
+    }
 }
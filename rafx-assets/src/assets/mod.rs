@@ -1,14 +1,36 @@
 mod image;
+pub use self::image::cube_face_direction;
+pub use self::image::image_asset_data_from_dds;
+pub use self::image::image_asset_data_from_ktx2;
 #[cfg(feature = "basis-universal")]
 pub use self::image::BasisImageImporter;
+pub use self::image::CubemapFaces;
+pub use self::image::CubemapImporter;
+pub use self::image::CubemapImporterOptions;
+pub use self::image::CubemapSource;
+pub use self::image::DdsImageImporter;
+#[cfg(feature = "exr")]
+pub use self::image::ExrImageImporter;
+pub use self::image::HdrImageImporter;
+pub use self::image::HdrImageImporterOptions;
 pub use self::image::ImageAsset;
 pub use self::image::ImageAssetBasisCompressionSettings;
 pub use self::image::ImageAssetBasisCompressionType;
 pub use self::image::ImageAssetColorSpace;
 pub use self::image::ImageAssetData;
 pub use self::image::ImageAssetDataFormat;
+pub use self::image::ImageAssetDataFormatConfig;
+pub use self::image::ImageAssetMipFilter;
 pub use self::image::ImageAssetMipGeneration;
+pub use self::image::ImageAssetMipSettings;
 pub use self::image::ImageImporter;
+pub use self::image::ImageImporterNormalMapConvention;
+pub use self::image::ImageImporterOptions;
+pub use self::image::Ktx2ImageImporter;
+pub use self::image::TextureStreaming;
+pub use self::image::TextureStreamingConfig;
+pub use self::image::TextureStreamingFeedback;
+pub use self::image::TextureStreamingMetrics;
 
 mod shader;
 pub use shader::ShaderAsset;
@@ -23,9 +45,12 @@ pub use graphics_pipeline::MaterialAssetData;
 pub use graphics_pipeline::MaterialImporter;
 pub use graphics_pipeline::MaterialInstanceAsset;
 pub use graphics_pipeline::MaterialInstanceAssetData;
+pub use graphics_pipeline::MaterialInstanceDescriptorSets;
 pub use graphics_pipeline::MaterialInstanceImporter;
 pub use graphics_pipeline::MaterialInstanceSlotAssignment;
+pub use graphics_pipeline::MaterialInstanceUniformParam;
 pub use graphics_pipeline::MaterialPassData;
+pub use graphics_pipeline::MaterialPassVariant;
 pub use graphics_pipeline::SamplerAsset;
 pub use graphics_pipeline::SamplerAssetData;
 pub use graphics_pipeline::SamplerImporter;
@@ -35,6 +60,20 @@ pub use compute_pipeline::ComputePipelineAsset;
 pub use compute_pipeline::ComputePipelineAssetData;
 pub use compute_pipeline::ComputePipelineImporter;
 
+mod render_graph;
+pub use render_graph::RenderGraphAsset;
+pub use render_graph::RenderGraphAssetAttachment;
+pub use render_graph::RenderGraphAssetBinding;
+pub use render_graph::RenderGraphAssetClearValue;
+pub use render_graph::RenderGraphAssetData;
+pub use render_graph::RenderGraphAssetImageExtents;
+pub use render_graph::RenderGraphAssetImageFormat;
+pub use render_graph::RenderGraphAssetNode;
+pub use render_graph::RenderGraphAssetNodeAction;
+pub use render_graph::RenderGraphAssetSampledImage;
+pub use render_graph::RenderGraphAssetStorageBuffer;
+pub use render_graph::RenderGraphImporter;
+
 mod buffer;
 pub use buffer::BufferAsset;
 pub use buffer::BufferAssetData;
@@ -49,7 +88,10 @@ pub use asset_manager_render_resource::AssetManagerExtractRef;
 pub use asset_manager_render_resource::AssetManagerRenderResource;
 
 mod upload;
-pub use upload::UploadQueueConfig;
+pub use upload::{UploadQueueConfig, UploadQueueMetrics};
+
+mod upload_schedule;
+pub use upload_schedule::UploadPriority;
 
 mod asset_lookup;
 pub use asset_lookup::AssetLookup;
@@ -69,3 +111,7 @@ pub use load_queue::LoadQueues;
 mod material_descriptor_sets;
 pub use material_descriptor_sets::DynMaterialInstance;
 pub use material_descriptor_sets::DynPassMaterialInstance;
+
+mod derived_material_instance;
+pub use derived_material_instance::DerivedMaterialInstance;
+pub use derived_material_instance::MaterialInstanceOverrides;
@@ -0,0 +1,581 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::*;
+
+use crate::{
+    AssetManager, ComputePipelineAsset, DefaultAssetTypeHandler, DefaultAssetTypeLoadHandler,
+    MaterialAsset,
+};
+use distill::loader::handle::Handle;
+use fnv::{FnvHashMap, FnvHashSet};
+use rafx_api::{RafxFormat, RafxResult, RafxSampleCount};
+use rafx_framework::render_features::RenderRegistry;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// The format of an image created by a render graph asset. Most images use one of the formats
+/// chosen for the current swapchain so that the asset works on any device.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum RenderGraphAssetImageFormat {
+    Swapchain,
+    DefaultColorSdr,
+    DefaultColorHdr,
+    DefaultDepth,
+    Explicit(RafxFormat),
+}
+
+/// The size of an image created by a render graph asset
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum RenderGraphAssetImageExtents {
+    MatchSurface,
+    // Multiplier applied to the width and height of the surface
+    ScaledSurface(f32),
+    // (width, height, depth)
+    Custom(u32, u32, u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum RenderGraphAssetClearValue {
+    Color([f32; 4]),
+    // (depth, stencil)
+    DepthStencil(f32, u32),
+}
+
+/// Where a resource is bound when the node's callback runs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RenderGraphAssetBinding {
+    // A slot name in the material. Only valid for nodes that draw a fullscreen material
+    Slot(String),
+    // (descriptor set index, binding index)
+    Binding(u32, u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenderGraphAssetAttachment {
+    /// The name later nodes use to refer to the image written by this attachment
+    pub name: String,
+    /// An image written by a previous node that this attachment loads and modifies. If None, a new
+    /// image is created.
+    #[serde(default)]
+    pub input: Option<String>,
+    /// An image written by a previous node that is used under this attachment's name when the node
+    /// is disabled. Attachments with an input pass the input through instead.
+    #[serde(default)]
+    pub fallback: Option<String>,
+    #[serde(default)]
+    pub clear_value: Option<RenderGraphAssetClearValue>,
+    #[serde(default)]
+    pub format: Option<RenderGraphAssetImageFormat>,
+    #[serde(default)]
+    pub samples: Option<RafxSampleCount>,
+    #[serde(default)]
+    pub extents: Option<RenderGraphAssetImageExtents>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenderGraphAssetSampledImage {
+    /// An image, or the images drawn by a DrawViews node
+    pub image: String,
+    /// If None, the node depends on the image but does not bind it. This is used for images that a
+    /// render feature binds itself, like shadow maps. Images drawn by a DrawViews node can't be
+    /// bound.
+    #[serde(default)]
+    pub binding: Option<RenderGraphAssetBinding>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenderGraphAssetStorageBuffer {
+    /// The name later nodes use to refer to the buffer written by this node
+    pub name: String,
+    /// A buffer written by a previous node that this node modifies. If None, a new buffer is
+    /// created.
+    #[serde(default)]
+    pub input: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    pub binding: RenderGraphAssetBinding,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RenderGraphAssetNodeAction {
+    /// Draw everything in the named render phase for the main view
+    DrawPhase(String),
+    /// Draw everything in the named render phase for each view of the view provider registered
+    /// under the given name (e.g. shadow maps). Each image of the provider is created with the
+    /// node's depth attachment and has one layer per view. Later nodes refer to all of the images
+    /// by the depth attachment's name.
+    DrawViews { phase: String, views: String },
+    /// Draw a fullscreen triangle with the material pass registered for the named render phase
+    FullscreenMaterial {
+        material: Handle<MaterialAsset>,
+        phase: String,
+    },
+    /// Dispatch a compute pipeline
+    Compute {
+        pipeline: Handle<ComputePipelineAsset>,
+        group_count: [u32; 3],
+    },
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenderGraphAssetNode {
+    pub name: String,
+    /// Disabled nodes are skipped. Images they would have written are replaced by the attachment's
+    /// input or fallback.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub color_attachments: Vec<RenderGraphAssetAttachment>,
+    #[serde(default)]
+    pub depth_attachment: Option<RenderGraphAssetAttachment>,
+    #[serde(default)]
+    pub sampled_images: Vec<RenderGraphAssetSampledImage>,
+    #[serde(default)]
+    pub storage_buffers: Vec<RenderGraphAssetStorageBuffer>,
+    pub action: RenderGraphAssetNodeAction,
+}
+
+/// A render graph described by data rather than code. Nodes are listed in the order they are
+/// added to the graph and refer to images and buffers written by earlier nodes by name. The image
+/// named by output_image is presented to the swapchain.
+#[derive(TypeUuid, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[uuid = "32cfb93e-b819-4c24-a1db-7c2e4ba98ffe"]
+pub struct RenderGraphAssetData {
+    pub nodes: Vec<RenderGraphAssetNode>,
+    pub output_image: String,
+}
+
+fn require_resource(
+    available: &FnvHashSet<&str>,
+    node: &str,
+    kind: &str,
+    name: &str,
+) -> RafxResult<()> {
+    if available.contains(name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Render graph node {} refers to {} {} which is not written by an earlier node",
+            node, kind, name
+        ))?
+    }
+}
+
+impl RenderGraphAssetData {
+    /// Checks that every resource and render phase referenced by the graph exists. This runs when
+    /// the asset is loaded so that a broken edit is rejected instead of breaking rendering.
+    pub fn validate(
+        &self,
+        render_registry: &RenderRegistry,
+    ) -> RafxResult<()> {
+        let mut images = FnvHashSet::<&str>::default();
+        let mut view_images = FnvHashSet::<&str>::default();
+        let mut buffers = FnvHashSet::<&str>::default();
+        let mut node_names = FnvHashSet::<&str>::default();
+
+        for node in &self.nodes {
+            // Names identify nodes in errors and when debugging the graph
+            if !node_names.insert(node.name.as_str()) {
+                return Err(format!(
+                    "Render graph has more than one node named {}",
+                    node.name
+                ))?;
+            }
+
+            let attachments = node
+                .color_attachments
+                .iter()
+                .chain(node.depth_attachment.iter());
+
+            // Later nodes couldn't tell which of the resources a name refers to
+            let mut written_images = FnvHashSet::<&str>::default();
+            for attachment in attachments.clone() {
+                if !written_images.insert(attachment.name.as_str()) {
+                    return Err(format!(
+                        "Render graph node {} writes more than one image named {}",
+                        node.name, attachment.name
+                    ))?;
+                }
+            }
+
+            let mut written_buffers = FnvHashSet::<&str>::default();
+            for storage_buffer in &node.storage_buffers {
+                if !written_buffers.insert(storage_buffer.name.as_str()) {
+                    return Err(format!(
+                        "Render graph node {} writes more than one buffer named {}",
+                        node.name, storage_buffer.name
+                    ))?;
+                }
+            }
+
+            if let RenderGraphAssetNodeAction::DrawViews { .. } = &node.action {
+                let depth_attachment = node.depth_attachment.as_ref().ok_or_else(|| {
+                    format!(
+                        "Render graph node {} draws views but has no depth attachment",
+                        node.name
+                    )
+                })?;
+
+                if !node.color_attachments.is_empty()
+                    || !node.sampled_images.is_empty()
+                    || !node.storage_buffers.is_empty()
+                    || depth_attachment.input.is_some()
+                    || depth_attachment.fallback.is_some()
+                {
+                    return Err(format!(
+                        "Render graph node {} draws views, it may only create a depth attachment",
+                        node.name
+                    ))?;
+                }
+
+                // A disabled node draws no images
+                view_images.insert(depth_attachment.name.as_str());
+            }
+
+            if !node.enabled {
+                for attachment in attachments {
+                    if let Some(passthrough) =
+                        attachment.input.as_ref().or(attachment.fallback.as_ref())
+                    {
+                        require_resource(&images, &node.name, "image", passthrough)?;
+                        images.insert(attachment.name.as_str());
+                    }
+                }
+
+                for storage_buffer in &node.storage_buffers {
+                    if let Some(input) = &storage_buffer.input {
+                        require_resource(&buffers, &node.name, "buffer", input)?;
+                        buffers.insert(storage_buffer.name.as_str());
+                    }
+                }
+
+                continue;
+            }
+
+            let phase = match &node.action {
+                RenderGraphAssetNodeAction::DrawPhase(phase) => Some(phase),
+                RenderGraphAssetNodeAction::DrawViews { phase, .. } => Some(phase),
+                RenderGraphAssetNodeAction::FullscreenMaterial { phase, .. } => Some(phase),
+                RenderGraphAssetNodeAction::Compute { .. } => None,
+            };
+
+            if let Some(phase) = phase {
+                if render_registry
+                    .render_phase_index_from_name(phase)
+                    .is_none()
+                {
+                    return Err(format!(
+                        "Render graph node {} refers to render phase {} which is not registered",
+                        node.name, phase
+                    ))?;
+                }
+            }
+
+            let is_compute = matches!(node.action, RenderGraphAssetNodeAction::Compute { .. });
+
+            if is_compute && (!node.color_attachments.is_empty() || node.depth_attachment.is_some())
+            {
+                return Err(format!(
+                    "Render graph node {} dispatches a compute pipeline but has attachments",
+                    node.name
+                ))?;
+            }
+
+            let uses_slot_bindings = node
+                .sampled_images
+                .iter()
+                .filter_map(|x| x.binding.as_ref())
+                .chain(node.storage_buffers.iter().map(|x| &x.binding))
+                .any(|x| matches!(x, RenderGraphAssetBinding::Slot(_)));
+
+            let has_material = matches!(
+                node.action,
+                RenderGraphAssetNodeAction::FullscreenMaterial { .. }
+            );
+
+            if uses_slot_bindings && !has_material {
+                return Err(format!(
+                    "Render graph node {} binds resources by slot name but does not draw a material",
+                    node.name
+                ))?;
+            }
+
+            for sampled_image in &node.sampled_images {
+                if view_images.contains(sampled_image.image.as_str()) {
+                    if sampled_image.binding.is_some() {
+                        return Err(format!(
+                            "Render graph node {} binds {}, which are images drawn for views",
+                            node.name, sampled_image.image
+                        ))?;
+                    }
+                } else {
+                    require_resource(&images, &node.name, "image", &sampled_image.image)?;
+                }
+            }
+
+            for attachment in attachments.clone() {
+                if let Some(input) = &attachment.input {
+                    require_resource(&images, &node.name, "image", input)?;
+                }
+            }
+
+            for storage_buffer in &node.storage_buffers {
+                if let Some(input) = &storage_buffer.input {
+                    require_resource(&buffers, &node.name, "buffer", input)?;
+                }
+            }
+
+            if !matches!(node.action, RenderGraphAssetNodeAction::DrawViews { .. }) {
+                for attachment in attachments {
+                    images.insert(attachment.name.as_str());
+                }
+            }
+
+            for storage_buffer in &node.storage_buffers {
+                buffers.insert(storage_buffer.name.as_str());
+            }
+        }
+
+        if !images.contains(self.output_image.as_str()) {
+            return Err(format!(
+                "Render graph output image {} is not written by any node",
+                self.output_image
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct RenderGraphAssetInner {
+    pub data: RenderGraphAssetData,
+
+    // Names in a render graph are &'static str, see intern_name()
+    pub names: FnvHashMap<String, &'static str>,
+}
+
+// Each distinct name is leaked once and shared by all render graph assets, including every version
+// of an asset that is hot-reloaded, so the leaked memory is bounded by the names ever used
+fn intern_name(name: &str) -> &'static str {
+    lazy_static::lazy_static! {
+        static ref INTERNED_NAMES: Mutex<FnvHashSet<&'static str>> = Default::default();
+    }
+
+    let mut interned_names = INTERNED_NAMES.lock().unwrap();
+    if let Some(interned_name) = interned_names.get(name) {
+        return interned_name;
+    }
+
+    let interned_name: &'static str = Box::leak(name.to_string().into_boxed_str());
+    interned_names.insert(interned_name);
+    interned_name
+}
+
+#[derive(TypeUuid, Clone)]
+#[uuid = "f352f19c-6824-4d8d-88d0-f62c1fce6e1f"]
+pub struct RenderGraphAsset {
+    pub inner: Arc<RenderGraphAssetInner>,
+}
+
+impl RenderGraphAsset {
+    pub fn new(data: RenderGraphAssetData) -> Self {
+        let mut names = FnvHashMap::default();
+        for node in &data.nodes {
+            let node_names = std::iter::once(&node.name)
+                .chain(node.color_attachments.iter().map(|x| &x.name))
+                .chain(node.depth_attachment.iter().map(|x| &x.name))
+                .chain(node.storage_buffers.iter().map(|x| &x.name));
+
+            for name in node_names {
+                names
+                    .entry(name.clone())
+                    .or_insert_with(|| intern_name(name));
+            }
+        }
+
+        let inner = RenderGraphAssetInner { data, names };
+        RenderGraphAsset {
+            inner: Arc::new(inner),
+        }
+    }
+
+    pub fn name(
+        &self,
+        name: &str,
+    ) -> Option<&'static str> {
+        self.inner.names.get(name).copied()
+    }
+}
+
+impl Deref for RenderGraphAsset {
+    type Target = RenderGraphAssetInner;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.inner
+    }
+}
+
+pub struct RenderGraphLoadHandler;
+
+impl DefaultAssetTypeLoadHandler<RenderGraphAssetData, RenderGraphAsset>
+    for RenderGraphLoadHandler
+{
+    #[profiling::function]
+    fn load(
+        asset_manager: &mut AssetManager,
+        asset_data: RenderGraphAssetData,
+    ) -> RafxResult<RenderGraphAsset> {
+        if let Err(error) = asset_data.validate(asset_manager.resource_manager().render_registry())
+        {
+            log::error!("Load Render Graph Failed - {}", error);
+            return Err(error);
+        }
+
+        Ok(RenderGraphAsset::new(asset_data))
+    }
+}
+
+pub type RenderGraphAssetTypeHandler =
+    DefaultAssetTypeHandler<RenderGraphAssetData, RenderGraphAsset, RenderGraphLoadHandler>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rafx_framework::render_features::{
+        RenderFeatureSubmitNode, RenderPhase, RenderPhaseIndex, RenderRegistryBuilder,
+    };
+
+    rafx_framework::declare_render_phase!(
+        TestOpaqueRenderPhase,
+        TEST_OPAQUE_RENDER_PHASE_INDEX,
+        rafx_framework::render_features::sort_submit_nodes_by_state
+    );
+
+    rafx_framework::declare_render_phase!(
+        TestShadowMapRenderPhase,
+        TEST_SHADOW_MAP_RENDER_PHASE_INDEX,
+        rafx_framework::render_features::sort_submit_nodes_by_state
+    );
+
+    // Render phases can only be registered once per process
+    lazy_static::lazy_static! {
+        static ref RENDER_REGISTRY: RenderRegistry = RenderRegistryBuilder::default()
+            .register_render_phase::<TestOpaqueRenderPhase>("Opaque")
+            .register_render_phase::<TestShadowMapRenderPhase>("ShadowMap")
+            .build();
+    }
+
+    fn valid_graph() -> RenderGraphAssetData {
+        ron::de::from_str(
+            r#"(
+                nodes: [
+                    (
+                        name: "shadow_maps",
+                        depth_attachment: Some((name: "shadow_maps")),
+                        action: DrawViews(phase: "ShadowMap", views: "shadow_maps"),
+                    ),
+                    (
+                        name: "opaque",
+                        color_attachments: [(name: "color")],
+                        depth_attachment: Some((name: "depth")),
+                        sampled_images: [(image: "shadow_maps")],
+                        storage_buffers: [(name: "lights", binding: Binding(0, 0))],
+                        action: DrawPhase("Opaque"),
+                    ),
+                    (
+                        name: "overlay",
+                        color_attachments: [(name: "color", input: Some("color"))],
+                        storage_buffers: [
+                            (name: "lights", input: Some("lights"), binding: Binding(0, 0)),
+                        ],
+                        action: DrawPhase("Opaque"),
+                    ),
+                    (
+                        name: "post",
+                        enabled: false,
+                        color_attachments: [(name: "sdr", fallback: Some("color"))],
+                        sampled_images: [(image: "color", binding: Some(Binding(0, 1)))],
+                        action: DrawPhase("Opaque"),
+                    ),
+                ],
+                output_image: "sdr",
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn validation_error(graph: &RenderGraphAssetData) -> String {
+        match graph.validate(&RENDER_REGISTRY) {
+            Ok(()) => panic!("expected the graph to be rejected"),
+            Err(error) => format!("{}", error),
+        }
+    }
+
+    #[test]
+    fn test_valid_graph() {
+        valid_graph().validate(&RENDER_REGISTRY).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_image_references() {
+        let mut graph = valid_graph();
+        graph.nodes[1].sampled_images[0].image = "missing".to_string();
+        assert!(validation_error(&graph).contains(
+            "node opaque refers to image missing which is not written by an earlier node"
+        ));
+
+        let mut graph = valid_graph();
+        graph.nodes[2].color_attachments[0].input = Some("missing".to_string());
+        assert!(validation_error(&graph).contains("node overlay refers to image missing"));
+
+        // The fallback of a disabled node must exist too
+        let mut graph = valid_graph();
+        graph.nodes[3].color_attachments[0].fallback = Some("missing".to_string());
+        assert!(validation_error(&graph).contains("node post refers to image missing"));
+
+        // Images can only be used after the node that writes them
+        let mut graph = valid_graph();
+        graph.nodes.swap(1, 2);
+        assert!(validation_error(&graph).contains("node overlay refers to image color"));
+
+        let mut graph = valid_graph();
+        graph.nodes[2].storage_buffers[0].input = Some("missing".to_string());
+        assert!(validation_error(&graph).contains("node overlay refers to buffer missing"));
+
+        let mut graph = valid_graph();
+        graph.output_image = "missing".to_string();
+        assert!(
+            validation_error(&graph).contains("output image missing is not written by any node")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut graph = valid_graph();
+        graph.nodes[2].name = "opaque".to_string();
+        assert!(validation_error(&graph).contains("more than one node named opaque"));
+
+        let mut graph = valid_graph();
+        graph.nodes[1].depth_attachment.as_mut().unwrap().name = "color".to_string();
+        assert!(
+            validation_error(&graph).contains("node opaque writes more than one image named color")
+        );
+
+        let mut graph = valid_graph();
+        let storage_buffer = graph.nodes[1].storage_buffers[0].clone();
+        graph.nodes[1].storage_buffers.push(storage_buffer);
+        assert!(validation_error(&graph)
+            .contains("node opaque writes more than one buffer named lights"));
+    }
+
+    #[test]
+    fn test_unknown_render_phase() {
+        let mut graph = valid_graph();
+        graph.nodes[1].action = RenderGraphAssetNodeAction::DrawPhase("Missing".to_string());
+        assert!(validation_error(&graph)
+            .contains("node opaque refers to render phase Missing which is not registered"));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::assets::render_graph::RenderGraphAssetData;
+use distill::core::AssetUuid;
+use distill::importer::{ImportOp, ImportedAsset, Importer, ImporterValue};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use type_uuid::*;
+
+#[derive(TypeUuid, Serialize, Deserialize, Default)]
+#[uuid = "37850fb3-6fb2-4b31-b4dc-945bc65caba1"]
+pub struct RenderGraphImporterState(Option<AssetUuid>);
+
+#[derive(TypeUuid)]
+#[uuid = "c095dd9e-345c-4bad-9b4a-8429850c448f"]
+pub struct RenderGraphImporter;
+impl Importer for RenderGraphImporter {
+    fn version_static() -> u32
+    where
+        Self: Sized,
+    {
+        1
+    }
+
+    fn version(&self) -> u32 {
+        Self::version_static()
+    }
+
+    type Options = ();
+
+    type State = RenderGraphImporterState;
+
+    /// Reads the given bytes and produces assets.
+    #[profiling::function]
+    fn import(
+        &self,
+        _op: &mut ImportOp,
+        source: &mut dyn Read,
+        _options: &Self::Options,
+        state: &mut Self::State,
+    ) -> distill::importer::Result<ImporterValue> {
+        let id = state
+            .0
+            .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+        *state = RenderGraphImporterState(Some(id));
+
+        let render_graph_asset = ron::de::from_reader::<_, RenderGraphAssetData>(source)?;
+        log::trace!("IMPORTED RENDER GRAPH:\n{:#?}", render_graph_asset);
+
+        Ok(ImporterValue {
+            assets: vec![ImportedAsset {
+                id,
+                search_tags: vec![],
+                build_deps: vec![],
+                load_deps: vec![],
+                build_pipeline: None,
+                asset_data: Box::new(render_graph_asset),
+            }],
+        })
+    }
+}
@@ -0,0 +1,5 @@
+pub mod assets;
+pub use assets::*;
+
+mod importer;
+pub use importer::*;
@@ -1,8 +1,14 @@
 use crate::assets::shader::ShaderAssetData;
 use distill::core::AssetUuid;
 use distill::importer::{ImportOp, ImportedAsset, Importer, ImporterValue};
-use rafx_api::{RafxShaderPackage, RafxShaderPackageVulkan};
-use rafx_framework::{CookedShaderPackage, ShaderModuleHash};
+use rafx_api::{
+    RafxSamplerDef, RafxShaderPackage, RafxShaderPackageVulkan, RafxShaderResource,
+    RafxShaderStageReflection,
+};
+use rafx_framework::{
+    CookedShaderPackage, ReflectedDescriptorSetLayout, ReflectedDescriptorSetLayoutBinding,
+    ReflectedEntryPoint, ReflectedVertexInput, ShaderModuleHash,
+};
 use serde::{Deserialize, Serialize};
 use std::io::Read;
 use type_uuid::*;
@@ -20,6 +26,73 @@ fn coerce_result_string<T>(result: Result<T, String>) -> distill::importer::Resu
     Ok(ok)
 }
 
+// Cooked shaders are serialized with bincode, which can't skip missing fields. These mirror the
+// format from before uniform members were reflected so that existing cooked shaders still import.
+// They can be removed once all cooked shaders have been regenerated.
+#[derive(Deserialize)]
+struct LegacyReflectedDescriptorSetLayoutBinding {
+    resource: RafxShaderResource,
+    immutable_samplers: Option<Vec<RafxSamplerDef>>,
+    internal_buffer_per_descriptor_size: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct LegacyReflectedDescriptorSetLayout {
+    bindings: Vec<LegacyReflectedDescriptorSetLayoutBinding>,
+}
+
+#[derive(Deserialize)]
+struct LegacyReflectedEntryPoint {
+    rafx_api_reflection: RafxShaderStageReflection,
+    descriptor_set_layouts: Vec<Option<LegacyReflectedDescriptorSetLayout>>,
+    vertex_inputs: Vec<ReflectedVertexInput>,
+}
+
+#[derive(Deserialize)]
+struct LegacyCookedShaderPackage {
+    hash: ShaderModuleHash,
+    shader_package: RafxShaderPackage,
+    entry_points: Vec<LegacyReflectedEntryPoint>,
+}
+
+impl Into<CookedShaderPackage> for LegacyCookedShaderPackage {
+    fn into(self) -> CookedShaderPackage {
+        let entry_points = self
+            .entry_points
+            .into_iter()
+            .map(|entry_point| ReflectedEntryPoint {
+                rafx_api_reflection: entry_point.rafx_api_reflection,
+                descriptor_set_layouts: entry_point
+                    .descriptor_set_layouts
+                    .into_iter()
+                    .map(|layout| {
+                        layout.map(|layout| ReflectedDescriptorSetLayout {
+                            bindings: layout
+                                .bindings
+                                .into_iter()
+                                .map(|binding| ReflectedDescriptorSetLayoutBinding {
+                                    resource: binding.resource,
+                                    immutable_samplers: binding.immutable_samplers,
+                                    internal_buffer_per_descriptor_size: binding
+                                        .internal_buffer_per_descriptor_size,
+                                    uniform_members: Vec::default(),
+                                })
+                                .collect(),
+                        })
+                    })
+                    .collect(),
+                vertex_inputs: entry_point.vertex_inputs,
+            })
+            .collect();
+
+        CookedShaderPackage {
+            hash: self.hash,
+            shader_package: self.shader_package,
+            entry_points,
+        }
+    }
+}
+
 #[derive(TypeUuid, Serialize, Deserialize, Default)]
 #[uuid = "867bc278-67b5-469c-aeea-1c05da722918"]
 pub struct ShaderImporterSpvState(Option<AssetUuid>);
@@ -108,7 +181,7 @@ impl Importer for ShaderImporterCooked {
     where
         Self: Sized,
     {
-        5
+        6
     }
 
     fn version(&self) -> u32 {
@@ -139,6 +212,14 @@ impl Importer for ShaderImporterCooked {
 
         let cooked_shader: CookedShaderPackage = coerce_result_string(
             bincode::deserialize::<CookedShaderPackage>(&bytes)
+                .or_else(|x| {
+                    bincode::deserialize::<LegacyCookedShaderPackage>(&bytes)
+                        .map(|legacy| {
+                            log::warn!("Cooked shader was generated by an older version of the shader processor and does not include uniform members. Regenerate it to set uniform params by name.");
+                            legacy.into()
+                        })
+                        .map_err(|_| x)
+                })
                 .map_err(|x| format!("Failed to deserialize cooked shader: {:?}", x)),
         )?;
 
@@ -1,9 +1,15 @@
 use super::load_queue::LoadRequest;
+use super::upload_schedule::{
+    PendingUpload, UploadPriority, UploadRank, UploadSchedule, UploadScheduleRequest,
+    UploadScheduleStep,
+};
 use super::BufferAssetData;
 use super::ImageAssetData;
 use super::{BufferAsset, ImageAsset};
-use crate::assets::image::ImageAssetDataFormat;
-use crate::image_upload::{ImageUploadParams, IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT};
+use crate::assets::image::{ImageAssetDataFormat, TextureStreaming};
+use crate::image_upload::{
+    ImageUploadParams, PartialImageUpload, IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT,
+};
 use crate::{
     buffer_upload, image_upload, GpuImageData, GpuImageDataColorSpace, GpuImageDataLayer,
     GpuImageDataMipLevel,
@@ -13,8 +19,8 @@ use basis_universal::{TranscodeParameters, TranscoderTextureFormat};
 use crossbeam_channel::{Receiver, Sender};
 use distill::loader::{storage::AssetLoadOp, LoadHandle};
 use rafx_api::{
-    extra::upload::*, RafxBuffer, RafxDeviceContext, RafxError, RafxQueue, RafxResourceType,
-    RafxResult, RafxTexture,
+    extra::mipmaps, extra::upload::*, RafxBuffer, RafxDeviceContext, RafxError, RafxQueue,
+    RafxResourceType, RafxResult, RafxTexture,
 };
 
 //
@@ -98,6 +104,16 @@ pub struct PendingImageUpload {
     pub generate_mips: bool,
 }
 
+impl PendingImageUpload {
+    fn upload_params(&self) -> ImageUploadParams<'static> {
+        ImageUploadParams {
+            resource_type: self.resource_type,
+            generate_mips: self.generate_mips,
+            ..Default::default()
+        }
+    }
+}
+
 pub struct PendingBufferUpload {
     pub load_op: AssetLoadOp,
     pub upload_op: BufferUploadOp,
@@ -105,6 +121,59 @@ pub struct PendingBufferUpload {
     pub data: Vec<u8>,
 }
 
+enum PendingUploadRequest {
+    Image(PendingImageUpload),
+    Buffer(PendingBufferUpload),
+}
+
+impl UploadScheduleRequest for PendingUploadRequest {
+    fn load_handle(&self) -> LoadHandle {
+        match self {
+            PendingUploadRequest::Image(image) => image.load_op.load_handle(),
+            PendingUploadRequest::Buffer(buffer) => buffer.load_op.load_handle(),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            PendingUploadRequest::Image(image) => {
+                crate::image_upload::image_upload_size(&image.image_data, &image.upload_params())
+            }
+            PendingUploadRequest::Buffer(buffer) => buffer.data.len() as u64,
+        }
+    }
+
+    fn is_image(&self) -> bool {
+        matches!(self, PendingUploadRequest::Image(_))
+    }
+}
+
+impl PendingUploadRequest {
+    fn error(
+        self,
+        error: RafxError,
+    ) {
+        match self {
+            PendingUploadRequest::Image(image) => {
+                image.load_op.error(error);
+                image.upload_op.error();
+            }
+            PendingUploadRequest::Buffer(buffer) => {
+                buffer.load_op.error(error);
+                buffer.upload_op.error();
+            }
+        }
+    }
+}
+
+// An image too large for one staging buffer that is being copied over several uploads
+struct InProgressPartialImageUpload {
+    // The rank of the request, the image is only continued while it outranks every queued request
+    rank: UploadRank,
+    pending_image: PendingImageUpload,
+    partial_upload: PartialImageUpload,
+}
+
 //
 // Represents a single request that the upload queue has started
 //
@@ -139,6 +208,8 @@ struct InProgressUploadInner {
     image_uploads: Vec<InFlightImageUpload>,
     buffer_uploads: Vec<InFlightBufferUpload>,
     upload: RafxTransferUpload,
+    // Textures of cancelled partial image uploads that must outlive the copies into them
+    retained_textures: Vec<PartialImageUpload>,
 }
 
 struct InProgressUploadDebugInfo {
@@ -147,6 +218,8 @@ struct InProgressUploadDebugInfo {
     size: u64,
     image_count: usize,
     buffer_count: usize,
+    // True if this upload carries part of an image that is split across several uploads
+    has_partial_image: bool,
 }
 
 // A single upload which may contain multiple images
@@ -167,6 +240,7 @@ impl InProgressUpload {
             image_uploads,
             buffer_uploads,
             upload,
+            retained_textures: Default::default(),
         };
 
         InProgressUpload {
@@ -264,6 +338,23 @@ pub struct UploadQueueConfig {
     pub max_bytes_per_upload: usize,
     pub max_concurrent_uploads: usize,
     pub max_new_uploads_in_single_frame: usize,
+    // Stop starting new uploads in a frame once this many bytes have been started. At least one
+    // request is always started per frame so that requests larger than the budget make progress.
+    // Zero disables the budget.
+    pub max_bytes_per_frame: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct UploadQueueMetrics {
+    pub queued_count: usize,
+    pub queued_bytes: u64,
+    pub in_flight_upload_count: usize,
+    pub in_flight_bytes: u64,
+    // Totals since the queue was created
+    pub completed_count: usize,
+    pub completed_bytes: u64,
+    pub failed_count: usize,
+    pub cancelled_count: usize,
 }
 
 //
@@ -278,15 +369,15 @@ pub struct UploadQueue {
     pending_image_tx: Sender<PendingImageUpload>,
     pending_image_rx: Receiver<PendingImageUpload>,
 
-    // If we fail to upload due to size limitation, keep the failed upload here to retry later
-    next_image_upload: Option<PendingImageUpload>,
-
     // For enqueueing buffers to upload
     pending_buffer_tx: Sender<PendingBufferUpload>,
     pending_buffer_rx: Receiver<PendingBufferUpload>,
 
-    // If we fail to upload due to size limitation, keep the failed upload here to retry later
-    next_buffer_upload: Option<PendingBufferUpload>,
+    // Requests drained from the channels and the order they start in
+    schedule: UploadSchedule<PendingUploadRequest>,
+
+    // At most one oversized image is split across uploads at a time
+    partial_image_upload: Option<InProgressPartialImageUpload>,
 
     // These are uploads that are currently in progress
     uploads_in_progress: Vec<InProgressUpload>,
@@ -295,6 +386,7 @@ pub struct UploadQueue {
     transfer_queue: RafxQueue,
 
     next_upload_id: usize,
+    metrics: UploadQueueMetrics,
 }
 
 impl UploadQueue {
@@ -307,19 +399,25 @@ impl UploadQueue {
         let (pending_image_tx, pending_image_rx) = crossbeam_channel::unbounded();
         let (pending_buffer_tx, pending_buffer_rx) = crossbeam_channel::unbounded();
 
+        let schedule = UploadSchedule::new(
+            config.max_bytes_per_upload as u64,
+            config.max_bytes_per_frame as u64,
+        );
+
         UploadQueue {
             device_context: device_context.clone(),
             config,
             pending_image_tx,
             pending_image_rx,
-            next_image_upload: None,
             pending_buffer_tx,
             pending_buffer_rx,
-            next_buffer_upload: None,
+            schedule,
+            partial_image_upload: None,
             uploads_in_progress: Default::default(),
             next_upload_id: 1,
             graphics_queue,
             transfer_queue,
+            metrics: Default::default(),
         }
     }
 
@@ -331,6 +429,233 @@ impl UploadQueue {
         &self.pending_buffer_tx
     }
 
+    // Applies to requests that are already queued and to any later request for the same load
+    // handle (i.e. when the asset is reloaded)
+    pub fn set_upload_priority(
+        &mut self,
+        load_handle: LoadHandle,
+        priority: UploadPriority,
+    ) {
+        self.schedule.set_upload_priority(load_handle, priority);
+
+        if let Some(partial_image_upload) = &mut self.partial_image_upload {
+            if partial_image_upload.pending_image.load_op.load_handle() == load_handle {
+                partial_image_upload.rank.priority = priority;
+            }
+        }
+    }
+
+    // Cancels queued requests for which the callback returns false. Requests that have been
+    // submitted to the GPU are allowed to finish.
+    pub fn retain_uploads<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        mut f: F,
+    ) {
+        self.receive_pending_uploads();
+
+        let cancelled = self.schedule.retain_uploads(&mut f);
+
+        let cancel_partial_image = self
+            .partial_image_upload
+            .as_ref()
+            .map(|x| !f(x.pending_image.load_op.load_handle()))
+            .unwrap_or(false);
+
+        if cancel_partial_image {
+            let partial_image_upload = self.partial_image_upload.take().unwrap();
+            self.abort_partial_image_upload(
+                partial_image_upload,
+                "Image upload cancelled because the asset is no longer referenced",
+            );
+            self.metrics.cancelled_count += 1;
+        }
+
+        for pending_upload in cancelled {
+            log::debug!(
+                "Cancelling upload of {} bytes for {:?}, the asset is no longer referenced",
+                pending_upload.size,
+                pending_upload.request.load_handle()
+            );
+
+            pending_upload.request.error(RafxError::StringError(
+                "Upload cancelled because the asset is no longer referenced".to_string(),
+            ));
+            self.metrics.cancelled_count += 1;
+        }
+    }
+
+    // Forgets priorities set for load handles for which the callback returns false
+    pub fn retain_upload_priorities<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        f: F,
+    ) {
+        self.schedule.retain_upload_priorities(f);
+    }
+
+    pub fn metrics(&self) -> UploadQueueMetrics {
+        let mut metrics = self.metrics.clone();
+        metrics.queued_count = self.schedule.len();
+        metrics.queued_bytes = self.schedule.queued_bytes();
+        if let Some(partial_image_upload) = &self.partial_image_upload {
+            let pending_image = &partial_image_upload.pending_image;
+            metrics.queued_count += 1;
+            metrics.queued_bytes += partial_image_upload
+                .partial_upload
+                .remaining_size(&pending_image.image_data, &pending_image.upload_params());
+        }
+
+        metrics.in_flight_upload_count = self.uploads_in_progress.len();
+        metrics.in_flight_bytes = self
+            .uploads_in_progress
+            .iter()
+            .map(|x| x.debug_info.size)
+            .sum();
+        metrics
+    }
+
+    fn receive_pending_uploads(&mut self) {
+        let images = self
+            .pending_image_rx
+            .try_iter()
+            .map(PendingUploadRequest::Image);
+        let buffers = self
+            .pending_buffer_rx
+            .try_iter()
+            .map(PendingUploadRequest::Buffer);
+        self.schedule.push(images.chain(buffers));
+    }
+
+    fn abort_partial_image_upload(
+        &mut self,
+        partial_image_upload: InProgressPartialImageUpload,
+        error: &str,
+    ) {
+        let InProgressPartialImageUpload {
+            pending_image,
+            partial_upload,
+            ..
+        } = partial_image_upload;
+
+        pending_image
+            .load_op
+            .error(RafxError::StringError(error.to_string()));
+        pending_image.upload_op.error();
+
+        // Earlier parts of the image may still be copying, so keep the texture alive until the most
+        // recent upload that wrote to it is done
+        let last_partial_upload = self
+            .uploads_in_progress
+            .iter_mut()
+            .filter(|upload| upload.debug_info.has_partial_image)
+            .max_by_key(|upload| upload.debug_info.upload_id);
+
+        if let Some(upload) = last_partial_upload {
+            if let Some(inner) = &mut upload.inner {
+                inner.retained_textures.push(partial_upload);
+            }
+        }
+    }
+
+    // Ok(true) = the image was completely written into this upload
+    // Ok(false) = the upload is full, continue in the next upload
+    fn continue_partial_image_upload(
+        &mut self,
+        upload: &mut RafxTransferUpload,
+        in_flight_uploads: &mut Vec<InFlightImageUpload>,
+    ) -> RafxResult<bool> {
+        let mut partial_image_upload = self.partial_image_upload.take().unwrap();
+        let params = partial_image_upload.pending_image.upload_params();
+
+        let result = crate::image_upload::enqueue_partial_image_upload(
+            upload,
+            &mut partial_image_upload.partial_upload,
+            &partial_image_upload.pending_image.image_data,
+            &params,
+        );
+
+        match result {
+            Ok(true) => {
+                let InProgressPartialImageUpload {
+                    pending_image,
+                    partial_upload,
+                    ..
+                } = partial_image_upload;
+
+                in_flight_uploads.push(InFlightImageUpload {
+                    texture: partial_upload.into_texture(),
+                    load_op: pending_image.load_op,
+                    upload_op: pending_image.upload_op,
+                });
+                Ok(true)
+            }
+            Ok(false) | Err(RafxUploadError::BufferFull) => {
+                self.partial_image_upload = Some(partial_image_upload);
+                Ok(false)
+            }
+            Err(RafxUploadError::Other(e)) => {
+                log::error!("Failed to upload part of an image: {:?}", e);
+                self.abort_partial_image_upload(partial_image_upload, "Failed to upload image");
+                self.metrics.failed_count += 1;
+                Ok(false)
+            }
+        }
+    }
+
+    fn begin_partial_image_upload(
+        &mut self,
+        pending_image: PendingImageUpload,
+        rank: UploadRank,
+    ) -> RafxResult<()> {
+        let params = pending_image.upload_params();
+        let largest_subresource_size =
+            crate::image_upload::largest_image_subresource_size(&pending_image.image_data);
+
+        // A single layer/mip level is never split, so it must fit in the staging buffer
+        if largest_subresource_size > self.config.max_bytes_per_upload as u64 {
+            let error = format!(
+                "Image of {} bytes has a mip level of {} bytes, which exceeds the upload buffer size of {} bytes",
+                pending_image
+                    .image_data
+                    .total_size(IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT),
+                largest_subresource_size,
+                self.config.max_bytes_per_upload
+            );
+            log::error!("{}", error);
+            PendingUploadRequest::Image(pending_image).error(RafxError::StringError(error));
+            self.metrics.failed_count += 1;
+            return Ok(());
+        }
+
+        log::debug!(
+            "Image of {} bytes exceeds the upload buffer size of {} bytes, splitting it across several uploads",
+            crate::image_upload::image_upload_size(&pending_image.image_data, &params),
+            self.config.max_bytes_per_upload
+        );
+
+        match crate::image_upload::begin_partial_image_upload(
+            &self.device_context,
+            &pending_image.image_data,
+            &params,
+        ) {
+            Ok(partial_upload) => {
+                self.partial_image_upload = Some(InProgressPartialImageUpload {
+                    rank,
+                    pending_image,
+                    partial_upload,
+                });
+                Ok(())
+            }
+            Err(RafxUploadError::Other(e)) => {
+                let error = format!("{:?}", e);
+                PendingUploadRequest::Image(pending_image).error(e);
+                log::error!("Failed to create texture for image upload: {}", error);
+                self.metrics.failed_count += 1;
+                Ok(())
+            }
+            Err(RafxUploadError::BufferFull) => unreachable!(),
+        }
+    }
+
     // Ok(None) = upload enqueue
     // Ok(Some) = upload not enqueued because there was not enough room
     // Err = Vulkan error
@@ -346,11 +671,7 @@ impl UploadQueue {
             // self.transfer_queue.queue_family_index(),
             // self.graphics_queue.queue_family_index(),
             &pending_image.image_data,
-            ImageUploadParams {
-                resource_type: pending_image.resource_type,
-                generate_mips: pending_image.generate_mips,
-                ..Default::default()
-            },
+            pending_image.upload_params(),
         );
 
         match result {
@@ -367,49 +688,6 @@ impl UploadQueue {
         }
     }
 
-    fn start_new_image_uploads(
-        &mut self,
-        upload: &mut RafxTransferUpload,
-    ) -> RafxResult<Vec<InFlightImageUpload>> {
-        let mut in_flight_uploads = vec![];
-
-        // If we had a pending image upload from before, try to upload it now
-        self.next_image_upload = if let Some(next_image_upload) = self.next_image_upload.take() {
-            self.try_enqueue_image_upload(upload, next_image_upload, &mut in_flight_uploads)?
-        } else {
-            None
-        };
-
-        // The first image we tried to upload failed. Log an error since we aren't making forward progress
-        if let Some(next_image_upload) = &self.next_image_upload {
-            log::error!(
-                "Image of {} bytes has repeatedly exceeded the available room in the upload buffer. ({} of {} bytes free)",
-                next_image_upload.image_data.total_size(IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT as u64),
-                upload.bytes_free(),
-                upload.buffer_size()
-            );
-            return Ok(vec![]);
-        }
-
-        let rx = self.pending_image_rx.clone();
-        for pending_upload in rx.try_iter() {
-            self.next_image_upload =
-                self.try_enqueue_image_upload(upload, pending_upload, &mut in_flight_uploads)?;
-
-            if let Some(next_image_upload) = &self.next_image_upload {
-                log::debug!(
-                    "Image of {} bytes exceeds the available room in the upload buffer. ({} of {} bytes free)",
-                    next_image_upload.image_data.total_size(IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT as u64),
-                    upload.bytes_free(),
-                    upload.buffer_size(),
-                );
-                break;
-            }
-        }
-
-        Ok(in_flight_uploads)
-    }
-
     // Ok(None) = upload enqueue
     // Ok(Some) = upload not enqueued because there was not enough room
     // Err = Vulkan error
@@ -442,56 +720,99 @@ impl UploadQueue {
         }
     }
 
-    fn start_new_buffer_uploads(
+    // Fills the upload with the highest priority requests until it is full or the frame budget is
+    // spent. Returns true if part of a split image was written.
+    fn fill_upload(
         &mut self,
         upload: &mut RafxTransferUpload,
-    ) -> RafxResult<Vec<InFlightBufferUpload>> {
-        let mut in_flight_uploads = vec![];
-
-        // If we had a pending image upload from before, try to upload it now
-        self.next_buffer_upload = if let Some(next_buffer_upload) = self.next_buffer_upload.take() {
-            self.try_enqueue_buffer_upload(upload, next_buffer_upload, &mut in_flight_uploads)?
-        } else {
-            None
-        };
-
-        // The first buffer we tried to upload failed. Log an error since we aren't making forward progress
-        if let Some(next_buffer_upload) = &self.next_buffer_upload {
-            log::error!(
-                "Buffer of {} bytes has repeatedly exceeded the available room in the upload buffer. ({} of {} bytes free)",
-                next_buffer_upload.data.len(),
-                upload.bytes_free(),
-                upload.buffer_size()
-            );
-            return Ok(vec![]);
-        }
+        in_flight_image_uploads: &mut Vec<InFlightImageUpload>,
+        in_flight_buffer_uploads: &mut Vec<InFlightBufferUpload>,
+    ) -> RafxResult<bool> {
+        let mut has_partial_image = false;
+        loop {
+            let partial_image_rank = self.partial_image_upload.as_ref().map(|x| x.rank);
+            let pending_upload = match self
+                .schedule
+                .next_step(partial_image_rank, upload.bytes_written())
+            {
+                UploadScheduleStep::Stop => return Ok(has_partial_image),
+                UploadScheduleStep::ContinuePartialImage => {
+                    // If the image still doesn't finish, this upload is full
+                    has_partial_image = true;
+                    if !self.continue_partial_image_upload(upload, in_flight_image_uploads)? {
+                        return Ok(has_partial_image);
+                    }
+                    continue;
+                }
+                UploadScheduleStep::Start(pending_upload) => pending_upload,
+            };
 
-        let rx = self.pending_buffer_rx.clone();
-        for pending_upload in rx.try_iter() {
-            self.next_buffer_upload =
-                self.try_enqueue_buffer_upload(upload, pending_upload, &mut in_flight_uploads)?;
+            let PendingUpload {
+                rank,
+                size,
+                request,
+            } = pending_upload;
+
+            let not_enqueued = match request {
+                PendingUploadRequest::Image(pending_image) => {
+                    if size > self.config.max_bytes_per_upload as u64 {
+                        // The image is continued by the next step since it outranks everything
+                        // still queued
+                        self.begin_partial_image_upload(pending_image, rank)?;
+                        None
+                    } else {
+                        self.try_enqueue_image_upload(
+                            upload,
+                            pending_image,
+                            in_flight_image_uploads,
+                        )?
+                        .map(PendingUploadRequest::Image)
+                    }
+                }
+                PendingUploadRequest::Buffer(pending_buffer) => {
+                    if size > self.config.max_bytes_per_upload as u64 {
+                        let error = format!(
+                            "Buffer of {} bytes exceeds the upload buffer size of {} bytes",
+                            size, self.config.max_bytes_per_upload
+                        );
+                        log::error!("{}", error);
+                        PendingUploadRequest::Buffer(pending_buffer)
+                            .error(RafxError::StringError(error));
+                        self.metrics.failed_count += 1;
+                        None
+                    } else {
+                        self.try_enqueue_buffer_upload(
+                            upload,
+                            pending_buffer,
+                            in_flight_buffer_uploads,
+                        )?
+                        .map(PendingUploadRequest::Buffer)
+                    }
+                }
+            };
 
-            if let Some(next_buffer_upload) = &self.next_buffer_upload {
+            if let Some(request) = not_enqueued {
                 log::debug!(
-                    "Buffer of {} bytes exceeds the available room in the upload buffer. ({} of {} bytes free)",
-                    next_buffer_upload.data.len(),
+                    "Upload of {} bytes exceeds the available room in the upload buffer. ({} of {} bytes free)",
+                    size,
                     upload.bytes_free(),
                     upload.buffer_size(),
                 );
-                break;
+
+                // Put it back where it was, it will go in the next upload
+                self.schedule.requeue(PendingUpload {
+                    rank,
+                    size,
+                    request,
+                });
+                return Ok(has_partial_image);
             }
         }
-
-        Ok(in_flight_uploads)
     }
 
     fn start_new_uploads(&mut self) -> RafxResult<()> {
         for _ in 0..self.config.max_new_uploads_in_single_frame {
-            if self.pending_image_rx.is_empty()
-                && self.next_image_upload.is_none()
-                && self.pending_buffer_rx.is_empty()
-                && self.next_buffer_upload.is_none()
-            {
+            if self.schedule.is_empty() && self.partial_image_upload.is_none() {
                 return Ok(());
             }
 
@@ -502,6 +823,11 @@ impl UploadQueue {
                 return Ok(());
             }
 
+            if self.schedule.remaining_frame_budget() == Some(0) {
+                log::trace!("Upload budget for this frame is spent");
+                return Ok(());
+            }
+
             if !self.start_new_upload()? {
                 return Ok(());
             }
@@ -518,10 +844,18 @@ impl UploadQueue {
             self.config.max_bytes_per_upload as u64,
         )?;
 
-        let in_flight_image_uploads = self.start_new_image_uploads(&mut upload)?;
-        let in_flight_buffer_uploads = self.start_new_buffer_uploads(&mut upload)?;
+        let mut in_flight_image_uploads = vec![];
+        let mut in_flight_buffer_uploads = vec![];
+
+        let result = self.fill_upload(
+            &mut upload,
+            &mut in_flight_image_uploads,
+            &mut in_flight_buffer_uploads,
+        );
+        self.schedule.end_upload(upload.bytes_written());
+        let has_partial_image = result?;
 
-        if !in_flight_image_uploads.is_empty() || !in_flight_buffer_uploads.is_empty() {
+        if upload.bytes_written() > 0 {
             let upload_id = self.next_upload_id;
             self.next_upload_id += 1;
 
@@ -541,6 +875,7 @@ impl UploadQueue {
                 image_count: in_flight_image_uploads.len(),
                 size: upload.bytes_written(),
                 start_time: rafx_base::Instant::now(),
+                has_partial_image,
             };
 
             self.uploads_in_progress.push(InProgressUpload::new(
@@ -577,6 +912,10 @@ impl UploadQueue {
                         debug_info.upload_id
                     );
 
+                    self.metrics.completed_count +=
+                        debug_info.image_count + debug_info.buffer_count;
+                    self.metrics.completed_bytes += debug_info.size;
+
                     self.uploads_in_progress.swap_remove(i);
                 }
                 InProgressUploadPollResult::Error => {
@@ -592,7 +931,20 @@ impl UploadQueue {
                         debug_info.upload_id
                     );
 
+                    self.metrics.failed_count += debug_info.image_count + debug_info.buffer_count;
+
+                    // If part of a split image was lost, the rest of it can't be completed
+                    let has_partial_image = debug_info.has_partial_image;
                     self.uploads_in_progress.swap_remove(i);
+                    if has_partial_image {
+                        if let Some(partial_image_upload) = self.partial_image_upload.take() {
+                            self.abort_partial_image_upload(
+                                partial_image_upload,
+                                "Failed to upload image",
+                            );
+                            self.metrics.failed_count += 1;
+                        }
+                    }
                 }
                 InProgressUploadPollResult::Destroyed => {
                     // not expected - this only occurs if polling the upload when it is already in a complete or error state
@@ -603,6 +955,8 @@ impl UploadQueue {
     }
 
     pub fn update(&mut self) -> RafxResult<()> {
+        self.schedule.begin_frame();
+        self.receive_pending_uploads();
         self.start_new_uploads()?;
         self.update_existing_uploads();
         Ok(())
@@ -610,6 +964,7 @@ impl UploadQueue {
 }
 
 pub struct UploadManager {
+    device_context: RafxDeviceContext,
     upload_queue: UploadQueue,
 
     pub image_upload_result_tx: Sender<ImageUploadOpResult>,
@@ -633,6 +988,7 @@ impl UploadManager {
         let (buffer_upload_result_tx, buffer_upload_result_rx) = crossbeam_channel::unbounded();
 
         UploadManager {
+            device_context: device_context.clone(),
             upload_queue: UploadQueue::new(
                 device_context,
                 upload_queue_config,
@@ -652,9 +1008,71 @@ impl UploadManager {
         self.upload_queue.update()
     }
 
+    pub fn set_upload_priority(
+        &mut self,
+        load_handle: LoadHandle,
+        priority: UploadPriority,
+    ) {
+        self.upload_queue.set_upload_priority(load_handle, priority);
+    }
+
+    pub fn retain_uploads<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        f: F,
+    ) {
+        self.upload_queue.retain_uploads(f);
+    }
+
+    pub fn retain_upload_priorities<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        f: F,
+    ) {
+        self.upload_queue.retain_upload_priorities(f);
+    }
+
+    pub fn metrics(&self) -> UploadQueueMetrics {
+        self.upload_queue.metrics()
+    }
+
+    // Returns an error if the device can't sample images in the asset's format, or can't generate
+    // mips for them when the asset asks for mips to be generated at runtime. Basis compressed data
+    // is transcoded to a supported format, so it is never rejected.
+    fn unsupported_format_error(
+        &self,
+        asset: &ImageAssetData,
+    ) -> Option<String> {
+        let format = match asset.format {
+            ImageAssetDataFormat::BasisCompressed => return None,
+            _ => asset.format.raw_format(asset.color_space)?,
+        };
+
+        if self
+            .device_context
+            .find_supported_format(&[format], RafxResourceType::TEXTURE)
+            .is_none()
+        {
+            return Some(format!(
+                "Image format {:?} is not supported by the device",
+                format
+            ));
+        }
+
+        if asset.generate_mips_at_runtime
+            && !mipmaps::format_supports_mipmap_generation(&self.device_context, format)
+        {
+            return Some(format!(
+                "Image format {:?} does not support generating mips at runtime on this device",
+                format
+            ));
+        }
+
+        None
+    }
+
     pub fn upload_image(
         &self,
         request: LoadRequest<ImageAssetData, ImageAsset>,
+        texture_streaming: &mut TextureStreaming,
     ) -> RafxResult<()> {
         assert_ne!(request.asset.width, 0);
         assert_ne!(request.asset.height, 0);
@@ -662,6 +1080,20 @@ impl UploadManager {
 
         let generate_mips = request.asset.generate_mips_at_runtime;
 
+        // The data can't be converted, so fail the load rather than the upload queue if the device
+        // can't sample the format (i.e. BC formats on mobile GPUs) or generate its mips
+        if let Some(error) = self.unsupported_format_error(&request.asset) {
+            log::error!("{}", error);
+            request.load_op.error(RafxError::StringError(error));
+            UploadOp::new(
+                request.load_handle,
+                request.result_tx,
+                self.image_upload_result_tx.clone(),
+            )
+            .error();
+            return Ok(());
+        }
+
         let t0 = rafx_base::Instant::now();
         let image_data = match request.asset.format {
             ImageAssetDataFormat::RawRGBA32 => GpuImageData::new_simple(
@@ -670,6 +1102,30 @@ impl UploadManager {
                 color_space.rgba8(),
                 request.asset.data,
             ),
+            ImageAssetDataFormat::RawRGBA16Float
+            | ImageAssetDataFormat::RawRGBA32Float
+            | ImageAssetDataFormat::RawB10G11R11Float => GpuImageData::new_simple(
+                request.asset.width,
+                request.asset.height,
+                request
+                    .asset
+                    .format
+                    .raw_format(request.asset.color_space)
+                    .unwrap(),
+                request.asset.data,
+            ),
+            ImageAssetDataFormat::Precomputed {
+                format,
+                layer_count,
+                mip_count,
+            } => GpuImageData::new_precomputed(
+                request.asset.width,
+                request.asset.height,
+                format,
+                layer_count,
+                mip_count,
+                &request.asset.data,
+            ),
             #[cfg(not(feature = "basis-universal"))]
             ImageAssetDataFormat::BasisCompressed => {
                 unimplemented!("Not built with basis-universal feature");
@@ -776,6 +1232,17 @@ impl UploadManager {
             (t1 - t0).as_secs_f64() * 1000.0
         );
 
+        // Mips generated at runtime only exist on the GPU so they can't be streamed
+        let image_data = if generate_mips {
+            image_data
+        } else {
+            texture_streaming.split_streamed_image(
+                request.load_handle,
+                image_data,
+                request.asset.resource_type,
+            )
+        };
+
         self.upload_queue
             .pending_image_tx()
             .send(PendingImageUpload {
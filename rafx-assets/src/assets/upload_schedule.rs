@@ -0,0 +1,549 @@
+// Decides the order in which queued upload requests start. This is kept apart from the GPU work in
+// upload.rs so that it can be tested without a device.
+use distill::loader::LoadHandle;
+use fnv::FnvHashMap;
+use std::cmp::Ordering;
+
+// Uploads with a higher priority are started first. Uploads with the same priority are started in
+// the order they were requested.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UploadPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Default for UploadPriority {
+    fn default() -> Self {
+        UploadPriority::Normal
+    }
+}
+
+// The position of a request in the queue. A greater rank starts first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) struct UploadRank {
+    pub(super) priority: UploadPriority,
+    pub(super) sequence_number: u64,
+}
+
+impl Ord for UploadRank {
+    fn cmp(
+        &self,
+        other: &Self,
+    ) -> Ordering {
+        // Highest priority, then lowest sequence number
+        self.priority
+            .cmp(&other.priority)
+            .then(other.sequence_number.cmp(&self.sequence_number))
+    }
+}
+
+impl PartialOrd for UploadRank {
+    fn partial_cmp(
+        &self,
+        other: &Self,
+    ) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub(super) trait UploadScheduleRequest {
+    fn load_handle(&self) -> LoadHandle;
+
+    // Space required in the staging buffer
+    fn size(&self) -> u64;
+
+    // Only images can be split across several uploads
+    fn is_image(&self) -> bool;
+}
+
+// A request waiting in the queue, ordered by priority and then by the order it arrived
+pub(super) struct PendingUpload<RequestT> {
+    pub(super) rank: UploadRank,
+    pub(super) size: u64,
+    pub(super) request: RequestT,
+}
+
+// What to write into the upload that is being filled next
+pub(super) enum UploadScheduleStep<RequestT> {
+    // Continue the image that is being split across uploads, it outranks every queued request
+    ContinuePartialImage,
+    // Start this request. If it can't be written, it must be given back with requeue()
+    Start(PendingUpload<RequestT>),
+    // Nothing else can start in this upload
+    Stop,
+}
+
+pub(super) struct UploadSchedule<RequestT> {
+    max_bytes_per_upload: u64,
+    // Zero disables the budget
+    max_bytes_per_frame: u64,
+
+    // Requests sorted so that the next request to start is last
+    pending_uploads: Vec<PendingUpload<RequestT>>,
+    next_sequence_number: u64,
+    upload_priorities: FnvHashMap<LoadHandle, UploadPriority>,
+
+    // Oversized images skipped while filling the current upload because another image is being
+    // split. They are restored by end_upload().
+    deferred: Vec<PendingUpload<RequestT>>,
+
+    bytes_started_this_frame: u64,
+}
+
+impl<RequestT: UploadScheduleRequest> UploadSchedule<RequestT> {
+    pub(super) fn new(
+        max_bytes_per_upload: u64,
+        max_bytes_per_frame: u64,
+    ) -> Self {
+        UploadSchedule {
+            max_bytes_per_upload,
+            max_bytes_per_frame,
+            pending_uploads: Default::default(),
+            next_sequence_number: 0,
+            upload_priorities: Default::default(),
+            deferred: Default::default(),
+            bytes_started_this_frame: 0,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.pending_uploads.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.pending_uploads.is_empty()
+    }
+
+    pub(super) fn queued_bytes(&self) -> u64 {
+        self.pending_uploads.iter().map(|x| x.size).sum()
+    }
+
+    // Adds requests to the queue, with the priority set for their load handles
+    pub(super) fn push<I: IntoIterator<Item = RequestT>>(
+        &mut self,
+        requests: I,
+    ) {
+        let mut received = false;
+        for request in requests {
+            let priority = self
+                .upload_priorities
+                .get(&request.load_handle())
+                .copied()
+                .unwrap_or_default();
+
+            let sequence_number = self.next_sequence_number;
+            self.next_sequence_number += 1;
+
+            self.pending_uploads.push(PendingUpload {
+                rank: UploadRank {
+                    priority,
+                    sequence_number,
+                },
+                size: request.size(),
+                request,
+            });
+            received = true;
+        }
+
+        if received {
+            self.sort_pending_uploads();
+        }
+    }
+
+    // Applies to requests that are already queued and to any later request for the same load
+    // handle (i.e. when the asset is reloaded)
+    pub(super) fn set_upload_priority(
+        &mut self,
+        load_handle: LoadHandle,
+        priority: UploadPriority,
+    ) {
+        if priority == UploadPriority::default() {
+            self.upload_priorities.remove(&load_handle);
+        } else {
+            self.upload_priorities.insert(load_handle, priority);
+        }
+
+        let mut changed = false;
+        for pending_upload in &mut self.pending_uploads {
+            if pending_upload.request.load_handle() == load_handle {
+                pending_upload.rank.priority = priority;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.sort_pending_uploads();
+        }
+    }
+
+    // Removes and returns the queued requests for which the callback returns false
+    pub(super) fn retain_uploads<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        mut f: F,
+    ) -> Vec<PendingUpload<RequestT>> {
+        let mut cancelled = vec![];
+        let mut i = 0;
+        while i < self.pending_uploads.len() {
+            if f(self.pending_uploads[i].request.load_handle()) {
+                i += 1;
+            } else {
+                // remove rather than swap_remove to keep the vec sorted
+                cancelled.push(self.pending_uploads.remove(i));
+            }
+        }
+
+        cancelled
+    }
+
+    // Forgets priorities set for load handles for which the callback returns false
+    pub(super) fn retain_upload_priorities<F: FnMut(LoadHandle) -> bool>(
+        &mut self,
+        mut f: F,
+    ) {
+        self.upload_priorities
+            .retain(|load_handle, _| f(*load_handle));
+    }
+
+    pub(super) fn begin_frame(&mut self) {
+        self.bytes_started_this_frame = 0;
+    }
+
+    pub(super) fn remaining_frame_budget(&self) -> Option<u64> {
+        if self.max_bytes_per_frame == 0 {
+            None
+        } else {
+            Some(
+                self.max_bytes_per_frame
+                    .saturating_sub(self.bytes_started_this_frame),
+            )
+        }
+    }
+
+    // Returns the next thing to write into the upload being filled. partial_image_rank is the rank
+    // of the image being split across uploads, if there is one.
+    pub(super) fn next_step(
+        &mut self,
+        partial_image_rank: Option<UploadRank>,
+        upload_bytes_written: u64,
+    ) -> UploadScheduleStep<RequestT> {
+        loop {
+            if let Some(partial_image_rank) = partial_image_rank {
+                let outranks_pending = self
+                    .pending_uploads
+                    .last()
+                    .map(|x| partial_image_rank > x.rank)
+                    .unwrap_or(true);
+                if outranks_pending {
+                    return UploadScheduleStep::ContinuePartialImage;
+                }
+            }
+
+            let pending_upload = match self.pending_uploads.pop() {
+                Some(pending_upload) => pending_upload,
+                None => return UploadScheduleStep::Stop,
+            };
+
+            // Only one image is split at a time. Another oversized image waits until the current
+            // one finishes, but doesn't hold up smaller requests.
+            if pending_upload.size > self.max_bytes_per_upload
+                && partial_image_rank.is_some()
+                && pending_upload.request.is_image()
+            {
+                self.deferred.push(pending_upload);
+                continue;
+            }
+
+            // At least one request is always started per frame so that requests larger than the
+            // budget make progress
+            let nothing_started = self.bytes_started_this_frame == 0 && upload_bytes_written == 0;
+            if let Some(remaining_frame_budget) = self.remaining_frame_budget() {
+                if pending_upload.size + upload_bytes_written > remaining_frame_budget
+                    && !nothing_started
+                {
+                    log::trace!("Upload budget for this frame is spent");
+                    self.pending_uploads.push(pending_upload);
+                    return UploadScheduleStep::Stop;
+                }
+            }
+
+            return UploadScheduleStep::Start(pending_upload);
+        }
+    }
+
+    // Puts back a request returned by next_step() that could not be written, it will be next
+    pub(super) fn requeue(
+        &mut self,
+        pending_upload: PendingUpload<RequestT>,
+    ) {
+        self.pending_uploads.push(pending_upload);
+    }
+
+    // Called once the upload being filled is complete, with the number of bytes written into it
+    pub(super) fn end_upload(
+        &mut self,
+        upload_bytes_written: u64,
+    ) {
+        self.bytes_started_this_frame += upload_bytes_written;
+
+        // Deferred requests were popped before anything still queued, so they have a higher
+        // priority and go back on the end. The first one popped is pushed last so that it is next.
+        while let Some(pending_upload) = self.deferred.pop() {
+            self.pending_uploads.push(pending_upload);
+        }
+    }
+
+    fn sort_pending_uploads(&mut self) {
+        // Highest rank at the end of the vec so we can pop()
+        self.pending_uploads.sort_by_key(|x| x.rank);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAX_BYTES_PER_UPLOAD: u64 = 100;
+
+    struct TestRequest {
+        id: u64,
+        size: u64,
+        is_image: bool,
+    }
+
+    impl UploadScheduleRequest for TestRequest {
+        fn load_handle(&self) -> LoadHandle {
+            LoadHandle(self.id)
+        }
+
+        fn size(&self) -> u64 {
+            self.size
+        }
+
+        fn is_image(&self) -> bool {
+            self.is_image
+        }
+    }
+
+    fn buffer(
+        id: u64,
+        size: u64,
+    ) -> TestRequest {
+        TestRequest {
+            id,
+            size,
+            is_image: false,
+        }
+    }
+
+    fn image(
+        id: u64,
+        size: u64,
+    ) -> TestRequest {
+        TestRequest {
+            id,
+            size,
+            is_image: true,
+        }
+    }
+
+    // Starts requests until the schedule stops or continues the split image, as if every request fit
+    // in the upload. Returns the ids of the started requests.
+    fn fill_upload(
+        schedule: &mut UploadSchedule<TestRequest>,
+        partial_image_rank: Option<UploadRank>,
+    ) -> Vec<u64> {
+        let mut started = vec![];
+        let mut bytes_written = 0;
+        while let UploadScheduleStep::Start(pending_upload) =
+            schedule.next_step(partial_image_rank, bytes_written)
+        {
+            bytes_written += pending_upload.size;
+            started.push(pending_upload.request.id);
+        }
+
+        schedule.end_upload(bytes_written);
+        started
+    }
+
+    fn start_next(
+        schedule: &mut UploadSchedule<TestRequest>,
+        partial_image_rank: Option<UploadRank>,
+    ) -> PendingUpload<TestRequest> {
+        match schedule.next_step(partial_image_rank, 0) {
+            UploadScheduleStep::Start(pending_upload) => pending_upload,
+            UploadScheduleStep::ContinuePartialImage => panic!("expected a request to start"),
+            UploadScheduleStep::Stop => panic!("expected a request to start"),
+        }
+    }
+
+    #[test]
+    fn test_priority_and_sequence_order() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 0);
+        schedule.set_upload_priority(LoadHandle(4), UploadPriority::High);
+        schedule.push(vec![
+            buffer(1, 10),
+            buffer(2, 10),
+            buffer(3, 10),
+            buffer(4, 10),
+        ]);
+        schedule.push(vec![buffer(5, 10)]);
+
+        // Changing the priority of a queued request moves it
+        schedule.set_upload_priority(LoadHandle(2), UploadPriority::Low);
+        assert_eq!(schedule.len(), 5);
+        assert_eq!(schedule.queued_bytes(), 50);
+
+        assert_eq!(fill_upload(&mut schedule, None), vec![4, 1, 3, 5, 2]);
+        assert!(schedule.is_empty());
+
+        // Priorities apply to later requests for the same load handle until they are reset
+        schedule.push(vec![buffer(6, 10), buffer(2, 10)]);
+        assert_eq!(fill_upload(&mut schedule, None), vec![6, 2]);
+        schedule.set_upload_priority(LoadHandle(2), UploadPriority::Normal);
+        schedule.push(vec![buffer(2, 10), buffer(7, 10), buffer(4, 10)]);
+        assert_eq!(fill_upload(&mut schedule, None), vec![4, 2, 7]);
+
+        // Forgotten priorities no longer apply
+        schedule.retain_upload_priorities(|load_handle| load_handle != LoadHandle(4));
+        schedule.push(vec![buffer(8, 10), buffer(4, 10)]);
+        assert_eq!(fill_upload(&mut schedule, None), vec![8, 4]);
+    }
+
+    #[test]
+    fn test_requeue_keeps_position() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 0);
+        schedule.push(vec![buffer(1, 10), buffer(2, 10)]);
+
+        let pending_upload = start_next(&mut schedule, None);
+        assert_eq!(pending_upload.request.id, 1);
+        schedule.requeue(pending_upload);
+        schedule.end_upload(0);
+
+        assert_eq!(fill_upload(&mut schedule, None), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_oversized_images_deferred_while_splitting() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 0);
+        schedule.set_upload_priority(LoadHandle(1), UploadPriority::High);
+        schedule.push(vec![
+            image(1, 1000),
+            image(2, 1000),
+            buffer(3, 10),
+            image(4, 10),
+            buffer(5, 1000),
+        ]);
+
+        // The first oversized image starts and is split
+        let partial_image = start_next(&mut schedule, None);
+        assert_eq!(partial_image.request.id, 1);
+        let partial_image_rank = Some(partial_image.rank);
+
+        // It outranks everything still queued, so it is continued before anything else
+        assert!(matches!(
+            schedule.next_step(partial_image_rank, 0),
+            UploadScheduleStep::ContinuePartialImage
+        ));
+        schedule.end_upload(0);
+
+        // Once the split image no longer outranks the queue, smaller requests start past the
+        // oversized image waiting for it. Oversized buffers aren't split, so they aren't deferred.
+        schedule.set_upload_priority(LoadHandle(3), UploadPriority::High);
+        let lower_rank = Some(UploadRank {
+            priority: UploadPriority::Low,
+            ..partial_image.rank
+        });
+        assert_eq!(fill_upload(&mut schedule, lower_rank), vec![3, 4, 5]);
+
+        // The deferred image is next once the split image is done
+        assert_eq!(schedule.len(), 1);
+        assert_eq!(fill_upload(&mut schedule, None), vec![2]);
+    }
+
+    #[test]
+    fn test_partial_image_continues_only_when_it_outranks_queue() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 0);
+        schedule.push(vec![image(1, 1000), buffer(2, 10)]);
+        let partial_image_rank = Some(start_next(&mut schedule, None).rank);
+
+        // Same priority, requested earlier
+        assert!(matches!(
+            schedule.next_step(partial_image_rank, 0),
+            UploadScheduleStep::ContinuePartialImage
+        ));
+
+        // A higher priority request starts first, then the split image continues
+        schedule.set_upload_priority(LoadHandle(3), UploadPriority::High);
+        schedule.push(vec![buffer(3, 10)]);
+        assert_eq!(start_next(&mut schedule, partial_image_rank).request.id, 3);
+        assert!(matches!(
+            schedule.next_step(partial_image_rank, 10),
+            UploadScheduleStep::ContinuePartialImage
+        ));
+        schedule.end_upload(10);
+
+        // With nothing queued the split image continues
+        assert_eq!(fill_upload(&mut schedule, None), vec![2]);
+        assert!(matches!(
+            schedule.next_step(partial_image_rank, 0),
+            UploadScheduleStep::ContinuePartialImage
+        ));
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 50);
+        schedule.push(vec![
+            buffer(1, 20),
+            buffer(2, 20),
+            buffer(3, 20),
+            buffer(4, 80),
+            buffer(5, 10),
+        ]);
+
+        // The third request would exceed the budget
+        schedule.begin_frame();
+        assert_eq!(fill_upload(&mut schedule, None), vec![1, 2]);
+        assert_eq!(schedule.remaining_frame_budget(), Some(10));
+
+        // The budget carries across uploads in the same frame. The next request doesn't fit in
+        // what's left, and is not skipped for smaller ones.
+        assert_eq!(fill_upload(&mut schedule, None), Vec::<u64>::new());
+
+        // A request larger than the budget still starts if it's the first one in the frame
+        schedule.begin_frame();
+        assert_eq!(fill_upload(&mut schedule, None), vec![3]);
+        schedule.begin_frame();
+        assert_eq!(fill_upload(&mut schedule, None), vec![4]);
+        assert_eq!(schedule.remaining_frame_budget(), Some(0));
+        schedule.begin_frame();
+        assert_eq!(fill_upload(&mut schedule, None), vec![5]);
+
+        // Zero disables the budget
+        let schedule = UploadSchedule::<TestRequest>::new(MAX_BYTES_PER_UPLOAD, 0);
+        assert_eq!(schedule.remaining_frame_budget(), None);
+    }
+
+    #[test]
+    fn test_retain_uploads() {
+        let mut schedule = UploadSchedule::new(MAX_BYTES_PER_UPLOAD, 0);
+        schedule.set_upload_priority(LoadHandle(3), UploadPriority::High);
+        schedule.push(vec![
+            buffer(1, 10),
+            image(2, 20),
+            buffer(3, 30),
+            buffer(4, 40),
+        ]);
+
+        let cancelled =
+            schedule.retain_uploads(|load_handle| load_handle.0 == 1 || load_handle.0 == 3);
+        let mut cancelled_ids: Vec<_> = cancelled.iter().map(|x| x.request.id).collect();
+        cancelled_ids.sort_unstable();
+        assert_eq!(cancelled_ids, vec![2, 4]);
+
+        assert_eq!(schedule.len(), 2);
+        assert_eq!(schedule.queued_bytes(), 40);
+        assert_eq!(fill_upload(&mut schedule, None), vec![3, 1]);
+    }
+}
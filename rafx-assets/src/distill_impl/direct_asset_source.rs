@@ -0,0 +1,1016 @@
+use distill::core::importer_context::{get_importer_contexts, ImporterContextHandle};
+use distill::core::{
+    ArtifactId, ArtifactMetadata, AssetMetadata, AssetRef, AssetTypeId, AssetUuid, CompressionType,
+};
+use distill::importer::{ImportOp, Importer, ImporterValue, SerdeObj};
+use distill::loader::io::{DataRequest, LoaderIO, MetadataRequest, ResolveRequest};
+use distill::loader::loader::LoaderState;
+use distill::loader::storage::IndirectIdentifier;
+use fnv::{FnvHashMap, FnvHasher};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use type_uuid::{TypeUuid, TypeUuidDynamic};
+
+// Bump this if the layout of the cache files changes
+const CACHE_VERSION: u32 = 2;
+
+// Matches the version the daemon writes into .meta files so the two can share an asset directory
+const SOURCE_METADATA_VERSION: u32 = 2;
+
+type DirectResult<T> = Result<T, String>;
+
+#[derive(Serialize)]
+struct SourceMetadataRef<'a, OptionsT, StateT> {
+    version: u32,
+    importer_options: &'a OptionsT,
+    importer_state: &'a StateT,
+}
+
+#[derive(Deserialize)]
+struct SourceMetadata<OptionsT, StateT> {
+    #[allow(dead_code)]
+    version: u32,
+    importer_options: OptionsT,
+    importer_state: StateT,
+}
+
+// The result of importing a single source file. This is what gets written to the cache.
+#[derive(Serialize, Deserialize)]
+struct ImportedSource {
+    assets: Vec<ImportedSourceAsset>,
+    // Other files the importer read, from the build_deps of the imported assets
+    dependencies: Vec<SourceDependency>,
+}
+
+// The cached import is only used while the dependency's contents hash to the same value
+#[derive(Serialize, Deserialize)]
+struct SourceDependency {
+    path: PathBuf,
+    hash: u64,
+}
+
+impl SourceDependency {
+    fn is_unchanged(&self) -> bool {
+        file_hash(&self.path) == Some(self.hash)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ImportedSourceAsset {
+    id: AssetUuid,
+    type_id: AssetTypeId,
+    search_tags: Vec<(String, Option<String>)>,
+    load_deps: Vec<AssetUuid>,
+    data: Vec<u8>,
+}
+
+// Object-safe wrapper around distill's Importer trait
+trait DirectImporter: Send + Sync {
+    fn importer_type(&self) -> [u8; 16];
+
+    fn version(&self) -> u32;
+
+    fn import(
+        &self,
+        source: &[u8],
+        metadata: Option<&str>,
+    ) -> DirectResult<(ImporterValue, Option<String>)>;
+}
+
+struct DirectImporterImpl<T>(T);
+
+impl<T> DirectImporter for DirectImporterImpl<T>
+where
+    T: Importer + TypeUuid + Send + Sync + 'static,
+    T::Options: Default + Serialize + DeserializeOwned,
+    T::State: Default + Serialize + DeserializeOwned,
+{
+    fn importer_type(&self) -> [u8; 16] {
+        T::UUID
+    }
+
+    fn version(&self) -> u32 {
+        self.0.version()
+    }
+
+    fn import(
+        &self,
+        source: &[u8],
+        metadata: Option<&str>,
+    ) -> DirectResult<(ImporterValue, Option<String>)> {
+        let (options, mut state) = match metadata {
+            Some(metadata) => {
+                match ron::de::from_str::<SourceMetadata<T::Options, T::State>>(metadata) {
+                    Ok(metadata) => (metadata.importer_options, metadata.importer_state),
+                    Err(e) => {
+                        log::warn!("Ignoring .meta file that failed to parse: {:?}", e);
+                        Default::default()
+                    }
+                }
+            }
+            None => Default::default(),
+        };
+
+        let mut op = ImportOp::default();
+        let value = self
+            .0
+            .import(&mut op, &mut &source[..], &options, &mut state)
+            .map_err(|e| format!("{}", e))?;
+
+        // Write the state back so that asset UUIDs are stable across runs
+        let new_metadata = ron::ser::to_string_pretty(
+            &SourceMetadataRef {
+                version: SOURCE_METADATA_VERSION,
+                importer_options: &options,
+                importer_state: &state,
+            },
+            Default::default(),
+        )
+        .map_err(|e| format!("{}", e))?;
+
+        let new_metadata = if Some(new_metadata.as_str()) != metadata {
+            Some(new_metadata)
+        } else {
+            None
+        };
+
+        Ok((value, new_metadata))
+    }
+}
+
+struct SourceFile {
+    path: PathBuf,
+    importer_index: usize,
+    // UUIDs found in the .meta file. These are known without importing the file, which lets us
+    // find the file that holds an asset without importing everything up front.
+    candidate_asset_ids: Vec<AssetUuid>,
+    imported: Option<ImportedSource>,
+    // Set while the file is being imported, to catch files that reference each other
+    importing: bool,
+    // Set if importing the file failed while searching for an asset UUID, so that later searches
+    // don't retry it
+    search_failed: bool,
+}
+
+/// Serves assets straight from the asset directories without a daemon process. Source files are
+/// imported on first request using the registered importers. Import results are cached in
+/// `cache_dir` keyed by a hash of the source file, its .meta file and the importer version, so
+/// later runs only re-import files that changed. Files that imported assets list as path build
+/// dependencies are hashed too, and a change to any of them also re-imports the source file.
+///
+/// Importer state is written to .meta files next to the source files in the same format the daemon
+/// uses, so both can be used on the same asset directories.
+pub struct DirectAssetSource {
+    asset_dirs: Vec<PathBuf>,
+    cache_dir: PathBuf,
+    importers: Vec<Box<dyn DirectImporter>>,
+    importer_extensions: FnvHashMap<String, usize>,
+    sources: Vec<SourceFile>,
+    source_by_path: FnvHashMap<PathBuf, usize>,
+    source_by_asset: FnvHashMap<AssetUuid, usize>,
+    scanned: bool,
+}
+
+impl Default for DirectAssetSource {
+    fn default() -> Self {
+        DirectAssetSource {
+            asset_dirs: vec!["assets".into()],
+            cache_dir: ".assets_cache".into(),
+            importers: Default::default(),
+            importer_extensions: Default::default(),
+            sources: Default::default(),
+            source_by_path: Default::default(),
+            source_by_asset: Default::default(),
+            scanned: false,
+        }
+    }
+}
+
+impl DirectAssetSource {
+    pub fn with_importer<T>(
+        mut self,
+        extension: &str,
+        importer: T,
+    ) -> Self
+    where
+        T: Importer + TypeUuid + Send + Sync + 'static,
+        T::Options: Default + Serialize + DeserializeOwned,
+        T::State: Default + Serialize + DeserializeOwned,
+    {
+        let importer_index = self.importers.len();
+        self.importers.push(Box::new(DirectImporterImpl(importer)));
+        self.importer_extensions
+            .insert(extension.to_lowercase(), importer_index);
+        self
+    }
+
+    pub fn with_asset_dirs(
+        mut self,
+        asset_dirs: Vec<PathBuf>,
+    ) -> Self {
+        self.asset_dirs = asset_dirs;
+        self
+    }
+
+    pub fn with_cache_dir(
+        mut self,
+        cache_dir: PathBuf,
+    ) -> Self {
+        self.cache_dir = cache_dir;
+        self
+    }
+
+    fn scan(&mut self) {
+        if self.scanned {
+            return;
+        }
+
+        self.scanned = true;
+
+        let mut pending_dirs = self.asset_dirs.clone();
+        while let Some(dir) = pending_dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    log::warn!("Could not read asset directory {:?}: {:?}", dir, e);
+                    continue;
+                }
+            };
+
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending_dirs.push(path);
+                    continue;
+                }
+
+                let importer_index = path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .and_then(|extension| {
+                        self.importer_extensions
+                            .get(&extension.to_lowercase())
+                            .copied()
+                    });
+
+                if let Some(importer_index) = importer_index {
+                    self.add_source(path, importer_index);
+                }
+            }
+        }
+
+        log::info!("Found {} asset source files", self.sources.len());
+    }
+
+    fn add_source(
+        &mut self,
+        path: PathBuf,
+        importer_index: usize,
+    ) {
+        let path = path.canonicalize().unwrap_or(path);
+        let source_index = self.sources.len();
+
+        let mut candidate_asset_ids = vec![];
+        if let Ok(metadata) = std::fs::read_to_string(meta_path(&path)) {
+            if let Ok(metadata) =
+                ron::de::from_str::<SourceMetadata<ron::Value, ron::Value>>(&metadata)
+            {
+                collect_asset_ids(&metadata.importer_state, &mut candidate_asset_ids);
+            }
+        }
+
+        for &asset_id in &candidate_asset_ids {
+            self.source_by_asset.insert(asset_id, source_index);
+        }
+
+        self.source_by_path.insert(path.clone(), source_index);
+        self.sources.push(SourceFile {
+            path,
+            importer_index,
+            candidate_asset_ids,
+            imported: None,
+            importing: false,
+            search_failed: false,
+        });
+    }
+
+    fn import_source(
+        &mut self,
+        source_index: usize,
+    ) -> DirectResult<&ImportedSource> {
+        if self.sources[source_index].imported.is_none() {
+            if self.sources[source_index].importing {
+                return Err(format!(
+                    "{:?} is part of a circular reference",
+                    self.sources[source_index].path
+                ));
+            }
+
+            self.sources[source_index].importing = true;
+            let imported = self.load_or_import_source(source_index);
+            self.sources[source_index].importing = false;
+
+            let imported = imported?;
+            for asset in &imported.assets {
+                self.source_by_asset.insert(asset.id, source_index);
+            }
+
+            self.sources[source_index].imported = Some(imported);
+        }
+
+        Ok(self.sources[source_index].imported.as_ref().unwrap())
+    }
+
+    fn load_or_import_source(
+        &mut self,
+        source_index: usize,
+    ) -> DirectResult<ImportedSource> {
+        let path = self.sources[source_index].path.clone();
+        let importer_index = self.sources[source_index].importer_index;
+
+        let source_bytes =
+            std::fs::read(&path).map_err(|e| format!("Could not read {:?}: {:?}", path, e))?;
+        let metadata = std::fs::read_to_string(meta_path(&path)).ok();
+
+        let cache_path = self.cache_path(importer_index, &source_bytes, metadata.as_deref());
+        if let Ok(cached) = std::fs::read(&cache_path) {
+            match bincode::deserialize::<ImportedSource>(&cached) {
+                Ok(imported) => {
+                    if imported
+                        .dependencies
+                        .iter()
+                        .all(|dependency| dependency.is_unchanged())
+                    {
+                        return Ok(imported);
+                    }
+
+                    log::info!("A file used by {:?} changed since it was imported", path);
+                }
+                Err(e) => log::warn!("Ignoring corrupt cache file {:?}: {:?}", cache_path, e),
+            }
+        }
+
+        log::info!("Importing {:?}", path);
+
+        // Handles are only deserialized/serialized correctly while distill's importer contexts
+        // are active
+        let mut contexts: Vec<Box<dyn ImporterContextHandle>> = get_importer_contexts().collect();
+
+        let importer = &self.importers[importer_index];
+        let (value, new_metadata) = in_importer_contexts(&contexts, || {
+            importer.import(&source_bytes, metadata.as_deref())
+        })?;
+
+        // Serialize once to find out what each asset references. Path references must be turned
+        // into UUIDs before the final serialization since the loader only understands UUIDs.
+        let mut asset_refs = Vec::with_capacity(value.assets.len());
+        for asset in &value.assets {
+            let mut refs = serialize_asset(&mut contexts, asset.id, &*asset.asset_data)?.1;
+            refs.extend(asset.load_deps.iter().cloned());
+            asset_refs.push(refs);
+        }
+
+        let mut imported = ImportedSource {
+            assets: Vec::with_capacity(value.assets.len()),
+            dependencies: vec![],
+        };
+
+        // Build dependencies are files the importer read besides the source file. Only path
+        // references can be checked for changes without importing them.
+        for asset in &value.assets {
+            for build_dep in &asset.build_deps {
+                if let AssetRef::Path(dependency_path) = build_dep {
+                    let dependency_path = self
+                        .find_file(dependency_path, path.parent())
+                        .ok_or_else(|| {
+                            format!(
+                                "{:?} depends on {:?} which does not exist",
+                                path, dependency_path
+                            )
+                        })?;
+
+                    if imported
+                        .dependencies
+                        .iter()
+                        .any(|dependency| dependency.path == dependency_path)
+                    {
+                        continue;
+                    }
+
+                    let hash = file_hash(&dependency_path)
+                        .ok_or_else(|| format!("Could not read {:?}", dependency_path))?;
+                    imported.dependencies.push(SourceDependency {
+                        path: dependency_path,
+                        hash,
+                    });
+                }
+            }
+        }
+
+        for (asset, refs) in value.assets.iter().zip(asset_refs) {
+            let mut load_deps = vec![];
+            for asset_ref in refs {
+                let uuid = match &asset_ref {
+                    AssetRef::Uuid(uuid) => *uuid,
+                    AssetRef::Path(ref_path) => {
+                        let uuid = self.resolve_path_ref(&path, ref_path)?;
+                        for context in &mut contexts {
+                            context.resolve_ref(&asset_ref, uuid);
+                        }
+                        uuid
+                    }
+                };
+
+                if !load_deps.contains(&uuid) {
+                    load_deps.push(uuid);
+                }
+            }
+
+            let data = serialize_asset(&mut contexts, asset.id, &*asset.asset_data)?.0;
+            imported.assets.push(ImportedSourceAsset {
+                id: asset.id,
+                type_id: AssetTypeId(asset.asset_data.uuid()),
+                search_tags: asset.search_tags.clone(),
+                load_deps,
+                data,
+            });
+        }
+
+        // Write the .meta file first. If it changed, the cache key changes with it, so the cache
+        // is keyed by the new metadata.
+        let metadata = match new_metadata {
+            Some(new_metadata) => {
+                if let Err(e) = std::fs::write(meta_path(&path), &new_metadata) {
+                    log::warn!("Could not write .meta file for {:?}: {:?}", path, e);
+                }
+                Some(new_metadata)
+            }
+            None => metadata,
+        };
+
+        let cache_path = self.cache_path(importer_index, &source_bytes, metadata.as_deref());
+        let write_result = std::fs::create_dir_all(&self.cache_dir)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|_| bincode::serialize(&imported).map_err(|e| format!("{:?}", e)))
+            .and_then(|bytes| std::fs::write(&cache_path, bytes).map_err(|e| format!("{:?}", e)));
+        if let Err(e) = write_result {
+            log::warn!("Could not write cache file {:?}: {}", cache_path, e);
+        }
+
+        Ok(imported)
+    }
+
+    fn cache_path(
+        &self,
+        importer_index: usize,
+        source_bytes: &[u8],
+        metadata: Option<&str>,
+    ) -> PathBuf {
+        let importer = &self.importers[importer_index];
+
+        let mut hasher = FnvHasher::default();
+        hasher.write_u32(CACHE_VERSION);
+        hasher.write(&importer.importer_type());
+        hasher.write_u32(importer.version());
+        hasher.write(source_bytes);
+        hasher.write(metadata.unwrap_or_default().as_bytes());
+
+        self.cache_dir.join(format!("{:016x}.bin", hasher.finish()))
+    }
+
+    // Path references are relative to the file that contains them, or to one of the asset dirs.
+    // A path refers to the first asset produced by the file.
+    fn resolve_path_ref(
+        &mut self,
+        referencing_path: &Path,
+        ref_path: &Path,
+    ) -> DirectResult<AssetUuid> {
+        let source_index = self
+            .find_source(ref_path, referencing_path.parent())
+            .ok_or_else(|| {
+                format!(
+                    "{:?} references {:?} which is not an importable asset",
+                    referencing_path, ref_path
+                )
+            })?;
+
+        self.import_source(source_index)?
+            .assets
+            .first()
+            .map(|asset| asset.id)
+            .ok_or_else(|| format!("{:?} did not produce any assets", ref_path))
+    }
+
+    fn find_source(
+        &self,
+        path: &Path,
+        relative_to: Option<&Path>,
+    ) -> Option<usize> {
+        self.path_candidates(path, relative_to)
+            .into_iter()
+            .find_map(|candidate| {
+                let candidate = candidate.canonicalize().ok()?;
+                self.source_by_path.get(&candidate).copied()
+            })
+    }
+
+    // Like find_source, but for any file rather than only files that have an importer
+    fn find_file(
+        &self,
+        path: &Path,
+        relative_to: Option<&Path>,
+    ) -> Option<PathBuf> {
+        self.path_candidates(path, relative_to)
+            .into_iter()
+            .find_map(|candidate| candidate.canonicalize().ok())
+    }
+
+    fn path_candidates(
+        &self,
+        path: &Path,
+        relative_to: Option<&Path>,
+    ) -> Vec<PathBuf> {
+        let mut candidates = vec![];
+        if path.is_absolute() {
+            candidates.push(path.to_path_buf());
+        } else {
+            candidates.extend(relative_to.map(|dir| dir.join(path)));
+            candidates.extend(self.asset_dirs.iter().map(|dir| dir.join(path)));
+        }
+
+        candidates
+    }
+
+    // Files that have never been imported have no .meta file yet, so the only way to learn their
+    // asset UUIDs is to import them. This is only done when a UUID isn't known from any .meta file,
+    // and stops at the first file that produces it.
+    fn find_source_by_asset(
+        &mut self,
+        asset_id: &AssetUuid,
+    ) -> Option<usize> {
+        if let Some(&source_index) = self.source_by_asset.get(asset_id) {
+            return Some(source_index);
+        }
+
+        for source_index in 0..self.sources.len() {
+            let source = &self.sources[source_index];
+            if source.imported.is_some()
+                || source.search_failed
+                || !source.candidate_asset_ids.is_empty()
+            {
+                continue;
+            }
+
+            if let Err(e) = self.import_source(source_index) {
+                log::error!(
+                    "Failed to import {:?}: {}",
+                    self.sources[source_index].path,
+                    e
+                );
+                self.sources[source_index].search_failed = true;
+                continue;
+            }
+
+            if let Some(&source_index) = self.source_by_asset.get(asset_id) {
+                return Some(source_index);
+            }
+        }
+
+        None
+    }
+
+    fn asset(
+        &mut self,
+        asset_id: &AssetUuid,
+    ) -> DirectResult<&ImportedSourceAsset> {
+        let source_index = self
+            .find_source_by_asset(asset_id)
+            .ok_or_else(|| format!("Asset {:?} was not found in any source file", asset_id))?;
+
+        self.import_source(source_index)?
+            .assets
+            .iter()
+            .find(|asset| asset.id == *asset_id)
+            .ok_or_else(|| format!("Asset {:?} is no longer produced by its source", asset_id))
+    }
+}
+
+impl LoaderIO for DirectAssetSource {
+    fn get_asset_metadata_with_dependencies(
+        &mut self,
+        request: MetadataRequest,
+    ) {
+        self.scan();
+
+        let requested_assets: Vec<AssetUuid> = request.requested_assets().cloned().collect();
+        let mut metadata = Vec::with_capacity(requested_assets.len());
+        for asset_id in requested_assets {
+            match self.asset(&asset_id) {
+                Ok(asset) => metadata.push(artifact_metadata(asset)),
+                Err(e) => log::error!("{}", e),
+            }
+        }
+
+        request.complete(metadata);
+    }
+
+    fn get_asset_candidates(
+        &mut self,
+        requests: Vec<ResolveRequest>,
+    ) {
+        self.scan();
+
+        for request in requests {
+            let path = match request.identifier() {
+                IndirectIdentifier::Path(path) => PathBuf::from(path),
+                IndirectIdentifier::PathWithTagOrType(path, _) => PathBuf::from(path),
+            };
+
+            let imported = match self.find_source(&path, None) {
+                Some(source_index) => match self.import_source(source_index) {
+                    Ok(imported) => Some(imported),
+                    Err(e) => {
+                        log::error!("{}", e);
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            match imported {
+                Some(imported) => {
+                    let asset_metadata = imported
+                        .assets
+                        .iter()
+                        .map(|asset| AssetMetadata {
+                            id: asset.id,
+                            search_tags: asset.search_tags.clone(),
+                            build_pipeline: None,
+                            artifact: Some(artifact_metadata(asset)),
+                        })
+                        .collect();
+
+                    request.complete(vec![(path, asset_metadata)]);
+                }
+                None => request.complete(vec![]),
+            }
+        }
+    }
+
+    fn get_artifacts(
+        &mut self,
+        requests: Vec<DataRequest>,
+    ) {
+        self.scan();
+
+        for request in requests {
+            match self.asset(&request.asset_id()) {
+                Ok(asset) => request.complete(asset.data.clone()),
+                Err(e) => request.error(std::io::Error::new(std::io::ErrorKind::Other, e)),
+            }
+        }
+    }
+
+    fn tick(
+        &mut self,
+        _loader: &mut LoaderState,
+    ) {
+    }
+}
+
+fn meta_path(path: &Path) -> PathBuf {
+    let mut meta_path = path.as_os_str().to_os_string();
+    meta_path.push(".meta");
+    PathBuf::from(meta_path)
+}
+
+fn file_hash(path: &Path) -> Option<u64> {
+    let bytes = std::fs::read(path).ok()?;
+    let mut hasher = FnvHasher::default();
+    hasher.write(&bytes);
+    Some(hasher.finish())
+}
+
+fn collect_asset_ids(
+    value: &ron::Value,
+    asset_ids: &mut Vec<AssetUuid>,
+) {
+    match value {
+        ron::Value::String(s) => {
+            if let Ok(uuid) = uuid::Uuid::parse_str(s) {
+                asset_ids.push(AssetUuid(*uuid.as_bytes()));
+            }
+        }
+        ron::Value::Option(Some(value)) => collect_asset_ids(value, asset_ids),
+        ron::Value::Seq(values) => {
+            for value in values {
+                collect_asset_ids(value, asset_ids);
+            }
+        }
+        ron::Value::Map(map) => {
+            for (key, value) in map.iter() {
+                collect_asset_ids(key, asset_ids);
+                collect_asset_ids(value, asset_ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn artifact_metadata(asset: &ImportedSourceAsset) -> ArtifactMetadata {
+    let mut hasher = FnvHasher::default();
+    hasher.write(&asset.id.0);
+    hasher.write(&asset.data);
+    let hash = hasher.finish();
+
+    ArtifactMetadata {
+        id: ArtifactId(hash),
+        asset_id: asset.id,
+        hash,
+        load_deps: asset
+            .load_deps
+            .iter()
+            .map(|uuid| AssetRef::Uuid(*uuid))
+            .collect(),
+        build_deps: vec![],
+        type_id: asset.type_id,
+        compression: CompressionType::None,
+        compressed_size: None,
+        uncompressed_size: Some(asset.data.len() as u64),
+    }
+}
+
+// Serializes asset data the way the loader expects it. Handles are written through distill's
+// importer contexts, which also report every asset the data references.
+fn serialize_asset(
+    contexts: &mut [Box<dyn ImporterContextHandle>],
+    asset_id: AssetUuid,
+    asset_data: &dyn SerdeObj,
+) -> DirectResult<(Vec<u8>, Vec<AssetRef>)> {
+    for context in contexts.iter_mut() {
+        context.begin_serialize_asset(asset_id);
+    }
+
+    let data = in_importer_contexts(contexts, || {
+        bincode::serialize(asset_data).map_err(|e| format!("{:?}", e))
+    });
+
+    let mut refs = vec![];
+    for context in contexts.iter_mut() {
+        refs.extend(context.end_serialize_asset(asset_id));
+    }
+
+    Ok((data?, refs))
+}
+
+fn in_importer_contexts<R>(
+    contexts: &[Box<dyn ImporterContextHandle>],
+    f: impl FnOnce() -> R,
+) -> R {
+    let mut result = None;
+    {
+        let mut future: Pin<Box<dyn Future<Output = ()> + '_>> = Box::pin(async {
+            result = Some(f());
+        });
+        for context in contexts {
+            future = context.scope(future);
+        }
+        futures_lite::future::block_on(future);
+    }
+
+    result.unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use distill::importer::ImportedAsset;
+    use std::io::Read;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(TypeUuid, Serialize, Deserialize, Debug, PartialEq)]
+    #[uuid = "027297c2-d0b4-40b0-b0d1-293573c01e32"]
+    struct TestAssetData {
+        contents: String,
+        suffix: String,
+    }
+
+    #[derive(TypeUuid, Serialize, Deserialize, Default, Clone, Debug)]
+    #[uuid = "757c9faa-4144-4647-8945-2f5399722474"]
+    #[serde(default)]
+    struct TestImporterOptions {
+        suffix: String,
+    }
+
+    #[derive(TypeUuid, Serialize, Deserialize, Default)]
+    #[uuid = "5f969133-9410-4a72-b41e-ed73a7e375f6"]
+    struct TestImporterState(Option<AssetUuid>);
+
+    // Produces one asset per file. Lines starting with "ref " are path references to other assets
+    // and lines starting with "dep " are build dependencies on other files.
+    #[derive(TypeUuid)]
+    #[uuid = "37bcf36b-e497-46bf-bbe9-2c1cb4e2c18e"]
+    struct TestImporter(Arc<AtomicUsize>);
+
+    impl Importer for TestImporter {
+        fn version_static() -> u32
+        where
+            Self: Sized,
+        {
+            1
+        }
+
+        fn version(&self) -> u32 {
+            Self::version_static()
+        }
+
+        type Options = TestImporterOptions;
+
+        type State = TestImporterState;
+
+        fn import(
+            &self,
+            _op: &mut ImportOp,
+            source: &mut dyn Read,
+            options: &Self::Options,
+            state: &mut Self::State,
+        ) -> distill::importer::Result<ImporterValue> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+
+            let id = state
+                .0
+                .unwrap_or_else(|| AssetUuid(*uuid::Uuid::new_v4().as_bytes()));
+            *state = TestImporterState(Some(id));
+
+            let mut contents = String::new();
+            source.read_to_string(&mut contents)?;
+
+            let mut load_deps = vec![];
+            let mut build_deps = vec![];
+            for line in contents.lines() {
+                if let Some(path) = line.strip_prefix("ref ") {
+                    load_deps.push(AssetRef::Path(path.into()));
+                } else if let Some(path) = line.strip_prefix("dep ") {
+                    build_deps.push(AssetRef::Path(path.into()));
+                }
+            }
+
+            Ok(ImporterValue {
+                assets: vec![ImportedAsset {
+                    id,
+                    search_tags: vec![],
+                    build_deps,
+                    load_deps,
+                    build_pipeline: None,
+                    asset_data: Box::new(TestAssetData {
+                        contents,
+                        suffix: options.suffix.clone(),
+                    }),
+                }],
+            })
+        }
+    }
+
+    struct TestDir {
+        path: PathBuf,
+        import_count: Arc<AtomicUsize>,
+    }
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "rafx_direct_asset_source_{}_{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(path.join("assets")).unwrap();
+
+            TestDir {
+                path,
+                import_count: Default::default(),
+            }
+        }
+
+        fn write(
+            &self,
+            file_name: &str,
+            contents: &str,
+        ) {
+            std::fs::write(self.path.join("assets").join(file_name), contents).unwrap();
+        }
+
+        fn import_count(&self) -> usize {
+            self.import_count.load(Ordering::SeqCst)
+        }
+
+        // A new source each time, as if the game was restarted, so that only the cache persists
+        fn asset_source(&self) -> DirectAssetSource {
+            let mut asset_source = DirectAssetSource::default()
+                .with_importer("txt", TestImporter(self.import_count.clone()))
+                .with_asset_dirs(vec![self.path.join("assets")])
+                .with_cache_dir(self.path.join("cache"));
+            asset_source.scan();
+            asset_source
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    fn import(
+        asset_source: &mut DirectAssetSource,
+        file_name: &str,
+    ) -> DirectResult<(AssetUuid, Vec<AssetUuid>, TestAssetData)> {
+        let source_index = asset_source
+            .find_source(Path::new(file_name), None)
+            .unwrap();
+        let asset = &asset_source.import_source(source_index)?.assets[0];
+        let data = bincode::deserialize(&asset.data).unwrap();
+        Ok((asset.id, asset.load_deps.clone(), data))
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let test_dir = TestDir::new("cache");
+        test_dir.write("a.txt", "first\ndep b.bin");
+        test_dir.write("b.bin", "dependency");
+
+        let (asset_id, _, data) = import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(data.contents, "first\ndep b.bin");
+        assert_eq!(test_dir.import_count(), 1);
+
+        // Nothing changed, so the cached import is used. The asset keeps its UUID from the .meta
+        // file that the first import wrote.
+        let (cached_asset_id, _, _) = import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(cached_asset_id, asset_id);
+        assert_eq!(test_dir.import_count(), 1);
+
+        // The source file changed
+        test_dir.write("a.txt", "second\ndep b.bin");
+        let (reimported_asset_id, _, data) = import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(reimported_asset_id, asset_id);
+        assert_eq!(data.contents, "second\ndep b.bin");
+        assert_eq!(test_dir.import_count(), 2);
+
+        // The .meta file changed
+        let meta_path = meta_path(&test_dir.path.join("assets").join("a.txt"));
+        let metadata = std::fs::read_to_string(&meta_path).unwrap();
+        let metadata = metadata.replace("suffix: \"\"", "suffix: \"!\"");
+        std::fs::write(&meta_path, metadata).unwrap();
+        let (_, _, data) = import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(data.suffix, "!");
+        assert_eq!(test_dir.import_count(), 3);
+
+        // A build dependency changed
+        test_dir.write("b.bin", "changed dependency");
+        import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(test_dir.import_count(), 4);
+
+        import(&mut test_dir.asset_source(), "a.txt").unwrap();
+        assert_eq!(test_dir.import_count(), 4);
+    }
+
+    #[test]
+    fn test_path_ref_resolves_to_uuid() {
+        let test_dir = TestDir::new("path_ref");
+        test_dir.write("a.txt", "ref b.txt");
+        test_dir.write("b.txt", "referenced");
+
+        let mut asset_source = test_dir.asset_source();
+        let (_, load_deps, _) = import(&mut asset_source, "a.txt").unwrap();
+        let (b_asset_id, _, _) = import(&mut asset_source, "b.txt").unwrap();
+        assert_eq!(load_deps, vec![b_asset_id]);
+        assert_eq!(test_dir.import_count(), 2);
+
+        // The referenced asset can be found by the UUID the reference resolved to
+        assert_eq!(asset_source.asset(&b_asset_id).unwrap().id, b_asset_id);
+
+        // References to files that don't exist fail the import
+        test_dir.write("c.txt", "ref missing.txt");
+        let mut asset_source = test_dir.asset_source();
+        assert!(import(&mut asset_source, "c.txt").is_err());
+    }
+
+    #[test]
+    fn test_circular_reference() {
+        let test_dir = TestDir::new("circular");
+        test_dir.write("a.txt", "ref b.txt");
+        test_dir.write("b.txt", "ref a.txt");
+
+        let mut asset_source = test_dir.asset_source();
+        let error = import(&mut asset_source, "a.txt").err().unwrap();
+        assert!(error.contains("circular reference"), "{}", error);
+
+        // The failed import doesn't leave either file marked as importing
+        assert!(asset_source.sources.iter().all(|source| !source.importing));
+    }
+}
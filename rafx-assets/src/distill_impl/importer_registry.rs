@@ -0,0 +1,84 @@
+use super::DirectAssetSource;
+use distill::daemon::AssetDaemon;
+use distill::importer::{BoxedImporter, Importer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use type_uuid::TypeUuid;
+
+/// Whatever importers are being registered with: an in-process asset daemon or a
+/// `DirectAssetSource`. Lets the same list of importers be registered with either one.
+pub enum ImporterRegistry {
+    Daemon(AssetDaemon),
+    Direct(DirectAssetSource),
+}
+
+impl ImporterRegistry {
+    pub fn with_importer<T>(
+        self,
+        extension: &str,
+        importer: T,
+    ) -> Self
+    where
+        T: Importer + BoxedImporter + TypeUuid + Send + Sync + 'static,
+        T::Options: Default + Serialize + DeserializeOwned,
+        T::State: Default + Serialize + DeserializeOwned,
+    {
+        match self {
+            ImporterRegistry::Daemon(asset_daemon) => {
+                ImporterRegistry::Daemon(asset_daemon.with_importer(extension, importer))
+            }
+            ImporterRegistry::Direct(asset_source) => {
+                ImporterRegistry::Direct(asset_source.with_importer(extension, importer))
+            }
+        }
+    }
+
+    /// Registers the importers for every asset type in this crate
+    pub fn with_default_importers(self) -> Self {
+        use crate::assets::*;
+
+        let mut registry = self
+            .with_importer("sampler", SamplerImporter)
+            .with_importer("material", MaterialImporter)
+            .with_importer("materialinstance", MaterialInstanceImporter)
+            .with_importer("compute", ComputePipelineImporter)
+            .with_importer("rendergraph", RenderGraphImporter)
+            .with_importer("cookedshaderpackage", ShaderImporterCooked)
+            .with_importer("png", ImageImporter(image::ImageFormat::Png))
+            .with_importer("jpg", ImageImporter(image::ImageFormat::Jpeg))
+            .with_importer("jpeg", ImageImporter(image::ImageFormat::Jpeg))
+            .with_importer("tga", ImageImporter(image::ImageFormat::Tga))
+            .with_importer("tif", ImageImporter(image::ImageFormat::Tiff))
+            .with_importer("tiff", ImageImporter(image::ImageFormat::Tiff))
+            .with_importer("bmp", ImageImporter(image::ImageFormat::Bmp))
+            .with_importer("hdr", HdrImageImporter)
+            .with_importer("ktx2", Ktx2ImageImporter)
+            .with_importer("dds", DdsImageImporter);
+
+        #[cfg(feature = "basis-universal")]
+        {
+            registry = registry.with_importer("basis", BasisImageImporter);
+        }
+
+        #[cfg(feature = "exr")]
+        {
+            registry = registry.with_importer("exr", ExrImageImporter);
+        }
+
+        registry
+    }
+
+    pub fn into_asset_daemon(self) -> Option<AssetDaemon> {
+        match self {
+            ImporterRegistry::Daemon(asset_daemon) => Some(asset_daemon),
+            ImporterRegistry::Direct(_) => None,
+        }
+    }
+
+    pub fn into_direct_asset_source(self) -> Option<DirectAssetSource> {
+        match self {
+            ImporterRegistry::Daemon(_) => None,
+            ImporterRegistry::Direct(asset_source) => Some(asset_source),
+        }
+    }
+}
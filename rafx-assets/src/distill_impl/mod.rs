@@ -7,27 +7,22 @@ pub use asset_resource::*;
 pub mod asset_storage;
 pub use asset_storage::*;
 
-pub fn default_daemon() -> distill::daemon::AssetDaemon {
-    use crate::assets::*;
+pub mod direct_asset_source;
+pub use direct_asset_source::DirectAssetSource;
 
-    let mut daemon = distill::daemon::AssetDaemon::default()
-        .with_importer("sampler", SamplerImporter)
-        .with_importer("material", MaterialImporter)
-        .with_importer("materialinstance", MaterialInstanceImporter)
-        .with_importer("compute", ComputePipelineImporter)
-        .with_importer("cookedshaderpackage", ShaderImporterCooked)
-        .with_importer("png", ImageImporter(image::ImageFormat::Png))
-        .with_importer("jpg", ImageImporter(image::ImageFormat::Jpeg))
-        .with_importer("jpeg", ImageImporter(image::ImageFormat::Jpeg))
-        .with_importer("tga", ImageImporter(image::ImageFormat::Tga))
-        .with_importer("tif", ImageImporter(image::ImageFormat::Tiff))
-        .with_importer("tiff", ImageImporter(image::ImageFormat::Tiff))
-        .with_importer("bmp", ImageImporter(image::ImageFormat::Bmp));
+pub mod importer_registry;
+pub use importer_registry::ImporterRegistry;
 
-    #[cfg(feature = "basis-universal")]
-    {
-        daemon = daemon.with_importer("basis", BasisImageImporter);
-    }
+pub fn default_daemon() -> distill::daemon::AssetDaemon {
+    ImporterRegistry::Daemon(Default::default())
+        .with_default_importers()
+        .into_asset_daemon()
+        .unwrap()
+}
 
-    daemon
+pub fn default_direct_asset_source() -> DirectAssetSource {
+    ImporterRegistry::Direct(Default::default())
+        .with_default_importers()
+        .into_direct_asset_source()
+        .unwrap()
 }
@@ -83,6 +83,77 @@ impl GpuImageData {
         bytes_required
     }
 
+    // Splits tightly packed data into layers and mip levels. Each layer is stored one after another
+    // with its mip levels from largest to smallest.
+    pub fn new_precomputed(
+        width: u32,
+        height: u32,
+        format: RafxFormat,
+        layer_count: u32,
+        mip_count: u32,
+        data: &[u8],
+    ) -> Self {
+        let mut offset = 0;
+        let mut layers = Vec::with_capacity(layer_count as usize);
+        for _ in 0..layer_count {
+            let mut mip_levels = Vec::with_capacity(mip_count as usize);
+            for mip in 0..mip_count {
+                let mip_width = (width >> mip).max(1);
+                let mip_height = (height >> mip).max(1);
+                let size = Self::mip_level_size_in_bytes(format, mip_width, mip_height);
+                mip_levels.push(GpuImageDataMipLevel {
+                    width: mip_width,
+                    height: mip_height,
+                    data: data[offset..offset + size].to_vec(),
+                });
+                offset += size;
+            }
+
+            layers.push(GpuImageDataLayer::new(mip_levels));
+        }
+
+        GpuImageData::new(layers, format)
+    }
+
+    // The size of a tightly packed mip level with the given dimensions
+    pub fn mip_level_size_in_bytes(
+        format: RafxFormat,
+        width: u32,
+        height: u32,
+    ) -> usize {
+        let block_width = format.block_width_in_pixels();
+        let block_height = format.block_height_in_pixels();
+        let blocks_wide = (width + block_width - 1) / block_width;
+        let blocks_high = (height + block_height - 1) / block_height;
+        blocks_wide as usize * blocks_high as usize * format.block_or_pixel_size_in_bytes() as usize
+    }
+
+    // A copy of the image that starts at the given mip level, i.e. to upload a lower resolution
+    // version of it
+    pub fn copy_mip_levels_from(
+        &self,
+        first_mip: usize,
+    ) -> GpuImageData {
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                GpuImageDataLayer::new(
+                    layer.mip_levels[first_mip..]
+                        .iter()
+                        .map(|level| GpuImageDataMipLevel {
+                            width: level.width,
+                            height: level.height,
+                            data: level.data.clone(),
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        GpuImageData::new(layers, self.format)
+    }
+
     pub fn verify_state(&self) {
         let first_layer = &self.layers[0];
         let first_level = &first_layer.mip_levels[0];
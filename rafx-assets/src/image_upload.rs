@@ -34,14 +34,10 @@ pub fn enqueue_load_image(
     image_data: &GpuImageData,
     params: ImageUploadParams,
 ) -> Result<RafxTexture, RafxUploadError> {
-    // All images must have identical mip level count, sizes, etc.
-    #[cfg(debug_assertions)]
-    image_data.verify_state();
-
     //
     // Determine the total amount of data we need to upload and verify there is enough space
     //
-    let bytes_required = image_data.total_size(IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT as u64);
+    let bytes_required = image_upload_size(image_data, &params);
 
     let has_space_available = upload.has_space_available(
         bytes_required as usize,
@@ -53,6 +49,162 @@ pub fn enqueue_load_image(
         Err(RafxUploadError::BufferFull)?;
     }
 
+    let mut partial_upload = begin_partial_image_upload(device_context, image_data, &params)?;
+    let complete = enqueue_partial_image_upload(upload, &mut partial_upload, image_data, &params)?;
+    assert!(complete);
+
+    Ok(partial_upload.texture)
+}
+
+// An image that is copied into its texture over one or more uploads. Used for images that are too
+// large to fit in a single staging buffer. Layers and mip levels (subresources) are copied in order
+// and a single subresource is never split, so the largest mip level must fit in a staging buffer.
+pub struct PartialImageUpload {
+    texture: RafxTexture,
+    layer_count: u32,
+    mip_count: u32,
+    next_subresource: usize,
+}
+
+impl PartialImageUpload {
+    pub fn texture(&self) -> &RafxTexture {
+        &self.texture
+    }
+
+    pub fn into_texture(self) -> RafxTexture {
+        self.texture
+    }
+
+    // Subresources are ordered by destination layer, then mip level
+    pub fn subresource_count(
+        &self,
+        image_data: &GpuImageData,
+    ) -> usize {
+        self.layer_count as usize * image_data.layers[0].mip_levels.len()
+    }
+
+    pub fn remaining_size(
+        &self,
+        image_data: &GpuImageData,
+        params: &ImageUploadParams,
+    ) -> u64 {
+        subresources_size(
+            image_data,
+            params,
+            self.next_subresource,
+            self.subresource_count(image_data),
+        )
+    }
+}
+
+// The staging buffer space needed to upload the whole image with the given params. This can be
+// larger than GpuImageData::total_size() if a layer swizzle references a layer more than once.
+pub fn image_upload_size(
+    image_data: &GpuImageData,
+    params: &ImageUploadParams,
+) -> u64 {
+    let layer_count = params
+        .layer_swizzle
+        .map(|x| x.len())
+        .unwrap_or_else(|| image_data.layers.len());
+
+    subresources_size(
+        image_data,
+        params,
+        0,
+        layer_count * image_data.layers[0].mip_levels.len(),
+    )
+}
+
+fn subresources_size(
+    image_data: &GpuImageData,
+    params: &ImageUploadParams,
+    first_subresource: usize,
+    end_subresource: usize,
+) -> u64 {
+    (first_subresource..end_subresource)
+        .map(|subresource| {
+            let (src_layer_index, level_index) =
+                subresource_source(image_data, params, subresource);
+            rafx_base::memory::round_size_up_to_alignment_u64(
+                image_data.layers[src_layer_index].mip_levels[level_index]
+                    .data
+                    .len() as u64,
+                IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT,
+            )
+        })
+        .sum()
+}
+
+// Returns (src layer, mip level) for the given subresource
+fn subresource_source(
+    image_data: &GpuImageData,
+    params: &ImageUploadParams,
+    subresource: usize,
+) -> (usize, usize) {
+    let level_count = image_data.layers[0].mip_levels.len();
+    let dst_layer_index = subresource / level_count;
+    let level_index = subresource % level_count;
+
+    let src_layer_index = if let Some(layer_swizzle) = params.layer_swizzle {
+        layer_swizzle[dst_layer_index] as usize
+    } else {
+        dst_layer_index
+    };
+
+    (src_layer_index, level_index)
+}
+
+// The size of the largest subresource, which is the smallest staging buffer that can hold the
+// image when it is split across uploads
+pub fn largest_image_subresource_size(image_data: &GpuImageData) -> u64 {
+    image_data
+        .layers
+        .iter()
+        .flat_map(|layer| layer.mip_levels.iter())
+        .map(|level| {
+            rafx_base::memory::round_size_up_to_alignment_u64(
+                level.data.len() as u64,
+                IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT,
+            )
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Validates the image data and creates the texture it will be copied into
+pub fn begin_partial_image_upload(
+    device_context: &RafxDeviceContext,
+    image_data: &GpuImageData,
+    params: &ImageUploadParams,
+) -> Result<PartialImageUpload, RafxUploadError> {
+    // All images must have identical mip level count, sizes, etc.
+    #[cfg(debug_assertions)]
+    image_data.verify_state();
+
+    //
+    // Catch data that doesn't match the format, i.e. 8-bit data uploaded as a floating point format
+    //
+    for layer in &image_data.layers {
+        for level in &layer.mip_levels {
+            let expected_size =
+                GpuImageData::mip_level_size_in_bytes(image_data.format, level.width, level.height);
+            if level.data.len() != expected_size {
+                Err(RafxUploadError::Other(
+                    format!(
+                        "Image data for a {}x{} {:?} image is {} bytes, expected {} bytes",
+                        level.width,
+                        level.height,
+                        image_data.format,
+                        level.data.len(),
+                        expected_size
+                    )
+                    .into(),
+                ))?;
+            }
+        }
+    }
+
     //
     // Determine mip count
     //
@@ -65,23 +217,6 @@ pub fn enqueue_load_image(
         image_data.layers[0].mip_levels.len() as u32
     };
 
-    //
-    // Push all image layers/levels into the staging buffer, keeping note of offsets within the
-    // buffer where each resource is stored
-    //
-    let mut layer_offsets = Vec::default();
-    for layer in &image_data.layers {
-        let mut level_offsets = Vec::default();
-        for level in &layer.mip_levels {
-            let offset = upload.push(
-                &level.data,
-                IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT as usize,
-            )?;
-            level_offsets.push(offset);
-        }
-        layer_offsets.push(level_offsets);
-    }
-
     // If we are swizzling layers, we create a layer per layer_swizzle entry. Otherwise, we use
     // the number of layers in the image data
     let layer_count = params
@@ -107,51 +242,111 @@ pub fn enqueue_load_image(
         dimensions: RafxTextureDimensions::Dim2D,
     })?;
 
+    Ok(PartialImageUpload {
+        texture,
+        layer_count,
+        mip_count,
+        next_subresource: 0,
+    })
+}
+
+// Copies as many of the remaining subresources as fit in the upload. Returns Ok(true) once the last
+// subresource is written, at which point the texture is transitioned for use on the dst queue.
+// Returns BufferFull if not even one subresource fit.
+pub fn enqueue_partial_image_upload(
+    upload: &mut RafxTransferUpload,
+    partial_upload: &mut PartialImageUpload,
+    image_data: &GpuImageData,
+    params: &ImageUploadParams,
+) -> Result<bool, RafxUploadError> {
+    let texture = &partial_upload.texture;
+    let subresource_count = partial_upload.subresource_count(image_data);
+
+    //
+    // Push image layers/levels into the staging buffer until it is full, keeping note of offsets
+    // within the buffer where each resource is stored
+    //
+    let first_subresource = partial_upload.next_subresource;
+    let mut copies = Vec::default();
+    while partial_upload.next_subresource < subresource_count {
+        let subresource = partial_upload.next_subresource;
+        let (src_layer_index, level_index) = subresource_source(image_data, params, subresource);
+        let level = &image_data.layers[src_layer_index].mip_levels[level_index];
+
+        let offset = match upload.push(
+            &level.data,
+            IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT as usize,
+        ) {
+            Ok(offset) => offset,
+            Err(RafxUploadError::BufferFull) => break,
+            Err(e) => Err(e)?,
+        };
+
+        let dst_layer_index = subresource / image_data.layers[0].mip_levels.len();
+        copies.push((offset, dst_layer_index, level_index));
+        partial_upload.next_subresource += 1;
+    }
+
+    if copies.is_empty() {
+        Err(RafxUploadError::BufferFull)?;
+    }
+
     //
     // Write into the transfer command buffer
-    // - transition destination memory to receive the data
+    // - transition destination memory to receive the data (first upload only)
     // - copy the data
-    // - transition the destination to the graphics queue
+    // - transition the destination to the graphics queue (last upload only)
     //
 
-    upload
-        .transfer_command_buffer()
-        .cmd_resource_barrier(
-            &[],
-            &[RafxTextureBarrier {
-                texture: &texture,
-                src_state: RafxResourceState::UNDEFINED,
-                dst_state: RafxResourceState::COPY_DST,
-                queue_transition: RafxBarrierQueueTransition::None,
-                array_slice: None,
-                mip_slice: None,
-            }],
-        )
-        .unwrap();
+    if first_subresource == 0 {
+        upload
+            .transfer_command_buffer()
+            .cmd_resource_barrier(
+                &[],
+                &[RafxTextureBarrier {
+                    texture,
+                    src_state: RafxResourceState::UNDEFINED,
+                    dst_state: RafxResourceState::COPY_DST,
+                    queue_transition: RafxBarrierQueueTransition::None,
+                    array_slice: None,
+                    mip_slice: None,
+                }],
+            )
+            .unwrap();
+    }
 
-    for dst_layer_index in 0..layer_count {
-        let src_layer_index = if let Some(layer_swizzle) = params.layer_swizzle {
-            layer_swizzle[dst_layer_index as usize] as usize
-        } else {
-            dst_layer_index as usize
-        };
+    for (buffer_offset, dst_layer_index, level_index) in copies {
+        upload
+            .transfer_command_buffer()
+            .cmd_copy_buffer_to_texture(
+                upload.staging_buffer(),
+                texture,
+                &RafxCmdCopyBufferToTextureParams {
+                    buffer_offset,
+                    array_layer: dst_layer_index as u16,
+                    mip_level: level_index as u8,
+                },
+            )
+            .unwrap();
+    }
 
-        for level_index in 0..image_data.layers[src_layer_index].mip_levels.len() {
-            upload
-                .transfer_command_buffer()
-                .cmd_copy_buffer_to_texture(
-                    upload.staging_buffer(),
-                    &texture,
-                    &RafxCmdCopyBufferToTextureParams {
-                        buffer_offset: layer_offsets[src_layer_index][level_index],
-                        array_layer: dst_layer_index as u16,
-                        mip_level: level_index as u8,
-                    },
-                )
-                .unwrap();
-        }
+    if partial_upload.next_subresource < subresource_count {
+        log::debug!(
+            "upload image {}x{} format {:?} subresources {}..{} of {}",
+            image_data.width,
+            image_data.height,
+            image_data.format,
+            first_subresource,
+            partial_upload.next_subresource,
+            subresource_count
+        );
+
+        return Ok(false);
     }
 
+    let layer_count = partial_upload.layer_count;
+    let mip_count = partial_upload.mip_count;
+
     log::debug!(
         "upload image {}x{} format {:?} layers: {} levels: {} generate mips: {} resource type: {:?}",
         image_data.width,
@@ -170,7 +365,7 @@ pub fn enqueue_load_image(
         upload.transfer_command_buffer().cmd_resource_barrier(
             &[],
             &[RafxTextureBarrier {
-                texture: &texture,
+                texture,
                 src_state: RafxResourceState::COPY_DST,
                 dst_state: RafxResourceState::COPY_SRC,
                 queue_transition: RafxBarrierQueueTransition::ReleaseTo(
@@ -187,7 +382,7 @@ pub fn enqueue_load_image(
         upload.dst_command_buffer().cmd_resource_barrier(
             &[],
             &[RafxTextureBarrier {
-                texture: &texture,
+                texture,
                 src_state: RafxResourceState::COPY_DST,
                 dst_state: RafxResourceState::COPY_SRC,
                 queue_transition: RafxBarrierQueueTransition::AcquireFrom(
@@ -198,7 +393,7 @@ pub fn enqueue_load_image(
             }],
         )?;
 
-        rafx_api::extra::mipmaps::generate_mipmaps(upload.dst_command_buffer(), &texture)?;
+        rafx_api::extra::mipmaps::generate_mipmaps(upload.dst_command_buffer(), texture)?;
 
         //
         // Transition everything to the final layout
@@ -206,7 +401,7 @@ pub fn enqueue_load_image(
         upload.dst_command_buffer().cmd_resource_barrier(
             &[],
             &[RafxTextureBarrier {
-                texture: &texture,
+                texture,
                 src_state: RafxResourceState::COPY_SRC,
                 dst_state: RafxResourceState::SHADER_RESOURCE,
                 queue_transition: RafxBarrierQueueTransition::None,
@@ -218,7 +413,7 @@ pub fn enqueue_load_image(
         upload.transfer_command_buffer().cmd_resource_barrier(
             &[],
             &[RafxTextureBarrier {
-                texture: &texture,
+                texture,
                 src_state: RafxResourceState::COPY_DST,
                 dst_state: RafxResourceState::SHADER_RESOURCE,
                 queue_transition: RafxBarrierQueueTransition::ReleaseTo(
@@ -232,7 +427,7 @@ pub fn enqueue_load_image(
         upload.dst_command_buffer().cmd_resource_barrier(
             &[],
             &[RafxTextureBarrier {
-                texture: &texture,
+                texture,
                 src_state: RafxResourceState::COPY_DST,
                 dst_state: RafxResourceState::SHADER_RESOURCE,
                 queue_transition: RafxBarrierQueueTransition::AcquireFrom(
@@ -244,7 +439,7 @@ pub fn enqueue_load_image(
         )?;
     }
 
-    Ok(texture)
+    Ok(true)
 }
 
 pub fn load_image_blocking(
@@ -255,7 +450,7 @@ pub fn load_image_blocking(
     image_data: &GpuImageData,
     params: ImageUploadParams,
 ) -> Result<RafxTexture, RafxUploadError> {
-    let total_size = image_data.total_size(IMAGE_UPLOAD_REQUIRED_SUBRESOURCE_ALIGNMENT);
+    let total_size = image_upload_size(image_data, &params);
     if upload_buffer_max_size < total_size {
         Err(RafxUploadError::BufferFull)?;
     }
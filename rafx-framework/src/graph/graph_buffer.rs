@@ -2,7 +2,7 @@ use crate::graph::{RenderGraphNodeId, RenderGraphResourceName};
 use rafx_api::RafxResourceType;
 
 /// Unique ID for a particular usage (read or write) of a specific buffer
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct RenderGraphBufferUsageId(pub(super) usize);
 
 /// An ID for a buffer used within the graph between passes
@@ -10,7 +10,7 @@ pub struct RenderGraphBufferUsageId(pub(super) usize);
 pub struct VirtualBufferId(pub(super) usize);
 
 /// An ID for a buffer allocation (possibly reused)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PhysicalBufferId(pub(super) usize);
 
 /// Unique ID provided for any buffer registered as an output buffer
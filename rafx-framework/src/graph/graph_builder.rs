@@ -84,6 +84,13 @@ pub struct RenderGraphBuilder {
         FnvHashMap<RenderGraphNodeId, RenderGraphNodeVisitNodeCallback>,
     pub(super) render_phase_dependencies:
         FnvHashMap<RenderGraphNodeId, FnvHashSet<RenderPhaseIndex>>,
+
+    /// If true, the plan records a RenderGraphPlanDescription that can be exported as DOT or JSON
+    pub(super) describe_plan: bool,
+
+    /// If true, nodes with different attachments may be merged into a single renderpass with
+    /// multiple subpasses, and nodes may read input attachments
+    pub(super) supports_subpasses: bool,
 }
 
 impl RenderGraphBuilder {
@@ -555,6 +562,26 @@ impl RenderGraphBuilder {
         usage
     }
 
+    /// Reads an attachment written by an earlier node as an input attachment. The input attachment
+    /// index is the order in which this is called for the node. The image is read directly from
+    /// tile memory if the nodes are merged into the same renderpass, which requires a backend that
+    /// supports subpasses.
+    pub fn read_input_attachment(
+        &mut self,
+        node: RenderGraphNodeId,
+        image: RenderGraphImageUsageId,
+        mut constraint: RenderGraphImageConstraint,
+        view_options: RenderGraphImageViewOptions,
+    ) -> RenderGraphImageUsageId {
+        constraint.resource_type |= RafxResourceType::INPUT_ATTACHMENT;
+
+        // Add the read to the graph
+        let usage = self.add_image_read(node, image, constraint, view_options);
+
+        self.node_mut(node).input_attachments.push(usage);
+        usage
+    }
+
     pub fn set_output_image(
         &mut self,
         image_id: RenderGraphImageUsageId,
@@ -930,6 +957,17 @@ impl RenderGraphBuilder {
             .insert(PhaseT::render_phase_index());
     }
 
+    pub fn add_render_phase_dependency_by_index(
+        &mut self,
+        node_id: RenderGraphNodeId,
+        render_phase_index: RenderPhaseIndex,
+    ) {
+        self.render_phase_dependencies
+            .entry(node_id)
+            .or_default()
+            .insert(render_phase_index);
+    }
+
     //
     // Get nodes
     //
@@ -1070,7 +1108,30 @@ impl RenderGraphBuilder {
         self.buffer_resources[version.index].versions[version.version].create_usage
     }
 
-    pub fn build_plan(self) -> RenderGraphPlan {
+    /// Records the decisions made while planning so that the plan can be exported with
+    /// RenderGraphPlan::to_dot() or to_json(). Off by default because graphs are usually planned
+    /// every frame and the description is only needed for debugging.
+    pub fn set_describe_plan(
+        &mut self,
+        describe_plan: bool,
+    ) {
+        self.describe_plan = describe_plan;
+    }
+
+    /// Allows nodes with different attachments to be merged into a single renderpass with multiple
+    /// subpasses, and allows nodes to read input attachments. Set this from
+    /// RafxDeviceInfo::supports_subpasses. (PreparedRenderGraph::new() does this automatically.)
+    pub fn set_supports_subpasses(
+        &mut self,
+        supports_subpasses: bool,
+    ) {
+        self.supports_subpasses = supports_subpasses;
+    }
+
+    /// Determines execution order, resource specifications, and barriers for the graph. Returns
+    /// an error if the graph is not valid (for example, if it has a cycle or if the constraints
+    /// on an image conflict).
+    pub fn build_plan(self) -> RenderGraphPlanResult<RenderGraphPlan> {
         profiling::scope!("Build Plan");
         RenderGraphPlan::new(self)
     }
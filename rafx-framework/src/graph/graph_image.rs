@@ -1,8 +1,9 @@
 use super::*;
 use rafx_api::{RafxExtents3D, RafxFormat, RafxResourceType, RafxSampleCount, RafxTextureBindType};
+use serde::Serialize;
 
 /// Unique ID for a particular usage (read or write) of a specific image
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct RenderGraphImageUsageId(pub(super) usize);
 
 /// An ID for an image used within the graph between passes
@@ -10,7 +11,7 @@ pub struct RenderGraphImageUsageId(pub(super) usize);
 pub struct VirtualImageId(pub(super) usize);
 
 /// An ID for an image allocation (possibly reused)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PhysicalImageId(pub(super) usize);
 
 /// An ID for an image view allocation (possibly reused)
@@ -62,7 +63,7 @@ pub enum RenderGraphImageUser {
     Output(RenderGraphOutputImageId),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum RenderGraphImageExtents {
     MatchSurface,
     // (width, height, depth)
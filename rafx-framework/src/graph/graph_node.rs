@@ -140,6 +140,10 @@ pub struct RenderGraphNode {
     pub(super) resolve_attachments: Vec<Option<RenderGraphPassResolveAttachmentInfo>>,
 
     pub(super) sampled_images: Vec<RenderGraphImageUsageId>,
+
+    // Attachments of earlier nodes in the same renderpass that are read as input attachments,
+    // indexed by input attachment index
+    pub(super) input_attachments: Vec<RenderGraphImageUsageId>,
 }
 
 impl std::fmt::Debug for RenderGraphNode {
@@ -160,6 +164,7 @@ impl std::fmt::Debug for RenderGraphNode {
             .field("depth_attachment", &self.depth_attachment)
             .field("resolve_attachments", &self.resolve_attachments)
             .field("sampled_images", &self.sampled_images)
+            .field("input_attachments", &self.input_attachments)
             .finish()
     }
 }
@@ -185,6 +190,7 @@ impl RenderGraphNode {
             depth_attachment: Default::default(),
             resolve_attachments: Default::default(),
             sampled_images: Default::default(),
+            input_attachments: Default::default(),
         }
     }
 
@@ -6,7 +6,7 @@ use crate::GraphicsPipelineRenderTargetMeta;
 use fnv::FnvHashMap;
 use rafx_api::{
     RafxColorClearValue, RafxDepthStencilClearValue, RafxFormat, RafxLoadOp, RafxResourceState,
-    RafxSampleCount, RafxStoreOp,
+    RafxSampleCount, RafxStoreOp, RafxSubpassDef,
 };
 
 /// Information provided per image used in a pass to properly synchronize access to it from
@@ -142,13 +142,31 @@ pub struct PrepassBufferBarrier {
     pub new_state: RafxResourceState,
 }
 
+/// The attachments a node uses within a renderpass. All values are indexes into
+/// RenderGraphRenderPass::attachments
+#[derive(Debug)]
+pub struct RenderGraphSubpass {
+    pub(super) node: RenderGraphNodeId,
+    // Indexed by the node's color attachment index
+    pub(super) color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS],
+    pub(super) depth_attachment: Option<usize>,
+    // Indexed by the node's input attachment index
+    pub(super) input_attachments: Vec<usize>,
+}
+
 /// Metadata required to create a renderpass
 #[derive(Debug)]
 pub struct RenderGraphRenderPass {
-    pub(super) node_id: RenderGraphNodeId,
+    // Nodes that are executed within the renderpass, in execution order. Consecutive nodes may be
+    // merged into a single renderpass, each node being a subpass.
+    pub(super) nodes: Vec<RenderGraphNodeId>,
+    pub(super) subpasses: Vec<RenderGraphSubpass>,
     pub(super) attachments: Vec<RenderGraphPassAttachment>,
 
-    pub(super) color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS], // could ref back to node
+    // The color targets of the renderpass, in the order they are bound. These are the color
+    // attachments of all subpasses and any color images read as input attachments.
+    pub(super) color_attachments: Vec<usize>,
+    // Indexed by the color attachment index of the (only) node. Passes with resolves are not merged.
     pub(super) resolve_attachments: [Option<usize>; MAX_RESOLVE_ATTACHMENTS],
     pub(super) depth_attachment: Option<usize>,
 
@@ -170,10 +188,10 @@ pub enum RenderGraphPass {
 }
 
 impl RenderGraphPass {
-    pub fn node(&self) -> RenderGraphNodeId {
+    pub fn nodes(&self) -> &[RenderGraphNodeId] {
         match self {
-            RenderGraphPass::Renderpass(renderpass) => renderpass.node_id,
-            RenderGraphPass::Compute(compute_pass) => compute_pass.node,
+            RenderGraphPass::Renderpass(renderpass) => &renderpass.nodes,
+            RenderGraphPass::Compute(compute_pass) => std::slice::from_ref(&compute_pass.node),
         }
     }
 
@@ -215,14 +233,18 @@ pub struct RenderGraphDepthStencilRenderTarget {
 }
 
 pub struct RenderGraphOutputRenderPass {
-    pub(super) node_id: RenderGraphNodeId,
+    pub(super) nodes: Vec<RenderGraphNodeId>,
     pub(super) pre_pass_barrier: Option<PrepassBarrier>,
     pub(super) post_pass_barrier: Option<PostpassBarrier>,
     pub(super) debug_name: Option<RenderGraphNodeName>,
     pub(super) attachment_images: Vec<PhysicalImageViewId>,
     pub(super) color_render_targets: Vec<RenderGraphColorRenderTarget>,
     pub(super) depth_stencil_render_target: Option<RenderGraphDepthStencilRenderTarget>,
-    pub(super) render_target_meta: GraphicsPipelineRenderTargetMeta,
+    // Empty if all nodes draw to the same attachments, in which case they run one after another
+    // in a single subpass
+    pub(super) subpasses: Vec<RafxSubpassDef>,
+    // Indexed the same as nodes
+    pub(super) render_target_metas: Vec<GraphicsPipelineRenderTargetMeta>,
 }
 
 impl std::fmt::Debug for RenderGraphOutputRenderPass {
@@ -233,6 +255,7 @@ impl std::fmt::Debug for RenderGraphOutputRenderPass {
         f.debug_struct("RenderGraphOutputRenderPass")
             //.field("description", &self.description)
             .field("attachment_images", &self.attachment_images)
+            .field("subpasses", &self.subpasses)
             //.field("extents", &self.extents)
             .finish()
     }
@@ -253,10 +276,10 @@ pub enum RenderGraphOutputPass {
 }
 
 impl RenderGraphOutputPass {
-    pub fn node(&self) -> RenderGraphNodeId {
+    pub fn nodes(&self) -> &[RenderGraphNodeId] {
         match self {
-            RenderGraphOutputPass::Renderpass(pass) => pass.node_id,
-            RenderGraphOutputPass::Compute(pass) => pass.node,
+            RenderGraphOutputPass::Renderpass(pass) => &pass.nodes,
+            RenderGraphOutputPass::Compute(pass) => std::slice::from_ref(&pass.node),
         }
     }
 
@@ -7,7 +7,10 @@ use crate::render_features::RenderPhaseIndex;
 use crate::{BufferResource, GraphicsPipelineRenderTargetMeta};
 use crate::{ImageViewResource, ResourceArc};
 use fnv::{FnvHashMap, FnvHashSet};
-use rafx_api::{RafxFormat, RafxLoadOp, RafxResourceState, RafxSampleCount, RafxStoreOp};
+use rafx_api::{
+    RafxFormat, RafxLoadOp, RafxResourceState, RafxResourceType, RafxSampleCount, RafxStoreOp,
+    RafxSubpassDef, RafxSubpassInputAttachment,
+};
 
 // Recursively called to topologically sort the nodes to determine execution order. See
 // determine_node_order which kicks this off.
@@ -19,10 +22,10 @@ fn visit_node(
     visiting: &mut Vec<bool>,
     visiting_stack: &mut Vec<RenderGraphNodeId>,
     ordered_list: &mut Vec<RenderGraphNodeId>,
-) {
+) -> RenderGraphPlanResult<()> {
     // This node is already visited and inserted into ordered_list
     if visited[node_id.0] {
-        return;
+        return Ok(());
     }
 
     // This node is already being visited higher up in the stack. This indicates a cycle in the
@@ -33,7 +36,18 @@ fn visit_node(
         for v in visiting_stack.iter().rev() {
             log::trace!("{:?}", graph.node(*v));
         }
-        panic!("Graph has a cycle");
+
+        // The cycle is the portion of the stack starting from the first visit of this node
+        let cycle_start = visiting_stack
+            .iter()
+            .position(|x| *x == node_id)
+            .unwrap_or(0);
+        return Err(RenderGraphPlanError::Cycle {
+            nodes: visiting_stack[cycle_start..]
+                .iter()
+                .map(|x| RenderGraphPlanErrorNode::new(graph, *x))
+                .collect(),
+        });
     }
 
     // When we enter the node, mark the node as being in-progress of being visited to help
@@ -59,7 +73,7 @@ fn visit_node(
             visiting,
             visiting_stack,
             ordered_list,
-        );
+        )?;
     }
 
     for modify in &node.image_modifies {
@@ -71,7 +85,7 @@ fn visit_node(
             visiting,
             visiting_stack,
             ordered_list,
-        );
+        )?;
     }
 
     for sampled_image in &node.sampled_images {
@@ -83,7 +97,7 @@ fn visit_node(
             visiting,
             visiting_stack,
             ordered_list,
-        );
+        )?;
     }
 
     for read in &node.buffer_reads {
@@ -95,7 +109,7 @@ fn visit_node(
             visiting,
             visiting_stack,
             ordered_list,
-        );
+        )?;
     }
 
     for modify in &node.buffer_modifies {
@@ -107,7 +121,7 @@ fn visit_node(
             visiting,
             visiting_stack,
             ordered_list,
-        );
+        )?;
     }
 
     // All our pre-requisites were visited, so it's now safe to push this node onto the
@@ -119,6 +133,8 @@ fn visit_node(
     //log::trace!("  End visit {:?}", node_id);
     visiting_stack.pop();
     visiting[node_id.0] = false;
+
+    Ok(())
 }
 
 //
@@ -126,7 +142,9 @@ fn visit_node(
 // by following the graph from the outputs backwards.
 //
 #[profiling::function]
-fn determine_node_order(graph: &RenderGraphBuilder) -> Vec<RenderGraphNodeId> {
+fn determine_node_order(
+    graph: &RenderGraphBuilder
+) -> RenderGraphPlanResult<Vec<RenderGraphNodeId>> {
     // As we depth-first traverse nodes, mark them as visiting and push them onto this stack.
     // We will use this to detect and print out cycles
     let mut visiting = vec![false; graph.nodes.len()];
@@ -155,7 +173,7 @@ fn determine_node_order(graph: &RenderGraphBuilder) -> Vec<RenderGraphNodeId> {
             &mut visiting,
             &mut visiting_stack,
             &mut ordered_list,
-        );
+        )?;
     }
 
     // Iterate all the buffers we need to output. This will visit all the nodes we need to execute,
@@ -178,10 +196,10 @@ fn determine_node_order(graph: &RenderGraphBuilder) -> Vec<RenderGraphNodeId> {
             &mut visiting,
             &mut visiting_stack,
             &mut ordered_list,
-        );
+        )?;
     }
 
-    ordered_list
+    Ok(ordered_list)
 }
 
 /// The specification for the image by image usage
@@ -219,7 +237,7 @@ impl DetermineConstraintsResult {
 fn determine_constraints(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
-) -> DetermineConstraintsResult {
+) -> RenderGraphPlanResult<DetermineConstraintsResult> {
     let mut image_version_states: FnvHashMap<RenderGraphImageUsageId, RenderGraphImageConstraint> =
         Default::default();
 
@@ -370,11 +388,22 @@ fn determine_constraints(
 
             //let image = graph.image_version_info(image_modify.input);
             //log::trace!("  Modify image {:?} {:?}", image_modify.input, graph.image_resource(image_modify.input).name);
-            let input_state = image_version_states
-                .entry(graph.image_version_create_usage(image_modify.input))
-                .or_default();
+            let input_create_usage = graph.image_version_create_usage(image_modify.input);
+            let input_state = image_version_states.entry(input_create_usage).or_default();
             let mut image_modify_constraint = image_modify.constraint.clone();
 
+            // The node modifies the image in place, so it can't require anything that conflicts
+            // with the image it receives
+            if !image_modify_constraint.can_merge(&input_state) {
+                return Err(RenderGraphPlanError::image_conflict(
+                    graph,
+                    input_create_usage,
+                    image_modify.input,
+                    &input_state,
+                    &image_modify.constraint,
+                ));
+            }
+
             // Merge the input image constraints with this node's constraints
             image_modify_constraint.partial_merge(&input_state);
 
@@ -402,11 +431,22 @@ fn determine_constraints(
 
             //let buffer = graph.buffer_version_info(buffer_modify.input);
             //log::trace!("  Modify buffer {:?} {:?}", buffer_modify.input, graph.buffer_resource(buffer_modify.input).name);
-            let input_state = buffer_version_states
-                .entry(graph.buffer_version_create_usage(buffer_modify.input))
-                .or_default();
+            let input_create_usage = graph.buffer_version_create_usage(buffer_modify.input);
+            let input_state = buffer_version_states.entry(input_create_usage).or_default();
             let mut buffer_modify_constraint = buffer_modify.constraint.clone();
 
+            // The node modifies the buffer in place, so it can't require anything that conflicts
+            // with the buffer it receives
+            if !buffer_modify_constraint.can_merge(&input_state) {
+                return Err(RenderGraphPlanError::buffer_conflict(
+                    graph,
+                    input_create_usage,
+                    buffer_modify.input,
+                    &input_state,
+                    &buffer_modify.constraint,
+                ));
+            }
+
             // Merge the input buffer constraints with this node's constraints
             buffer_modify_constraint.partial_merge(&input_state);
 
@@ -506,14 +546,9 @@ fn determine_constraints(
             if let Some(spec) = image_read_constraint.try_convert_to_specification() {
                 image_version_states.insert(image_read.image, spec.into());
             } else {
-                panic!(
-                    "Not enough information in the graph to determine the specification for image {:?} {:?} being read by node {:?} {:?}. Constraints are: {:?}",
-                    image_read.image,
-                    graph.image_resource(image_read.image).name,
-                    node.id(),
-                    node.name(),
-                    image_version_states.get(&image_read.image)
-                );
+                return Err(RenderGraphPlanError::ImageSpecificationMissing {
+                    image: RenderGraphPlanErrorImage::new(graph, image_read.image),
+                });
             }
         }
 
@@ -546,14 +581,9 @@ fn determine_constraints(
             if let Some(spec) = buffer_read_constraint.try_convert_to_specification() {
                 buffer_version_states.insert(buffer_read.buffer, spec.into());
             } else {
-                panic!(
-                    "Not enough information in the graph to determine the specification for buffer {:?} {:?} being read by node {:?} {:?}. Constraints are: {:?}",
-                    buffer_read.buffer,
-                    graph.buffer_resource(buffer_read.buffer).name,
-                    node.id(),
-                    node.name(),
-                    buffer_version_states.get(&buffer_read.buffer)
-                );
+                return Err(RenderGraphPlanError::BufferSpecificationMissing {
+                    buffer: RenderGraphPlanErrorBuffer::new(graph, buffer_read.buffer),
+                });
             }
         }
 
@@ -610,18 +640,28 @@ fn determine_constraints(
 
     let mut image_specs = FnvHashMap::default();
     for (k, v) in image_version_states {
-        image_specs.insert(k, v.try_convert_to_specification().unwrap());
+        let spec = v.try_convert_to_specification().ok_or_else(|| {
+            RenderGraphPlanError::ImageSpecificationMissing {
+                image: RenderGraphPlanErrorImage::new(graph, k),
+            }
+        })?;
+        image_specs.insert(k, spec);
     }
 
     let mut buffer_specs = FnvHashMap::default();
     for (k, v) in buffer_version_states {
-        buffer_specs.insert(k, v.try_convert_to_specification().unwrap());
+        let spec = v.try_convert_to_specification().ok_or_else(|| {
+            RenderGraphPlanError::BufferSpecificationMissing {
+                buffer: RenderGraphPlanErrorBuffer::new(graph, k),
+            }
+        })?;
+        buffer_specs.insert(k, spec);
     }
 
-    DetermineConstraintsResult {
+    Ok(DetermineConstraintsResult {
         images: image_specs,
         buffers: buffer_specs,
-    }
+    })
 }
 
 //
@@ -634,7 +674,7 @@ fn insert_resolves(
     graph: &mut RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
     constraint_results: &mut DetermineConstraintsResult,
-) {
+) -> RenderGraphPlanResult<()> {
     log::trace!("Insert resolves in graph where necessary");
     for node_id in node_execution_order {
         let mut resolves_to_add = Vec::default();
@@ -650,7 +690,11 @@ fn insert_resolves(
                 if let Some(write_image) = color_attachment.write_image {
                     //let write_version = graph.image_usages[write_image.0].version;
                     // Skip if it's not an MSAA image
-                    let write_spec = constraint_results.image_specification(write_image).unwrap();
+                    let write_spec = constraint_results
+                        .image_specification(write_image)
+                        .ok_or_else(|| RenderGraphPlanError::ImageSpecificationMissing {
+                            image: RenderGraphPlanErrorImage::new(graph, write_image),
+                        })?;
                     if write_spec.samples == RafxSampleCount::SampleCount1 {
                         log::trace!("      already non-MSAA");
                         continue;
@@ -674,8 +718,11 @@ fn insert_resolves(
                             usage_index,
                             graph.image_usages[read_usage.0].usage_type
                         );
-                        let read_spec =
-                            constraint_results.image_specification(*read_usage).unwrap();
+                        let read_spec = constraint_results
+                            .image_specification(*read_usage)
+                            .ok_or_else(|| RenderGraphPlanError::ImageSpecificationMissing {
+                                image: RenderGraphPlanErrorImage::new(graph, *read_usage),
+                            })?;
                         if *read_spec == *write_spec {
                             continue;
                         } else if *read_spec == resolve_spec {
@@ -728,6 +775,8 @@ fn insert_resolves(
             }
         }
     }
+
+    Ok(())
 }
 
 /// Assignment of usages to actual images. This allows a single image to be passed through a
@@ -750,7 +799,7 @@ fn assign_virtual_resources(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
     constraint_results: &mut DetermineConstraintsResult,
-) -> AssignVirtualResourcesResult {
+) -> RenderGraphPlanResult<AssignVirtualResourcesResult> {
     #[derive(Default)]
     struct VirtualImageIdAllocator {
         next_id: usize,
@@ -836,11 +885,22 @@ fn assign_virtual_resources(
         //
         for image_modify in &node.image_modifies {
             // The virtual image in the read portion of a image_modify must also be the write image.
-            // The format of the input/output is guaranteed to match
-            assert_eq!(
-                constraint_results.image_specification(image_modify.input),
-                constraint_results.image_specification(image_modify.output)
-            );
+            // The format of the input/output must match
+            let input_spec = constraint_results
+                .image_specification(image_modify.input)
+                .unwrap();
+            let output_spec = constraint_results
+                .image_specification(image_modify.output)
+                .unwrap();
+            if input_spec != output_spec {
+                return Err(RenderGraphPlanError::image_conflict(
+                    graph,
+                    image_modify.input,
+                    image_modify.output,
+                    &input_spec.clone().into(),
+                    &output_spec.clone().into(),
+                ));
+            }
 
             // Assign the image
             let virtual_image = *image_usage_to_virtual.get(&image_modify.input).unwrap();
@@ -860,11 +920,22 @@ fn assign_virtual_resources(
         //
         for buffer_modify in &node.buffer_modifies {
             // The virtual buffer in the read portion of a buffer_modify must also be the write buffer.
-            // The format of the input/output is guaranteed to match
-            assert_eq!(
-                constraint_results.buffer_specification(buffer_modify.input),
-                constraint_results.buffer_specification(buffer_modify.output)
-            );
+            // The format of the input/output must match
+            let input_spec = constraint_results
+                .buffer_specification(buffer_modify.input)
+                .unwrap();
+            let output_spec = constraint_results
+                .buffer_specification(buffer_modify.output)
+                .unwrap();
+            if input_spec != output_spec {
+                return Err(RenderGraphPlanError::buffer_conflict(
+                    graph,
+                    buffer_modify.input,
+                    buffer_modify.output,
+                    &input_spec.clone().into(),
+                    &output_spec.clone().into(),
+                ));
+            }
 
             // Assign the buffer
             let virtual_buffer = *buffer_usage_to_virtual.get(&buffer_modify.input).unwrap();
@@ -921,7 +992,9 @@ fn assign_virtual_resources(
 
             let written_spec = constraint_results
                 .image_specification(written_image)
-                .unwrap();
+                .ok_or_else(|| RenderGraphPlanError::ImageSpecificationMissing {
+                    image: RenderGraphPlanErrorImage::new(graph, written_image),
+                })?;
 
             for usage_resource_id in &written_image_version_info.read_usages {
                 let usage_spec = match constraint_results.image_specification(*usage_resource_id) {
@@ -954,13 +1027,15 @@ fn assign_virtual_resources(
                     let overwritten_image =
                         image_usage_to_virtual.insert(*usage_resource_id, write_virtual_image);
 
-                    assert!(overwritten_image.is_none());
+                    if overwritten_image.is_some() {
+                        return Err(RenderGraphPlanError::ImageUsageConflict {
+                            image: RenderGraphPlanErrorImage::new(graph, *usage_resource_id),
+                        });
+                    }
                 } else {
-                    // allocate new image
-                    let virtual_image = virtual_image_id_allocator.allocate();
+                    // This would require allocating a new image and copying into it
                     log::trace!(
-                        "    Allocate image {:?} for {:?} ({:?} -> {:?})  (specifications_match match: {} is_read_or_exclusive_write: {})",
-                        virtual_image,
+                        "    Can't share image for {:?} ({:?} -> {:?})  (specifications_match match: {} is_read_or_exclusive_write: {})",
                         usage_resource_id,
                         write_type,
                         read_type,
@@ -971,16 +1046,24 @@ fn assign_virtual_resources(
                         log::trace!("      written: {:?}", written_spec);
                         log::trace!("      usage  : {:?}", usage_spec);
                     }
-                    let overwritten_image =
-                        image_usage_to_virtual.insert(*usage_resource_id, virtual_image);
 
-                    assert!(overwritten_image.is_none());
-
-                    //TODO: One issue (aside from not doing any blits right now) is that images created in this way
-                    // aren't included in the assign_physical_images logic
-                    println!("      written: {:?}", written_spec);
-                    println!("      usage  : {:?}", usage_spec);
-                    panic!("Render graph does not currently support blit from one image to another to fix image compatibility");
+                    //TODO: Render graph does not currently support blit from one image to another
+                    // to fix image compatibility. (If it did, images created in this way would
+                    // need to be included in the assign_physical_images logic)
+                    if !specifications_match {
+                        return Err(RenderGraphPlanError::image_conflict(
+                            graph,
+                            written_image,
+                            *usage_resource_id,
+                            &written_spec.clone().into(),
+                            &usage_spec.clone().into(),
+                        ));
+                    } else {
+                        return Err(RenderGraphPlanError::WriteToReadOnlyImage {
+                            written: RenderGraphPlanErrorImage::new(graph, written_image),
+                            used: RenderGraphPlanErrorImage::new(graph, *usage_resource_id),
+                        });
+                    }
                 }
             }
         }
@@ -1011,7 +1094,9 @@ fn assign_virtual_resources(
 
             let written_spec = constraint_results
                 .buffer_specification(written_buffer)
-                .unwrap();
+                .ok_or_else(|| RenderGraphPlanError::BufferSpecificationMissing {
+                    buffer: RenderGraphPlanErrorBuffer::new(graph, written_buffer),
+                })?;
 
             for usage_resource_id in &written_buffer_version_info.read_usages {
                 let usage_spec = match constraint_results.buffer_specification(*usage_resource_id) {
@@ -1044,13 +1129,15 @@ fn assign_virtual_resources(
                     let overwritten_buffer =
                         buffer_usage_to_virtual.insert(*usage_resource_id, write_virtual_buffer);
 
-                    assert!(overwritten_buffer.is_none());
+                    if overwritten_buffer.is_some() {
+                        return Err(RenderGraphPlanError::BufferUsageConflict {
+                            buffer: RenderGraphPlanErrorBuffer::new(graph, *usage_resource_id),
+                        });
+                    }
                 } else {
-                    // allocate new buffer
-                    let virtual_buffer = virtual_buffer_id_allocator.allocate();
+                    // This would require allocating a new buffer and copying into it
                     log::trace!(
-                        "    Allocate buffer {:?} for {:?} ({:?} -> {:?})  (specifications_match match: {} is_read_or_exclusive_write: {})",
-                        virtual_buffer,
+                        "    Can't share buffer for {:?} ({:?} -> {:?})  (specifications_match match: {} is_read_or_exclusive_write: {})",
                         usage_resource_id,
                         write_type,
                         read_type,
@@ -1061,24 +1148,262 @@ fn assign_virtual_resources(
                         log::trace!("      written: {:?}", written_spec);
                         log::trace!("      usage  : {:?}", usage_spec);
                     }
-                    let overwritten_buffer =
-                        buffer_usage_to_virtual.insert(*usage_resource_id, virtual_buffer);
 
-                    assert!(overwritten_buffer.is_none());
-
-                    //TODO: One issue (aside from not doing any copies right now) is that buffers created in this way
-                    // aren't included in the assign_physical_buffers logic
-                    panic!("Render graph does not currently support blit from one buffer to another to fix buffer compatibility");
+                    //TODO: Render graph does not currently support copying from one buffer to
+                    // another to fix buffer compatibility. (If it did, buffers created in this way
+                    // would need to be included in the assign_physical_buffers logic)
+                    if !specifications_match {
+                        return Err(RenderGraphPlanError::buffer_conflict(
+                            graph,
+                            written_buffer,
+                            *usage_resource_id,
+                            &written_spec.clone().into(),
+                            &usage_spec.clone().into(),
+                        ));
+                    } else {
+                        return Err(RenderGraphPlanError::WriteToReadOnlyBuffer {
+                            written: RenderGraphPlanErrorBuffer::new(graph, written_buffer),
+                            used: RenderGraphPlanErrorBuffer::new(graph, *usage_resource_id),
+                        });
+                    }
                 }
             }
         }
     }
 
     // vulkan image layouts: https://github.com/nannou-org/nannou/issues/271#issuecomment-465876622
-    AssignVirtualResourcesResult {
+    Ok(AssignVirtualResourcesResult {
         image_usage_to_virtual,
         buffer_usage_to_virtual,
+    })
+}
+
+//
+// Consecutive render nodes can share a single renderpass. This avoids storing the attachments at
+// the end of one node and loading them again at the start of the next, which is expensive on
+// tile-based GPUs. If the nodes draw to exactly the same attachments, they run one after another
+// within a single subpass. Otherwise each node becomes a subpass of the renderpass (i.e. a depth
+// prepass followed by an opaque pass, or a node that reads the attachments of an earlier node as
+// input attachments), which requires a backend that supports subpasses. A node is only merged if:
+// - Neither the pass nor the node have resolve attachments (resolves happen when the pass ends)
+// - Attachments it shares with the pass are the same image view, and are loaded rather than
+//   cleared
+// - All of its attachments have the same sample count and size as the attachments of the pass
+// - The pass would still have at most one depth/stencil attachment
+// - No image is both an attachment and a sampled image within the pass, and it doesn't read any
+//   other resources written by nodes already in the pass, because barriers for the whole pass are
+//   placed before the pass begins
+//
+fn can_merge_into_renderpass(
+    graph: &RenderGraphBuilder,
+    constraints: &DetermineConstraintsResult,
+    virtual_resources: &AssignVirtualResourcesResult,
+    pass: &RenderGraphRenderPass,
+    node: &RenderGraphNode,
+) -> bool {
+    if pass.resolve_attachments.iter().any(|x| x.is_some())
+        || node.resolve_attachments.iter().any(|x| x.is_some())
+    {
+        return false;
+    }
+
+    if graph.supports_subpasses {
+        if !can_merge_into_renderpass_as_subpass(graph, constraints, virtual_resources, pass, node)
+        {
+            return false;
+        }
+    } else if !node.input_attachments.is_empty()
+        || !has_same_attachments(graph, virtual_resources, pass, node)
+    {
+        return false;
+    }
+
+    let is_written_by_pass = |creator_node: RenderGraphNodeId| pass.nodes.contains(&creator_node);
+
+    for sampled_image in &node.sampled_images {
+        if is_written_by_pass(graph.image_version_info(*sampled_image).creator_node) {
+            return false;
+        }
+    }
+
+    for buffer_read in &node.buffer_reads {
+        if is_written_by_pass(graph.buffer_version_info(buffer_read.buffer).creator_node) {
+            return false;
+        }
+    }
+
+    for buffer_modify in &node.buffer_modifies {
+        if is_written_by_pass(graph.buffer_version_info(buffer_modify.input).creator_node) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// Returns true if the node uses the same image and view as the pass in each color/depth attachment
+// slot, and does not clear any of them. Such a node can share the pass's only subpass.
+fn has_same_attachments(
+    graph: &RenderGraphBuilder,
+    virtual_resources: &AssignVirtualResourcesResult,
+    pass: &RenderGraphRenderPass,
+    node: &RenderGraphNode,
+) -> bool {
+    // Nodes are only merged into a single subpass if all of them have the same attachments, so
+    // comparing against the first one is enough
+    let subpass = &pass.subpasses[0];
+
+    // Returns true if the usage refers to the same image and view as the pass attachment
+    let is_same_attachment = |pass_attachment: Option<usize>,
+                              usage: Option<RenderGraphImageUsageId>| {
+        match (pass_attachment, usage) {
+            (None, None) => true,
+            (Some(pass_attachment), Some(usage)) => {
+                let pass_attachment = &pass.attachments[pass_attachment];
+                virtual_resources.image_usage_to_virtual.get(&usage)
+                    == Some(&pass_attachment.virtual_image)
+                    && graph.image_usages[usage.0].view_options
+                        == graph.image_usages[pass_attachment.usage.0].view_options
+            }
+            _ => false,
+        }
+    };
+
+    for (color_attachment_index, pass_color_attachment) in
+        subpass.color_attachments.iter().enumerate()
+    {
+        let color_attachment = node
+            .color_attachments
+            .get(color_attachment_index)
+            .and_then(|x| x.as_ref());
+
+        if let Some(color_attachment) = color_attachment {
+            if color_attachment.clear_color_value.is_some() {
+                return false;
+            }
+        }
+
+        if !is_same_attachment(
+            *pass_color_attachment,
+            color_attachment.and_then(|x| x.read_image),
+        ) {
+            return false;
+        }
+    }
+
+    if let Some(depth_attachment) = &node.depth_attachment {
+        if depth_attachment.clear_depth_stencil_value.is_some() {
+            return false;
+        }
+    }
+
+    is_same_attachment(
+        subpass.depth_attachment,
+        node.depth_attachment.as_ref().and_then(|x| x.read_image),
+    )
+}
+
+// Returns true if the node can be added to the pass as another subpass. The node may use a
+// different set of attachments than the earlier subpasses.
+fn can_merge_into_renderpass_as_subpass(
+    graph: &RenderGraphBuilder,
+    constraints: &DetermineConstraintsResult,
+    virtual_resources: &AssignVirtualResourcesResult,
+    pass: &RenderGraphRenderPass,
+    node: &RenderGraphNode,
+) -> bool {
+    // All attachments of a renderpass must have the same size and sample count
+    let pass_specification = &constraints.images[&pass.attachments[0].usage];
+    let pass_mip_slice = graph.image_usages[pass.attachments[0].usage.0]
+        .view_options
+        .mip_slice;
+
+    let mut depth_virtual_image = pass
+        .depth_attachment
+        .map(|depth_attachment| pass.attachments[depth_attachment].virtual_image);
+
+    // The attachments of the node, and whether they are cleared
+    let node_attachments = node
+        .color_attachments
+        .iter()
+        .filter_map(|x| x.as_ref())
+        .map(|x| {
+            (
+                x.read_image.or(x.write_image).unwrap(),
+                x.clear_color_value.is_some(),
+            )
+        })
+        .chain(node.depth_attachment.iter().map(|x| {
+            (
+                x.read_image.or(x.write_image).unwrap(),
+                x.clear_depth_stencil_value.is_some(),
+            )
+        }))
+        .chain(node.input_attachments.iter().map(|x| (*x, false)));
+
+    let mut node_virtual_images = FnvHashSet::default();
+    for (usage, is_cleared) in node_attachments {
+        let virtual_image = virtual_resources.image_usage_to_virtual[&usage];
+        let specification = &constraints.images[&usage];
+        let view_options = &graph.image_usages[usage.0].view_options;
+        node_virtual_images.insert(virtual_image);
+
+        if let Some(pass_attachment) = pass
+            .attachments
+            .iter()
+            .find(|x| x.virtual_image == virtual_image)
+        {
+            // A clear would discard what earlier subpasses rendered
+            if is_cleared
+                || *view_options != graph.image_usages[pass_attachment.usage.0].view_options
+            {
+                return false;
+            }
+        }
+
+        if specification.samples != pass_specification.samples
+            || specification.extents != pass_specification.extents
+            || view_options.mip_slice != pass_mip_slice
+        {
+            return false;
+        }
+
+        if specification.format.has_depth_or_stencil() {
+            if depth_virtual_image.is_some() && depth_virtual_image != Some(virtual_image) {
+                return false;
+            }
+
+            depth_virtual_image = Some(virtual_image);
+        }
+    }
+
+    let attachment_virtual_images: FnvHashSet<_> = pass
+        .attachments
+        .iter()
+        .map(|x| x.virtual_image)
+        .chain(node_virtual_images.iter().copied())
+        .collect();
+
+    // An image can't be both an attachment and sampled within the same pass
+    for pass_node in &pass.nodes {
+        for sampled_image in &graph.node(*pass_node).sampled_images {
+            if node_virtual_images
+                .contains(&virtual_resources.image_usage_to_virtual[sampled_image])
+            {
+                return false;
+            }
+        }
+    }
+
+    for sampled_image in &node.sampled_images {
+        if attachment_virtual_images
+            .contains(&virtual_resources.image_usage_to_virtual[sampled_image])
+        {
+            return false;
+        }
     }
+
+    true
 }
 
 //
@@ -1092,7 +1417,7 @@ fn build_physical_passes(
     node_execution_order: &[RenderGraphNodeId],
     constraints: &DetermineConstraintsResult,
     virtual_resources: &AssignVirtualResourcesResult,
-) -> Vec<RenderGraphPass> {
+) -> RenderGraphPlanResult<Vec<RenderGraphPass>> {
     #[derive(Debug)]
     enum PassNode {
         RenderNode(RenderGraphNodeId),
@@ -1143,6 +1468,7 @@ fn build_physical_passes(
 
                     load_op: RafxLoadOp::DontCare,
                     stencil_load_op: RafxLoadOp::DontCare,
+                    // NOTE: These get assigned later in determine_attachment_store_ops
                     store_op: RafxStoreOp::DontCare,
                     stencil_store_op: RafxStoreOp::DontCare,
                     clear_color: None,
@@ -1165,8 +1491,6 @@ fn build_physical_passes(
                 }));
             }
             PassNode::RenderNode(renderpass_node) => {
-                let mut renderpass_attachments = Vec::default();
-
                 log::trace!("    subpass node: {:?}", renderpass_node);
                 let subpass_node = graph.node(renderpass_node);
 
@@ -1179,11 +1503,58 @@ fn build_physical_passes(
                     continue;
                 }
 
-                let mut pass_color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
-                    Default::default();
-                let mut pass_resolve_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
-                    Default::default();
-                let mut pass_depth_attachment = Default::default();
+                if !graph.supports_subpasses {
+                    if let Some(input_attachment) = subpass_node.input_attachments.first() {
+                        return Err(RenderGraphPlanError::UnsupportedInputAttachment {
+                            image: RenderGraphPlanErrorImage::new(graph, *input_attachment),
+                        });
+                    }
+                }
+
+                let merge_with_previous_pass = match passes.last() {
+                    Some(RenderGraphPass::Renderpass(previous_pass)) => can_merge_into_renderpass(
+                        graph,
+                        constraints,
+                        virtual_resources,
+                        previous_pass,
+                        subpass_node,
+                    ),
+                    _ => false,
+                };
+
+                // Either continue the previous renderpass or start a new one
+                let mut renderpass = match passes.pop() {
+                    Some(RenderGraphPass::Renderpass(previous_pass))
+                        if merge_with_previous_pass =>
+                    {
+                        log::trace!(
+                            "      Merging into renderpass with nodes {:?}",
+                            previous_pass.nodes
+                        );
+                        previous_pass
+                    }
+                    previous_pass => {
+                        passes.extend(previous_pass);
+                        RenderGraphRenderPass {
+                            nodes: Default::default(),
+                            subpasses: Default::default(),
+                            attachments: Default::default(),
+                            color_attachments: Default::default(),
+                            resolve_attachments: Default::default(),
+                            depth_attachment: None,
+                            pre_pass_barrier: None,
+                            post_pass_barrier: None,
+                        }
+                    }
+                };
+
+                renderpass.nodes.push(renderpass_node);
+                let mut subpass = RenderGraphSubpass {
+                    node: renderpass_node,
+                    color_attachments: Default::default(),
+                    depth_attachment: None,
+                    input_attachments: Default::default(),
+                };
 
                 for (color_attachment_index, color_attachment) in
                     subpass_node.color_attachments.iter().enumerate()
@@ -1202,14 +1573,20 @@ fn build_physical_passes(
                         log::trace!("      virtual attachment (color): {:?}", virtual_image);
 
                         let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
-                            &mut renderpass_attachments,
+                            &mut renderpass.attachments,
                             read_or_write_usage,
                             *virtual_image, /*, subresource_range*/
                         );
-                        pass_color_attachments[color_attachment_index] =
+                        subpass.color_attachments[color_attachment_index] =
                             Some(pass_attachment_index);
+                        if !renderpass
+                            .color_attachments
+                            .contains(&pass_attachment_index)
+                        {
+                            renderpass.color_attachments.push(pass_attachment_index);
+                        }
 
-                        let mut attachment = &mut renderpass_attachments[pass_attachment_index];
+                        let mut attachment = &mut renderpass.attachments[pass_attachment_index];
                         if is_first_usage {
                             // Check if we load or clear
                             if color_attachment.clear_color_value.is_some() {
@@ -1224,19 +1601,6 @@ fn build_physical_passes(
                             attachment.format = specification.format.into();
                             attachment.samples = specification.samples.into();
                         };
-
-                        let store_op = if let Some(write_image) = color_attachment.write_image {
-                            if !graph.image_version_info(write_image).read_usages.is_empty() {
-                                RafxStoreOp::Store
-                            } else {
-                                RafxStoreOp::DontCare
-                            }
-                        } else {
-                            RafxStoreOp::DontCare
-                        };
-
-                        attachment.store_op = store_op;
-                        attachment.stencil_store_op = RafxStoreOp::DontCare;
                     }
                 }
 
@@ -1254,28 +1618,17 @@ fn build_physical_passes(
                         log::trace!("      virtual attachment (resolve): {:?}", virtual_image);
 
                         let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
-                            &mut renderpass_attachments,
+                            &mut renderpass.attachments,
                             write_image,
                             *virtual_image, /*, subresource_range*/
                         );
-                        pass_resolve_attachments[resolve_attachment_index] =
+                        renderpass.resolve_attachments[resolve_attachment_index] =
                             Some(pass_attachment_index);
 
                         assert!(is_first_usage); // Not sure if this assert is valid
-                        let mut attachment = &mut renderpass_attachments[pass_attachment_index];
+                        let mut attachment = &mut renderpass.attachments[pass_attachment_index];
                         attachment.format = specification.format.into();
                         attachment.samples = specification.samples.into();
-
-                        //TODO: Should we skip resolving if there is no reader?
-                        let store_op =
-                            if !graph.image_version_info(write_image).read_usages.is_empty() {
-                                RafxStoreOp::Store
-                            } else {
-                                RafxStoreOp::DontCare
-                            };
-
-                        attachment.store_op = store_op;
-                        attachment.stencil_store_op = RafxStoreOp::DontCare;
                     }
                 }
 
@@ -1292,13 +1645,14 @@ fn build_physical_passes(
                     log::trace!("      virtual attachment (depth): {:?}", virtual_image);
 
                     let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
-                        &mut renderpass_attachments,
+                        &mut renderpass.attachments,
                         read_or_write_usage,
                         *virtual_image, /*, subresource_range*/
                     );
-                    pass_depth_attachment = Some(pass_attachment_index);
+                    subpass.depth_attachment = Some(pass_attachment_index);
+                    renderpass.depth_attachment = Some(pass_attachment_index);
 
-                    let mut attachment = &mut renderpass_attachments[pass_attachment_index];
+                    let mut attachment = &mut renderpass.attachments[pass_attachment_index];
                     if is_first_usage {
                         // Check if we load or clear
                         //TODO: Support load_op for stencil
@@ -1326,40 +1680,177 @@ fn build_physical_passes(
                         attachment.format = specification.format.into();
                         attachment.samples = specification.samples.into();
                     };
+                }
 
-                    let store_op = if let Some(write_image) = depth_attachment.write_image {
-                        if !graph.image_version_info(write_image).read_usages.is_empty() {
-                            RafxStoreOp::Store
-                        } else {
-                            RafxStoreOp::DontCare
+                for input_attachment in &subpass_node.input_attachments {
+                    let virtual_image = virtual_resources
+                        .image_usage_to_virtual
+                        .get(input_attachment)
+                        .unwrap();
+                    let specification = constraints.images.get(input_attachment).unwrap();
+                    log::trace!("      virtual attachment (input): {:?}", virtual_image);
+
+                    let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
+                        &mut renderpass.attachments,
+                        *input_attachment,
+                        *virtual_image,
+                    );
+                    subpass.input_attachments.push(pass_attachment_index);
+
+                    let format = specification.format;
+                    if format.has_depth_or_stencil() {
+                        // A renderpass only has one depth/stencil attachment
+                        if renderpass.depth_attachment.is_some()
+                            && renderpass.depth_attachment != Some(pass_attachment_index)
+                        {
+                            return Err(RenderGraphPlanError::UnsupportedInputAttachment {
+                                image: RenderGraphPlanErrorImage::new(graph, *input_attachment),
+                            });
                         }
-                    } else {
-                        RafxStoreOp::DontCare
-                    };
 
-                    if depth_attachment.has_depth {
+                        renderpass.depth_attachment = Some(pass_attachment_index);
+                    } else if !renderpass
+                        .color_attachments
+                        .contains(&pass_attachment_index)
+                    {
+                        renderpass.color_attachments.push(pass_attachment_index);
+                    }
+
+                    // The image was written by an earlier pass, so load it
+                    let mut attachment = &mut renderpass.attachments[pass_attachment_index];
+                    if is_first_usage {
+                        if format.has_depth() || !format.has_depth_or_stencil() {
+                            attachment.load_op = RafxLoadOp::Load;
+                        }
+
+                        if format.has_stencil() {
+                            attachment.stencil_load_op = RafxLoadOp::Load;
+                        }
+
+                        attachment.format = format;
+                        attachment.samples = specification.samples;
+                    }
+                }
+
+                renderpass.subpasses.push(subpass);
+                passes.push(RenderGraphPass::Renderpass(renderpass));
+            }
+        }
+    }
+
+    Ok(passes)
+}
+
+//
+// An attachment only needs to be stored if its contents are used after the pass ends. Contents
+// that are only read within the pass (for example as input attachments of later subpasses) never
+// need to leave tile memory.
+//
+#[profiling::function]
+fn determine_attachment_store_ops(
+    graph: &RenderGraphBuilder,
+    passes: &mut [RenderGraphPass],
+) {
+    for pass in passes {
+        if let RenderGraphPass::Renderpass(renderpass) = pass {
+            // The last usage of each attachment within the pass, and which aspects of
+            // depth/stencil attachments are used
+            let mut last_usages: Vec<Option<RenderGraphImageUsageId>> =
+                vec![None; renderpass.attachments.len()];
+            let mut has_depth = false;
+            let mut has_stencil = false;
+
+            for subpass in &renderpass.subpasses {
+                let node = graph.node(subpass.node);
+
+                for (color_attachment_index, color_attachment) in
+                    node.color_attachments.iter().enumerate()
+                {
+                    if let Some(color_attachment) = color_attachment {
+                        let pass_attachment_index =
+                            subpass.color_attachments[color_attachment_index].unwrap();
+                        last_usages[pass_attachment_index] =
+                            color_attachment.write_image.or(color_attachment.read_image);
+                    }
+                }
+
+                for (resolve_attachment_index, resolve_attachment) in
+                    node.resolve_attachments.iter().enumerate()
+                {
+                    if let Some(resolve_attachment) = resolve_attachment {
+                        let pass_attachment_index =
+                            renderpass.resolve_attachments[resolve_attachment_index].unwrap();
+                        last_usages[pass_attachment_index] = Some(resolve_attachment.write_image);
+                    }
+                }
+
+                if let Some(depth_attachment) = &node.depth_attachment {
+                    last_usages[subpass.depth_attachment.unwrap()] =
+                        depth_attachment.write_image.or(depth_attachment.read_image);
+                    has_depth |= depth_attachment.has_depth;
+                    has_stencil |= depth_attachment.has_stencil;
+                }
+
+                for (input_attachment_index, input_attachment) in
+                    node.input_attachments.iter().enumerate()
+                {
+                    let pass_attachment_index = subpass.input_attachments[input_attachment_index];
+                    last_usages[pass_attachment_index] = Some(*input_attachment);
+                }
+            }
+
+            let pass_nodes = &renderpass.nodes;
+            let depth_attachment = renderpass.depth_attachment;
+            for (attachment_index, attachment) in renderpass.attachments.iter_mut().enumerate() {
+                let is_read_after_pass = last_usages[attachment_index]
+                    .map(|usage| {
+                        graph
+                            .image_version_info(usage)
+                            .read_usages
+                            .iter()
+                            .any(|read_usage| match graph.image_usages[read_usage.0].user {
+                                RenderGraphImageUser::Node(node_id) => {
+                                    !pass_nodes.contains(&node_id)
+                                }
+                                RenderGraphImageUser::Output(_) => true,
+                            })
+                    })
+                    .unwrap_or(false);
+
+                let store_op = if is_read_after_pass {
+                    RafxStoreOp::Store
+                } else {
+                    RafxStoreOp::DontCare
+                };
+
+                if depth_attachment == Some(attachment_index) {
+                    // Depth/stencil attachments that are only read as input attachments use
+                    // every aspect of the format
+                    if !has_depth && !has_stencil {
+                        has_depth = attachment.format.has_depth();
+                        has_stencil = attachment.format.has_stencil();
+                    }
+
+                    if has_depth {
                         attachment.store_op = store_op;
                     }
 
-                    if depth_attachment.has_stencil {
+                    if has_stencil {
                         attachment.stencil_store_op = store_op;
                     }
+                } else {
+                    attachment.store_op = store_op;
+                    attachment.stencil_store_op = RafxStoreOp::DontCare;
                 }
 
-                passes.push(RenderGraphPass::Renderpass(RenderGraphRenderPass {
-                    node_id: renderpass_node,
-                    attachments: renderpass_attachments,
-                    color_attachments: pass_color_attachments,
-                    depth_attachment: pass_depth_attachment,
-                    resolve_attachments: pass_resolve_attachments,
-                    pre_pass_barrier: None,
-                    post_pass_barrier: None,
-                }));
+                log::trace!(
+                    "  Attachment {:?} store op {:?}",
+                    attachment.virtual_image,
+                    attachment.store_op
+                );
             }
         }
     }
-
-    passes
 }
 
 #[derive(Debug)]
@@ -1387,7 +1878,7 @@ fn assign_physical_resources(
     constraints: &DetermineConstraintsResult,
     virtual_resources: &AssignVirtualResourcesResult,
     passes: &mut [RenderGraphPass],
-) -> AssignPhysicalResourcesResult {
+) -> RenderGraphPlanResult<AssignPhysicalResourcesResult> {
     log::trace!("-- Assign physical resources --");
     struct PhysicalImageReuseRequirements {
         virtual_id: VirtualImageId,
@@ -1484,100 +1975,101 @@ fn assign_physical_resources(
     // Walk through all image/buffer usages to determine their lifetimes
     //
     for (pass_index, pass) in passes.iter().enumerate() {
-        let subpass_node_id = pass.node();
-        let node = graph.node(subpass_node_id);
-
-        for image_modify in &node.image_modifies {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_modify.input,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_modify.output,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+        for subpass_node_id in pass.nodes() {
+            let node = graph.node(*subpass_node_id);
+
+            for image_modify in &node.image_modifies {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_modify.input,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_modify.output,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_read in &node.image_reads {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_read.image,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_read in &node.image_reads {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_read.image,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_create in &node.image_creates {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_create.image,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_create in &node.image_creates {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_create.image,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_sample in &node.sampled_images {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                *image_sample,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_sample in &node.sampled_images {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    *image_sample,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_modify in &node.buffer_modifies {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_modify.input,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_modify.output,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-        }
+            for buffer_modify in &node.buffer_modifies {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_modify.input,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_modify.output,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_read in &node.buffer_reads {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_read.buffer,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-        }
+            for buffer_read in &node.buffer_reads {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_read.buffer,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_create in &node.buffer_creates {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_create.buffer,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
+            for buffer_create in &node.buffer_creates {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_create.buffer,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
         }
     }
 
@@ -1794,7 +2286,11 @@ fn assign_physical_resources(
     // Create a list and lookup for all image views that are needed for the graph
     //
     for (&usage, &physical_image) in &image_usage_to_physical {
-        let image_specification = constraints.image_specification(usage).unwrap();
+        let image_specification = constraints.image_specification(usage).ok_or_else(|| {
+            RenderGraphPlanError::ImageSpecificationMissing {
+                image: RenderGraphPlanErrorImage::new(graph, usage),
+            }
+        })?;
         let image_view = RenderGraphImageView {
             physical_image,
             format: image_specification.format,
@@ -1842,7 +2338,7 @@ fn assign_physical_resources(
         .map(|x| x.specification)
         .collect();
 
-    AssignPhysicalResourcesResult {
+    Ok(AssignPhysicalResourcesResult {
         image_usage_to_physical,
         image_virtual_to_physical,
         image_usage_to_image_view,
@@ -1851,14 +2347,239 @@ fn assign_physical_resources(
         buffer_usage_to_physical,
         buffer_virtual_to_physical,
         buffer_specifications,
+    })
+}
+
+/// The range of passes in which a resource is used
+#[derive(Debug, Copy, Clone)]
+pub(super) struct RenderGraphResourceLifetime {
+    pub(super) first_pass: usize,
+    pub(super) last_pass: usize,
+}
+
+impl RenderGraphResourceLifetime {
+    fn new(pass_index: usize) -> Self {
+        RenderGraphResourceLifetime {
+            first_pass: pass_index,
+            last_pass: pass_index,
+        }
+    }
+
+    fn extend(
+        &mut self,
+        pass_index: usize,
+    ) {
+        self.first_pass = self.first_pass.min(pass_index);
+        self.last_pass = self.last_pass.max(pass_index);
+    }
+}
+
+// All images a node creates, reads (including sampling), or modifies
+fn node_image_usages(node: &RenderGraphNode) -> impl Iterator<Item = RenderGraphImageUsageId> + '_ {
+    node.image_creates
+        .iter()
+        .map(|x| x.image)
+        .chain(node.image_reads.iter().map(|x| x.image))
+        .chain(node.image_modifies.iter().map(|x| x.input))
+        .chain(node.image_modifies.iter().map(|x| x.output))
+}
+
+// All buffers a node creates, reads, or modifies
+fn node_buffer_usages(
+    node: &RenderGraphNode
+) -> impl Iterator<Item = RenderGraphBufferUsageId> + '_ {
+    node.buffer_creates
+        .iter()
+        .map(|x| x.buffer)
+        .chain(node.buffer_reads.iter().map(|x| x.buffer))
+        .chain(node.buffer_modifies.iter().map(|x| x.input))
+        .chain(node.buffer_modifies.iter().map(|x| x.output))
+}
+
+//
+// Images that are only used as attachments within a single pass never need their contents to
+// be written out to memory. Mark them as transient so that the backend can avoid allocating memory
+// for them (i.e. lazily allocated or memoryless), and make sure they are never stored.
+//
+#[profiling::function]
+fn mark_transient_images(
+    graph: &RenderGraphBuilder,
+    virtual_resources: &AssignVirtualResourcesResult,
+    physical_resources: &mut AssignPhysicalResourcesResult,
+    passes: &mut [RenderGraphPass],
+) {
+    log::trace!("-- Mark transient images --");
+
+    // Determine the passes each virtual image is used in. A physical image may be shared by
+    // several virtual images, each of which must only be used in a single pass.
+    let mut virtual_image_lifetimes =
+        FnvHashMap::<VirtualImageId, RenderGraphResourceLifetime>::default();
+    let mut non_transient_images = FnvHashSet::<PhysicalImageId>::default();
+
+    for (pass_index, pass) in passes.iter().enumerate() {
+        for node_id in pass.nodes() {
+            let node = graph.node(*node_id);
+            for usage in node_image_usages(node) {
+                let virtual_image = virtual_resources.image_usage_to_virtual[&usage];
+                virtual_image_lifetimes
+                    .entry(virtual_image)
+                    .or_insert_with(|| RenderGraphResourceLifetime::new(pass_index))
+                    .extend(pass_index);
+            }
+
+            for sampled_image in &node.sampled_images {
+                non_transient_images
+                    .insert(physical_resources.image_usage_to_physical[sampled_image]);
+            }
+        }
+    }
+
+    for output_image in &graph.output_images {
+        non_transient_images
+            .insert(physical_resources.image_usage_to_physical[&output_image.usage]);
+    }
+
+    for (virtual_image, lifetime) in &virtual_image_lifetimes {
+        if lifetime.first_pass != lifetime.last_pass {
+            non_transient_images
+                .insert(physical_resources.image_virtual_to_physical[virtual_image]);
+        }
+    }
+
+    let mut transient_images = FnvHashSet::<PhysicalImageId>::default();
+    for (index, specification) in physical_resources
+        .image_specifications
+        .iter_mut()
+        .enumerate()
+    {
+        let physical_image = PhysicalImageId(index);
+        let is_attachment_only = specification.resource_type.intersects(
+            RafxResourceType::RENDER_TARGET_COLOR | RafxResourceType::RENDER_TARGET_DEPTH_STENCIL,
+        ) && !specification
+            .resource_type
+            .intersects(RafxResourceType::TEXTURE | RafxResourceType::TEXTURE_READ_WRITE);
+
+        if is_attachment_only && !non_transient_images.contains(&physical_image) {
+            log::trace!("  Transient image {:?}", physical_image);
+            specification.resource_type |= RafxResourceType::TRANSIENT_ATTACHMENT;
+            transient_images.insert(physical_image);
+        }
+    }
+
+    // Nothing reads a transient image after its pass ends, so it never needs to be stored
+    for pass in passes {
+        if let RenderGraphPass::Renderpass(renderpass) = pass {
+            for attachment in &mut renderpass.attachments {
+                if transient_images.contains(&attachment.image.unwrap()) {
+                    attachment.store_op = RafxStoreOp::DontCare;
+                    attachment.stencil_store_op = RafxStoreOp::DontCare;
+                }
+            }
+        }
+    }
+}
+
+//
+// Determine the range of passes in which each physical image and buffer is used
+//
+fn determine_physical_resource_lifetimes(
+    graph: &RenderGraphBuilder,
+    physical_resources: &AssignPhysicalResourcesResult,
+    passes: &[RenderGraphPass],
+) -> (
+    FnvHashMap<PhysicalImageId, RenderGraphResourceLifetime>,
+    FnvHashMap<PhysicalBufferId, RenderGraphResourceLifetime>,
+) {
+    let mut image_lifetimes = FnvHashMap::<PhysicalImageId, RenderGraphResourceLifetime>::default();
+    let mut buffer_lifetimes =
+        FnvHashMap::<PhysicalBufferId, RenderGraphResourceLifetime>::default();
+
+    for (pass_index, pass) in passes.iter().enumerate() {
+        for node_id in pass.nodes() {
+            let node = graph.node(*node_id);
+            for usage in node_image_usages(node) {
+                let physical_image = physical_resources.image_usage_to_physical[&usage];
+                image_lifetimes
+                    .entry(physical_image)
+                    .or_insert_with(|| RenderGraphResourceLifetime::new(pass_index))
+                    .extend(pass_index);
+            }
+
+            for usage in node_buffer_usages(node) {
+                let physical_buffer = physical_resources.buffer_usage_to_physical[&usage];
+                buffer_lifetimes
+                    .entry(physical_buffer)
+                    .or_insert_with(|| RenderGraphResourceLifetime::new(pass_index))
+                    .extend(pass_index);
+            }
+        }
+    }
+
+    (image_lifetimes, buffer_lifetimes)
+}
+
+//
+// Resources whose lifetimes don't overlap can share the same memory, even if their specifications
+// are different. Group resources into slots such that no two resources in a slot are alive at the
+// same time. A slot needs as much memory as the largest resource in it. Resources in a slot are
+// ordered by when they are first used.
+//
+fn assign_memory_slots<IdT: Copy + Ord>(
+    lifetimes: &FnvHashMap<IdT, RenderGraphResourceLifetime>
+) -> Vec<Vec<IdT>> {
+    let mut resources: Vec<_> = lifetimes
+        .iter()
+        .map(|(id, lifetime)| (*id, *lifetime))
+        .collect();
+    // Include the ID so that the same graph produces the same slots every frame
+    resources.sort_by_key(|(id, lifetime)| (lifetime.first_pass, lifetime.last_pass, *id));
+
+    // Each slot is tracked with the last pass it is occupied and the resources assigned to it
+    let mut slots: Vec<(usize, Vec<IdT>)> = Vec::default();
+    for (id, lifetime) in resources {
+        if let Some(slot) = slots
+            .iter_mut()
+            .find(|(slot_last_pass, _)| *slot_last_pass < lifetime.first_pass)
+        {
+            slot.0 = lifetime.last_pass;
+            slot.1.push(id);
+        } else {
+            slots.push((lifetime.last_pass, vec![id]));
+        }
     }
+
+    slots.into_iter().map(|(_, ids)| ids).collect()
+}
+
+// Transient attachments may be lazily allocated (or never allocated at all) so they always get a
+// slot of their own
+fn assign_image_memory_slots(
+    physical_resources: &AssignPhysicalResourcesResult,
+    lifetimes: &FnvHashMap<PhysicalImageId, RenderGraphResourceLifetime>,
+) -> Vec<Vec<PhysicalImageId>> {
+    let (transient_lifetimes, aliasable_lifetimes): (FnvHashMap<_, _>, FnvHashMap<_, _>) =
+        lifetimes
+            .iter()
+            .map(|(id, lifetime)| (*id, *lifetime))
+            .partition(|(id, _)| {
+                physical_resources.image_specifications[id.0]
+                    .resource_type
+                    .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+            });
+
+    let mut transient_images: Vec<_> = transient_lifetimes.keys().copied().collect();
+    transient_images.sort();
+
+    let mut slots = assign_memory_slots(&aliasable_lifetimes);
+    slots.extend(transient_images.into_iter().map(|id| vec![id]));
+    slots
 }
 
 #[profiling::function]
 fn build_node_barriers(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
-    _constraints: &DetermineConstraintsResult,
+    constraints: &DetermineConstraintsResult,
     physical_resources: &AssignPhysicalResourcesResult,
 ) -> FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers> {
     let mut resource_barriers =
@@ -1923,6 +2644,28 @@ fn build_node_barriers(
                 });
         }
 
+        // Input attachments stay in the attachment's state. The renderpass transitions them to a
+        // readable layout for the subpasses that read them.
+        for input_attachment in &node.input_attachments {
+            let physical_image = physical_resources
+                .image_usage_to_physical
+                .get(input_attachment)
+                .unwrap();
+
+            let resource_state = if constraints.images[input_attachment]
+                .format
+                .has_depth_or_stencil()
+            {
+                RafxResourceState::DEPTH_WRITE
+            } else {
+                RafxResourceState::RENDER_TARGET
+            };
+
+            image_node_barriers
+                .entry(*physical_image)
+                .or_insert_with(|| RenderGraphPassImageBarriers::new(resource_state));
+        }
+
         for sampled_image in &node.sampled_images {
             let physical_image = physical_resources
                 .image_usage_to_physical
@@ -1999,10 +2742,30 @@ fn build_pass_barriers(
     _constraints: &DetermineConstraintsResult,
     physical_resources: &AssignPhysicalResourcesResult,
     node_barriers: &FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers>,
+    image_memory_slots: &[Vec<PhysicalImageId>],
+    buffer_memory_slots: &[Vec<PhysicalBufferId>],
     passes: &mut [RenderGraphPass],
 ) {
     log::trace!("-- build_pass_barriers --");
 
+    // Resources sharing memory with a resource that was used before them. Before such a resource
+    // is first used, the previous occupant of the memory gets a barrier so that its accesses
+    // complete before the memory is overwritten.
+    let mut aliased_image_predecessors = FnvHashMap::<PhysicalImageId, PhysicalImageId>::default();
+    for slot in image_memory_slots {
+        for occupants in slot.windows(2) {
+            aliased_image_predecessors.insert(occupants[1], occupants[0]);
+        }
+    }
+
+    let mut aliased_buffer_predecessors =
+        FnvHashMap::<PhysicalBufferId, PhysicalBufferId>::default();
+    for slot in buffer_memory_slots {
+        for occupants in slot.windows(2) {
+            aliased_buffer_predecessors.insert(occupants[1], occupants[0]);
+        }
+    }
+
     //
     // We will walk through all nodes keeping track of memory access as we go
     //
@@ -2030,7 +2793,6 @@ fn build_pass_barriers(
         }
     }
 
-    //TODO: to support subpass, probably need image states for each previous subpass
     // TODO: This is coarse-grained over the whole image. Ideally it would be per-layer and per-mip
     let mut image_states: Vec<ImageState> =
         Vec::with_capacity(physical_resources.image_specifications.len());
@@ -2053,11 +2815,6 @@ fn build_pass_barriers(
             attachment_initial_state.resize_with(pass.attachments.len(), || None);
         }
 
-        //let nodes: Vec<_> = pass.nodes().iter().copied().collect();
-        //for (subpass_index, subpass_node_id) in nodes.iter().enumerate() {
-        let subpass_node_id = pass.node();
-        let node_barriers = &node_barriers[&subpass_node_id];
-
         struct ImageTransition {
             physical_image_id: PhysicalImageId,
             old_state: RafxResourceState,
@@ -2070,78 +2827,115 @@ fn build_pass_barriers(
             new_state: RafxResourceState,
         }
 
+        // All transitions for nodes merged into the pass are placed before the pass begins. Nodes
+        // are only merged if they don't read anything written by earlier nodes in the same pass,
+        // other than attachments (synchronized by the renderpass's subpass dependencies).
         let mut image_transitions = Vec::default();
-        // Look at all the images we read and determine what invalidates we need
-        for (physical_image_id, image_barrier) in &node_barriers.image_barriers {
-            log::trace!("    image {:?}", physical_image_id);
-            let image_state = &mut image_states[physical_image_id.0];
+        let mut buffer_transitions = Vec::default();
 
-            let resource_state_change = image_state.resource_state != image_barrier.resource_state;
-            if resource_state_change {
-                log::trace!(
-                    "      state change! {:?} -> {:?}",
-                    image_state.resource_state,
-                    image_barrier.resource_state
-                );
+        let nodes: Vec<_> = pass.nodes().iter().copied().collect();
+        for subpass_node_id in &nodes {
+            let node_barriers = &node_barriers[subpass_node_id];
 
+            // Look at all the images we read and determine what invalidates we need
+            for (physical_image_id, image_barrier) in &node_barriers.image_barriers {
+                log::trace!("    image {:?}", physical_image_id);
+                if image_states[physical_image_id.0].resource_state == RafxResourceState::UNDEFINED
+                {
+                    if let Some(predecessor) = aliased_image_predecessors.get(physical_image_id) {
+                        let predecessor_state = image_states[predecessor.0].resource_state;
+                        log::trace!("      aliases {:?}", predecessor);
+                        image_transitions.push(ImageTransition {
+                            physical_image_id: *predecessor,
+                            old_state: predecessor_state,
+                            new_state: predecessor_state,
+                        });
+                    }
+                }
+
+                let image_state = &mut image_states[physical_image_id.0];
+
+                let resource_state_change =
+                    image_state.resource_state != image_barrier.resource_state;
                 if resource_state_change {
+                    log::trace!(
+                        "      state change! {:?} -> {:?}",
+                        image_state.resource_state,
+                        image_barrier.resource_state
+                    );
+
                     image_transitions.push(ImageTransition {
                         physical_image_id: *physical_image_id,
                         old_state: image_state.resource_state,
                         new_state: image_barrier.resource_state,
                     });
+
+                    image_state.resource_state = image_barrier.resource_state;
                 }
 
-                image_state.resource_state = image_barrier.resource_state;
-            }
-
-            // Set the initial layout for the attachment, but only if it's the first time we've seen it
-            //TODO: This is bad and does not properly handle an image being used in multiple ways requiring
-            // multiple layouts
-            if let RenderGraphPass::Renderpass(pass) = pass {
-                for (attachment_index, attachment) in &mut pass.attachments.iter_mut().enumerate() {
-                    //log::trace!("      attachment {:?}", attachment.image);
-                    if attachment.image.unwrap() == *physical_image_id {
-                        if attachment_initial_state[attachment_index].is_none() {
-                            //log::trace!("        initial layout {:?}", image_barrier.layout);
-                            attachment_initial_state[attachment_index] =
-                                Some(image_state.resource_state.into());
-
-                            // Use an image barrier before the pass to transition the layout,
-                            // so we will already be in the correct layout before starting the
-                            // pass.
-                            attachment.initial_state = image_barrier.resource_state.into();
-                        }
+                // Set the initial layout for the attachment, but only if it's the first time we've seen it
+                //TODO: This is bad and does not properly handle an image being used in multiple ways requiring
+                // multiple layouts
+                if let RenderGraphPass::Renderpass(pass) = pass {
+                    for (attachment_index, attachment) in
+                        &mut pass.attachments.iter_mut().enumerate()
+                    {
+                        //log::trace!("      attachment {:?}", attachment.image);
+                        if attachment.image.unwrap() == *physical_image_id {
+                            if attachment_initial_state[attachment_index].is_none() {
+                                //log::trace!("        initial layout {:?}", image_barrier.layout);
+                                attachment_initial_state[attachment_index] =
+                                    Some(image_state.resource_state.into());
+
+                                // Use an image barrier before the pass to transition the layout,
+                                // so we will already be in the correct layout before starting the
+                                // pass.
+                                attachment.initial_state = image_barrier.resource_state.into();
+                            }
 
-                        attachment.final_state = image_barrier.resource_state.into();
-                        break;
+                            attachment.final_state = image_barrier.resource_state.into();
+                            break;
+                        }
                     }
                 }
             }
-        }
 
-        // Look at all the buffers we read and determine what invalidates we need
-        let mut buffer_transitions = Vec::default();
-        for (physical_buffer_id, buffer_barrier) in &node_barriers.buffer_barriers {
-            log::trace!("    buffer {:?}", physical_buffer_id);
-            let buffer_state = &mut buffer_states[physical_buffer_id.0];
+            // Look at all the buffers we read and determine what invalidates we need
+            for (physical_buffer_id, buffer_barrier) in &node_barriers.buffer_barriers {
+                log::trace!("    buffer {:?}", physical_buffer_id);
+                if buffer_states[physical_buffer_id.0].resource_state
+                    == RafxResourceState::UNDEFINED
+                {
+                    if let Some(predecessor) = aliased_buffer_predecessors.get(physical_buffer_id) {
+                        let predecessor_state = buffer_states[predecessor.0].resource_state;
+                        log::trace!("      aliases {:?}", predecessor);
+                        buffer_transitions.push(BufferTransition {
+                            physical_buffer_id: *predecessor,
+                            old_state: predecessor_state,
+                            new_state: predecessor_state,
+                        });
+                    }
+                }
 
-            let resource_state_change =
-                buffer_state.resource_state != buffer_barrier.resource_state;
-            if resource_state_change {
-                log::trace!(
-                    "      state change! {:?} -> {:?}",
-                    buffer_state.resource_state,
-                    buffer_barrier.resource_state
-                );
+                let buffer_state = &mut buffer_states[physical_buffer_id.0];
 
-                buffer_transitions.push(BufferTransition {
-                    physical_buffer_id: *physical_buffer_id,
-                    old_state: buffer_state.resource_state,
-                    new_state: buffer_barrier.resource_state,
-                });
+                let resource_state_change =
+                    buffer_state.resource_state != buffer_barrier.resource_state;
+                if resource_state_change {
+                    log::trace!(
+                        "      state change! {:?} -> {:?}",
+                        buffer_state.resource_state,
+                        buffer_barrier.resource_state
+                    );
+
+                    buffer_transitions.push(BufferTransition {
+                        physical_buffer_id: *physical_buffer_id,
+                        old_state: buffer_state.resource_state,
+                        new_state: buffer_barrier.resource_state,
+                    });
 
-                buffer_state.resource_state = buffer_barrier.resource_state;
+                    buffer_state.resource_state = buffer_barrier.resource_state;
+                }
             }
         }
 
@@ -2182,14 +2976,15 @@ fn build_pass_barriers(
         // TODO: This only works if no one else reads it?
         log::trace!("Check for output images");
         for (output_image_index, output_image) in graph.output_images.iter().enumerate() {
-            if graph.image_version_info(output_image.usage).creator_node == subpass_node_id {
+            let creator_node = graph.image_version_info(output_image.usage).creator_node;
+            if nodes.contains(&creator_node) {
                 let output_physical_image =
                     physical_resources.image_usage_to_physical[&output_image.usage];
                 log::trace!(
                     "Output image {} usage {:?} created by node {:?} physical image {:?}",
                     output_image_index,
                     output_image.usage,
-                    subpass_node_id,
+                    creator_node,
                     output_physical_image
                 );
 
@@ -2231,7 +3026,7 @@ fn build_pass_barriers(
 fn create_output_passes(
     graph: &RenderGraphBuilder,
     passes: Vec<RenderGraphPass>,
-) -> Vec<RenderGraphOutputPass> {
+) -> RenderGraphPlanResult<Vec<RenderGraphOutputPass>> {
     let mut renderpasses = Vec::with_capacity(passes.len());
 
     for pass in passes {
@@ -2245,20 +3040,25 @@ fn create_output_passes(
                     .map(|attachment| attachment.image_view.unwrap())
                     .collect();
 
-                let debug_name = graph.node(pass.node_id).name;
+                // Merged renderpasses are named after the first node in the pass
+                let debug_name = graph.node(pass.nodes[0]).name;
 
                 let mut color_formats = vec![];
                 let mut sample_count = None;
                 for color_attachment in &pass.color_attachments {
-                    if let Some(color_attachment) = color_attachment {
-                        color_formats.push(pass.attachments[*color_attachment].format);
+                    color_formats.push(pass.attachments[*color_attachment].format);
 
-                        let expected_sample_count = pass.attachments[*color_attachment].samples;
-                        if let Some(sample_count) = sample_count {
-                            assert_eq!(sample_count, expected_sample_count, "Render node has color attachments with different sample counts, this is unsupported.");
-                        } else {
-                            sample_count = Some(expected_sample_count);
+                    let expected_sample_count = pass.attachments[*color_attachment].samples;
+                    if let Some(sample_count) = sample_count {
+                        if sample_count != expected_sample_count {
+                            return Err(RenderGraphPlanError::AttachmentSampleCountConflict {
+                                node: RenderGraphPlanErrorNode::new(graph, pass.nodes[0]),
+                                expected_samples: expected_sample_count,
+                                found_samples: sample_count,
+                            });
                         }
+                    } else {
+                        sample_count = Some(expected_sample_count);
                     }
                 }
 
@@ -2268,60 +3068,132 @@ fn create_output_passes(
 
                     let expected_sample_count = pass.attachments[depth_attachment].samples;
                     if let Some(sample_count) = sample_count {
-                        assert_eq!(sample_count, expected_sample_count, "Render node has color attachment and depth attachment with different sample counts, this is unsupported.");
+                        if sample_count != expected_sample_count {
+                            return Err(RenderGraphPlanError::AttachmentSampleCountConflict {
+                                node: RenderGraphPlanErrorNode::new(graph, pass.nodes[0]),
+                                expected_samples: expected_sample_count,
+                                found_samples: sample_count,
+                            });
+                        }
                     } else {
                         sample_count = Some(expected_sample_count);
                     }
                 }
 
-                let render_target_meta = GraphicsPipelineRenderTargetMeta::new(
-                    color_formats,
-                    depth_format,
-                    sample_count.unwrap(),
-                );
+                // Color and input attachments of subpasses refer to the color targets by index
+                let color_target_index = |attachment_index: usize| {
+                    pass.color_attachments
+                        .iter()
+                        .position(|x| *x == attachment_index)
+                        .unwrap() as u32
+                };
 
-                let mut color_render_targets = Vec::with_capacity(MAX_COLOR_ATTACHMENTS);
-
-                for (color_index, attachment_index) in pass.color_attachments.iter().enumerate() {
-                    if let Some(attachment_index) = attachment_index {
-                        let attachment = &pass.attachments[*attachment_index]; //.image.unwrap();
-                        let attachment_usage = &graph.image_usages[attachment.usage.0];
-                        let array_slice = attachment_usage.view_options.array_slice;
-                        let mip_slice = attachment_usage.view_options.mip_slice;
-
-                        let mut resolve_image = None;
-                        let mut resolve_array_slice = None;
-                        let mut resolve_mip_slice = None;
-                        let mut resolve_store_op = RafxStoreOp::DontCare;
-                        if let Some(resolve_attachment_index) =
-                            pass.resolve_attachments[color_index]
-                        {
-                            let resolve_attachment = &pass.attachments[resolve_attachment_index]; //.image.unwrap();
-                            let resolve_attachment_usage =
-                                &graph.image_usages[resolve_attachment.usage.0];
-                            resolve_image = Some(resolve_attachment.image.unwrap());
-                            resolve_array_slice = resolve_attachment_usage.view_options.array_slice;
-                            resolve_mip_slice = resolve_attachment_usage.view_options.mip_slice;
-                            resolve_store_op = resolve_attachment.store_op;
-                        }
+                let subpasses: Vec<_> = pass
+                    .subpasses
+                    .iter()
+                    .map(|subpass| RafxSubpassDef {
+                        color_attachments: subpass
+                            .color_attachments
+                            .iter()
+                            .filter_map(|x| *x)
+                            .map(color_target_index)
+                            .collect(),
+                        input_attachments: subpass
+                            .input_attachments
+                            .iter()
+                            .map(|attachment_index| {
+                                if pass.depth_attachment == Some(*attachment_index) {
+                                    RafxSubpassInputAttachment::DepthStencil
+                                } else {
+                                    RafxSubpassInputAttachment::Color(color_target_index(
+                                        *attachment_index,
+                                    ))
+                                }
+                            })
+                            .collect(),
+                        use_depth_stencil_attachment: subpass.depth_attachment.is_some(),
+                    })
+                    .collect();
 
-                        color_render_targets.push(RenderGraphColorRenderTarget {
-                            image: attachment.image.unwrap(),
-                            load_op: attachment.load_op,
-                            store_op: attachment.store_op,
-                            clear_value: attachment
-                                .clear_color
-                                .clone()
-                                .map(|x| x.to_color_clear_value())
-                                .unwrap_or_default(),
-                            array_slice,
-                            mip_slice,
-                            resolve_image,
-                            resolve_store_op,
-                            resolve_array_slice,
-                            resolve_mip_slice,
-                        });
+                // If every node draws to all the attachments, they can run one after another in
+                // a single subpass, which is supported by all backends
+                let single_subpass = RafxSubpassDef {
+                    color_attachments: (0..pass.color_attachments.len() as u32).collect(),
+                    input_attachments: vec![],
+                    use_depth_stencil_attachment: pass.depth_attachment.is_some(),
+                };
+
+                let (subpasses, render_target_metas) =
+                    if subpasses.iter().all(|subpass| *subpass == single_subpass) {
+                        let render_target_meta = GraphicsPipelineRenderTargetMeta::new(
+                            color_formats,
+                            depth_format,
+                            sample_count.unwrap(),
+                        );
+
+                        (vec![], vec![render_target_meta; pass.nodes.len()])
+                    } else {
+                        let render_target_metas = (0..subpasses.len())
+                            .map(|subpass_index| {
+                                GraphicsPipelineRenderTargetMeta::new_for_subpass(
+                                    color_formats.clone(),
+                                    depth_format,
+                                    sample_count.unwrap(),
+                                    subpasses.clone(),
+                                    subpass_index as u32,
+                                )
+                            })
+                            .collect();
+
+                        (subpasses, render_target_metas)
+                    };
+
+                let mut color_render_targets = Vec::with_capacity(pass.color_attachments.len());
+
+                for attachment_index in &pass.color_attachments {
+                    let attachment = &pass.attachments[*attachment_index]; //.image.unwrap();
+                    let attachment_usage = &graph.image_usages[attachment.usage.0];
+                    let array_slice = attachment_usage.view_options.array_slice;
+                    let mip_slice = attachment_usage.view_options.mip_slice;
+
+                    // Passes with resolves only have a single node, look up the resolve by the
+                    // node's color attachment index
+                    let resolve_attachment_index = pass.subpasses[0]
+                        .color_attachments
+                        .iter()
+                        .position(|x| *x == Some(*attachment_index))
+                        .and_then(|color_index| pass.resolve_attachments[color_index]);
+
+                    let mut resolve_image = None;
+                    let mut resolve_array_slice = None;
+                    let mut resolve_mip_slice = None;
+                    let mut resolve_store_op = RafxStoreOp::DontCare;
+                    if let Some(resolve_attachment_index) = resolve_attachment_index {
+                        let resolve_attachment = &pass.attachments[resolve_attachment_index]; //.image.unwrap();
+                        let resolve_attachment_usage =
+                            &graph.image_usages[resolve_attachment.usage.0];
+                        resolve_image = Some(resolve_attachment.image.unwrap());
+                        resolve_array_slice = resolve_attachment_usage.view_options.array_slice;
+                        resolve_mip_slice = resolve_attachment_usage.view_options.mip_slice;
+                        resolve_store_op = resolve_attachment.store_op;
                     }
+
+                    color_render_targets.push(RenderGraphColorRenderTarget {
+                        image: attachment.image.unwrap(),
+                        load_op: attachment.load_op,
+                        store_op: attachment.store_op,
+                        clear_value: attachment
+                            .clear_color
+                            .clone()
+                            .map(|x| x.to_color_clear_value())
+                            .unwrap_or_default(),
+                        array_slice,
+                        mip_slice,
+                        resolve_image,
+                        resolve_store_op,
+                        resolve_array_slice,
+                        resolve_mip_slice,
+                    });
                 }
 
                 let mut depth_stencil_render_target = None;
@@ -2350,14 +3222,15 @@ fn create_output_passes(
                 }
 
                 let output_pass = RenderGraphOutputRenderPass {
-                    node_id: pass.node_id,
+                    nodes: pass.nodes,
                     attachment_images,
                     pre_pass_barrier: pass.pre_pass_barrier,
                     post_pass_barrier: pass.post_pass_barrier,
                     debug_name,
                     color_render_targets,
                     depth_stencil_render_target,
-                    render_target_meta,
+                    subpasses,
+                    render_target_metas,
                 };
 
                 renderpasses.push(RenderGraphOutputPass::Renderpass(output_pass));
@@ -2375,7 +3248,7 @@ fn create_output_passes(
         }
     }
 
-    renderpasses
+    Ok(renderpasses)
 }
 
 #[allow(dead_code)]
@@ -2544,79 +3417,82 @@ fn print_final_image_usage(
     for (pass_index, pass) in renderpasses.iter().enumerate() {
         log::debug!("pass {}", pass_index);
 
-        let node = graph.node(pass.node());
-        log::debug!("  subpass {:?} {:?}", pass.node(), node.name);
+        for node_id in pass.nodes() {
+            let node = graph.node(*node_id);
+            log::debug!("  subpass {:?} {:?}", node_id, node.name);
 
-        for (color_attachment_index, color_attachment) in node.color_attachments.iter().enumerate()
-        {
-            if let Some(color_attachment) = color_attachment {
-                let read_or_write = color_attachment
+            for (color_attachment_index, color_attachment) in
+                node.color_attachments.iter().enumerate()
+            {
+                if let Some(color_attachment) = color_attachment {
+                    let read_or_write = color_attachment
+                        .read_image
+                        .or_else(|| color_attachment.write_image)
+                        .unwrap();
+                    let physical_image =
+                        assign_physical_resources_result.image_usage_to_physical[&read_or_write];
+                    let write_name = color_attachment
+                        .write_image
+                        .map(|x| graph.image_resource(x).name)
+                        .flatten();
+                    log::debug!(
+                        "    Color Attachment {}: {:?} Name: {:?} Constraints: {:?}",
+                        color_attachment_index,
+                        physical_image,
+                        write_name,
+                        constraint_results.images[&read_or_write]
+                    );
+                }
+            }
+
+            for (resolve_attachment_index, resolve_attachment) in
+                node.resolve_attachments.iter().enumerate()
+            {
+                if let Some(resolve_attachment) = resolve_attachment {
+                    let physical_image = assign_physical_resources_result.image_usage_to_physical
+                        [&resolve_attachment.write_image];
+                    let write_name = graph.image_resource(resolve_attachment.write_image).name;
+                    log::debug!(
+                        "    Resolve Attachment {}: {:?} Name: {:?} Constraints: {:?}",
+                        resolve_attachment_index,
+                        physical_image,
+                        write_name,
+                        constraint_results.images[&resolve_attachment.write_image]
+                    );
+                }
+            }
+
+            if let Some(depth_attachment) = &node.depth_attachment {
+                let read_or_write = depth_attachment
                     .read_image
-                    .or_else(|| color_attachment.write_image)
+                    .or_else(|| depth_attachment.write_image)
                     .unwrap();
                 let physical_image =
                     assign_physical_resources_result.image_usage_to_physical[&read_or_write];
-                let write_name = color_attachment
+                let write_name = depth_attachment
                     .write_image
                     .map(|x| graph.image_resource(x).name)
                     .flatten();
                 log::debug!(
-                    "    Color Attachment {}: {:?} Name: {:?} Constraints: {:?}",
-                    color_attachment_index,
+                    "    Depth Attachment: {:?} Name: {:?} Constraints: {:?}",
                     physical_image,
                     write_name,
                     constraint_results.images[&read_or_write]
                 );
             }
-        }
 
-        for (resolve_attachment_index, resolve_attachment) in
-            node.resolve_attachments.iter().enumerate()
-        {
-            if let Some(resolve_attachment) = resolve_attachment {
-                let physical_image = assign_physical_resources_result.image_usage_to_physical
-                    [&resolve_attachment.write_image];
-                let write_name = graph.image_resource(resolve_attachment.write_image).name;
+            for sampled_image in &node.sampled_images {
+                let physical_image =
+                    assign_physical_resources_result.image_usage_to_physical[sampled_image];
+                let write_name = graph.image_resource(*sampled_image).name;
                 log::debug!(
-                    "    Resolve Attachment {}: {:?} Name: {:?} Constraints: {:?}",
-                    resolve_attachment_index,
+                    "    Sampled: {:?} Name: {:?} Constraints: {:?}",
                     physical_image,
                     write_name,
-                    constraint_results.images[&resolve_attachment.write_image]
+                    constraint_results.images[sampled_image]
                 );
             }
         }
-
-        if let Some(depth_attachment) = &node.depth_attachment {
-            let read_or_write = depth_attachment
-                .read_image
-                .or_else(|| depth_attachment.write_image)
-                .unwrap();
-            let physical_image =
-                assign_physical_resources_result.image_usage_to_physical[&read_or_write];
-            let write_name = depth_attachment
-                .write_image
-                .map(|x| graph.image_resource(x).name)
-                .flatten();
-            log::debug!(
-                "    Depth Attachment: {:?} Name: {:?} Constraints: {:?}",
-                physical_image,
-                write_name,
-                constraint_results.images[&read_or_write]
-            );
-        }
-
-        for sampled_image in &node.sampled_images {
-            let physical_image =
-                assign_physical_resources_result.image_usage_to_physical[sampled_image];
-            let write_name = graph.image_resource(*sampled_image).name;
-            log::debug!(
-                "    Sampled: {:?} Name: {:?} Constraints: {:?}",
-                physical_image,
-                write_name,
-                constraint_results.images[sampled_image]
-            );
-        }
     }
     for output_image in &graph.output_images {
         let physical_image =
@@ -2631,6 +3507,292 @@ fn print_final_image_usage(
     }
 }
 
+//
+// Gather the decisions made while planning into a form that can be exported for debugging. This
+// runs after all other steps so that it sees the final passes, physical resources, and barriers.
+//
+#[profiling::function]
+fn describe_plan(
+    graph: &RenderGraphBuilder,
+    node_execution_order: &[RenderGraphNodeId],
+    virtual_resources: &AssignVirtualResourcesResult,
+    physical_resources: &AssignPhysicalResourcesResult,
+    output_passes: &[RenderGraphOutputPass],
+    node_to_pass_index: &FnvHashMap<RenderGraphNodeId, usize>,
+) -> RenderGraphPlanDescription {
+    let last_pass_index = output_passes.len().saturating_sub(1);
+
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| RenderGraphPlanNodeDescription {
+            id: node.id().0,
+            name: node.name(),
+            execution_index: node_execution_order.iter().position(|x| *x == node.id()),
+            pass_index: node_to_pass_index.get(&node.id()).copied(),
+        })
+        .collect();
+
+    fn describe_barriers(
+        image_barriers: &[PrepassImageBarrier],
+        buffer_barriers: &[PrepassBufferBarrier],
+    ) -> Vec<RenderGraphPlanBarrierDescription> {
+        let mut barriers: Vec<_> = image_barriers
+            .iter()
+            .map(|barrier| RenderGraphPlanBarrierDescription {
+                resource: RenderGraphPlanResourceId::Image(barrier.image.0),
+                old_state: format!("{:?}", barrier.old_state),
+                new_state: format!("{:?}", barrier.new_state),
+            })
+            .chain(
+                buffer_barriers
+                    .iter()
+                    .map(|barrier| RenderGraphPlanBarrierDescription {
+                        resource: RenderGraphPlanResourceId::Buffer(barrier.buffer.0),
+                        old_state: format!("{:?}", barrier.old_state),
+                        new_state: format!("{:?}", barrier.new_state),
+                    }),
+            )
+            .collect();
+
+        barriers.sort_by_key(|barrier| barrier.resource);
+        barriers
+    }
+
+    let mut passes = Vec::with_capacity(output_passes.len());
+    for (pass_index, pass) in output_passes.iter().enumerate() {
+        let (kind, attachment_images) = match pass {
+            RenderGraphOutputPass::Renderpass(renderpass) => (
+                RenderGraphPlanPassKind::Renderpass,
+                renderpass
+                    .attachment_images
+                    .iter()
+                    .map(|image_view| {
+                        physical_resources.image_views[image_view.0]
+                            .physical_image
+                            .0
+                    })
+                    .collect(),
+            ),
+            RenderGraphOutputPass::Compute(_) => (RenderGraphPlanPassKind::Compute, vec![]),
+        };
+
+        let pre_pass_barriers = pass
+            .pre_pass_barrier()
+            .map(|barrier| describe_barriers(&barrier.image_barriers, &barrier.buffer_barriers))
+            .unwrap_or_default();
+        let post_pass_barriers = pass
+            .post_pass_barrier()
+            .map(|barrier| describe_barriers(&barrier.image_barriers, &barrier.buffer_barriers))
+            .unwrap_or_default();
+
+        passes.push(RenderGraphPlanPassDescription {
+            index: pass_index,
+            kind,
+            name: pass.debug_name(),
+            nodes: pass.nodes().iter().map(|node_id| node_id.0).collect(),
+            attachment_images,
+            pre_pass_barriers,
+            post_pass_barriers,
+        });
+    }
+
+    // Returns the range of passes in which the given virtual resources are used, sorted by id. Usages
+    // by the graph output are treated as lasting until the end of the graph. A virtual resource is
+    // named after its lowest usage so that the description doesn't depend on hash map order.
+    fn virtual_resource_lifetimes<UsageIdT: Copy + Ord, UserNodeFnT>(
+        usage_to_virtual: impl Iterator<Item = (UsageIdT, usize)>,
+        user_node: UserNodeFnT,
+        resource_name: impl Fn(UsageIdT) -> Option<RenderGraphResourceName>,
+        node_to_pass_index: &FnvHashMap<RenderGraphNodeId, usize>,
+        last_pass_index: usize,
+    ) -> Vec<RenderGraphPlanVirtualResourceDescription>
+    where
+        UserNodeFnT: Fn(UsageIdT) -> Option<RenderGraphNodeId>,
+    {
+        let mut usage_to_virtual: Vec<_> = usage_to_virtual.collect();
+        usage_to_virtual.sort_by_key(|(usage, _)| *usage);
+
+        let mut lifetimes =
+            FnvHashMap::<usize, RenderGraphPlanVirtualResourceDescription>::default();
+        for (usage, virtual_id) in usage_to_virtual {
+            let pass_index = match user_node(usage) {
+                Some(node_id) => match node_to_pass_index.get(&node_id) {
+                    Some(pass_index) => *pass_index,
+                    None => continue,
+                },
+                None => last_pass_index,
+            };
+
+            let lifetime = lifetimes.entry(virtual_id).or_insert_with(|| {
+                RenderGraphPlanVirtualResourceDescription {
+                    id: virtual_id,
+                    name: resource_name(usage),
+                    first_pass: pass_index,
+                    last_pass: pass_index,
+                }
+            });
+            lifetime.first_pass = lifetime.first_pass.min(pass_index);
+            lifetime.last_pass = lifetime.last_pass.max(pass_index);
+        }
+
+        let mut lifetimes: Vec<_> = lifetimes
+            .into_iter()
+            .map(|(_, lifetime)| lifetime)
+            .collect();
+        lifetimes.sort_by_key(|lifetime| lifetime.id);
+        lifetimes
+    }
+
+    let virtual_images = virtual_resource_lifetimes(
+        virtual_resources
+            .image_usage_to_virtual
+            .iter()
+            .map(|(usage, virtual_image)| (*usage, virtual_image.0)),
+        |usage| match graph.image_usages[usage.0].user {
+            RenderGraphImageUser::Node(node_id) => Some(node_id),
+            RenderGraphImageUser::Output(_) => None,
+        },
+        |usage| graph.image_resource(usage).name,
+        node_to_pass_index,
+        last_pass_index,
+    );
+
+    let virtual_buffers = virtual_resource_lifetimes(
+        virtual_resources
+            .buffer_usage_to_virtual
+            .iter()
+            .map(|(usage, virtual_buffer)| (*usage, virtual_buffer.0)),
+        |usage| match graph.buffer_usage(usage).user {
+            RenderGraphBufferUser::Node(node_id) => Some(node_id),
+            RenderGraphBufferUser::Output(_) => None,
+        },
+        |usage| graph.buffer_resource(usage).name,
+        node_to_pass_index,
+        last_pass_index,
+    );
+
+    let output_images: FnvHashSet<_> = graph
+        .output_images
+        .iter()
+        .map(|output_image| physical_resources.image_usage_to_physical[&output_image.usage])
+        .collect();
+
+    let mut images: Vec<_> = physical_resources
+        .image_specifications
+        .iter()
+        .enumerate()
+        .map(|(index, specification)| RenderGraphPlanImageDescription {
+            id: index,
+            is_output: output_images.contains(&PhysicalImageId(index)),
+            format: specification.format,
+            samples: specification.samples,
+            extents: specification.extents,
+            layer_count: specification.layer_count,
+            mip_count: specification.mip_count,
+            first_pass: last_pass_index,
+            last_pass: 0,
+            virtual_images: vec![],
+        })
+        .collect();
+
+    for virtual_image in virtual_images {
+        let physical_image =
+            physical_resources.image_virtual_to_physical[&VirtualImageId(virtual_image.id)];
+        let image = &mut images[physical_image.0];
+        image.first_pass = image.first_pass.min(virtual_image.first_pass);
+        image.last_pass = image.last_pass.max(virtual_image.last_pass);
+        image.virtual_images.push(virtual_image);
+    }
+
+    let output_buffers: FnvHashSet<_> = graph
+        .output_buffers
+        .iter()
+        .map(|output_buffer| physical_resources.buffer_usage_to_physical[&output_buffer.usage])
+        .collect();
+
+    let mut buffers: Vec<_> = physical_resources
+        .buffer_specifications
+        .iter()
+        .enumerate()
+        .map(|(index, specification)| RenderGraphPlanBufferDescription {
+            id: index,
+            is_output: output_buffers.contains(&PhysicalBufferId(index)),
+            size: specification.size,
+            first_pass: last_pass_index,
+            last_pass: 0,
+            virtual_buffers: vec![],
+        })
+        .collect();
+
+    for virtual_buffer in virtual_buffers {
+        let physical_buffer =
+            physical_resources.buffer_virtual_to_physical[&VirtualBufferId(virtual_buffer.id)];
+        let buffer = &mut buffers[physical_buffer.0];
+        buffer.first_pass = buffer.first_pass.min(virtual_buffer.first_pass);
+        buffer.last_pass = buffer.last_pass.max(virtual_buffer.last_pass);
+        buffer.virtual_buffers.push(virtual_buffer);
+    }
+
+    //
+    // Edges are produced for every version of every resource, including ones used by culled nodes
+    //
+    let mut edges = Vec::default();
+    for image_resource in &graph.image_resources {
+        for version in &image_resource.versions {
+            for read_usage in &version.read_usages {
+                let to_node = match graph.image_usages[read_usage.0].user {
+                    RenderGraphImageUser::Node(node_id) => Some(node_id.0),
+                    RenderGraphImageUser::Output(_) => None,
+                };
+
+                let resource = physical_resources
+                    .image_usage_to_physical
+                    .get(read_usage)
+                    .map(|physical_image| RenderGraphPlanResourceId::Image(physical_image.0));
+
+                edges.push(RenderGraphPlanEdgeDescription {
+                    from_node: version.creator_node.0,
+                    to_node,
+                    name: image_resource.name,
+                    resource,
+                });
+            }
+        }
+    }
+
+    for buffer_resource in &graph.buffer_resources {
+        for version in &buffer_resource.versions {
+            for read_usage in &version.read_usages {
+                let to_node = match graph.buffer_usage(*read_usage).user {
+                    RenderGraphBufferUser::Node(node_id) => Some(node_id.0),
+                    RenderGraphBufferUser::Output(_) => None,
+                };
+
+                let resource = physical_resources
+                    .buffer_usage_to_physical
+                    .get(read_usage)
+                    .map(|physical_buffer| RenderGraphPlanResourceId::Buffer(physical_buffer.0));
+
+                edges.push(RenderGraphPlanEdgeDescription {
+                    from_node: version.creator_node.0,
+                    to_node,
+                    name: buffer_resource.name,
+                    resource,
+                });
+            }
+        }
+    }
+
+    RenderGraphPlanDescription {
+        nodes,
+        passes,
+        images,
+        buffers,
+        edges,
+    }
+}
+
 #[derive(Debug)]
 pub struct RenderGraphPlanOutputImage {
     pub output_id: RenderGraphOutputImageId,
@@ -2651,6 +3813,13 @@ pub struct RenderGraphPlan {
     pub(super) output_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanOutputBuffer>,
     pub(super) intermediate_images: FnvHashMap<PhysicalImageId, RenderGraphImageSpecification>,
     pub(super) intermediate_buffers: FnvHashMap<PhysicalBufferId, RenderGraphBufferSpecification>,
+    pub(super) intermediate_image_lifetimes:
+        FnvHashMap<PhysicalImageId, RenderGraphResourceLifetime>,
+    pub(super) intermediate_buffer_lifetimes:
+        FnvHashMap<PhysicalBufferId, RenderGraphResourceLifetime>,
+    // Intermediate resources in the same slot are never alive at the same time
+    pub(super) image_memory_slots: Vec<Vec<PhysicalImageId>>,
+    pub(super) buffer_memory_slots: Vec<Vec<PhysicalBufferId>>,
     pub(super) image_views: Vec<RenderGraphImageView>, // index by physical image view id
     pub(super) node_to_pass_index: FnvHashMap<RenderGraphNodeId, usize>,
     pub(super) _image_usage_to_physical: FnvHashMap<RenderGraphImageUsageId, PhysicalImageId>,
@@ -2662,11 +3831,15 @@ pub struct RenderGraphPlan {
         FnvHashMap<RenderGraphNodeId, RenderGraphNodeVisitNodeCallback>,
     pub(super) _render_phase_dependencies:
         FnvHashMap<RenderGraphNodeId, FnvHashSet<RenderPhaseIndex>>,
+
+    // debugging
+    pub(super) node_names: Vec<Option<RenderGraphNodeName>>, // index by node id
+    pub(super) description: Option<RenderGraphPlanDescription>,
 }
 
 impl RenderGraphPlan {
     #[profiling::function]
-    pub(super) fn new(mut graph: RenderGraphBuilder) -> RenderGraphPlan {
+    pub(super) fn new(mut graph: RenderGraphBuilder) -> RenderGraphPlanResult<RenderGraphPlan> {
         log::trace!("-- Create render graph plan --");
 
         //
@@ -2676,7 +3849,7 @@ impl RenderGraphPlan {
         // an implementation detail, we try to put renderpass merge candidates adjacent to each
         // other in this list
         //
-        let node_execution_order = determine_node_order(&graph);
+        let node_execution_order = determine_node_order(&graph)?;
 
         // Print out the execution order
         log::trace!("Execution order of unculled nodes:");
@@ -2688,10 +3861,10 @@ impl RenderGraphPlan {
         // Traverse the graph to determine specifications for all images that will be used. This
         // iterates forwards and backwards through the node graph. This allows us to specify
         // attributes about images (like format, sample count) in key areas and infer it elsewhere.
-        // If there is not enough information to infer then the render graph cannot be used and
-        // building it will panic.
+        // If there is not enough information to infer, or the constraints conflict, then the
+        // render graph cannot be used and building it will return an error.
         //
-        let mut constraint_results = determine_constraints(&graph, &node_execution_order);
+        let mut constraint_results = determine_constraints(&graph, &node_execution_order)?;
 
         // Look at all image versions and ensure a constraint exists for usages where the node was
         // not culled
@@ -2704,7 +3877,7 @@ impl RenderGraphPlan {
         // Add resolves to the graph - this will occur when a renderpass outputs a multisample image
         // to a renderpass that is expecting a non-multisampled image.
         //
-        insert_resolves(&mut graph, &node_execution_order, &mut constraint_results);
+        insert_resolves(&mut graph, &node_execution_order, &mut constraint_results)?;
 
         // Print the cases where we can't reuse images
         //print_image_compatibility(&graph, &constraint_results);
@@ -2714,7 +3887,7 @@ impl RenderGraphPlan {
         // if we are not reusing or aliasing. (We reuse when we assign physical indexes)
         //
         let assign_virtual_images_result =
-            assign_virtual_resources(&graph, &node_execution_order, &mut constraint_results);
+            assign_virtual_resources(&graph, &node_execution_order, &mut constraint_results)?;
 
         //
         // Combine nodes into passes where possible
@@ -2724,18 +3897,74 @@ impl RenderGraphPlan {
             &node_execution_order,
             &constraint_results,
             &assign_virtual_images_result,
-        );
+        )?;
+
+        //
+        // Only store attachments whose contents are needed after their pass ends
+        //
+        determine_attachment_store_ops(&graph, &mut passes);
 
         //
         // Find virtual images with matching specification and non-overlapping lifetimes. Assign
         // the same physical index to them so that we reuse a single allocation
         //
-        let assign_physical_resources_result = assign_physical_resources(
+        let mut assign_physical_resources_result = assign_physical_resources(
             &graph,
             &constraint_results,
             &assign_virtual_images_result,
             &mut passes,
+        )?;
+
+        //
+        // Find images that are only used within a single pass so the backend can avoid allocating
+        // memory for them
+        //
+        mark_transient_images(
+            &graph,
+            &assign_virtual_images_result,
+            &mut assign_physical_resources_result,
+            &mut passes,
+        );
+
+        let (physical_image_lifetimes, physical_buffer_lifetimes) =
+            determine_physical_resource_lifetimes(
+                &graph,
+                &assign_physical_resources_result,
+                &passes,
+            );
+
+        //
+        // Group intermediate resources with non-overlapping lifetimes so that they share memory.
+        // Output resources are provided by the caller so they are never aliased.
+        //
+        let output_image_physical_ids: FnvHashSet<_> = graph
+            .output_images
+            .iter()
+            .map(|output_image| {
+                assign_physical_resources_result.image_usage_to_physical[&output_image.usage]
+            })
+            .collect();
+        let output_buffer_physical_ids: FnvHashSet<_> = graph
+            .output_buffers
+            .iter()
+            .map(|output_buffer| {
+                assign_physical_resources_result.buffer_usage_to_physical[&output_buffer.usage]
+            })
+            .collect();
+
+        let intermediate_image_lifetimes: FnvHashMap<_, _> = physical_image_lifetimes
+            .into_iter()
+            .filter(|(physical_image, _)| !output_image_physical_ids.contains(physical_image))
+            .collect();
+        let intermediate_buffer_lifetimes: FnvHashMap<_, _> = physical_buffer_lifetimes
+            .into_iter()
+            .filter(|(physical_buffer, _)| !output_buffer_physical_ids.contains(physical_buffer))
+            .collect();
+        let image_memory_slots = assign_image_memory_slots(
+            &assign_physical_resources_result,
+            &intermediate_image_lifetimes,
         );
+        let buffer_memory_slots = assign_memory_slots(&intermediate_buffer_lifetimes);
 
         // log::trace!("Merged Renderpasses:");
         // for (index, pass) in passes.iter().enumerate() {
@@ -2775,6 +4004,8 @@ impl RenderGraphPlan {
             &constraint_results,
             &assign_physical_resources_result,
             &node_barriers,
+            &image_memory_slots,
+            &buffer_memory_slots,
             &mut passes,
         );
 
@@ -2813,7 +4044,7 @@ impl RenderGraphPlan {
         // passed into the resource system to create the renderpass but also includes other metadata
         // required to push them through the command queue
         //
-        let output_passes = create_output_passes(&graph, passes);
+        let output_passes = create_output_passes(&graph, passes)?;
 
         //
         // Separate the output images from the intermediate images (the rendergraph will be
@@ -2821,7 +4052,6 @@ impl RenderGraphPlan {
         //
         let mut output_images: FnvHashMap<PhysicalImageViewId, RenderGraphPlanOutputImage> =
             Default::default();
-        let mut output_image_physical_ids = FnvHashSet::default();
         for output_image in &graph.output_images {
             let output_image_view =
                 assign_physical_resources_result.image_usage_to_image_view[&output_image.usage];
@@ -2833,15 +4063,10 @@ impl RenderGraphPlan {
                     dst_image: output_image.dst_image.clone(),
                 },
             );
-
-            output_image_physical_ids.insert(
-                assign_physical_resources_result.image_views[output_image_view.0].physical_image,
-            );
         }
 
         let mut output_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanOutputBuffer> =
             Default::default();
-        let mut output_buffer_physical_ids = FnvHashSet::default();
         for output_buffer in &graph.output_buffers {
             let output_buffer_id =
                 assign_physical_resources_result.buffer_usage_to_physical[&output_buffer.usage];
@@ -2853,8 +4078,6 @@ impl RenderGraphPlan {
                     dst_buffer: output_buffer.dst_buffer.clone(),
                 },
             );
-
-            output_buffer_physical_ids.insert(output_buffer_id);
         }
 
         let mut intermediate_images: FnvHashMap<PhysicalImageId, RenderGraphImageSpecification> =
@@ -2887,6 +4110,14 @@ impl RenderGraphPlan {
             intermediate_buffers.insert(physical_buffer, specification.clone());
         }
 
+        // Every intermediate resource is used by a pass, so they are all allocated by a memory slot
+        debug_assert!(intermediate_images
+            .keys()
+            .all(|id| intermediate_image_lifetimes.contains_key(id)));
+        debug_assert!(intermediate_buffers
+            .keys()
+            .all(|id| intermediate_buffer_lifetimes.contains_key(id)));
+
         // log::trace!("-- RENDERPASS {} --", renderpass_index);
         // for (renderpass_index, renderpass) in renderpasses.iter().enumerate() {
         //     log::trace!("-- RENDERPASS {} --", renderpass_index);
@@ -2908,15 +4139,39 @@ impl RenderGraphPlan {
         //
         let mut node_to_pass_index = FnvHashMap::default();
         for (pass_index, pass) in output_passes.iter().enumerate() {
-            node_to_pass_index.insert(pass.node(), pass_index);
+            for node_id in pass.nodes() {
+                node_to_pass_index.insert(*node_id, pass_index);
+            }
         }
 
-        RenderGraphPlan {
+        //
+        // Record the decisions made during planning so that the plan can be inspected or exported
+        //
+        let description = if graph.describe_plan {
+            Some(describe_plan(
+                &graph,
+                &node_execution_order,
+                &assign_virtual_images_result,
+                &assign_physical_resources_result,
+                &output_passes,
+                &node_to_pass_index,
+            ))
+        } else {
+            None
+        };
+
+        let node_names = graph.nodes.iter().map(|node| node.name()).collect();
+
+        Ok(RenderGraphPlan {
             passes: output_passes,
             output_images,
             output_buffers,
             intermediate_images,
             intermediate_buffers,
+            intermediate_image_lifetimes,
+            intermediate_buffer_lifetimes,
+            image_memory_slots,
+            buffer_memory_slots,
             image_views: assign_physical_resources_result.image_views,
             node_to_pass_index,
             _image_usage_to_physical: assign_physical_resources_result.image_usage_to_physical,
@@ -2925,6 +4180,688 @@ impl RenderGraphPlan {
 
             visit_node_callbacks: graph.visit_node_callbacks,
             _render_phase_dependencies: graph.render_phase_dependencies,
+            node_names,
+            description,
+        })
+    }
+
+    /// Describes the nodes, passes, resources, and barriers chosen by the plan. None unless
+    /// RenderGraphBuilder::set_describe_plan() was enabled
+    pub fn description(&self) -> Option<&RenderGraphPlanDescription> {
+        self.description.as_ref()
+    }
+
+    /// Exports the plan as a graphviz (DOT) graph. None unless
+    /// RenderGraphBuilder::set_describe_plan() was enabled
+    pub fn to_dot(&self) -> Option<String> {
+        self.description.as_ref().map(|x| x.to_dot())
+    }
+
+    /// Exports the plan as JSON. None unless RenderGraphBuilder::set_describe_plan() was enabled
+    pub fn to_json(&self) -> Option<String> {
+        self.description.as_ref().map(|x| x.to_json())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn color_constraint() -> RenderGraphImageConstraint {
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            format: Some(RafxFormat::R8G8B8A8_UNORM),
+            extents: Some(RenderGraphImageExtents::MatchSurface),
+            ..Default::default()
+        }
+    }
+
+    fn depth_constraint() -> RenderGraphImageConstraint {
+        RenderGraphImageConstraint {
+            format: Some(RafxFormat::D32_SFLOAT),
+            ..color_constraint()
+        }
+    }
+
+    // Runs planning up to the point renderpasses are built. The execution order is given because
+    // determining it requires output images, which can't be created without a device
+    fn build_passes(
+        graph: &RenderGraphBuilder,
+        node_execution_order: &[RenderGraphNodeId],
+    ) -> RenderGraphPlanResult<Vec<RenderGraphPass>> {
+        let mut constraints = determine_constraints(graph, node_execution_order)?;
+        let virtual_resources =
+            assign_virtual_resources(graph, node_execution_order, &mut constraints)?;
+        let mut passes = build_physical_passes(
+            graph,
+            node_execution_order,
+            &constraints,
+            &virtual_resources,
+        )?;
+        determine_attachment_store_ops(graph, &mut passes);
+        Ok(passes)
+    }
+
+    fn renderpass(pass: &RenderGraphPass) -> &RenderGraphRenderPass {
+        match pass {
+            RenderGraphPass::Renderpass(renderpass) => renderpass,
+            RenderGraphPass::Compute(_) => panic!("expected a renderpass"),
+        }
+    }
+
+    fn lifetime(
+        first_pass: usize,
+        last_pass: usize,
+    ) -> RenderGraphResourceLifetime {
+        RenderGraphResourceLifetime {
+            first_pass,
+            last_pass,
+        }
+    }
+
+    #[test]
+    fn test_assign_memory_slots_by_lifetime_overlap() {
+        let mut lifetimes = FnvHashMap::default();
+        lifetimes.insert(PhysicalImageId(0), lifetime(0, 1));
+        // Overlaps image 0 in pass 1
+        lifetimes.insert(PhysicalImageId(1), lifetime(1, 2));
+        // Starts after image 0 ends
+        lifetimes.insert(PhysicalImageId(2), lifetime(2, 3));
+        // Starts after images 1 and 2 end
+        lifetimes.insert(PhysicalImageId(3), lifetime(4, 4));
+        // Overlaps everything
+        lifetimes.insert(PhysicalImageId(4), lifetime(0, 4));
+
+        let slots = assign_memory_slots(&lifetimes);
+        assert_eq!(
+            slots,
+            vec![
+                vec![PhysicalImageId(0), PhysicalImageId(2), PhysicalImageId(3)],
+                vec![PhysicalImageId(4)],
+                vec![PhysicalImageId(1)],
+            ]
+        );
+
+        // No two resources in a slot are alive at the same time
+        for slot in &slots {
+            for occupants in slot.windows(2) {
+                assert!(lifetimes[&occupants[0]].last_pass < lifetimes[&occupants[1]].first_pass);
+            }
+        }
+    }
+
+    // A depth prepass followed by an opaque pass that also writes color, and a node that samples
+    // the color
+    fn depth_prepass_graph(
+        supports_subpasses: bool
+    ) -> (RenderGraphBuilder, Vec<RenderGraphNodeId>) {
+        let mut graph = RenderGraphBuilder::default();
+        graph.set_supports_subpasses(supports_subpasses);
+
+        let prepass = graph.add_node("depth_prepass", RenderGraphQueue::DefaultGraphics);
+        let depth = graph.create_depth_attachment(
+            prepass,
+            Some(Default::default()),
+            depth_constraint(),
+            Default::default(),
+        );
+
+        let opaque = graph.add_node("opaque", RenderGraphQueue::DefaultGraphics);
+        graph.read_depth_attachment(opaque, depth, depth_constraint(), Default::default());
+        let color = graph.create_color_attachment(
+            opaque,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(post, color, color_constraint(), Default::default());
+        graph.create_color_attachment(
+            post,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        (graph, vec![prepass, opaque, post])
+    }
+
+    #[test]
+    fn test_merge_nodes_with_different_attachments_as_subpasses() {
+        let (graph, node_execution_order) = depth_prepass_graph(true);
+        let passes = build_passes(&graph, &node_execution_order).unwrap();
+
+        // The sampling node can't be merged because it reads what the pass wrote
+        assert_eq!(passes.len(), 2);
+        let pass = renderpass(&passes[0]);
+        assert_eq!(pass.nodes, &node_execution_order[0..2]);
+        assert_eq!(pass.subpasses.len(), 2);
+        assert_eq!(pass.attachments.len(), 2);
+
+        let depth_attachment = pass.depth_attachment.unwrap();
+        assert_eq!(pass.subpasses[0].depth_attachment, Some(depth_attachment));
+        assert_eq!(pass.subpasses[0].color_attachments[0], None);
+        assert_eq!(pass.subpasses[1].depth_attachment, Some(depth_attachment));
+        assert_eq!(pass.color_attachments.len(), 1);
+        assert_eq!(
+            pass.subpasses[1].color_attachments[0],
+            Some(pass.color_attachments[0])
+        );
+
+        // Depth is not used after the pass, but color is sampled by a later pass
+        let depth = &pass.attachments[depth_attachment];
+        assert_eq!(depth.load_op, RafxLoadOp::Clear);
+        assert_eq!(depth.store_op, RafxStoreOp::DontCare);
+        let color = &pass.attachments[pass.color_attachments[0]];
+        assert_eq!(color.load_op, RafxLoadOp::Clear);
+        assert_eq!(color.store_op, RafxStoreOp::Store);
+    }
+
+    #[test]
+    fn test_no_subpass_merge_without_backend_support() {
+        let (graph, node_execution_order) = depth_prepass_graph(false);
+        let passes = build_passes(&graph, &node_execution_order).unwrap();
+
+        assert_eq!(passes.len(), 3);
+        let prepass = renderpass(&passes[0]);
+        let depth = &prepass.attachments[prepass.depth_attachment.unwrap()];
+        assert_eq!(depth.store_op, RafxStoreOp::Store);
+
+        let opaque = renderpass(&passes[1]);
+        let depth = &opaque.attachments[opaque.depth_attachment.unwrap()];
+        assert_eq!(depth.load_op, RafxLoadOp::Load);
+        assert_eq!(depth.store_op, RafxStoreOp::DontCare);
+    }
+
+    // Writes a color attachment, then reads it as an input attachment while writing another one.
+    // The second node is sampled so that its output is stored
+    fn input_attachment_graph(
+        supports_subpasses: bool
+    ) -> (RenderGraphBuilder, Vec<RenderGraphNodeId>) {
+        let mut graph = RenderGraphBuilder::default();
+        graph.set_supports_subpasses(supports_subpasses);
+
+        let gbuffer = graph.add_node("gbuffer", RenderGraphQueue::DefaultGraphics);
+        let albedo = graph.create_color_attachment(
+            gbuffer,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        let lighting = graph.add_node("lighting", RenderGraphQueue::DefaultGraphics);
+        graph.read_input_attachment(lighting, albedo, color_constraint(), Default::default());
+        let lit = graph.create_color_attachment(
+            lighting,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(post, lit, color_constraint(), Default::default());
+        graph.create_color_attachment(
+            post,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        (graph, vec![gbuffer, lighting, post])
+    }
+
+    #[test]
+    fn test_input_attachment_stays_in_renderpass() {
+        let (graph, node_execution_order) = input_attachment_graph(true);
+        let passes = build_passes(&graph, &node_execution_order).unwrap();
+
+        assert_eq!(passes.len(), 2);
+        let pass = renderpass(&passes[0]);
+        assert_eq!(pass.subpasses.len(), 2);
+        assert_eq!(pass.color_attachments.len(), 2);
+
+        let albedo = pass.subpasses[0].color_attachments[0].unwrap();
+        let lit = pass.subpasses[1].color_attachments[0].unwrap();
+        assert_ne!(albedo, lit);
+        assert_eq!(pass.subpasses[1].input_attachments, vec![albedo]);
+
+        // The input attachment is only read within the pass, so it is never stored
+        assert_eq!(pass.attachments[albedo].store_op, RafxStoreOp::DontCare);
+        assert_eq!(pass.attachments[lit].store_op, RafxStoreOp::Store);
+    }
+
+    #[test]
+    fn test_input_attachment_requires_subpass_support() {
+        let (graph, node_execution_order) = input_attachment_graph(false);
+        match build_passes(&graph, &node_execution_order) {
+            Err(RenderGraphPlanError::UnsupportedInputAttachment { image }) => {
+                assert_eq!(image.node.unwrap().id, node_execution_order[1]);
+            }
+            _ => panic!("expected an UnsupportedInputAttachment error"),
+        }
+    }
+
+    // Orders the nodes the given node depends on, as determine_node_order does for the nodes that
+    // write output images
+    fn determine_node_order_from(
+        graph: &RenderGraphBuilder,
+        node_id: RenderGraphNodeId,
+    ) -> RenderGraphPlanResult<Vec<RenderGraphNodeId>> {
+        let mut visited = vec![false; graph.nodes.len()];
+        let mut visiting = vec![false; graph.nodes.len()];
+        let mut visiting_stack = Vec::default();
+        let mut ordered_list = Vec::default();
+        visit_node(
+            graph,
+            node_id,
+            &mut visited,
+            &mut visiting,
+            &mut visiting_stack,
+            &mut ordered_list,
+        )?;
+        Ok(ordered_list)
+    }
+
+    // Runs planning up to the point renderpasses are converted to what the graph executes
+    fn build_output_passes(
+        graph: &RenderGraphBuilder,
+        node_execution_order: &[RenderGraphNodeId],
+    ) -> RenderGraphPlanResult<Vec<RenderGraphOutputPass>> {
+        let mut constraints = determine_constraints(graph, node_execution_order)?;
+        let virtual_resources =
+            assign_virtual_resources(graph, node_execution_order, &mut constraints)?;
+        let mut passes = build_physical_passes(
+            graph,
+            node_execution_order,
+            &constraints,
+            &virtual_resources,
+        )?;
+        determine_attachment_store_ops(graph, &mut passes);
+        assign_physical_resources(graph, &constraints, &virtual_resources, &mut passes)?;
+        create_output_passes(graph, passes)
+    }
+
+    #[test]
+    fn test_cycle_error() {
+        let mut graph = RenderGraphBuilder::default();
+        let first = graph.add_node("first", RenderGraphQueue::DefaultGraphics);
+        let second = graph.add_node("second", RenderGraphQueue::DefaultGraphics);
+
+        let first_output = graph.create_color_attachment(
+            first,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+        graph.sample_image(second, first_output, color_constraint(), Default::default());
+        let second_output = graph.create_color_attachment(
+            second,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+        graph.sample_image(first, second_output, color_constraint(), Default::default());
+
+        match determine_node_order_from(&graph, second) {
+            Err(RenderGraphPlanError::Cycle { nodes }) => {
+                let node_ids: Vec<_> = nodes.iter().map(|x| x.id).collect();
+                assert_eq!(node_ids, vec![second, first]);
+                assert_eq!(nodes[1].name, Some("first"));
+            }
+            _ => panic!("expected a Cycle error"),
+        }
+    }
+
+    #[test]
+    fn test_image_format_conflict_error() {
+        let mut graph = RenderGraphBuilder::default();
+        let write = graph.add_node("write", RenderGraphQueue::DefaultGraphics);
+        let image = graph.create_color_attachment(
+            write,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        let read = graph.add_node("read", RenderGraphQueue::DefaultGraphics);
+        let hdr_constraint = RenderGraphImageConstraint {
+            format: Some(RafxFormat::R16G16B16A16_SFLOAT),
+            ..color_constraint()
+        };
+        graph.modify_color_attachment(read, image, 0, None, hdr_constraint, Default::default());
+
+        match build_passes(&graph, &[write, read]) {
+            Err(RenderGraphPlanError::ImageFormatConflict {
+                written,
+                used,
+                written_format,
+                used_format,
+            }) => {
+                assert_eq!(written.node.unwrap().id, write);
+                assert_eq!(used.node.unwrap().id, read);
+                assert_eq!(written_format, RafxFormat::R8G8B8A8_UNORM);
+                assert_eq!(used_format, RafxFormat::R16G16B16A16_SFLOAT);
+            }
+            _ => panic!("expected an ImageFormatConflict error"),
+        }
+    }
+
+    #[test]
+    fn test_write_to_read_only_image_error() {
+        let mut graph = RenderGraphBuilder::default();
+        let write = graph.add_node("write", RenderGraphQueue::DefaultGraphics);
+        let image = graph.create_color_attachment(
+            write,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        // Both nodes modify the same version of the image, so neither can have it exclusively
+        let first_modify = graph.add_node("first_modify", RenderGraphQueue::DefaultGraphics);
+        graph.modify_color_attachment(
+            first_modify,
+            image,
+            0,
+            None,
+            color_constraint(),
+            Default::default(),
+        );
+        let second_modify = graph.add_node("second_modify", RenderGraphQueue::DefaultGraphics);
+        graph.modify_color_attachment(
+            second_modify,
+            image,
+            0,
+            None,
+            color_constraint(),
+            Default::default(),
+        );
+
+        match build_passes(&graph, &[write, first_modify, second_modify]) {
+            Err(RenderGraphPlanError::WriteToReadOnlyImage { written, used }) => {
+                assert_eq!(written.node.unwrap().id, write);
+                assert_eq!(used.node.unwrap().id, first_modify);
+            }
+            _ => panic!("expected a WriteToReadOnlyImage error"),
         }
     }
+
+    #[test]
+    fn test_attachment_sample_count_conflict_error() {
+        let mut graph = RenderGraphBuilder::default();
+        let node = graph.add_node("msaa", RenderGraphQueue::DefaultGraphics);
+        let color = graph.create_color_attachment(
+            node,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+        graph.create_depth_attachment(
+            node,
+            Some(Default::default()),
+            RenderGraphImageConstraint {
+                samples: Some(RafxSampleCount::SampleCount4),
+                ..depth_constraint()
+            },
+            Default::default(),
+        );
+
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(post, color, color_constraint(), Default::default());
+        graph.create_color_attachment(
+            post,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+
+        match build_output_passes(&graph, &[node, post]) {
+            Err(RenderGraphPlanError::AttachmentSampleCountConflict {
+                node: error_node,
+                expected_samples,
+                found_samples,
+            }) => {
+                assert_eq!(error_node.id, node);
+                // The depth attachment doesn't match the color attachment checked before it
+                assert_eq!(expected_samples, RafxSampleCount::SampleCount4);
+                assert_eq!(found_samples, RafxSampleCount::SampleCount1);
+            }
+            _ => panic!("expected an AttachmentSampleCountConflict error"),
+        }
+    }
+
+    #[test]
+    fn test_image_specification_missing_error() {
+        let mut graph = RenderGraphBuilder::default();
+        let write = graph.add_node("write", RenderGraphQueue::DefaultGraphics);
+        let image = graph.create_color_attachment(
+            write,
+            0,
+            Some(Default::default()),
+            RenderGraphImageConstraint {
+                format: None,
+                ..color_constraint()
+            },
+            Default::default(),
+        );
+        graph.set_image_name(image, "unformatted");
+
+        let read = graph.add_node("read", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(
+            read,
+            image,
+            RenderGraphImageConstraint::default(),
+            Default::default(),
+        );
+
+        match build_passes(&graph, &[write, read]) {
+            Err(RenderGraphPlanError::ImageSpecificationMissing { image }) => {
+                assert_eq!(image.name, Some("unformatted"));
+            }
+            _ => panic!("expected an ImageSpecificationMissing error"),
+        }
+    }
+
+    // Runs all of planning that doesn't need output images and describes the result
+    fn describe_graph(
+        graph: &RenderGraphBuilder,
+        node_execution_order: &[RenderGraphNodeId],
+    ) -> RenderGraphPlanResult<RenderGraphPlanDescription> {
+        let mut constraints = determine_constraints(graph, node_execution_order)?;
+        let virtual_resources =
+            assign_virtual_resources(graph, node_execution_order, &mut constraints)?;
+        let mut passes = build_physical_passes(
+            graph,
+            node_execution_order,
+            &constraints,
+            &virtual_resources,
+        )?;
+        determine_attachment_store_ops(graph, &mut passes);
+        let mut physical_resources =
+            assign_physical_resources(graph, &constraints, &virtual_resources, &mut passes)?;
+        mark_transient_images(
+            graph,
+            &virtual_resources,
+            &mut physical_resources,
+            &mut passes,
+        );
+
+        let (image_lifetimes, buffer_lifetimes) =
+            determine_physical_resource_lifetimes(graph, &physical_resources, &passes);
+        let image_memory_slots = assign_image_memory_slots(&physical_resources, &image_lifetimes);
+        let buffer_memory_slots = assign_memory_slots(&buffer_lifetimes);
+
+        let node_barriers = build_node_barriers(
+            graph,
+            node_execution_order,
+            &constraints,
+            &physical_resources,
+        );
+        build_pass_barriers(
+            graph,
+            node_execution_order,
+            &constraints,
+            &physical_resources,
+            &node_barriers,
+            &image_memory_slots,
+            &buffer_memory_slots,
+            &mut passes,
+        );
+
+        let output_passes = create_output_passes(graph, passes)?;
+        let mut node_to_pass_index = FnvHashMap::default();
+        for (pass_index, pass) in output_passes.iter().enumerate() {
+            for node_id in pass.nodes() {
+                node_to_pass_index.insert(*node_id, pass_index);
+            }
+        }
+
+        Ok(describe_plan(
+            graph,
+            node_execution_order,
+            &virtual_resources,
+            &physical_resources,
+            &output_passes,
+            &node_to_pass_index,
+        ))
+    }
+
+    #[test]
+    fn test_describe_plan() {
+        let mut graph = RenderGraphBuilder::default();
+        graph.set_supports_subpasses(true);
+
+        let prepass = graph.add_node("depth_prepass", RenderGraphQueue::DefaultGraphics);
+        let depth = graph.create_depth_attachment(
+            prepass,
+            Some(Default::default()),
+            depth_constraint(),
+            Default::default(),
+        );
+        graph.set_image_name(depth, "depth");
+
+        let opaque = graph.add_node("opaque", RenderGraphQueue::DefaultGraphics);
+        graph.read_depth_attachment(opaque, depth, depth_constraint(), Default::default());
+        let color = graph.create_color_attachment(
+            opaque,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+        graph.set_image_name(color, "color");
+
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(post, color, color_constraint(), Default::default());
+        let post_color = graph.create_color_attachment(
+            post,
+            0,
+            Some(Default::default()),
+            color_constraint(),
+            Default::default(),
+        );
+        graph.set_image_name(post_color, "post_color");
+
+        // Not in the execution order, so it is described as culled
+        let culled = graph.add_node("culled", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(culled, color, color_constraint(), Default::default());
+
+        let description = describe_graph(&graph, &[prepass, opaque, post]).unwrap();
+
+        let expected_dot = r#"digraph render_graph_plan {
+    rankdir=LR;
+    node [shape=box];
+    subgraph cluster_pass_0 {
+        label="pass 0: depth_prepass (Renderpass)\lpre: image 0 UNDEFINED -> DEPTH_WRITE\lpre: image 1 UNDEFINED -> RENDER_TARGET\l";
+        node_0 [label="0: depth_prepass"];
+        node_1 [label="1: opaque"];
+    }
+    subgraph cluster_pass_1 {
+        label="pass 1: post (Renderpass)\lpre: image 1 RENDER_TARGET -> PIXEL_SHADER_RESOURCE\lpre: image 2 UNDEFINED -> RENDER_TARGET\l";
+        node_2 [label="2: post"];
+    }
+    node_3 [label="3: culled (culled)", style=dashed, color=gray];
+    output [label="output", shape=doublecircle];
+    node_0 -> node_1 [label="depth (image 0)"];
+    node_1 -> node_2 [label="color (image 1)"];
+    node_1 -> node_3 [label="color", style=dashed, color=gray];
+    image_0 [label="image 0: D32_SFLOAT SampleCount1\lpasses 0-0\l  virtual 0 depth: passes 0-0\l", shape=note];
+    image_1 [label="image 1: R8G8B8A8_UNORM SampleCount1\lpasses 0-1\l  virtual 1 color: passes 0-1\l", shape=note];
+    image_2 [label="image 2: R8G8B8A8_UNORM SampleCount1\lpasses 1-1\l  virtual 2 post_color: passes 1-1\l", shape=note];
+}
+"#;
+        assert_eq!(description.to_dot(), expected_dot);
+
+        let json: serde_json::Value = serde_json::from_str(&description.to_json()).unwrap();
+        assert_eq!(
+            json["nodes"],
+            serde_json::json!([
+                { "id": 0, "name": "depth_prepass", "execution_index": 0, "pass_index": 0 },
+                { "id": 1, "name": "opaque", "execution_index": 1, "pass_index": 0 },
+                { "id": 2, "name": "post", "execution_index": 2, "pass_index": 1 },
+                { "id": 3, "name": "culled", "execution_index": null, "pass_index": null },
+            ])
+        );
+        assert_eq!(
+            json["passes"][1],
+            serde_json::json!({
+                "index": 1,
+                "kind": "Renderpass",
+                "name": "post",
+                "nodes": [2],
+                "attachment_images": [2],
+                "pre_pass_barriers": [
+                    {
+                        "resource": { "Image": 1 },
+                        "old_state": "RENDER_TARGET",
+                        "new_state": "PIXEL_SHADER_RESOURCE"
+                    },
+                    {
+                        "resource": { "Image": 2 },
+                        "old_state": "UNDEFINED",
+                        "new_state": "RENDER_TARGET"
+                    },
+                ],
+                "post_pass_barriers": [],
+            })
+        );
+        assert_eq!(
+            json["images"][1],
+            serde_json::json!({
+                "id": 1,
+                "is_output": false,
+                "format": "R8G8B8A8_UNORM",
+                "samples": "SampleCount1",
+                "extents": "MatchSurface",
+                "layer_count": 1,
+                "mip_count": 1,
+                "first_pass": 0,
+                "last_pass": 1,
+                "virtual_images": [
+                    { "id": 1, "name": "color", "first_pass": 0, "last_pass": 1 },
+                ],
+            })
+        );
+        assert_eq!(json["buffers"], serde_json::json!([]));
+        assert_eq!(
+            json["edges"][2],
+            serde_json::json!({ "from_node": 1, "to_node": 3, "name": "color", "resource": null })
+        );
+
+        // Planning the same graph again produces an identical description
+        let description_again = describe_graph(&graph, &[prepass, opaque, post]).unwrap();
+        assert_eq!(description_again.to_json(), description.to_json());
+    }
 }
@@ -0,0 +1,278 @@
+use super::*;
+use rafx_api::{RafxFormat, RafxSampleCount};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A node in the graph, including nodes that were culled
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanNodeDescription {
+    pub id: usize,
+    pub name: Option<RenderGraphNodeName>,
+    /// Position of the node in the execution order, or None if the node was culled
+    pub execution_index: Option<usize>,
+    /// The pass the node was placed in, or None if the node was culled
+    pub pass_index: Option<usize>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum RenderGraphPlanPassKind {
+    Renderpass,
+    Compute,
+}
+
+/// A physical image or buffer allocated by the plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum RenderGraphPlanResourceId {
+    Image(usize),
+    Buffer(usize),
+}
+
+impl std::fmt::Display for RenderGraphPlanResourceId {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            RenderGraphPlanResourceId::Image(id) => write!(f, "image {}", id),
+            RenderGraphPlanResourceId::Buffer(id) => write!(f, "buffer {}", id),
+        }
+    }
+}
+
+/// A state transition for a physical resource inserted before or after a pass
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanBarrierDescription {
+    pub resource: RenderGraphPlanResourceId,
+    pub old_state: String,
+    pub new_state: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanPassDescription {
+    pub index: usize,
+    pub kind: RenderGraphPlanPassKind,
+    pub name: Option<RenderGraphNodeName>,
+    /// The nodes merged into this pass, one per subpass, in execution order
+    pub nodes: Vec<usize>,
+    /// Physical images bound as attachments of the pass (empty for compute passes)
+    pub attachment_images: Vec<usize>,
+    pub pre_pass_barriers: Vec<RenderGraphPlanBarrierDescription>,
+    pub post_pass_barriers: Vec<RenderGraphPlanBarrierDescription>,
+}
+
+/// A virtual resource that was assigned to a physical resource. Several virtual resources with
+/// non-overlapping lifetimes may alias the same physical resource.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanVirtualResourceDescription {
+    pub id: usize,
+    pub name: Option<RenderGraphResourceName>,
+    pub first_pass: usize,
+    pub last_pass: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanImageDescription {
+    pub id: usize,
+    /// True if the image is provided from outside the graph via set_output_image
+    pub is_output: bool,
+    pub format: RafxFormat,
+    pub samples: RafxSampleCount,
+    pub extents: RenderGraphImageExtents,
+    pub layer_count: u32,
+    pub mip_count: u32,
+    pub first_pass: usize,
+    pub last_pass: usize,
+    pub virtual_images: Vec<RenderGraphPlanVirtualResourceDescription>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanBufferDescription {
+    pub id: usize,
+    /// True if the buffer is provided from outside the graph via set_output_buffer
+    pub is_output: bool,
+    pub size: u64,
+    pub first_pass: usize,
+    pub last_pass: usize,
+    pub virtual_buffers: Vec<RenderGraphPlanVirtualResourceDescription>,
+}
+
+/// Data flowing from the node that wrote a resource to a node (or graph output) that uses it
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphPlanEdgeDescription {
+    pub from_node: usize,
+    /// None if the resource is consumed as an output of the graph
+    pub to_node: Option<usize>,
+    pub name: Option<RenderGraphResourceName>,
+    /// The physical resource backing the data, or None if it was culled
+    pub resource: Option<RenderGraphPlanResourceId>,
+}
+
+/// A description of the decisions made while building a RenderGraphPlan. This is intended for
+/// debugging and tooling. It can be exported as graphviz (DOT) or as JSON.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenderGraphPlanDescription {
+    pub nodes: Vec<RenderGraphPlanNodeDescription>,
+    pub passes: Vec<RenderGraphPlanPassDescription>,
+    pub images: Vec<RenderGraphPlanImageDescription>,
+    pub buffers: Vec<RenderGraphPlanBufferDescription>,
+    pub edges: Vec<RenderGraphPlanEdgeDescription>,
+}
+
+// Names are user-provided, so quotes and backslashes must be escaped in DOT labels
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn format_name(name: Option<&str>) -> String {
+    match name {
+        Some(name) => escape_dot(name),
+        None => "unnamed".to_string(),
+    }
+}
+
+impl RenderGraphPlanDescription {
+    /// Produces a JSON representation of the plan
+    pub fn to_json(&self) -> String {
+        // The description only contains types that always serialize successfully
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Produces a graphviz representation of the plan. Passes are drawn as clusters containing
+    /// their nodes, culled nodes are dashed, and physical resources are listed with the virtual
+    /// resources they alias.
+    pub fn to_dot(&self) -> String {
+        // Writing to a String can't fail, so the results of write! are ignored
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph render_graph_plan {{");
+        let _ = writeln!(dot, "    rankdir=LR;");
+        let _ = writeln!(dot, "    node [shape=box];");
+
+        for pass in &self.passes {
+            let _ = writeln!(dot, "    subgraph cluster_pass_{} {{", pass.index);
+            let mut label = format!(
+                "pass {}: {} ({:?})",
+                pass.index,
+                format_name(pass.name),
+                pass.kind
+            );
+            for barrier in &pass.pre_pass_barriers {
+                let _ = write!(
+                    label,
+                    "\\lpre: {} {} -> {}",
+                    barrier.resource, barrier.old_state, barrier.new_state
+                );
+            }
+            for barrier in &pass.post_pass_barriers {
+                let _ = write!(
+                    label,
+                    "\\lpost: {} {} -> {}",
+                    barrier.resource, barrier.old_state, barrier.new_state
+                );
+            }
+            let _ = writeln!(dot, "        label=\"{}\\l\";", label);
+
+            for node_id in &pass.nodes {
+                let node = &self.nodes[*node_id];
+                let _ = writeln!(
+                    dot,
+                    "        node_{} [label=\"{}: {}\"];",
+                    node.id,
+                    node.id,
+                    format_name(node.name)
+                );
+            }
+            let _ = writeln!(dot, "    }}");
+        }
+
+        for node in &self.nodes {
+            if node.pass_index.is_none() {
+                let _ = writeln!(
+                    dot,
+                    "    node_{} [label=\"{}: {} (culled)\", style=dashed, color=gray];",
+                    node.id,
+                    node.id,
+                    format_name(node.name)
+                );
+            }
+        }
+
+        let _ = writeln!(dot, "    output [label=\"output\", shape=doublecircle];");
+
+        for edge in &self.edges {
+            let to = match edge.to_node {
+                Some(to_node) => format!("node_{}", to_node),
+                None => "output".to_string(),
+            };
+            let label = match edge.resource {
+                Some(resource) => format!("{} ({})", format_name(edge.name), resource),
+                None => format_name(edge.name),
+            };
+            let style = if edge.resource.is_some() {
+                ""
+            } else {
+                ", style=dashed, color=gray"
+            };
+            let _ = writeln!(
+                dot,
+                "    node_{} -> {} [label=\"{}\"{}];",
+                edge.from_node, to, label, style
+            );
+        }
+
+        for image in &self.images {
+            let mut label = format!(
+                "image {}{}: {:?} {:?}\\lpasses {}-{}",
+                image.id,
+                if image.is_output { " (output)" } else { "" },
+                image.format,
+                image.samples,
+                image.first_pass,
+                image.last_pass
+            );
+            for virtual_image in &image.virtual_images {
+                let _ = write!(
+                    label,
+                    "\\l  virtual {} {}: passes {}-{}",
+                    virtual_image.id,
+                    format_name(virtual_image.name),
+                    virtual_image.first_pass,
+                    virtual_image.last_pass
+                );
+            }
+            let _ = writeln!(
+                dot,
+                "    image_{} [label=\"{}\\l\", shape=note];",
+                image.id, label
+            );
+        }
+
+        for buffer in &self.buffers {
+            let mut label = format!(
+                "buffer {}{}: {} bytes\\lpasses {}-{}",
+                buffer.id,
+                if buffer.is_output { " (output)" } else { "" },
+                buffer.size,
+                buffer.first_pass,
+                buffer.last_pass
+            );
+            for virtual_buffer in &buffer.virtual_buffers {
+                let _ = write!(
+                    label,
+                    "\\l  virtual {} {}: passes {}-{}",
+                    virtual_buffer.id,
+                    format_name(virtual_buffer.name),
+                    virtual_buffer.first_pass,
+                    virtual_buffer.last_pass
+                );
+            }
+            let _ = writeln!(
+                dot,
+                "    buffer_{} [label=\"{}\\l\", shape=note];",
+                buffer.id, label
+            );
+        }
+
+        let _ = writeln!(dot, "}}");
+        dot
+    }
+}
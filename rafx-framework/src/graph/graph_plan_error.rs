@@ -0,0 +1,404 @@
+use super::*;
+use rafx_api::{RafxError, RafxFormat, RafxSampleCount};
+
+/// Identifies a node in a RenderGraphPlanError
+#[derive(Debug, Copy, Clone)]
+pub struct RenderGraphPlanErrorNode {
+    pub id: RenderGraphNodeId,
+    pub name: Option<RenderGraphNodeName>,
+}
+
+impl RenderGraphPlanErrorNode {
+    pub(super) fn new(
+        graph: &RenderGraphBuilder,
+        node_id: RenderGraphNodeId,
+    ) -> Self {
+        RenderGraphPlanErrorNode {
+            id: node_id,
+            name: graph.node(node_id).name(),
+        }
+    }
+}
+
+impl std::fmt::Display for RenderGraphPlanErrorNode {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "node {} \"{}\"", self.id.0, name),
+            None => write!(f, "node {} (unnamed)", self.id.0),
+        }
+    }
+}
+
+/// Identifies a usage of an image in a RenderGraphPlanError. The node is None if the usage is an
+/// output of the graph rather than a node.
+#[derive(Debug, Copy, Clone)]
+pub struct RenderGraphPlanErrorImage {
+    pub usage: RenderGraphImageUsageId,
+    pub usage_type: RenderGraphImageUsageType,
+    pub name: Option<RenderGraphResourceName>,
+    pub node: Option<RenderGraphPlanErrorNode>,
+}
+
+impl RenderGraphPlanErrorImage {
+    pub(super) fn new(
+        graph: &RenderGraphBuilder,
+        usage: RenderGraphImageUsageId,
+    ) -> Self {
+        let image_usage = &graph.image_usages[usage.0];
+        let node = match image_usage.user {
+            RenderGraphImageUser::Node(node_id) => {
+                Some(RenderGraphPlanErrorNode::new(graph, node_id))
+            }
+            RenderGraphImageUser::Output(_) => None,
+        };
+
+        RenderGraphPlanErrorImage {
+            usage,
+            usage_type: image_usage.usage_type,
+            name: graph.image_resource(usage).name,
+            node,
+        }
+    }
+}
+
+impl std::fmt::Display for RenderGraphPlanErrorImage {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "image \"{}\"", name)?,
+            None => write!(f, "unnamed image")?,
+        }
+
+        match &self.node {
+            Some(node) => write!(
+                f,
+                " ({:?} usage {} by {})",
+                self.usage_type, self.usage.0, node
+            ),
+            None => write!(f, " (output usage {})", self.usage.0),
+        }
+    }
+}
+
+/// Identifies a usage of a buffer in a RenderGraphPlanError. The node is None if the usage is an
+/// output of the graph rather than a node.
+#[derive(Debug, Copy, Clone)]
+pub struct RenderGraphPlanErrorBuffer {
+    pub usage: RenderGraphBufferUsageId,
+    pub usage_type: RenderGraphBufferUsageType,
+    pub name: Option<RenderGraphResourceName>,
+    pub node: Option<RenderGraphPlanErrorNode>,
+}
+
+impl RenderGraphPlanErrorBuffer {
+    pub(super) fn new(
+        graph: &RenderGraphBuilder,
+        usage: RenderGraphBufferUsageId,
+    ) -> Self {
+        let buffer_usage = graph.buffer_usage(usage);
+        let node = match buffer_usage.user {
+            RenderGraphBufferUser::Node(node_id) => {
+                Some(RenderGraphPlanErrorNode::new(graph, node_id))
+            }
+            RenderGraphBufferUser::Output(_) => None,
+        };
+
+        RenderGraphPlanErrorBuffer {
+            usage,
+            usage_type: buffer_usage.usage_type,
+            name: graph.buffer_resource(usage).name,
+            node,
+        }
+    }
+}
+
+impl std::fmt::Display for RenderGraphPlanErrorBuffer {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "buffer \"{}\"", name)?,
+            None => write!(f, "unnamed buffer")?,
+        }
+
+        match &self.node {
+            Some(node) => write!(
+                f,
+                " ({:?} usage {} by {})",
+                self.usage_type, self.usage.0, node
+            ),
+            None => write!(f, " (output usage {})", self.usage.0),
+        }
+    }
+}
+
+pub type RenderGraphPlanResult<T> = Result<T, RenderGraphPlanError>;
+
+/// Errors that can occur when building a RenderGraphPlan from a RenderGraphBuilder. These are
+/// almost always caused by the graph being set up incorrectly.
+#[derive(Debug, Clone)]
+pub enum RenderGraphPlanError {
+    /// The listed nodes depend on each other in a loop, so they cannot be ordered. The first node
+    /// in the list depends on the last one.
+    Cycle {
+        nodes: Vec<RenderGraphPlanErrorNode>,
+    },
+
+    /// Not enough constraints were provided to determine the specification of the image (usually
+    /// this means no format was given anywhere the image is used)
+    ImageSpecificationMissing { image: RenderGraphPlanErrorImage },
+
+    /// Not enough constraints were provided to determine the size of the buffer
+    BufferSpecificationMissing { buffer: RenderGraphPlanErrorBuffer },
+
+    /// An image is written in one format but used in another. The graph does not insert blits to
+    /// convert between formats.
+    ImageFormatConflict {
+        written: RenderGraphPlanErrorImage,
+        used: RenderGraphPlanErrorImage,
+        written_format: RafxFormat,
+        used_format: RafxFormat,
+    },
+
+    /// An image is written with one sample count but used with another, and the conflict could not
+    /// be fixed by inserting a resolve
+    ImageSampleCountConflict {
+        written: RenderGraphPlanErrorImage,
+        used: RenderGraphPlanErrorImage,
+        written_samples: RafxSampleCount,
+        used_samples: RafxSampleCount,
+    },
+
+    /// An image is written with constraints that conflict with how it is used (extents, layer
+    /// count, or mip count)
+    ImageConstraintConflict {
+        written: RenderGraphPlanErrorImage,
+        used: RenderGraphPlanErrorImage,
+        written_constraint: RenderGraphImageConstraint,
+        used_constraint: RenderGraphImageConstraint,
+    },
+
+    /// A buffer is written with constraints that conflict with how it is used
+    BufferConstraintConflict {
+        written: RenderGraphPlanErrorBuffer,
+        used: RenderGraphPlanErrorBuffer,
+        written_constraint: RenderGraphBufferConstraint,
+        used_constraint: RenderGraphBufferConstraint,
+    },
+
+    /// A node modifies a version of an image that other nodes only read. Those reads require the
+    /// contents to remain unchanged, so the write would require a copy of the image.
+    WriteToReadOnlyImage {
+        written: RenderGraphPlanErrorImage,
+        used: RenderGraphPlanErrorImage,
+    },
+
+    /// A node modifies a version of a buffer that other nodes only read. Those reads require the
+    /// contents to remain unchanged, so the write would require a copy of the buffer.
+    WriteToReadOnlyBuffer {
+        written: RenderGraphPlanErrorBuffer,
+        used: RenderGraphPlanErrorBuffer,
+    },
+
+    /// The attachments of a render node do not all have the same sample count
+    AttachmentSampleCountConflict {
+        node: RenderGraphPlanErrorNode,
+        /// Sample count of the attachment that doesn't match the ones checked before it
+        expected_samples: RafxSampleCount,
+        /// Sample count of the attachments checked before the mismatching one
+        found_samples: RafxSampleCount,
+    },
+
+    /// An image is read as an input attachment but it can't be bound in the node's renderpass.
+    /// Input attachments require a backend that supports subpasses, and a renderpass may only have
+    /// one depth/stencil attachment.
+    UnsupportedInputAttachment { image: RenderGraphPlanErrorImage },
+
+    /// A usage of an image was assigned to more than one image. This means the usage is a read of
+    /// more than one version of an image, the graph cannot be built from these versions.
+    ImageUsageConflict { image: RenderGraphPlanErrorImage },
+
+    /// A usage of a buffer was assigned to more than one buffer. This means the usage is a read of
+    /// more than one version of a buffer, the graph cannot be built from these versions.
+    BufferUsageConflict { buffer: RenderGraphPlanErrorBuffer },
+}
+
+impl RenderGraphPlanError {
+    // Produces the most specific error possible for an image that is written with one set of
+    // constraints and used with another
+    pub(super) fn image_conflict(
+        graph: &RenderGraphBuilder,
+        written: RenderGraphImageUsageId,
+        used: RenderGraphImageUsageId,
+        written_constraint: &RenderGraphImageConstraint,
+        used_constraint: &RenderGraphImageConstraint,
+    ) -> Self {
+        let written_image = RenderGraphPlanErrorImage::new(graph, written);
+        let used_image = RenderGraphPlanErrorImage::new(graph, used);
+
+        match (
+            written_constraint.format,
+            used_constraint.format,
+            written_constraint.samples,
+            used_constraint.samples,
+        ) {
+            (Some(written_format), Some(used_format), _, _) if written_format != used_format => {
+                RenderGraphPlanError::ImageFormatConflict {
+                    written: written_image,
+                    used: used_image,
+                    written_format,
+                    used_format,
+                }
+            }
+            (_, _, Some(written_samples), Some(used_samples))
+                if written_samples != used_samples =>
+            {
+                RenderGraphPlanError::ImageSampleCountConflict {
+                    written: written_image,
+                    used: used_image,
+                    written_samples,
+                    used_samples,
+                }
+            }
+            _ => RenderGraphPlanError::ImageConstraintConflict {
+                written: written_image,
+                used: used_image,
+                written_constraint: written_constraint.clone(),
+                used_constraint: used_constraint.clone(),
+            },
+        }
+    }
+
+    pub(super) fn buffer_conflict(
+        graph: &RenderGraphBuilder,
+        written: RenderGraphBufferUsageId,
+        used: RenderGraphBufferUsageId,
+        written_constraint: &RenderGraphBufferConstraint,
+        used_constraint: &RenderGraphBufferConstraint,
+    ) -> Self {
+        RenderGraphPlanError::BufferConstraintConflict {
+            written: RenderGraphPlanErrorBuffer::new(graph, written),
+            used: RenderGraphPlanErrorBuffer::new(graph, used),
+            written_constraint: written_constraint.clone(),
+            used_constraint: used_constraint.clone(),
+        }
+    }
+}
+
+impl std::error::Error for RenderGraphPlanError {}
+
+impl std::fmt::Display for RenderGraphPlanError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            RenderGraphPlanError::Cycle { nodes } => {
+                write!(f, "Render graph has a cycle: ")?;
+                for node in nodes {
+                    write!(f, "{} -> ", node)?;
+                }
+                match nodes.first() {
+                    Some(first) => write!(f, "{}", first),
+                    None => Ok(()),
+                }
+            }
+            RenderGraphPlanError::ImageSpecificationMissing { image } => write!(
+                f,
+                "Not enough information in the render graph to determine the specification of {}. (Is a format specified?)",
+                image
+            ),
+            RenderGraphPlanError::BufferSpecificationMissing { buffer } => write!(
+                f,
+                "Not enough information in the render graph to determine the specification of {}. (Is a size specified?)",
+                buffer
+            ),
+            RenderGraphPlanError::ImageFormatConflict {
+                written,
+                used,
+                written_format,
+                used_format,
+            } => write!(
+                f,
+                "{} is written as {:?} but {} requires {:?}",
+                written, written_format, used, used_format
+            ),
+            RenderGraphPlanError::ImageSampleCountConflict {
+                written,
+                used,
+                written_samples,
+                used_samples,
+            } => write!(
+                f,
+                "{} is written with {:?} but {} requires {:?}",
+                written, written_samples, used, used_samples
+            ),
+            RenderGraphPlanError::ImageConstraintConflict {
+                written,
+                used,
+                written_constraint,
+                used_constraint,
+            } => write!(
+                f,
+                "{} is written as {:?} but {} requires {:?}",
+                written, written_constraint, used, used_constraint
+            ),
+            RenderGraphPlanError::BufferConstraintConflict {
+                written,
+                used,
+                written_constraint,
+                used_constraint,
+            } => write!(
+                f,
+                "{} is written as {:?} but {} requires {:?}",
+                written, written_constraint, used, used_constraint
+            ),
+            RenderGraphPlanError::WriteToReadOnlyImage { written, used } => write!(
+                f,
+                "{} writes to the contents of {} but the contents are also read by other nodes",
+                used, written
+            ),
+            RenderGraphPlanError::WriteToReadOnlyBuffer { written, used } => write!(
+                f,
+                "{} writes to the contents of {} but the contents are also read by other nodes",
+                used, written
+            ),
+            RenderGraphPlanError::AttachmentSampleCountConflict {
+                node,
+                expected_samples,
+                found_samples,
+            } => write!(
+                f,
+                "Attachments of {} have different sample counts ({:?} and {:?}), this is unsupported",
+                node, expected_samples, found_samples
+            ),
+            RenderGraphPlanError::UnsupportedInputAttachment { image } => write!(
+                f,
+                "{} is read as an input attachment but can't be bound in its renderpass (input attachments require a backend that supports subpasses, and a renderpass may only have one depth/stencil attachment)",
+                image
+            ),
+            RenderGraphPlanError::ImageUsageConflict { image } => write!(
+                f,
+                "{} reads more than one version of the image",
+                image
+            ),
+            RenderGraphPlanError::BufferUsageConflict { buffer } => write!(
+                f,
+                "{} reads more than one version of the buffer",
+                buffer
+            ),
+        }
+    }
+}
+
+impl From<RenderGraphPlanError> for RafxError {
+    fn from(error: RenderGraphPlanError) -> Self {
+        RafxError::StringError(error.to_string())
+    }
+}
@@ -6,35 +6,91 @@ use crate::graph::{
 };
 use crate::{BufferResource, ImageResource, ImageViewResource, ResourceArc, ResourceLookupSet};
 use fnv::FnvHashMap;
-use rafx_api::{RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResult, RafxTextureDef};
+use rafx_api::{
+    RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResourceType, RafxResult,
+    RafxSampleCount, RafxTextureDef,
+};
 use std::sync::{Arc, Mutex};
 
+// Buffers are cached per memory slot. Buffers in the same slot share memory.
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 struct RenderGraphCachedBufferKey {
-    specification: RenderGraphBufferSpecification,
+    specifications: Vec<RenderGraphBufferSpecification>,
 }
 
-struct RenderGraphCachedBuffer {
+struct RenderGraphCachedBuffers {
     keep_until_frame: u64,
-    buffer: ResourceArc<BufferResource>,
+    buffers: Vec<ResourceArc<BufferResource>>,
 }
 
+// Images are cached per memory slot. Images in the same slot share memory.
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 struct RenderGraphCachedImageKey {
-    specification: RenderGraphImageSpecification,
+    specifications: Vec<RenderGraphImageSpecification>,
     swapchain_surface_info: SwapchainSurfaceInfo,
 }
 
-struct RenderGraphCachedImage {
+struct RenderGraphCachedImages {
     keep_until_frame: u64,
-    image: ResourceArc<ImageResource>,
+    images: Vec<ResourceArc<ImageResource>>,
+}
+
+/// Memory required by the intermediate resources of the render graphs prepared in a frame. All
+/// values are estimates in bytes based on resource specifications, ignoring alignment and padding.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RenderGraphMemoryStats {
+    /// Memory required if every intermediate resource had its own allocation
+    pub total_bytes: u64,
+    /// Memory required when resources with non-overlapping lifetimes share allocations
+    pub aliased_bytes: u64,
+    /// The largest amount of memory required by resources alive during any single pass
+    pub peak_bytes: u64,
+    /// Memory used by attachments that the backend may avoid allocating entirely (i.e. lazily
+    /// allocated or memoryless attachments)
+    pub transient_attachment_bytes: u64,
+}
+
+fn sample_count_as_u64(samples: RafxSampleCount) -> u64 {
+    match samples {
+        RafxSampleCount::SampleCount1 => 1,
+        RafxSampleCount::SampleCount2 => 2,
+        RafxSampleCount::SampleCount4 => 4,
+        RafxSampleCount::SampleCount8 => 8,
+        RafxSampleCount::SampleCount16 => 16,
+    }
+}
+
+fn estimate_image_size_in_bytes(
+    specification: &RenderGraphImageSpecification,
+    swapchain_surface_info: &SwapchainSurfaceInfo,
+) -> u64 {
+    let extents = specification
+        .extents
+        .into_rafx_extents(swapchain_surface_info);
+    let block_width = specification.format.block_width_in_pixels();
+    let block_height = specification.format.block_height_in_pixels();
+    let block_size = specification.format.block_or_pixel_size_in_bytes() as u64;
+
+    let mut size = 0;
+    for mip in 0..specification.mip_count {
+        let width = (extents.width >> mip).max(1);
+        let height = (extents.height >> mip).max(1);
+        let depth = (extents.depth >> mip).max(1);
+        let blocks_wide = ((width + block_width - 1) / block_width) as u64;
+        let blocks_high = ((height + block_height - 1) / block_height) as u64;
+        size += blocks_wide * blocks_high * depth as u64 * block_size;
+    }
+
+    size * specification.layer_count as u64 * sample_count_as_u64(specification.samples)
 }
 
 pub struct RenderGraphCacheInner {
-    buffers: FnvHashMap<RenderGraphCachedBufferKey, Vec<RenderGraphCachedBuffer>>,
-    images: FnvHashMap<RenderGraphCachedImageKey, Vec<RenderGraphCachedImage>>,
+    buffers: FnvHashMap<RenderGraphCachedBufferKey, Vec<RenderGraphCachedBuffers>>,
+    images: FnvHashMap<RenderGraphCachedImageKey, Vec<RenderGraphCachedImages>>,
     current_frame_index: u64,
     frames_to_persist: u64,
+    current_frame_memory_stats: RenderGraphMemoryStats,
+    last_frame_memory_stats: RenderGraphMemoryStats,
 }
 
 impl RenderGraphCacheInner {
@@ -44,6 +100,8 @@ impl RenderGraphCacheInner {
             images: Default::default(),
             current_frame_index: 0,
             frames_to_persist: max_frames_in_flight as u64 + 1,
+            current_frame_memory_stats: Default::default(),
+            last_frame_memory_stats: Default::default(),
         }
     }
 
@@ -63,6 +121,7 @@ impl RenderGraphCacheInner {
 
         self.images.retain(|_k, v| !v.is_empty());
 
+        self.last_frame_memory_stats = std::mem::take(&mut self.current_frame_memory_stats);
         self.current_frame_index += 1;
     }
 
@@ -71,6 +130,71 @@ impl RenderGraphCacheInner {
         self.images.clear();
     }
 
+    // Accumulates the memory required by a plan's intermediate resources into the current frame's
+    // stats
+    pub(super) fn update_memory_stats(
+        &mut self,
+        graph: &RenderGraphPlan,
+        swapchain_surface_info: &SwapchainSurfaceInfo,
+    ) {
+        let image_sizes: FnvHashMap<PhysicalImageId, u64> = graph
+            .intermediate_images
+            .iter()
+            .map(|(&id, specification)| {
+                (
+                    id,
+                    estimate_image_size_in_bytes(specification, swapchain_surface_info),
+                )
+            })
+            .collect();
+        let buffer_sizes: FnvHashMap<PhysicalBufferId, u64> = graph
+            .intermediate_buffers
+            .iter()
+            .map(|(&id, specification)| (id, specification.size))
+            .collect();
+
+        let total_bytes = image_sizes.values().sum::<u64>() + buffer_sizes.values().sum::<u64>();
+
+        // A slot needs as much memory as the largest resource assigned to it
+        let mut aliased_bytes = 0;
+        for slot in &graph.image_memory_slots {
+            aliased_bytes += slot.iter().map(|id| image_sizes[id]).max().unwrap_or(0);
+        }
+        for slot in &graph.buffer_memory_slots {
+            aliased_bytes += slot.iter().map(|id| buffer_sizes[id]).max().unwrap_or(0);
+        }
+
+        let pass_count = graph.passes.len();
+        let mut bytes_per_pass = vec![0; pass_count];
+        for (id, lifetime) in &graph.intermediate_image_lifetimes {
+            for pass_bytes in &mut bytes_per_pass[lifetime.first_pass..=lifetime.last_pass] {
+                *pass_bytes += image_sizes[id];
+            }
+        }
+        for (id, lifetime) in &graph.intermediate_buffer_lifetimes {
+            for pass_bytes in &mut bytes_per_pass[lifetime.first_pass..=lifetime.last_pass] {
+                *pass_bytes += buffer_sizes[id];
+            }
+        }
+        let peak_bytes = bytes_per_pass.into_iter().max().unwrap_or(0);
+
+        let mut transient_attachment_bytes = 0;
+        for (id, specification) in &graph.intermediate_images {
+            if specification
+                .resource_type
+                .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+            {
+                transient_attachment_bytes += image_sizes[id];
+            }
+        }
+
+        let frame_stats = &mut self.current_frame_memory_stats;
+        frame_stats.total_bytes += total_bytes;
+        frame_stats.aliased_bytes += aliased_bytes;
+        frame_stats.peak_bytes += peak_bytes;
+        frame_stats.transient_attachment_bytes += transient_attachment_bytes;
+    }
+
     pub(super) fn allocate_buffers(
         &mut self,
         device_context: &RafxDeviceContext,
@@ -82,9 +206,9 @@ impl RenderGraphCacheInner {
             Default::default();
 
         // Keeps track of what index in the cache we will use next. This starts at 0 for each key
-        // and increments every time we use an image. If the next image is >= length of buffers, we
-        // allocate one and push it into that key's list of cached buffers
-        let mut next_buffer_to_use = FnvHashMap::<RenderGraphCachedBufferKey, usize>::default();
+        // and increments every time we use a slot's buffers. If the next index is >= length of
+        // cached buffers, we allocate them and push them into that key's list of cached buffers
+        let mut next_buffers_to_use = FnvHashMap::<RenderGraphCachedBufferKey, usize>::default();
 
         // Using a buffer will bump the keep_until_frame for that buffer
         let keep_until_frame = self.current_frame_index + self.frames_to_persist;
@@ -93,62 +217,80 @@ impl RenderGraphCacheInner {
             buffer_resources.insert(physical_id, buffer.dst_buffer.clone());
         }
 
-        // Iterate all intermediate buffers, assigning an existing buffer from a previous frame or
-        // allocating a new one
-        for (&id, specification) in &graph.intermediate_buffers {
+        // Iterate all memory slots, assigning existing buffers from a previous frame or allocating
+        // new ones. Buffers in the same slot share memory.
+        for slot in &graph.buffer_memory_slots {
             let key = RenderGraphCachedBufferKey {
-                specification: specification.clone(),
+                specifications: slot
+                    .iter()
+                    .map(|id| graph.intermediate_buffers[id].clone())
+                    .collect(),
             };
 
-            let next_buffer_index = next_buffer_to_use.entry(key.clone()).or_insert(0);
+            let next_buffers_index = next_buffers_to_use.entry(key.clone()).or_insert(0);
             let matching_cached_buffers = self
                 .buffers
                 .entry(key.clone())
                 .or_insert_with(Default::default);
 
-            if let Some(cached_buffer) = matching_cached_buffers.get_mut(*next_buffer_index) {
+            let buffers = if let Some(cached_buffers) =
+                matching_cached_buffers.get_mut(*next_buffers_index)
+            {
                 log::trace!(
-                    "  Buffer {:?} - REUSE {:?}  (key: {:?}, index: {})",
-                    id,
-                    cached_buffer.buffer,
+                    "  Buffers {:?} - REUSE  (key: {:?}, index: {})",
+                    slot,
                     key,
-                    next_buffer_index
+                    next_buffers_index
                 );
 
-                // Reuse a buffer from a previous frame, bump keep_until_frame
-                cached_buffer.keep_until_frame = keep_until_frame;
-                *next_buffer_index += 1;
-
-                buffer_resources.insert(id, cached_buffer.buffer.clone());
+                // Reuse buffers from a previous frame, bump keep_until_frame
+                cached_buffers.keep_until_frame = keep_until_frame;
+                cached_buffers.buffers.clone()
             } else {
-                // No unused buffer available, create one
-                let buffer = device_context.create_buffer(&RafxBufferDef {
-                    size: key.specification.size,
-                    //alignment: key.specification.alignment,
-                    memory_usage: RafxMemoryUsage::GpuOnly,
-                    resource_type: key.specification.resource_type,
-                    //initial_state: key.specification.initial_state,
-                    ..Default::default()
-                })?;
-                let buffer = resources.insert_buffer(buffer);
+                // No unused buffers available, create them
+                let buffer_defs: Vec<_> = key
+                    .specifications
+                    .iter()
+                    .map(|specification| RafxBufferDef {
+                        size: specification.size,
+                        //alignment: specification.alignment,
+                        memory_usage: RafxMemoryUsage::GpuOnly,
+                        resource_type: specification.resource_type,
+                        //initial_state: specification.initial_state,
+                        ..Default::default()
+                    })
+                    .collect();
+
+                let buffers = if buffer_defs.len() == 1 {
+                    vec![device_context.create_buffer(&buffer_defs[0])?]
+                } else {
+                    device_context.create_aliased_buffers(&buffer_defs)?
+                };
+
+                let buffers: Vec<_> = buffers
+                    .into_iter()
+                    .map(|buffer| resources.insert_buffer(buffer))
+                    .collect();
 
                 log::trace!(
-                    "  Buffer {:?} - CREATE {:?}  (key: {:?}, index: {})",
-                    id,
-                    buffer.get_raw().buffer,
+                    "  Buffers {:?} - CREATE  (key: {:?}, index: {})",
+                    slot,
                     key,
-                    next_buffer_index
+                    next_buffers_index
                 );
 
-                // Add the buffer to the cache
-                debug_assert_eq!(matching_cached_buffers.len(), *next_buffer_index);
-                matching_cached_buffers.push(RenderGraphCachedBuffer {
+                // Add the buffers to the cache
+                debug_assert_eq!(matching_cached_buffers.len(), *next_buffers_index);
+                matching_cached_buffers.push(RenderGraphCachedBuffers {
                     keep_until_frame,
-                    buffer: buffer.clone(),
+                    buffers: buffers.clone(),
                 });
-                *next_buffer_index += 1;
+                buffers
+            };
+            *next_buffers_index += 1;
 
-                // Associate the physical id with this buffer
+            // Associate the physical ids with the buffers
+            for (&id, buffer) in slot.iter().zip(buffers) {
                 buffer_resources.insert(id, buffer);
             }
         }
@@ -168,9 +310,9 @@ impl RenderGraphCacheInner {
             Default::default();
 
         // Keeps track of what index in the cache we will use next. This starts at 0 for each key
-        // and increments every time we use an image. If the next image is >= length of images, we
-        // allocate one and push it into that key's list of cached images
-        let mut next_image_to_use = FnvHashMap::<RenderGraphCachedImageKey, usize>::default();
+        // and increments every time we use a slot's images. If the next index is >= length of
+        // cached images, we allocate them and push them into that key's list of cached images
+        let mut next_images_to_use = FnvHashMap::<RenderGraphCachedImageKey, usize>::default();
 
         // Using an image will bump the keep_until_frame for that image
         let keep_until_frame = self.current_frame_index + self.frames_to_persist;
@@ -180,69 +322,83 @@ impl RenderGraphCacheInner {
             image_resources.insert(physical_id, image.dst_image.get_raw().image);
         }
 
-        // Iterate all intermediate images, assigning an existing image from a previous frame or
-        // allocating a new one
-        for (&id, specification) in &graph.intermediate_images {
+        // Iterate all memory slots, assigning existing images from a previous frame or allocating
+        // new ones. Images in the same slot share memory.
+        for slot in &graph.image_memory_slots {
             let key = RenderGraphCachedImageKey {
-                specification: specification.clone(),
+                specifications: slot
+                    .iter()
+                    .map(|id| graph.intermediate_images[id].clone())
+                    .collect(),
                 swapchain_surface_info: swapchain_surface_info.clone(),
             };
 
-            let next_image_index = next_image_to_use.entry(key.clone()).or_insert(0);
+            let next_images_index = next_images_to_use.entry(key.clone()).or_insert(0);
             let matching_cached_images = self
                 .images
                 .entry(key.clone())
                 .or_insert_with(Default::default);
 
-            if let Some(cached_image) = matching_cached_images.get_mut(*next_image_index) {
-                log::trace!(
-                    "  Image {:?} - REUSE {:?}  (key: {:?}, index: {})",
-                    id,
-                    cached_image.image.get_raw().image,
-                    key,
-                    next_image_index
-                );
-
-                // Reuse an image from a previous frame, bump keep_until_frame
-                cached_image.keep_until_frame = keep_until_frame;
-                *next_image_index += 1;
-
-                image_resources.insert(id, cached_image.image.clone());
-            } else {
-                // No unused image available, create one
-                let extents = key
-                    .specification
-                    .extents
-                    .into_rafx_extents(&key.swapchain_surface_info);
-
-                let image = device_context.create_texture(&RafxTextureDef {
-                    extents,
-                    array_length: specification.layer_count,
-                    mip_count: specification.mip_count,
-                    format: specification.format,
-                    sample_count: specification.samples,
-                    resource_type: specification.resource_type,
-                    dimensions: Default::default(),
-                })?;
-                let image = resources.insert_image(image);
-
-                log::trace!(
-                    "  Image {:?} - CREATE {:?}  (key: {:?}, index: {})",
-                    id,
-                    image.get_raw().image,
-                    key,
-                    next_image_index
-                );
-
-                // Add the image to the cache
-                debug_assert_eq!(matching_cached_images.len(), *next_image_index);
-                matching_cached_images.push(RenderGraphCachedImage {
-                    keep_until_frame,
-                    image: image.clone(),
-                });
-                *next_image_index += 1;
-
-                // Associate the physical id with this image
+            let images =
+                if let Some(cached_images) = matching_cached_images.get_mut(*next_images_index) {
+                    log::trace!(
+                        "  Images {:?} - REUSE  (key: {:?}, index: {})",
+                        slot,
+                        key,
+                        next_images_index
+                    );
+
+                    // Reuse images from a previous frame, bump keep_until_frame
+                    cached_images.keep_until_frame = keep_until_frame;
+                    cached_images.images.clone()
+                } else {
+                    // No unused images available, create them
+                    let texture_defs: Vec<_> = key
+                        .specifications
+                        .iter()
+                        .map(|specification| RafxTextureDef {
+                            extents: specification
+                                .extents
+                                .into_rafx_extents(&key.swapchain_surface_info),
+                            array_length: specification.layer_count,
+                            mip_count: specification.mip_count,
+                            format: specification.format,
+                            sample_count: specification.samples,
+                            resource_type: specification.resource_type,
+                            dimensions: Default::default(),
+                        })
+                        .collect();
+
+                    let images = if texture_defs.len() == 1 {
+                        vec![device_context.create_texture(&texture_defs[0])?]
+                    } else {
+                        device_context.create_aliased_textures(&texture_defs)?
+                    };
+
+                    let images: Vec<_> = images
+                        .into_iter()
+                        .map(|image| resources.insert_image(image))
+                        .collect();
+
+                    log::trace!(
+                        "  Images {:?} - CREATE  (key: {:?}, index: {})",
+                        slot,
+                        key,
+                        next_images_index
+                    );
+
+                    // Add the images to the cache
+                    debug_assert_eq!(matching_cached_images.len(), *next_images_index);
+                    matching_cached_images.push(RenderGraphCachedImages {
+                        keep_until_frame,
+                        images: images.clone(),
+                    });
+                    images
+                };
+            *next_images_index += 1;
+
+            // Associate the physical ids with the images
+            for (&id, image) in slot.iter().zip(images) {
                 image_resources.insert(id, image);
             }
         }
@@ -313,4 +469,9 @@ impl RenderGraphCache {
     pub fn clear(&self) {
         self.inner.lock().unwrap().clear();
     }
+
+    /// Memory required by the render graphs prepared during the most recently completed frame
+    pub fn memory_stats(&self) -> RenderGraphMemoryStats {
+        self.inner.lock().unwrap().last_frame_memory_stats
+    }
 }
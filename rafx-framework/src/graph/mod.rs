@@ -7,6 +7,7 @@ pub use graph_image::RenderGraphImageConstraint;
 pub use graph_image::RenderGraphImageExtents;
 pub use graph_image::RenderGraphImageSpecification;
 pub use graph_image::RenderGraphImageUsageId;
+pub use graph_image::RenderGraphImageUsageType;
 pub use graph_image::RenderGraphImageViewOptions;
 use graph_image::*;
 
@@ -14,10 +15,12 @@ mod graph_buffer;
 pub use graph_buffer::RenderGraphBufferConstraint;
 pub use graph_buffer::RenderGraphBufferSpecification;
 pub use graph_buffer::RenderGraphBufferUsageId;
+pub use graph_buffer::RenderGraphBufferUsageType;
 use graph_buffer::*;
 
 mod graph_node;
 pub use graph_node::RenderGraphNodeId;
+pub use graph_node::RenderGraphNodeName;
 use graph_node::*;
 
 mod graph_pass;
@@ -26,12 +29,32 @@ use graph_pass::*;
 mod graph_plan;
 pub use graph_plan::RenderGraphPlan;
 
+mod graph_plan_description;
+pub use graph_plan_description::RenderGraphPlanBarrierDescription;
+pub use graph_plan_description::RenderGraphPlanBufferDescription;
+pub use graph_plan_description::RenderGraphPlanDescription;
+pub use graph_plan_description::RenderGraphPlanEdgeDescription;
+pub use graph_plan_description::RenderGraphPlanImageDescription;
+pub use graph_plan_description::RenderGraphPlanNodeDescription;
+pub use graph_plan_description::RenderGraphPlanPassDescription;
+pub use graph_plan_description::RenderGraphPlanPassKind;
+pub use graph_plan_description::RenderGraphPlanResourceId;
+pub use graph_plan_description::RenderGraphPlanVirtualResourceDescription;
+
+mod graph_plan_error;
+pub use graph_plan_error::RenderGraphPlanError;
+pub use graph_plan_error::RenderGraphPlanErrorBuffer;
+pub use graph_plan_error::RenderGraphPlanErrorImage;
+pub use graph_plan_error::RenderGraphPlanErrorNode;
+pub use graph_plan_error::RenderGraphPlanResult;
+
 mod graph_resource_cache;
-pub use graph_resource_cache::RenderGraphCache;
+pub use graph_resource_cache::{RenderGraphCache, RenderGraphMemoryStats};
 
 mod prepared_graph;
 pub use prepared_graph::OnBeginExecuteGraphArgs;
 pub use prepared_graph::PreparedRenderGraph;
+pub use prepared_graph::RenderGraphContext;
 pub use prepared_graph::SwapchainSurfaceInfo;
 pub use prepared_graph::VisitComputeNodeArgs;
 pub use prepared_graph::VisitRenderpassNodeArgs;
@@ -91,20 +91,29 @@ impl PreparedRenderGraph {
         &self,
         node_id: RenderGraphNodeId,
     ) -> Option<RenderGraphNodeName> {
-        let pass_index = *self.graph_plan.node_to_pass_index.get(&node_id)?;
-        self.graph_plan.passes[pass_index].debug_name()
+        // Several nodes may be merged into a single pass, so look up the node itself
+        self.graph_plan.node_names.get(node_id.0).copied().flatten()
+    }
+
+    /// The plan the graph was prepared from. Useful for inspecting or exporting the decisions
+    /// made while planning
+    pub fn graph_plan(&self) -> &RenderGraphPlan {
+        &self.graph_plan
     }
 
     pub fn new(
         device_context: &RafxDeviceContext,
         resource_context: &ResourceContext,
-        graph: RenderGraphBuilder,
+        mut graph: RenderGraphBuilder,
         swapchain_surface_info: &SwapchainSurfaceInfo,
     ) -> RafxResult<Self> {
-        let graph_plan = graph.build_plan();
+        graph.set_supports_subpasses(device_context.device_info().supports_subpasses);
+        let graph_plan = graph.build_plan()?;
         let mut cache_guard = resource_context.render_graph_cache().inner.lock().unwrap();
         let cache = &mut *cache_guard;
 
+        cache.update_memory_stats(&graph_plan, swapchain_surface_info);
+
         profiling::scope!("allocate resources");
         let buffer_resources =
             cache.allocate_buffers(device_context, &graph_plan, resource_context.resources())?;
@@ -311,8 +320,6 @@ impl PreparedRenderGraph {
             profiling::scope!("pass", pass.debug_name().unwrap_or("unnamed"));
             log::trace!("Execute pass name: {:?}", pass.debug_name());
 
-            let node_id = pass.node();
-
             if let Some(pre_pass_barrier) = pass.pre_pass_barrier() {
                 log::trace!(
                     "prepass barriers for pass {} {:?}",
@@ -383,26 +390,75 @@ impl PreparedRenderGraph {
                     //println!("color bindings:\n{:#?}", color_target_bindings);
                     //println!("depth binding:\n{:#?}", depth_target_binding);
 
-                    command_buffer
-                        .cmd_begin_render_pass(&color_target_bindings, depth_target_binding)?;
+                    // Size of the render targets, used to reset the viewport for merged nodes
+                    let (render_target_texture, render_target_mip_slice) = color_target_bindings
+                        .first()
+                        .map(|x| (x.texture, x.mip_slice))
+                        .or_else(|| {
+                            depth_target_binding
+                                .as_ref()
+                                .map(|x| (x.texture, x.mip_slice))
+                        })
+                        .unwrap();
+                    let render_target_extents = render_target_texture.texture_def().extents;
+                    let mip_slice = render_target_mip_slice.unwrap_or(0) as u32;
+                    let render_target_width = (render_target_extents.width >> mip_slice).max(1);
+                    let render_target_height = (render_target_extents.height >> mip_slice).max(1);
+
+                    if pass.subpasses.is_empty() {
+                        command_buffer
+                            .cmd_begin_render_pass(&color_target_bindings, depth_target_binding)?;
+                    } else {
+                        command_buffer.cmd_begin_render_pass_with_subpasses(
+                            &color_target_bindings,
+                            depth_target_binding,
+                            &pass.subpasses,
+                        )?;
+                    }
+
+                    for (node_index, node_id) in pass.nodes.iter().enumerate() {
+                        // cmd_begin_render_pass sets the viewport and scissor to cover the render
+                        // targets. Restore that for each node merged into the pass so it doesn't
+                        // inherit state from the previous node.
+                        if node_index > 0 {
+                            if !pass.subpasses.is_empty() {
+                                command_buffer.cmd_next_subpass()?;
+                            }
+
+                            command_buffer.cmd_set_viewport(
+                                0.0,
+                                0.0,
+                                render_target_width as f32,
+                                render_target_height as f32,
+                                0.0,
+                                1.0,
+                            )?;
+                            command_buffer.cmd_set_scissor(
+                                0,
+                                0,
+                                render_target_width,
+                                render_target_height,
+                            )?;
+                        }
 
-                    let args = VisitRenderpassNodeArgs {
-                        render_target_meta: pass.render_target_meta.clone(),
-                        graph_context: render_graph_context,
-                        command_buffer: command_buffer.clone(),
-                    };
+                        let args = VisitRenderpassNodeArgs {
+                            render_target_meta: pass.render_target_metas[node_index].clone(),
+                            graph_context: render_graph_context,
+                            command_buffer: command_buffer.clone(),
+                        };
 
-                    self.visit_renderpass_node(node_id, args)?;
+                        self.visit_renderpass_node(*node_id, args)?;
+                    }
 
                     command_buffer.cmd_end_render_pass()?;
                 }
-                RenderGraphOutputPass::Compute(_pass) => {
+                RenderGraphOutputPass::Compute(pass) => {
                     let args = VisitComputeNodeArgs {
                         graph_context: render_graph_context,
                         command_buffer: command_buffer.clone(),
                     };
 
-                    self.visit_compute_node(node_id, args)?;
+                    self.visit_compute_node(pass.node, args)?;
                 }
             }
 
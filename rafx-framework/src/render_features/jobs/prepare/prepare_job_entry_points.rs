@@ -357,6 +357,40 @@ impl<'prepare, 'entry, PrepareJobEntryPointsT: PrepareJobEntryPoints<'prepare>>
         )
     }
 
+    pub fn push_batchable_submit_node<RenderPhaseT: RenderPhase>(
+        &self,
+        data: <PrepareJobEntryPointsT::SubmitPacketDataT as SubmitPacketData>::SubmitNodeData,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: SubmitNodeBatchKey,
+    ) -> SubmitNodeId {
+        self.push_batchable_submit_node_into_render_phase(
+            RenderPhaseT::render_phase_index(),
+            data,
+            sort_key,
+            distance,
+            batch_key,
+        )
+    }
+
+    pub fn push_batchable_submit_node_into_render_phase(
+        &self,
+        render_phase: RenderPhaseIndex,
+        data: <PrepareJobEntryPointsT::SubmitPacketDataT as SubmitPacketData>::SubmitNodeData,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: SubmitNodeBatchKey,
+    ) -> SubmitNodeId {
+        self.view_submit_packet
+            .push_batchable_submit_node_into_render_phase(
+                render_phase,
+                data,
+                sort_key,
+                distance,
+                batch_key,
+            )
+    }
+
     pub fn get_submit_node_data<RenderPhaseT: RenderPhase>(
         &self,
         index: SubmitNodeId,
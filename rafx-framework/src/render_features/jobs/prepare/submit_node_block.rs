@@ -66,10 +66,23 @@ impl<SubmitPacketDataT: 'static + Sync + Send + SubmitPacketData>
         data: SubmitPacketDataT::SubmitNodeData,
         sort_key: SubmitNodeSortKey,
         distance: f32,
+    ) -> SubmitNodeId {
+        self.push_batchable_submit_node(data, sort_key, distance, None)
+    }
+
+    /// Same as `push_submit_node`, but the node may be drawn together with other nodes that have the
+    /// same `batch_key` (see `SubmitNodeBatchKey`).
+    pub fn push_batchable_submit_node(
+        &self,
+        data: SubmitPacketDataT::SubmitNodeData,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: Option<SubmitNodeBatchKey>,
     ) -> SubmitNodeId {
         self.submit_nodes.push(SubmitNode {
             sort_key,
             distance,
+            batch_key,
             data,
         }) as SubmitNodeId
     }
@@ -111,6 +124,7 @@ impl<SubmitPacketDataT: 'static + Sync + Send + SubmitPacketData> RenderFeatureS
             submit_node.sort_key,
             submit_node.distance,
         )
+        .with_batch_key(submit_node.batch_key)
     }
 
     fn feature_index(&self) -> RenderFeatureIndex {
@@ -125,6 +139,7 @@ impl<SubmitPacketDataT: 'static + Sync + Send + SubmitPacketData> RenderFeatureS
 pub struct SubmitNode<T> {
     pub sort_key: SubmitNodeSortKey,
     pub distance: f32,
+    pub batch_key: Option<SubmitNodeBatchKey>,
     pub data: T,
 }
 
@@ -133,6 +148,7 @@ impl<T: Default> SubmitNode<T> {
         Self {
             sort_key: 0,
             distance: 0.,
+            batch_key: None,
             data: T::default(),
         }
     }
@@ -1,4 +1,5 @@
 use crate::render_features::render_features_prelude::*;
+use std::ops::Range;
 
 /// The `ID` of a `SubmitNode` in a specific `RenderFeature` in the current frame.
 pub type SubmitNodeId = u32;
@@ -7,15 +8,24 @@ pub type SubmitNodeId = u32;
 /// `RenderFeatureSubmitNode` in a particular `ViewPhase`. This can be used to minimize state changes
 /// in the rendering pipeline, e.g. by setting the bits of the key so that higher bits represent more
 /// expensive state changes like shaders and lower bits represent cheaper state changes like uniforms.
+/// Features that share a `RenderPhase` should build their keys with `SubmitNodeSortKeyBuilder` so
+/// that their submit nodes interleave correctly.
 ///
 /// Example: https://web.archive.org/web/20210110113523/https://realtimecollisiondetection.net/blog/?p=86
-pub type SubmitNodeSortKey = u32;
+pub type SubmitNodeSortKey = u64;
+
+/// A key identifying `SubmitNode`s that a `RenderFeature` can draw together, e.g. as a single instanced
+/// draw call. The key should be equal only when the nodes share everything the draw call binds, such
+/// as the pipeline, vertex and index buffers, and descriptor sets. Consecutive nodes of the same
+/// `RenderFeature` with the same key are passed to `RenderFeatureWriteJob::render_submit_node_batch`
+/// together after the `ViewPhaseSubmitNodeBlock` is sorted.
+pub type SubmitNodeBatchKey = u64;
 
 /// The sort function used by a particular `RenderPhase`. This is usually one of the following:
-/// 1. front-to-back
-/// 2. back-to-front
-/// 3. by feature index
-/// 4. unsorted
+/// 1. front-to-back (`sort_submit_nodes_front_to_back`)
+/// 2. back-to-front (`sort_submit_nodes_back_to_front`)
+/// 3. by state (`sort_submit_nodes_by_state`)
+/// 4. by insertion order (`sort_submit_nodes_by_insertion_order`)
 pub type SubmitNodeSortFunction = fn(&mut Vec<RenderFeatureSubmitNode>);
 
 /// A combination of a particular `RenderView` and `RenderPhase`. The `ViewPhaseSubmitNodeBlock`s
@@ -34,6 +44,8 @@ pub struct ViewPhase {
 pub struct ViewPhaseSubmitNodeBlock {
     view_phase: ViewPhase,
     submit_nodes: Vec<RenderFeatureSubmitNode>,
+    // Ranges of `submit_nodes` that are drawn together. Empty until `batch_submit_nodes` is called.
+    submit_node_batches: Vec<Range<usize>>,
 }
 
 impl ViewPhaseSubmitNodeBlock {
@@ -44,6 +56,7 @@ impl ViewPhaseSubmitNodeBlock {
         Self {
             view_phase,
             submit_nodes: Vec::with_capacity(num_submit_nodes),
+            submit_node_batches: Default::default(),
         }
     }
 
@@ -59,6 +72,7 @@ impl ViewPhaseSubmitNodeBlock {
         &mut self,
         submit_node: RenderFeatureSubmitNode,
     ) {
+        self.submit_node_batches.clear();
         self.submit_nodes.push(submit_node)
     }
 
@@ -66,12 +80,54 @@ impl ViewPhaseSubmitNodeBlock {
         &mut self,
         sort_function: SubmitNodeSortFunction,
     ) {
+        self.submit_node_batches.clear();
         sort_function(&mut self.submit_nodes)
     }
 
+    /// Groups consecutive `RenderFeatureSubmitNode`s from the same `RenderFeature` that have the same
+    /// `SubmitNodeBatchKey`. This must be called after `sort_submit_nodes` so that batching does not
+    /// change the draw order chosen by the `RenderPhase`. Nodes without a batch key are never batched.
+    pub fn batch_submit_nodes(&mut self) {
+        self.submit_node_batches.clear();
+
+        let mut batch_start = 0;
+        for index in 1..=self.submit_nodes.len() {
+            let ends_batch = if index == self.submit_nodes.len() {
+                true
+            } else {
+                let previous = &self.submit_nodes[index - 1];
+                let current = &self.submit_nodes[index];
+                previous.feature_index != current.feature_index
+                    || previous.batch_key.is_none()
+                    || previous.batch_key != current.batch_key
+            };
+
+            if ends_batch {
+                self.submit_node_batches.push(batch_start..index);
+                batch_start = index;
+            }
+        }
+    }
+
     pub fn submit_nodes(&self) -> &[RenderFeatureSubmitNode] {
         self.submit_nodes.as_slice()
     }
+
+    /// Returns the batches found by `batch_submit_nodes` in draw order. Every `RenderFeatureSubmitNode`
+    /// is in exactly one batch. If the nodes were not batched, each node is returned as its own batch.
+    pub fn submit_node_batches<'a>(
+        &'a self
+    ) -> Box<dyn Iterator<Item = &'a [RenderFeatureSubmitNode]> + 'a> {
+        if self.submit_node_batches.is_empty() {
+            Box::new(self.submit_nodes.chunks(1))
+        } else {
+            Box::new(
+                self.submit_node_batches
+                    .iter()
+                    .map(move |range| &self.submit_nodes[range.clone()]),
+            )
+        }
+    }
 }
 
 /// A type-erased struct representing some `RenderFeature`'s `SubmitNode`. The `PreparedRenderData`
@@ -83,6 +139,7 @@ pub struct RenderFeatureSubmitNode {
     submit_node_id: SubmitNodeId,
     sort_key: SubmitNodeSortKey,
     distance: f32,
+    batch_key: Option<SubmitNodeBatchKey>,
 }
 
 impl RenderFeatureSubmitNode {
@@ -97,9 +154,18 @@ impl RenderFeatureSubmitNode {
             submit_node_id,
             sort_key,
             distance,
+            batch_key: None,
         }
     }
 
+    pub fn with_batch_key(
+        mut self,
+        batch_key: Option<SubmitNodeBatchKey>,
+    ) -> Self {
+        self.batch_key = batch_key;
+        self
+    }
+
     pub fn feature_index(&self) -> RenderFeatureIndex {
         self.feature_index
     }
@@ -115,4 +181,130 @@ impl RenderFeatureSubmitNode {
     pub fn distance(&self) -> f32 {
         self.distance
     }
+
+    pub fn batch_key(&self) -> Option<SubmitNodeBatchKey> {
+        self.batch_key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render_features::{
+        sort_submit_nodes_by_insertion_order, sort_submit_nodes_by_state,
+    };
+
+    fn submit_node(
+        feature_index: RenderFeatureIndex,
+        submit_node_id: SubmitNodeId,
+        sort_key: SubmitNodeSortKey,
+        batch_key: Option<SubmitNodeBatchKey>,
+    ) -> RenderFeatureSubmitNode {
+        RenderFeatureSubmitNode::new(feature_index, submit_node_id, sort_key, 0.0)
+            .with_batch_key(batch_key)
+    }
+
+    fn new_block(submit_nodes: &[RenderFeatureSubmitNode]) -> ViewPhaseSubmitNodeBlock {
+        let view_phase = ViewPhase {
+            view_index: 0,
+            phase_index: 0,
+        };
+        let mut block = ViewPhaseSubmitNodeBlock::new(view_phase, submit_nodes.len());
+        for submit_node in submit_nodes {
+            block.push_submit_node(*submit_node);
+        }
+        block
+    }
+
+    fn batch_ids(block: &ViewPhaseSubmitNodeBlock) -> Vec<Vec<SubmitNodeId>> {
+        block
+            .submit_node_batches()
+            .map(|batch| batch.iter().map(|x| x.submit_node_id()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_adjacent_equal_keys_are_batched() {
+        let mut block = new_block(&[
+            submit_node(0, 0, 0, Some(1)),
+            submit_node(0, 1, 0, Some(1)),
+            submit_node(0, 2, 0, Some(1)),
+            submit_node(0, 3, 0, Some(2)),
+            submit_node(0, 4, 0, Some(2)),
+        ]);
+
+        // Each node is its own batch until the nodes are batched
+        assert_eq!(
+            batch_ids(&block),
+            vec![vec![0], vec![1], vec![2], vec![3], vec![4]]
+        );
+
+        block.batch_submit_nodes();
+        assert_eq!(batch_ids(&block), vec![vec![0, 1, 2], vec![3, 4]]);
+
+        // Pushing another node invalidates the batches
+        block.push_submit_node(submit_node(0, 5, 0, Some(2)));
+        assert_eq!(batch_ids(&block).len(), 6);
+        block.batch_submit_nodes();
+        assert_eq!(batch_ids(&block), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_non_adjacent_nodes_are_not_batched() {
+        let mut block = new_block(&[
+            // Separated by a node with another key
+            submit_node(0, 0, 0, Some(1)),
+            submit_node(0, 1, 0, Some(2)),
+            submit_node(0, 2, 0, Some(1)),
+            // Equal keys from different features
+            submit_node(1, 3, 0, Some(1)),
+            // Nodes without a key
+            submit_node(1, 4, 0, None),
+            submit_node(1, 5, 0, None),
+        ]);
+
+        block.batch_submit_nodes();
+        assert_eq!(
+            batch_ids(&block),
+            vec![vec![0], vec![1], vec![2], vec![3], vec![4], vec![5]]
+        );
+    }
+
+    #[test]
+    fn test_batches_follow_sorted_order() {
+        // Nodes that can be batched are pushed apart from each other
+        let submit_nodes = [
+            submit_node(0, 0, 20, Some(2)),
+            submit_node(0, 1, 10, Some(1)),
+            submit_node(0, 2, 20, Some(2)),
+            submit_node(0, 3, 10, Some(1)),
+            submit_node(0, 4, 10, Some(3)),
+        ];
+
+        // Sorting by state brings them together
+        let mut block = new_block(&submit_nodes);
+        block.sort_submit_nodes(sort_submit_nodes_by_state);
+        block.batch_submit_nodes();
+        assert_eq!(batch_ids(&block), vec![vec![1, 3], vec![4], vec![0, 2]]);
+
+        // Keeping the insertion order keeps them apart. Batching never reorders nodes, so they stay
+        // separate.
+        let mut block = new_block(&submit_nodes);
+        block.sort_submit_nodes(sort_submit_nodes_by_insertion_order);
+        block.batch_submit_nodes();
+        assert_eq!(batch_ids(&block).len(), 5);
+
+        // Sorting invalidates the batches
+        let mut block = new_block(&submit_nodes);
+        block.batch_submit_nodes();
+        block.sort_submit_nodes(sort_submit_nodes_by_state);
+        assert_eq!(batch_ids(&block).len(), 5);
+    }
+
+    #[test]
+    fn test_empty_block() {
+        let mut block = new_block(&[]);
+        block.batch_submit_nodes();
+        assert_eq!(batch_ids(&block), Vec::<Vec<SubmitNodeId>>::new());
+    }
 }
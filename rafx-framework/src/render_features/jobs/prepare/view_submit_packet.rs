@@ -85,6 +85,34 @@ impl<SubmitPacketDataT: 'static + Send + Sync + SubmitPacketData>
             .push_submit_node(data, sort_key, distance)
     }
 
+    pub fn push_batchable_submit_node<RenderPhaseT: RenderPhase>(
+        &self,
+        data: SubmitPacketDataT::SubmitNodeData,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: SubmitNodeBatchKey,
+    ) -> SubmitNodeId {
+        self.push_batchable_submit_node_into_render_phase(
+            RenderPhaseT::render_phase_index(),
+            data,
+            sort_key,
+            distance,
+            batch_key,
+        )
+    }
+
+    pub fn push_batchable_submit_node_into_render_phase(
+        &self,
+        render_phase: RenderPhaseIndex,
+        data: SubmitPacketDataT::SubmitNodeData,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: SubmitNodeBatchKey,
+    ) -> SubmitNodeId {
+        self.submit_node_block(render_phase)
+            .push_batchable_submit_node(data, sort_key, distance, Some(batch_key))
+    }
+
     fn submit_node_block(
         &self,
         render_phase: RenderPhaseIndex,
@@ -52,31 +52,43 @@ impl<'write> PreparedRenderData<'write> {
         &self,
         view: &RenderView,
         write_context: &mut RenderJobCommandBufferContext,
+    ) -> RafxResult<()> {
+        self.write_view_phase_by_index(PhaseT::render_phase_index(), view, write_context)
+    }
+
+    /// Same as `write_view_phase` for when the phase is only known at runtime, for example when it
+    /// is looked up by name with `RenderRegistry::render_phase_index_from_name`
+    pub fn write_view_phase_by_index(
+        &self,
+        render_phase_index: RenderPhaseIndex,
+        view: &RenderView,
+        write_context: &mut RenderJobCommandBufferContext,
     ) -> RafxResult<()> {
         profiling::scope!({
             use rafx_base::memory::force_to_static_lifetime;
             unsafe { force_to_static_lifetime(view).debug_name() }
         });
 
-        let render_phase_index = PhaseT::render_phase_index();
-
         let view_phase = ViewPhase {
             view_index: view.view_index(),
             phase_index: render_phase_index,
         };
 
         let submit_node_block = self.submit_node_blocks.get(&view_phase);
-        let submit_nodes: &[RenderFeatureSubmitNode] =
+        let submit_node_batches: Box<dyn Iterator<Item = &[RenderFeatureSubmitNode]>> =
             if let Some(submit_node_block) = submit_node_block {
-                submit_node_block.submit_nodes()
+                submit_node_block.submit_node_batches()
             } else {
-                &[]
+                Box::new(std::iter::empty())
             };
 
         let mut previous_node_feature_index: i32 = -1;
         let mut previous_view_frame_index: Option<ViewFrameIndex> = None;
 
-        for submit_node in submit_nodes {
+        for submit_node_batch in submit_node_batches {
+            // Every node in a batch belongs to the same feature
+            let submit_node = &submit_node_batch[0];
+
             if submit_node.feature_index() as i32 != previous_node_feature_index {
                 if previous_node_feature_index != -1 {
                     // call revert setup
@@ -111,21 +123,38 @@ impl<'write> PreparedRenderData<'write> {
                     )?;
             }
 
-            log::trace!(
-                "draw render node feature: {} node id: {}",
-                submit_node.feature_index(),
-                submit_node.submit_node_id(),
-            );
-
-            self.write_jobs[submit_node.feature_index() as usize]
+            let write_job = self.write_jobs[submit_node.feature_index() as usize]
                 .as_ref()
-                .unwrap()
-                .render_submit_node(
+                .unwrap();
+
+            if submit_node_batch.len() == 1 {
+                log::trace!(
+                    "draw render node feature: {} node id: {}",
+                    submit_node.feature_index(),
+                    submit_node.submit_node_id(),
+                );
+
+                write_job.render_submit_node(
                     write_context,
                     previous_view_frame_index.unwrap(),
                     render_phase_index,
                     submit_node.submit_node_id(),
                 )?;
+            } else {
+                log::trace!(
+                    "draw render node batch feature: {} first node id: {} node count: {}",
+                    submit_node.feature_index(),
+                    submit_node.submit_node_id(),
+                    submit_node_batch.len(),
+                );
+
+                write_job.render_submit_node_batch(
+                    write_context,
+                    previous_view_frame_index.unwrap(),
+                    render_phase_index,
+                    submit_node_batch,
+                )?;
+            }
         }
 
         if previous_node_feature_index != -1 {
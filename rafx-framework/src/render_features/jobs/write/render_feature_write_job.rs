@@ -1,6 +1,7 @@
 use crate::render_features::{
-    RenderFeatureDebugConstants, RenderFeatureIndex, RenderJobBeginExecuteGraphContext,
-    RenderJobCommandBufferContext, RenderPhaseIndex, RenderView, SubmitNodeId, ViewFrameIndex,
+    RenderFeatureDebugConstants, RenderFeatureIndex, RenderFeatureSubmitNode,
+    RenderJobBeginExecuteGraphContext, RenderJobCommandBufferContext, RenderPhaseIndex, RenderView,
+    SubmitNodeId, ViewFrameIndex,
 };
 use rafx_api::RafxResult;
 
@@ -49,6 +50,30 @@ pub trait RenderFeatureWriteJob<'write>: Sync + Send {
         _submit_node_id: SubmitNodeId,
     ) -> RafxResult<()>;
 
+    /// Called by `PreparedRenderData` in `write_view_phase` instead of `render_submit_node` when two or
+    /// more consecutive `RenderFeatureSubmitNode`s share a `SubmitNodeBatchKey`. A `RenderFeature` opts
+    /// into batching by pushing its submit nodes with a batch key and overriding this function to draw
+    /// all of the nodes at once, e.g. with an instanced draw call. The default implementation calls
+    /// `render_submit_node` for each node.
+    fn render_submit_node_batch(
+        &self,
+        write_context: &mut RenderJobCommandBufferContext,
+        view_frame_index: ViewFrameIndex,
+        render_phase_index: RenderPhaseIndex,
+        submit_nodes: &[RenderFeatureSubmitNode],
+    ) -> RafxResult<()> {
+        for submit_node in submit_nodes {
+            self.render_submit_node(
+                write_context,
+                view_frame_index,
+                render_phase_index,
+                submit_node.submit_node_id(),
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Called by `PreparedRenderData` in `write_view_phase` whenever the current `RenderFeatureIndex`
     /// changes **away** from this `RenderFeature`. This can be used to teardown pipelines or other expensive
     /// state changes that remained constant for previous `render_submit_node` calls.
@@ -9,19 +9,24 @@
 //
 // The function provided is a sort function like this:
 //
-// fn sort_submit_nodes(mut submit_nodes: Vec<SubmitNode>) -> Vec<SubmitNode> {
+// fn sort_submit_nodes(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
 //     // Sort by feature
 //     log::trace!("Sort phase {}", MyRenderPhase::render_phase_debug_name());
 //     submit_nodes.sort_unstable_by(|a, b| a.feature_index().cmp(&b.feature_index()));
-//
-//     submit_nodes
 // }
 //
 // This can be use to implement back to front and front to back sorting, or just sort by feature
-// if order doesn't matter to get the best batching
+// if order doesn't matter to get the best batching. The stock sort functions in
+// rafx::render_features (i.e. sort_submit_nodes_by_state) can be passed directly:
+//
+//      rafx::declare_render_phase!(
+//          OpaqueRenderPhase,
+//          OPAQUE_RENDER_PHASE_INDEX,
+//          rafx::render_features::sort_submit_nodes_by_state
+//      );
 #[macro_export]
 macro_rules! declare_render_phase {
-    ($struct_name:ident, $atomic_constant_name:ident, $sort_fn:ident) => {
+    ($struct_name:ident, $atomic_constant_name:ident, $sort_fn:path) => {
         static $atomic_constant_name: std::sync::atomic::AtomicI32 =
             std::sync::atomic::AtomicI32::new(-1);
 
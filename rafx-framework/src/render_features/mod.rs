@@ -42,6 +42,20 @@ pub use render_feature_flag_mask::RenderFeatureFlagMaskBuilder;
 mod render_phase_mask;
 pub use render_phase_mask::RenderPhaseMask;
 pub use render_phase_mask::RenderPhaseMaskBuilder;
+mod shader_keywords;
+pub use shader_keywords::select_shader_variant;
+pub use shader_keywords::ShaderKeyword;
+pub use shader_keywords::ShaderKeywordIndex;
+pub use shader_keywords::ShaderKeywordMask;
+pub use shader_keywords::ShaderVariantKeywords;
+mod submit_node_sort;
+pub use submit_node_sort::sort_submit_nodes_back_to_front;
+pub use submit_node_sort::sort_submit_nodes_by_insertion_order;
+pub use submit_node_sort::sort_submit_nodes_by_state;
+pub use submit_node_sort::sort_submit_nodes_front_to_back;
+pub use submit_node_sort::submit_node_sort_key_layer;
+pub use submit_node_sort::SubmitNodeDepthOrder;
+pub use submit_node_sort::SubmitNodeSortKeyBuilder;
 
 pub mod render_features_prelude {
     pub use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -59,9 +73,9 @@ pub mod render_features_prelude {
         RenderObjectInstanceId, RenderObjectInstanceObjectIds, RenderObjectInstancePerViewId,
         RenderObjectsMap, RenderPhase, RenderPhaseIndex, RenderRegistry, RenderRegistryBuilder,
         RenderView, RenderViewIndex, RenderViewSet, RenderViewSubmitNodeCount,
-        RenderViewVisibilityQuery, SubmitNode, SubmitNodeBlocks, SubmitNodeId, SubmitNodeSortKey,
-        SubmitPacketData, ViewFrameIndex, ViewPacketSize, ViewPhase, ViewPhaseSubmitNodeBlock,
-        ViewVisibilityJob, VisibleRenderObjects,
+        RenderViewVisibilityQuery, SubmitNode, SubmitNodeBatchKey, SubmitNodeBlocks, SubmitNodeId,
+        SubmitNodeSortKey, SubmitPacketData, ViewFrameIndex, ViewPacketSize, ViewPhase,
+        ViewPhaseSubmitNodeBlock, ViewVisibilityJob, VisibleRenderObjects,
     };
 
     pub use crate::visibility::{ObjectId, VisibilityRegion};
@@ -1,5 +1,5 @@
 use super::RenderFeatureSubmitNode;
-use crate::render_features::SubmitNodeSortFunction;
+use crate::render_features::{ShaderKeyword, SubmitNodeSortFunction};
 use fnv::FnvHashMap;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
@@ -23,6 +23,9 @@ pub const MAX_RENDER_FEATURE_FLAG_COUNT: u32 = 128;
 pub type RenderPhaseMaskInnerType = u32;
 pub const MAX_RENDER_PHASE_COUNT: u32 = 32;
 
+pub type ShaderKeywordMaskInnerType = u64;
+pub const MAX_SHADER_KEYWORD_COUNT: u32 = 64;
+
 pub struct RenderFeatureDebugConstants {
     pub feature_name: &'static str,
 
@@ -88,6 +91,8 @@ static RENDER_REGISTRY_PHASE_COUNT: AtomicU32 = AtomicU32::new(0);
 pub struct RenderRegistryBuilder {
     registered_phases: FnvHashMap<RenderPhaseIndex, RegisteredPhase>,
     phase_name_to_index: FnvHashMap<String, RenderPhaseIndex>,
+    shader_keyword_name_to_keyword: FnvHashMap<String, ShaderKeyword>,
+    shader_keyword_count: u32,
 }
 
 impl RenderRegistryBuilder {
@@ -130,10 +135,58 @@ impl RenderRegistryBuilder {
         self
     }
 
+    /// Registers a keyword that material pass variants can require. It is enabled per instance
+    /// (see ShaderKeywordMask)
+    pub fn register_shader_keyword(
+        mut self,
+        name: &str,
+    ) -> Self {
+        let keyword_index = self.shader_keyword_count;
+        assert!(keyword_index < MAX_SHADER_KEYWORD_COUNT);
+        self.shader_keyword_count += 1;
+        let old = self
+            .shader_keyword_name_to_keyword
+            .insert(name.to_string(), ShaderKeyword::Instance(keyword_index));
+        assert!(
+            old.is_none(),
+            "shader keyword {} was already registered",
+            name
+        );
+        self
+    }
+
+    /// Registers a keyword that material pass variants can require. It is enabled for any view that
+    /// has the feature flag. The feature flag must already be registered.
+    pub fn register_feature_flag_keyword<T>(
+        mut self,
+        name: &str,
+    ) -> Self
+    where
+        T: RenderFeatureFlag,
+    {
+        let feature_flag_index = T::feature_flag_index();
+        assert!(
+            feature_flag_index < MAX_RENDER_FEATURE_FLAG_COUNT,
+            "feature flag {} is not registered",
+            T::feature_flag_debug_name()
+        );
+        let old = self.shader_keyword_name_to_keyword.insert(
+            name.to_string(),
+            ShaderKeyword::FeatureFlag(feature_flag_index),
+        );
+        assert!(
+            old.is_none(),
+            "shader keyword {} was already registered",
+            name
+        );
+        self
+    }
+
     pub fn build(self) -> RenderRegistry {
         let inner = RenderRegistryInner {
             registered_phases: self.registered_phases,
             phase_name_to_index: self.phase_name_to_index,
+            shader_keyword_name_to_keyword: self.shader_keyword_name_to_keyword,
         };
 
         RenderRegistry {
@@ -145,6 +198,7 @@ impl RenderRegistryBuilder {
 struct RenderRegistryInner {
     registered_phases: FnvHashMap<RenderPhaseIndex, RegisteredPhase>,
     phase_name_to_index: FnvHashMap<String, RenderPhaseIndex>,
+    shader_keyword_name_to_keyword: FnvHashMap<String, ShaderKeyword>,
 }
 
 #[derive(Clone)]
@@ -168,6 +222,13 @@ impl RenderRegistry {
         self.inner.phase_name_to_index.get(name).copied()
     }
 
+    pub fn shader_keyword_from_name(
+        &self,
+        name: &str,
+    ) -> Option<ShaderKeyword> {
+        self.inner.shader_keyword_name_to_keyword.get(name).copied()
+    }
+
     pub fn submit_node_sort_function(
         &self,
         render_phase_index: RenderPhaseIndex,
@@ -25,8 +25,8 @@ impl RenderFeatureFlagMaskBuilder {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct RenderFeatureFlagMask(RenderFeatureFlagMaskInnerType);
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RenderFeatureFlagMask(pub(crate) RenderFeatureFlagMaskInnerType);
 
 impl RenderFeatureFlagMask {
     pub fn is_included<RenderFeatureFlagT: RenderFeatureFlag>(&self) -> bool {
@@ -58,6 +58,14 @@ impl RenderFeatureFlagMask {
         RenderFeatureFlagMask(0)
     }
 
+    #[inline(always)]
+    pub(crate) fn contains_all_inner(
+        &self,
+        other: RenderFeatureFlagMaskInnerType,
+    ) -> bool {
+        (self.0 & other) == other
+    }
+
     #[inline(always)]
     fn is_included_index_unchecked(
         &self,
@@ -0,0 +1,249 @@
+use crate::render_features::registry::{
+    RenderFeatureFlagMaskInnerType, ShaderKeywordMaskInnerType, MAX_SHADER_KEYWORD_COUNT,
+};
+use crate::render_features::{RenderFeatureFlagIndex, RenderFeatureFlagMask};
+
+/// The `ID` of a registered shader keyword that is not tied to a `RenderFeatureFlag`.
+pub type ShaderKeywordIndex = u32;
+
+/// A keyword that selects between variants of a material pass. Keywords registered for a
+/// `RenderFeatureFlag` are enabled by the `RenderView`. Other keywords are enabled per instance,
+/// usually by the feature that draws the instance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderKeyword {
+    FeatureFlag(RenderFeatureFlagIndex),
+    Instance(ShaderKeywordIndex),
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderKeywordMask(ShaderKeywordMaskInnerType);
+
+impl ShaderKeywordMask {
+    pub fn empty() -> Self {
+        ShaderKeywordMask(0)
+    }
+
+    pub fn add_keyword_index(
+        self,
+        index: ShaderKeywordIndex,
+    ) -> Self {
+        assert!(
+            index < MAX_SHADER_KEYWORD_COUNT,
+            "shader keyword index {} is invalid (did you forget to register a shader keyword?)",
+            index
+        );
+
+        ShaderKeywordMask(self.0 | 1 << index)
+    }
+
+    pub fn union(
+        self,
+        other: ShaderKeywordMask,
+    ) -> Self {
+        ShaderKeywordMask(self.0 | other.0)
+    }
+
+    pub fn is_included_index(
+        &self,
+        index: ShaderKeywordIndex,
+    ) -> bool {
+        index < MAX_SHADER_KEYWORD_COUNT && (self.0 & 1 << index) != 0
+    }
+
+    pub fn contains_all(
+        &self,
+        other: ShaderKeywordMask,
+    ) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+/// The keywords a variant of a material pass was compiled with. A variant is usable when the view
+/// has all of its feature flag keywords enabled and the instance has all of its instance keywords
+/// enabled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ShaderVariantKeywords {
+    feature_flags: RenderFeatureFlagMaskInnerType,
+    keywords: ShaderKeywordMask,
+}
+
+impl Default for ShaderVariantKeywords {
+    fn default() -> Self {
+        ShaderVariantKeywords {
+            feature_flags: 0,
+            keywords: ShaderKeywordMask::empty(),
+        }
+    }
+}
+
+impl ShaderVariantKeywords {
+    pub fn new(keywords: &[ShaderKeyword]) -> Self {
+        let mut variant_keywords = ShaderVariantKeywords::default();
+        for keyword in keywords {
+            match keyword {
+                ShaderKeyword::FeatureFlag(index) => variant_keywords.feature_flags |= 1 << index,
+                ShaderKeyword::Instance(index) => {
+                    variant_keywords.keywords = variant_keywords.keywords.add_keyword_index(*index)
+                }
+            }
+        }
+
+        variant_keywords
+    }
+
+    pub fn is_satisfied_by(
+        &self,
+        feature_flags: RenderFeatureFlagMask,
+        keywords: ShaderKeywordMask,
+    ) -> bool {
+        feature_flags.contains_all_inner(self.feature_flags) && keywords.contains_all(self.keywords)
+    }
+
+    // The number of keywords required by the variant. When several variants are satisfied, the one
+    // that requires the most keywords is the most specialized
+    pub fn keyword_count(&self) -> u32 {
+        self.feature_flags.count_ones() + self.keywords.count()
+    }
+}
+
+/// Picks the most specialized variant that is satisfied by the view's feature flags and the
+/// instance's keywords. Ties go to the variant listed first. Returns the index into `variants`.
+pub fn select_shader_variant<'a, T: IntoIterator<Item = &'a ShaderVariantKeywords>>(
+    variants: T,
+    feature_flags: RenderFeatureFlagMask,
+    keywords: ShaderKeywordMask,
+) -> Option<usize> {
+    let mut best: Option<(usize, u32)> = None;
+    for (index, variant) in variants.into_iter().enumerate() {
+        if !variant.is_satisfied_by(feature_flags, keywords) {
+            continue;
+        }
+
+        let keyword_count = variant.keyword_count();
+        let is_better = match best {
+            Some((_, best_count)) => keyword_count > best_count,
+            None => true,
+        };
+
+        if is_better {
+            best = Some((index, keyword_count));
+        }
+    }
+
+    best.map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shader_keyword_mask() {
+        let mask = ShaderKeywordMask::empty()
+            .add_keyword_index(0)
+            .add_keyword_index(5);
+        assert!(mask.is_included_index(0));
+        assert!(mask.is_included_index(5));
+        assert!(!mask.is_included_index(1));
+        assert!(!mask.is_included_index(MAX_SHADER_KEYWORD_COUNT));
+        assert_eq!(mask.count(), 2);
+
+        let other = ShaderKeywordMask::empty().add_keyword_index(5);
+        assert!(mask.contains_all(other));
+        assert!(!other.contains_all(mask));
+        assert!(mask.contains_all(ShaderKeywordMask::empty()));
+        assert_eq!(
+            other.union(ShaderKeywordMask::empty().add_keyword_index(0)),
+            mask
+        );
+
+        let last = ShaderKeywordMask::empty().add_keyword_index(MAX_SHADER_KEYWORD_COUNT - 1);
+        assert!(last.is_included_index(MAX_SHADER_KEYWORD_COUNT - 1));
+        assert_eq!(last.count(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shader_keyword_mask_invalid_index() {
+        ShaderKeywordMask::empty().add_keyword_index(MAX_SHADER_KEYWORD_COUNT);
+    }
+
+    #[test]
+    fn test_select_shader_variant() {
+        let variants = [
+            ShaderVariantKeywords::new(&[]),
+            ShaderVariantKeywords::new(&[ShaderKeyword::FeatureFlag(1)]),
+            ShaderVariantKeywords::new(&[ShaderKeyword::Instance(2)]),
+            ShaderVariantKeywords::new(&[
+                ShaderKeyword::FeatureFlag(1),
+                ShaderKeyword::Instance(2),
+            ]),
+        ];
+
+        let no_flags = RenderFeatureFlagMask::empty();
+        let flag_1 = RenderFeatureFlagMask(1 << 1);
+        let no_keywords = ShaderKeywordMask::empty();
+        let keyword_2 = ShaderKeywordMask::empty().add_keyword_index(2);
+
+        assert_eq!(
+            select_shader_variant(&variants, no_flags, no_keywords),
+            Some(0)
+        );
+        assert_eq!(
+            select_shader_variant(&variants, flag_1, no_keywords),
+            Some(1)
+        );
+        assert_eq!(
+            select_shader_variant(&variants, no_flags, keyword_2),
+            Some(2)
+        );
+        assert_eq!(select_shader_variant(&variants, flag_1, keyword_2), Some(3));
+
+        // Unrelated flags and keywords don't affect the selection
+        let flag_0_1 = RenderFeatureFlagMask(1 << 0 | 1 << 1);
+        let keyword_2_3 = keyword_2.add_keyword_index(3);
+        assert_eq!(
+            select_shader_variant(&variants, flag_0_1, keyword_2_3),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_select_shader_variant_ties_and_no_match() {
+        // Both variants require one keyword, the first one listed wins
+        let variants = [
+            ShaderVariantKeywords::new(&[ShaderKeyword::Instance(0)]),
+            ShaderVariantKeywords::new(&[ShaderKeyword::FeatureFlag(0)]),
+        ];
+
+        let flag_0 = RenderFeatureFlagMask(1 << 0);
+        let keyword_0 = ShaderKeywordMask::empty().add_keyword_index(0);
+        assert_eq!(select_shader_variant(&variants, flag_0, keyword_0), Some(0));
+        assert_eq!(
+            select_shader_variant(&variants, flag_0, ShaderKeywordMask::empty()),
+            Some(1)
+        );
+
+        // Without a variant that has no requirements, nothing may be usable
+        assert_eq!(
+            select_shader_variant(
+                &variants,
+                RenderFeatureFlagMask::empty(),
+                ShaderKeywordMask::empty()
+            ),
+            None
+        );
+        assert_eq!(
+            select_shader_variant(
+                &[],
+                RenderFeatureFlagMask::empty(),
+                ShaderKeywordMask::empty()
+            ),
+            None
+        );
+    }
+}
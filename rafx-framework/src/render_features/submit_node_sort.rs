@@ -0,0 +1,378 @@
+use crate::render_features::{RenderFeatureIndex, RenderFeatureSubmitNode, SubmitNodeSortKey};
+use fnv::FnvHasher;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+// Number of bits used by each field of a `SubmitNodeSortKey`. Each layout adds up to 64 bits. The
+// layer is always stored in the highest bits and the feature in the lowest bits so that both layouts
+// can be decoded the same way. MAX_RENDER_FEATURE_COUNT must fit in FEATURE_BITS.
+const LAYER_BITS: u32 = 8;
+const FEATURE_BITS: u32 = 6;
+
+const STATE_SORTED_PIPELINE_BITS: u32 = 25;
+const STATE_SORTED_MATERIAL_BITS: u32 = 25;
+
+const DEPTH_SORTED_DEPTH_BITS: u32 = 24;
+const DEPTH_SORTED_PIPELINE_BITS: u32 = 13;
+const DEPTH_SORTED_MATERIAL_BITS: u32 = 13;
+
+/// The direction that the depth field of a `SubmitNodeSortKey` sorts in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SubmitNodeDepthOrder {
+    FrontToBack,
+    BackToFront,
+}
+
+impl Default for SubmitNodeDepthOrder {
+    fn default() -> Self {
+        SubmitNodeDepthOrder::FrontToBack
+    }
+}
+
+/// Builds a `SubmitNodeSortKey` from a standard set of fields so that submit nodes from different
+/// `RenderFeature`s in the same `RenderPhase` sort consistently with each other. The layer is always
+/// the most significant field, so everything in a lower layer is drawn before anything in a higher
+/// layer. The order of the remaining fields depends on how the key is built:
+///
+/// - `build_state_sorted`: layer, pipeline, material, feature. Use this for opaque phases to
+///   minimize state changes (see `sort_submit_nodes_by_state`). Depth is not part of the key so that
+///   it doesn't split up nodes that could be batched. `sort_submit_nodes_by_state` orders nodes
+///   front to back after grouping them by key and batch key.
+/// - `build_depth_sorted`: layer, depth, pipeline, material, feature. Use this when draw order
+///   matters more than state changes, such as for transparent objects.
+///
+/// Pipelines and materials are hashed, so two different pipelines may occasionally share a value.
+/// This only affects how well nodes are grouped, never correctness.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SubmitNodeSortKeyBuilder {
+    layer: u8,
+    distance: f32,
+    depth_order: SubmitNodeDepthOrder,
+    pipeline: u64,
+    material: u64,
+    feature_index: RenderFeatureIndex,
+}
+
+fn hash_sort_key_field<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = FnvHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Keeps the highest `bits` bits of a hash. FNV mixes the high bits better than the low bits.
+fn truncate_hash(
+    hash: u64,
+    bits: u32,
+) -> u64 {
+    hash >> (64 - bits)
+}
+
+// Quantizes a non-negative distance to `bits` bits. The bit pattern of a positive f32 increases
+// with its value, so keeping the highest bits preserves the order without needing a maximum distance.
+fn quantize_distance(
+    distance: f32,
+    depth_order: SubmitNodeDepthOrder,
+    bits: u32,
+) -> u64 {
+    // NaN and negative distances sort as if they were at the camera
+    let distance = if distance > 0.0 { distance } else { 0.0 };
+
+    // The sign bit is always 0, so skip it
+    let quantized = ((distance.to_bits() << 1) >> (32 - bits)) as u64;
+    match depth_order {
+        SubmitNodeDepthOrder::FrontToBack => quantized,
+        SubmitNodeDepthOrder::BackToFront => ((1 << bits) - 1) - quantized,
+    }
+}
+
+impl SubmitNodeSortKeyBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Nodes in a lower layer are drawn before nodes in a higher layer regardless of the other fields.
+    pub fn layer(
+        mut self,
+        layer: u8,
+    ) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// The distance from the camera, usually the same distance that is given to `push_submit_node`.
+    /// Only used by `build_depth_sorted`.
+    pub fn depth(
+        mut self,
+        distance: f32,
+        depth_order: SubmitNodeDepthOrder,
+    ) -> Self {
+        self.distance = distance;
+        self.depth_order = depth_order;
+        self
+    }
+
+    /// Anything that identifies the pipeline, i.e. the `ResourceArc<MaterialPassResource>`.
+    pub fn pipeline<T: Hash + ?Sized>(
+        mut self,
+        pipeline: &T,
+    ) -> Self {
+        self.pipeline = hash_sort_key_field(pipeline);
+        self
+    }
+
+    /// Anything that identifies the material, i.e. the per-material `DescriptorSetArc`.
+    pub fn material<T: Hash + ?Sized>(
+        mut self,
+        material: &T,
+    ) -> Self {
+        self.material = hash_sort_key_field(material);
+        self
+    }
+
+    pub fn feature(
+        mut self,
+        feature_index: RenderFeatureIndex,
+    ) -> Self {
+        self.feature_index = feature_index;
+        self
+    }
+
+    fn layer_and_feature(&self) -> u64 {
+        let feature_index = self.feature_index as u64 & ((1 << FEATURE_BITS) - 1);
+        ((self.layer as u64) << (64 - LAYER_BITS)) | feature_index
+    }
+
+    pub fn build_state_sorted(&self) -> SubmitNodeSortKey {
+        let pipeline = truncate_hash(self.pipeline, STATE_SORTED_PIPELINE_BITS);
+        let material = truncate_hash(self.material, STATE_SORTED_MATERIAL_BITS);
+
+        self.layer_and_feature()
+            | pipeline << (FEATURE_BITS + STATE_SORTED_MATERIAL_BITS)
+            | material << FEATURE_BITS
+    }
+
+    pub fn build_depth_sorted(&self) -> SubmitNodeSortKey {
+        let depth = quantize_distance(self.distance, self.depth_order, DEPTH_SORTED_DEPTH_BITS);
+        let pipeline = truncate_hash(self.pipeline, DEPTH_SORTED_PIPELINE_BITS);
+        let material = truncate_hash(self.material, DEPTH_SORTED_MATERIAL_BITS);
+
+        self.layer_and_feature()
+            | depth << (FEATURE_BITS + DEPTH_SORTED_MATERIAL_BITS + DEPTH_SORTED_PIPELINE_BITS)
+            | pipeline << (FEATURE_BITS + DEPTH_SORTED_MATERIAL_BITS)
+            | material << FEATURE_BITS
+    }
+}
+
+/// Returns the layer of a key built by `SubmitNodeSortKeyBuilder`.
+pub fn submit_node_sort_key_layer(sort_key: SubmitNodeSortKey) -> u8 {
+    (sort_key >> (64 - LAYER_BITS)) as u8
+}
+
+fn compare_distance(
+    a: f32,
+    b: f32,
+) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+/// Sorts by the full `SubmitNodeSortKey`, then by `SubmitNodeBatchKey` so that nodes that can be
+/// batched end up next to each other, then front to back by distance. Intended for opaque phases
+/// with keys built by `SubmitNodeSortKeyBuilder::build_state_sorted`. The sort is stable, so nodes
+/// with equal keys and distances keep the order they were pushed in.
+#[profiling::function]
+pub fn sort_submit_nodes_by_state(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
+    submit_nodes.sort_by(|a, b| {
+        (a.sort_key(), a.batch_key())
+            .cmp(&(b.sort_key(), b.batch_key()))
+            .then_with(|| compare_distance(a.distance(), b.distance()))
+    });
+}
+
+/// Sorts by layer, then front to back by distance, then by the rest of the `SubmitNodeSortKey`.
+/// The distance given to `push_submit_node` is used directly, so features do not need to encode
+/// depth in the key.
+#[profiling::function]
+pub fn sort_submit_nodes_front_to_back(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
+    submit_nodes.sort_by(|a, b| {
+        submit_node_sort_key_layer(a.sort_key())
+            .cmp(&submit_node_sort_key_layer(b.sort_key()))
+            .then_with(|| compare_distance(a.distance(), b.distance()))
+            .then_with(|| a.sort_key().cmp(&b.sort_key()))
+    });
+}
+
+/// Sorts by layer, then back to front by distance, then by the rest of the `SubmitNodeSortKey`.
+/// Intended for transparent phases. The distance given to `push_submit_node` is used directly, so
+/// features do not need to encode depth in the key.
+#[profiling::function]
+pub fn sort_submit_nodes_back_to_front(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
+    submit_nodes.sort_by(|a, b| {
+        submit_node_sort_key_layer(a.sort_key())
+            .cmp(&submit_node_sort_key_layer(b.sort_key()))
+            .then_with(|| compare_distance(b.distance(), a.distance()))
+            .then_with(|| a.sort_key().cmp(&b.sort_key()))
+    });
+}
+
+/// Sorts by layer only, keeping the order that the nodes were pushed in within each layer. Intended
+/// for UI, where later elements are drawn over earlier ones. Nodes from different features are
+/// combined in feature order, so features that need to interleave should use layers.
+#[profiling::function]
+pub fn sort_submit_nodes_by_insertion_order(submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {
+    submit_nodes.sort_by_key(|x| submit_node_sort_key_layer(x.sort_key()));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn submit_node(
+        submit_node_id: u32,
+        sort_key: SubmitNodeSortKey,
+        distance: f32,
+        batch_key: Option<u64>,
+    ) -> RenderFeatureSubmitNode {
+        RenderFeatureSubmitNode::new(0, submit_node_id, sort_key, distance)
+            .with_batch_key(batch_key)
+    }
+
+    fn submit_node_ids(submit_nodes: &[RenderFeatureSubmitNode]) -> Vec<u32> {
+        submit_nodes.iter().map(|x| x.submit_node_id()).collect()
+    }
+
+    #[test]
+    fn test_layer_is_most_significant() {
+        let low_layer = SubmitNodeSortKeyBuilder::new()
+            .layer(0)
+            .pipeline(&u64::MAX)
+            .material(&u64::MAX)
+            .depth(1000.0, SubmitNodeDepthOrder::BackToFront)
+            .feature(63);
+        let high_layer = SubmitNodeSortKeyBuilder::new().layer(1);
+
+        assert!(low_layer.build_state_sorted() < high_layer.build_state_sorted());
+        assert!(low_layer.build_depth_sorted() < high_layer.build_depth_sorted());
+        assert_eq!(
+            submit_node_sort_key_layer(high_layer.build_state_sorted()),
+            1
+        );
+        assert_eq!(
+            submit_node_sort_key_layer(high_layer.build_depth_sorted()),
+            1
+        );
+        assert_eq!(
+            submit_node_sort_key_layer(
+                SubmitNodeSortKeyBuilder::new()
+                    .layer(255)
+                    .build_depth_sorted()
+            ),
+            255
+        );
+    }
+
+    #[test]
+    fn test_state_sorted_ignores_depth() {
+        let builder = SubmitNodeSortKeyBuilder::new()
+            .pipeline(&1)
+            .material(&2)
+            .feature(3);
+        let near = builder.depth(1.0, SubmitNodeDepthOrder::FrontToBack);
+        let far = builder.depth(100.0, SubmitNodeDepthOrder::FrontToBack);
+
+        assert_eq!(near.build_state_sorted(), far.build_state_sorted());
+        assert_ne!(
+            builder.build_state_sorted(),
+            builder.pipeline(&4).build_state_sorted()
+        );
+        assert_ne!(
+            builder.build_state_sorted(),
+            builder.material(&4).build_state_sorted()
+        );
+    }
+
+    #[test]
+    fn test_depth_sorted_key_order() {
+        let builder = SubmitNodeSortKeyBuilder::new().pipeline(&1).material(&2);
+
+        let front_to_back = |distance| {
+            builder
+                .depth(distance, SubmitNodeDepthOrder::FrontToBack)
+                .build_depth_sorted()
+        };
+        assert!(front_to_back(1.0) < front_to_back(2.0));
+        assert!(front_to_back(2.0) < front_to_back(1000.0));
+        // Negative and NaN distances sort as if they were at the camera
+        assert_eq!(front_to_back(-5.0), front_to_back(0.0));
+        assert_eq!(front_to_back(std::f32::NAN), front_to_back(0.0));
+
+        let back_to_front = |distance| {
+            builder
+                .depth(distance, SubmitNodeDepthOrder::BackToFront)
+                .build_depth_sorted()
+        };
+        assert!(back_to_front(2.0) < back_to_front(1.0));
+        assert!(back_to_front(1000.0) < back_to_front(2.0));
+
+        // Depth is more significant than the pipeline
+        let near_pipeline_b = builder
+            .pipeline(&u64::MAX)
+            .depth(1.0, SubmitNodeDepthOrder::FrontToBack)
+            .build_depth_sorted();
+        assert!(near_pipeline_b < front_to_back(2.0));
+    }
+
+    #[test]
+    fn test_sort_by_state_keeps_batches_together() {
+        let key_a = SubmitNodeSortKeyBuilder::new()
+            .pipeline(&1)
+            .build_state_sorted();
+        let key_b = SubmitNodeSortKeyBuilder::new()
+            .pipeline(&2)
+            .build_state_sorted();
+        let (first_key, second_key) = if key_a < key_b {
+            (key_a, key_b)
+        } else {
+            (key_b, key_a)
+        };
+
+        // Nodes that share a sort key are grouped by batch key even if their distances interleave,
+        // then drawn front to back within a batch
+        let mut submit_nodes = vec![
+            submit_node(0, second_key, 1.0, None),
+            submit_node(1, first_key, 3.0, Some(1)),
+            submit_node(2, first_key, 2.0, Some(2)),
+            submit_node(3, first_key, 1.0, Some(1)),
+            submit_node(4, first_key, 4.0, Some(2)),
+        ];
+
+        sort_submit_nodes_by_state(&mut submit_nodes);
+        assert_eq!(submit_node_ids(&submit_nodes), vec![3, 1, 2, 4, 0]);
+    }
+
+    #[test]
+    fn test_sort_by_distance() {
+        let layer_0 = SubmitNodeSortKeyBuilder::new().build_depth_sorted();
+        let layer_1 = SubmitNodeSortKeyBuilder::new()
+            .layer(1)
+            .build_depth_sorted();
+
+        let submit_nodes = vec![
+            submit_node(0, layer_1, 1.0, None),
+            submit_node(1, layer_0, 3.0, None),
+            submit_node(2, layer_0, 1.0, None),
+            submit_node(3, layer_0, 2.0, None),
+        ];
+
+        let mut front_to_back = submit_nodes.clone();
+        sort_submit_nodes_front_to_back(&mut front_to_back);
+        assert_eq!(submit_node_ids(&front_to_back), vec![2, 3, 1, 0]);
+
+        let mut back_to_front = submit_nodes.clone();
+        sort_submit_nodes_back_to_front(&mut back_to_front);
+        assert_eq!(submit_node_ids(&back_to_front), vec![1, 3, 2, 0]);
+
+        let mut insertion_order = submit_nodes;
+        sort_submit_nodes_by_insertion_order(&mut insertion_order);
+        assert_eq!(submit_node_ids(&insertion_order), vec![1, 2, 3, 0]);
+    }
+}
@@ -17,6 +17,83 @@ pub struct SlotLocation {
 
 pub type SlotNameLookup = FnvHashMap<String, FnvHashSet<SlotLocation>>;
 
+/// The type of a member within a uniform or storage buffer. These are the builtin types supported by
+/// the shader processor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReflectedUniformMemberType {
+    Int,
+    Uint,
+    Bool,
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Mat2,
+    Mat3,
+    Mat4,
+}
+
+impl ReflectedUniformMemberType {
+    /// Matrices are stored as columns, other types are a single column
+    pub fn column_count(self) -> u32 {
+        match self {
+            ReflectedUniformMemberType::Mat2 => 2,
+            ReflectedUniformMemberType::Mat3 => 3,
+            ReflectedUniformMemberType::Mat4 => 4,
+            _ => 1,
+        }
+    }
+
+    pub fn column_size_in_bytes(self) -> u32 {
+        match self {
+            ReflectedUniformMemberType::Int => 4,
+            ReflectedUniformMemberType::Uint => 4,
+            ReflectedUniformMemberType::Bool => 4,
+            ReflectedUniformMemberType::Float => 4,
+            ReflectedUniformMemberType::Vec2 => 8,
+            ReflectedUniformMemberType::Vec3 => 12,
+            ReflectedUniformMemberType::Vec4 => 16,
+            ReflectedUniformMemberType::Mat2 => 8,
+            ReflectedUniformMemberType::Mat3 => 12,
+            ReflectedUniformMemberType::Mat4 => 16,
+        }
+    }
+}
+
+/// A value within a uniform or storage buffer, laid out using the buffer's memory layout (std140 for
+/// uniform buffers, std430 for storage buffers). Members of structs are flattened into dotted paths
+/// like "light.range". Arrays of structs are expanded per element ("lights[1].color") and arrays of
+/// builtin types are a single member with an array_stride.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ReflectedUniformMember {
+    pub path: String,
+    pub member_type: ReflectedUniformMemberType,
+    pub offset: u32,
+    // Zero if the member is not an array
+    pub array_length: u32,
+    pub array_stride: u32,
+    // Byte offset between the columns of a matrix. Zero for other types, or if the columns are
+    // tightly packed.
+    #[serde(default)]
+    pub matrix_stride: u32,
+}
+
+impl ReflectedUniformMember {
+    pub fn column_stride(&self) -> u32 {
+        if self.matrix_stride != 0 {
+            self.matrix_stride
+        } else {
+            self.member_type.column_size_in_bytes()
+        }
+    }
+
+    /// The number of bytes written when setting a single (non-array) value of this member
+    pub fn size_in_bytes(&self) -> u32 {
+        let column_count = self.member_type.column_count();
+        (column_count - 1) * self.column_stride() + self.member_type.column_size_in_bytes()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct ReflectedDescriptorSetLayoutBinding {
     // Basic info required to create the RafxRootSignature
@@ -29,6 +106,11 @@ pub struct ReflectedDescriptorSetLayoutBinding {
     // and automatically bind it - this makes binding data easy to do without having to manage
     // buffers.
     pub internal_buffer_per_descriptor_size: Option<u32>,
+
+    // The members of a uniform or storage buffer, used to set values by name at runtime. Empty for
+    // other resource types and for shaders cooked before this was reflected.
+    #[serde(default)]
+    pub uniform_members: Vec<ReflectedUniformMember>,
 }
 
 impl Into<DescriptorSetLayoutBinding> for ReflectedDescriptorSetLayoutBinding {
@@ -37,6 +119,7 @@ impl Into<DescriptorSetLayoutBinding> for ReflectedDescriptorSetLayoutBinding {
             resource: self.resource.clone(),
             immutable_samplers: self.immutable_samplers.clone(),
             internal_buffer_per_descriptor_size: self.internal_buffer_per_descriptor_size,
+            uniform_members: self.uniform_members,
         }
     }
 }
@@ -203,6 +286,20 @@ impl ReflectedShader {
                                 return Err(error)?;
                             }
 
+                            if existing_binding.uniform_members.is_empty() {
+                                existing_binding.uniform_members = binding.uniform_members.clone();
+                            } else if !binding.uniform_members.is_empty()
+                                && existing_binding.uniform_members != binding.uniform_members
+                            {
+                                let error = format!(
+                                    "Load Material Failed - Pass is using shaders in different stages with different uniform buffer layouts for set={} binding={}",
+                                    set_index,
+                                    binding.resource.binding
+                                );
+                                log::error!("{}", error);
+                                return Err(error)?;
+                            }
+
                             log::trace!("    Descriptor for binding set={} binding={} already exists, adding stage {:?}", set_index, binding.resource.binding, binding.resource.used_in_shader_stages);
                             existing_binding.resource.used_in_shader_stages |=
                                 binding.resource.used_in_shader_stages;
@@ -82,6 +82,22 @@ impl DescriptorSetArc {
             &self.inner.handle,
         )
     }
+
+    /// Bind the descriptor set, providing an offset for each dynamic uniform buffer in the set, in
+    /// order of binding index (see DynRingBufferAllocation::dynamic_offset)
+    pub fn bind_with_dynamic_offsets(
+        &self,
+        command_buffer: &RafxCommandBuffer,
+        dynamic_offsets: &[u32],
+    ) -> RafxResult<()> {
+        let descriptor_set_layout = &self.inner.descriptor_set_layout.get_raw();
+        command_buffer.cmd_bind_descriptor_set_handle_with_dynamic_offsets(
+            &descriptor_set_layout.root_signature,
+            descriptor_set_layout.set_index,
+            &self.inner.handle,
+            dynamic_offsets,
+        )
+    }
 }
 
 impl std::fmt::Debug for DescriptorSetArc {
@@ -94,3 +110,25 @@ impl std::fmt::Debug for DescriptorSetArc {
             .finish()
     }
 }
+
+// Two DescriptorSetArcs are equal if they keep the same descriptor set allocated. This allows
+// comparing descriptor sets, i.e. to check if draw calls can be batched.
+impl PartialEq for DescriptorSetArc {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for DescriptorSetArc {}
+
+impl std::hash::Hash for DescriptorSetArc {
+    fn hash<H: std::hash::Hasher>(
+        &self,
+        state: &mut H,
+    ) {
+        Arc::as_ptr(&self.inner).hash(state);
+    }
+}
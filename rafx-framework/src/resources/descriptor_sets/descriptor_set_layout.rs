@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::ReflectedUniformMember;
 use rafx_api::{RafxSamplerDef, RafxShaderResource};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
@@ -14,6 +15,10 @@ pub struct DescriptorSetLayoutBinding {
     // and automatically bind it - this makes binding data easy to do without having to manage
     // buffers.
     pub internal_buffer_per_descriptor_size: Option<u32>,
+
+    // The members of a uniform or storage buffer, used to set values by name at runtime (see
+    // DynDescriptorSet::set_uniform_param)
+    pub uniform_members: Vec<ReflectedUniformMember>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
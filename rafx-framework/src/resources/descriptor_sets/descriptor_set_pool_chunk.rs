@@ -302,6 +302,8 @@ impl DescriptorSetWriterContext<'_> {
         binding_index: u32,
         array_index: usize,
         buffer: &ResourceArc<BufferResource>,
+        byte_offset: Option<u64>,
+        size: Option<u64>,
     ) -> RafxResult<()> {
         let key = DescriptorSetElementKey {
             dst_binding: binding_index,
@@ -316,8 +318,8 @@ impl DescriptorSetWriterContext<'_> {
                 element.buffer_info.buffer = Some(DescriptorSetWriteElementBufferData::BufferRef(
                     DescriptorSetWriteElementBufferDataBufferRef {
                         buffer: buffer.clone(),
-                        byte_offset: None,
-                        size: None,
+                        byte_offset,
+                        size,
                     },
                 ));
                 self.write(&key, &element)?;
@@ -416,7 +418,7 @@ impl DescriptorSetBindings for DescriptorSetWriterContext<'_> {
         binding_index: u32,
         data: &ResourceArc<BufferResource>,
     ) {
-        self.set_buffer_array_element(binding_index, 0, data)
+        self.set_buffer_array_element(binding_index, 0, data, None, None)
             .unwrap();
     }
 
@@ -426,7 +428,18 @@ impl DescriptorSetBindings for DescriptorSetWriterContext<'_> {
         array_index: usize,
         data: &ResourceArc<BufferResource>,
     ) {
-        self.set_buffer_array_element(binding_index, array_index, data)
+        self.set_buffer_array_element(binding_index, array_index, data, None, None)
+            .unwrap();
+    }
+
+    fn set_buffer_range(
+        &mut self,
+        binding_index: u32,
+        data: &ResourceArc<BufferResource>,
+        byte_offset: u64,
+        size: u64,
+    ) {
+        self.set_buffer_array_element(binding_index, 0, data, Some(byte_offset), Some(size))
             .unwrap();
     }
 
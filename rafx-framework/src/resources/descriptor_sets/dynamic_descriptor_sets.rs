@@ -5,11 +5,11 @@ use crate::resources::descriptor_sets::descriptor_write_set::{
     DescriptorSetWriteElementBufferData, DescriptorSetWriteElementImageValue,
 };
 use crate::resources::descriptor_sets::{
-    DescriptorSetAllocator, DescriptorSetWriteElementBufferDataBufferRef,
+    DescriptorSetAllocator, DescriptorSetWriteElementBufferDataBufferRef, UniformParamValue,
 };
 use crate::resources::resource_lookup::{DescriptorSetLayoutResource, ImageViewResource};
 use crate::resources::ResourceArc;
-use crate::BufferResource;
+use crate::{BufferResource, DynRingBufferAllocation};
 use rafx_api::{RafxCommandBuffer, RafxResult};
 use std::fmt::Formatter;
 
@@ -86,6 +86,32 @@ pub trait DescriptorSetBindings {
         data: &ResourceArc<BufferResource>,
     );
 
+    // Bind part of a buffer, for example an allocation from DynRingBufferAllocator. The offset must
+    // respect the device's min_uniform_buffer_offset_alignment
+    fn set_buffer_range(
+        &mut self,
+        binding_index: u32,
+        data: &ResourceArc<BufferResource>,
+        byte_offset: u64,
+        size: u64,
+    );
+
+    // Bind a dynamic uniform buffer to the buffer an allocation from DynRingBufferAllocator was
+    // made in. The offset of the allocation is provided when binding the descriptor set, so the
+    // descriptor set can be reused by other allocations of the same size in the same buffer
+    fn set_dynamic_uniform_buffer(
+        &mut self,
+        binding_index: u32,
+        allocation: &DynRingBufferAllocation,
+    ) {
+        self.set_buffer_range(
+            binding_index,
+            allocation.buffer(),
+            allocation.descriptor_byte_offset(),
+            allocation.size(),
+        )
+    }
+
     fn set_buffer_data<T: Copy + 'static>(
         &mut self,
         binding_index: u32,
@@ -156,6 +182,31 @@ impl DynDescriptorSet {
         Ok(())
     }
 
+    /// Sets a member of a uniform buffer by name, i.e. "base_color_factor" or "light.range". The
+    /// buffer must use an internal buffer and the value's type must match the reflected member.
+    pub fn set_uniform_param(
+        &mut self,
+        path: &str,
+        value: &UniformParamValue,
+    ) -> RafxResult<()> {
+        let key = super::write_uniform_param_to_write_set(
+            &self.descriptor_set_layout.get_raw().descriptor_set_layout_def,
+            &mut self.write_set,
+            path,
+            value,
+        )?
+        .ok_or_else(|| {
+            format!(
+                "No uniform buffer in the descriptor set has a member named {} (shaders must be cooked with uniform member reflection data)",
+                path
+            )
+        })?;
+
+        let element = self.write_set.elements.get(&key).unwrap().clone();
+        self.pending_write_set.elements.insert(key, element);
+        Ok(())
+    }
+
     fn set_image_array_element(
         &mut self,
         binding_index: u32,
@@ -188,6 +239,8 @@ impl DynDescriptorSet {
         binding_index: u32,
         array_index: usize,
         buffer: &ResourceArc<BufferResource>,
+        byte_offset: Option<u64>,
+        size: Option<u64>,
     ) {
         let key = DescriptorSetElementKey {
             dst_binding: binding_index,
@@ -200,8 +253,8 @@ impl DynDescriptorSet {
                 element.buffer_info.buffer = Some(DescriptorSetWriteElementBufferData::BufferRef(
                     DescriptorSetWriteElementBufferDataBufferRef {
                         buffer: buffer.clone(),
-                        byte_offset: None,
-                        size: None,
+                        byte_offset,
+                        size,
                     },
                 ));
 
@@ -295,7 +348,7 @@ impl DescriptorSetBindings for DynDescriptorSet {
         binding_index: u32,
         data: &ResourceArc<BufferResource>,
     ) {
-        self.set_buffer_array_element(binding_index, 0, data)
+        self.set_buffer_array_element(binding_index, 0, data, None, None)
     }
 
     fn set_buffer_at_index(
@@ -304,7 +357,17 @@ impl DescriptorSetBindings for DynDescriptorSet {
         array_index: usize,
         data: &ResourceArc<BufferResource>,
     ) {
-        self.set_buffer_array_element(binding_index, array_index, data)
+        self.set_buffer_array_element(binding_index, array_index, data, None, None)
+    }
+
+    fn set_buffer_range(
+        &mut self,
+        binding_index: u32,
+        data: &ResourceArc<BufferResource>,
+        byte_offset: u64,
+        size: u64,
+    ) {
+        self.set_buffer_array_element(binding_index, 0, data, Some(byte_offset), Some(size))
     }
 
     // Requiring 'static helps us catch accidentally trying to store a reference in the buffer
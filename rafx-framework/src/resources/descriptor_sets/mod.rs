@@ -35,6 +35,10 @@ pub use descriptor_write_set::DescriptorSetWriteElementImage;
 pub use descriptor_write_set::DescriptorSetWriteElementImageValue;
 pub use descriptor_write_set::DescriptorSetWriteSet;
 
+mod uniform_params;
+pub use uniform_params::write_uniform_param_to_write_set;
+pub use uniform_params::UniformParamValue;
+
 mod descriptor_set_allocator;
 pub use descriptor_set_allocator::DescriptorSetAllocator;
 pub use descriptor_set_allocator::DescriptorSetAllocatorMetrics;
@@ -107,9 +111,15 @@ pub fn what_to_bind(element_write: &DescriptorSetElementWrite) -> WhatToBind {
         RafxResourceType::TEXTURE => {
             what.bind_images = true;
         }
+        RafxResourceType::INPUT_ATTACHMENT => {
+            what.bind_images = true;
+        }
         RafxResourceType::UNIFORM_BUFFER => {
             what.bind_buffers = true;
         }
+        RafxResourceType::UNIFORM_BUFFER_DYNAMIC => {
+            what.bind_buffers = true;
+        }
         RafxResourceType::BUFFER => {
             what.bind_buffers = true;
         }
@@ -0,0 +1,513 @@
+use super::{DescriptorSetElementKey, DescriptorSetLayout, DescriptorSetWriteSet};
+use crate::resources::descriptor_sets::DescriptorSetWriteElementBufferData;
+use crate::{ReflectedUniformMember, ReflectedUniformMemberType};
+use rafx_api::RafxResult;
+use serde::{Deserialize, Serialize};
+
+/// A value that can be written to a member of a uniform or storage buffer by name (see
+/// DynDescriptorSet::set_uniform_param). The value's type must match the type of the member.
+/// Matrices are column-major.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UniformParamValue {
+    Int(i32),
+    Uint(u32),
+    Bool(bool),
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Mat2([[f32; 2]; 2]),
+    Mat3([[f32; 3]; 3]),
+    Mat4([[f32; 4]; 4]),
+}
+
+impl UniformParamValue {
+    pub fn member_type(&self) -> ReflectedUniformMemberType {
+        match self {
+            UniformParamValue::Int(_) => ReflectedUniformMemberType::Int,
+            UniformParamValue::Uint(_) => ReflectedUniformMemberType::Uint,
+            UniformParamValue::Bool(_) => ReflectedUniformMemberType::Bool,
+            UniformParamValue::Float(_) => ReflectedUniformMemberType::Float,
+            UniformParamValue::Vec2(_) => ReflectedUniformMemberType::Vec2,
+            UniformParamValue::Vec3(_) => ReflectedUniformMemberType::Vec3,
+            UniformParamValue::Vec4(_) => ReflectedUniformMemberType::Vec4,
+            UniformParamValue::Mat2(_) => ReflectedUniformMemberType::Mat2,
+            UniformParamValue::Mat3(_) => ReflectedUniformMemberType::Mat3,
+            UniformParamValue::Mat4(_) => ReflectedUniformMemberType::Mat4,
+        }
+    }
+
+    // Writes the value into bytes, which must be the size of the member. Matrix columns are written
+    // column_stride bytes apart.
+    fn write_bytes(
+        &self,
+        bytes: &mut [u8],
+        column_stride: usize,
+    ) {
+        match self {
+            UniformParamValue::Int(x) => bytes.copy_from_slice(&x.to_ne_bytes()),
+            UniformParamValue::Uint(x) => bytes.copy_from_slice(&x.to_ne_bytes()),
+            // Booleans are 32-bit in GLSL
+            UniformParamValue::Bool(x) => bytes.copy_from_slice(&(*x as u32).to_ne_bytes()),
+            UniformParamValue::Float(x) => bytes.copy_from_slice(&x.to_ne_bytes()),
+            UniformParamValue::Vec2(x) => bytes.copy_from_slice(rafx_base::memory::any_as_bytes(x)),
+            UniformParamValue::Vec3(x) => bytes.copy_from_slice(rafx_base::memory::any_as_bytes(x)),
+            UniformParamValue::Vec4(x) => bytes.copy_from_slice(rafx_base::memory::any_as_bytes(x)),
+            UniformParamValue::Mat2(x) => write_columns(bytes, x, column_stride),
+            UniformParamValue::Mat3(x) => write_columns(bytes, x, column_stride),
+            UniformParamValue::Mat4(x) => write_columns(bytes, x, column_stride),
+        }
+    }
+}
+
+fn write_columns<T: Copy>(
+    bytes: &mut [u8],
+    columns: &[T],
+    column_stride: usize,
+) {
+    let column_size = std::mem::size_of::<T>();
+    for (i, column) in columns.iter().enumerate() {
+        let column_offset = i * column_stride;
+        bytes[column_offset..column_offset + column_size]
+            .copy_from_slice(rafx_base::memory::any_as_bytes(column));
+    }
+}
+
+// Splits a trailing array index off a path, i.e. "colors[2]" -> ("colors", Some(2))
+fn split_array_index(path: &str) -> RafxResult<(&str, Option<u32>)> {
+    if !path.ends_with(']') {
+        return Ok((path, None));
+    }
+
+    let open = path
+        .rfind('[')
+        .ok_or_else(|| format!("Uniform param path {} has an unmatched ]", path))?;
+    let index = path[open + 1..path.len() - 1]
+        .parse::<u32>()
+        .map_err(|_| format!("Uniform param path {} has an invalid array index", path))?;
+
+    Ok((&path[..open], Some(index)))
+}
+
+// Returns the member and byte offset of the value at the given path, or None if no member matches. Arrays of
+// structs are flattened per element by the shader processor, so an exact match is tried first.
+fn find_member_offset<'a>(
+    members: &'a [ReflectedUniformMember],
+    path: &str,
+    value_type: ReflectedUniformMemberType,
+) -> RafxResult<Option<(&'a ReflectedUniformMember, u32)>> {
+    let (member, array_index) = match members.iter().find(|x| x.path == path) {
+        Some(member) => (member, None),
+        None => {
+            let (member_path, array_index) = split_array_index(path)?;
+            if array_index.is_none() {
+                return Ok(None);
+            }
+
+            match members.iter().find(|x| x.path == member_path) {
+                Some(member) => (member, array_index),
+                None => return Ok(None),
+            }
+        }
+    };
+
+    if member.member_type != value_type {
+        Err(format!(
+            "Uniform param {} is a {:?} but a {:?} was provided",
+            path, member.member_type, value_type
+        ))?;
+    }
+
+    match array_index {
+        Some(array_index) => {
+            if member.array_length == 0 {
+                Err(format!(
+                    "Uniform param {} is indexed but {} is not an array",
+                    path, member.path
+                ))?;
+            }
+
+            if array_index >= member.array_length {
+                Err(format!(
+                    "Uniform param {} is out of bounds, {} has {} elements",
+                    path, member.path, member.array_length
+                ))?;
+            }
+
+            Ok(Some((
+                member,
+                member.offset + array_index * member.array_stride,
+            )))
+        }
+        None => {
+            if member.array_length != 0 {
+                Err(format!(
+                    "Uniform param {} is an array, an element must be specified (i.e. {}[0])",
+                    path, path
+                ))?;
+            }
+
+            Ok(Some((member, member.offset)))
+        }
+    }
+}
+
+/// Writes a value by name into the internal buffer data of the uniform or storage buffer in the
+/// layout that contains it. The path may be prefixed with the binding's slot name (i.e.
+/// "per_material_data.base_color_factor") to disambiguate members with the same name in different
+/// buffers. Returns the element that was modified, or None if no binding in the layout has a member
+/// at that path.
+pub fn write_uniform_param_to_write_set(
+    layout: &DescriptorSetLayout,
+    write_set: &mut DescriptorSetWriteSet,
+    path: &str,
+    value: &UniformParamValue,
+) -> RafxResult<Option<DescriptorSetElementKey>> {
+    for binding in &layout.bindings {
+        if !binding.resource.resource_type.is_uniform_buffer()
+            && !binding.resource.resource_type.is_storage_buffer()
+        {
+            continue;
+        }
+
+        let member_path = match &binding.resource.name {
+            Some(slot_name)
+                if path.len() > slot_name.len() + 1
+                    && path.starts_with(slot_name.as_str())
+                    && path.as_bytes()[slot_name.len()] == b'.' =>
+            {
+                &path[slot_name.len() + 1..]
+            }
+            _ => path,
+        };
+
+        let (member, offset) =
+            match find_member_offset(&binding.uniform_members, member_path, value.member_type())? {
+                Some((member, offset)) => (member, offset as usize),
+                None => continue,
+            };
+
+        let buffer_size = binding.internal_buffer_per_descriptor_size.ok_or_else(|| {
+            format!(
+                "Uniform param {} is in a buffer that does not use an internal buffer",
+                path
+            )
+        })? as usize;
+
+        let end = offset + member.size_in_bytes() as usize;
+        if end > buffer_size {
+            Err(format!(
+                "Uniform param {} at byte offset {} does not fit in a buffer of {} bytes",
+                path, offset, buffer_size
+            ))?;
+        }
+
+        let key = DescriptorSetElementKey {
+            dst_binding: binding.resource.binding,
+            array_index: 0,
+        };
+
+        let element = write_set.elements.get_mut(&key).ok_or_else(|| {
+            format!(
+                "Uniform param {} is in binding {} which is not in the write set",
+                path, binding.resource.binding
+            )
+        })?;
+
+        let mut data = match element.buffer_info.buffer.take() {
+            Some(DescriptorSetWriteElementBufferData::Data(data)) => data,
+            Some(buffer_ref @ DescriptorSetWriteElementBufferData::BufferRef(_)) => {
+                element.buffer_info.buffer = Some(buffer_ref);
+                Err(format!(
+                    "Uniform param {} is in binding {} which is bound to a buffer rather than data",
+                    path, binding.resource.binding
+                ))?
+            }
+            None => vec![0; buffer_size],
+        };
+
+        if data.len() < buffer_size {
+            data.resize(buffer_size, 0);
+        }
+
+        value.write_bytes(&mut data[offset..end], member.column_stride() as usize);
+        element.buffer_info.buffer = Some(DescriptorSetWriteElementBufferData::Data(data));
+        return Ok(Some(key));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resources::descriptor_sets::{
+        create_uninitialized_write_set_for_layout, DescriptorSetLayoutBinding,
+    };
+    use rafx_api::{RafxResourceType, RafxShaderResource};
+
+    fn member(
+        path: &str,
+        member_type: ReflectedUniformMemberType,
+        offset: u32,
+        array_length: u32,
+        array_stride: u32,
+        matrix_stride: u32,
+    ) -> ReflectedUniformMember {
+        ReflectedUniformMember {
+            path: path.to_string(),
+            member_type,
+            offset,
+            array_length,
+            array_stride,
+            matrix_stride,
+        }
+    }
+
+    fn binding(
+        binding: u32,
+        name: &str,
+        resource_type: RafxResourceType,
+        size: u32,
+        uniform_members: Vec<ReflectedUniformMember>,
+    ) -> DescriptorSetLayoutBinding {
+        DescriptorSetLayoutBinding {
+            resource: RafxShaderResource {
+                resource_type,
+                binding,
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            immutable_samplers: None,
+            internal_buffer_per_descriptor_size: Some(size),
+            uniform_members,
+        }
+    }
+
+    // The same members laid out as the shader processor reflects them for a uniform buffer (std140)
+    // and a storage buffer (std430):
+    //
+    // struct Light { float intensity; };
+    // float scale; vec2 offsets[2]; mat2 rotation; vec3 color; Light lights[2];
+    fn test_layout() -> DescriptorSetLayout {
+        use ReflectedUniformMemberType::*;
+        DescriptorSetLayout {
+            bindings: vec![
+                binding(
+                    0,
+                    "per_material_data",
+                    RafxResourceType::UNIFORM_BUFFER,
+                    128,
+                    vec![
+                        member("scale", Float, 0, 0, 0, 0),
+                        member("offsets", Vec2, 16, 2, 16, 0),
+                        member("rotation", Mat2, 48, 0, 0, 16),
+                        member("color", Vec3, 80, 0, 0, 0),
+                        member("lights[0].intensity", Float, 96, 0, 0, 0),
+                        member("lights[1].intensity", Float, 112, 0, 0, 0),
+                    ],
+                ),
+                binding(
+                    1,
+                    "per_object_data",
+                    RafxResourceType::BUFFER,
+                    68,
+                    vec![
+                        member("scale", Float, 0, 0, 0, 0),
+                        member("offsets", Vec2, 8, 2, 8, 0),
+                        member("rotation", Mat2, 24, 0, 0, 8),
+                        member("color", Vec3, 48, 0, 0, 0),
+                        member("lights[0].intensity", Float, 60, 0, 0, 0),
+                        member("lights[1].intensity", Float, 64, 0, 0, 0),
+                    ],
+                ),
+            ],
+        }
+    }
+
+    fn write(
+        layout: &DescriptorSetLayout,
+        write_set: &mut DescriptorSetWriteSet,
+        path: &str,
+        value: UniformParamValue,
+    ) -> RafxResult<Option<u32>> {
+        Ok(
+            write_uniform_param_to_write_set(layout, write_set, path, &value)?
+                .map(|x| x.dst_binding),
+        )
+    }
+
+    fn read_f32s(
+        write_set: &DescriptorSetWriteSet,
+        binding: u32,
+        offset: usize,
+        count: usize,
+    ) -> Vec<f32> {
+        let key = DescriptorSetElementKey {
+            dst_binding: binding,
+            array_index: 0,
+        };
+        match &write_set.elements[&key].buffer_info.buffer {
+            Some(DescriptorSetWriteElementBufferData::Data(data)) => data
+                [offset..offset + count * 4]
+                .chunks_exact(4)
+                .map(|x| f32::from_ne_bytes([x[0], x[1], x[2], x[3]]))
+                .collect(),
+            _ => panic!("expected binding {} to contain data", binding),
+        }
+    }
+
+    #[test]
+    fn test_split_array_index() {
+        assert_eq!(split_array_index("colors").unwrap(), ("colors", None));
+        assert_eq!(split_array_index("colors[2]").unwrap(), ("colors", Some(2)));
+        assert_eq!(
+            split_array_index("lights[1].colors[3]").unwrap(),
+            ("lights[1].colors", Some(3))
+        );
+        assert!(split_array_index("colors]").is_err());
+        assert!(split_array_index("colors[x]").is_err());
+        assert!(split_array_index("colors[-1]").is_err());
+    }
+
+    #[test]
+    fn test_name_lookup() {
+        let layout = test_layout();
+        let mut write_set = create_uninitialized_write_set_for_layout(&layout);
+
+        // Without the slot name the first buffer with a matching member is written
+        let value = UniformParamValue::Float(2.0);
+        assert_eq!(
+            write(&layout, &mut write_set, "scale", value).unwrap(),
+            Some(0)
+        );
+        assert_eq!(read_f32s(&write_set, 0, 0, 1), vec![2.0]);
+
+        // The slot name selects the buffer
+        let value = UniformParamValue::Float(3.0);
+        let path = "per_object_data.scale";
+        assert_eq!(
+            write(&layout, &mut write_set, path, value).unwrap(),
+            Some(1)
+        );
+        assert_eq!(read_f32s(&write_set, 1, 0, 1), vec![3.0]);
+        assert_eq!(read_f32s(&write_set, 0, 0, 1), vec![2.0]);
+
+        // Paths that don't match any member
+        let value = UniformParamValue::Float(1.0);
+        assert_eq!(
+            write(&layout, &mut write_set, "missing", value).unwrap(),
+            None
+        );
+        let path = "per_object_data.missing";
+        assert_eq!(write(&layout, &mut write_set, path, value).unwrap(), None);
+        let path = "per_object_data";
+        assert_eq!(write(&layout, &mut write_set, path, value).unwrap(), None);
+    }
+
+    #[test]
+    fn test_array_indexing() {
+        let layout = test_layout();
+        let mut write_set = create_uninitialized_write_set_for_layout(&layout);
+
+        let value = UniformParamValue::Vec2([1.0, 2.0]);
+        assert_eq!(
+            write(&layout, &mut write_set, "offsets[1]", value).unwrap(),
+            Some(0)
+        );
+        assert_eq!(read_f32s(&write_set, 0, 16, 4), vec![0.0; 4]);
+        assert_eq!(read_f32s(&write_set, 0, 32, 2), vec![1.0, 2.0]);
+
+        // Arrays must be indexed, in bounds, and only arrays can be indexed
+        assert!(write(&layout, &mut write_set, "offsets", value).is_err());
+        assert!(write(&layout, &mut write_set, "offsets[2]", value).is_err());
+        assert!(write(&layout, &mut write_set, "offsets[x]", value).is_err());
+        let value = UniformParamValue::Float(1.0);
+        assert!(write(&layout, &mut write_set, "scale[0]", value).is_err());
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let layout = test_layout();
+        let mut write_set = create_uninitialized_write_set_for_layout(&layout);
+
+        // Arrays of structs are flattened per element, so the whole path matches a member
+        let value = UniformParamValue::Float(5.0);
+        let path = "per_material_data.lights[1].intensity";
+        assert_eq!(
+            write(&layout, &mut write_set, path, value).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            read_f32s(&write_set, 0, 96, 5),
+            vec![0.0, 0.0, 0.0, 0.0, 5.0]
+        );
+
+        let path = "per_material_data.lights[2].intensity";
+        assert_eq!(write(&layout, &mut write_set, path, value).unwrap(), None);
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let layout = test_layout();
+        let mut write_set = create_uninitialized_write_set_for_layout(&layout);
+
+        let value = UniformParamValue::Uint(1);
+        assert!(write(&layout, &mut write_set, "scale", value).is_err());
+        let value = UniformParamValue::Vec4([1.0; 4]);
+        assert!(write(&layout, &mut write_set, "color", value).is_err());
+        let value = UniformParamValue::Float(1.0);
+        assert!(write(&layout, &mut write_set, "offsets[0]", value).is_err());
+
+        // Nothing was written
+        let key = DescriptorSetElementKey {
+            dst_binding: 0,
+            array_index: 0,
+        };
+        assert!(write_set.elements[&key].buffer_info.buffer.is_none());
+    }
+
+    #[test]
+    fn test_std140_and_std430_offsets() {
+        let layout = test_layout();
+        let mut write_set = create_uninitialized_write_set_for_layout(&layout);
+
+        let offset = UniformParamValue::Vec2([1.0, 2.0]);
+        let rotation = UniformParamValue::Mat2([[3.0, 4.0], [5.0, 6.0]]);
+        let color = UniformParamValue::Vec3([7.0, 8.0, 9.0]);
+        for slot_name in &["per_material_data", "per_object_data"] {
+            let path = format!("{}.offsets[1]", slot_name);
+            write(&layout, &mut write_set, &path, offset).unwrap();
+            let path = format!("{}.rotation", slot_name);
+            write(&layout, &mut write_set, &path, rotation).unwrap();
+            let path = format!("{}.color", slot_name);
+            write(&layout, &mut write_set, &path, color).unwrap();
+        }
+
+        // std140 rounds the stride of arrays and matrix columns up to 16 bytes
+        assert_eq!(
+            read_f32s(&write_set, 0, 16, 20),
+            vec![
+                0.0, 0.0, 0.0, 0.0, // offsets[0]
+                1.0, 2.0, 0.0, 0.0, // offsets[1]
+                3.0, 4.0, 0.0, 0.0, // rotation column 0
+                5.0, 6.0, 0.0, 0.0, // rotation column 1
+                7.0, 8.0, 9.0, 0.0, // color
+            ]
+        );
+
+        // std430 packs them tightly
+        assert_eq!(
+            read_f32s(&write_set, 1, 8, 14),
+            vec![
+                0.0, 0.0, // offsets[0]
+                1.0, 2.0, // offsets[1]
+                3.0, 4.0, // rotation column 0
+                5.0, 6.0, // rotation column 1
+                0.0, 0.0, // padding before the vec3
+                7.0, 8.0, 9.0, // color
+                0.0, // lights[0].intensity
+            ]
+        );
+    }
+}
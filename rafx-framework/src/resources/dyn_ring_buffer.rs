@@ -0,0 +1,300 @@
+use crate::resources::resource_lookup::ResourceLookupSet;
+use crate::{BufferResource, ResourceArc};
+use rafx_api::{RafxBufferDef, RafxMemoryUsage, RafxResourceType, RafxResult};
+use std::sync::{Arc, Mutex};
+
+// Size of each buffer the allocator creates. Allocations larger than this get a buffer of their own
+const DEFAULT_PAGE_SIZE: u64 = 1024 * 1024;
+
+/// A range of a buffer that was written this frame by DynRingBufferAllocator. The range is valid
+/// until the frame it was allocated in is no longer in flight. It should not be kept beyond that.
+#[derive(Debug, Clone)]
+pub struct DynRingBufferAllocation {
+    buffer: ResourceArc<BufferResource>,
+    byte_offset: u64,
+    size: u64,
+    supports_dynamic_offsets: bool,
+}
+
+impl DynRingBufferAllocation {
+    pub fn buffer(&self) -> &ResourceArc<BufferResource> {
+        &self.buffer
+    }
+
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The offset to write into a descriptor set when binding this allocation as a dynamic uniform
+    /// buffer. This is 0 unless the device does not support dynamic offsets, in which case the
+    /// offset must be baked into the descriptor set.
+    pub fn descriptor_byte_offset(&self) -> u64 {
+        if self.supports_dynamic_offsets {
+            0
+        } else {
+            self.byte_offset
+        }
+    }
+
+    /// The offset to provide when binding a descriptor set that uses this allocation as a dynamic
+    /// uniform buffer (see DescriptorSetArc::bind_with_dynamic_offsets)
+    pub fn dynamic_offset(&self) -> u32 {
+        if self.supports_dynamic_offsets {
+            self.byte_offset as u32
+        } else {
+            0
+        }
+    }
+}
+
+struct DynRingBufferPage {
+    buffer: ResourceArc<BufferResource>,
+    size: u64,
+    used: u64,
+}
+
+struct DynRingBufferAllocatorInner {
+    resources: ResourceLookupSet,
+    alignment: u64,
+    page_size: u64,
+    supports_dynamic_offsets: bool,
+
+    // One list of pages per frame that may be in flight, indexed by frame index modulo the length.
+    // A frame's pages are reset when the frame comes around again
+    frames: Vec<Vec<DynRingBufferPage>>,
+    current_frame_index: u64,
+
+    allocated_bytes: u64,
+    used_bytes: u64,
+    used_bytes_previous_frame: u64,
+}
+
+#[derive(Debug)]
+pub struct DynRingBufferAllocatorMetrics {
+    pub page_count: usize,
+    pub allocated_bytes: u64,
+    pub used_bytes_previous_frame: u64,
+}
+
+/// Hands out ranges of large, persistently mapped CPU-to-GPU buffers for data that is only needed
+/// for the current frame, such as per-frame vertex, index, and uniform data. This avoids creating a
+/// new buffer for every small upload. Allocations are aligned so that they can be bound as dynamic
+/// uniform buffers, allowing many allocations to share a descriptor set (see
+/// DescriptorSetBindings::set_dynamic_uniform_buffer). Memory is reclaimed automatically once the
+/// frame that allocated it is no longer in flight.
+#[derive(Clone)]
+pub struct DynRingBufferAllocator {
+    inner: Arc<Mutex<DynRingBufferAllocatorInner>>,
+}
+
+impl DynRingBufferAllocator {
+    pub fn new(
+        resources: &ResourceLookupSet,
+        max_frames_in_flight: u32,
+    ) -> Self {
+        let device_info = resources.device_context().device_info();
+        let alignment = device_info
+            .min_uniform_buffer_offset_alignment
+            .max(device_info.min_storage_buffer_offset_alignment)
+            .max(1) as u64;
+
+        // The frame currently being prepared may be one ahead of the frames in flight
+        let mut frames = Vec::with_capacity(max_frames_in_flight as usize + 1);
+        frames.resize_with(max_frames_in_flight as usize + 1, Default::default);
+
+        let inner = DynRingBufferAllocatorInner {
+            resources: resources.clone(),
+            alignment,
+            page_size: DEFAULT_PAGE_SIZE,
+            supports_dynamic_offsets: device_info.supports_dynamic_uniform_buffers,
+            frames,
+            current_frame_index: 0,
+            allocated_bytes: 0,
+            used_bytes: 0,
+            used_bytes_previous_frame: 0,
+        };
+
+        DynRingBufferAllocator {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    /// Copies the data into a buffer that can be bound as a vertex, index, or uniform buffer
+    pub fn allocate<T: Copy + 'static>(
+        &self,
+        data: &[T],
+    ) -> RafxResult<DynRingBufferAllocation> {
+        let size = rafx_base::memory::slice_size_in_bytes(data) as u64;
+        let (allocation, mapped_memory) =
+            self.allocate_uninitialized(size, std::mem::align_of::<T>() as u64)?;
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr() as *const u8, mapped_memory, size as usize);
+        }
+
+        Ok(allocation)
+    }
+
+    /// Writes the elements produced by the iterator into a buffer that can be bound as a vertex,
+    /// index, or uniform buffer. This avoids collecting data into a temporary Vec before calling
+    /// allocate(). The iterator must produce exactly as many elements as it reports.
+    pub fn allocate_from_iter<T: Copy + 'static, IterT: ExactSizeIterator<Item = T>>(
+        &self,
+        data: IterT,
+    ) -> RafxResult<DynRingBufferAllocation> {
+        let element_count = data.len();
+        let size = (element_count * std::mem::size_of::<T>()) as u64;
+        let (allocation, mapped_memory) =
+            self.allocate_uninitialized(size, std::mem::align_of::<T>() as u64)?;
+
+        // The allocator's lock is not held here, so the iterator may allocate too
+        let mapped_memory = mapped_memory as *mut T;
+        let mut written_count = 0;
+        for (index, element) in data.take(element_count).enumerate() {
+            unsafe {
+                mapped_memory.add(index).write(element);
+            }
+            written_count += 1;
+        }
+
+        if written_count != element_count {
+            Err(format!(
+                "Iterator passed to allocate_from_iter produced {} elements but reported a length of {}",
+                written_count, element_count
+            ))?;
+        }
+
+        Ok(allocation)
+    }
+
+    // Reserves size bytes aligned to at least the given alignment and returns a pointer to the
+    // mapped memory of the range
+    fn allocate_uninitialized(
+        &self,
+        size: u64,
+        alignment: u64,
+    ) -> RafxResult<(DynRingBufferAllocation, *mut u8)> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let alignment = inner.alignment.max(alignment);
+
+        let frame_slot = (inner.current_frame_index % inner.frames.len() as u64) as usize;
+
+        // Find the first page of this frame with enough space left. If there isn't one, add a page
+        let mut found = None;
+        for (page_index, page) in inner.frames[frame_slot].iter().enumerate() {
+            let byte_offset =
+                rafx_base::memory::round_size_up_to_alignment_u64(page.used, alignment);
+            if byte_offset + size <= page.size {
+                found = Some((page_index, byte_offset));
+                break;
+            }
+        }
+
+        let (page_index, byte_offset) = match found {
+            Some(found) => found,
+            None => {
+                let required_size =
+                    rafx_base::memory::round_size_up_to_alignment_u64(size, inner.alignment);
+                let page_size = inner.page_size.max(required_size);
+
+                log::debug!("Creating ring buffer page of {} bytes", page_size);
+                let buffer = inner
+                    .resources
+                    .device_context()
+                    .create_buffer(&RafxBufferDef {
+                        size: page_size,
+                        memory_usage: RafxMemoryUsage::CpuToGpu,
+                        resource_type: RafxResourceType::VERTEX_BUFFER
+                            | RafxResourceType::INDEX_BUFFER
+                            | RafxResourceType::UNIFORM_BUFFER,
+                        always_mapped: true,
+                        ..Default::default()
+                    })?;
+
+                let buffer = inner.resources.insert_buffer(buffer);
+                inner.allocated_bytes += page_size;
+                inner.frames[frame_slot].push(DynRingBufferPage {
+                    buffer,
+                    size: page_size,
+                    used: 0,
+                });
+
+                (inner.frames[frame_slot].len() - 1, 0)
+            }
+        };
+
+        let page = &mut inner.frames[frame_slot][page_index];
+
+        // Pages are persistently mapped, so write directly rather than mapping/unmapping per
+        // allocation
+        let mapped_memory = page
+            .buffer
+            .get_raw()
+            .buffer
+            .mapped_memory()
+            .ok_or("Ring buffer page is not mapped")?;
+
+        page.used = byte_offset + size;
+        inner.used_bytes += size;
+
+        let allocation = DynRingBufferAllocation {
+            buffer: page.buffer.clone(),
+            byte_offset,
+            size,
+            supports_dynamic_offsets: inner.supports_dynamic_offsets,
+        };
+
+        Ok((allocation, unsafe {
+            mapped_memory.add(byte_offset as usize)
+        }))
+    }
+
+    /// Copies a single value into a buffer that can be bound as a uniform buffer
+    pub fn allocate_uniform<T: Copy + 'static>(
+        &self,
+        data: &T,
+    ) -> RafxResult<DynRingBufferAllocation> {
+        self.allocate(std::slice::from_ref(data))
+    }
+
+    pub fn metrics(&self) -> DynRingBufferAllocatorMetrics {
+        let guard = self.inner.lock().unwrap();
+        DynRingBufferAllocatorMetrics {
+            page_count: guard.frames.iter().map(|x| x.len()).sum(),
+            allocated_bytes: guard.allocated_bytes,
+            used_bytes_previous_frame: guard.used_bytes_previous_frame,
+        }
+    }
+
+    /// Call every frame to reclaim memory used by frames that are no longer in flight
+    #[profiling::function]
+    pub fn on_frame_complete(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+
+        inner.used_bytes_previous_frame = inner.used_bytes;
+        inner.used_bytes = 0;
+        inner.current_frame_index += 1;
+
+        // The pages for the next frame were last used frames.len() frames ago, so they are no
+        // longer in flight
+        let frame_slot = (inner.current_frame_index % inner.frames.len() as u64) as usize;
+        for page in &mut inner.frames[frame_slot] {
+            page.used = 0;
+        }
+    }
+
+    /// Drops all pages. Allocations that are still held keep their buffer alive
+    pub fn clear(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        for frame in &mut guard.frames {
+            frame.clear();
+        }
+        guard.allocated_bytes = 0;
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     DescriptorSetWriteSet, FixedFunctionState, MaterialPassResource, MaterialPassVertexInput,
     RafxResult, ReflectedEntryPoint, ReflectedShader, ResourceArc, ResourceContext,
-    ShaderModuleResource, SlotNameLookup,
+    ShaderModuleResource, SlotNameLookup, UniformParamValue,
 };
 use fnv::FnvHashSet;
 use rafx_api::{RafxImmutableSamplerKey, RafxShaderStageFlags};
@@ -181,6 +181,40 @@ impl MaterialPass {
 
         pass_descriptor_set_writes
     }
+
+    /// Sets a member of a uniform buffer by name in write sets created by
+    /// create_uninitialized_write_sets_for_material_pass, i.e. when building a material instance.
+    /// The path may be prefixed with a slot name. See DynDescriptorSet::set_uniform_param
+    pub fn set_uniform_param(
+        &self,
+        write_sets: &mut [DescriptorSetWriteSet],
+        path: &str,
+        value: &UniformParamValue,
+    ) -> RafxResult<()> {
+        let descriptor_set_layouts = &self
+            .inner
+            .material_pass_resource
+            .get_raw()
+            .descriptor_set_layouts;
+
+        for (layout, write_set) in descriptor_set_layouts.iter().zip(write_sets) {
+            let written = super::descriptor_sets::write_uniform_param_to_write_set(
+                &layout.get_raw().descriptor_set_layout_def,
+                write_set,
+                path,
+                value,
+            )?;
+
+            if written.is_some() {
+                return Ok(());
+            }
+        }
+
+        Err(format!(
+            "No uniform buffer in the material pass has a member named {} (shaders must be cooked with uniform member reflection data)",
+            path
+        ))?
+    }
 }
 
 impl Deref for MaterialPass {
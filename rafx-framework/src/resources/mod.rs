@@ -1,6 +1,7 @@
 mod resource_arc;
 pub use resource_arc::ResourceArc;
 pub(crate) use resource_arc::ResourceId;
+pub use resource_arc::WeakResourceArc;
 
 mod resource_lookup;
 pub use resource_lookup::BufferKey;
@@ -25,6 +26,11 @@ mod dyn_resources;
 pub use dyn_resources::DynResourceAllocatorSet;
 pub use dyn_resources::DynResourceAllocatorSetProvider;
 
+mod dyn_ring_buffer;
+pub use dyn_ring_buffer::DynRingBufferAllocation;
+pub use dyn_ring_buffer::DynRingBufferAllocator;
+pub use dyn_ring_buffer::DynRingBufferAllocatorMetrics;
+
 pub mod descriptor_sets;
 pub use descriptor_sets::DescriptorSetAllocator;
 pub use descriptor_sets::DescriptorSetAllocatorMetrics;
@@ -39,6 +45,7 @@ pub use descriptor_sets::DescriptorSetWriteSet;
 pub use descriptor_sets::DescriptorSetWriter;
 pub use descriptor_sets::DescriptorSetWriterContext;
 pub use descriptor_sets::DynDescriptorSet;
+pub use descriptor_sets::UniformParamValue;
 
 mod resource_manager;
 pub use resource_manager::*;
@@ -54,6 +61,8 @@ pub use material::MaterialShaderStage;
 
 mod pipeline_cache;
 pub use pipeline_cache::GraphicsPipelineCache;
+pub use pipeline_cache::GraphicsPipelineRecord;
+pub use pipeline_cache::GraphicsPipelineRecordEntry;
 pub use pipeline_cache::GraphicsPipelineRenderTargetMeta;
 pub use pipeline_cache::GraphicsPipelineRenderTargetMetaHash;
 
@@ -3,19 +3,31 @@ use crate::render_features::{
 };
 use crate::resources::resource_arc::{ResourceId, WeakResourceArc};
 use crate::resources::vertex_data::{VertexDataSetLayout, VertexDataSetLayoutHash};
-use crate::{GraphicsPipelineResource, MaterialPassResource, ResourceArc, ResourceLookupSet};
+use crate::{
+    GraphicsPipelineResource, MaterialPassResource, ResourceArc, ResourceLookupSet,
+    VertexDataLayout, VertexMember,
+};
 use fnv::{FnvHashMap, FnvHashSet, FnvHasher};
 use rafx_api::{
-    RafxFormat, RafxResult, RafxSampleCount, RafxVertexLayout, RafxVertexLayoutAttribute,
+    RafxFormat, RafxGraphicsPipelineSubpass, RafxPrimitiveTopology, RafxResult, RafxSampleCount,
+    RafxSubpassDef, RafxVertexAttributeRate, RafxVertexLayout, RafxVertexLayoutAttribute,
     RafxVertexLayoutBuffer,
 };
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 //TODO: Allow caching for N frames
 //TODO: Return a kind of ResourceArc for a cached pipeline. Allow dropping after N frames pass with
 // nothing request/using it
-//TODO: vulkan pipeline cache object
+
+const PIPELINE_RECORD_FILE_NAME: &str = "graphics_pipelines.json";
+const PIPELINE_CACHE_DATA_FILE_NAME: &str = "pipeline_cache.bin";
+
+// Recorded pipelines that are not created for this many runs in a row are not saved again. This
+// evicts entries for material passes that were edited or are no longer used.
+const PIPELINE_RECORD_MAX_UNUSED_RUNS: u32 = 4;
 
 //TODO: Remove Serialize/Deserialize
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,6 +35,9 @@ pub struct GraphicsPipelineRenderTargetMeta {
     color_formats: Vec<RafxFormat>,
     depth_stencil_format: Option<RafxFormat>,
     sample_count: RafxSampleCount,
+    // Empty unless the renderpass has multiple subpasses
+    subpasses: Vec<RafxSubpassDef>,
+    subpass_index: u32,
     hash: GraphicsPipelineRenderTargetMetaHash,
 }
 
@@ -31,16 +46,38 @@ impl GraphicsPipelineRenderTargetMeta {
         color_formats: Vec<RafxFormat>,
         depth_stencil_format: Option<RafxFormat>,
         sample_count: RafxSampleCount,
+    ) -> Self {
+        Self::new_for_subpass(
+            color_formats,
+            depth_stencil_format,
+            sample_count,
+            Vec::default(),
+            0,
+        )
+    }
+
+    /// Describes a subpass of a renderpass with multiple subpasses. The formats are for all the
+    /// targets of the renderpass, not just the ones used by the subpass.
+    pub fn new_for_subpass(
+        color_formats: Vec<RafxFormat>,
+        depth_stencil_format: Option<RafxFormat>,
+        sample_count: RafxSampleCount,
+        subpasses: Vec<RafxSubpassDef>,
+        subpass_index: u32,
     ) -> Self {
         let hash = GraphicsPipelineRenderTargetMetaHash::new(
             &color_formats,
             depth_stencil_format,
             sample_count,
+            &subpasses,
+            subpass_index,
         );
         GraphicsPipelineRenderTargetMeta {
             color_formats,
             depth_stencil_format,
             sample_count,
+            subpasses,
+            subpass_index,
             hash,
         }
     }
@@ -57,6 +94,17 @@ impl GraphicsPipelineRenderTargetMeta {
         self.sample_count
     }
 
+    pub fn subpass(&self) -> Option<RafxGraphicsPipelineSubpass> {
+        if self.subpasses.is_empty() {
+            None
+        } else {
+            Some(RafxGraphicsPipelineSubpass {
+                subpasses: &self.subpasses,
+                subpass_index: self.subpass_index,
+            })
+        }
+    }
+
     pub fn render_target_meta_hash(&self) -> GraphicsPipelineRenderTargetMetaHash {
         self.hash
     }
@@ -69,17 +117,21 @@ impl GraphicsPipelineRenderTargetMetaHash {
         color_formats: &[RafxFormat],
         depth_stencil_format: Option<RafxFormat>,
         sample_count: RafxSampleCount,
+        subpasses: &[RafxSubpassDef],
+        subpass_index: u32,
     ) -> Self {
         let mut hasher = FnvHasher::default();
         color_formats.hash(&mut hasher);
         depth_stencil_format.hash(&mut hasher);
         sample_count.hash(&mut hasher);
+        subpasses.hash(&mut hasher);
+        subpass_index.hash(&mut hasher);
         let hash = hasher.finish();
         GraphicsPipelineRenderTargetMetaHash(hash)
     }
 }
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct CachedGraphicsPipelineKey {
     material_pass: ResourceId,
     render_target_meta_hash: GraphicsPipelineRenderTargetMetaHash,
@@ -98,6 +150,152 @@ struct RegisteredRenderTargetMeta {
     meta: GraphicsPipelineRenderTargetMeta,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicsPipelineRecordVertexMember {
+    pub semantic: String,
+    pub format: RafxFormat,
+    pub byte_offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicsPipelineRecordVertexBinding {
+    pub vertex_stride: usize,
+    pub vertex_rate: RafxVertexAttributeRate,
+    pub members: Vec<GraphicsPipelineRecordVertexMember>,
+}
+
+/// A (material pass, render target, vertex layout) combination that a pipeline was created for.
+/// The material pass is identified by a hash of its contents, so entries for a material that has
+/// since been edited are ignored and eventually evicted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphicsPipelineRecordEntry {
+    pub material_pass: u64,
+    pub color_formats: Vec<RafxFormat>,
+    pub depth_stencil_format: Option<RafxFormat>,
+    pub sample_count: RafxSampleCount,
+    #[serde(default)]
+    pub subpasses: Vec<RafxSubpassDef>,
+    #[serde(default)]
+    pub subpass_index: u32,
+    pub vertex_bindings: Vec<GraphicsPipelineRecordVertexBinding>,
+    pub primitive_topology: RafxPrimitiveTopology,
+    // The number of runs in a row, up to and including the one that saved the record, in which
+    // the pipeline was not created
+    #[serde(default)]
+    pub unused_run_count: u32,
+}
+
+impl GraphicsPipelineRecordEntry {
+    fn new(
+        material_pass: &ResourceArc<MaterialPassResource>,
+        render_target_meta: &GraphicsPipelineRenderTargetMeta,
+        vertex_data_set_layout: &VertexDataSetLayout,
+    ) -> Self {
+        let vertex_bindings = vertex_data_set_layout
+            .bindings()
+            .iter()
+            .map(|binding| {
+                let mut members: Vec<_> = binding
+                    .members()
+                    .iter()
+                    .map(|(semantic, meta)| GraphicsPipelineRecordVertexMember {
+                        semantic: semantic.clone(),
+                        format: meta.format,
+                        byte_offset: meta.byte_offset,
+                    })
+                    .collect();
+
+                // Sort so that the same layout always produces the same record
+                members.sort_by(|a, b| a.semantic.cmp(&b.semantic));
+
+                GraphicsPipelineRecordVertexBinding {
+                    vertex_stride: binding.vertex_stride(),
+                    vertex_rate: binding.vertex_rate(),
+                    members,
+                }
+            })
+            .collect();
+
+        GraphicsPipelineRecordEntry {
+            material_pass: material_pass.get_hash().0,
+            color_formats: render_target_meta.color_formats().to_vec(),
+            depth_stencil_format: render_target_meta.depth_stencil_format(),
+            sample_count: render_target_meta.sample_count(),
+            subpasses: render_target_meta.subpasses.clone(),
+            subpass_index: render_target_meta.subpass_index,
+            vertex_bindings,
+            primitive_topology: vertex_data_set_layout.primitive_topology(),
+            unused_run_count: 0,
+        }
+    }
+
+    fn render_target_meta(&self) -> GraphicsPipelineRenderTargetMeta {
+        GraphicsPipelineRenderTargetMeta::new_for_subpass(
+            self.color_formats.clone(),
+            self.depth_stencil_format,
+            self.sample_count,
+            self.subpasses.clone(),
+            self.subpass_index,
+        )
+    }
+
+    fn vertex_data_set_layout(&self) -> VertexDataSetLayout {
+        let layouts = self
+            .vertex_bindings
+            .iter()
+            .map(|binding| {
+                let members: Vec<_> = binding
+                    .members
+                    .iter()
+                    .map(|member| VertexMember {
+                        semantic: member.semantic.clone(),
+                        format: member.format,
+                        byte_offset: member.byte_offset,
+                    })
+                    .collect();
+
+                VertexDataLayout::new(binding.vertex_stride, binding.vertex_rate, &members)
+            })
+            .collect();
+
+        VertexDataSetLayout::new(layouts, self.primitive_topology)
+    }
+}
+
+/// Every combination of material pass, render target and vertex layout that the cache has created
+/// a pipeline for. Saving this at shutdown and loading it on the next run lets pipelines be created
+/// as soon as their material is loaded instead of the first time it's drawn.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphicsPipelineRecord {
+    pub entries: Vec<GraphicsPipelineRecordEntry>,
+}
+
+impl GraphicsPipelineRecord {
+    pub fn to_json(&self) -> String {
+        // The record only contains types that always serialize successfully
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> RafxResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse graphics pipeline record: {}", e).into())
+    }
+
+    /// Removes entries that were not used in the last `max_unused_runs` runs
+    pub fn evict_unused_entries(
+        &mut self,
+        max_unused_runs: u32,
+    ) {
+        self.entries
+            .retain(|entry| entry.unused_run_count <= max_unused_runs);
+    }
+}
+
+struct PipelineToPrecompile {
+    render_target_meta: GraphicsPipelineRenderTargetMeta,
+    vertex_data_set_layout: VertexDataSetLayout,
+}
+
 pub struct GraphicsPipelineCacheInner {
     resource_lookup_set: ResourceLookupSet,
 
@@ -108,6 +306,15 @@ pub struct GraphicsPipelineCacheInner {
 
     cached_pipelines: FnvHashMap<CachedGraphicsPipelineKey, CachedGraphicsPipeline>,
 
+    // Every pipeline that was created this run or loaded from a previous run's record
+    recorded_pipelines: FnvHashMap<CachedGraphicsPipelineKey, GraphicsPipelineRecordEntry>,
+    // Pipelines from a loaded record, indexed by material pass. They are created when the material
+    // pass is registered.
+    pipelines_to_precompile: FnvHashMap<ResourceId, Vec<PipelineToPrecompile>>,
+    // Pipelines that were created ahead of time. They move into cached_pipelines the first time
+    // they are requested and are kept until then as long as the material pass is alive.
+    precompiled_pipelines: FnvHashMap<CachedGraphicsPipelineKey, CachedGraphicsPipeline>,
+
     current_frame_index: u64,
     frames_to_persist: u64,
 
@@ -166,6 +373,9 @@ impl GraphicsPipelineCache {
             render_target_meta_assignments,
             material_pass_assignments,
             cached_pipelines: Default::default(),
+            recorded_pipelines: Default::default(),
+            pipelines_to_precompile: Default::default(),
+            precompiled_pipelines: Default::default(),
             current_frame_index: 0,
             frames_to_persist: DEFAULT_FRAMES_TO_PERSIST,
             #[cfg(debug_assertions)]
@@ -357,10 +567,73 @@ impl GraphicsPipelineCache {
 
         inner.material_pass_assignments[render_phase_index as usize]
             .insert(material_pass.get_hash(), material_pass.downgrade());
+
+        Self::precompile_pipelines_for_material_pass(inner, material_pass);
         //TODO: Do we need to mark this as a dirty material that may need to build additional
         // pipelines?
     }
 
+    fn precompile_pipelines_for_material_pass(
+        inner: &mut GraphicsPipelineCacheInner,
+        material_pass: &ResourceArc<MaterialPassResource>,
+    ) {
+        let pipelines_to_precompile =
+            match inner.pipelines_to_precompile.get(&material_pass.get_hash()) {
+                Some(pipelines_to_precompile) => pipelines_to_precompile,
+                None => return,
+            };
+
+        let mut precompiled_pipelines = Vec::default();
+        for pipeline_to_precompile in pipelines_to_precompile {
+            let key = CachedGraphicsPipelineKey {
+                material_pass: material_pass.get_hash(),
+                render_target_meta_hash: pipeline_to_precompile
+                    .render_target_meta
+                    .render_target_meta_hash(),
+                vertex_data_set_layout: pipeline_to_precompile.vertex_data_set_layout.hash(),
+            };
+
+            if inner.cached_pipelines.contains_key(&key)
+                || inner.precompiled_pipelines.contains_key(&key)
+            {
+                continue;
+            }
+
+            log::debug!("Precompiling graphics pipeline");
+            profiling::scope!("Precompile Pipeline");
+            let pipeline = Self::create_graphics_pipeline(
+                &inner.resource_lookup_set,
+                material_pass,
+                &pipeline_to_precompile.render_target_meta,
+                &pipeline_to_precompile.vertex_data_set_layout,
+            );
+
+            match pipeline {
+                Ok(pipeline) => precompiled_pipelines.push((key, pipeline)),
+                Err(e) => log::warn!("Failed to precompile graphics pipeline: {}", e),
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        {
+            inner.pipeline_create_count += precompiled_pipelines.len() as u64;
+        }
+
+        for (key, pipeline) in precompiled_pipelines {
+            if let Some(entry) = inner.recorded_pipelines.get_mut(&key) {
+                entry.unused_run_count = 0;
+            }
+
+            inner.precompiled_pipelines.insert(
+                key,
+                CachedGraphicsPipeline {
+                    graphics_pipeline: pipeline,
+                    material_pass_resource: material_pass.downgrade(),
+                },
+            );
+        }
+    }
+
     pub fn try_get_graphics_pipeline(
         &self,
         render_phase_index: RenderPhaseIndex,
@@ -427,164 +700,281 @@ impl GraphicsPipelineCache {
         );
         Self::do_register_material_to_phase_index(inner, material_pass, render_phase_index);
 
-        inner
-            .cached_pipelines
-            .get(&key)
-            .map(|x| {
-                debug_assert!(x.material_pass_resource.upgrade().is_some());
-                Ok(x.graphics_pipeline.clone())
+        if let Some(cached_pipeline) = inner.cached_pipelines.get(&key) {
+            debug_assert!(cached_pipeline.material_pass_resource.upgrade().is_some());
+            return Some(Ok(cached_pipeline.graphics_pipeline.clone()));
+        }
+
+        if let Some(precompiled_pipeline) = inner.precompiled_pipelines.remove(&key) {
+            let pipeline = precompiled_pipeline.graphics_pipeline.clone();
+            inner.cached_pipelines.insert(key, precompiled_pipeline);
+            return Some(Ok(pipeline));
+        }
+
+        if !create_if_missing {
+            return None;
+        }
+
+        log::debug!("Creating graphics pipeline");
+        profiling::scope!("Create Pipeline");
+
+        #[cfg(debug_assertions)]
+        {
+            inner.pipeline_create_count += 1;
+        }
+
+        let pipeline = Self::create_graphics_pipeline(
+            &inner.resource_lookup_set,
+            material_pass,
+            render_target_meta,
+            vertex_data_set_layout,
+        );
+
+        if let Ok(pipeline) = &pipeline {
+            inner.cached_pipelines.insert(
+                key,
+                CachedGraphicsPipeline {
+                    graphics_pipeline: pipeline.clone(),
+                    //render_target_meta: render_target_meta.clone(),
+                    material_pass_resource: material_pass.downgrade(),
+                },
+            );
+
+            inner
+                .recorded_pipelines
+                .entry(key)
+                .or_insert_with(|| {
+                    GraphicsPipelineRecordEntry::new(
+                        material_pass,
+                        render_target_meta,
+                        vertex_data_set_layout,
+                    )
+                })
+                .unused_run_count = 0;
+        }
+
+        Some(pipeline)
+    }
+
+    fn create_graphics_pipeline(
+        resource_lookup_set: &ResourceLookupSet,
+        material_pass: &ResourceArc<MaterialPassResource>,
+        render_target_meta: &GraphicsPipelineRenderTargetMeta,
+        vertex_data_set_layout: &VertexDataSetLayout,
+    ) -> RafxResult<ResourceArc<GraphicsPipelineResource>> {
+        //let mut binding_descriptions = Vec::default();
+        let mut vertex_layout_buffers = Vec::with_capacity(vertex_data_set_layout.bindings().len());
+        for binding in vertex_data_set_layout.bindings() {
+            vertex_layout_buffers.push(RafxVertexLayoutBuffer {
+                rate: binding.vertex_rate(),
+                stride: binding.vertex_stride() as u32,
             })
-            .or_else(|| {
-                if create_if_missing {
-                    log::debug!("Creating graphics pipeline");
-                    profiling::scope!("Create Pipeline");
-                    //let mut binding_descriptions = Vec::default();
-                    let mut vertex_layout_buffers =
-                        Vec::with_capacity(vertex_data_set_layout.bindings().len());
-                    for binding in vertex_data_set_layout.bindings() {
-                        vertex_layout_buffers.push(RafxVertexLayoutBuffer {
-                            rate: binding.vertex_rate(),
-                            stride: binding.vertex_stride() as u32,
-                        })
-                    }
-
-                    //let mut attribute_descriptions = Vec::default();
-                    let mut vertex_layout_attributes =
-                        Vec::with_capacity(material_pass.get_raw().vertex_inputs.len());
-
-                    for vertex_input in &*material_pass.get_raw().vertex_inputs {
-                        let member = vertex_data_set_layout
-                            .member(&vertex_input.semantic)
-                            .ok_or_else(|| {
-                                let error_message = format!(
-                                    "Vertex data does not support this material. Missing data {}",
-                                    vertex_input.semantic
-                                );
-                                log::error!("{}", error_message);
-                                log::info!(
-                                    "  required inputs:\n{:#?}",
-                                    material_pass.get_raw().vertex_inputs
-                                );
-                                log::info!(
-                                    "  available inputs:\n{:#?}",
-                                    vertex_data_set_layout.members()
-                                );
-                                error_message
-                            })
-                            .ok()?;
-
-                        vertex_layout_attributes.push(RafxVertexLayoutAttribute {
-                            location: vertex_input.location,
-                            byte_offset: member.byte_offset as u32,
-                            buffer_index: member.binding as u32,
-                            format: member.format,
-                            gl_attribute_name: Some(vertex_input.gl_attribute_name.clone()),
-                        });
-                    }
-
-                    let vertex_layout = RafxVertexLayout {
-                        attributes: vertex_layout_attributes,
-                        buffers: vertex_layout_buffers,
-                    };
-
-                    log::trace!("Creating graphics pipeline. Setting up vertex formats:");
-                    log::trace!(
+        }
+
+        //let mut attribute_descriptions = Vec::default();
+        let mut vertex_layout_attributes =
+            Vec::with_capacity(material_pass.get_raw().vertex_inputs.len());
+
+        for vertex_input in &*material_pass.get_raw().vertex_inputs {
+            let member = vertex_data_set_layout
+                .member(&vertex_input.semantic)
+                .ok_or_else(|| {
+                    let error_message = format!(
+                        "Vertex data does not support this material. Missing data {}",
+                        vertex_input.semantic
+                    );
+                    log::error!("{}", error_message);
+                    log::info!(
                         "  required inputs:\n{:#?}",
                         material_pass.get_raw().vertex_inputs
                     );
-                    log::trace!(
+                    log::info!(
                         "  available inputs:\n{:#?}",
                         vertex_data_set_layout.members()
                     );
+                    error_message
+                })?;
+
+            vertex_layout_attributes.push(RafxVertexLayoutAttribute {
+                location: vertex_input.location,
+                byte_offset: member.byte_offset as u32,
+                buffer_index: member.binding as u32,
+                format: member.format,
+                gl_attribute_name: Some(vertex_input.gl_attribute_name.clone()),
+            });
+        }
 
-                    #[cfg(debug_assertions)]
-                    {
-                        inner.pipeline_create_count += 1;
-                    }
+        let vertex_layout = RafxVertexLayout {
+            attributes: vertex_layout_attributes,
+            buffers: vertex_layout_buffers,
+        };
 
-                    log::trace!("Create vertex layout {:#?}", vertex_layout);
-                    let pipeline = inner.resource_lookup_set.get_or_create_graphics_pipeline(
-                        &material_pass,
-                        render_target_meta,
-                        vertex_data_set_layout.primitive_topology(),
-                        &vertex_layout,
-                    );
+        log::trace!("Creating graphics pipeline. Setting up vertex formats:");
+        log::trace!(
+            "  required inputs:\n{:#?}",
+            material_pass.get_raw().vertex_inputs
+        );
+        log::trace!(
+            "  available inputs:\n{:#?}",
+            vertex_data_set_layout.members()
+        );
 
-                    if let Ok(pipeline) = pipeline {
-                        inner.cached_pipelines.insert(
-                            key,
-                            CachedGraphicsPipeline {
-                                graphics_pipeline: pipeline.clone(),
-                                //render_target_meta: render_target_meta.clone(),
-                                material_pass_resource: material_pass.downgrade(),
-                            },
-                        );
-
-                        Some(Ok(pipeline))
-                    } else {
-                        Some(pipeline)
-                    }
-                } else {
-                    None
-                }
-            })
+        log::trace!("Create vertex layout {:#?}", vertex_layout);
+        resource_lookup_set.get_or_create_graphics_pipeline(
+            &material_pass,
+            render_target_meta,
+            vertex_data_set_layout.primitive_topology(),
+            &vertex_layout,
+        )
     }
 
+    /// Creates pipelines from a loaded GraphicsPipelineRecord for all currently registered material
+    /// passes. Material passes registered later are handled when they are registered.
     pub fn precache_pipelines_for_all_phases(&self) -> RafxResult<()> {
         let mut guard = self.inner.lock().unwrap();
-        let _inner = &mut *guard;
+        let inner = &mut *guard;
         #[cfg(debug_assertions)]
         {
-            _inner.lock_call_count += 1;
+            inner.lock_call_count += 1;
         }
 
-        //TODO: Avoid iterating everything all the time
-        //TODO: This will have to be reworked to include vertex layout as part of the key. Current
-        // plan is to register vertex types in code with the registry and have materials reference
-        // them by name
-        /*
-        for render_phase_index in 0..MAX_RENDER_PHASE_COUNT {
-            for (render_target_meta, renderpass) in
-                &inner.render_target_meta_assignments[render_phase_index as usize]
-            {
-                for (material_pass_hash, material_pass) in
-                    &inner.material_pass_assignments[render_phase_index as usize]
-                {
-                    let key = CachedGraphicsPipelineKey {
-                        renderpass: *renderpass_hash,
-                        material_pass: *material_pass_hash,
-                    };
-
-                    if !inner.cached_pipelines.contains_key(&key) {
-                        if let Some(renderpass) = renderpass.renderpass.upgrade() {
-                            if let Some(material_pass) = material_pass.upgrade() {
-                                #[cfg(debug_assertions)]
-                                {
-                                    guard.pipeline_create_count += 1;
-                                }
-
-                                let pipeline = inner
-                                    .resource_lookup_set
-                                    .get_or_create_graphics_pipeline(&material_pass, &renderpass)?;
-                                inner.cached_pipelines.insert(
-                                    key,
-                                    CachedGraphicsPipeline {
-                                        graphics_pipeline: pipeline,
-                                        renderpass_resource: renderpass.downgrade(),
-                                        material_pass_resource: material_pass.downgrade(),
-                                    },
-                                );
-                            }
-                        }
-                    }
+        Self::do_precache_pipelines_for_all_phases(inner);
+        Ok(())
+    }
+
+    fn do_precache_pipelines_for_all_phases(inner: &mut GraphicsPipelineCacheInner) {
+        let mut material_passes = FnvHashMap::default();
+        for phase in &inner.material_pass_assignments {
+            for (material_pass_hash, material_pass) in phase {
+                if let Some(material_pass) = material_pass.upgrade() {
+                    material_passes.insert(*material_pass_hash, material_pass);
                 }
             }
         }
-        */
+
+        for material_pass in material_passes.values() {
+            Self::precompile_pipelines_for_material_pass(inner, material_pass);
+        }
+    }
+
+    /// Returns every pipeline combination created this run, plus any loaded from a previous run
+    /// that was created in one of the last few runs
+    pub fn pipeline_record(&self) -> GraphicsPipelineRecord {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        #[cfg(debug_assertions)]
+        {
+            inner.lock_call_count += 1;
+        }
+
+        let mut entries: Vec<_> = inner.recorded_pipelines.values().cloned().collect();
+        entries.sort_by_key(|x| x.material_pass);
+        let mut record = GraphicsPipelineRecord { entries };
+        record.evict_unused_entries(PIPELINE_RECORD_MAX_UNUSED_RUNS);
+        record
+    }
+
+    /// Loads a record saved by a previous run. Pipelines in the record are created for material
+    /// passes that are already registered, and for other material passes when they are registered.
+    pub fn load_pipeline_record(
+        &self,
+        record: &GraphicsPipelineRecord,
+    ) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        #[cfg(debug_assertions)]
+        {
+            inner.lock_call_count += 1;
+        }
+
+        for entry in &record.entries {
+            let render_target_meta = entry.render_target_meta();
+            let vertex_data_set_layout = entry.vertex_data_set_layout();
+            let material_pass = ResourceId(entry.material_pass);
+
+            let key = CachedGraphicsPipelineKey {
+                material_pass,
+                render_target_meta_hash: render_target_meta.render_target_meta_hash(),
+                vertex_data_set_layout: vertex_data_set_layout.hash(),
+            };
+
+            if inner.recorded_pipelines.contains_key(&key) {
+                continue;
+            }
+
+            // Reset if the pipeline is created during this run
+            let mut entry = entry.clone();
+            entry.unused_run_count += 1;
+
+            inner.recorded_pipelines.insert(key, entry);
+            inner
+                .pipelines_to_precompile
+                .entry(material_pass)
+                .or_default()
+                .push(PipelineToPrecompile {
+                    render_target_meta,
+                    vertex_data_set_layout,
+                });
+        }
+
+        Self::do_precache_pipelines_for_all_phases(inner);
+    }
+
+    /// Writes the pipeline record and the driver's pipeline cache data to the given directory
+    pub fn save_to_disk(
+        &self,
+        directory: &Path,
+    ) -> RafxResult<()> {
+        std::fs::create_dir_all(directory)?;
+
+        let record = self.pipeline_record();
+        std::fs::write(directory.join(PIPELINE_RECORD_FILE_NAME), record.to_json())?;
+
+        let pipeline_cache_data = self
+            .resource_lookup_set()
+            .device_context()
+            .pipeline_cache_data()?;
+        if !pipeline_cache_data.is_empty() {
+            std::fs::write(
+                directory.join(PIPELINE_CACHE_DATA_FILE_NAME),
+                pipeline_cache_data,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads data written by save_to_disk() from the given directory. Missing files are not an
+    /// error. This should be called on startup before materials are loaded.
+    pub fn load_from_disk(
+        &self,
+        directory: &Path,
+    ) -> RafxResult<()> {
+        let pipeline_cache_data_path = directory.join(PIPELINE_CACHE_DATA_FILE_NAME);
+        if pipeline_cache_data_path.exists() {
+            let pipeline_cache_data = std::fs::read(pipeline_cache_data_path)?;
+            self.resource_lookup_set()
+                .device_context()
+                .merge_pipeline_cache_data(&pipeline_cache_data)?;
+        }
+
+        let record_path = directory.join(PIPELINE_RECORD_FILE_NAME);
+        if record_path.exists() {
+            let record = GraphicsPipelineRecord::from_json(&std::fs::read_to_string(record_path)?)?;
+            log::info!(
+                "Loaded {} graphics pipelines to precompile",
+                record.entries.len()
+            );
+            self.load_pipeline_record(&record);
+        }
 
         Ok(())
     }
 
+    fn resource_lookup_set(&self) -> ResourceLookupSet {
+        self.inner.lock().unwrap().resource_lookup_set.clone()
+    }
+
     fn drop_stale_pipelines(inner: &mut GraphicsPipelineCacheInner) {
         let current_frame_index = inner.current_frame_index;
 
@@ -596,6 +986,10 @@ impl GraphicsPipelineCache {
             phase.retain(|_k, v| v.upgrade().is_some());
         }
 
+        inner
+            .precompiled_pipelines
+            .retain(|_k, v| v.material_pass_resource.upgrade().is_some());
+
         //TODO: Could do something smarter than this to track when the last one is dropped
         let mut all_render_target_meta = FnvHashSet::default();
         for phase in &inner.render_target_meta_assignments {
@@ -628,5 +1022,53 @@ impl GraphicsPipelineCache {
         }
 
         guard.cached_pipelines.clear();
+        guard.precompiled_pipelines.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record_entry(
+        material_pass: u64,
+        unused_run_count: u32,
+    ) -> GraphicsPipelineRecordEntry {
+        GraphicsPipelineRecordEntry {
+            material_pass,
+            color_formats: vec![RafxFormat::R8G8B8A8_UNORM],
+            depth_stencil_format: None,
+            sample_count: RafxSampleCount::SampleCount1,
+            subpasses: vec![],
+            subpass_index: 0,
+            vertex_bindings: vec![],
+            primitive_topology: RafxPrimitiveTopology::TriangleList,
+            unused_run_count,
+        }
+    }
+
+    #[test]
+    fn test_evict_unused_pipeline_record_entries() {
+        let mut record = GraphicsPipelineRecord {
+            entries: vec![record_entry(1, 0), record_entry(2, 2), record_entry(3, 3)],
+        };
+
+        record.evict_unused_entries(2);
+        let material_passes: Vec<_> = record.entries.iter().map(|x| x.material_pass).collect();
+        assert_eq!(material_passes, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_pipeline_record_without_unused_run_count() {
+        // Records saved before unused runs were counted
+        let mut json = GraphicsPipelineRecord {
+            entries: vec![record_entry(1, 0)],
+        }
+        .to_json();
+        json = json.replace(",\n      \"unused_run_count\": 0", "");
+        assert!(!json.contains("unused_run_count"));
+
+        let record = GraphicsPipelineRecord::from_json(&json).unwrap();
+        assert_eq!(record.entries, vec![record_entry(1, 0)]);
     }
 }
@@ -1016,6 +1016,7 @@ impl ResourceLookupSet {
                         color_formats: &render_target_meta.color_formats(),
                         depth_stencil_format: render_target_meta.depth_stencil_format(),
                         sample_count: render_target_meta.sample_count(),
+                        subpass: render_target_meta.subpass(),
                     },
                 )?;
 
@@ -1,9 +1,10 @@
 use super::dyn_resources;
+use super::dyn_ring_buffer;
 use super::pipeline_cache;
 use super::resource_lookup;
 use crate::{
     DescriptorSetAllocatorProvider, DescriptorSetAllocatorRef, DynResourceAllocatorSet,
-    GraphicsPipelineCache, MAX_FRAMES_IN_FLIGHT,
+    DynRingBufferAllocator, GraphicsPipelineCache, MAX_FRAMES_IN_FLIGHT,
 };
 
 use crate::graph::RenderGraphCache;
@@ -24,6 +25,7 @@ use std::sync::Arc;
 #[derive(Debug)]
 pub struct ResourceManagerMetrics {
     pub dyn_resource_metrics: dyn_resources::ResourceMetrics,
+    pub dyn_ring_buffer_metrics: dyn_ring_buffer::DynRingBufferAllocatorMetrics,
     pub resource_metrics: resource_lookup::ResourceMetrics,
     pub graphics_pipeline_cache_metrics: pipeline_cache::GraphicsPipelineCacheMetrics,
 }
@@ -32,6 +34,7 @@ struct ResourceContextInner {
     descriptor_set_allocator_provider: DescriptorSetAllocatorProvider,
     dyn_resources_allocator_provider: DynResourceAllocatorSetProvider,
     dyn_command_pool_allocator: DynCommandPoolAllocator,
+    dyn_ring_buffer_allocator: DynRingBufferAllocator,
     resources: ResourceLookupSet,
     graphics_pipeline_cache: GraphicsPipelineCache,
     render_graph_cache: RenderGraphCache,
@@ -63,6 +66,10 @@ impl ResourceContext {
         self.inner.dyn_command_pool_allocator.clone()
     }
 
+    pub fn dyn_ring_buffer_allocator(&self) -> &DynRingBufferAllocator {
+        &self.inner.dyn_ring_buffer_allocator
+    }
+
     pub fn create_dyn_resource_allocator_set(&self) -> DynResourceAllocatorSet {
         self.inner.dyn_resources_allocator_provider.get_allocator()
     }
@@ -76,6 +83,7 @@ pub struct ResourceManager {
     render_registry: RenderRegistry,
     dyn_resource_allocators: DynResourceAllocatorSetManager,
     dyn_command_pool_allocator: DynCommandPoolAllocator,
+    dyn_ring_buffer_allocator: DynRingBufferAllocator,
     resources: ResourceLookupSet,
     render_graph_cache: RenderGraphCache,
     descriptor_set_allocator: DescriptorSetAllocatorManager,
@@ -92,6 +100,10 @@ impl ResourceManager {
         ResourceManager {
             render_registry: render_registry.clone(),
             dyn_command_pool_allocator: DynCommandPoolAllocator::new(MAX_FRAMES_IN_FLIGHT as u32),
+            dyn_ring_buffer_allocator: DynRingBufferAllocator::new(
+                &resources,
+                MAX_FRAMES_IN_FLIGHT as u32,
+            ),
             dyn_resource_allocators: DynResourceAllocatorSetManager::new(
                 device_context,
                 MAX_FRAMES_IN_FLIGHT as u32,
@@ -116,6 +128,7 @@ impl ResourceManager {
                 .dyn_resource_allocators
                 .create_allocator_provider(),
             dyn_command_pool_allocator: self.dyn_command_pool_allocator.clone(),
+            dyn_ring_buffer_allocator: self.dyn_ring_buffer_allocator.clone(),
             resources: self.resources.clone(),
             graphics_pipeline_cache: self.graphics_pipeline_cache.clone(),
             render_graph_cache: self.render_graph_cache.clone(),
@@ -138,6 +151,10 @@ impl ResourceManager {
         &self.dyn_command_pool_allocator
     }
 
+    pub fn dyn_ring_buffer_allocator(&self) -> &DynRingBufferAllocator {
+        &self.dyn_ring_buffer_allocator
+    }
+
     pub fn create_dyn_resource_allocator_set(&self) -> DynResourceAllocatorSet {
         self.dyn_resource_allocators.get_allocator()
     }
@@ -160,11 +177,13 @@ impl ResourceManager {
 
     pub fn metrics(&self) -> ResourceManagerMetrics {
         let dyn_resource_metrics = self.dyn_resource_allocators.metrics();
+        let dyn_ring_buffer_metrics = self.dyn_ring_buffer_allocator.metrics();
         let resource_metrics = self.resources.metrics();
         let graphics_pipeline_cache_metrics = self.graphics_pipeline_cache.metrics();
 
         ResourceManagerMetrics {
             dyn_resource_metrics,
+            dyn_ring_buffer_metrics,
             resource_metrics,
             graphics_pipeline_cache_metrics,
         }
@@ -176,6 +195,7 @@ impl ResourceManager {
         self.graphics_pipeline_cache.on_frame_complete();
         self.resources.on_frame_complete()?;
         self.dyn_command_pool_allocator.on_frame_complete()?;
+        self.dyn_ring_buffer_allocator.on_frame_complete();
         self.dyn_resource_allocators.on_frame_complete()?;
         self.descriptor_set_allocator.on_frame_complete();
         Ok(())
@@ -190,6 +210,7 @@ impl Drop for ResourceManager {
         // Wipe caches to ensure we don't keep anything alive
         self.render_graph_cache.clear();
         self.graphics_pipeline_cache.clear_all_pipelines();
+        self.dyn_ring_buffer_allocator.clear();
 
         // Drop all descriptors. These bind to raw resources, so we need to drop them before
         // dropping resources
@@ -0,0 +1,712 @@
+use crate::swapchain_render_resource::SwapchainRenderResourceInner;
+use crate::{
+    add_render_to_texture_view_node, add_render_window_view_node, RenderGraphGenerator,
+    RenderToTextureView, RenderWindowView, RendererConfigResource, SwapchainRenderResource,
+};
+use fnv::FnvHashMap;
+use rafx_api::{
+    RafxColorClearValue, RafxCommandBuffer, RafxDepthStencilClearValue, RafxPrimitiveTopology,
+    RafxResourceType, RafxSampleCount,
+};
+use rafx_assets::distill::loader::handle::Handle;
+use rafx_assets::{
+    AssetManager, RenderGraphAsset, RenderGraphAssetAttachment, RenderGraphAssetBinding,
+    RenderGraphAssetClearValue, RenderGraphAssetImageExtents, RenderGraphAssetImageFormat,
+    RenderGraphAssetNodeAction,
+};
+use rafx_framework::graph::{
+    PreparedRenderGraph, RenderGraphBufferConstraint, RenderGraphBufferUsageId, RenderGraphBuilder,
+    RenderGraphContext, RenderGraphImageConstraint, RenderGraphImageExtents,
+    RenderGraphImageSpecification, RenderGraphImageUsageId, RenderGraphImageViewOptions,
+    RenderGraphQueue, SwapchainSurfaceInfo,
+};
+use rafx_framework::render_features::{
+    ExtractResources, RenderJobCommandBufferContext, RenderPhaseIndex, RenderView,
+};
+use rafx_framework::{
+    DescriptorSetBindings, DescriptorSetLayoutResource, ImageViewResource, MaterialPass,
+    RafxResult, RenderResources, ResourceArc, SlotNameLookup, VertexDataSetLayout,
+};
+
+// A resource used by a node along with the descriptor set index and binding it is bound to
+type ResourceBinding<T> = (T, u32, u32);
+
+/// An image drawn by a `DrawViews` node, with one array layer per view
+pub struct RenderGraphAssetViewImage {
+    pub views: Vec<RenderView>,
+    // For example TEXTURE_CUBE for the six faces of a cube map
+    pub resource_type: RafxResourceType,
+}
+
+/// Supplies the views drawn by `DrawViews` nodes (e.g. shadow maps). Providers are registered on
+/// the `AssetRenderGraphGenerator` under the name the asset refers to them by.
+pub trait RenderGraphAssetViewProvider: 'static + Send {
+    /// The images to draw this frame
+    fn view_images(
+        &self,
+        render_resources: &RenderResources,
+    ) -> Vec<RenderGraphAssetViewImage>;
+
+    /// Called once the graph is prepared with the images that were drawn, in the order they were
+    /// returned by `view_images`. This is not called if the node drawing the views is disabled.
+    fn on_render_graph_prepared(
+        &self,
+        render_resources: &RenderResources,
+        prepared_render_graph: &PreparedRenderGraph,
+        images: &[RenderGraphImageUsageId],
+    );
+}
+
+/// Builds the render graph described by a `RenderGraphAsset`. The committed version of the asset
+/// is used every frame, so the graph follows the asset when it is hot-reloaded. Render-to-texture
+/// views draw the phases used by the asset's enabled `DrawPhase` nodes, in order, without the
+/// asset's other nodes.
+///
+/// Attachments that match the surface are rendered at `RendererConfigResource::render_scale`. When
+/// the scale is not 1.0 the image written to the swapchain must come from a node that samples the
+/// main view (i.e. a fullscreen pass) and whose attachment doesn't specify extents.
+pub struct AssetRenderGraphGenerator {
+    render_graph: Handle<RenderGraphAsset>,
+    view_providers: FnvHashMap<String, Box<dyn RenderGraphAssetViewProvider>>,
+}
+
+impl AssetRenderGraphGenerator {
+    pub fn new(render_graph: Handle<RenderGraphAsset>) -> Self {
+        AssetRenderGraphGenerator {
+            render_graph,
+            view_providers: Default::default(),
+        }
+    }
+
+    /// Registers the provider of the views drawn by `DrawViews` nodes that refer to `name`
+    pub fn add_view_provider<T: RenderGraphAssetViewProvider>(
+        mut self,
+        name: impl Into<String>,
+        view_provider: T,
+    ) -> Self {
+        self.view_providers
+            .insert(name.into(), Box::new(view_provider));
+        self
+    }
+}
+
+fn image_constraint(
+    attachment: &RenderGraphAssetAttachment,
+    swapchain_info: &SwapchainRenderResourceInner,
+    surface_info: &SwapchainSurfaceInfo,
+) -> RenderGraphImageConstraint {
+    let format = attachment.format.map(|format| match format {
+        RenderGraphAssetImageFormat::Swapchain => swapchain_info.swapchain_surface_info.format,
+        RenderGraphAssetImageFormat::DefaultColorSdr => swapchain_info.default_color_format_sdr,
+        RenderGraphAssetImageFormat::DefaultColorHdr => swapchain_info.default_color_format_hdr,
+        RenderGraphAssetImageFormat::DefaultDepth => swapchain_info.default_depth_format,
+        RenderGraphAssetImageFormat::Explicit(format) => format,
+    });
+
+    let extents = attachment.extents.map(|extents| match extents {
+        RenderGraphAssetImageExtents::MatchSurface => RenderGraphImageExtents::MatchSurface,
+        RenderGraphAssetImageExtents::ScaledSurface(scale) => {
+            let surface_extents = surface_info.extents;
+            RenderGraphImageExtents::Custom(
+                ((surface_extents.width as f32 * scale) as u32).max(1),
+                ((surface_extents.height as f32 * scale) as u32).max(1),
+                1,
+            )
+        }
+        RenderGraphAssetImageExtents::Custom(width, height, depth) => {
+            RenderGraphImageExtents::Custom(width, height, depth)
+        }
+    });
+
+    RenderGraphImageConstraint {
+        samples: attachment.samples,
+        format,
+        extents,
+        ..Default::default()
+    }
+}
+
+fn depth_clear_value(
+    attachment: &RenderGraphAssetAttachment
+) -> RafxResult<Option<RafxDepthStencilClearValue>> {
+    match attachment.clear_value {
+        Some(RenderGraphAssetClearValue::DepthStencil(depth, stencil)) => {
+            Ok(Some(RafxDepthStencilClearValue { depth, stencil }))
+        }
+        Some(RenderGraphAssetClearValue::Color(..)) => Err(format!(
+            "Depth attachment {} has a color clear value",
+            attachment.name
+        ))?,
+        None => Ok(None),
+    }
+}
+
+fn find_resource<'a, T: Copy>(
+    resources: &FnvHashMap<&'a str, T>,
+    node_name: &str,
+    name: &str,
+) -> RafxResult<T> {
+    resources.get(name).copied().ok_or_else(|| {
+        format!(
+            "Render graph node {} refers to {} which is not written by an earlier node",
+            node_name, name
+        )
+        .into()
+    })
+}
+
+// Adds a node per view that draws the phase into the view's layer of its image. Returns the final
+// usage of each image.
+fn add_view_image_nodes(
+    graph: &mut RenderGraphBuilder,
+    node_name: &'static str,
+    image_name: &'static str,
+    phase_index: RenderPhaseIndex,
+    clear_value: Option<RafxDepthStencilClearValue>,
+    constraint: RenderGraphImageConstraint,
+    view_images: Vec<RenderGraphAssetViewImage>,
+) -> Vec<RenderGraphImageUsageId> {
+    let mut drawn_images = Vec::with_capacity(view_images.len());
+    for view_image in view_images {
+        let first_view = match view_image.views.first() {
+            Some(first_view) => first_view,
+            None => continue,
+        };
+
+        let layer_count = view_image.views.len() as u32;
+        let mut resource_type = view_image.resource_type;
+        if layer_count > 1 {
+            resource_type |= RafxResourceType::RENDER_TARGET_ARRAY_SLICES;
+        }
+
+        let create_node = graph.add_node(node_name, RenderGraphQueue::DefaultGraphics);
+        let mut image = graph.create_unattached_image(
+            create_node,
+            RenderGraphImageConstraint {
+                extents: Some(RenderGraphImageExtents::Custom(
+                    first_view.extents_width(),
+                    first_view.extents_height(),
+                    1,
+                )),
+                layer_count: Some(layer_count),
+                resource_type,
+                ..constraint.clone()
+            },
+            Default::default(),
+        );
+
+        for (layer, view) in view_image.views.into_iter().enumerate() {
+            let node = graph.add_node(node_name, RenderGraphQueue::DefaultGraphics);
+            image = graph.modify_depth_attachment(
+                node,
+                image,
+                clear_value,
+                Default::default(),
+                RenderGraphImageViewOptions::array_slice(layer as u16),
+            );
+            graph.set_image_name(image, image_name);
+            graph.add_render_phase_dependency_by_index(node, phase_index);
+
+            graph.set_renderpass_callback(node, move |args| {
+                let mut write_context =
+                    RenderJobCommandBufferContext::from_graph_visit_render_pass_args(&args);
+                args.graph_context
+                    .prepared_render_data()
+                    .write_view_phase_by_index(phase_index, &view, &mut write_context)
+            });
+        }
+
+        drawn_images.push(image);
+    }
+
+    drawn_images
+}
+
+// Resolves the binding of a resource to (set index, binding index) pairs. Slot names may be bound
+// in more than one place.
+fn resolve_binding(
+    binding: &RenderGraphAssetBinding,
+    slot_name_lookup: Option<&SlotNameLookup>,
+) -> RafxResult<Vec<(u32, u32)>> {
+    match binding {
+        RenderGraphAssetBinding::Binding(set_index, binding_index) => {
+            Ok(vec![(*set_index, *binding_index)])
+        }
+        RenderGraphAssetBinding::Slot(slot_name) => {
+            let locations = slot_name_lookup
+                .and_then(|lookup| lookup.get(slot_name))
+                .ok_or_else(|| format!("Material has no slot named {}", slot_name))?;
+
+            Ok(locations
+                .iter()
+                .map(|location| (location.layout_index, location.binding_index))
+                .collect())
+        }
+    }
+}
+
+// Creates descriptor sets pointing at the node's resources and binds them
+fn bind_resources(
+    graph_context: &RenderGraphContext,
+    command_buffer: &RafxCommandBuffer,
+    descriptor_set_layouts: &[ResourceArc<DescriptorSetLayoutResource>],
+    images: &[ResourceBinding<RenderGraphImageUsageId>],
+    buffers: &[ResourceBinding<RenderGraphBufferUsageId>],
+) -> RafxResult<()> {
+    let mut descriptor_set_allocator = graph_context
+        .resource_context()
+        .create_descriptor_set_allocator();
+
+    let mut descriptor_sets = Vec::default();
+    for (set_index, descriptor_set_layout) in descriptor_set_layouts.iter().enumerate() {
+        let set_index = set_index as u32;
+        let set_images: Vec<_> = images.iter().filter(|x| x.1 == set_index).collect();
+        let set_buffers: Vec<_> = buffers.iter().filter(|x| x.1 == set_index).collect();
+        if set_images.is_empty() && set_buffers.is_empty() {
+            continue;
+        }
+
+        let mut descriptor_set = descriptor_set_allocator
+            .create_dyn_descriptor_set_uninitialized(descriptor_set_layout)?;
+
+        for (image, _, binding_index) in set_images {
+            descriptor_set.set_image(*binding_index, &graph_context.image_view(*image).unwrap());
+        }
+
+        for (buffer, _, binding_index) in set_buffers {
+            descriptor_set.set_buffer(*binding_index, &graph_context.buffer(*buffer).unwrap());
+        }
+
+        descriptor_set.flush(&mut descriptor_set_allocator)?;
+        descriptor_sets.push(descriptor_set);
+    }
+
+    // Explicit flush since we're going to use the descriptors immediately
+    descriptor_set_allocator.flush_changes()?;
+
+    for descriptor_set in &descriptor_sets {
+        descriptor_set.bind(command_buffer)?;
+    }
+
+    Ok(())
+}
+
+impl RenderGraphGenerator for AssetRenderGraphGenerator {
+    fn generate_render_graph(
+        &self,
+        asset_manager: &AssetManager,
+        swapchain_image: ResourceArc<ImageViewResource>,
+        main_view: RenderView,
+        render_to_texture_views: &[RenderToTextureView],
+        window_views: &[RenderWindowView],
+        extract_resources: &ExtractResources,
+        render_resources: &RenderResources,
+    ) -> RafxResult<PreparedRenderGraph> {
+        profiling::scope!("Build Render Graph");
+
+        let device_context = asset_manager.device_context();
+        let resource_context = asset_manager.resource_manager().resource_context();
+        let render_registry = asset_manager.resource_manager().render_registry();
+        let swapchain_render_resource = render_resources.fetch::<SwapchainRenderResource>();
+        let swapchain_info = swapchain_render_resource.get();
+
+        // Images that match the surface are rendered at the render scale
+        let renderer_config = extract_resources
+            .try_fetch::<RendererConfigResource>()
+            .map(|x| *x)
+            .unwrap_or_default();
+        let surface_info =
+            renderer_config.scaled_surface_info(&swapchain_info.swapchain_surface_info);
+
+        let render_graph = asset_manager
+            .committed_asset(&self.render_graph)
+            .ok_or("The render graph asset is not loaded")?;
+
+        let mut graph = RenderGraphBuilder::default();
+        let mut images = FnvHashMap::<&str, RenderGraphImageUsageId>::default();
+        let mut buffers = FnvHashMap::<&str, RenderGraphBufferUsageId>::default();
+
+        // The images drawn by each DrawViews node, by the name of its depth attachment
+        let mut view_images = FnvHashMap::<&str, Vec<RenderGraphImageUsageId>>::default();
+        let mut drawn_view_images = Vec::default();
+
+        for node_data in &render_graph.data.nodes {
+            //
+            // Nodes that draw provided views add a node per view
+            //
+            if let RenderGraphAssetNodeAction::DrawViews { phase, views } = &node_data.action {
+                // Validated when the asset is loaded
+                let attachment = node_data.depth_attachment.as_ref().unwrap();
+                let mut drawn_images = Vec::default();
+
+                if node_data.enabled {
+                    let view_provider = self.view_providers.get(views).ok_or_else(|| {
+                        format!(
+                            "Render graph node {} draws views from unregistered provider {}",
+                            node_data.name, views
+                        )
+                    })?;
+
+                    let phase_index = render_registry
+                        .render_phase_index_from_name(phase)
+                        .ok_or_else(|| format!("Render phase {} is not registered", phase))?;
+
+                    drawn_images = add_view_image_nodes(
+                        &mut graph,
+                        render_graph.name(&node_data.name).unwrap(),
+                        render_graph.name(&attachment.name).unwrap(),
+                        phase_index,
+                        depth_clear_value(attachment)?,
+                        image_constraint(attachment, swapchain_info, &surface_info),
+                        view_provider.view_images(render_resources),
+                    );
+                    drawn_view_images.push((view_provider, drawn_images.clone()));
+                }
+
+                view_images.insert(&attachment.name, drawn_images);
+                continue;
+            }
+
+            //
+            // Disabled nodes pass their inputs (or fallbacks) through under the names of the
+            // resources they would have written
+            //
+            if !node_data.enabled {
+                let attachments = node_data
+                    .color_attachments
+                    .iter()
+                    .chain(node_data.depth_attachment.iter());
+
+                for attachment in attachments {
+                    let passthrough = attachment.input.as_ref().or(attachment.fallback.as_ref());
+                    if let Some(passthrough) = passthrough {
+                        let image = find_resource(&images, &node_data.name, passthrough)?;
+                        images.insert(&attachment.name, image);
+                    }
+                }
+
+                for storage_buffer in &node_data.storage_buffers {
+                    if let Some(input) = &storage_buffer.input {
+                        let buffer = find_resource(&buffers, &node_data.name, input)?;
+                        buffers.insert(&storage_buffer.name, buffer);
+                    }
+                }
+
+                continue;
+            }
+
+            let node = graph.add_node(
+                render_graph.name(&node_data.name).unwrap(),
+                RenderGraphQueue::DefaultGraphics,
+            );
+
+            // Material passes resolve slot names to bindings, so look up the material first
+            let material_pass = match &node_data.action {
+                RenderGraphAssetNodeAction::FullscreenMaterial { material, phase } => {
+                    let phase_index = render_registry
+                        .render_phase_index_from_name(phase)
+                        .ok_or_else(|| format!("Render phase {} is not registered", phase))?;
+                    let material = asset_manager
+                        .committed_asset(material)
+                        .ok_or("A material used by the render graph is not loaded")?;
+                    let pass_index = material.find_pass_by_phase_index(phase_index).ok_or_else(
+                        || {
+                            format!(
+                                "Material used by render graph node {} has no pass for phase {}",
+                                node_data.name, phase
+                            )
+                        },
+                    )?;
+
+                    Some((phase_index, material.passes[pass_index].clone()))
+                }
+                _ => None,
+            };
+
+            let slot_name_lookup = material_pass
+                .as_ref()
+                .map(|(_, material_pass)| &*material_pass.pass_slot_name_lookup);
+
+            //
+            // Sampled images and storage buffers
+            //
+            let mut image_bindings = Vec::default();
+            for sampled_image in &node_data.sampled_images {
+                // Images drawn for views are only depended on, never bound
+                if let Some(drawn_images) = view_images.get(sampled_image.image.as_str()) {
+                    for &image in drawn_images {
+                        graph.sample_image(node, image, Default::default(), Default::default());
+                    }
+                    continue;
+                }
+
+                let image = find_resource(&images, &node_data.name, &sampled_image.image)?;
+                let usage = graph.sample_image(
+                    node,
+                    image,
+                    RenderGraphImageConstraint {
+                        samples: Some(RafxSampleCount::SampleCount1),
+                        ..Default::default()
+                    },
+                    Default::default(),
+                );
+
+                if let Some(binding) = &sampled_image.binding {
+                    for (set_index, binding_index) in resolve_binding(binding, slot_name_lookup)? {
+                        image_bindings.push((usage, set_index, binding_index));
+                    }
+                }
+            }
+
+            let mut buffer_bindings = Vec::default();
+            for storage_buffer in &node_data.storage_buffers {
+                let constraint = RenderGraphBufferConstraint {
+                    size: storage_buffer.size,
+                    ..Default::default()
+                };
+
+                let usage = if let Some(input) = &storage_buffer.input {
+                    let buffer = find_resource(&buffers, &node_data.name, input)?;
+                    graph.modify_storage_buffer(node, buffer, constraint)
+                } else {
+                    graph.create_storage_buffer(node, constraint)
+                };
+
+                graph.set_buffer_name(usage, render_graph.name(&storage_buffer.name).unwrap());
+                buffers.insert(&storage_buffer.name, usage);
+
+                for (set_index, binding_index) in
+                    resolve_binding(&storage_buffer.binding, slot_name_lookup)?
+                {
+                    buffer_bindings.push((usage, set_index, binding_index));
+                }
+            }
+
+            //
+            // Attachments
+            //
+            for (color_attachment_index, attachment) in
+                node_data.color_attachments.iter().enumerate()
+            {
+                let clear_value = match attachment.clear_value {
+                    Some(RenderGraphAssetClearValue::Color(color)) => {
+                        Some(RafxColorClearValue(color))
+                    }
+                    Some(RenderGraphAssetClearValue::DepthStencil(..)) => Err(format!(
+                        "Color attachment {} has a depth/stencil clear value",
+                        attachment.name
+                    ))?,
+                    None => None,
+                };
+
+                let constraint = image_constraint(attachment, swapchain_info, &surface_info);
+                let image = if let Some(input) = &attachment.input {
+                    let input = find_resource(&images, &node_data.name, input)?;
+                    graph.modify_color_attachment(
+                        node,
+                        input,
+                        color_attachment_index,
+                        clear_value,
+                        constraint,
+                        Default::default(),
+                    )
+                } else {
+                    graph.create_color_attachment(
+                        node,
+                        color_attachment_index,
+                        clear_value,
+                        constraint,
+                        Default::default(),
+                    )
+                };
+
+                graph.set_image_name(image, render_graph.name(&attachment.name).unwrap());
+                images.insert(&attachment.name, image);
+            }
+
+            if let Some(attachment) = &node_data.depth_attachment {
+                let clear_value = depth_clear_value(attachment)?;
+
+                let constraint = image_constraint(attachment, swapchain_info, &surface_info);
+                let image = if let Some(input) = &attachment.input {
+                    let input = find_resource(&images, &node_data.name, input)?;
+                    graph.modify_depth_attachment(
+                        node,
+                        input,
+                        clear_value,
+                        constraint,
+                        Default::default(),
+                    )
+                } else {
+                    graph.create_depth_attachment(node, clear_value, constraint, Default::default())
+                };
+
+                graph.set_image_name(image, render_graph.name(&attachment.name).unwrap());
+                images.insert(&attachment.name, image);
+            }
+
+            //
+            // Callbacks
+            //
+            match &node_data.action {
+                RenderGraphAssetNodeAction::DrawPhase(phase) => {
+                    let phase_index = render_registry
+                        .render_phase_index_from_name(phase)
+                        .ok_or_else(|| format!("Render phase {} is not registered", phase))?;
+                    graph.add_render_phase_dependency_by_index(node, phase_index);
+
+                    let main_view = main_view.clone();
+                    graph.set_renderpass_callback(node, move |args| {
+                        let mut write_context =
+                            RenderJobCommandBufferContext::from_graph_visit_render_pass_args(&args);
+                        args.graph_context
+                            .prepared_render_data()
+                            .write_view_phase_by_index(phase_index, &main_view, &mut write_context)
+                    });
+                }
+                RenderGraphAssetNodeAction::DrawViews { .. } => unreachable!(),
+                RenderGraphAssetNodeAction::FullscreenMaterial { .. } => {
+                    let (phase_index, material_pass): (_, MaterialPass) = material_pass.unwrap();
+                    let vertex_layout =
+                        VertexDataSetLayout::new(vec![], RafxPrimitiveTopology::TriangleList);
+
+                    graph.set_renderpass_callback(node, move |args| {
+                        let pipeline = args
+                            .graph_context
+                            .resource_context()
+                            .graphics_pipeline_cache()
+                            .get_or_create_graphics_pipeline(
+                                phase_index,
+                                &material_pass.material_pass_resource,
+                                &args.render_target_meta,
+                                &vertex_layout,
+                            )?;
+
+                        let command_buffer = &args.command_buffer;
+                        command_buffer.cmd_bind_pipeline(&*pipeline.get_raw().pipeline)?;
+                        bind_resources(
+                            &args.graph_context,
+                            command_buffer,
+                            &pipeline.get_raw().descriptor_set_layouts,
+                            &image_bindings,
+                            &buffer_bindings,
+                        )?;
+                        command_buffer.cmd_draw(3, 0)
+                    });
+                }
+                RenderGraphAssetNodeAction::Compute {
+                    pipeline,
+                    group_count,
+                } => {
+                    let compute_pipeline = asset_manager
+                        .committed_asset(pipeline)
+                        .ok_or("A compute pipeline used by the render graph is not loaded")?
+                        .compute_pipeline
+                        .clone();
+                    let group_count = *group_count;
+
+                    graph.set_compute_callback(node, move |args| {
+                        let command_buffer = &args.command_buffer;
+                        command_buffer.cmd_bind_pipeline(&*compute_pipeline.get_raw().pipeline)?;
+                        bind_resources(
+                            &args.graph_context,
+                            command_buffer,
+                            &compute_pipeline.get_raw().descriptor_set_layouts,
+                            &image_bindings,
+                            &buffer_bindings,
+                        )?;
+                        command_buffer.cmd_dispatch(group_count[0], group_count[1], group_count[2])
+                    });
+                }
+            }
+        }
+
+        let output_image = images
+            .get(render_graph.data.output_image.as_str())
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Render graph output image {} is not written by any node",
+                    render_graph.data.output_image
+                )
+            })?;
+
+        if !render_to_texture_views.is_empty() || !window_views.is_empty() {
+            let mut render_phases = Vec::default();
+            for node_data in &render_graph.data.nodes {
+                if let RenderGraphAssetNodeAction::DrawPhase(phase) = &node_data.action {
+                    if !node_data.enabled {
+                        continue;
+                    }
+
+                    let phase_index = render_registry
+                        .render_phase_index_from_name(phase)
+                        .ok_or_else(|| format!("Render phase {} is not registered", phase))?;
+                    if !render_phases.contains(&phase_index) {
+                        render_phases.push(phase_index);
+                    }
+                }
+            }
+
+            let mut secondary_view_nodes = Vec::default();
+            for render_to_texture_view in render_to_texture_views {
+                secondary_view_nodes.push(add_render_to_texture_view_node(
+                    &mut graph,
+                    render_to_texture_view,
+                    &render_phases,
+                    swapchain_info.default_depth_format,
+                    RafxColorClearValue([0.0, 0.0, 0.0, 1.0]),
+                ));
+            }
+
+            for window_view in window_views {
+                secondary_view_nodes.push(add_render_window_view_node(
+                    &mut graph,
+                    window_view,
+                    &render_phases,
+                    swapchain_info.default_depth_format,
+                    RafxColorClearValue([0.0, 0.0, 0.0, 1.0]),
+                ));
+            }
+
+            // The phases drawn for these views may sample the images drawn by DrawViews nodes (e.g.
+            // shadow maps), so those images must be drawn first
+            for &node in &secondary_view_nodes {
+                for drawn_images in view_images.values() {
+                    for &image in drawn_images {
+                        graph.sample_image(node, image, Default::default(), Default::default());
+                    }
+                }
+            }
+        }
+
+        graph.set_output_image(
+            output_image,
+            swapchain_image,
+            RenderGraphImageSpecification {
+                samples: RafxSampleCount::SampleCount1,
+                format: swapchain_info.swapchain_surface_info.format,
+                resource_type: RafxResourceType::TEXTURE | RafxResourceType::RENDER_TARGET_COLOR,
+                extents: renderer_config
+                    .swapchain_output_extents(&swapchain_info.swapchain_surface_info),
+                layer_count: 1,
+                mip_count: 1,
+            },
+            Default::default(),
+            swapchain_info.output_image_state,
+        );
+
+        let prepared_render_graph =
+            PreparedRenderGraph::new(&device_context, &resource_context, graph, &surface_info)?;
+
+        for (view_provider, drawn_images) in drawn_view_images {
+            view_provider.on_render_graph_prepared(
+                render_resources,
+                &prepared_render_graph,
+                &drawn_images,
+            );
+        }
+
+        Ok(prepared_render_graph)
+    }
+}
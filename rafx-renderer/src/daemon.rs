@@ -1,6 +1,6 @@
 use rafx_assets::distill::loader::storage::DefaultIndirectionResolver;
 use rafx_assets::distill::loader::{Loader, PackfileReader, RpcIO};
-use rafx_assets::distill_impl::AssetResource;
+use rafx_assets::distill_impl::{AssetResource, DirectAssetSource};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
@@ -27,6 +27,13 @@ pub fn init_distill_daemon(connect_string: String) -> AssetResource {
     AssetResource::new(loader, resolver)
 }
 
+// Imports and loads assets in-process, without a daemon
+pub fn init_distill_direct(asset_source: DirectAssetSource) -> AssetResource {
+    let loader = Loader::new(Box::new(asset_source));
+    let resolver = Box::new(DefaultIndirectionResolver);
+    AssetResource::new(loader, resolver)
+}
+
 pub fn init_distill_packfile(pack_file: &std::path::Path) -> AssetResource {
     let packfile = std::fs::File::open(pack_file).unwrap();
     let packfile_loader = PackfileReader::new(packfile).unwrap();
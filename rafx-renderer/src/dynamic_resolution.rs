@@ -0,0 +1,105 @@
+use super::{MAX_RENDER_SCALE, MIN_RENDER_SCALE};
+
+/// Picks a render scale (see `RendererConfigResource::render_scale`) that holds a target framerate.
+/// Call `update` once per frame with the previous frame's duration and write the result to the
+/// `RendererConfigResource`. The duration should not include time spent waiting for the frame to
+/// be presented (see `Renderer::previous_frame_wait_time`). With vsync enabled, frames are paced
+/// to the display, so the full frame time never drops below the refresh interval and the scale
+/// would never be raised.
+///
+/// The cost of a frame is assumed to scale with the number of pixels rendered, so the scale moves
+/// towards `scale * sqrt(target_frame_time / frame_time)`. Frame times are smoothed and the scale is
+/// quantized to `scale_step` so that the render graph's images are not recreated every frame.
+#[derive(Clone, Debug)]
+pub struct DynamicResolutionController {
+    pub target_frame_time: f32,
+    pub min_render_scale: f32,
+    pub max_render_scale: f32,
+    // Weight of the most recent frame time in the smoothed frame time
+    pub frame_time_smoothing: f32,
+    // Fraction of the distance to the ideal scale covered each frame
+    pub adjust_rate: f32,
+    pub scale_step: f32,
+    smoothed_frame_time: Option<f32>,
+    ideal_render_scale: f32,
+    render_scale: f32,
+}
+
+impl DynamicResolutionController {
+    pub fn new(target_framerate: f32) -> Self {
+        DynamicResolutionController {
+            target_frame_time: 1.0 / target_framerate.max(1.0),
+            min_render_scale: 0.5,
+            max_render_scale: 1.0,
+            frame_time_smoothing: 0.1,
+            adjust_rate: 0.1,
+            scale_step: 0.05,
+            smoothed_frame_time: None,
+            ideal_render_scale: 1.0,
+            render_scale: 1.0,
+        }
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    pub fn smoothed_frame_time(&self) -> Option<f32> {
+        self.smoothed_frame_time
+    }
+
+    /// Restarts from the given scale, i.e. when the controller is enabled after the scale was set
+    /// manually
+    pub fn reset(
+        &mut self,
+        render_scale: f32,
+    ) {
+        self.smoothed_frame_time = None;
+        self.ideal_render_scale = self.clamp_render_scale(render_scale);
+        self.render_scale = self.ideal_render_scale;
+    }
+
+    /// Returns the render scale to use for the next frame
+    pub fn update(
+        &mut self,
+        frame_time: f32,
+    ) -> f32 {
+        // Ignore hitches (i.e. loading or a paused debugger) and invalid measurements
+        if !frame_time.is_finite() || frame_time <= 0.0 || frame_time > 1.0 {
+            return self.render_scale;
+        }
+
+        let smoothed_frame_time = match self.smoothed_frame_time {
+            Some(smoothed_frame_time) => {
+                smoothed_frame_time + (frame_time - smoothed_frame_time) * self.frame_time_smoothing
+            }
+            None => frame_time,
+        };
+        self.smoothed_frame_time = Some(smoothed_frame_time);
+
+        let desired_render_scale = self.clamp_render_scale(
+            self.render_scale * (self.target_frame_time / smoothed_frame_time).sqrt(),
+        );
+        self.ideal_render_scale +=
+            (desired_render_scale - self.ideal_render_scale) * self.adjust_rate;
+        self.ideal_render_scale = self.clamp_render_scale(self.ideal_render_scale);
+
+        let render_scale = if self.scale_step > 0.0 {
+            (self.ideal_render_scale / self.scale_step).round() * self.scale_step
+        } else {
+            self.ideal_render_scale
+        };
+        self.render_scale = self.clamp_render_scale(render_scale);
+
+        self.render_scale
+    }
+
+    fn clamp_render_scale(
+        &self,
+        render_scale: f32,
+    ) -> f32 {
+        let min_render_scale = self.min_render_scale.max(MIN_RENDER_SCALE);
+        let max_render_scale = self.max_render_scale.min(MAX_RENDER_SCALE);
+        render_scale.max(min_render_scale).min(max_render_scale)
+    }
+}
@@ -0,0 +1,440 @@
+use super::{Renderer, SwapchainRenderResource};
+use rafx_api::{
+    RafxBuffer, RafxBufferDef, RafxCmdCopyTextureToBufferParams, RafxCommandBuffer,
+    RafxCommandBufferDef, RafxCommandPool, RafxCommandPoolDef, RafxDeviceContext, RafxError,
+    RafxExtents2D, RafxExtents3D, RafxFence, RafxFormat, RafxMemoryUsage, RafxQueue,
+    RafxResourceState, RafxResourceType, RafxResult, RafxTexture, RafxTextureDef,
+};
+use rafx_framework::graph::SwapchainSurfaceInfo;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// The number of frames that may be in flight at once, the same as the swapchain helper allows
+const HEADLESS_FRAME_COUNT: usize = 2;
+
+/// Describes the offscreen texture that a `HeadlessRenderTarget` renders into
+#[derive(Debug, Clone)]
+pub struct HeadlessRenderTargetDef {
+    pub extents: RafxExtents2D,
+    /// An sRGB format (i.e. R8G8B8A8_SRGB) matches what a swapchain would normally use
+    pub format: RafxFormat,
+    /// If true, every finished frame is copied back to the CPU and delivered in
+    /// `HeadlessFrame::pixels`. This is not supported on GL ES.
+    pub read_back_pixels: bool,
+}
+
+impl HeadlessRenderTargetDef {
+    /// The state render graphs leave the texture in: COPY_SRC if the pixels are read back,
+    /// otherwise SHADER_RESOURCE so the texture can be sampled
+    pub fn output_image_state(&self) -> RafxResourceState {
+        if self.read_back_pixels {
+            RafxResourceState::COPY_SRC
+        } else {
+            RafxResourceState::SHADER_RESOURCE
+        }
+    }
+}
+
+/// A frame that the GPU has finished rendering into a `HeadlessRenderTarget`
+pub struct HeadlessFrame {
+    pub frame_index: u64,
+    pub extents: RafxExtents2D,
+    pub format: RafxFormat,
+    /// The texture the frame was rendered into, in `HeadlessRenderTargetDef::output_image_state`.
+    /// Textures are reused by later frames, so the contents are only guaranteed to be this frame's
+    /// while the frame callback is running
+    pub texture: RafxTexture,
+    /// Tightly packed rows of pixels in `format`, if `read_back_pixels` is enabled
+    pub pixels: Option<Vec<u8>>,
+}
+
+pub type HeadlessFrameCallback = Box<dyn FnMut(HeadlessFrame) + Send>;
+
+struct HeadlessFrameSlot {
+    texture: RafxTexture,
+    readback_buffer: Option<RafxBuffer>,
+    fence: RafxFence,
+    command_pool: RafxCommandPool,
+    command_buffer: RafxCommandBuffer,
+    // The frame that was last submitted using this slot, if it has not been delivered yet
+    submitted_frame_index: Option<u64>,
+}
+
+struct HeadlessRenderTargetInner {
+    def: HeadlessRenderTargetDef,
+    slots: Vec<HeadlessFrameSlot>,
+    next_frame_index: u64,
+    // Errors from presenting a frame are returned the next time a frame is acquired, the same way
+    // the swapchain helper reports them
+    pending_error: Option<RafxError>,
+    completed_frames: VecDeque<HeadlessFrame>,
+}
+
+impl HeadlessRenderTargetInner {
+    fn submit_frame(
+        &mut self,
+        queue: &RafxQueue,
+        slot_index: usize,
+        frame_index: u64,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<()> {
+        let slot = &mut self.slots[slot_index];
+        if let Some(readback_buffer) = &slot.readback_buffer {
+            slot.command_pool.reset_command_pool()?;
+            slot.command_buffer.begin()?;
+
+            // The render graph leaves the output image in COPY_SRC when pixels are read back (see
+            // HeadlessRenderTargetDef::output_image_state)
+            slot.command_buffer.cmd_copy_texture_to_buffer(
+                &slot.texture,
+                readback_buffer,
+                &RafxCmdCopyTextureToBufferParams::default(),
+            )?;
+
+            slot.command_buffer.end()?;
+
+            let mut all_command_buffers = command_buffers.to_vec();
+            all_command_buffers.push(&slot.command_buffer);
+            queue.submit(&all_command_buffers, &[], &[], Some(&slot.fence))?;
+        } else {
+            queue.submit(command_buffers, &[], &[], Some(&slot.fence))?;
+        }
+
+        slot.submitted_frame_index = Some(frame_index);
+        Ok(())
+    }
+
+    // Blocks until the frame submitted with the given slot finishes and returns it
+    fn finish_frame(
+        &mut self,
+        slot_index: usize,
+    ) -> RafxResult<Option<HeadlessFrame>> {
+        let slot = &mut self.slots[slot_index];
+        let frame_index = match slot.submitted_frame_index.take() {
+            Some(frame_index) => frame_index,
+            None => return Ok(None),
+        };
+
+        slot.fence.wait()?;
+
+        let pixels = if let Some(readback_buffer) = &slot.readback_buffer {
+            let size = readback_buffer.buffer_def().size as usize;
+            let mapped_memory = readback_buffer.map_buffer()?;
+            let pixels = unsafe { std::slice::from_raw_parts(mapped_memory, size) }.to_vec();
+            readback_buffer.unmap_buffer()?;
+            Some(pixels)
+        } else {
+            None
+        };
+
+        Ok(Some(HeadlessFrame {
+            frame_index,
+            extents: self.def.extents,
+            format: self.def.format,
+            texture: slot.texture.clone(),
+            pixels,
+        }))
+    }
+
+    // Returns the finished frames, oldest first. If an error occurs, the frames finished before it
+    // are still returned
+    fn finish_all_frames(&mut self) -> (Vec<HeadlessFrame>, RafxResult<()>) {
+        // The slot that the next frame will use holds the oldest frame
+        let slot_count = self.slots.len() as u64;
+        let mut frames = Vec::default();
+        for i in 0..slot_count {
+            let slot_index = ((self.next_frame_index + i) % slot_count) as usize;
+            match self.finish_frame(slot_index) {
+                Ok(frame) => frames.extend(frame),
+                Err(error) => return (frames, Err(error)),
+            }
+        }
+
+        (frames, Ok(()))
+    }
+}
+
+impl Drop for HeadlessRenderTargetInner {
+    fn drop(&mut self) {
+        // Don't free the textures while the GPU may still be using them
+        for slot in &self.slots {
+            if slot.submitted_frame_index.is_some() {
+                let _ = slot.fence.wait();
+            }
+        }
+    }
+}
+
+/// Renders frames into offscreen textures instead of a swapchain, for use without a window (i.e.
+/// thumbnail generation, image comparison tests, or video capture). Insert it into the
+/// `ExtractResources` passed to `Renderer::start_rendering_next_frame` in place of a
+/// `RafxSwapchainHelper`.
+///
+/// Finished frames are passed to the frame callback if one is set, otherwise they are queued and
+/// can be retrieved with `take_completed_frames`. A frame is delivered once the GPU finishes it,
+/// which is usually a couple of frames after it was started. Call `flush` to deliver all frames
+/// that have been started.
+pub struct HeadlessRenderTarget {
+    inner: Arc<Mutex<HeadlessRenderTargetInner>>,
+    // Kept separate from inner so that the callback is not called with inner locked
+    frame_callback: Mutex<Option<HeadlessFrameCallback>>,
+}
+
+impl HeadlessRenderTarget {
+    pub fn new(
+        renderer: &Renderer,
+        def: &HeadlessRenderTargetDef,
+    ) -> RafxResult<Self> {
+        let device_context = renderer.graphics_queue().device_context();
+
+        if def.extents.width == 0 || def.extents.height == 0 {
+            Err("HeadlessRenderTarget extents must be non-zero")?;
+        }
+
+        if def.read_back_pixels && def.format.is_compressed() {
+            Err(format!(
+                "HeadlessRenderTarget cannot read back pixels in compressed format {:?}",
+                def.format
+            ))?;
+        }
+
+        let mut slots = Vec::with_capacity(HEADLESS_FRAME_COUNT);
+        for _ in 0..HEADLESS_FRAME_COUNT {
+            slots.push(Self::create_slot(
+                &device_context,
+                renderer.graphics_queue(),
+                def,
+            )?);
+        }
+
+        // Resources that would normally be created when the swapchain is created (i.e. the
+        // default color formats) are based on the offscreen texture instead
+        let swapchain_surface_info = SwapchainSurfaceInfo {
+            extents: def.extents,
+            format: def.format,
+        };
+
+        renderer
+            .render_resources
+            .fetch_mut::<SwapchainRenderResource>()
+            .set_offscreen_target(
+                &device_context,
+                swapchain_surface_info,
+                def.output_image_state(),
+            )?;
+
+        let inner = HeadlessRenderTargetInner {
+            def: def.clone(),
+            slots,
+            next_frame_index: 0,
+            pending_error: None,
+            completed_frames: Default::default(),
+        };
+
+        Ok(HeadlessRenderTarget {
+            inner: Arc::new(Mutex::new(inner)),
+            frame_callback: Default::default(),
+        })
+    }
+
+    fn create_slot(
+        device_context: &RafxDeviceContext,
+        graphics_queue: &RafxQueue,
+        def: &HeadlessRenderTargetDef,
+    ) -> RafxResult<HeadlessFrameSlot> {
+        let texture = device_context.create_texture(&RafxTextureDef {
+            extents: RafxExtents3D {
+                width: def.extents.width,
+                height: def.extents.height,
+                depth: 1,
+            },
+            format: def.format,
+            resource_type: RafxResourceType::TEXTURE | RafxResourceType::RENDER_TARGET_COLOR,
+            ..Default::default()
+        })?;
+
+        let readback_buffer = if def.read_back_pixels {
+            let size = def.extents.width as u64
+                * def.extents.height as u64
+                * def.format.block_or_pixel_size_in_bytes() as u64;
+
+            Some(device_context.create_buffer(&RafxBufferDef {
+                size,
+                memory_usage: RafxMemoryUsage::GpuToCpu,
+                ..Default::default()
+            })?)
+        } else {
+            None
+        };
+
+        let mut command_pool =
+            graphics_queue.create_command_pool(&RafxCommandPoolDef { transient: false })?;
+        let command_buffer = command_pool.create_command_buffer(&RafxCommandBufferDef {
+            is_secondary: false,
+        })?;
+
+        Ok(HeadlessFrameSlot {
+            texture,
+            readback_buffer,
+            fence: device_context.create_fence()?,
+            command_pool,
+            command_buffer,
+            submitted_frame_index: None,
+        })
+    }
+
+    pub fn def(&self) -> HeadlessRenderTargetDef {
+        self.inner.lock().unwrap().def.clone()
+    }
+
+    /// Sets a callback that receives every finished frame. The callback is called on whichever
+    /// thread acquires the next frame (usually the thread calling `start_rendering_next_frame`)
+    /// or calls `flush`. It must not call `set_frame_callback`.
+    pub fn set_frame_callback(
+        &self,
+        frame_callback: Option<HeadlessFrameCallback>,
+    ) {
+        *self.frame_callback.lock().unwrap() = frame_callback;
+    }
+
+    // Passes the frames to the frame callback, or queues them if there isn't one. Must not be
+    // called with inner locked.
+    fn deliver_frames(
+        &self,
+        frames: Vec<HeadlessFrame>,
+    ) {
+        if frames.is_empty() {
+            return;
+        }
+
+        let mut frame_callback = self.frame_callback.lock().unwrap();
+        if let Some(frame_callback) = &mut *frame_callback {
+            for frame in frames {
+                (frame_callback)(frame);
+            }
+        } else {
+            self.inner.lock().unwrap().completed_frames.extend(frames);
+        }
+    }
+
+    /// Returns the finished frames that have not been taken yet, oldest first. Always empty if a
+    /// frame callback is set.
+    pub fn take_completed_frames(&self) -> Vec<HeadlessFrame> {
+        self.inner
+            .lock()
+            .unwrap()
+            .completed_frames
+            .drain(..)
+            .collect()
+    }
+
+    /// Blocks until every frame that has been started is finished and delivers them. Returns any
+    /// error that occurred while rendering.
+    pub fn flush(
+        &self,
+        renderer: &Renderer,
+    ) -> RafxResult<()> {
+        renderer.wait_for_render_finish();
+
+        let (frames, result) = {
+            let mut guard = self.inner.lock().unwrap();
+            let (frames, result) = guard.finish_all_frames();
+            let pending_error = guard.pending_error.take();
+            (frames, result.and(pending_error.map_or(Ok(()), Err)))
+        };
+
+        self.deliver_frames(frames);
+        result
+    }
+
+    #[profiling::function]
+    pub(crate) fn acquire_next_frame(&self) -> RafxResult<HeadlessPresentableFrame> {
+        let (finished_frame, slot_index, frame_index, texture) = {
+            let mut guard = self.inner.lock().unwrap();
+
+            if let Some(error) = guard.pending_error.take() {
+                return Err(error);
+            }
+
+            let frame_index = guard.next_frame_index;
+            let slot_index = (frame_index % guard.slots.len() as u64) as usize;
+
+            // Wait for the frame that last used this slot, just like waiting on a swapchain image
+            let finished_frame = guard.finish_frame(slot_index)?;
+            guard.next_frame_index += 1;
+
+            let texture = guard.slots[slot_index].texture.clone();
+            (finished_frame, slot_index, frame_index, texture)
+        };
+
+        self.deliver_frames(finished_frame.into_iter().collect());
+
+        Ok(HeadlessPresentableFrame {
+            inner: Some(self.inner.clone()),
+            slot_index,
+            frame_index,
+            texture,
+        })
+    }
+}
+
+/// An offscreen texture acquired from a `HeadlessRenderTarget`. Like `RafxPresentableFrame`, it
+/// can move between threads and must be submitted with `present` or `present_with_error`.
+pub struct HeadlessPresentableFrame {
+    inner: Option<Arc<Mutex<HeadlessRenderTargetInner>>>,
+    slot_index: usize,
+    frame_index: u64,
+    texture: RafxTexture,
+}
+
+impl HeadlessPresentableFrame {
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    pub fn texture(&self) -> &RafxTexture {
+        &self.texture
+    }
+
+    /// Submits the given command buffers. The frame is delivered once the GPU finishes them
+    pub fn present(
+        mut self,
+        queue: &RafxQueue,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<()> {
+        let inner = self.inner.take().unwrap();
+        let mut guard = inner.lock().unwrap();
+
+        let result = guard.submit_frame(queue, self.slot_index, self.frame_index, command_buffers);
+        if let Err(error) = &result {
+            guard.pending_error = Some(error.clone());
+        }
+
+        result
+    }
+
+    /// Skips this frame and returns the given error during the next frame acquisition attempt
+    pub fn present_with_error(
+        mut self,
+        error: RafxError,
+    ) {
+        log::warn!(
+            "Calling HeadlessPresentableFrame::present_with_error {:?}",
+            error
+        );
+
+        let inner = self.inner.take().unwrap();
+        inner.lock().unwrap().pending_error = Some(error);
+    }
+}
+
+impl Drop for HeadlessPresentableFrame {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            inner.lock().unwrap().pending_error = Some(RafxError::StringError(
+                "HeadlessPresentableFrame was dropped without calling present or present_with_error"
+                    .to_string(),
+            ));
+        }
+    }
+}
@@ -5,21 +5,50 @@ mod renderer_asset_plugin;
 pub use renderer_asset_plugin::RendererAssetPlugin;
 
 mod render_graph_generator;
+pub use render_graph_generator::add_render_to_texture_view_node;
+pub use render_graph_generator::add_render_window_view_node;
 pub use render_graph_generator::RenderGraphGenerator;
+pub use render_graph_generator::RenderToTextureView;
+pub use render_graph_generator::RenderWindowView;
+
+mod asset_render_graph_generator;
+pub use asset_render_graph_generator::AssetRenderGraphGenerator;
+pub use asset_render_graph_generator::RenderGraphAssetViewImage;
+pub use asset_render_graph_generator::RenderGraphAssetViewProvider;
 
 mod viewports_resource;
+pub use viewports_resource::RenderToTextureViewMeta;
 pub use viewports_resource::RenderViewMeta;
+pub use viewports_resource::RenderWindowId;
+pub use viewports_resource::RenderWindowViewportMeta;
 pub use viewports_resource::ViewportsResource;
 
 mod render_thread;
 use render_thread::RenderThread;
 
+mod render_windows;
+pub use render_windows::RenderWindowFrame;
+pub use render_windows::RenderWindows;
+
 mod swapchain_render_resource;
 pub use swapchain_render_resource::SwapchainRenderResource;
 
 mod render_frame_job;
 pub use render_frame_job::RenderFrameJob;
 
+mod render_frame_target;
+pub use render_frame_target::RenderFrameTarget;
+
+mod headless_render_target;
+pub use headless_render_target::HeadlessFrame;
+pub use headless_render_target::HeadlessFrameCallback;
+pub use headless_render_target::HeadlessPresentableFrame;
+pub use headless_render_target::HeadlessRenderTarget;
+pub use headless_render_target::HeadlessRenderTargetDef;
+
+mod dynamic_resolution;
+pub use dynamic_resolution::DynamicResolutionController;
+
 mod renderer_builder;
 pub use renderer_builder::AssetSource;
 pub use renderer_builder::RendererBuilder;
@@ -1,7 +1,7 @@
-use crate::{RenderFeaturePlugin, RendererThreadPool};
+use crate::{RenderFeaturePlugin, RenderFrameTarget, RendererThreadPool};
 use fnv::FnvBuildHasher;
+use rafx_api::RafxResult;
 use rafx_api::{RafxCommandBuffer, RafxDeviceContext, RafxQueue};
-use rafx_api::{RafxPresentableFrame, RafxResult};
 use rafx_framework::graph::PreparedRenderGraph;
 use rafx_framework::render_features::render_features_prelude::*;
 use rafx_framework::{DynCommandBuffer, RenderResources, ResourceContext};
@@ -27,7 +27,7 @@ pub struct RenderFrameJob {
 impl RenderFrameJob {
     pub fn render_async(
         mut self,
-        presentable_frame: RafxPresentableFrame,
+        frame_target: RenderFrameTarget,
     ) -> RenderFrameJobResult {
         let t0 = rafx_base::Instant::now();
 
@@ -54,12 +54,12 @@ impl RenderFrameJob {
             Ok(command_buffers) => {
                 // ignore the error, we will receive it when we try to acquire the next image
                 let refs: Vec<&RafxCommandBuffer> = command_buffers.iter().map(|x| &**x).collect();
-                let _ = presentable_frame.present(&graphics_queue, &refs);
+                let _ = frame_target.present(&graphics_queue, &refs);
             }
             Err(err) => {
                 log::error!("Render thread failed with error {:?}", err);
                 // Pass error on to the next swapchain image acquire call
-                presentable_frame.present_with_error(&graphics_queue, err);
+                frame_target.present_with_error(&graphics_queue, err);
             }
         }
 
@@ -350,6 +350,7 @@ impl RenderFrameJob {
             submit_node_block.sort_submit_nodes(
                 render_registry.submit_node_sort_function(render_phase_index as RenderPhaseIndex),
             );
+            submit_node_block.batch_submit_nodes();
 
             submit_node_blocks.push(submit_node_block)
         }
@@ -0,0 +1,108 @@
+use super::{HeadlessPresentableFrame, RenderWindowFrame};
+use rafx_api::RafxTexture;
+use rafx_api::{RafxCommandBuffer, RafxError, RafxPresentableFrame, RafxQueue, RafxResult};
+
+/// The image that a frame is rendered into. This is either an image acquired from a swapchain or
+/// an offscreen texture owned by a `HeadlessRenderTarget`.
+pub enum RenderFrameTarget {
+    Swapchain {
+        presentable_frame: RafxPresentableFrame,
+        // Images acquired from the swapchains of additional windows. They are rendered by the same
+        // command buffers as the main window but presented separately
+        window_frames: Vec<RenderWindowFrame>,
+    },
+    Headless(HeadlessPresentableFrame),
+}
+
+impl RenderFrameTarget {
+    pub fn texture(&self) -> &RafxTexture {
+        match self {
+            RenderFrameTarget::Swapchain {
+                presentable_frame, ..
+            } => presentable_frame.swapchain_texture(),
+            RenderFrameTarget::Headless(frame) => frame.texture(),
+        }
+    }
+
+    /// The additional windows being drawn this frame. Always empty for headless frames.
+    pub fn window_frames(&self) -> &[RenderWindowFrame] {
+        match self {
+            RenderFrameTarget::Swapchain { window_frames, .. } => window_frames,
+            RenderFrameTarget::Headless(_) => &[],
+        }
+    }
+
+    /// Submits the given command buffers. Swapchain images are presented, headless frames are
+    /// delivered once the GPU finishes with them.
+    pub fn present(
+        self,
+        queue: &RafxQueue,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<()> {
+        match self {
+            RenderFrameTarget::Swapchain {
+                mut presentable_frame,
+                mut window_frames,
+            } => {
+                if window_frames.is_empty() {
+                    return presentable_frame
+                        .present(queue, command_buffers)
+                        .map(|_| ());
+                }
+
+                // The command buffers write to every window's image, so they are submitted once
+                // after all of the images are acquired
+                let submit_result = {
+                    let mut frames = Vec::with_capacity(window_frames.len() + 1);
+                    frames.push(&mut presentable_frame);
+                    frames.extend(window_frames.iter_mut().map(|x| &mut x.presentable_frame));
+                    RafxPresentableFrame::submit_for_frames(&mut frames, queue, command_buffers)
+                };
+
+                if let Err(error) = submit_result {
+                    for window_frame in window_frames {
+                        window_frame
+                            .presentable_frame
+                            .present_with_error(queue, error.clone());
+                    }
+
+                    presentable_frame.present_with_error(queue, error.clone());
+                    return Err(error);
+                }
+
+                // Each window is presented on its own. A window's result is returned by its
+                // swapchain helper the next time it acquires an image, so it does not affect the
+                // main window or the other windows
+                for window_frame in window_frames {
+                    let _ = window_frame.presentable_frame.present_submitted(queue);
+                }
+
+                presentable_frame.present_submitted(queue).map(|_| ())
+            }
+            RenderFrameTarget::Headless(frame) => frame.present(queue, command_buffers),
+        }
+    }
+
+    /// Skips rendering this frame. The error is returned the next time a frame is acquired.
+    pub fn present_with_error(
+        self,
+        queue: &RafxQueue,
+        error: RafxError,
+    ) {
+        match self {
+            RenderFrameTarget::Swapchain {
+                presentable_frame,
+                window_frames,
+            } => {
+                for window_frame in window_frames {
+                    window_frame
+                        .presentable_frame
+                        .present_with_error(queue, error.clone());
+                }
+
+                presentable_frame.present_with_error(queue, error)
+            }
+            RenderFrameTarget::Headless(frame) => frame.present_with_error(error),
+        }
+    }
+}
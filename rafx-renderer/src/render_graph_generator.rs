@@ -1,14 +1,221 @@
+use super::RenderWindowId;
+use rafx_api::{
+    RafxColorClearValue, RafxDepthStencilClearValue, RafxFormat, RafxResourceState,
+    RafxResourceType, RafxSampleCount,
+};
 use rafx_assets::AssetManager;
-use rafx_framework::graph::PreparedRenderGraph;
-use rafx_framework::render_features::{ExtractResources, RenderView};
+use rafx_framework::graph::{
+    PreparedRenderGraph, RenderGraphBuilder, RenderGraphImageConstraint, RenderGraphImageExtents,
+    RenderGraphImageSpecification, RenderGraphNodeId, RenderGraphQueue, SwapchainSurfaceInfo,
+};
+use rafx_framework::render_features::{
+    ExtractResources, RenderJobCommandBufferContext, RenderPhaseIndex, RenderView,
+};
 use rafx_framework::{ImageViewResource, RafxResult, RenderResources, ResourceArc};
 
+/// A view registered in `ViewportsResource::render_to_texture_views` for the current frame. The
+/// `RenderGraphGenerator` is responsible for drawing the view into `output_image`.
+#[derive(Clone)]
+pub struct RenderToTextureView {
+    pub view: RenderView,
+    pub output_image: ResourceArc<ImageViewResource>,
+}
+
+impl RenderToTextureView {
+    /// The specification to pass to `RenderGraphBuilder::set_output_image` when writing to the
+    /// output image.
+    pub fn output_image_specification(&self) -> RenderGraphImageSpecification {
+        let texture_def = self
+            .output_image
+            .get_raw()
+            .image
+            .get_raw()
+            .image
+            .texture_def();
+        RenderGraphImageSpecification {
+            samples: texture_def.sample_count,
+            format: texture_def.format,
+            resource_type: texture_def.resource_type,
+            extents: RenderGraphImageExtents::Custom(
+                texture_def.extents.width,
+                texture_def.extents.height,
+                texture_def.extents.depth,
+            ),
+            layer_count: 1,
+            mip_count: 1,
+        }
+    }
+}
+
+/// An additional window (see `RenderWindows`) being drawn this frame. The `RenderGraphGenerator` is
+/// responsible for drawing the view into `swapchain_image`.
+#[derive(Clone)]
+pub struct RenderWindowView {
+    pub window_id: RenderWindowId,
+    pub view: RenderView,
+    pub swapchain_image: ResourceArc<ImageViewResource>,
+    pub swapchain_surface_info: SwapchainSurfaceInfo,
+}
+
+impl RenderWindowView {
+    /// The specification to pass to `RenderGraphBuilder::set_output_image` when writing to the
+    /// window's swapchain image. The extents are explicit since `MatchSurface` refers to the main
+    /// window.
+    pub fn output_image_specification(&self) -> RenderGraphImageSpecification {
+        RenderGraphImageSpecification {
+            samples: RafxSampleCount::SampleCount1,
+            format: self.swapchain_surface_info.format,
+            resource_type: RafxResourceType::TEXTURE | RafxResourceType::RENDER_TARGET_COLOR,
+            extents: RenderGraphImageExtents::Custom(
+                self.swapchain_surface_info.extents.width,
+                self.swapchain_surface_info.extents.height,
+                1,
+            ),
+            layer_count: 1,
+            mip_count: 1,
+        }
+    }
+}
+
+/// Adds a node that draws the given render phases of a render-to-texture view, in order, into the
+/// view's output image. A depth buffer is created and cleared for the node. The output image is left
+/// in the SHADER_RESOURCE state so that it can be sampled by later frames. Returns the node so that
+/// the caller can add more dependencies to it, i.e. sampling shadow maps.
+pub fn add_render_to_texture_view_node(
+    graph: &mut RenderGraphBuilder,
+    render_to_texture_view: &RenderToTextureView,
+    render_phases: &[RenderPhaseIndex],
+    depth_format: RafxFormat,
+    clear_color: RafxColorClearValue,
+) -> RenderGraphNodeId {
+    add_view_output_node(
+        graph,
+        "RenderToTexture",
+        &render_to_texture_view.view,
+        render_to_texture_view.output_image.clone(),
+        render_to_texture_view.output_image_specification(),
+        RafxResourceState::SHADER_RESOURCE,
+        render_phases,
+        depth_format,
+        clear_color,
+    )
+}
+
+/// Adds a node that draws the given render phases of an additional window's view, in order, into
+/// the window's swapchain image. A depth buffer is created and cleared for the node. Returns the
+/// node so that the caller can add more dependencies to it, i.e. sampling shadow maps.
+pub fn add_render_window_view_node(
+    graph: &mut RenderGraphBuilder,
+    render_window_view: &RenderWindowView,
+    render_phases: &[RenderPhaseIndex],
+    depth_format: RafxFormat,
+    clear_color: RafxColorClearValue,
+) -> RenderGraphNodeId {
+    add_view_output_node(
+        graph,
+        "RenderWindow",
+        &render_window_view.view,
+        render_window_view.swapchain_image.clone(),
+        render_window_view.output_image_specification(),
+        RafxResourceState::PRESENT,
+        render_phases,
+        depth_format,
+        clear_color,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_view_output_node(
+    graph: &mut RenderGraphBuilder,
+    node_name: &'static str,
+    view: &RenderView,
+    output_image: ResourceArc<ImageViewResource>,
+    specification: RenderGraphImageSpecification,
+    final_state: RafxResourceState,
+    render_phases: &[RenderPhaseIndex],
+    depth_format: RafxFormat,
+    clear_color: RafxColorClearValue,
+) -> RenderGraphNodeId {
+    let node = graph.add_node(node_name, RenderGraphQueue::DefaultGraphics);
+
+    let color = graph.create_color_attachment(
+        node,
+        0,
+        Some(clear_color),
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            format: Some(specification.format),
+            extents: Some(specification.extents),
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    graph.set_image_name(color, "view_output_color");
+
+    let depth = graph.create_depth_attachment(
+        node,
+        Some(RafxDepthStencilClearValue {
+            depth: 0.0,
+            stencil: 0,
+        }),
+        RenderGraphImageConstraint {
+            samples: Some(RafxSampleCount::SampleCount1),
+            format: Some(depth_format),
+            extents: Some(specification.extents),
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    graph.set_image_name(depth, "view_output_depth");
+
+    for &render_phase in render_phases {
+        graph.add_render_phase_dependency_by_index(node, render_phase);
+    }
+
+    let view = view.clone();
+    let render_phases = render_phases.to_vec();
+    graph.set_renderpass_callback(node, move |args| {
+        let mut write_context =
+            RenderJobCommandBufferContext::from_graph_visit_render_pass_args(&args);
+
+        for &render_phase in &render_phases {
+            args.graph_context
+                .prepared_render_data()
+                .write_view_phase_by_index(render_phase, &view, &mut write_context)?;
+        }
+
+        Ok(())
+    });
+
+    graph.set_output_image(
+        color,
+        output_image,
+        RenderGraphImageSpecification {
+            resource_type: specification.resource_type
+                | RafxResourceType::TEXTURE
+                | RafxResourceType::RENDER_TARGET_COLOR,
+            ..specification
+        },
+        Default::default(),
+        final_state,
+    );
+
+    node
+}
+
 pub trait RenderGraphGenerator: 'static + Send {
+    /// Builds the render graph for the frame. The graph should draw `main_view` to the swapchain
+    /// image, each of `render_to_texture_views` to its output image (see
+    /// `add_render_to_texture_view_node`), and each of `window_views` to its window's swapchain
+    /// image (see `add_render_window_view_node`).
+    #[allow(clippy::too_many_arguments)]
     fn generate_render_graph(
         &self,
         asset_manager: &AssetManager,
         swapchain_image: ResourceArc<ImageViewResource>,
         main_view: RenderView,
+        render_to_texture_views: &[RenderToTextureView],
+        window_views: &[RenderWindowView],
         extract_resources: &ExtractResources,
         render_resources: &RenderResources,
     ) -> RafxResult<PreparedRenderGraph>;
@@ -1,12 +1,12 @@
 use super::render_frame_job::RenderFrameJobResult;
 use super::RenderFrameJob;
+use super::RenderFrameTarget;
 use crossbeam_channel::{Receiver, Sender};
-use rafx_api::RafxPresentableFrame;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::JoinHandle;
 
 enum RenderThreadMessage {
-    Render(RenderFrameJob, RafxPresentableFrame),
+    Render(RenderFrameJob, RenderFrameTarget),
     Finish,
 }
 
@@ -42,13 +42,10 @@ impl RenderThread {
     pub fn render(
         &self,
         prepared_frame: RenderFrameJob,
-        presentable_frame: RafxPresentableFrame,
+        frame_target: RenderFrameTarget,
     ) {
         self.job_tx
-            .send(RenderThreadMessage::Render(
-                prepared_frame,
-                presentable_frame,
-            ))
+            .send(RenderThreadMessage::Render(prepared_frame, frame_target))
             .unwrap();
 
         let was_expecting_result = self.expecting_result.swap(true, Ordering::Relaxed);
@@ -0,0 +1,146 @@
+use super::{RenderWindowId, Renderer, SwapchainHandler, ViewportsResource};
+use rafx_api::raw_window_handle::HasRawWindowHandle;
+use rafx_api::{RafxPresentableFrame, RafxResult, RafxSwapchainHelper};
+use rafx_assets::AssetManager;
+
+/// An image acquired from the swapchain of an additional window for the current frame
+pub struct RenderWindowFrame {
+    pub window_id: RenderWindowId,
+    pub presentable_frame: RafxPresentableFrame,
+}
+
+/// The swapchains of windows that are drawn in addition to the main window, i.e. the scene view,
+/// material preview, and texture inspector windows of an editor. Insert it into the
+/// `ExtractResources` passed to `Renderer::start_rendering_next_frame` next to the main window's
+/// `RafxSwapchainHelper`.
+///
+/// Each window's size and view are set in `ViewportsResource::window_viewports`. A window that has
+/// no entry there or no view is skipped that frame. All windows are drawn by the same render graph
+/// as the main window, but each window's image is acquired and presented on its own, so an error
+/// in one window's swapchain only skips that window.
+#[derive(Default)]
+pub struct RenderWindows {
+    // Kept in the order windows were added so that views are created in a consistent order
+    windows: Vec<(RenderWindowId, RafxSwapchainHelper)>,
+}
+
+impl RenderWindows {
+    pub fn add_window(
+        &mut self,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        window_id: RenderWindowId,
+        window: &dyn HasRawWindowHandle,
+        width: u32,
+        height: u32,
+    ) -> RafxResult<()> {
+        if self.contains_window(window_id) {
+            Err(format!("Window {:?} was already added", window_id))?;
+        }
+
+        let swapchain_helper = SwapchainHandler::create_window_swapchain(
+            asset_manager,
+            renderer,
+            window_id,
+            window,
+            width,
+            height,
+        )?;
+
+        self.windows.push((window_id, swapchain_helper));
+        Ok(())
+    }
+
+    /// Destroys the window's swapchain. Call this before the OS window is destroyed.
+    pub fn remove_window(
+        &mut self,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        window_id: RenderWindowId,
+    ) -> RafxResult<()> {
+        let index = self
+            .windows
+            .iter()
+            .position(|(id, _)| *id == window_id)
+            .ok_or_else(|| format!("Window {:?} was not added", window_id))?;
+
+        let (_, swapchain_helper) = self.windows.remove(index);
+        SwapchainHandler::destroy_window_swapchain(
+            swapchain_helper,
+            asset_manager,
+            renderer,
+            Some(window_id),
+        )
+    }
+
+    /// Destroys the swapchains of all windows
+    pub fn remove_all_windows(
+        &mut self,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+    ) -> RafxResult<()> {
+        for (window_id, swapchain_helper) in self.windows.drain(..) {
+            SwapchainHandler::destroy_window_swapchain(
+                swapchain_helper,
+                asset_manager,
+                renderer,
+                Some(window_id),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn contains_window(
+        &self,
+        window_id: RenderWindowId,
+    ) -> bool {
+        self.windows.iter().any(|(id, _)| *id == window_id)
+    }
+
+    pub fn window_ids<'a>(&'a self) -> impl Iterator<Item = RenderWindowId> + 'a {
+        self.windows.iter().map(|(window_id, _)| *window_id)
+    }
+
+    // Acquires an image from the swapchain of every window that has a view this frame. Swapchains
+    // are resized to match the window size in the viewports resource. Windows are acquired
+    // independently: a window that fails to acquire an image (i.e. because presenting its
+    // previous frame failed) is skipped this frame without affecting the other windows.
+    pub(crate) fn acquire_next_images(
+        &mut self,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        viewports_resource: &ViewportsResource,
+    ) -> Vec<RenderWindowFrame> {
+        let mut window_frames = Vec::with_capacity(self.windows.len());
+        for (window_id, swapchain_helper) in &mut self.windows {
+            let window_viewport = match viewports_resource.window_viewports.get(window_id) {
+                Some(window_viewport) if window_viewport.view_meta.is_some() => window_viewport,
+                _ => continue,
+            };
+
+            match SwapchainHandler::acquire_next_window_image(
+                swapchain_helper,
+                asset_manager,
+                renderer,
+                Some(*window_id),
+                window_viewport.window_size.width,
+                window_viewport.window_size.height,
+            ) {
+                Ok(presentable_frame) => window_frames.push(RenderWindowFrame {
+                    window_id: *window_id,
+                    presentable_frame,
+                }),
+                Err(error) => {
+                    log::warn!(
+                        "Skipping window {:?} this frame, acquiring its swapchain image failed: {:?}",
+                        window_id,
+                        error
+                    );
+                }
+            }
+        }
+
+        window_frames
+    }
+}
@@ -1,6 +1,7 @@
 use rafx_assets::distill_impl::AssetResource;
 use rafx_assets::{image_upload, AssetManagerRenderResource, GpuImageDataColorSpace};
 use rafx_assets::{AssetManager, GpuImageData};
+use rafx_framework::graph::{RenderGraphImageExtents, SwapchainSurfaceInfo};
 use rafx_framework::render_features::render_features_prelude::*;
 use rafx_framework::visibility::{VisibilityConfig, VisibilityRegion};
 use rafx_framework::{DynResourceAllocatorSet, RenderResources};
@@ -12,14 +13,91 @@ use super::*;
 use super::{RenderFeaturePlugin, RenderGraphGenerator, ViewportsResource};
 use rafx_api::extra::upload::{RafxTransferUpload, RafxUploadError};
 use rafx_api::{
-    RafxDeviceContext, RafxError, RafxPresentableFrame, RafxQueue, RafxResourceType, RafxResult,
+    RafxDeviceContext, RafxError, RafxExtents2D, RafxQueue, RafxResourceType, RafxResult,
     RafxSwapchainHelper,
 };
 use rafx_assets::image_upload::ImageUploadParams;
 
-#[derive(Default, Copy, Clone, Debug)]
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+#[derive(Copy, Clone, Debug)]
 pub struct RendererConfigResource {
     pub visibility_config: VisibilityConfig,
+    // Resolution of the main view relative to the main window. The main view and the attachments
+    // that match the surface are rendered at this scale and the render graph generator upscales
+    // the result to the swapchain. It may be changed every frame (see
+    // `DynamicResolutionController`) and is clamped to MIN_RENDER_SCALE..=MAX_RENDER_SCALE.
+    pub render_scale: f32,
+}
+
+impl Default for RendererConfigResource {
+    fn default() -> Self {
+        RendererConfigResource {
+            visibility_config: Default::default(),
+            render_scale: 1.0,
+        }
+    }
+}
+
+impl RendererConfigResource {
+    pub fn clamped_render_scale(&self) -> f32 {
+        if self.render_scale.is_finite() {
+            self.render_scale
+                .max(MIN_RENDER_SCALE)
+                .min(MAX_RENDER_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    /// True if the main view is rendered at a different resolution than the swapchain
+    pub fn is_render_scaled(&self) -> bool {
+        self.clamped_render_scale() != 1.0
+    }
+
+    /// The extents the main view is rendered at when the output has the given extents
+    pub fn scaled_extents(
+        &self,
+        extents: RafxExtents2D,
+    ) -> RafxExtents2D {
+        let render_scale = self.clamped_render_scale();
+        RafxExtents2D {
+            width: ((extents.width as f32 * render_scale).round() as u32).max(1),
+            height: ((extents.height as f32 * render_scale).round() as u32).max(1),
+        }
+    }
+
+    /// The surface info to build a render graph with so that images that match the surface are
+    /// created at the render resolution. Images written to the swapchain must use explicit
+    /// `RenderGraphImageExtents::Custom` with the swapchain extents when the render scale is not 1.0.
+    pub fn scaled_surface_info(
+        &self,
+        swapchain_surface_info: &SwapchainSurfaceInfo,
+    ) -> SwapchainSurfaceInfo {
+        SwapchainSurfaceInfo {
+            extents: self.scaled_extents(swapchain_surface_info.extents),
+            format: swapchain_surface_info.format,
+        }
+    }
+
+    /// The extents of the swapchain image in a render graph built with `scaled_surface_info`.
+    /// When the render scale is not 1.0 the swapchain doesn't match the surface, so the image
+    /// written to it must come from a pass that upscales the main view.
+    pub fn swapchain_output_extents(
+        &self,
+        swapchain_surface_info: &SwapchainSurfaceInfo,
+    ) -> RenderGraphImageExtents {
+        if self.is_render_scaled() {
+            RenderGraphImageExtents::Custom(
+                swapchain_surface_info.extents.width,
+                swapchain_surface_info.extents.height,
+                1,
+            )
+        } else {
+            RenderGraphImageExtents::MatchSurface
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -44,6 +122,9 @@ pub struct Renderer {
     pub(super) render_resources: Arc<RenderResources>,
     pub(super) graphics_queue: RafxQueue,
     pub(super) transfer_queue: RafxQueue,
+    // Seconds that start_rendering_next_frame last spent blocked waiting for the previous frame to
+    // be presented and an image to be acquired
+    pub(super) previous_frame_wait_time: Mutex<f32>,
 }
 
 impl Renderer {
@@ -173,6 +254,7 @@ impl Renderer {
             render_resources: Arc::new(render_resources),
             graphics_queue: graphics_queue.clone(),
             transfer_queue: transfer_queue.clone(),
+            previous_frame_wait_time: Mutex::new(0.0),
         })
     }
 
@@ -216,53 +298,89 @@ impl Renderer {
         //
         let t0 = rafx_base::Instant::now();
 
-        let presentable_frame = {
-            let viewports_resource = extract_resources.fetch::<ViewportsResource>();
-            let mut swapchain_helper = extract_resources.fetch_mut::<RafxSwapchainHelper>();
-            let mut asset_manager = extract_resources.fetch_mut::<AssetManager>();
-            SwapchainHandler::acquire_next_image(
-                &mut *swapchain_helper,
-                &mut *asset_manager,
-                self,
-                viewports_resource.main_window_size.width,
-                viewports_resource.main_window_size.height,
-            )
-        }?;
+        // A HeadlessRenderTarget takes the place of the swapchain when rendering without a window
+        let headless_frame = extract_resources
+            .try_fetch::<HeadlessRenderTarget>()
+            .map(|headless_render_target| headless_render_target.acquire_next_frame());
+
+        let frame_target = match headless_frame {
+            Some(headless_frame) => RenderFrameTarget::Headless(headless_frame?),
+            None => {
+                let viewports_resource = extract_resources.fetch::<ViewportsResource>();
+                let mut swapchain_helper = extract_resources.fetch_mut::<RafxSwapchainHelper>();
+                let mut asset_manager = extract_resources.fetch_mut::<AssetManager>();
+                let presentable_frame = SwapchainHandler::acquire_next_image(
+                    &mut *swapchain_helper,
+                    &mut *asset_manager,
+                    self,
+                    viewports_resource.main_window_size.width,
+                    viewports_resource.main_window_size.height,
+                )?;
+
+                let window_frames = match extract_resources.try_fetch_mut::<RenderWindows>() {
+                    Some(mut render_windows) => render_windows.acquire_next_images(
+                        &mut *asset_manager,
+                        self,
+                        &*viewports_resource,
+                    ),
+                    None => Vec::default(),
+                };
+
+                RenderFrameTarget::Swapchain {
+                    presentable_frame,
+                    window_frames,
+                }
+            }
+        };
 
-        if let Some(render_thread) = &self.render_thread {
-            render_thread.wait_for_render_finish();
-        }
+        self.wait_for_render_finish();
 
         let t1 = rafx_base::Instant::now();
         log::trace!(
             "[main] wait for previous frame present {} ms",
             (t1 - t0).as_secs_f32() * 1000.0
         );
+        *self.previous_frame_wait_time.lock().unwrap() = (t1 - t0).as_secs_f32();
 
-        Self::create_and_start_render_job(self, extract_resources, presentable_frame);
+        Self::create_and_start_render_job(self, extract_resources, frame_target);
 
         Ok(())
     }
 
+    /// Seconds the last call to `start_rendering_next_frame` spent blocked waiting for the previous
+    /// frame to be presented and the next image to be acquired. With vsync enabled this is mostly
+    /// time spent waiting for the display, so subtract it from the frame time when measuring how
+    /// long the frame took to produce (see `DynamicResolutionController`).
+    pub fn previous_frame_wait_time(&self) -> f32 {
+        *self.previous_frame_wait_time.lock().unwrap()
+    }
+
+    /// Blocks until the render thread (if there is one) has submitted the previous frame
+    pub fn wait_for_render_finish(&self) {
+        if let Some(render_thread) = &self.render_thread {
+            render_thread.wait_for_render_finish();
+        }
+    }
+
     fn create_and_start_render_job(
         renderer: &Renderer,
         extract_resources: &mut ExtractResources,
-        presentable_frame: RafxPresentableFrame,
+        frame_target: RenderFrameTarget,
     ) {
-        let result = Self::try_create_render_job(&renderer, extract_resources, &presentable_frame);
+        let result = Self::try_create_render_job(&renderer, extract_resources, &frame_target);
 
         match result {
             Ok(prepared_frame) => {
                 if let Some(render_thread) = &renderer.render_thread {
-                    render_thread.render(prepared_frame, presentable_frame);
+                    render_thread.render(prepared_frame, frame_target);
                 } else {
                     // This path is required for backends that do not support multithreaded use
-                    prepared_frame.render_async(presentable_frame);
+                    prepared_frame.render_async(frame_target);
                 }
             }
             Err(e) => {
                 let graphics_queue = renderer.graphics_queue();
-                presentable_frame.present_with_error(graphics_queue, e)
+                frame_target.present_with_error(graphics_queue, e)
             }
         };
     }
@@ -270,7 +388,7 @@ impl Renderer {
     fn try_create_render_job(
         renderer: &Renderer,
         extract_resources: &mut ExtractResources,
-        presentable_frame: &RafxPresentableFrame,
+        frame_target: &RenderFrameTarget,
     ) -> RafxResult<RenderFrameJob> {
         //
         // Fetch resources
@@ -304,7 +422,7 @@ impl Renderer {
             // Temporary hack to jam a swapchain image into the existing resource lookups.. may want
             // to reconsider this later since the ResourceArc can be held past the lifetime of the
             // swapchain image
-            let swapchain_image = presentable_frame.swapchain_texture().clone();
+            let swapchain_image = frame_target.texture().clone();
 
             let swapchain_image = resource_context.resources().insert_image(swapchain_image);
 
@@ -322,7 +440,8 @@ impl Renderer {
         let viewports_resource = extract_resources.fetch::<ViewportsResource>();
         let view_meta = viewports_resource.main_view_meta.clone().unwrap();
 
-        let main_window_size = viewports_resource.main_window_size;
+        // The main view is rendered at the render scale. The generator upscales it to the swapchain.
+        let main_window_size = renderer_config.scaled_extents(viewports_resource.main_window_size);
 
         let main_view = render_view_set.create_view(
             view_meta.view_frustum,
@@ -337,6 +456,92 @@ impl Renderer {
             view_meta.debug_name,
         );
 
+        let render_to_texture_views: Vec<_> = viewports_resource
+            .render_to_texture_views
+            .iter()
+            .map(|render_to_texture_view_meta| {
+                let view_meta = render_to_texture_view_meta.view_meta.clone();
+                let output_image = render_to_texture_view_meta.output_image.clone();
+                let extents = output_image
+                    .get_raw()
+                    .image
+                    .get_raw()
+                    .image
+                    .texture_def()
+                    .extents;
+
+                let view = render_view_set.create_view(
+                    view_meta.view_frustum,
+                    view_meta.eye_position,
+                    view_meta.view,
+                    view_meta.proj,
+                    (extents.width, extents.height),
+                    view_meta.depth_range,
+                    view_meta.render_phase_mask,
+                    view_meta.render_feature_mask,
+                    view_meta.render_feature_flag_mask,
+                    view_meta.debug_name,
+                );
+
+                RenderToTextureView { view, output_image }
+            })
+            .collect();
+
+        let window_views = {
+            let swapchain_render_resource = render_resources.fetch::<SwapchainRenderResource>();
+
+            let mut window_views = Vec::with_capacity(frame_target.window_frames().len());
+            for window_frame in frame_target.window_frames() {
+                let window_id = window_frame.window_id;
+
+                // Windows are only acquired if they have a view
+                let view_meta = viewports_resource.window_viewports[&window_id]
+                    .view_meta
+                    .clone()
+                    .unwrap();
+
+                let swapchain_surface_info = swapchain_render_resource
+                    .window_surface_info(window_id)
+                    .ok_or_else(|| format!("Window {:?} has no swapchain", window_id))?
+                    .clone();
+
+                let swapchain_image = {
+                    let swapchain_image =
+                        window_frame.presentable_frame.swapchain_texture().clone();
+
+                    let swapchain_image =
+                        resource_context.resources().insert_image(swapchain_image);
+
+                    resource_context
+                        .resources()
+                        .get_or_create_image_view(&swapchain_image, None)?
+                };
+
+                let extents = swapchain_surface_info.extents;
+                let view = render_view_set.create_view(
+                    view_meta.view_frustum,
+                    view_meta.eye_position,
+                    view_meta.view,
+                    view_meta.proj,
+                    (extents.width, extents.height),
+                    view_meta.depth_range,
+                    view_meta.render_phase_mask,
+                    view_meta.render_feature_mask,
+                    view_meta.render_feature_flag_mask,
+                    view_meta.debug_name,
+                );
+
+                window_views.push(RenderWindowView {
+                    window_id,
+                    view,
+                    swapchain_image,
+                    swapchain_surface_info,
+                });
+            }
+
+            window_views
+        };
+
         //
         // Compute Views
         //
@@ -346,6 +551,13 @@ impl Renderer {
         {
             profiling::scope!("Compute Views");
             render_views.push(main_view.clone());
+            for render_to_texture_view in &render_to_texture_views {
+                render_views.push(render_to_texture_view.view.clone());
+            }
+            for window_view in &window_views {
+                render_views.push(window_view.view.clone());
+            }
+
             for plugin in &*renderer.feature_plugins {
                 plugin.add_render_views(
                     extract_resources,
@@ -438,6 +650,8 @@ impl Renderer {
             asset_manager,
             swapchain_image,
             main_view.clone(),
+            &render_to_texture_views,
+            &window_views,
             extract_resources,
             render_resources,
         )?;
@@ -1,18 +1,18 @@
 use rafx_api::extra::upload::RafxTransferUpload;
 use rafx_api::RafxResult;
-use rafx_assets::distill::daemon::AssetDaemon;
-use rafx_assets::distill_impl::AssetResource;
+use rafx_assets::distill_impl::{AssetResource, ImporterRegistry};
 use rafx_assets::AssetManager;
 use rafx_base::resource_map::ResourceMap;
 use rafx_framework::render_features::{ExtractResources, RenderRegistryBuilder};
 
 pub trait RendererAssetPlugin: Send + Sync {
-    // If the daemon is not running in-process, this will not be called
-    fn configure_asset_daemon(
+    // Called with the in-process asset daemon or the direct asset source, whichever is importing
+    // assets. If the daemon is not running in-process, this will not be called.
+    fn configure_importers(
         &self,
-        asset_daemon: AssetDaemon,
-    ) -> AssetDaemon {
-        asset_daemon
+        importers: ImporterRegistry,
+    ) -> ImporterRegistry {
+        importers
     }
 
     fn register_asset_types(
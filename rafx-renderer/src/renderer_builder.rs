@@ -4,9 +4,11 @@ use super::{RenderFeaturePlugin, RenderGraphGenerator};
 use crate::renderer_thread_pool_none::RendererThreadPoolNone;
 use crate::{RendererAssetPlugin, RendererThreadPool};
 use rafx_api::{RafxApi, RafxQueueType, RafxResult};
-use rafx_assets::distill_impl::AssetResource;
-use rafx_assets::{AssetManager, UploadQueueConfig};
+use rafx_assets::distill::daemon::AssetDaemon;
+use rafx_assets::distill_impl::{AssetResource, DirectAssetSource, ImporterRegistry};
+use rafx_assets::{AssetManager, CubemapImporter, TextureStreamingConfig, UploadQueueConfig};
 use rafx_framework::render_features::{ExtractResources, RenderRegistryBuilder};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub enum AssetSource {
@@ -15,6 +17,11 @@ pub enum AssetSource {
         external_daemon: bool,
         daemon_args: AssetDaemonOpt,
     },
+    // Import assets in-process on demand, caching the results in cache_dir
+    Direct {
+        asset_dirs: Vec<std::path::PathBuf>,
+        cache_dir: std::path::PathBuf,
+    },
 }
 
 pub struct RendererBuilderResult {
@@ -27,6 +34,7 @@ pub struct RendererBuilder {
     feature_plugins: Vec<Arc<dyn RenderFeaturePlugin>>,
     asset_plugins: Vec<Arc<dyn RendererAssetPlugin>>,
     allow_use_render_thread: bool,
+    texture_streaming_config: TextureStreamingConfig,
 }
 
 impl Default for RendererBuilder {
@@ -35,6 +43,7 @@ impl Default for RendererBuilder {
             feature_plugins: Default::default(),
             asset_plugins: Default::default(),
             allow_use_render_thread: true,
+            texture_streaming_config: Default::default(),
         }
     }
 }
@@ -64,6 +73,32 @@ impl RendererBuilder {
         self
     }
 
+    pub fn texture_streaming_config(
+        mut self,
+        texture_streaming_config: TextureStreamingConfig,
+    ) -> Self {
+        self.texture_streaming_config = texture_streaming_config;
+        self
+    }
+
+    // Registers the same importers whether assets are imported by the daemon or directly
+    fn configure_importers(
+        &self,
+        importers: ImporterRegistry,
+        asset_dirs: &[PathBuf],
+    ) -> ImporterRegistry {
+        // Cubemap manifests reference images by their path within the asset dirs
+        let mut importers = importers
+            .with_default_importers()
+            .with_importer("cubemap", CubemapImporter::new(asset_dirs.to_vec()));
+
+        for plugin in &self.asset_plugins {
+            importers = plugin.configure_importers(importers);
+        }
+
+        importers
+    }
+
     pub fn build(
         self,
         extract_resources: ExtractResources,
@@ -86,14 +121,17 @@ impl RendererBuilder {
                 if !external_daemon {
                     log::info!("Hosting local daemon at {:?}", daemon_args.address);
 
-                    let mut asset_daemon = rafx_assets::distill_impl::default_daemon()
+                    let asset_daemon = AssetDaemon::default()
                         .with_db_path(daemon_args.db_dir)
                         .with_address(daemon_args.address)
-                        .with_asset_dirs(daemon_args.asset_dirs);
-
-                    for plugin in &self.asset_plugins {
-                        asset_daemon = plugin.configure_asset_daemon(asset_daemon);
-                    }
+                        .with_asset_dirs(daemon_args.asset_dirs.clone());
+                    let asset_daemon = self
+                        .configure_importers(
+                            ImporterRegistry::Daemon(asset_daemon),
+                            &daemon_args.asset_dirs,
+                        )
+                        .into_asset_daemon()
+                        .unwrap();
 
                     // Spawn the daemon in a background thread.
                     std::thread::spawn(move || {
@@ -106,6 +144,22 @@ impl RendererBuilder {
                 // Connect to the daemon we just launched
                 daemon::init_distill_daemon(daemon_args.address.to_string())
             }
+            AssetSource::Direct {
+                asset_dirs,
+                cache_dir,
+            } => {
+                log::info!("Loading assets directly from {:?}", asset_dirs);
+
+                let direct_asset_source = DirectAssetSource::default()
+                    .with_asset_dirs(asset_dirs.clone())
+                    .with_cache_dir(cache_dir);
+                let direct_asset_source = self
+                    .configure_importers(ImporterRegistry::Direct(direct_asset_source), &asset_dirs)
+                    .into_direct_asset_source()
+                    .unwrap();
+
+                daemon::init_distill_direct(direct_asset_source)
+            }
         };
 
         let mut render_registry_builder = RenderRegistryBuilder::default();
@@ -130,11 +184,13 @@ impl RendererBuilder {
                 max_concurrent_uploads: 4,
                 max_new_uploads_in_single_frame: 4,
                 max_bytes_per_upload: 64 * 1024 * 1024,
+                max_bytes_per_frame: 64 * 1024 * 1024,
             },
             &graphics_queue,
             &transfer_queue,
         );
 
+        asset_manager.set_texture_streaming_config(self.texture_streaming_config);
         asset_manager.register_default_asset_types(&mut asset_resource);
 
         for plugin in &self.asset_plugins {
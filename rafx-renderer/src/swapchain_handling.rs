@@ -1,5 +1,5 @@
 use super::swapchain_render_resource::SwapchainRenderResource;
-use super::Renderer;
+use super::{RenderWindowId, Renderer};
 use rafx_api::raw_window_handle::HasRawWindowHandle;
 use rafx_api::{
     RafxDeviceContext, RafxExtents2D, RafxPresentableFrame, RafxResult, RafxSwapchain,
@@ -11,6 +11,8 @@ use rafx_framework::graph::SwapchainSurfaceInfo;
 pub struct SwapchainHandler<'a> {
     pub asset_manager: &'a mut AssetManager,
     pub renderer: &'a Renderer,
+    // None for the main window
+    pub window_id: Option<RenderWindowId>,
 }
 
 impl<'a> SwapchainHandler<'a> {
@@ -21,6 +23,37 @@ impl<'a> SwapchainHandler<'a> {
         window: &dyn HasRawWindowHandle,
         width: u32,
         height: u32,
+    ) -> RafxResult<RafxSwapchainHelper> {
+        Self::do_create_swapchain(asset_manager, renderer, None, window, width, height)
+    }
+
+    /// Creates the swapchain of an additional window. Usually called by `RenderWindows::add_window`
+    #[profiling::function]
+    pub fn create_window_swapchain(
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        window_id: RenderWindowId,
+        window: &dyn HasRawWindowHandle,
+        width: u32,
+        height: u32,
+    ) -> RafxResult<RafxSwapchainHelper> {
+        Self::do_create_swapchain(
+            asset_manager,
+            renderer,
+            Some(window_id),
+            window,
+            width,
+            height,
+        )
+    }
+
+    fn do_create_swapchain(
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        window_id: Option<RenderWindowId>,
+        window: &dyn HasRawWindowHandle,
+        width: u32,
+        height: u32,
     ) -> RafxResult<RafxSwapchainHelper> {
         let swapchain_helper = {
             let device_context = asset_manager.device_context().clone();
@@ -36,6 +69,7 @@ impl<'a> SwapchainHandler<'a> {
             let mut lifetime_listener = SwapchainHandler {
                 asset_manager,
                 renderer,
+                window_id,
             };
 
             RafxSwapchainHelper::new(&device_context, swapchain, Some(&mut lifetime_listener))?
@@ -51,10 +85,30 @@ impl<'a> SwapchainHandler<'a> {
         renderer: &Renderer,
         window_width: u32,
         window_height: u32,
+    ) -> RafxResult<RafxPresentableFrame> {
+        Self::acquire_next_window_image(
+            swapchain_helper,
+            asset_manager,
+            renderer,
+            None,
+            window_width,
+            window_height,
+        )
+    }
+
+    #[profiling::function]
+    pub fn acquire_next_window_image(
+        swapchain_helper: &mut RafxSwapchainHelper,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+        window_id: Option<RenderWindowId>,
+        window_width: u32,
+        window_height: u32,
     ) -> RafxResult<RafxPresentableFrame> {
         let mut lifetime_listener = SwapchainHandler {
             asset_manager,
             renderer,
+            window_id,
         };
 
         swapchain_helper.acquire_next_image(
@@ -66,13 +120,24 @@ impl<'a> SwapchainHandler<'a> {
 
     #[profiling::function]
     pub fn destroy_swapchain(
+        swapchain_helper: RafxSwapchainHelper,
+        asset_manager: &mut AssetManager,
+        renderer: &Renderer,
+    ) -> RafxResult<()> {
+        Self::destroy_window_swapchain(swapchain_helper, asset_manager, renderer, None)
+    }
+
+    #[profiling::function]
+    pub fn destroy_window_swapchain(
         mut swapchain_helper: RafxSwapchainHelper,
         asset_manager: &mut AssetManager,
         renderer: &Renderer,
+        window_id: Option<RenderWindowId>,
     ) -> RafxResult<()> {
         let mut lifetime_listener = SwapchainHandler {
             asset_manager,
             renderer,
+            window_id,
         };
 
         swapchain_helper.destroy(Some(&mut lifetime_listener))?;
@@ -112,7 +177,14 @@ impl<'a> RafxSwapchainEventListener for SwapchainHandler<'a> {
             .renderer
             .render_resources
             .fetch_mut::<SwapchainRenderResource>();
-        swapchain_render_resource.set_swapchain(device_context, swapchain_surface_info)?;
+        match self.window_id {
+            Some(window_id) => {
+                swapchain_render_resource.set_window_swapchain(window_id, swapchain_surface_info)
+            }
+            None => {
+                swapchain_render_resource.set_swapchain(device_context, swapchain_surface_info)?
+            }
+        }
 
         log::debug!("renderer swapchain_created finished");
 
@@ -131,7 +203,10 @@ impl<'a> RafxSwapchainEventListener for SwapchainHandler<'a> {
             .renderer
             .render_resources
             .fetch_mut::<SwapchainRenderResource>();
-        swapchain_render_resource.clear_swapchain();
+        match self.window_id {
+            Some(window_id) => swapchain_render_resource.clear_window_swapchain(window_id),
+            None => swapchain_render_resource.clear_swapchain(),
+        }
 
         //TODO: Explicitly remove the images instead of just dropping them. This prevents anything
         // from accidentally using them after they've been freed
@@ -1,12 +1,14 @@
-use rafx_api::{RafxDeviceContext, RafxFormat, RafxResourceType, RafxResult};
+use super::RenderWindowId;
+use fnv::FnvHashMap;
+use rafx_api::{RafxDeviceContext, RafxFormat, RafxResourceState, RafxResourceType, RafxResult};
 use rafx_framework::graph::SwapchainSurfaceInfo;
 
 pub struct SwapchainRenderResourceInner {
     // The images presented by the swapchain
-    //TODO: We don't properly support multiple swapchains right now. This would ideally be a map
-    // of window/surface to info for the swapchain
     //pub swapchain_images: Vec<ResourceArc<ImageViewResource>>,
     pub swapchain_surface_info: SwapchainSurfaceInfo,
+    // The state render graphs should leave the output image in. PRESENT for a swapchain
+    pub output_image_state: RafxResourceState,
 
     pub default_color_format_hdr: RafxFormat,
     pub default_color_format_sdr: RafxFormat,
@@ -17,7 +19,11 @@ pub struct SwapchainRenderResourceInner {
 // resource into the render resources map on init while we still have mut access to it, and not
 // require adding/removing it when we create/destroy the swapchain
 #[derive(Default)]
-pub struct SwapchainRenderResource(Option<SwapchainRenderResourceInner>);
+pub struct SwapchainRenderResource {
+    main: Option<SwapchainRenderResourceInner>,
+    // The swapchains of additional windows. The default formats are shared with the main window
+    window_surface_infos: FnvHashMap<RenderWindowId, SwapchainSurfaceInfo>,
+}
 
 impl SwapchainRenderResource {
     pub fn set_swapchain(
@@ -26,7 +32,31 @@ impl SwapchainRenderResource {
         swapchain_surface_info: SwapchainSurfaceInfo,
     ) -> RafxResult<()> {
         log::debug!("creating swapchain resources");
+        self.set_output(
+            device_context,
+            swapchain_surface_info,
+            RafxResourceState::PRESENT,
+        )
+    }
 
+    /// Like `set_swapchain`, but for rendering into a texture that is not presented (see
+    /// `HeadlessRenderTarget`). Render graphs leave the output image in `output_image_state`.
+    pub fn set_offscreen_target(
+        &mut self,
+        device_context: &RafxDeviceContext,
+        surface_info: SwapchainSurfaceInfo,
+        output_image_state: RafxResourceState,
+    ) -> RafxResult<()> {
+        log::debug!("creating offscreen target resources");
+        self.set_output(device_context, surface_info, output_image_state)
+    }
+
+    fn set_output(
+        &mut self,
+        device_context: &RafxDeviceContext,
+        swapchain_surface_info: SwapchainSurfaceInfo,
+        output_image_state: RafxResourceState,
+    ) -> RafxResult<()> {
         // Use swapchain format for SDR color
         let default_color_format_sdr = swapchain_surface_info.format;
 
@@ -44,8 +74,9 @@ impl SwapchainRenderResource {
             )
             .ok_or_else(|| "Could not find a supported depth format")?;
 
-        self.0 = Some(SwapchainRenderResourceInner {
+        self.main = Some(SwapchainRenderResourceInner {
             swapchain_surface_info,
+            output_image_state,
             default_color_format_hdr,
             default_color_format_sdr,
             default_depth_format,
@@ -55,14 +86,38 @@ impl SwapchainRenderResource {
     }
 
     pub fn clear_swapchain(&mut self) {
-        self.0 = None;
+        self.main = None;
     }
 
     pub fn try_get(&self) -> Option<&SwapchainRenderResourceInner> {
-        self.0.as_ref()
+        self.main.as_ref()
     }
 
     pub fn get(&self) -> &SwapchainRenderResourceInner {
-        self.0.as_ref().unwrap()
+        self.main.as_ref().unwrap()
+    }
+
+    pub fn set_window_swapchain(
+        &mut self,
+        window_id: RenderWindowId,
+        swapchain_surface_info: SwapchainSurfaceInfo,
+    ) {
+        log::debug!("setting swapchain for window {:?}", window_id);
+        self.window_surface_infos
+            .insert(window_id, swapchain_surface_info);
+    }
+
+    pub fn clear_window_swapchain(
+        &mut self,
+        window_id: RenderWindowId,
+    ) {
+        self.window_surface_infos.remove(&window_id);
+    }
+
+    pub fn window_surface_info(
+        &self,
+        window_id: RenderWindowId,
+    ) -> Option<&SwapchainSurfaceInfo> {
+        self.window_surface_infos.get(&window_id)
     }
 }
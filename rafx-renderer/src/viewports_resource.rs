@@ -1,13 +1,19 @@
+use fnv::FnvHashMap;
 use glam::{Mat4, Vec3};
 use rafx_api::RafxExtents2D;
 use rafx_framework::render_features::{
     RenderFeatureFlagMask, RenderFeatureMask, RenderPhaseMask, RenderViewDepthRange,
 };
 use rafx_framework::visibility::ViewFrustumArc;
+use rafx_framework::{ImageViewResource, ResourceArc};
 
-// Very bare-bones for now, in the future this could support multiple windows, multiple viewports
-// per window, and some method for configuring the graph that's being drawn (maybe the graph
-// is provided some metadata like a string)
+// Very bare-bones for now, in the future this could support multiple viewports per window, and
+// some method for configuring the graph that's being drawn (maybe the graph is provided some
+// metadata like a string)
+
+/// Identifies an additional window that is drawn along with the main window (see `RenderWindows`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RenderWindowId(pub u32);
 
 #[derive(Clone)]
 pub struct RenderViewMeta {
@@ -22,8 +28,29 @@ pub struct RenderViewMeta {
     pub debug_name: String,
 }
 
+/// A view that is drawn into an image instead of the swapchain, i.e. for security camera monitors,
+/// portals, minimaps, or editor thumbnails. The view's extents are the extents of the output image.
+/// The image must be a single-sampled 2D image created with the RENDER_TARGET_COLOR and TEXTURE
+/// resource types so that it can be sampled after it is drawn.
+#[derive(Clone)]
+pub struct RenderToTextureViewMeta {
+    pub view_meta: RenderViewMeta,
+    pub output_image: ResourceArc<ImageViewResource>,
+}
+
+/// The size and view of an additional window. The window is not drawn while it has no view.
+#[derive(Clone, Default)]
+pub struct RenderWindowViewportMeta {
+    pub window_size: RafxExtents2D,
+    pub view_meta: Option<RenderViewMeta>,
+}
+
 #[derive(Default)]
 pub struct ViewportsResource {
     pub main_window_size: RafxExtents2D,
     pub main_view_meta: Option<RenderViewMeta>,
+    // Drawn every frame in addition to the main view until they are removed
+    pub render_to_texture_views: Vec<RenderToTextureViewMeta>,
+    // Additional windows that have a swapchain in `RenderWindows`
+    pub window_viewports: FnvHashMap<RenderWindowId, RenderWindowViewportMeta>,
 }
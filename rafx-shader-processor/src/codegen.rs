@@ -561,7 +561,8 @@ fn create_binding_wrapper_binding_item(
         }
         RafxResourceType::TEXTURE
         | RafxResourceType::TEXTURE_READ_WRITE
-        | RafxResourceType::COMBINED_IMAGE_SAMPLER => {
+        | RafxResourceType::COMBINED_IMAGE_SAMPLER
+        | RafxResourceType::INPUT_ATTACHMENT => {
             if e.resource.element_count_normalized() > 1 {
                 binding_wrapper_items.push(BindingWrapperItem {
                     binding_name,
@@ -616,12 +617,25 @@ fn create_binding_wrapper_binding_item(
             });
             binding_wrapper_struct_lifetimes.push("'a".to_string());
         }
+        RafxResourceType::UNIFORM_BUFFER_DYNAMIC => {
+            binding_wrapper_items.push(BindingWrapperItem {
+                binding_name,
+                setter_fn_name_single: "set_dynamic_uniform_buffer".to_string(),
+                setter_fn_name_multi: "set_dynamic_uniform_buffer".to_string(),
+                args_struct_member_type: "&'a rafx_framework::DynRingBufferAllocation".to_string(),
+                set_element_param_type_single: "&rafx_framework::DynRingBufferAllocation"
+                    .to_string(),
+                set_element_param_type_multi: "&[rafx_framework::DynRingBufferAllocation]"
+                    .to_string(),
+                binding_index_string,
+                descriptor_count: e.resource.element_count_normalized(),
+            });
+            binding_wrapper_struct_lifetimes.push("'a".to_string());
+        }
         // No support for these yet
-        // RafxResourceType::UniformBufferDynamic => {}
         // RafxResourceType::StorageBufferDynamic => {}
         // RafxResourceType::UniformTexelBuffer => {}
         // RafxResourceType::StorageTexelBuffer => {}
-        // RafxResourceType::InputAttachment => {}
         _ => {
             Err(format!(
                 "Unsupported resource type {:?}",
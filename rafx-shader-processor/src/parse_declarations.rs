@@ -15,6 +15,10 @@ pub(crate) struct ExportAnnotation(/*pub(crate) String*/);
 #[serde(rename = "internal_buffer")]
 pub(crate) struct UseInternalBufferAnnotation(/*pub(crate) u32*/);
 
+#[derive(Default, Deserialize, Debug)]
+#[serde(rename = "dynamic")]
+pub(crate) struct DynamicAnnotation(/*pub(crate) u32*/);
+
 #[derive(Default, Deserialize, Debug)]
 #[serde(rename = "immutable_samplers")]
 pub(crate) struct ImmutableSamplersAnnotation(pub(crate) Vec<RafxSamplerDef>);
@@ -78,6 +82,7 @@ impl StructAnnotations {
 pub(crate) struct BindingAnnotations {
     pub(crate) export: Option<ExportAnnotation>,
     pub(crate) use_internal_buffer: Option<UseInternalBufferAnnotation>,
+    pub(crate) dynamic: Option<DynamicAnnotation>,
     pub(crate) immutable_samplers: Option<ImmutableSamplersAnnotation>,
     pub(crate) slot_name: Option<SlotNameAnnotation>,
     pub(crate) semantic: Option<SemanticAnnotation>,
@@ -107,6 +112,9 @@ impl BindingAnnotations {
                     parsed_annotations.use_internal_buffer =
                         Some(parse_ron_or_default(&annotation_data)?);
                 }
+                "dynamic" => {
+                    parsed_annotations.dynamic = Some(parse_ron_or_default(&annotation_data)?);
+                }
                 "immutable_samplers" => {
                     parsed_annotations.immutable_samplers =
                         Some(parse_ron_or_default(&annotation_data)?);
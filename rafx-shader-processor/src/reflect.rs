@@ -1,6 +1,6 @@
 use rafx_framework::cooked_shader::{
     ReflectedDescriptorSetLayout, ReflectedDescriptorSetLayoutBinding, ReflectedEntryPoint,
-    ReflectedVertexInput,
+    ReflectedUniformMember, ReflectedUniformMemberType, ReflectedVertexInput,
 };
 
 use crate::shader_types::{
@@ -144,6 +144,28 @@ where
         parsed_binding.parsed.instance_name.clone()
     };
 
+    // Dynamic uniform buffers are bound to a buffer once and given an offset when the descriptor
+    // set is bound, so they can't also use an internal buffer
+    let resource_type = if parsed_binding.annotations.dynamic.is_some() {
+        if resource_type != RafxResourceType::UNIFORM_BUFFER {
+            Err(format!(
+                "Resource {} is annotated as dynamic but only uniform buffers can be dynamic",
+                resource.name
+            ))?;
+        }
+
+        if parsed_binding.annotations.use_internal_buffer.is_some() {
+            Err(format!(
+                "Resource {} is annotated as dynamic and internal_buffer. Only one may be used",
+                resource.name
+            ))?;
+        }
+
+        RafxResourceType::UNIFORM_BUFFER_DYNAMIC
+    } else {
+        resource_type
+    };
+
     let resource = RafxShaderResource {
         resource_type,
         set_index: set,
@@ -210,10 +232,32 @@ where
             None
         };
 
+    let mut uniform_members = Vec::default();
+    let memory_layout = if resource_type.is_uniform_buffer() {
+        Some(MemoryLayout::Std140)
+    } else if resource_type.is_storage_buffer() {
+        Some(MemoryLayout::Std430)
+    } else {
+        None
+    };
+
+    if let Some(memory_layout) = memory_layout {
+        generate_uniform_members(
+            builtin_types,
+            user_types,
+            &parsed_binding.parsed.type_name,
+            None,
+            0,
+            memory_layout,
+            &mut uniform_members,
+        )?;
+    }
+
     Ok(ReflectedDescriptorSetLayoutBinding {
         resource: rafx_resource,
         internal_buffer_per_descriptor_size,
         immutable_samplers,
+        uniform_members,
     })
 }
 
@@ -321,6 +365,16 @@ where
         RafxResourceType::SAMPLER,
         stage_flags,
     )?;
+    get_reflected_bindings(
+        builtin_types,
+        user_types,
+        &mut bindings,
+        ast,
+        declarations,
+        &shader_resources.subpass_inputs,
+        RafxResourceType::INPUT_ATTACHMENT,
+        stage_flags,
+    )?;
 
     Ok(bindings)
 }
@@ -641,6 +695,124 @@ fn generate_gl_uniform_members(
     Ok(())
 }
 
+fn uniform_member_type(type_name: &str) -> Option<ReflectedUniformMemberType> {
+    Some(match type_name {
+        "int" => ReflectedUniformMemberType::Int,
+        "uint" => ReflectedUniformMemberType::Uint,
+        "bool" => ReflectedUniformMemberType::Bool,
+        "float" => ReflectedUniformMemberType::Float,
+        "vec2" => ReflectedUniformMemberType::Vec2,
+        "vec3" => ReflectedUniformMemberType::Vec3,
+        "vec4" => ReflectedUniformMemberType::Vec4,
+        "mat2" => ReflectedUniformMemberType::Mat2,
+        "mat3" => ReflectedUniformMemberType::Mat3,
+        "mat4" => ReflectedUniformMemberType::Mat4,
+        _ => return None,
+    })
+}
+
+// Similar to generate_gl_uniform_members, but arrays of builtin types are kept as a single member
+// with a stride and the type of each member is recorded so that values can be set by name at
+// runtime
+fn generate_uniform_members(
+    builtin_types: &FnvHashMap<String, TypeAlignmentInfo>,
+    user_types: &FnvHashMap<String, UserType>,
+    type_name: &str,
+    prefix: Option<String>,
+    offset: usize,
+    memory_layout: MemoryLayout,
+    uniform_members: &mut Vec<ReflectedUniformMember>,
+) -> RafxResult<()> {
+    let user_type = user_types.get(type_name).ok_or_else(|| {
+        format!(
+            "Could not find type named {} in generate_uniform_members",
+            type_name
+        )
+    })?;
+
+    let generated_struct = generate_struct(
+        builtin_types,
+        user_types,
+        &user_type.type_name,
+        user_type,
+        memory_layout,
+    )?;
+
+    for field in &*user_type.fields {
+        let struct_member = generated_struct
+            .members
+            .iter()
+            .find(|x| x.name == field.field_name)
+            .ok_or_else(|| {
+                format!(
+                    "Could not find member {} within generated struct {}",
+                    field.field_name, generated_struct.name
+                )
+            })?;
+
+        let member_path = match &prefix {
+            Some(prefix) => format!("{}.{}", prefix, field.field_name),
+            None => field.field_name.clone(),
+        };
+        let field_offset = offset + struct_member.offset;
+
+        if let Some(member_type) = uniform_member_type(&field.type_name) {
+            let (array_length, array_stride) = if field.array_sizes.is_empty() {
+                (0, 0)
+            } else {
+                let element_count = element_count(&field.array_sizes);
+                (element_count, struct_member.size / element_count)
+            };
+
+            let matrix_stride = if member_type.column_count() > 1 {
+                let builtin_type = builtin_types.get(&field.type_name).unwrap();
+                builtin_type.size(memory_layout) / member_type.column_count() as usize
+            } else {
+                0
+            };
+
+            uniform_members.push(ReflectedUniformMember {
+                path: member_path,
+                member_type,
+                offset: field_offset as u32,
+                array_length: array_length as u32,
+                array_stride: array_stride as u32,
+                matrix_stride: matrix_stride as u32,
+            });
+        } else if builtin_types.contains_key(&field.type_name) {
+            Err(format!(
+                "Member {} of {} has type {} which is not supported by ReflectedUniformMemberType",
+                field.field_name, type_name, field.type_name
+            ))?;
+        } else if field.array_sizes.is_empty() {
+            generate_uniform_members(
+                builtin_types,
+                user_types,
+                &field.type_name,
+                Some(member_path),
+                field_offset,
+                memory_layout,
+                uniform_members,
+            )?;
+        } else {
+            let element_count = element_count(&field.array_sizes);
+            for i in 0..element_count {
+                generate_uniform_members(
+                    builtin_types,
+                    user_types,
+                    &field.type_name,
+                    Some(format!("{}[{}]", member_path, i)),
+                    field_offset + (i * struct_member.size / element_count),
+                    memory_layout,
+                    uniform_members,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub struct ShaderProcessorRefectionData {
     pub reflection: Vec<ReflectedEntryPoint>,
     pub msl_argument_buffer_assignments: BTreeMap<ResourceBindingLocation, ResourceBinding>,
@@ -718,7 +890,6 @@ where
 
         // stage inputs
         // stage outputs
-        // subpass inputs
         // atomic counters
         // push constant buffers
 
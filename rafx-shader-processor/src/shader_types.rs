@@ -28,6 +28,32 @@ pub(crate) struct TypeAlignmentInfo {
     pub(crate) align: usize,
     pub(crate) std140_alignment: usize, // for structs/array elements, round up to multiple of 16
     pub(crate) std430_alignment: usize,
+    // Usually the same as rust_type/size. Matrices with columns smaller than a vec4 (i.e. mat2) are
+    // packed more tightly in std430 because the column stride isn't rounded up to 16 bytes
+    pub(crate) std430_rust_type: String,
+    pub(crate) std430_size: usize,
+}
+
+impl TypeAlignmentInfo {
+    pub(crate) fn rust_type(
+        &self,
+        layout: MemoryLayout,
+    ) -> &str {
+        match layout {
+            MemoryLayout::Std430 => &self.std430_rust_type,
+            MemoryLayout::Std140 | MemoryLayout::C => &self.rust_type,
+        }
+    }
+
+    pub(crate) fn size(
+        &self,
+        layout: MemoryLayout,
+    ) -> usize {
+        match layout {
+            MemoryLayout::Std430 => self.std430_size,
+            MemoryLayout::Std140 | MemoryLayout::C => self.size,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -145,7 +171,37 @@ fn add_type_alignment_info<T>(
         // As far as I can tell, alignment is always 4, 8, or 16
         std140_alignment: next_power_of_2(size.min(16).max(4)),
         std430_alignment: next_power_of_2(size.min(16).max(4)),
+        std430_rust_type: rust_type.to_string(),
+        std430_size: size,
+    };
+    log::trace!("built in type: {:?}", type_alignment_info);
+
+    let old = type_alignment_infos.insert(type_name.to_string(), type_alignment_info);
+    assert!(old.is_none());
+}
+
+// Matrices are arrays of column vectors. In std140 the column stride is rounded up to 16 bytes. In
+// std430 the column stride is the alignment of the column vector.
+fn add_matrix_type_alignment_info<Std140T, Std430T>(
+    type_alignment_infos: &mut FnvHashMap<String, TypeAlignmentInfo>,
+    type_name: &str,
+    std140_rust_type: &str,
+    std430_rust_type: &str,
+    std430_column_alignment: usize,
+) {
+    let type_alignment_info = TypeAlignmentInfo {
+        rust_type: std140_rust_type.to_string(),
+        size: std::mem::size_of::<Std140T>(),
+        align: std::mem::align_of::<Std140T>(),
+        std140_alignment: 16,
+        std430_alignment: std430_column_alignment,
+        std430_rust_type: std430_rust_type.to_string(),
+        std430_size: std::mem::size_of::<Std430T>(),
     };
+    assert_eq!(
+        std::mem::align_of::<Std140T>(),
+        std::mem::align_of::<Std430T>()
+    );
     log::trace!("built in type: {:?}", type_alignment_info);
 
     let old = type_alignment_infos.insert(type_name.to_string(), type_alignment_info);
@@ -163,6 +219,8 @@ pub(crate) fn create_builtin_type_lookup() -> FnvHashMap<String, TypeAlignmentIn
     add_type_alignment_info::<[f32; 2]>(&mut builtin_types, "vec2", "[f32; 2]");
     add_type_alignment_info::<[f32; 3]>(&mut builtin_types, "vec3", "[f32; 3]");
     add_type_alignment_info::<[f32; 4]>(&mut builtin_types, "vec4", "[f32; 4]");
+    add_matrix_type_alignment_info::<[[f32; 4]; 2], [[f32; 2]; 2]>(&mut builtin_types, "mat2", "[[f32; 4]; 2]", "[[f32; 2]; 2]", 8);
+    add_matrix_type_alignment_info::<[[f32; 4]; 3], [[f32; 4]; 3]>(&mut builtin_types, "mat3", "[[f32; 4]; 3]", "[[f32; 4]; 3]", 16);
     add_type_alignment_info::<[[f32; 4]; 4]>(&mut builtin_types, "mat4", "[[f32; 4]; 4]");
     builtin_types
 }
@@ -417,7 +475,7 @@ pub(crate) fn get_rust_type_name_non_array(
     layout: MemoryLayout,
 ) -> Result<String, String> {
     let type_name = if let Some(builtin_type) = builtin_types.get(name) {
-        builtin_type.rust_type.clone()
+        builtin_type.rust_type(layout).to_string()
     } else if let Some(user_type) = user_types.get(name) {
         format!("{}{:?}", user_type.type_name.clone(), layout)
     } else {
@@ -462,8 +520,11 @@ fn determine_size_of_member_in_rust(
     logging_name: &str,
     gpu_layout: MemoryLayout,
 ) -> Result<usize, String> {
-    let memory_layout = if builtin_types.contains_key(query_type) {
-        MemoryLayout::C
+    let memory_layout = if let Some(builtin_type) = builtin_types.get(query_type) {
+        // Builtin types are tightly packed in rust, but the rust type itself depends on the gpu
+        // layout (i.e. mat2 columns are padded in std140)
+        let padded_size = align_offset(builtin_type.size(gpu_layout), builtin_type.align);
+        return Ok(offset + padded_size * element_count(array_sizes));
     } else if user_types.contains_key(query_type) {
         gpu_layout
     } else {
@@ -509,20 +570,20 @@ fn determine_size(
             "      {} +{} (size: {}) [{} elements of size {}, alignment: {}, name: {}]",
             query_type,
             logging_offset,
-            element_count * builtin_type.size,
+            element_count * builtin_type.size(layout),
             element_count,
-            builtin_type.size,
+            builtin_type.size(layout),
             alignment,
             logging_name
         );
         if array_sizes.is_empty() {
             // For example, a single vec3 is 16 byte aligned but only requires 12 bytes
-            offset += builtin_type.size;
+            offset += builtin_type.size(layout);
         } else {
             // Ensure every element is properly aligned
             // For example, a single vec3 is 16 byte aligned and in an array, every element is
             // 12 bytes for the vec + 4 padding
-            let padded_size = align_offset(builtin_type.size, alignment);
+            let padded_size = align_offset(builtin_type.size(layout), alignment);
             offset += padded_size * element_count;
         }
 
@@ -1064,6 +1125,80 @@ mod test {
         verify_all_binding_layouts_in_test(reflect_data, &parsed_declarations)
     }
 
+    // mat2 columns are padded to 16 bytes in std140 but not in std430, mat3 columns are padded to
+    // 16 bytes in both
+    #[test]
+    fn test_matrix_layout() {
+        let shader_code = r#"
+            #version 450
+
+            struct Transform {
+                float scale;
+                mat2 rotation;
+                float depth;
+                mat3 normal;
+                mat2 rotations[2];
+            };
+
+            // @[export]
+            layout (set = 0, binding = 0) uniform TransformsUbo {
+                mat3 view;
+                float scale;
+                Transform transforms[4];
+            } transforms_uniform;
+
+            // @[export]
+            layout (set = 0, binding = 1) buffer TransformsSbo {
+                mat3 view;
+                float scale;
+                Transform transforms[4];
+            } transforms_buffer;
+
+            layout (location = 0) out vec4 out_color;
+            void main() {
+                out_color = vec4(transforms_uniform.scale + transforms_buffer.scale);
+            }
+        "#;
+
+        let (reflect_data, parsed_declarations) = compile_code_for_test(&shader_code);
+        let user_types = create_user_type_lookup(&parsed_declarations).unwrap();
+        let builtin_types = create_builtin_type_lookup();
+        verify_all_binding_layouts(&builtin_types, &user_types, &reflect_data).unwrap();
+
+        let transform = user_types.get("Transform").unwrap();
+        let std140 = generate_struct(
+            &builtin_types,
+            &user_types,
+            "Transform",
+            transform,
+            MemoryLayout::Std140,
+        )
+        .unwrap();
+        let std430 = generate_struct(
+            &builtin_types,
+            &user_types,
+            "Transform",
+            transform,
+            MemoryLayout::Std430,
+        )
+        .unwrap();
+
+        let member = |s: &GenerateStructResult, name: &str| {
+            let member = s.members.iter().find(|x| x.name == name).unwrap();
+            (member.offset, member.size, member.ty.clone())
+        };
+
+        assert_eq!(member(&std140, "rotation"), (16, 32, "[[f32; 4]; 2]".to_string()));
+        assert_eq!(member(&std140, "normal"), (64, 48, "[[f32; 4]; 3]".to_string()));
+        assert_eq!(member(&std140, "rotations"), (112, 64, "[[[f32; 4]; 2]; 2]".to_string()));
+        assert_eq!(std140.size, 176);
+
+        assert_eq!(member(&std430, "rotation"), (8, 16, "[[f32; 2]; 2]".to_string()));
+        assert_eq!(member(&std430, "normal"), (32, 48, "[[f32; 4]; 3]".to_string()));
+        assert_eq!(member(&std430, "rotations"), (80, 32, "[[[f32; 2]; 2]; 2]".to_string()));
+        assert_eq!(std430.size, 112);
+    }
+
     fn compile_code_for_test(
         shader_code: &str
     ) -> (
@@ -137,6 +137,7 @@ fn run() -> RafxResult<()> {
                 max_concurrent_uploads: 4,
                 max_new_uploads_in_single_frame: 4,
                 max_bytes_per_upload: 64 * 1024 * 1024,
+                max_bytes_per_frame: 64 * 1024 * 1024,
             },
             &graphics_queue,
             &transfer_queue,
@@ -209,6 +210,7 @@ fn run() -> RafxResult<()> {
             }
 
             asset_resource.update();
+            asset_manager.cancel_unreferenced_uploads(&asset_resource);
             asset_manager.update_asset_loaders()?;
 
             let current_time = std::time::Instant::now();
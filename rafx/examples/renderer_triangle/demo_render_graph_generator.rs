@@ -7,8 +7,11 @@ use rafx::graph::{
     PreparedRenderGraph, RenderGraphBuilder, RenderGraphImageConstraint, RenderGraphImageExtents,
     RenderGraphImageSpecification, RenderGraphQueue,
 };
-use rafx::render_features::RenderJobCommandBufferContext;
-use rafx::renderer::{RenderGraphGenerator, SwapchainRenderResource};
+use rafx::render_features::{RenderJobCommandBufferContext, RenderPhase};
+use rafx::renderer::{
+    add_render_to_texture_view_node, add_render_window_view_node, RenderGraphGenerator,
+    RenderToTextureView, RenderWindowView, SwapchainRenderResource,
+};
 
 pub struct DemoRenderGraphGenerator;
 
@@ -18,6 +21,8 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
         asset_manager: &AssetManager,
         swapchain_image: ResourceArc<ImageViewResource>,
         main_view: RenderView,
+        render_to_texture_views: &[RenderToTextureView],
+        window_views: &[RenderWindowView],
         _extract_resources: &ExtractResources,
         render_resources: &RenderResources,
     ) -> RafxResult<PreparedRenderGraph> {
@@ -82,9 +87,36 @@ impl RenderGraphGenerator for DemoRenderGraphGenerator {
                 mip_count: 1,
             },
             Default::default(),
-            RafxResourceState::PRESENT,
+            swapchain_info.output_image_state,
         );
 
+        //
+        // Views registered in ViewportsResource::render_to_texture_views are drawn into their own
+        // images
+        //
+        for render_to_texture_view in render_to_texture_views {
+            add_render_to_texture_view_node(
+                &mut graph_builder,
+                render_to_texture_view,
+                &[OpaqueRenderPhase::render_phase_index()],
+                swapchain_info.default_depth_format,
+                RafxColorClearValue([0.0, 0.0, 0.0, 0.0]),
+            );
+        }
+
+        //
+        // Additional windows registered in RenderWindows are drawn into their own swapchains
+        //
+        for window_view in window_views {
+            add_render_window_view_node(
+                &mut graph_builder,
+                window_view,
+                &[OpaqueRenderPhase::render_phase_index()],
+                swapchain_info.default_depth_format,
+                RafxColorClearValue([0.0, 0.0, 0.0, 0.0]),
+            );
+        }
+
         let prepared_render_graph = PreparedRenderGraph::new(
             &device_context,
             &resource_context,
@@ -244,7 +244,9 @@ fn run() -> RafxResult<()> {
             }
 
             {
+                let asset_resource = resources.get::<AssetResource>().unwrap();
                 let mut asset_manager = resources.get_mut::<AssetManager>().unwrap();
+                asset_manager.cancel_unreferenced_uploads(&*asset_resource);
                 asset_manager.update_asset_loaders().unwrap();
             }
 